@@ -16,77 +16,780 @@
 // You should have received a copy of the GNU Affero General Public License along with ip_geo. If
 // not, see <https://www.gnu.org/licenses/>.
 
-use clap::Parser;
+use clap::{Arg, ArgAction, Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use ip_geo::{
+    country::{is_supported_language, DEFAULT_LANGUAGE},
+    country_names::LANGUAGES,
+};
 use serde::Deserialize;
 use std::{
+    ffi::OsString,
     fmt::Display,
     fs,
-    net::{Ipv4Addr, Ipv6Addr},
+    net::{IpAddr, SocketAddr},
+    num::NonZeroUsize,
     path::Path,
+    str::FromStr,
+};
+
+use crate::{
+    bench::Backend,
+    completions::{self, Shell},
+    logging::LogFormat,
+    logsum::AccessLogFormat,
+    output::{self, Template},
+    rdap, resolve,
 };
 
 /// Represents all execution paths that a user can request.
 pub enum RunType {
-    /// Resolve a given IPv4 address to a country.
-    Ipv4,
-    /// Resolve a given IPv6 address to a country.
-    Ipv6,
+    /// Resolve a given IP address to a country.
+    Lookup,
+    /// Print the ranges assigned to given countries.
+    Ranges,
+    /// Print the distance between the countries of two addresses.
+    Distance,
+    /// Measure the lookup throughput of different ways to search the databases.
+    Bench,
+    /// Look up addresses and run commands interactively.
+    Repl,
+    /// Summarize a packet capture by the countries of its endpoints.
+    Pcap,
+    /// Summarize an access log by the countries of its clients.
+    Logsum,
+    /// Run the HTTP API server.
+    Serve,
+    /// Download MaxMind GeoLite2 databases.
+    DbDownload,
+    /// Download and replace the IPv4 and IPv6 databases.
+    DbUpdate,
+    /// Export the IPv4 and IPv6 databases in a given format.
+    DbExport,
+    /// Write integrity manifests for the IPv4 and IPv6 databases.
+    DbManifest,
+    /// Check the IPv4 and IPv6 databases for problems.
+    DbVerify,
+    /// Convert the IPv4 and IPv6 databases between formats.
+    DbConvert,
+    /// Describe the contents of the IPv4 and IPv6 databases.
+    DbStats,
+    /// Compare two databases.
+    DbDiff,
+    /// Print the configuration in effect.
+    ConfigShow,
+    /// Check the configuration file for problems.
+    ConfigValidate,
+    /// Write a default configuration file.
+    ConfigInit,
+    /// Print a shell completion script.
+    Completions,
+    /// Print a man page.
+    Manpage,
     /// User did not select a path.
     None,
 }
 
 /// Inspect `arguments` to identify what `RunType` the user wants.
 pub fn get_run_type(arguments: &Arguments) -> RunType {
-    if arguments.ipv4_addr.is_some() {
-        return RunType::Ipv4;
-    }
-
-    if arguments.ipv6_addr.is_some() {
-        return RunType::Ipv6;
+    match arguments.command {
+        Some(Command::Lookup { .. }) => RunType::Lookup,
+        Some(Command::Ranges { .. }) => RunType::Ranges,
+        Some(Command::Distance { .. }) => RunType::Distance,
+        Some(Command::Bench { .. }) => RunType::Bench,
+        Some(Command::Repl { .. }) => RunType::Repl,
+        Some(Command::Pcap { .. }) => RunType::Pcap,
+        Some(Command::Logsum { .. }) => RunType::Logsum,
+        Some(Command::Serve { .. }) => RunType::Serve,
+        Some(Command::Db(DbCommand::Download { .. })) => RunType::DbDownload,
+        Some(Command::Db(DbCommand::Update { .. })) => RunType::DbUpdate,
+        Some(Command::Db(DbCommand::Export { .. })) => RunType::DbExport,
+        Some(Command::Db(DbCommand::Manifest { .. })) => RunType::DbManifest,
+        Some(Command::Db(DbCommand::Verify)) => RunType::DbVerify,
+        Some(Command::Db(DbCommand::Convert { .. })) => RunType::DbConvert,
+        Some(Command::Db(DbCommand::Stats { .. })) => RunType::DbStats,
+        Some(Command::Db(DbCommand::Diff { .. })) => RunType::DbDiff,
+        Some(Command::Config {
+            command: None | Some(ConfigCommand::Show),
+        }) => RunType::ConfigShow,
+        Some(Command::Config {
+            command: Some(ConfigCommand::Validate),
+        }) => RunType::ConfigValidate,
+        Some(Command::Config {
+            command: Some(ConfigCommand::Init { .. }),
+        }) => RunType::ConfigInit,
+        Some(Command::Completions { .. }) => RunType::Completions,
+        Some(Command::Manpage) => RunType::Manpage,
+        None => RunType::None,
     }
-
-    RunType::None
 }
 
 /// Represents the command-line arguments of the program.
-#[derive(Parser, Deserialize, Debug, Clone)]
+///
+/// Every option is global, so it can be given before or after the subcommand.
+#[derive(Parser, Deserialize, Debug, Clone, Default)]
 #[command(about, version, long_about = None)]
 pub struct Arguments {
-    #[arg(short = 'f', long = "config-path")]
+    #[command(subcommand)]
+    #[serde(skip, default)]
+    pub command: Option<Command>,
+
+    /// The configuration file to read, instead of `ip_geo_cli.toml` in the user's configuration
+    /// directory.
+    #[arg(short = 'f', long = "config-path", global = true)]
     #[serde(skip, default)]
     pub config_path: Option<Box<Path>>,
 
-    #[arg(short = '4', long = "IPv4-addr")]
-    #[serde(skip_serializing_if = "Option::is_none", default)]
-    pub ipv4_addr: Option<Ipv4Addr>,
+    /// Print more diagnostics to stderr: debug messages, or with `-vv`, trace messages.
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    #[serde(skip, default)]
+    pub verbose: u8,
 
-    #[arg(long = "IPv4-path")]
+    /// Print fewer diagnostics to stderr: only warnings, or with `-qq`, only errors, or with
+    /// `-qqq`, nothing.
+    #[arg(short, long, action = ArgAction::Count, global = true, conflicts_with = "verbose")]
+    #[serde(skip, default)]
+    pub quiet: u8,
+
+    /// The format to print diagnostics to stderr in.
+    #[arg(long, value_enum, global = true, default_value_t)]
+    #[serde(skip, default)]
+    pub log_format: LogFormat,
+
+    /// The IPv4 database, ex. `/usr/share/tor/geoip`, or `-` for stdin.
+    #[arg(long = "IPv4-path", global = true)]
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub ipv4_path: Option<Box<Path>>,
 
-    #[arg(long = "IPv4-length")]
+    /// The number of entries in the IPv4 database, to allocate space for in advance.
+    #[arg(long = "IPv4-length", global = true)]
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub ipv4_len: Option<usize>,
 
-    #[arg(long = "IPv4-comment")]
+    /// The character that begins comments in the IPv4 database.
+    #[arg(long = "IPv4-comment", global = true)]
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub ipv4_comment: Option<char>,
 
-    #[arg(short = '6', long = "IPv6-addr")]
+    /// Where `db update` downloads the IPv4 database from.
+    #[arg(long = "IPv4-url", global = true)]
     #[serde(skip_serializing_if = "Option::is_none", default)]
-    pub ipv6_addr: Option<Ipv6Addr>,
+    pub ipv4_url: Option<Box<str>>,
 
-    #[arg(long = "IPv6-path")]
+    /// The IPv6 database, ex. `/usr/share/tor/geoip6`, or `-` for stdin.
+    #[arg(long = "IPv6-path", global = true)]
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub ipv6_path: Option<Box<Path>>,
 
-    #[arg(long = "IPv6-length")]
+    /// The number of entries in the IPv6 database, to allocate space for in advance.
+    #[arg(long = "IPv6-length", global = true)]
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub ipv6_len: Option<usize>,
 
-    #[arg(long = "IPv6-comment")]
+    /// The character that begins comments in the IPv6 database.
+    #[arg(long = "IPv6-comment", global = true)]
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub ipv6_comment: Option<char>,
+
+    /// Where `db update` downloads the IPv6 database from.
+    #[arg(long = "IPv6-url", global = true)]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub ipv6_url: Option<Box<str>>,
+
+    /// What to print for addresses with no associated country.
+    #[arg(long = "fallback", value_enum, global = true)]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub fallback: Option<FallbackMode>,
+
+    /// The IPv4 database to consult when the fallback is `database`.
+    #[arg(long = "fallback-IPv4-path", global = true)]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub fallback_ipv4_path: Option<Box<Path>>,
+
+    /// The IPv6 database to consult when the fallback is `database`.
+    #[arg(long = "fallback-IPv6-path", global = true)]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub fallback_ipv6_path: Option<Box<Path>>,
+
+    /// The language to print country names in, ex. `de` or `pt-BR`, falling back to English for
+    /// countries that have not been translated into it. Defaults to English.
+    #[arg(long = "lang", value_name = "LANGUAGE", value_parser = parse_language, global = true)]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub lang: Option<Box<str>>,
+}
+
+/// Parse a language that country names can be printed in, for `--lang`.
+fn parse_language(language: &str) -> Result<Box<str>, String> {
+    match is_supported_language(language) {
+        true => Ok(language.into()),
+        false => Err(format!(
+            "no country names in `{language}`, expected one of {DEFAULT_LANGUAGE}, {}",
+            LANGUAGES.join(", ")
+        )),
+    }
+}
+
+/// Represents what to return for addresses with no associated country.
+#[derive(ValueEnum, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FallbackMode {
+    /// Report that no country was found.
+    None,
+    /// Report the placeholder "??" country.
+    Unknown,
+    /// Look the address up in the fallback databases.
+    Database,
+}
+
+/// Represents the subcommands of the program.
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Look up the country of an IPv4 or IPv6 address, exiting with an error if it has none.
+    ///
+    /// Without an address (or with `-`), newline-separated addresses are read from stdin (or
+    /// `--input`) and looked up in order, printing one result per line.
+    ///
+    /// Hostnames, given in place of an address or on a line of input, are resolved first and
+    /// looked up by their first address (or with `--all`, every address).
+    ///
+    /// Exits with 0 if a country was found, 1 if none was (or only the unknown country, `??`), 2
+    /// for usage errors, or 3 if the databases could not be read. Lookups from stdin or `--input`
+    /// exit with 1 only if a line was not a valid address.
+    Lookup {
+        /// The address or hostname to look up, ex. `1.2.3.4`, `2001:db8::1`, or `example.com`, or
+        /// `-` for stdin.
+        #[arg(conflicts_with = "input")]
+        address: Option<LookupInput>,
+
+        /// A file of newline-separated (or with `--null`, NUL-separated) addresses to look up
+        /// instead of stdin.
+        #[arg(long)]
+        input: Option<Box<Path>>,
+
+        /// Read addresses separated by NUL rather than newlines, and end each result with NUL
+        /// rather than a newline, ex. to compose with `find -print0` and `xargs -0`. Tables are
+        /// still printed as lines.
+        #[arg(short = '0', long, conflicts_with = "pretty")]
+        null: bool,
+
+        /// Look up `--input` again whenever it or the databases change, printing the refreshed
+        /// results (with `--summary`, a refreshed report) until interrupted.
+        #[arg(long, requires = "input", conflicts_with = "address")]
+        watch: bool,
+
+        /// The number of worker threads looking up addresses read from stdin or `--input`.
+        /// Defaults to the number of CPUs.
+        #[arg(long)]
+        threads: Option<NonZeroUsize>,
+
+        /// The DNS server to resolve hostnames with, ex. `1.1.1.1` or `[::1]:5353`, instead of the
+        /// system resolver.
+        #[arg(long, value_parser = resolve::parse_server)]
+        resolver: Option<SocketAddr>,
+
+        /// Look up every address that a hostname resolves to, rather than only the first.
+        #[arg(long)]
+        all: bool,
+
+        /// Ask RDAP for the country of addresses that the databases cannot place (or place as
+        /// `??`), marking the results as coming from RDAP.
+        #[arg(long)]
+        rdap_fallback: bool,
+
+        /// The RDAP service that `--rdap-fallback` asks, which addresses are appended to.
+        #[arg(long, value_name = "URL", default_value = rdap::DEFAULT_RDAP_URL)]
+        rdap_url: Box<str>,
+
+        /// Print each result with this template instead of `--output`, ex. `{ip}\t{code}\t{name}`.
+        ///
+        /// Placeholders are `{ip}`, `{host}`, `{code}`, `{name}`, `{continent}`, `{longitude}`,
+        /// `{latitude}`, `{flag}`, `{found}`, `{source}`, and `{error}`, which print nothing for
+        /// results without them. `{{` and `}}` print literal braces, and `\t`, `\n`, and `\\`
+        /// print a tab, a newline, and a backslash. Each result ends with a newline.
+        #[arg(
+            long = "format",
+            value_name = "TEMPLATE",
+            value_parser = output::parse_template,
+            conflicts_with = "format"
+        )]
+        template: Option<&'static Template>,
+
+        /// Comma-separated list of country codes to print the results of, when reading from stdin
+        /// or `--input`, dropping all others.
+        #[arg(long = "country", value_name = completions::COUNTRY_CODE, value_delimiter = ',')]
+        countries: Vec<Box<str>>,
+
+        /// Comma-separated list of country codes to drop the results of, when reading from stdin
+        /// or `--input`.
+        #[arg(
+            long = "exclude-country",
+            value_name = completions::COUNTRY_CODE,
+            value_delimiter = ','
+        )]
+        excluded_countries: Vec<Box<str>>,
+
+        #[command(flatten)]
+        output: OutputOptions,
+    },
+    /// Print every IPv4 and IPv6 CIDR block assigned to any of the given countries, one per line,
+    /// ex. for firewall rules.
+    Ranges {
+        /// The country codes to print the ranges of, ex. `BE`.
+        #[arg(required = true, value_name = completions::COUNTRY_CODE)]
+        countries: Vec<Box<str>>,
+
+        /// Print ranges as `<start>-<end>` instead of CIDR blocks.
+        #[arg(long)]
+        raw: bool,
+
+        /// Only print IPv4 ranges.
+        #[arg(short = '4', long, conflicts_with = "ipv6_only")]
+        ipv4_only: bool,
+
+        /// Only print IPv6 ranges.
+        #[arg(short = '6', long)]
+        ipv6_only: bool,
+    },
+    /// Print the great-circle distance between the countries of two addresses, ex. for rough
+    /// latency or anomaly triage.
+    ///
+    /// Distances are between the centers of the countries, not the addresses themselves, so
+    /// addresses in the same country are always 0 km apart.
+    Distance {
+        /// The first address, ex. `1.2.3.4`.
+        from: IpAddr,
+
+        /// The second address, ex. `2001:db8::1`.
+        to: IpAddr,
+
+        #[command(flatten)]
+        output: OutputOptions,
+    },
+    /// Measure the lookups per second, median and 99th percentile latency, and estimated memory
+    /// usage of searching the databases with each backend, ex. to size the exporter.
+    ///
+    /// Addresses are read from `--input`, or else generated from random entries of the databases.
+    Bench {
+        /// A file of newline-separated addresses to look up instead of generated addresses.
+        #[arg(long)]
+        input: Option<Box<Path>>,
+
+        /// The number of addresses to generate.
+        #[arg(long, default_value_t = 1_000_000, conflicts_with = "input")]
+        count: usize,
+
+        /// The seed to generate addresses from, so runs can be compared.
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+
+        /// Comma-separated list of backends to benchmark. The linear backend only looks up the
+        /// first 10,000 addresses.
+        #[arg(
+            long = "backend",
+            value_enum,
+            value_delimiter = ',',
+            default_values_t = [Backend::Linear, Backend::BinarySearch, Backend::Trie]
+        )]
+        backends: Vec<Backend>,
+
+        #[command(flatten)]
+        output: OutputOptions,
+    },
+    /// Load the databases once, then look up addresses or hostnames and run commands (ex.
+    /// `:stats` or `:ranges BE`) read line by line, until `:quit` or the end of input.
+    ///
+    /// Type `:help` for every command. Lines are saved to a history file, which `:history` prints
+    /// and `!!` or `!<number>` repeat.
+    Repl {
+        /// The DNS server to resolve hostnames with, ex. `1.1.1.1` or `[::1]:5353`, instead of the
+        /// system resolver.
+        #[arg(long, value_parser = resolve::parse_server)]
+        resolver: Option<SocketAddr>,
+
+        /// Look up every address that a hostname resolves to, rather than only the first.
+        #[arg(long)]
+        all: bool,
+
+        /// The file to save history to, instead of `ip_geo_cli_history` in the user's data
+        /// directory.
+        #[arg(long, conflicts_with = "no_history")]
+        history_file: Option<Box<Path>>,
+
+        /// Do not read or save history.
+        #[arg(long)]
+        no_history: bool,
+
+        /// Print each result with this template instead of `--output`, ex. `{ip}\t{code}\t{name}`.
+        ///
+        /// Placeholders are `{ip}`, `{host}`, `{code}`, `{name}`, `{continent}`, `{longitude}`,
+        /// `{latitude}`, `{flag}`, `{found}`, `{source}`, and `{error}`, which print nothing for
+        /// results without them. `{{` and `}}` print literal braces, and `\t`, `\n`, and `\\`
+        /// print a tab, a newline, and a backslash. Each result ends with a newline.
+        #[arg(
+            long = "format",
+            value_name = "TEMPLATE",
+            value_parser = output::parse_template,
+            conflicts_with = "format"
+        )]
+        template: Option<&'static Template>,
+
+        #[command(flatten)]
+        output: OutputOptions,
+    },
+    /// Read a packet capture offline and print the packets, bytes, and endpoints of its IPv4 and
+    /// IPv6 traffic per country of the endpoints, most bytes first.
+    ///
+    /// Captures must be in the classic libpcap format (ex. `tcpdump -w`), not pcapng.
+    Pcap {
+        /// The capture to read, or `-` for stdin.
+        file: Box<Path>,
+
+        #[command(flatten)]
+        output: OutputOptions,
+    },
+    /// Read a completed access log and print the requests and response bytes per country of the
+    /// clients, most requests first, without running the server.
+    ///
+    /// Lines without a client address, or with one without a country, are counted as dropped.
+    Logsum {
+        /// The log to read, or `-` for stdin.
+        file: Box<Path>,
+
+        /// The format to parse the log as.
+        #[arg(long = "format", value_name = "FORMAT", value_enum, default_value_t)]
+        access_format: AccessLogFormat,
+
+        /// Also write the totals to this file in the Prometheus text format, with the metrics the
+        /// server exports for followed logs, ex. for the node_exporter textfile collector.
+        #[arg(long, value_name = "PATH")]
+        textfile: Option<Box<Path>>,
+
+        #[command(flatten)]
+        output: OutputOptions,
+    },
+    /// Run `ip_geo_server`, passing it every following argument.
+    ///
+    /// The server is found next to this program, or else on `PATH`.
+    #[command(disable_help_flag = true)]
+    Serve {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<OsString>,
+    },
+    /// Manage IP geolocation databases.
+    #[command(subcommand)]
+    Db(DbCommand),
+    /// Print (or check) the configuration in effect, after reading the configuration file.
+    Config {
+        #[command(subcommand)]
+        command: Option<ConfigCommand>,
+    },
+    /// Print a completion script for a shell to stdout.
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Print a roff man page to stdout, for packaging.
+    #[command(hide = true)]
+    Manpage,
+}
+
+/// Represents the subcommands for inspecting the configuration, showing it by default.
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigCommand {
+    /// Print the configuration in effect, after reading the configuration file.
+    Show,
+    /// Check the configuration file for unknown keys, values of the wrong type, missing
+    /// databases, and conflicting options, exiting with an error if it has any.
+    Validate,
+    /// Write a configuration file with every option set to its default value and described.
+    Init {
+        /// Where to write the configuration file, instead of the file the program reads.
+        path: Option<Box<Path>>,
+
+        /// Overwrite the file if it already exists.
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+/// Represents where `lookup` reads addresses from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LookupInput {
+    /// A single address, given on the command line.
+    Address(IpAddr),
+    /// A hostname to resolve, given on the command line.
+    Host(Box<str>),
+    /// Newline-separated addresses (or hostnames), read from stdin.
+    Stdin,
+}
+
+impl FromStr for LookupInput {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "-" => Ok(Self::Stdin),
+            input => match input.parse() {
+                Ok(address) => Ok(Self::Address(address)),
+                Err(_) if resolve::is_hostname(input) => Ok(Self::Host(input.into())),
+                Err(error) => Err(error.to_string()),
+            },
+        }
+    }
+}
+
+/// Represents the subcommands for managing IP geolocation databases.
+#[derive(Subcommand, Debug, Clone)]
+pub enum DbCommand {
+    /// Download MaxMind GeoLite2 databases, keeping old copies.
+    Download {
+        /// MaxMind account ID.
+        #[arg(long)]
+        account_id: Box<str>,
+
+        /// MaxMind license key. Read from `MAXMIND_LICENSE_KEY` if unset.
+        #[arg(long)]
+        license_key: Option<Box<str>>,
+
+        /// Comma-separated list of database editions to download.
+        #[arg(
+            long = "edition",
+            value_delimiter = ',',
+            default_value = "GeoLite2-Country"
+        )]
+        editions: Vec<Box<str>>,
+
+        /// The directory to store the databases in.
+        #[arg(long, default_value = ".")]
+        directory: Box<Path>,
+
+        /// The number of old copies of each database to keep.
+        #[arg(long, default_value_t = 1)]
+        keep: usize,
+    },
+    /// Download the IPv4 and IPv6 databases from their URLs, check them, and replace the local
+    /// copies that changed, for running from cron or a systemd timer.
+    Update {
+        /// Send `SIGHUP` to the `ip_geo_server` with this process ID after replacing a database,
+        /// so that it reloads them.
+        #[arg(long)]
+        reload_pid: Option<u32>,
+
+        /// Send a `POST` request to this URL after replacing a database, ex.
+        /// `http://localhost:26000/-/reload`.
+        #[arg(long)]
+        reload_url: Option<Box<str>>,
+
+        /// A file holding the bearer token to send with `--reload-url`.
+        #[arg(long, requires = "reload_url")]
+        reload_token_file: Option<Box<Path>>,
+    },
+    /// Write the IPv4 and IPv6 databases to stdout in a format usable by other programs.
+    Export {
+        #[arg(long, value_enum)]
+        format: ExportFormat,
+
+        /// Comma-separated list of country codes to include, required for `nftables` and `ipset`.
+        #[arg(
+            long = "country",
+            value_name = completions::COUNTRY_CODE,
+            value_delimiter = ',',
+            required_if_eq_any([("format", "nftables"), ("format", "ipset")])
+        )]
+        countries: Vec<Box<str>>,
+
+        /// The name of the nftables table to create the sets in.
+        #[arg(long, default_value = "filter")]
+        table: Box<str>,
+
+        /// The prefix of the names of the sets, which are suffixed with `_v4` and `_v6`.
+        #[arg(long, default_value = "ip_geo")]
+        set_name: Box<str>,
+
+        /// The nftables address family of the table, which decides whether it has IPv4 sets, IPv6
+        /// sets, or both. Ignored by other formats.
+        #[arg(long, value_enum, default_value_t)]
+        family: ExportFamily,
+
+        /// With `nftables`, also write a chain (named after the sets, suffixed with `_input`) that
+        /// blocks or allows incoming traffic from the selected countries, making a ruleset ready
+        /// for `nft -f`. Ignored by other formats.
+        #[arg(long, value_enum)]
+        policy: Option<ExportPolicy>,
+    },
+    /// Write a `.manifest` file next to each database, recording its checksum and entry count.
+    Manifest {
+        /// Where the databases came from, ex. "tor 0.4.8.12".
+        #[arg(long, default_value = "unknown")]
+        source: Box<str>,
+    },
+    /// Check each database for malformed lines, unknown country codes, and unsorted or overlapping
+    /// ranges, and against its `.manifest` file if it has one, exiting with an error on any
+    /// problem.
+    Verify,
+    /// Convert both databases between formats, by default to a single binary snapshot, which is
+    /// faster to load.
+    #[command(alias = "snapshot")]
+    Convert {
+        /// The format to convert from.
+        #[arg(long, value_enum, default_value_t = DatabaseFormat::Tor)]
+        from: DatabaseFormat,
+        /// The format to convert to, which cannot be `mmdb`.
+        #[arg(long, value_enum, default_value_t = DatabaseFormat::Snapshot)]
+        to: DatabaseFormat,
+        /// The snapshot or MaxMind database to convert from, required with `--from snapshot` and
+        /// `--from mmdb`. Otherwise, the IPv4 and IPv6 databases are converted.
+        #[arg(long, required_if_eq_any([("from", "snapshot"), ("from", "mmdb")]))]
+        input: Option<Box<Path>>,
+        /// The path to write the snapshot (or the IPv4 database) to.
+        #[arg(short, long)]
+        output: Box<Path>,
+        /// The path to write the IPv6 database to, required unless converting to a snapshot.
+        #[arg(long, required_if_eq_any([("to", "tor"), ("to", "csv")]))]
+        ipv6_output: Option<Box<Path>>,
+    },
+    /// Print the format, size, entries, countries, coverage, and gaps of each database.
+    Stats {
+        #[command(flatten)]
+        output: OutputOptions,
+        /// Also count the entries of each country.
+        #[arg(long)]
+        countries: bool,
+    },
+    /// Print the ranges that were added, removed, or reassigned to another country between two
+    /// databases, each a snapshot, a MaxMind database (ending in `.mmdb`), or a single IPv4 or IPv6
+    /// database.
+    Diff {
+        /// The database before the changes.
+        old: Box<Path>,
+        /// The database after the changes.
+        new: Box<Path>,
+        #[command(flatten)]
+        output: OutputOptions,
+        /// Print the addresses gained and lost by each country instead of each changed range.
+        #[arg(long)]
+        countries: bool,
+    },
+}
+
+/// Represents how results are printed.
+#[derive(Args, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputOptions {
+    /// The format to print results in.
+    #[arg(long = "output", value_enum, default_value_t)]
+    pub format: OutputFormat,
+
+    /// With `--output json`, print a single indented JSON array (or object) instead of one JSON
+    /// object per line. Ignored by other formats.
+    #[arg(long)]
+    pub pretty: bool,
+
+    /// With `--output table`, follow lookup results with the number of addresses in each
+    /// country. Ignored by other formats.
+    #[arg(long)]
+    pub summary: bool,
+
+    /// With `--output text`, when to color the country codes and errors of lookup results.
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t)]
+    pub color: ColorChoice,
+
+    /// With `--output text`, print the flag emoji of each country before its code. Ignored by
+    /// other formats.
+    #[arg(long)]
+    pub flags: bool,
+
+    /// The template to print lookup results with instead of `--output`, from `--format`.
+    #[arg(skip)]
+    pub template: Option<&'static Template>,
+
+    /// Whether lookup results end with NUL rather than a newline, from `--null`.
+    #[arg(skip)]
+    pub null: bool,
+}
+
+/// Represents when text output is colored.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// When stdout is a terminal and `NO_COLOR` is not set.
+    #[default]
+    Auto,
+    /// Always, even when stdout is not a terminal.
+    Always,
+    /// Never.
+    Never,
+}
+
+/// Represents the formats that results can be printed in.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable text, ex. `BE Belgium`.
+    #[default]
+    Text,
+    /// JSON, for `jq` and scripts.
+    Json,
+    /// Comma-separated values, with a header row.
+    Csv,
+    /// Tab-separated values, with a header row.
+    Tsv,
+    /// A human-readable table, with aligned columns.
+    Table,
+}
+
+impl OutputFormat {
+    /// Return the character separating fields, if this is a delimited format.
+    pub const fn delimiter(self) -> Option<char> {
+        match self {
+            Self::Csv => Some(','),
+            Self::Tsv => Some('\t'),
+            Self::Text | Self::Json | Self::Table => None,
+        }
+    }
+}
+
+/// Represents the formats that databases can be exported in.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// An HAProxy map file, with `<cidr> <country_code>` lines.
+    Haproxy,
+    /// An nftables table with `inet` sets of the selected countries' ranges, for `nft -f`.
+    Nftables,
+    /// An `ipset restore` script with sets of the selected countries' ranges.
+    Ipset,
+}
+
+/// Represents the nftables address families that databases can be exported for.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportFamily {
+    /// Both IPv4 and IPv6.
+    #[default]
+    Inet,
+    /// Only IPv4.
+    Ip,
+    /// Only IPv6.
+    Ip6,
+}
+
+/// Represents what an exported nftables ruleset does with traffic from the selected countries.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportPolicy {
+    /// Drop incoming traffic from the selected countries.
+    Block,
+    /// Drop incoming traffic from everywhere but the selected countries, including local networks,
+    /// except for loopback traffic, established connections, and IPv6 neighbor discovery.
+    Allow,
+}
+
+/// Represents the formats that databases can be converted between.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatabaseFormat {
+    /// Tor's `geoip` and `geoip6` files, with IPv4 addresses written as integers.
+    Tor,
+    /// `<start>,<end>,<country_code>` lines, with addresses written as text.
+    Csv,
+    /// A single binary snapshot of both databases, written by `db convert`.
+    Snapshot,
+    /// A MaxMind database, ex. `GeoLite2-Country.mmdb` from `db download`, which can only be
+    /// converted from.
+    Mmdb,
 }
 
 impl Display for Arguments {
@@ -94,10 +797,28 @@ impl Display for Arguments {
         writeln!(f, "Config:")?;
         writeln!(f, " * Config: {:?}", self.config_path)?;
         writeln!(f, " * IPv4 DB: {:?}", self.ipv4_path)?;
-        writeln!(f, " * IPv6 DB: {:?}", self.ipv6_path)
+        writeln!(f, " * IPv4 DB length: {:?}", self.ipv4_len)?;
+        writeln!(f, " * IPv4 DB comment: {:?}", self.ipv4_comment)?;
+        writeln!(f, " * IPv4 DB URL: {:?}", self.ipv4_url)?;
+        writeln!(f, " * IPv6 DB: {:?}", self.ipv6_path)?;
+        writeln!(f, " * IPv6 DB length: {:?}", self.ipv6_len)?;
+        writeln!(f, " * IPv6 DB comment: {:?}", self.ipv6_comment)?;
+        writeln!(f, " * IPv6 DB URL: {:?}", self.ipv6_url)?;
+        writeln!(f, " * Fallback: {:?}", self.fallback)?;
+        writeln!(f, " * Fallback IPv4 DB: {:?}", self.fallback_ipv4_path)?;
+        writeln!(f, " * Fallback IPv6 DB: {:?}", self.fallback_ipv6_path)?;
+        writeln!(f, " * Language: {:?}", self.lang)
     }
 }
 
+/// Where `db update` downloads the IPv4 database from by default: Tor's copy.
+const DEFAULT_IPV4_URL: &str =
+    "https://gitlab.torproject.org/tpo/core/tor/-/raw/main/src/config/geoip";
+
+/// Where `db update` downloads the IPv6 database from by default: Tor's copy.
+const DEFAULT_IPV6_URL: &str =
+    "https://gitlab.torproject.org/tpo/core/tor/-/raw/main/src/config/geoip6";
+
 /// For a given `Arguments` result from Clap, return `arguments` with defaults inserted.
 pub fn get_config(arguments: Arguments) -> Arguments {
     let from_config = get_config_file_arguments(&arguments).and_then(|v| v.ok());
@@ -110,6 +831,7 @@ pub fn get_config(arguments: Arguments) -> Arguments {
 
     let ipv4_path = arguments
         .ipv4_path
+        .or_else(|| from_config.as_ref().and_then(|v| v.ipv4_path.clone()))
         .unwrap_or_else(|| Path::new("/usr/share/tor/geoip").into());
 
     let ipv4_len = arguments
@@ -122,6 +844,11 @@ pub fn get_config(arguments: Arguments) -> Arguments {
         .or_else(|| from_config.as_ref().and_then(|v| v.ipv4_comment))
         .unwrap_or('#');
 
+    let ipv4_url = arguments
+        .ipv4_url
+        .or_else(|| from_config.as_ref().and_then(|v| v.ipv4_url.clone()))
+        .unwrap_or_else(|| DEFAULT_IPV4_URL.into());
+
     let ipv6_path = arguments
         .ipv6_path
         .or_else(|| from_config.as_ref().and_then(|v| v.ipv6_path.clone()))
@@ -137,17 +864,202 @@ pub fn get_config(arguments: Arguments) -> Arguments {
         .or_else(|| from_config.as_ref().and_then(|v| v.ipv6_comment))
         .unwrap_or('#');
 
+    let ipv6_url = arguments
+        .ipv6_url
+        .or_else(|| from_config.as_ref().and_then(|v| v.ipv6_url.clone()))
+        .unwrap_or_else(|| DEFAULT_IPV6_URL.into());
+
+    let fallback = arguments
+        .fallback
+        .or_else(|| from_config.as_ref().and_then(|v| v.fallback))
+        .unwrap_or(FallbackMode::None);
+
+    let fallback_ipv4_path = arguments.fallback_ipv4_path.or_else(|| {
+        from_config
+            .as_ref()
+            .and_then(|v| v.fallback_ipv4_path.clone())
+    });
+
+    let fallback_ipv6_path = arguments.fallback_ipv6_path.or_else(|| {
+        from_config
+            .as_ref()
+            .and_then(|v| v.fallback_ipv6_path.clone())
+    });
+
+    let lang = arguments
+        .lang
+        .or_else(|| from_config.as_ref().and_then(|v| v.lang.clone()));
+
     Arguments {
+        command: arguments.command,
         config_path: Some(config),
-        ipv4_addr: arguments.ipv4_addr,
+        verbose: arguments.verbose,
+        quiet: arguments.quiet,
+        log_format: arguments.log_format,
         ipv4_path: Some(ipv4_path),
         ipv4_len: Some(ipv4_len),
         ipv4_comment: Some(ipv4_comment),
-        ipv6_addr: arguments.ipv6_addr,
+        ipv4_url: Some(ipv4_url),
         ipv6_path: Some(ipv6_path),
         ipv6_len: Some(ipv6_len),
         ipv6_comment: Some(ipv6_comment),
+        ipv6_url: Some(ipv6_url),
+        fallback: Some(fallback),
+        fallback_ipv4_path,
+        fallback_ipv6_path,
+        lang,
+    }
+}
+
+/// The global options that can only be given on the command line.
+const COMMAND_LINE_ONLY: [&str; 4] = ["config_path", "verbose", "quiet", "log_format"];
+
+/// Return whether `argument` can also be set in the configuration file, under its ID.
+pub fn is_config_key(argument: &Arg) -> bool {
+    argument.is_global_set() && !COMMAND_LINE_ONLY.contains(&argument.get_id().as_str())
+}
+
+/// For the configuration file at `path`, return a description of every problem with it: unknown
+/// keys, values of the wrong type, databases that do not exist, and options that conflict.
+pub fn validate_config(path: &Path) -> Vec<String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(error) => return vec![format!("Failed to read {}: {error}", path.display())],
+    };
+    let table: toml::Table = match toml::from_str(&contents) {
+        Ok(table) => table,
+        Err(error) => return vec![error.to_string().trim_end().to_owned()],
+    };
+
+    let mut problems = Vec::new();
+
+    let command = Arguments::command();
+    let keys: Vec<&str> = command
+        .get_arguments()
+        .filter(|argument| is_config_key(argument))
+        .map(|argument| argument.get_id().as_str())
+        .collect();
+    for key in table.keys().filter(|key| !keys.contains(&key.as_str())) {
+        let line = contents
+            .lines()
+            .position(|line| line.trim_start().starts_with(key.as_str()))
+            .map(|index| format!(" on line {}", index + 1))
+            .unwrap_or_default();
+        problems.push(format!("Unknown key `{key}`{line}"));
     }
+
+    let config: Arguments = match toml::from_str(&contents) {
+        Ok(config) => config,
+        Err(error) => {
+            problems.push(error.to_string().trim_end().to_owned());
+            return problems;
+        }
+    };
+
+    let paths = [
+        ("ipv4_path", &config.ipv4_path),
+        ("ipv6_path", &config.ipv6_path),
+        ("fallback_ipv4_path", &config.fallback_ipv4_path),
+        ("fallback_ipv6_path", &config.fallback_ipv6_path),
+    ];
+    for (key, path) in paths {
+        match path {
+            Some(path) if !path.is_file() => problems.push(format!(
+                "`{key}` refers to {}, which does not exist",
+                path.display()
+            )),
+            _ => (),
+        }
+    }
+
+    for (key, path) in &paths[2..] {
+        match (config.fallback, path) {
+            (Some(FallbackMode::Database), None) => {
+                problems.push(format!("`fallback` is `database`, but `{key}` is not set"))
+            }
+            (Some(FallbackMode::None | FallbackMode::Unknown) | None, Some(_)) => {
+                problems.push(format!("`{key}` is set, but `fallback` is not `database`"))
+            }
+            _ => (),
+        }
+    }
+
+    match &config.lang {
+        Some(language) if !is_supported_language(language) => problems.push(format!(
+            "`lang` is `{language}`, which has no country names"
+        )),
+        _ => (),
+    }
+
+    problems
+}
+
+/// Return the contents of a configuration file setting every key to its default value (or
+/// commenting it out if it has none), each described by its help.
+pub fn default_config() -> String {
+    // An empty path is never a readable file, so nothing overrides the defaults
+    let defaults = get_config(Arguments {
+        config_path: Some(Path::new("").into()),
+        ..Default::default()
+    });
+
+    let path = |path: Option<Box<Path>>| path.map(|path| path.display().to_string().into());
+    let length = |length: Option<usize>| {
+        length.and_then(|length| i64::try_from(length).ok().map(toml::Value::from))
+    };
+    let comment = |comment: Option<char>| comment.map(|comment| comment.to_string().into());
+    let url = |url: Option<Box<str>>| url.map(|url| url.to_string().into());
+    let values: [(&str, Option<toml::Value>); 11] = [
+        ("ipv4_path", path(defaults.ipv4_path)),
+        ("ipv4_len", length(defaults.ipv4_len)),
+        ("ipv4_comment", comment(defaults.ipv4_comment)),
+        ("ipv4_url", url(defaults.ipv4_url)),
+        ("ipv6_path", path(defaults.ipv6_path)),
+        ("ipv6_len", length(defaults.ipv6_len)),
+        ("ipv6_comment", comment(defaults.ipv6_comment)),
+        ("ipv6_url", url(defaults.ipv6_url)),
+        (
+            "fallback",
+            defaults
+                .fallback
+                .and_then(|fallback| fallback.to_possible_value())
+                .map(|fallback| fallback.get_name().into()),
+        ),
+        ("fallback_ipv4_path", path(defaults.fallback_ipv4_path)),
+        ("fallback_ipv6_path", path(defaults.fallback_ipv6_path)),
+    ];
+
+    let mut config = format!(
+        "# Configuration for {}, with every option set to its default value.\n\
+         # Options given on the command line override these.\n",
+        env!("CARGO_PKG_NAME")
+    );
+
+    let command = Arguments::command();
+    for argument in command
+        .get_arguments()
+        .filter(|argument| is_config_key(argument))
+    {
+        let key = argument.get_id().as_str();
+
+        config.push('\n');
+        if let Some(help) = argument.get_help() {
+            for line in help.to_string().lines() {
+                config.push_str(&format!("# {line}\n"));
+            }
+        }
+
+        let value = values
+            .iter()
+            .find(|(name, _)| *name == key)
+            .and_then(|(_, value)| value.as_ref());
+        match value {
+            Some(value) => config.push_str(&format!("{key} = {value}\n")),
+            None => config.push_str(&format!("# {key} =\n")),
+        }
+    }
+
+    config
 }
 
 /// Read the config file for the program for config values.
@@ -0,0 +1,472 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+// Copyright © 2024 Jaxydog
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Measuring the lookup throughput, latency, and memory usage of different ways to search the
+//! databases, for `bench`.
+
+use std::{
+    fmt::{self, Display},
+    hint::black_box,
+    io::{self, Write},
+    mem,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    time::Instant,
+};
+
+use clap::ValueEnum;
+use ip_geo::{cidr::Address, country_list::Country, IpAddrEntry, IpAddrMap};
+use serde::Serialize;
+
+use crate::output;
+
+/// The most addresses that `Backend::Linear` looks up, as it scans every entry for each.
+pub const LINEAR_LOOKUP_LIMIT: usize = 10_000;
+
+/// Represents a way to search the databases.
+#[derive(ValueEnum, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Backend {
+    /// Scan every entry until one contains the address.
+    Linear,
+    /// Binary search the sorted entries, as `lookup` and the server do.
+    BinarySearch,
+    /// Walk a binary trie of the CIDR blocks of every entry, one bit of the address at a time.
+    Trie,
+}
+
+impl Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self
+            .to_possible_value()
+            .expect("`Backend` has no skipped variants");
+        f.write_str(name.get_name())
+    }
+}
+
+/// Searches both databases for the country of an address.
+trait Search {
+    /// Return the country of `address`, if it has one.
+    fn lookup(&self, address: IpAddr) -> Option<&Country>;
+
+    /// Return the estimated size of the index, in bytes, not counting the countries it refers
+    /// to.
+    fn memory(&self) -> usize;
+}
+
+/// Searches by scanning every entry.
+struct Linear<'a> {
+    ipv4: &'a IpAddrMap<Ipv4Addr, Country>,
+    ipv6: &'a IpAddrMap<Ipv6Addr, Country>,
+}
+
+impl Search for Linear<'_> {
+    fn lookup(&self, address: IpAddr) -> Option<&Country> {
+        fn scan<A: Ord + Copy>(map: &IpAddrMap<A, Country>, address: A) -> Option<&Country> {
+            map.iter()
+                .find(|entry| entry.range().contains(&address))
+                .map(IpAddrEntry::value)
+        }
+
+        match address {
+            IpAddr::V4(address) => scan(self.ipv4, address),
+            IpAddr::V6(address) => scan(self.ipv6, address),
+        }
+    }
+
+    fn memory(&self) -> usize {
+        entries_size(self.ipv4, self.ipv6)
+    }
+}
+
+/// Searches by binary searching the sorted entries.
+struct BinarySearch<'a> {
+    ipv4: &'a IpAddrMap<Ipv4Addr, Country>,
+    ipv6: &'a IpAddrMap<Ipv6Addr, Country>,
+}
+
+impl Search for BinarySearch<'_> {
+    fn lookup(&self, address: IpAddr) -> Option<&Country> {
+        match address {
+            IpAddr::V4(address) => self.ipv4.try_search(address).ok(),
+            IpAddr::V6(address) => self.ipv6.try_search(address).ok(),
+        }
+    }
+
+    fn memory(&self) -> usize {
+        entries_size(self.ipv4, self.ipv6)
+    }
+}
+
+/// Return the size of the entries of `ipv4` and `ipv6`, in bytes.
+fn entries_size(ipv4: &IpAddrMap<Ipv4Addr, Country>, ipv6: &IpAddrMap<Ipv6Addr, Country>) -> usize {
+    ipv4.len() * mem::size_of::<IpAddrEntry<Ipv4Addr, Country>>()
+        + ipv6.len() * mem::size_of::<IpAddrEntry<Ipv6Addr, Country>>()
+}
+
+/// Searches by walking a binary trie of each database.
+struct Trie<'a> {
+    ipv4: BitTrie<'a>,
+    ipv6: BitTrie<'a>,
+}
+
+impl Search for Trie<'_> {
+    fn lookup(&self, address: IpAddr) -> Option<&Country> {
+        match address {
+            IpAddr::V4(address) => self.ipv4.lookup(address.to_u128()),
+            IpAddr::V6(address) => self.ipv6.lookup(address.to_u128()),
+        }
+    }
+
+    fn memory(&self) -> usize {
+        self.ipv4.memory() + self.ipv6.memory()
+    }
+}
+
+/// A node of a [`BitTrie`].
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    /// The indexes of the nodes for a next bit of 0 and 1, or 0 (the root) if there are none.
+    children: [u32; 2],
+    /// The index of the country of the CIDR block ending at this node, or `NO_VALUE`.
+    value: u32,
+}
+
+/// The `value` of nodes that do not end a CIDR block.
+const NO_VALUE: u32 = u32::MAX;
+
+impl Default for Node {
+    fn default() -> Self {
+        Self {
+            children: [0; 2],
+            value: NO_VALUE,
+        }
+    }
+}
+
+/// A binary trie of the CIDR blocks of a database, where each bit of an address (from the most
+/// significant) selects a child, and the deepest block containing the address holds its country.
+struct BitTrie<'a> {
+    bits: u32,
+    nodes: Vec<Node>,
+    values: Vec<&'a Country>,
+}
+
+impl<'a> BitTrie<'a> {
+    /// Create a new `BitTrie` of every entry of `map`.
+    fn new<A: Address>(map: &'a IpAddrMap<A, Country>) -> Self {
+        let mut trie = Self {
+            bits: A::BITS,
+            nodes: vec![Node::default()],
+            values: Vec::with_capacity(map.len()),
+        };
+
+        for entry in map.iter() {
+            let value =
+                u32::try_from(trie.values.len()).expect("Databases have under 2^32 entries");
+            trie.values.push(entry.value());
+
+            for cidr in ip_geo::cidr::range_to_cidrs(*entry.start(), *entry.end()) {
+                trie.insert(cidr.address.to_u128(), u32::from(cidr.prefix_len), value);
+            }
+        }
+
+        trie
+    }
+
+    /// Return bit `index` of `address`, counting from the most significant.
+    fn bit(&self, address: u128, index: u32) -> usize {
+        ((address >> (self.bits - 1 - index)) & 1) as usize
+    }
+
+    /// Store `value` for the block of the first `prefix_len` bits of `address`.
+    fn insert(&mut self, address: u128, prefix_len: u32, value: u32) {
+        let mut node = 0;
+
+        for index in 0..prefix_len {
+            let bit = self.bit(address, index);
+            node = match self.nodes[node].children[bit] {
+                0 => {
+                    let child = self.nodes.len();
+                    self.nodes.push(Node::default());
+                    self.nodes[node].children[bit] =
+                        u32::try_from(child).expect("Tries have under 2^32 nodes");
+                    child
+                }
+                child => child as usize,
+            };
+        }
+
+        self.nodes[node].value = value;
+    }
+
+    /// Return the country of the deepest block containing `address`, if any.
+    fn lookup(&self, address: u128) -> Option<&'a Country> {
+        let mut node = 0;
+        let mut value = self.nodes[0].value;
+
+        for index in 0..self.bits {
+            match self.nodes[node].children[self.bit(address, index)] {
+                0 => break,
+                child => node = child as usize,
+            }
+            if self.nodes[node].value != NO_VALUE {
+                value = self.nodes[node].value;
+            }
+        }
+
+        self.values.get(value as usize).copied()
+    }
+
+    /// Return the size of the nodes and values, in bytes.
+    fn memory(&self) -> usize {
+        self.nodes.len() * mem::size_of::<Node>() + self.values.len() * mem::size_of::<&Country>()
+    }
+}
+
+/// Generates pseudorandom numbers with xorshift64*, which is fast and reproducible, not secure.
+struct Random(u64);
+
+impl Random {
+    /// Return the next pseudorandom number.
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Return a pseudorandom number in `0..bound`, or any `u128` if `bound` is `None`.
+    fn below(&mut self, bound: Option<u128>) -> u128 {
+        let value = (u128::from(self.next()) << 64) | u128::from(self.next());
+        bound.map_or(value, |bound| value % bound)
+    }
+}
+
+/// Return `count` addresses from random entries of `ipv4` and `ipv6` (in proportion to their
+/// number of entries), each random within its entry, the same for every `seed`.
+pub fn generate(
+    ipv4: &IpAddrMap<Ipv4Addr, Country>,
+    ipv6: &IpAddrMap<Ipv6Addr, Country>,
+    count: usize,
+    seed: u64,
+) -> Vec<IpAddr> {
+    fn pick<A: Address>(entry: &IpAddrEntry<A, Country>, random: &mut Random) -> A {
+        let (start, end) = (entry.start().to_u128(), entry.end().to_u128());
+        // A range of every address has more addresses than a `u128` can count
+        let len = (end - start).checked_add(1);
+        A::from_u128(start + random.below(len))
+    }
+
+    let entries = ipv4.len() + ipv6.len();
+    if entries == 0 {
+        return Vec::new();
+    }
+
+    // Xorshift never leaves zero
+    let mut random = Random(seed.max(1));
+    (0..count)
+        .map(|_| {
+            let index = random.below(Some(entries as u128)) as usize;
+            match ipv4.get_from_index_as_ref(index) {
+                Ok(entry) => IpAddr::V4(pick(entry, &mut random)),
+                Err(_) => {
+                    let entry = ipv6
+                        .get_from_index_as_ref(index - ipv4.len())
+                        .expect("`index` is below the number of entries");
+                    IpAddr::V6(pick(entry, &mut random))
+                }
+            }
+        })
+        .collect()
+}
+
+/// The measurements of one backend.
+#[derive(Serialize, Debug, Clone)]
+pub struct BackendReport {
+    backend: Backend,
+    /// The number of addresses looked up.
+    lookups: usize,
+    /// The number of addresses that a country was found for.
+    found: usize,
+    lookups_per_second: f64,
+    /// The median time of a single lookup, in nanoseconds.
+    p50_ns: u64,
+    /// The 99th percentile time of a single lookup, in nanoseconds.
+    p99_ns: u64,
+    /// The estimated size of the index, in bytes, not counting the countries it refers to.
+    memory_bytes: usize,
+}
+
+/// The measurements of every backend benchmarked.
+#[derive(Serialize, Debug, Clone)]
+pub struct BenchReport {
+    ipv4_entries: usize,
+    ipv6_entries: usize,
+    /// The number of addresses available to look up.
+    addresses: usize,
+    backends: Vec<BackendReport>,
+}
+
+/// The header row of benchmarks printed as CSV, TSV, or a table.
+const BENCH_COLUMNS: [&str; 7] = [
+    "backend",
+    "lookups",
+    "found",
+    "lookups_per_second",
+    "p50_ns",
+    "p99_ns",
+    "memory_bytes",
+];
+
+/// Look up `addresses` in `ipv4` and `ipv6` with each of `backends`, which must be sorted (ex.
+/// with `IpAddrMap::cleanup`), measuring their throughput, latency, and memory usage.
+///
+/// `Backend::Linear` only looks up the first `LINEAR_LOOKUP_LIMIT` addresses.
+pub fn run(
+    ipv4: &IpAddrMap<Ipv4Addr, Country>,
+    ipv6: &IpAddrMap<Ipv6Addr, Country>,
+    addresses: &[IpAddr],
+    backends: &[Backend],
+) -> BenchReport {
+    let reports = backends
+        .iter()
+        .map(|&backend| {
+            tracing::debug!("Benchmarking {backend}");
+
+            match backend {
+                Backend::Linear => {
+                    let limit = addresses.len().min(LINEAR_LOOKUP_LIMIT);
+                    measure(backend, &Linear { ipv4, ipv6 }, &addresses[..limit])
+                }
+                Backend::BinarySearch => measure(backend, &BinarySearch { ipv4, ipv6 }, addresses),
+                Backend::Trie => {
+                    let trie = Trie {
+                        ipv4: BitTrie::new(ipv4),
+                        ipv6: BitTrie::new(ipv6),
+                    };
+                    measure(backend, &trie, addresses)
+                }
+            }
+        })
+        .collect();
+
+    BenchReport {
+        ipv4_entries: ipv4.len(),
+        ipv6_entries: ipv6.len(),
+        addresses: addresses.len(),
+        backends: reports,
+    }
+}
+
+/// Look up every address of `addresses` with `search`, once to measure throughput and again to
+/// measure the latency of each lookup.
+fn measure(backend: Backend, search: &dyn Search, addresses: &[IpAddr]) -> BackendReport {
+    let start = Instant::now();
+    let found = addresses
+        .iter()
+        .filter(|&&address| black_box(search.lookup(black_box(address))).is_some())
+        .count();
+    let elapsed = start.elapsed();
+
+    let mut latencies: Vec<u64> = addresses
+        .iter()
+        .map(|&address| {
+            let start = Instant::now();
+            black_box(search.lookup(black_box(address)));
+            u64::try_from(start.elapsed().as_nanos()).unwrap_or(u64::MAX)
+        })
+        .collect();
+    latencies.sort_unstable();
+    let percentile = |percent: usize| {
+        let index = (latencies.len() * percent / 100).min(latencies.len().saturating_sub(1));
+        latencies.get(index).copied().unwrap_or_default()
+    };
+
+    BackendReport {
+        backend,
+        lookups: addresses.len(),
+        found,
+        lookups_per_second: addresses.len() as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+        p50_ns: percentile(50),
+        p99_ns: percentile(99),
+        memory_bytes: search.memory(),
+    }
+}
+
+impl BenchReport {
+    /// Return the header row, `BENCH_COLUMNS`.
+    pub fn columns(&self) -> &'static [&'static str] {
+        &BENCH_COLUMNS
+    }
+
+    /// Return a row of fields per backend, in the order of `columns`.
+    pub fn rows(&self) -> Vec<Vec<String>> {
+        self.backends
+            .iter()
+            .map(|report| {
+                vec![
+                    report.backend.to_string(),
+                    report.lookups.to_string(),
+                    report.found.to_string(),
+                    format!("{:.0}", report.lookups_per_second),
+                    report.p50_ns.to_string(),
+                    report.p99_ns.to_string(),
+                    report.memory_bytes.to_string(),
+                ]
+            })
+            .collect()
+    }
+
+    /// Write the measurements to `writer` as a header row and the rows of `rows`, separated by
+    /// `delimiter`.
+    pub fn write_records(&self, mut writer: impl Write, delimiter: char) -> io::Result<()> {
+        output::write_record(&mut writer, delimiter, self.columns())?;
+        for row in self.rows() {
+            let row: Vec<&str> = row.iter().map(String::as_str).collect();
+            output::write_record(&mut writer, delimiter, &row)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} addresses, {} IPv4 and {} IPv6 entries",
+            self.addresses, self.ipv4_entries, self.ipv6_entries
+        )?;
+
+        for report in &self.backends {
+            writeln!(
+                f,
+                "{}: {:.0} lookups/s, p50 {} ns, p99 {} ns, {} bytes ({} lookups, {} found)",
+                report.backend,
+                report.lookups_per_second,
+                report.p50_ns,
+                report.p99_ns,
+                report.memory_bytes,
+                report.lookups,
+                report.found
+            )?;
+        }
+
+        Ok(())
+    }
+}
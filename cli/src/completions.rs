@@ -0,0 +1,419 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+// Copyright © 2024 Jaxydog
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Generating shell completion scripts for the subcommands and options of the program.
+
+use std::fmt::Write;
+
+use clap::{builder::StyledStr, Arg, Command, ValueEnum};
+use ip_geo::country_list::get_countries;
+
+/// The value name of options that take country codes, which are completed from the country list.
+pub const COUNTRY_CODE: &str = "COUNTRY_CODE";
+
+/// Represents the shells that completion scripts can be generated for.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+/// The words that can follow a command: its subcommands and options.
+struct Completion {
+    /// The subcommands leading to the command, ex. `["db", "export"]`, or none for the program.
+    path: Vec<String>,
+    /// The names of the subcommands of the command, and their descriptions.
+    subcommands: Vec<(String, String)>,
+    /// The long options of the command.
+    options: Vec<LongOption>,
+    /// The possible values of the positional arguments of the command, ex. `bash`.
+    positionals: Vec<String>,
+}
+
+/// A long option of a command.
+struct LongOption {
+    /// The option, ex. `--format`.
+    name: String,
+    /// The first line of the option's help.
+    description: String,
+    /// What the option's value is completed with, if it takes one.
+    value: Option<Value>,
+}
+
+/// What the value of an option is completed with.
+enum Value {
+    /// One of a list of words (or several, separated by `delimiter` if it is set).
+    Words {
+        words: Vec<String>,
+        delimiter: Option<char>,
+    },
+    /// Anything else, ex. a path, which the shell completes as a file.
+    Files,
+}
+
+/// Return a completion script for `shell`, completing the subcommands and options of `command`
+/// and the values of its options.
+pub fn generate(shell: Shell, mut command: Command) -> String {
+    // Building the command adds the help and version options, and propagates global options
+    command.build();
+    let name = command.get_name().to_owned();
+
+    let mut completions = Vec::new();
+    collect(&command, Vec::new(), &mut completions);
+
+    match shell {
+        Shell::Bash => bash(&name, &completions),
+        Shell::Zsh => format!(
+            "#compdef {name}\n\nautoload -U +X bashcompinit && bashcompinit\n\n{}",
+            bash(&name, &completions)
+        ),
+        Shell::Fish => fish(&name, &completions),
+        Shell::Powershell => powershell(&name, &completions),
+    }
+}
+
+/// Add the completions of `command`, found by following `path`, and of every one of its
+/// subcommands to `completions`.
+fn collect(command: &Command, path: Vec<String>, completions: &mut Vec<Completion>) {
+    let subcommands = command
+        .get_subcommands()
+        .filter(|subcommand| !subcommand.is_hide_set())
+        .map(|subcommand| {
+            let description = describe(subcommand.get_about());
+            (subcommand.get_name().to_owned(), description)
+        })
+        .collect();
+    let options = command
+        .get_arguments()
+        .filter(|argument| !argument.is_hide_set())
+        .filter_map(|argument| {
+            Some(LongOption {
+                name: format!("--{}", argument.get_long()?),
+                description: describe(argument.get_help()),
+                value: value(argument),
+            })
+        })
+        .collect();
+    let positionals = command
+        .get_positionals()
+        .filter(|argument| !argument.is_hide_set())
+        .flat_map(|argument| match value(argument) {
+            Some(Value::Words { words, .. }) => words,
+            Some(Value::Files) | None => Vec::new(),
+        })
+        .collect();
+
+    completions.push(Completion {
+        path: path.clone(),
+        subcommands,
+        options,
+        positionals,
+    });
+
+    for subcommand in command.get_subcommands() {
+        if subcommand.is_hide_set() {
+            continue;
+        }
+
+        let mut path = path.clone();
+        path.push(subcommand.get_name().to_owned());
+        collect(subcommand, path, completions);
+    }
+}
+
+/// Return what the value of `argument` is completed with, if it takes one.
+fn value(argument: &Arg) -> Option<Value> {
+    if !argument.get_action().takes_values() {
+        return None;
+    }
+
+    let delimiter = argument.get_value_delimiter();
+    if argument
+        .get_value_names()
+        .is_some_and(|names| names.iter().any(|name| name == COUNTRY_CODE))
+    {
+        let words = get_countries()
+            .into_keys()
+            .filter(|code| code.chars().all(|char| char.is_ascii_alphanumeric()))
+            .map(|code| code.to_string())
+            .collect();
+
+        return Some(Value::Words { words, delimiter });
+    }
+
+    let words: Vec<String> = argument
+        .get_possible_values()
+        .iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| value.get_name().to_owned())
+        .collect();
+
+    if words.is_empty() {
+        Some(Value::Files)
+    } else {
+        Some(Value::Words { words, delimiter })
+    }
+}
+
+/// Return the first line of a description, or nothing if there is none.
+fn describe(description: Option<&StyledStr>) -> String {
+    description
+        .map(|description| description.to_string())
+        .and_then(|description| description.lines().next().map(str::to_owned))
+        .unwrap_or_default()
+}
+
+/// Return the words that can follow `completion`: its subcommands, its positional values, then
+/// its options.
+fn words(completion: &Completion) -> Vec<&str> {
+    completion
+        .subcommands
+        .iter()
+        .map(|(subcommand, _)| subcommand.as_str())
+        .chain(completion.positionals.iter().map(String::as_str))
+        .chain(completion.options.iter().map(|option| option.name.as_str()))
+        .collect()
+}
+
+/// Return a Bash completion script, which follows the subcommands typed so far to complete the
+/// words that can follow them, or the value of the option before the cursor.
+fn bash(name: &str, completions: &[Completion]) -> String {
+    let function = format!("_{}", name.replace('-', "_"));
+    let paths: Vec<String> = completions
+        .iter()
+        .filter(|completion| !completion.path.is_empty())
+        .map(|completion| format!("\"{}\"", completion.path.join(" ")))
+        .collect();
+
+    let mut script = String::new();
+    let _ = writeln!(script, "{function}() {{");
+    let _ = writeln!(
+        script,
+        "    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\" path=\"\" word words"
+    );
+    let _ = writeln!(
+        script,
+        "    for word in \"${{COMP_WORDS[@]:1:COMP_CWORD-1}}\"; do"
+    );
+    let _ = writeln!(script, "        case \"${{path:+$path }}$word\" in");
+    let _ = writeln!(
+        script,
+        "            {}) path=\"${{path:+$path }}$word\" ;;",
+        paths.join("|")
+    );
+    let _ = writeln!(script, "        esac");
+    let _ = writeln!(script, "    done");
+    let _ = writeln!(
+        script,
+        "    case \"$path:${{COMP_WORDS[COMP_CWORD-1]}}\" in"
+    );
+    for completion in completions {
+        for option in &completion.options {
+            let reply = match &option.value {
+                None => continue,
+                Some(Value::Files) => "$(compgen -f -- \"$cur\")".to_owned(),
+                Some(Value::Words {
+                    words,
+                    delimiter: Some(delimiter),
+                }) => format!(
+                    "$(compgen -P \"${{cur%\"${{cur##*{delimiter}}}\"}}\" -W \"{}\" -- \"${{cur##*{delimiter}}}\")",
+                    words.join(" ")
+                ),
+                Some(Value::Words {
+                    words,
+                    delimiter: None,
+                }) => format!("$(compgen -W \"{}\" -- \"$cur\")", words.join(" ")),
+            };
+            let _ = writeln!(
+                script,
+                "        \"{}:{}\") COMPREPLY=({reply}); return ;;",
+                completion.path.join(" "),
+                option.name
+            );
+        }
+    }
+    let _ = writeln!(script, "    esac");
+    let _ = writeln!(script, "    case \"$path\" in");
+    for completion in completions {
+        let _ = writeln!(
+            script,
+            "        \"{}\") words=\"{}\" ;;",
+            completion.path.join(" "),
+            words(completion).join(" ")
+        );
+    }
+    let _ = writeln!(script, "    esac");
+    let _ = writeln!(
+        script,
+        "    COMPREPLY=($(compgen -W \"$words\" -- \"$cur\"))"
+    );
+    let _ = writeln!(script, "}}");
+    let _ = writeln!(script, "complete -F {function} {name}");
+
+    script
+}
+
+/// Return a fish completion script, with a description of every subcommand and option.
+fn fish(name: &str, completions: &[Completion]) -> String {
+    let mut script = String::new();
+
+    for completion in completions {
+        let condition = match completion.path.last() {
+            Some(parent) => format!("__fish_seen_subcommand_from {parent}"),
+            None => "__fish_use_subcommand".into(),
+        };
+
+        for (subcommand, description) in &completion.subcommands {
+            let _ = writeln!(
+                script,
+                "complete -c {name} -f -n '{condition}' -a {subcommand} -d '{}'",
+                escape_fish(description)
+            );
+        }
+
+        if !completion.positionals.is_empty() {
+            let _ = writeln!(
+                script,
+                "complete -c {name} -f -n '{condition}' -a '{}'",
+                completion.positionals.join(" ")
+            );
+        }
+
+        for option in &completion.options {
+            let long = option.name.trim_start_matches('-');
+            let value = match &option.value {
+                None => String::new(),
+                Some(Value::Files) => " -r -F".to_owned(),
+                Some(Value::Words { words, .. }) => format!(" -x -a '{}'", words.join(" ")),
+            };
+            let _ = match completion.path.last() {
+                Some(_) => writeln!(
+                    script,
+                    "complete -c {name} -n '{condition}' -l {long}{value} -d '{}'",
+                    escape_fish(&option.description)
+                ),
+                None => writeln!(
+                    script,
+                    "complete -c {name} -l {long}{value} -d '{}'",
+                    escape_fish(&option.description)
+                ),
+            };
+        }
+    }
+
+    script
+}
+
+/// Escape `text` for use in a single-quoted fish string.
+fn escape_fish(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+/// Return a PowerShell completion script, which follows the subcommands typed so far to complete
+/// the words that can follow them, or the value of the option before the cursor.
+fn powershell(name: &str, completions: &[Completion]) -> String {
+    let quote = |text: &str| format!("'{}'", text.replace('\'', "''"));
+    let list = |words: &[&str]| {
+        let words: Vec<String> = words.iter().map(|word| quote(word)).collect();
+        format!("@({})", words.join(", "))
+    };
+    let paths: Vec<String> = completions
+        .iter()
+        .filter(|completion| !completion.path.is_empty())
+        .map(|completion| completion.path.join(" "))
+        .collect();
+    let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+
+    let mut script = String::new();
+    let _ = writeln!(
+        script,
+        "Register-ArgumentCompleter -Native -CommandName {} -ScriptBlock {{",
+        quote(name)
+    );
+    let _ = writeln!(
+        script,
+        "    param($wordToComplete, $commandAst, $cursorPosition)"
+    );
+    let _ = writeln!(script);
+    let _ = writeln!(script, "    $paths = {}", list(&paths));
+    let _ = writeln!(script, "    $path = ''");
+    let _ = writeln!(script, "    $previous = ''");
+    let _ = writeln!(
+        script,
+        "    foreach ($element in $commandAst.CommandElements | Select-Object -Skip 1) {{"
+    );
+    let _ = writeln!(
+        script,
+        "        if ($element.Extent.EndOffset -ge $cursorPosition) {{ break }}"
+    );
+    let _ = writeln!(script, "        $previous = $element.ToString()");
+    let _ = writeln!(
+        script,
+        "        $candidate = if ($path) {{ \"$path $previous\" }} else {{ $previous }}"
+    );
+    let _ = writeln!(
+        script,
+        "        if ($paths -contains $candidate) {{ $path = $candidate }}"
+    );
+    let _ = writeln!(script, "    }}");
+    let _ = writeln!(script);
+    let _ = writeln!(script, "    $words = switch (\"${{path}}:$previous\") {{");
+    for completion in completions {
+        for option in &completion.options {
+            let action = match &option.value {
+                None => continue,
+                // Completing nothing falls back to completing files
+                Some(Value::Files) => "return".to_owned(),
+                Some(Value::Words { words, .. }) => {
+                    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+                    format!("{}; break", list(&words))
+                }
+            };
+            let key = format!("{}:{}", completion.path.join(" "), option.name);
+            let _ = writeln!(script, "        {} {{ {action} }}", quote(&key));
+        }
+    }
+    let _ = writeln!(script, "        default {{");
+    let _ = writeln!(script, "            switch ($path) {{");
+    for completion in completions {
+        let _ = writeln!(
+            script,
+            "                {} {{ {} }}",
+            quote(&completion.path.join(" ")),
+            list(&words(completion))
+        );
+    }
+    let _ = writeln!(script, "            }}");
+    let _ = writeln!(script, "        }}");
+    let _ = writeln!(script, "    }}");
+    let _ = writeln!(script);
+    let _ = writeln!(
+        script,
+        "    $words | Where-Object {{ $_ -like \"$wordToComplete*\" }} | ForEach-Object {{"
+    );
+    let _ = writeln!(
+        script,
+        "        [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)"
+    );
+    let _ = writeln!(script, "    }}");
+    let _ = writeln!(script, "}}");
+
+    script
+}
@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+// Copyright © 2024 Jaxydog
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Converting databases between formats, for `db convert`.
+
+use std::{
+    fs,
+    io::{self, BufWriter},
+    path::Path,
+    process::ExitCode,
+};
+
+use ip_geo::{snapshot::DatabaseMetadata, Error, GeoDatabase};
+
+use crate::{
+    arguments::{Arguments, Command, DatabaseFormat, DbCommand},
+    database_paths, load_database, EXIT_DATABASE_ERROR, EXIT_USAGE,
+};
+
+/// Convert the databases (specified in `arguments`) from one format to another.
+pub fn convert_database(arguments: Arguments) -> ExitCode {
+    let Some(Command::Db(DbCommand::Convert {
+        from,
+        to,
+        input,
+        output,
+        ipv6_output,
+    })) = arguments.command.clone()
+    else {
+        unreachable!("`RunType::DbConvert` requires `DbCommand::Convert`");
+    };
+
+    if to == DatabaseFormat::Mmdb {
+        tracing::error!("Cannot convert to a MaxMind database, only from one");
+        return ExitCode::from(EXIT_USAGE);
+    }
+
+    let (database, metadata) = match from {
+        DatabaseFormat::Tor | DatabaseFormat::Csv => {
+            let [(_, ipv4_path, _), (_, ipv6_path, _)] = database_paths(&arguments);
+            let metadata =
+                DatabaseMetadata::new(format!("{}, {}", ipv4_path.display(), ipv6_path.display()));
+
+            let Ok(database) = load_database(arguments) else {
+                return ExitCode::from(EXIT_DATABASE_ERROR);
+            };
+            (database, metadata)
+        }
+        DatabaseFormat::Snapshot | DatabaseFormat::Mmdb => {
+            let input = input.expect("A valid path to a snapshot or MaxMind database");
+            let result = fs::File::open(&input)
+                .map_err(Error::from)
+                .and_then(|file| {
+                    if from == DatabaseFormat::Snapshot {
+                        GeoDatabase::read_snapshot(io::BufReader::new(file))
+                    } else {
+                        GeoDatabase::read_mmdb(io::BufReader::new(file))
+                    }
+                });
+
+            match result {
+                Ok(database) => {
+                    let metadata = database
+                        .metadata()
+                        .cloned()
+                        .unwrap_or_else(|| DatabaseMetadata::new(input.display().to_string()));
+                    (database, metadata)
+                }
+                Err(error) => {
+                    tracing::error!("Failed to read {}: {error}", input.display());
+                    return ExitCode::from(EXIT_DATABASE_ERROR);
+                }
+            }
+        }
+    };
+
+    let create = |path: &Path| fs::File::create(path).map(BufWriter::new);
+    let result = match to {
+        DatabaseFormat::Snapshot => {
+            create(&output).and_then(|file| database.write_snapshot(file, &metadata))
+        }
+        DatabaseFormat::Mmdb => unreachable!("Converting to MMDB is rejected above"),
+        DatabaseFormat::Tor | DatabaseFormat::Csv => {
+            let ipv6_output = ipv6_output
+                .as_deref()
+                .expect("A valid path to write the IPv6 database to");
+
+            create(&output)
+                .and_then(|ipv4| Ok((ipv4, create(ipv6_output)?)))
+                .and_then(|(ipv4, ipv6)| {
+                    if to == DatabaseFormat::Tor {
+                        database.export_tor(ipv4, ipv6)
+                    } else {
+                        database.export_csv(ipv4, ipv6)
+                    }
+                })
+        }
+    };
+
+    match (result, ipv6_output) {
+        (Ok(()), None) => {
+            tracing::info!("Wrote snapshot to {}", output.display());
+            ExitCode::SUCCESS
+        }
+        (Ok(()), Some(ipv6_output)) => {
+            tracing::info!(
+                "Wrote databases to {} and {}",
+                output.display(),
+                ipv6_output.display()
+            );
+            ExitCode::SUCCESS
+        }
+        (Err(error), _) => {
+            tracing::error!("Failed to convert databases: {error}");
+            ExitCode::from(EXIT_DATABASE_ERROR)
+        }
+    }
+}
@@ -0,0 +1,349 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+// Copyright © 2024 Jaxydog
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Printing the ranges that changed between two databases, or the addresses gained and lost by
+//! each country, for `db diff`, from the changes found by [`ip_geo::diff`].
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    io::{self, Write},
+    net::IpAddr,
+    path::Path,
+    process::ExitCode,
+};
+
+use ip_geo::{
+    cidr::Address,
+    diff::{Change, RangeChange},
+    parse::{ParseMode, ParseReport},
+    Error, GeoDatabase, IpAddrMap,
+};
+use serde::Serialize;
+
+use crate::{
+    arguments::{Arguments, Command, DbCommand, OutputFormat},
+    first_entry_start, open_database, output, EXIT_DATABASE_ERROR,
+};
+
+/// Print the ranges that changed between two databases (specified in `arguments`), or the
+/// addresses gained and lost by each country.
+pub fn print_diff(arguments: Arguments) -> ExitCode {
+    let Some(Command::Db(DbCommand::Diff {
+        old,
+        new,
+        output,
+        countries,
+    })) = &arguments.command
+    else {
+        unreachable!("`RunType::DbDiff` requires `DbCommand::Diff`");
+    };
+
+    let mut databases = Vec::with_capacity(2);
+    for path in [old, new] {
+        match load_database_file(path, arguments.ipv4_comment) {
+            Ok(database) => databases.push(database),
+            Err(error) => {
+                tracing::error!("Failed to read {}: {error}", path.display());
+                return ExitCode::from(EXIT_DATABASE_ERROR);
+            }
+        }
+    }
+    let [old, new] = &databases[..] else {
+        unreachable!("Both databases were loaded");
+    };
+
+    let mut diff = Diff::default();
+    diff.extend(
+        "ipv4",
+        ip_geo::diff::diff(old.ipv4(), new.ipv4()),
+        0,
+        "addresses",
+    );
+    // IPv6 addresses are too numerous to be meaningful, so count /64 networks instead
+    diff.extend(
+        "ipv6",
+        ip_geo::diff::diff(old.ipv6(), new.ipv6()),
+        64,
+        "/64 networks",
+    );
+    if *countries {
+        diff.changes = None;
+    } else {
+        diff.countries = None;
+    }
+
+    let result = match output.format {
+        OutputFormat::Text => {
+            print!("{diff}");
+            Ok(())
+        }
+        OutputFormat::Json => output::write_json(io::stdout().lock(), &diff, output.pretty),
+        OutputFormat::Csv | OutputFormat::Tsv => diff.write_records(
+            io::stdout().lock(),
+            output
+                .format
+                .delimiter()
+                .expect("CSV and TSV have delimiters"),
+        ),
+        OutputFormat::Table => {
+            output::write_table(io::stdout().lock(), diff.columns(), &diff.rows())
+        }
+    };
+    if let Err(error) = result {
+        tracing::error!("Failed to print the diff: {error}");
+        return ExitCode::from(EXIT_DATABASE_ERROR);
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Load the database at `path`, which is either a snapshot, a MaxMind database (by its `.mmdb`
+/// extension), or a single IPv4 or IPv6 database in Tor's format or CSV, printing any malformed
+/// lines that were skipped.
+fn load_database_file(path: &Path, comment: Option<char>) -> Result<GeoDatabase, Error> {
+    if path
+        .extension()
+        .is_some_and(|extension| extension == "mmdb")
+    {
+        return GeoDatabase::read_mmdb(open_database(path)?);
+    }
+
+    let is_ipv6 = match first_entry_start(path, comment)? {
+        None => return Ok(GeoDatabase::new(IpAddrMap::new(), IpAddrMap::new())),
+        // Tor only writes IPv4 addresses as integers
+        Some(start) if start.parse::<u32>().is_ok() => false,
+        Some(start) => match start.parse::<IpAddr>() {
+            Ok(address) => address.is_ipv6(),
+            Err(_) => return GeoDatabase::read_snapshot(open_database(path)?),
+        },
+    };
+
+    let print_report = |report: ParseReport| {
+        for malformed in report.malformed {
+            tracing::warn!(
+                "Skipped malformed entry of {} on {malformed}",
+                path.display()
+            );
+        }
+    };
+
+    if is_ipv6 {
+        let (map, report) = ip_geo::ipv6::parse_ipv6_with_mode(
+            open_database(path)?,
+            0,
+            comment,
+            ParseMode::Lenient,
+        )?;
+        print_report(report);
+        Ok(GeoDatabase::new(IpAddrMap::new(), map))
+    } else {
+        let (map, report) = ip_geo::ipv4::parse_ipv4_with_mode(
+            open_database(path)?,
+            0,
+            comment,
+            ParseMode::Lenient,
+        )?;
+        print_report(report);
+        Ok(GeoDatabase::new(map, IpAddrMap::new()))
+    }
+}
+
+/// The ranges that changed between two databases, or the addresses gained and lost by each
+/// country.
+#[derive(Serialize, Debug)]
+struct Diff {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changes: Option<Vec<ChangedRange>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    countries: Option<Vec<CountryDelta>>,
+}
+
+impl Default for Diff {
+    fn default() -> Self {
+        Self {
+            changes: Some(Vec::new()),
+            countries: Some(Vec::new()),
+        }
+    }
+}
+
+/// A range whose country changed between two databases.
+#[derive(Serialize, Debug)]
+struct ChangedRange {
+    database: &'static str,
+    /// `added`, `removed`, or `reassigned`.
+    change: &'static str,
+    start: String,
+    end: String,
+    old_country: Option<String>,
+    new_country: Option<String>,
+}
+
+/// The addresses gained and lost by a country between two databases.
+#[derive(Serialize, Debug)]
+struct CountryDelta {
+    database: &'static str,
+    country_code: String,
+    gained: u128,
+    lost: u128,
+    unit: &'static str,
+}
+
+/// The header row of changed ranges printed as CSV, TSV, or a table.
+const DIFF_COLUMNS: [&str; 6] = [
+    "database",
+    "change",
+    "start",
+    "end",
+    "old_country",
+    "new_country",
+];
+
+/// The header row of per-country changes printed as CSV, TSV, or a table.
+const DIFF_COUNTRY_COLUMNS: [&str; 5] = ["database", "country_code", "gained", "lost", "unit"];
+
+impl Diff {
+    /// Add the changes of a database, labelled `database`, counting the addresses gained and lost
+    /// by each country in units of `2 ^ unit_bits` addresses.
+    fn extend<A: Address>(
+        &mut self,
+        database: &'static str,
+        changes: Vec<RangeChange<A>>,
+        unit_bits: u32,
+        unit: &'static str,
+    ) {
+        let mut deltas: BTreeMap<String, (u128, u128)> = BTreeMap::new();
+
+        for RangeChange {
+            range: (start, end),
+            change,
+        } in changes
+        {
+            let size = (end.to_u128() >> unit_bits) - (start.to_u128() >> unit_bits) + 1;
+            let (name, from, to) = match change {
+                Change::Added(to) => ("added", None, Some(to)),
+                Change::Removed(from) => ("removed", Some(from), None),
+                Change::Reassigned { from, to } => ("reassigned", Some(from), Some(to)),
+            };
+
+            if let Some(from) = &from {
+                let delta = deltas.entry(from.to_string()).or_default();
+                delta.1 = delta.1.saturating_add(size);
+            }
+            if let Some(to) = &to {
+                let delta = deltas.entry(to.to_string()).or_default();
+                delta.0 = delta.0.saturating_add(size);
+            }
+
+            if let Some(changes) = &mut self.changes {
+                changes.push(ChangedRange {
+                    database,
+                    change: name,
+                    start: start.to_string(),
+                    end: end.to_string(),
+                    old_country: from.map(|code| code.to_string()),
+                    new_country: to.map(|code| code.to_string()),
+                });
+            }
+        }
+
+        if let Some(countries) = &mut self.countries {
+            countries.extend(deltas.into_iter().map(|(country_code, (gained, lost))| {
+                CountryDelta {
+                    database,
+                    country_code,
+                    gained,
+                    lost,
+                    unit,
+                }
+            }));
+        }
+    }
+
+    /// Return the header row, which is `DIFF_COUNTRY_COLUMNS` if per-country changes were
+    /// requested, and `DIFF_COLUMNS` otherwise.
+    fn columns(&self) -> &'static [&'static str] {
+        if self.changes.is_none() {
+            &DIFF_COUNTRY_COLUMNS
+        } else {
+            &DIFF_COLUMNS
+        }
+    }
+
+    /// Return a row of fields per changed range (or per country), in the order of `columns`.
+    fn rows(&self) -> Vec<Vec<String>> {
+        let changes = self.changes.iter().flatten().map(|change| {
+            vec![
+                change.database.to_owned(),
+                change.change.to_owned(),
+                change.start.clone(),
+                change.end.clone(),
+                change.old_country.clone().unwrap_or_default(),
+                change.new_country.clone().unwrap_or_default(),
+            ]
+        });
+        let countries = self.countries.iter().flatten().map(|delta| {
+            vec![
+                delta.database.to_owned(),
+                delta.country_code.clone(),
+                delta.gained.to_string(),
+                delta.lost.to_string(),
+                delta.unit.to_owned(),
+            ]
+        });
+
+        changes.chain(countries).collect()
+    }
+
+    /// Write the diff to `writer` as a header row and the rows of `rows`, separated by
+    /// `delimiter`.
+    fn write_records(&self, mut writer: impl Write, delimiter: char) -> io::Result<()> {
+        output::write_record(&mut writer, delimiter, self.columns())?;
+        for row in self.rows() {
+            let row: Vec<&str> = row.iter().map(String::as_str).collect();
+            output::write_record(&mut writer, delimiter, &row)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in self.changes.iter().flatten() {
+            let (start, end) = (&change.start, &change.end);
+            match (&change.old_country, &change.new_country) {
+                (None, Some(to)) => writeln!(f, "+ {start}-{end} {to}")?,
+                (Some(from), None) => writeln!(f, "- {start}-{end} {from}")?,
+                (Some(from), Some(to)) => writeln!(f, "~ {start}-{end} {from} -> {to}")?,
+                (None, None) => unreachable!("Every change has an old or new country"),
+            }
+        }
+
+        for delta in self.countries.iter().flatten() {
+            writeln!(
+                f,
+                "{} {}: +{} -{} {}",
+                delta.database, delta.country_code, delta.gained, delta.lost, delta.unit
+            )?;
+        }
+
+        Ok(())
+    }
+}
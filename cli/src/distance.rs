@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! The great-circle distance between the countries of two addresses.
+//!
+//! The databases only place addresses in countries, so distances are between the coordinates of
+//! the centers of those countries, not of the addresses themselves.
+
+use std::{
+    fmt::{self, Display},
+    io::{self, Write},
+    net::IpAddr,
+};
+
+use ip_geo::country_list::Country;
+use serde::Serialize;
+
+use crate::output;
+
+/// The mean radius of the Earth, in kilometers.
+const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+/// The number of kilometers in a mile.
+const KM_PER_MILE: f64 = 1.609344;
+
+/// How precise distances are: no more than the distance between the centers of two countries.
+const PRECISION: &str = "country";
+
+/// The header row of a distance printed as CSV, TSV, or a table.
+const DISTANCE_COLUMNS: [&str; 7] = [
+    "from",
+    "from_country",
+    "to",
+    "to_country",
+    "kilometers",
+    "miles",
+    "precision",
+];
+
+/// Return the great-circle distance between two `(longitude, latitude)` coordinates, in
+/// kilometers, with the haversine formula.
+pub fn haversine(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (from_longitude, from_latitude) = (from.0.to_radians(), from.1.to_radians());
+    let (to_longitude, to_latitude) = (to.0.to_radians(), to.1.to_radians());
+
+    let latitude = ((to_latitude - from_latitude) / 2.0).sin();
+    let longitude = ((to_longitude - from_longitude) / 2.0).sin();
+    let a = latitude * latitude + from_latitude.cos() * to_latitude.cos() * longitude * longitude;
+
+    // Rounding can push `a` just past 1 for antipodal points
+    2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+}
+
+/// An address and the country it was found in.
+#[derive(Serialize, Debug)]
+pub struct Endpoint {
+    pub ip: IpAddr,
+    pub country: Country,
+}
+
+/// The distance between the countries of two addresses.
+#[derive(Serialize, Debug)]
+pub struct Distance {
+    pub from: Endpoint,
+    pub to: Endpoint,
+    pub kilometers: f64,
+    pub miles: f64,
+    /// Always `country`, as distances are between the centers of countries.
+    pub precision: &'static str,
+}
+
+impl Distance {
+    /// Return the distance between the countries of `from` and `to`.
+    pub fn new(from: Endpoint, to: Endpoint) -> Self {
+        let kilometers = haversine(from.country.coordinates, to.country.coordinates);
+
+        Self {
+            from,
+            to,
+            kilometers,
+            miles: kilometers / KM_PER_MILE,
+            precision: PRECISION,
+        }
+    }
+
+    /// Return the header row, `DISTANCE_COLUMNS`.
+    pub fn columns(&self) -> &'static [&'static str] {
+        &DISTANCE_COLUMNS
+    }
+
+    /// Return the single row of fields, in the order of `columns`.
+    pub fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.from.ip.to_string(),
+            self.from.country.code.to_string(),
+            self.to.ip.to_string(),
+            self.to.country.code.to_string(),
+            format!("{:.0}", self.kilometers),
+            format!("{:.0}", self.miles),
+            self.precision.to_owned(),
+        ]]
+    }
+
+    /// Write the distance to `writer` as a header row and the row of `rows`, separated by
+    /// `delimiter`.
+    pub fn write_records(&self, mut writer: impl Write, delimiter: char) -> io::Result<()> {
+        output::write_record(&mut writer, delimiter, self.columns())?;
+        for row in self.rows() {
+            let row: Vec<&str> = row.iter().map(String::as_str).collect();
+            output::write_record(&mut writer, delimiter, &row)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Distance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for Endpoint { ip, country } in [&self.from, &self.to] {
+            let (longitude, latitude) = country.coordinates;
+            writeln!(
+                f,
+                "{ip}: {} {} ({longitude:.2}, {latitude:.2})",
+                country.code, country.name
+            )?;
+        }
+
+        if self.from.country.code == self.to.country.code {
+            return writeln!(
+                f,
+                "Both addresses are in the same country, so they may be anywhere from 0 km apart to its width"
+            );
+        }
+
+        writeln!(
+            f,
+            "~{:.0} km ({:.0} mi) between the centers of their countries, not the addresses themselves",
+            self.kilometers, self.miles
+        )
+    }
+}
@@ -0,0 +1,69 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+// Copyright © 2024 Jaxydog
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Reporting diagnostics through `tracing` to stderr, as text or JSON, so that stdout only holds
+//! results.
+
+use std::io::{self, IsTerminal};
+
+use clap::ValueEnum;
+use tracing::level_filters::LevelFilter;
+
+/// Represents the format that diagnostics are printed to stderr in.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable lines, ex. `ERROR Failed to open ips.txt: ...`.
+    #[default]
+    Text,
+    /// One JSON object per line.
+    Json,
+}
+
+/// Return the most verbose level printed after `verbose` uses of `-v` and `quiet` uses of `-q`:
+/// informational messages by default, debug and then trace messages with `-v`, or warnings,
+/// errors, and then nothing with `-q`.
+pub fn level(verbose: u8, quiet: u8) -> LevelFilter {
+    match i16::from(verbose) - i16::from(quiet) {
+        ..=-3 => LevelFilter::OFF,
+        -2 => LevelFilter::ERROR,
+        -1 => LevelFilter::WARN,
+        0 => LevelFilter::INFO,
+        1 => LevelFilter::DEBUG,
+        2.. => LevelFilter::TRACE,
+    }
+}
+
+/// Install the `tracing` subscriber that prints diagnostics up to `level` to stderr in `format`.
+pub fn init(level: LevelFilter, format: LogFormat) {
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(io::stderr)
+        .with_target(false);
+
+    let result = match format {
+        LogFormat::Text => subscriber
+            .without_time()
+            .with_ansi(io::stderr().is_terminal())
+            .try_init(),
+        LogFormat::Json => subscriber.json().flatten_event(true).try_init(),
+    };
+
+    if let Err(error) = result {
+        eprintln!("Could not set up logging: {error}");
+    }
+}
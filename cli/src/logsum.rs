@@ -0,0 +1,372 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+// Copyright © 2024 Jaxydog
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Summarizing completed access logs by the countries of their clients, for `logsum`, like the
+//! server does for the logs it follows.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Write as _},
+    fs,
+    io::{self, BufRead, Write},
+    net::{IpAddr, SocketAddr},
+    path::Path,
+};
+
+use clap::ValueEnum;
+use ip_geo::GeoDatabase;
+use serde::Serialize;
+
+use crate::output;
+
+/// The fields of interest in a log line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LogEntry {
+    /// The client address.
+    pub address: IpAddr,
+    /// The HTTP status code of the response, if the format has one.
+    pub status: Option<u16>,
+    /// The size of the response in bytes, if the format has one.
+    pub bytes: Option<u64>,
+}
+
+/// Represents the formats that access logs can be parsed as, the same as the server's.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AccessLogFormat {
+    /// Try `combined`, then fall back to `plain` for lines that don't match.
+    #[default]
+    Auto,
+    /// The nginx/Apache common or combined access log format:
+    /// `%h %l %u %t "%r" %>s %b ["%{Referer}i" "%{User-agent}i"]`.
+    Combined,
+    /// Any format, using the first IP address in the line as the client address.
+    Plain,
+}
+
+impl AccessLogFormat {
+    /// Parse a log line, returning `None` if it has no client address.
+    pub fn parse(&self, line: &str) -> Option<LogEntry> {
+        match self {
+            Self::Auto => parse_combined(line).or_else(|| parse_plain(line)),
+            Self::Combined => parse_combined(line),
+            Self::Plain => parse_plain(line),
+        }
+    }
+}
+
+/// Parse a line using the first IP address in it as the client address.
+fn parse_plain(line: &str) -> Option<LogEntry> {
+    Some(LogEntry {
+        address: extract_address(line)?,
+        status: None,
+        bytes: None,
+    })
+}
+
+/// Return the first word of `line` that is an IP address, ignoring surrounding quotes and
+/// punctuation, a `key=` prefix, or a port.
+fn extract_address(line: &str) -> Option<IpAddr> {
+    line.split_ascii_whitespace().find_map(|word| {
+        let word = word.rsplit('=').next().unwrap_or(word);
+        let word =
+            word.trim_matches(|c| matches!(c, '"' | '\'' | ',' | ';' | '(' | ')' | '<' | '>'));
+
+        word.parse()
+            .ok()
+            .or_else(|| word.parse::<SocketAddr>().ok().map(|a| a.ip()))
+            .or_else(|| word.trim_matches(['[', ']']).parse().ok())
+    })
+}
+
+/// Parse a line in the common or combined access log format.
+///
+/// Ex. `192.0.2.1 - frank [10/Oct/2000:13:55:36 -0700] "GET / HTTP/1.0" 200 2326 "-" "curl/8.0"`.
+fn parse_combined(line: &str) -> Option<LogEntry> {
+    let (address, rest) = line.split_once(' ')?;
+    let address = address.parse().ok()?;
+
+    // Skip the identity, user, and timestamp, ex. `- frank [10/Oct/2000:13:55:36 -0700] `
+    let (_, rest) = rest.split_once("] \"")?;
+
+    // Skip the request line, which may contain escaped quotes
+    let mut escaped = false;
+    let end = rest.find(|c| {
+        let end = c == '"' && !escaped;
+        escaped = c == '\\' && !escaped;
+        end
+    })?;
+
+    let mut fields = rest.get(end + 1..)?.split_ascii_whitespace();
+    let status = fields.next()?.parse().ok()?;
+    let bytes = match fields.next()? {
+        "-" => 0,
+        bytes => bytes.parse().ok()?,
+    };
+
+    Some(LogEntry {
+        address,
+        status: Some(status),
+        bytes: Some(bytes),
+    })
+}
+
+/// The requests from the clients in one country.
+#[derive(Serialize, Debug, Clone)]
+struct CountryRequests {
+    code: Box<str>,
+    name: Box<str>,
+    /// The number of log lines from clients in the country.
+    requests: u64,
+    /// The size of the responses to those requests, in bytes, if the format has them.
+    bytes: Option<u64>,
+    /// The number of responses per status class, ex. `2xx`, if the format has them.
+    statuses: BTreeMap<Box<str>, u64>,
+}
+
+impl CountryRequests {
+    /// Return the labels of the country's series, as the server labels them.
+    fn labels(&self) -> Vec<(&'static str, &str)> {
+        vec![("country_code", &self.code), ("country_name", &self.name)]
+    }
+}
+
+/// The requests of an access log, by the countries of their clients.
+#[derive(Serialize, Debug, Clone)]
+pub struct LogSummary {
+    lines: u64,
+    /// The number of lines without a client address, or with one without a country.
+    dropped: u64,
+    /// The requests of each country, most first.
+    countries: Vec<CountryRequests>,
+}
+
+/// The header row of summaries printed as CSV, TSV, or a table.
+const LOGSUM_COLUMNS: [&str; 4] = ["country_code", "country", "requests", "bytes"];
+
+/// Read every line of `reader` as `format` and summarize the requests by the countries of their
+/// clients in `database`.
+///
+/// Lines that are not valid UTF-8 are read lossily, rather than stopping the summary.
+pub fn summarize(
+    mut reader: impl BufRead,
+    format: AccessLogFormat,
+    database: &GeoDatabase,
+) -> io::Result<LogSummary> {
+    let mut summary = LogSummary {
+        lines: 0,
+        dropped: 0,
+        countries: Vec::new(),
+    };
+    let mut countries: BTreeMap<Box<str>, CountryRequests> = BTreeMap::new();
+
+    let mut buffer = Vec::new();
+    loop {
+        buffer.clear();
+        if reader.read_until(b'\n', &mut buffer)? == 0 {
+            break;
+        }
+        let line = String::from_utf8_lossy(&buffer);
+        let line = line.trim_end();
+        if line.is_empty() {
+            continue;
+        }
+        summary.lines += 1;
+
+        let Some((entry, country)) = format
+            .parse(line)
+            .and_then(|entry| Some((entry, database.lookup(entry.address).ok()?)))
+        else {
+            summary.dropped += 1;
+            continue;
+        };
+
+        let requests = countries
+            .entry(country.code.as_ref().into())
+            .or_insert_with(|| CountryRequests {
+                code: country.code.as_ref().into(),
+                name: country.name.as_ref().into(),
+                requests: 0,
+                bytes: None,
+                statuses: BTreeMap::new(),
+            });
+        requests.requests += 1;
+        if let Some(bytes) = entry.bytes {
+            requests.bytes = Some(requests.bytes.unwrap_or_default() + bytes);
+        }
+        if let Some(status) = entry.status {
+            *requests
+                .statuses
+                .entry(format!("{}xx", status / 100).into())
+                .or_default() += 1;
+        }
+    }
+
+    summary.countries = countries.into_values().collect();
+    summary.countries.sort_by(|a, b| {
+        b.requests
+            .cmp(&a.requests)
+            .then_with(|| a.code.cmp(&b.code))
+    });
+
+    Ok(summary)
+}
+
+impl LogSummary {
+    /// Return the header row, `LOGSUM_COLUMNS`.
+    pub fn columns(&self) -> &'static [&'static str] {
+        &LOGSUM_COLUMNS
+    }
+
+    /// Return a row of fields per country, in the order of `columns`.
+    pub fn rows(&self) -> Vec<Vec<String>> {
+        self.countries
+            .iter()
+            .map(|requests| {
+                vec![
+                    requests.code.to_string(),
+                    requests.name.to_string(),
+                    requests.requests.to_string(),
+                    requests
+                        .bytes
+                        .map_or_else(|| "-".to_owned(), |bytes| bytes.to_string()),
+                ]
+            })
+            .collect()
+    }
+
+    /// Write the summary to `writer` as a header row and the rows of `rows`, separated by
+    /// `delimiter`.
+    pub fn write_records(&self, mut writer: impl Write, delimiter: char) -> io::Result<()> {
+        output::write_record(&mut writer, delimiter, self.columns())?;
+        for row in self.rows() {
+            let row: Vec<&str> = row.iter().map(String::as_str).collect();
+            output::write_record(&mut writer, delimiter, &row)?;
+        }
+
+        Ok(())
+    }
+
+    /// Return the summary in the Prometheus text format, with the same metrics the server exports
+    /// for the logs it follows, ex. `ip_geo_log_lines_total{country_code="BE",...} 3`.
+    pub fn render_prometheus(&self) -> String {
+        let mut text = String::new();
+
+        let mut family = |name: &str, help: &str, samples: Vec<(Vec<(&str, &str)>, u64)>| {
+            writeln!(text, "# HELP {name} {help}").expect("string concatenation");
+            writeln!(text, "# TYPE {name} counter").expect("string concatenation");
+            for (labels, value) in samples {
+                let labels: Vec<String> = labels
+                    .iter()
+                    .map(|(name, value)| format!("{name}=\"{}\"", escape_label(value)))
+                    .collect();
+                match labels[..] {
+                    [] => writeln!(text, "{name} {value}"),
+                    _ => writeln!(text, "{name}{{{}}} {value}", labels.join(",")),
+                }
+                .expect("string concatenation");
+            }
+        };
+
+        family(
+            "ip_geo_log_lines_total",
+            "The number of log lines, by the location of their client address.",
+            self.countries
+                .iter()
+                .map(|requests| (requests.labels(), requests.requests))
+                .collect(),
+        );
+        family(
+            "ip_geo_log_lines_dropped_total",
+            "The number of log lines without a client address with a location.",
+            vec![(Vec::new(), self.dropped)],
+        );
+        family(
+            "ip_geo_log_responses_total",
+            "The number of responses in access logs, by location and status class.",
+            self.countries
+                .iter()
+                .flat_map(|requests| {
+                    requests.statuses.iter().map(|(status, &count)| {
+                        let mut labels = requests.labels();
+                        labels.push(("status", status));
+                        (labels, count)
+                    })
+                })
+                .collect(),
+        );
+        family(
+            "ip_geo_log_response_bytes_total",
+            "The size of responses in access logs in bytes, by location.",
+            self.countries
+                .iter()
+                .filter_map(|requests| Some((requests.labels(), requests.bytes?)))
+                .collect(),
+        );
+
+        text
+    }
+
+    /// Replace the contents of the file at `path` with `render_prometheus`, for the node_exporter
+    /// textfile collector.
+    ///
+    /// Writes to a temporary file next to it first and renames it into place, so that the
+    /// collector never reads a partially written file.
+    pub fn write_textfile(&self, path: &Path) -> io::Result<()> {
+        // The collector only reads files ending in `.prom`, so it ignores the temporary file
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+
+        fs::write(&temporary, self.render_prometheus())?;
+        fs::rename(&temporary, path)
+    }
+}
+
+/// Escape a label value for the Prometheus text format.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+impl Display for LogSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} lines, {} without a client address with a country",
+            self.lines, self.dropped
+        )?;
+
+        for requests in &self.countries {
+            write!(
+                f,
+                "{} ({}): {} requests",
+                requests.code, requests.name, requests.requests
+            )?;
+            if let Some(bytes) = requests.bytes {
+                write!(f, ", {bytes} bytes")?;
+            }
+            for (status, count) in &requests.statuses {
+                write!(f, ", {count} {status}")?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
@@ -18,53 +18,684 @@
 
 #![allow(dead_code)]
 
-use clap::Parser;
-use ip_geo::{country_list::Country, Error};
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    io::{self, BufRead, BufWriter, IsTerminal, Read, Write},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    process::{self, ExitCode},
+    sync::OnceLock,
+    thread,
+};
 
+use clap::{CommandFactory, Parser};
+use ip_geo::{
+    cidr::Address,
+    country::UNKNOWN_COUNTRY_CODE,
+    country_list::Country,
+    database::Fallback,
+    export::{NftablesFamily, NftablesPolicy},
+    manifest::Manifest,
+    parse::ParseMode,
+    Error, GeoDatabase, IpAddrMap,
+};
 mod arguments;
-use arguments::{Arguments, RunType};
+use arguments::{
+    Arguments, Command, ConfigCommand, DbCommand, ExportFamily, ExportFormat, ExportPolicy,
+    FallbackMode, LookupInput, OutputFormat, OutputOptions, RunType,
+};
 
-fn main() {
-    let arguments = arguments::get_config(Arguments::parse());
+mod bench;
+
+mod completions;
+
+mod convert;
+
+mod diff;
+
+mod distance;
+use distance::{Distance, Endpoint};
+
+mod logging;
+
+mod logsum;
+
+mod manpage;
+
+mod output;
+use output::{CountryFilter, LookupResult, ResultWriter};
+
+mod pcap;
+
+mod rdap;
+use rdap::Rdap;
+
+mod repl;
+use repl::{History, Line};
+
+mod resolve;
+use resolve::Resolver;
+
+mod stats;
+
+mod update;
+
+mod verify;
+
+mod watch;
+use watch::Watcher;
+
+fn main() -> ExitCode {
+    let arguments = Arguments::parse();
+    logging::init(
+        logging::level(arguments.verbose, arguments.quiet),
+        arguments.log_format,
+    );
+    let arguments = arguments::get_config(arguments);
+
+    if let Some(conflict) = stdin_conflict(&arguments) {
+        tracing::error!("{conflict}");
+        return ExitCode::from(EXIT_USAGE);
+    }
 
     match arguments::get_run_type(&arguments) {
-        RunType::Ipv4 => print_country(find_ipv4(arguments)),
-        RunType::Ipv6 => print_country(find_ipv6(arguments)),
-        RunType::None => todo!("Trigger help message"),
+        RunType::Lookup => {
+            let Some(Command::Lookup {
+                ref address,
+                watch,
+                rdap_fallback,
+                ref rdap_url,
+                template,
+                null,
+                output,
+                ..
+            }) = arguments.command
+            else {
+                unreachable!("`RunType::Lookup` requires `Command::Lookup`");
+            };
+            let output = OutputOptions {
+                template,
+                null,
+                ..output
+            };
+            match address {
+                &Some(LookupInput::Address(address)) => {
+                    let rdap =
+                        rdap_fallback.then(|| Rdap::new(rdap_url, arguments.lang.as_deref()));
+                    let country = find_country(arguments, address);
+
+                    return match rdap {
+                        Some(rdap) if Rdap::should_ask(&country) => {
+                            print_rdap_country(address, rdap.country(address), output)
+                        }
+                        _ => print_country(address, country, output),
+                    };
+                }
+                _ if watch => return watch_lookups(arguments),
+                Some(LookupInput::Host(_)) | Some(LookupInput::Stdin) | None => {
+                    return lookup_all(arguments)
+                }
+            }
+        }
+        RunType::Ranges => return print_ranges(arguments),
+        RunType::Distance => return print_distance(arguments),
+        RunType::Bench => return print_bench(arguments),
+        RunType::Repl => return run_repl(arguments),
+        RunType::Pcap => return print_pcap(arguments),
+        RunType::Logsum => return print_logsum(arguments),
+        RunType::Serve => return run_server(arguments),
+        RunType::DbDownload => return update::download_databases(arguments),
+        RunType::DbUpdate => return update::update_databases(arguments),
+        RunType::DbExport => return export_database(arguments),
+        RunType::DbManifest => return write_manifests(arguments),
+        RunType::DbVerify => return verify::verify_databases(arguments),
+        RunType::DbConvert => return convert::convert_database(arguments),
+        RunType::DbStats => return stats::print_stats(arguments),
+        RunType::DbDiff => return diff::print_diff(arguments),
+        RunType::ConfigShow => print!("{arguments}"),
+        RunType::ConfigValidate => return validate_config(arguments),
+        RunType::ConfigInit => return write_default_config(arguments),
+        RunType::Completions => {
+            let Some(Command::Completions { shell }) = arguments.command else {
+                unreachable!("`RunType::Completions` requires `Command::Completions`");
+            };
+            print!("{}", completions::generate(shell, Arguments::command()));
+        }
+        RunType::Manpage => print!("{}", manpage::generate(Arguments::command())),
+        RunType::None => {
+            let _ = Arguments::command().print_help();
+            return ExitCode::from(EXIT_USAGE);
+        }
     }
+
+    ExitCode::SUCCESS
 }
 
-/// For a given `Country`, print ISO 3166-1 alpha-2 code and a country name (ex. `BE Belgium`).
-fn print_country(country: Result<Country, Error>) {
+/// The exit code of lookups that found no country, or only the unknown country (`??`).
+const EXIT_NOT_FOUND: u8 = 1;
+
+/// The exit code of invalid arguments or configuration, matching the usage errors reported by
+/// `clap`.
+const EXIT_USAGE: u8 = 2;
+
+/// The exit code of commands that failed to read, write, download, or verify a database or
+/// another file.
+const EXIT_DATABASE_ERROR: u8 = 3;
+
+/// For a given `Country`, print ISO 3166-1 alpha-2 code and a country name (ex. `BE Belgium`), or
+/// describe it and `address` in the format of `output`.
+///
+/// Returns `EXIT_NOT_FOUND` if no country (or only the unknown country) was found, or
+/// `EXIT_DATABASE_ERROR` if the databases could not be read.
+fn print_country(
+    address: IpAddr,
+    country: Result<Country, Error>,
+    output: OutputOptions,
+) -> ExitCode {
+    let code = match &country {
+        Ok(country) if country.code.as_ref() != UNKNOWN_COUNTRY_CODE => ExitCode::SUCCESS,
+        Ok(_) | Err(Error::NoValueFound) => ExitCode::from(EXIT_NOT_FOUND),
+        Err(_) => ExitCode::from(EXIT_DATABASE_ERROR),
+    };
+
+    if output.format != OutputFormat::Text || output.template.is_some() {
+        let address = address.to_string();
+        return match write_result(LookupResult::new(&address, country), output) {
+            Ok(()) => code,
+            Err(error) => {
+                tracing::error!("Failed to print the result: {error}");
+                ExitCode::from(EXIT_DATABASE_ERROR)
+            }
+        };
+    }
+
     match country {
-        Ok(country) => println!("{} {}", country.code, country.name),
+        Ok(country) => {
+            let mut stdout = io::stdout().lock();
+            if output::write_country(&mut stdout, &country, output)
+                .and_then(|()| writeln!(stdout))
+                .is_err()
+            {
+                return ExitCode::from(EXIT_DATABASE_ERROR);
+            }
+        }
         Err(error) => match error {
             Error::NoValueFound => println!("No country found!"),
-            _ => eprintln!("{error}"),
+            // Databases that could not be read were reported as they were read
+            Error::Io(_) => (),
+            _ => tracing::error!("{error}"),
         },
     }
+
+    code
+}
+
+/// For the country RDAP reports for `address` (after the databases could not place it), print it
+/// like [`print_country`] followed by `(from RDAP)`, or describe it and `address` in the format of
+/// `output`.
+///
+/// Returns `EXIT_NOT_FOUND` if no country was found.
+fn print_rdap_country(
+    address: IpAddr,
+    country: Result<Option<Country>, String>,
+    output: OutputOptions,
+) -> ExitCode {
+    let code = match country {
+        Ok(Some(_)) => ExitCode::SUCCESS,
+        _ => ExitCode::from(EXIT_NOT_FOUND),
+    };
+
+    if let (OutputFormat::Text, None, Err(error)) = (output.format, output.template, &country) {
+        tracing::error!("{error}");
+        return code;
+    }
+
+    let address = address.to_string();
+    match write_result(LookupResult::rdap(&address, country), output) {
+        Ok(()) => code,
+        Err(error) => {
+            tracing::error!("Failed to print the result: {error}");
+            ExitCode::from(EXIT_DATABASE_ERROR)
+        }
+    }
+}
+
+/// Write a single `result` to stdout in the format of `output`.
+fn write_result(result: LookupResult, output: OutputOptions) -> io::Result<()> {
+    let mut rendered = Vec::new();
+    result.render(&mut rendered, output)?;
+
+    let mut writer = ResultWriter::new(io::stdout().lock(), output)?;
+    writer.write_rendered(&rendered)?;
+    writer.finish()
 }
 
-/// For a given IPv4 address (contained in `arguments`), find the country it is associated with.
-fn find_ipv4(arguments: Arguments) -> Result<Country, Error> {
-    let mut ipv4_map = ip_geo::ipv4::parse_ipv4_file(
+/// Look up every newline-separated address or hostname read from stdin or a file (or the single
+/// hostname given on the command line, specified in `arguments`) in the databases (specified in
+/// `arguments`), printing one result per line in the same order.
+///
+/// Returns `EXIT_NOT_FOUND` if any line did not hold a valid address (or for a single hostname,
+/// if any of its addresses has no country), or `EXIT_DATABASE_ERROR` if the databases could not
+/// be read.
+fn lookup_all(arguments: Arguments) -> ExitCode {
+    let Some(Command::Lookup {
+        address,
+        input,
+        null,
+        watch: _,
+        threads,
+        resolver,
+        all,
+        rdap_fallback,
+        rdap_url,
+        template,
+        countries,
+        excluded_countries,
+        output,
+    }) = arguments.command.clone()
+    else {
+        unreachable!("`RunType::Lookup` requires `Command::Lookup`");
+    };
+    let language = arguments.lang.clone();
+    let output = OutputOptions {
+        template,
+        null,
+        ..output
+    };
+
+    let single_host = matches!(address, Some(LookupInput::Host(_)));
+    let reader: Box<dyn BufRead> = match (address, input) {
+        (Some(LookupInput::Host(host)), _) => Box::new(io::Cursor::new(host.into_string())),
+        (_, Some(path)) => match fs::File::open(&path) {
+            Ok(file) => Box::new(io::BufReader::new(file)),
+            Err(error) => {
+                tracing::error!("Failed to open {}: {error}", path.display());
+                return ExitCode::from(EXIT_DATABASE_ERROR);
+            }
+        },
+        _ => Box::new(io::stdin().lock()),
+    };
+    let threads = threads
+        .or_else(|| thread::available_parallelism().ok())
+        .unwrap_or(NonZeroUsize::MIN);
+
+    let filter = CountryFilter {
+        include: countries,
+        exclude: excluded_countries,
+    };
+
+    let database = match get_fallback(&arguments)
+        .and_then(|fallback| Ok(load_database(arguments)?.with_fallback(fallback)))
+    {
+        Ok(database) => database,
+        Err(_) => return ExitCode::from(EXIT_DATABASE_ERROR),
+    };
+
+    match lookup_lines(
+        &database,
+        reader,
+        BufWriter::new(io::stdout().lock()),
+        threads,
+        output,
+        &filter,
+        &Sources {
+            resolver: Resolver {
+                server: resolver,
+                all,
+            },
+            rdap: rdap_fallback.then(|| Rdap::new(&rdap_url, language.as_deref())),
+        },
+    ) {
+        Ok(Outcome::Found) => ExitCode::SUCCESS,
+        Ok(Outcome::NotFound) if !single_host => ExitCode::SUCCESS,
+        Ok(_) => ExitCode::from(EXIT_NOT_FOUND),
+        Err(error) => {
+            tracing::error!("Failed to look up addresses: {error}");
+            ExitCode::from(EXIT_DATABASE_ERROR)
+        }
+    }
+}
+
+/// Look up every address in `--input` (specified in `arguments`) like [`lookup_all`], then again
+/// whenever it or the databases change, until interrupted.
+///
+/// When stdout is a terminal, the screen is cleared before each refresh.
+fn watch_lookups(arguments: Arguments) -> ExitCode {
+    let Some(Command::Lookup {
+        input: Some(input), ..
+    }) = arguments.command.clone()
+    else {
+        unreachable!("`--watch` requires `--input`");
+    };
+
+    let mut paths = vec![input];
+    paths.extend(
+        [
+            &arguments.ipv4_path,
+            &arguments.ipv6_path,
+            &arguments.fallback_ipv4_path,
+            &arguments.fallback_ipv6_path,
+        ]
+        .into_iter()
+        .flatten()
+        .cloned(),
+    );
+    let mut watcher = Watcher::new(paths);
+    let clear = io::stdout().is_terminal();
+
+    loop {
+        if clear {
+            let mut stdout = io::stdout().lock();
+            let _ = write!(stdout, "\x1b[2J\x1b[H").and_then(|()| stdout.flush());
+        }
+
+        lookup_all(arguments.clone());
+        tracing::info!(
+            "Watching {} files for changes, press Ctrl-C to stop",
+            watcher.paths().len()
+        );
+        watcher.wait();
+    }
+}
+
+/// How lookups went, from best to worst, so that the outcome of several lookups is the worst of
+/// theirs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Outcome {
+    /// Every address was found in a country.
+    Found,
+    /// Some address had no country, or only the unknown country (`??`).
+    NotFound,
+    /// Some input was not a valid address, or a hostname that could be resolved.
+    Invalid,
+}
+
+impl Outcome {
+    /// Return the outcome of looking up `result`.
+    fn of(result: &LookupResult) -> Self {
+        match (result.is_valid(), result.is_found()) {
+            (false, _) => Self::Invalid,
+            (true, false) => Self::NotFound,
+            (true, true) => Self::Found,
+        }
+    }
+}
+
+/// Where [`lookup_lines`] finds what the databases cannot: the addresses of hostnames, and
+/// optionally the countries of addresses that the databases cannot place.
+#[derive(Debug, Default)]
+struct Sources {
+    resolver: Resolver,
+    rdap: Option<Rdap>,
+}
+
+/// The number of lines each worker thread of [`lookup_lines`] looks up at a time.
+const LOOKUP_CHUNK_LINES: usize = 16 * 1024;
+
+/// For every line of `reader` (or with `--null`, every NUL-separated record), write the country
+/// associated with the address on it (or the addresses the hostname on it resolves to) to `writer`
+/// in the format of `output`, skipping blank lines and results that `filter` does not allow, with
+/// the help of `sources`.
+///
+/// Lines are read in batches, which are split between `threads` worker threads and written in
+/// their original order.
+///
+/// Returns the worst outcome of any line.
+fn lookup_lines(
+    database: &GeoDatabase,
+    reader: impl BufRead,
+    writer: impl Write,
+    threads: NonZeroUsize,
+    output: OutputOptions,
+    filter: &CountryFilter,
+    sources: &Sources,
+) -> io::Result<Outcome> {
+    let mut writer = ResultWriter::new(writer, output)?;
+    let separator = if output.null { b'\0' } else { b'\n' };
+    let mut lines = reader.split(separator).map(|line| {
+        String::from_utf8(line?).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    });
+    let mut outcome = Outcome::Found;
+
+    loop {
+        let batch = lines
+            .by_ref()
+            .take(threads.get() * LOOKUP_CHUNK_LINES)
+            .collect::<io::Result<Vec<String>>>()?;
+        if batch.is_empty() {
+            break;
+        }
+
+        let outputs = thread::scope(|scope| {
+            let workers: Vec<_> = batch
+                .chunks(LOOKUP_CHUNK_LINES)
+                .map(|chunk| scope.spawn(|| lookup_chunk(database, chunk, output, filter, sources)))
+                .collect();
+
+            workers
+                .into_iter()
+                .map(|worker| worker.join().expect("Lookup worker threads do not panic"))
+                .collect::<io::Result<Vec<_>>>()
+        })?;
+
+        for (rendered, chunk_outcome) in outputs {
+            writer.write_rendered(&rendered)?;
+            outcome = outcome.max(chunk_outcome);
+        }
+    }
+
+    writer.finish()?;
+    Ok(outcome)
+}
+
+/// For every line of `lines`, return the country associated with the address on it (or the
+/// addresses the hostname on it resolves to) rendered in the format of `output` (if `filter`
+/// allows it) with the help of `sources`, and the worst outcome of any line.
+fn lookup_chunk(
+    database: &GeoDatabase,
+    lines: &[String],
+    output: OutputOptions,
+    filter: &CountryFilter,
+    sources: &Sources,
+) -> io::Result<(Vec<u8>, Outcome)> {
+    let mut rendered = Vec::new();
+    let mut outcome = Outcome::Found;
+
+    let mut write = |result: LookupResult| -> io::Result<()> {
+        if filter.allows(&result) {
+            result.render(&mut rendered, output)?;
+        }
+        outcome = outcome.max(Outcome::of(&result));
+        Ok(())
+    };
+
+    for line in lines {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Ok(address) = line.parse::<IpAddr>() {
+            write(locate(database, sources, line, address))?;
+            continue;
+        }
+        if !resolve::is_hostname(line) {
+            write(LookupResult::invalid(line))?;
+            continue;
+        }
+
+        match sources.resolver.resolve(line) {
+            Ok(addresses) => {
+                for address in addresses {
+                    let ip = address.to_string();
+                    write(locate(database, sources, &ip, address).with_host(line))?;
+                }
+            }
+            Err(error) => write(LookupResult::unresolved(line, &error))?,
+        }
+    }
+
+    Ok((rendered, outcome))
+}
+
+/// Look up `ip` (parsed as `address`) in `database`, asking RDAP (if `sources` has it) for
+/// addresses that the databases cannot place.
+fn locate<'a>(
+    database: &GeoDatabase,
+    sources: &Sources,
+    ip: &'a str,
+    address: IpAddr,
+) -> LookupResult<'a> {
+    let country = database.lookup(address).cloned();
+
+    match &sources.rdap {
+        Some(rdap) if Rdap::should_ask(&country) => LookupResult::rdap(ip, rdap.country(address)),
+        _ => LookupResult::new(ip, country),
+    }
+}
+
+/// For a given IP address, find the country it is associated with in the databases (specified in
+/// `arguments`).
+fn find_country(arguments: Arguments, address: IpAddr) -> Result<Country, Error> {
+    match address {
+        IpAddr::V4(address) => find_ipv4(arguments, address),
+        IpAddr::V6(address) => find_ipv6(arguments, address),
+    }
+}
+
+/// For a given IPv4 address, find the country it is associated with in the IPv4 database
+/// (specified in `arguments`).
+fn find_ipv4(arguments: Arguments, input_addr: Ipv4Addr) -> Result<Country, Error> {
+    let mut ipv4_map = read_ipv4(
         arguments
             .ipv4_path
+            .clone()
             .expect("A valid path to an IPv4 GeoIP database"),
         arguments
             .ipv4_len
             .expect("The number of lines in the IPv4 GeoIP database"),
         arguments.ipv4_comment,
-    );
+        arguments.lang.as_deref(),
+    )?;
+
+    match ipv4_map.search(input_addr) {
+        Err(Error::NoValueFound) => fall_back(&arguments, input_addr.into()),
+        result => result.cloned(),
+    }
+}
+
+/// For a given IPv6 address, find the country it is associated with in the IPv6 database
+/// (specified in `arguments`).
+fn find_ipv6(arguments: Arguments, input_addr: Ipv6Addr) -> Result<Country, Error> {
+    let mut ipv6_map = read_ipv6(
+        arguments
+            .ipv6_path
+            .clone()
+            .expect("A valid path to an IPv6 GeoIP database"),
+        arguments
+            .ipv6_len
+            .expect("The number of lines in the IPv6 GeoIP database"),
+        arguments.ipv6_comment,
+        arguments.lang.as_deref(),
+    )?;
+
+    match ipv6_map.search(input_addr) {
+        Err(Error::NoValueFound) => fall_back(&arguments, input_addr.into()),
+        result => result.cloned(),
+    }
+}
+
+/// For an address with no associated country, apply the fallback specified in `arguments`.
+fn fall_back(arguments: &Arguments, address: IpAddr) -> Result<Country, Error> {
+    match arguments.fallback.unwrap_or(FallbackMode::None) {
+        FallbackMode::None => Err(Error::NoValueFound),
+        FallbackMode::Unknown => Ok(Country::unknown()),
+        FallbackMode::Database => match address {
+            IpAddr::V4(address) => {
+                let path = arguments
+                    .fallback_ipv4_path
+                    .clone()
+                    .expect("A valid path to a fallback IPv4 GeoIP database");
+                let len = arguments.ipv4_len.unwrap_or_default();
+
+                read_ipv4(path, len, arguments.ipv4_comment, arguments.lang.as_deref())?
+                    .search(address)
+                    .cloned()
+            }
+            IpAddr::V6(address) => {
+                let path = arguments
+                    .fallback_ipv6_path
+                    .clone()
+                    .expect("A valid path to a fallback IPv6 GeoIP database");
+                let len = arguments.ipv6_len.unwrap_or_default();
+
+                read_ipv6(path, len, arguments.ipv6_comment, arguments.lang.as_deref())?
+                    .search(address)
+                    .cloned()
+            }
+        },
+    }
+}
+
+/// Return the fallback specified in `arguments`, parsing the fallback databases if it needs them.
+fn get_fallback(arguments: &Arguments) -> Result<Fallback, Error> {
+    let fallback = match arguments.fallback.unwrap_or(FallbackMode::None) {
+        FallbackMode::None => Fallback::NoValue,
+        FallbackMode::Unknown => Fallback::unknown(),
+        FallbackMode::Database => {
+            let ipv4_map = arguments
+                .fallback_ipv4_path
+                .clone()
+                .map(|path| {
+                    let len = arguments.ipv4_len.unwrap_or_default();
+                    read_ipv4(path, len, arguments.ipv4_comment, arguments.lang.as_deref())
+                })
+                .transpose()?
+                .unwrap_or_default();
+            let ipv6_map = arguments
+                .fallback_ipv6_path
+                .clone()
+                .map(|path| {
+                    let len = arguments.ipv6_len.unwrap_or_default();
+                    read_ipv6(path, len, arguments.ipv6_comment, arguments.lang.as_deref())
+                })
+                .transpose()?
+                .unwrap_or_default();
+
+            Fallback::Database(Box::new(GeoDatabase::new(ipv4_map, ipv6_map)))
+        }
+    };
 
-    let input_addr = arguments.ipv4_addr.expect("A valid IPv4 Address");
+    Ok(fallback)
+}
+
+/// Parse both the IPv4 and IPv6 databases (specified in `arguments`) into a `GeoDatabase`.
+fn load_database(arguments: Arguments) -> Result<GeoDatabase, Error> {
+    let (ipv4_map, ipv6_map) = load_maps(arguments)?;
 
-    ipv4_map.search(input_addr).cloned()
+    Ok(GeoDatabase::new(ipv4_map, ipv6_map))
 }
 
-/// For a given IPv6 address (contained in `arguments`), find the country it is associated with.
-fn find_ipv6(arguments: Arguments) -> Result<Country, Error> {
-    let mut ipv6_map = ip_geo::ipv6::parse_ipv6_file(
+/// The parsed IPv4 and IPv6 databases.
+type Maps = (IpAddrMap<Ipv4Addr, Country>, IpAddrMap<Ipv6Addr, Country>);
+
+/// Parse both the IPv4 and IPv6 databases (specified in `arguments`).
+fn load_maps(arguments: Arguments) -> Result<Maps, Error> {
+    let ipv4_map = read_ipv4(
+        arguments
+            .ipv4_path
+            .expect("A valid path to an IPv4 GeoIP database"),
+        arguments
+            .ipv4_len
+            .expect("The number of lines in the IPv4 GeoIP database"),
+        arguments.ipv4_comment,
+        arguments.lang.as_deref(),
+    )?;
+
+    let ipv6_map = read_ipv6(
         arguments
             .ipv6_path
             .expect("A valid path to an IPv6 GeoIP database"),
@@ -72,11 +703,863 @@ fn find_ipv6(arguments: Arguments) -> Result<Country, Error> {
             .ipv6_len
             .expect("The number of lines in the IPv6 GeoIP database"),
         arguments.ipv6_comment,
-    );
+        arguments.lang.as_deref(),
+    )?;
+
+    Ok((ipv4_map, ipv6_map))
+}
+
+/// Describe `error` followed by the error that caused it, if any, ex. `failed to read database: No
+/// such file or directory (os error 2)`.
+fn describe(error: &Error) -> String {
+    match std::error::Error::source(error) {
+        Some(source) => format!("{error}: {source}"),
+        None => error.to_string(),
+    }
+}
+
+/// Parse the IPv4 database at `path` (or stdin, if it is `-`), warning about the malformed lines
+/// it skips, or report that it could not be read.
+fn read_ipv4(
+    path: Box<Path>,
+    len: usize,
+    comment: Option<char>,
+    language: Option<&str>,
+) -> Result<IpAddrMap<Ipv4Addr, Country>, Error> {
+    let (mut map, report) = open_database(&path)
+        .map_err(Error::from)
+        .and_then(|reader| {
+            ip_geo::ipv4::parse_ipv4_with_mode(reader, len, comment, ParseMode::Lenient)
+        })
+        .inspect_err(|error| {
+            tracing::error!(
+                "Could not read the IPv4 database {}: {}",
+                path.display(),
+                describe(error)
+            );
+        })?;
+
+    for malformed in report.malformed {
+        tracing::warn!("Skipped malformed IPv4 database entry on {malformed}");
+    }
+    tracing::debug!("Loaded {} entries from {}", map.len(), path.display());
+
+    if let Some(language) = language {
+        map.localize(language);
+    }
+
+    Ok(map)
+}
+
+/// Parse the IPv6 database at `path` (or stdin, if it is `-`), warning about the malformed lines
+/// it skips, or report that it could not be read.
+fn read_ipv6(
+    path: Box<Path>,
+    len: usize,
+    comment: Option<char>,
+    language: Option<&str>,
+) -> Result<IpAddrMap<Ipv6Addr, Country>, Error> {
+    let (mut map, report) = open_database(&path)
+        .map_err(Error::from)
+        .and_then(|reader| {
+            ip_geo::ipv6::parse_ipv6_with_mode(reader, len, comment, ParseMode::Lenient)
+        })
+        .inspect_err(|error| {
+            tracing::error!(
+                "Could not read the IPv6 database {}: {}",
+                path.display(),
+                describe(error)
+            );
+        })?;
+
+    for malformed in report.malformed {
+        tracing::warn!("Skipped malformed IPv6 database entry on {malformed}");
+    }
+    tracing::debug!("Loaded {} entries from {}", map.len(), path.display());
+
+    if let Some(language) = language {
+        map.localize(language);
+    }
+
+    Ok(map)
+}
+
+/// The path of a database that is read from stdin instead of a file.
+const STDIN_PATH: &str = "-";
+
+/// Return whether `path` is [`STDIN_PATH`].
+fn is_stdin(path: &Path) -> bool {
+    path.as_os_str() == STDIN_PATH
+}
+
+/// Return the contents of stdin, reading them the first time, so that a database read from stdin
+/// can be read more than once (ex. by `db stats`, to detect its format before parsing it).
+fn stdin_contents() -> io::Result<&'static [u8]> {
+    static CONTENTS: OnceLock<Box<[u8]>> = OnceLock::new();
+
+    if let Some(contents) = CONTENTS.get() {
+        return Ok(contents);
+    }
+
+    let mut contents = Vec::new();
+    io::stdin().lock().read_to_end(&mut contents)?;
+    Ok(CONTENTS.get_or_init(|| contents.into()))
+}
+
+/// Open the database at `path`, or the contents of stdin if it is `-`.
+fn open_database(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    match is_stdin(path) {
+        true => Ok(Box::new(stdin_contents()?)),
+        false => Ok(Box::new(io::BufReader::new(fs::File::open(path)?))),
+    }
+}
+
+/// Return why the command (specified in `arguments`) cannot run with a database read from stdin,
+/// if any of its databases is `-` and it cannot.
+fn stdin_conflict(arguments: &Arguments) -> Option<&'static str> {
+    let from_stdin = [
+        &arguments.ipv4_path,
+        &arguments.ipv6_path,
+        &arguments.fallback_ipv4_path,
+        &arguments.fallback_ipv6_path,
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|path| is_stdin(path))
+    .count();
+
+    if from_stdin == 0 {
+        return None;
+    }
+    if from_stdin > 1 {
+        return Some("Only one database can be read from stdin");
+    }
+
+    match &arguments.command {
+        Some(Command::Lookup { watch: true, .. }) => {
+            Some("`--watch` cannot read a database from stdin again")
+        }
+        Some(Command::Lookup {
+            address: None | Some(LookupInput::Stdin),
+            input: None,
+            ..
+        })
+        | Some(Command::Repl { .. }) => {
+            Some("Addresses cannot be read from stdin along with a database")
+        }
+        Some(Command::Pcap { file, .. }) if is_stdin(file) => {
+            Some("A capture cannot be read from stdin along with a database")
+        }
+        Some(Command::Logsum { file, .. }) if is_stdin(file) => {
+            Some("A log cannot be read from stdin along with a database")
+        }
+        Some(Command::Db(
+            DbCommand::Download { .. }
+            | DbCommand::Update { .. }
+            | DbCommand::Manifest { .. }
+            | DbCommand::Verify,
+        )) => Some("This command requires database files, not stdin"),
+        _ => None,
+    }
+}
+
+/// Write the databases (specified in `arguments`) to stdout in the requested format.
+fn export_database(arguments: Arguments) -> ExitCode {
+    let Some(Command::Db(DbCommand::Export {
+        format,
+        countries,
+        table,
+        set_name,
+        family,
+        policy,
+    })) = arguments.command.clone()
+    else {
+        unreachable!("`RunType::DbExport` requires `DbCommand::Export`");
+    };
+
+    let family = match family {
+        ExportFamily::Inet => NftablesFamily::Inet,
+        ExportFamily::Ip => NftablesFamily::Ip,
+        ExportFamily::Ip6 => NftablesFamily::Ip6,
+    };
+    let policy = policy.map(|policy| match policy {
+        ExportPolicy::Block => NftablesPolicy::Block,
+        ExportPolicy::Allow => NftablesPolicy::Allow,
+    });
+
+    let Ok(database) = load_database(arguments) else {
+        return ExitCode::from(EXIT_DATABASE_ERROR);
+    };
+    let stdout = BufWriter::new(io::stdout().lock());
+    let codes: Vec<&str> = countries.iter().map(AsRef::as_ref).collect();
+
+    let result = match format {
+        ExportFormat::Haproxy => database.export_haproxy_map(stdout),
+        ExportFormat::Nftables => match policy {
+            Some(policy) => {
+                database.export_nftables_ruleset(stdout, &codes, family, &table, &set_name, policy)
+            }
+            None => database.export_nftables_set(stdout, &codes, family, &table, &set_name),
+        },
+        ExportFormat::Ipset => database.export_ipset(stdout, &codes, &set_name),
+    };
+
+    if let Err(error) = result {
+        tracing::error!("Failed to export database: {error}");
+        return ExitCode::from(EXIT_DATABASE_ERROR);
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Return the path and comment character of the IPv4 and IPv6 databases (specified in
+/// `arguments`), labelled for printing.
+fn database_paths(arguments: &Arguments) -> [(&'static str, &Path, Option<char>); 2] {
+    [
+        (
+            "IPv4",
+            arguments
+                .ipv4_path
+                .as_deref()
+                .expect("A valid path to an IPv4 GeoIP database"),
+            arguments.ipv4_comment,
+        ),
+        (
+            "IPv6",
+            arguments
+                .ipv6_path
+                .as_deref()
+                .expect("A valid path to an IPv6 GeoIP database"),
+            arguments.ipv6_comment,
+        ),
+    ]
+}
+
+/// Write a manifest next to each of the databases (specified in `arguments`).
+fn write_manifests(arguments: Arguments) -> ExitCode {
+    let Some(Command::Db(DbCommand::Manifest { source })) = &arguments.command else {
+        unreachable!("`RunType::DbManifest` requires `DbCommand::Manifest`");
+    };
+
+    let mut code = ExitCode::SUCCESS;
+
+    for (label, path, comment) in database_paths(&arguments) {
+        let result = Manifest::generate(path, comment, source.as_ref())
+            .and_then(|manifest| manifest.write(path).map(|_| manifest));
+
+        match result {
+            Ok(manifest) => tracing::info!(
+                "{label}: wrote {} ({} entries)",
+                Manifest::path_for(path).display(),
+                manifest.entries
+            ),
+            Err(error) => {
+                tracing::error!("{label}: failed to write manifest: {error}");
+                code = ExitCode::from(EXIT_DATABASE_ERROR);
+            }
+        }
+    }
+
+    code
+}
+
+/// Return the first field of `line`, the start of its range, or `None` if `line` is blank or a
+/// comment.
+fn entry_start(line: &str, comment: Option<char>) -> Option<&str> {
+    let line = line.trim();
+    if line.is_empty() || comment.is_some_and(|comment| line.starts_with(comment)) {
+        return None;
+    }
+
+    line.split(',').next().map(str::trim)
+}
+
+/// Return the first field of the first entry of the database at `path`, or `None` if it has no
+/// entries.
+///
+/// Lines that are not UTF-8, ex. in snapshots, are read lossily.
+fn first_entry_start(path: &Path, comment: Option<char>) -> io::Result<Option<String>> {
+    let mut reader = open_database(path)?;
+    let mut line = Vec::new();
+
+    while reader.read_until(b'\n', &mut line)? != 0 {
+        if let Some(start) = entry_start(&String::from_utf8_lossy(&line), comment) {
+            return Ok(Some(start.to_owned()));
+        }
+        line.clear();
+    }
+
+    Ok(None)
+}
+
+/// Check the configuration file (specified in `arguments`) for problems, printing each of them.
+fn validate_config(arguments: Arguments) -> ExitCode {
+    let path = arguments
+        .config_path
+        .expect("A valid path to a configuration file");
+    let problems = arguments::validate_config(&path);
+
+    if problems.is_empty() {
+        tracing::info!("{}: OK", path.display());
+        return ExitCode::SUCCESS;
+    }
+
+    for problem in problems {
+        tracing::error!("{}: {problem}", path.display());
+    }
+    ExitCode::from(EXIT_USAGE)
+}
+
+/// Write a default configuration file to the path specified in `arguments`, or else to the
+/// configuration file the program reads.
+fn write_default_config(arguments: Arguments) -> ExitCode {
+    let Some(Command::Config {
+        command: Some(ConfigCommand::Init { path, force }),
+    }) = arguments.command
+    else {
+        unreachable!("`RunType::ConfigInit` requires `ConfigCommand::Init`");
+    };
+
+    let path = path
+        .or(arguments.config_path)
+        .expect("A valid path to a configuration file");
+    if path.exists() && !force {
+        tracing::error!(
+            "{} already exists, pass `--force` to overwrite it",
+            path.display()
+        );
+        return ExitCode::from(EXIT_USAGE);
+    }
+
+    let result = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => fs::create_dir_all(parent),
+        _ => Ok(()),
+    }
+    .and_then(|()| fs::write(&path, arguments::default_config()));
+
+    match result {
+        Ok(()) => {
+            tracing::info!("Wrote {}", path.display());
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            tracing::error!("Failed to write {}: {error}", path.display());
+            ExitCode::from(EXIT_DATABASE_ERROR)
+        }
+    }
+}
+
+/// Print every range assigned to the countries (specified in `arguments`), as CIDR blocks or as
+/// `<start>-<end>` ranges.
+fn print_ranges(arguments: Arguments) -> ExitCode {
+    let Some(Command::Ranges {
+        countries,
+        raw,
+        ipv4_only,
+        ipv6_only,
+    }) = arguments.command.clone()
+    else {
+        unreachable!("`RunType::Ranges` requires `Command::Ranges`");
+    };
+
+    let codes: Vec<&str> = countries.iter().map(AsRef::as_ref).collect();
+    let unknown = unknown_countries(&codes);
+    if !unknown.is_empty() {
+        tracing::error!("Unknown country codes: {}", unknown.join(", "));
+        return ExitCode::from(EXIT_USAGE);
+    }
+
+    let Ok(database) = load_database(arguments) else {
+        return ExitCode::from(EXIT_DATABASE_ERROR);
+    };
+
+    let families = (!ipv6_only, !ipv4_only);
+    if let Err(error) = print_country_ranges(&database, &codes, families, raw) {
+        tracing::error!("Failed to print ranges: {error}");
+        return ExitCode::from(EXIT_DATABASE_ERROR);
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Return the codes of `codes` that are not known country codes, ignoring case.
+fn unknown_countries<'a>(codes: &[&'a str]) -> Vec<&'a str> {
+    let known = ip_geo::country_list::get_countries();
+
+    codes
+        .iter()
+        .copied()
+        .filter(|code| !known.contains_key(code.to_ascii_uppercase().as_str()))
+        .collect()
+}
+
+/// Print every range in `database` assigned to any of `codes` to stdout, like [`write_ranges`],
+/// for IPv4 and IPv6 as selected by `(ipv4, ipv6)`.
+fn print_country_ranges(
+    database: &GeoDatabase,
+    codes: &[&str],
+    (ipv4, ipv6): (bool, bool),
+    raw: bool,
+) -> io::Result<()> {
+    let (ipv4_ranges, ipv6_ranges) = database.ranges_for_countries(codes);
 
-    let input_addr = arguments.ipv6_addr.expect("A valid IPv6 Address");
+    let mut stdout = BufWriter::new(io::stdout().lock());
+    if ipv4 {
+        write_ranges(&mut stdout, &ipv4_ranges, raw)?;
+    }
+    if ipv6 {
+        write_ranges(&mut stdout, &ipv6_ranges, raw)?;
+    }
+
+    stdout.flush()
+}
+
+/// Write each of `ranges` to `writer` on its own line, as `<start>-<end>` if `raw`, or else as
+/// CIDR blocks.
+fn write_ranges<A: Address>(
+    writer: &mut impl Write,
+    ranges: &[(A, A)],
+    raw: bool,
+) -> io::Result<()> {
+    for &(start, end) in ranges {
+        if raw {
+            writeln!(writer, "{start}-{end}")?;
+            continue;
+        }
+
+        for cidr in ip_geo::cidr::range_to_cidrs(start, end) {
+            writeln!(writer, "{cidr}")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Benchmark looking addresses up in the databases (specified in `arguments`) with each of the
+/// requested backends, printing the report.
+fn print_bench(arguments: Arguments) -> ExitCode {
+    let Some(Command::Bench {
+        input,
+        count,
+        seed,
+        backends,
+        output,
+    }) = arguments.command.clone()
+    else {
+        unreachable!("`RunType::Bench` requires `Command::Bench`");
+    };
+
+    let Ok((mut ipv4, mut ipv6)) = load_maps(arguments) else {
+        return ExitCode::from(EXIT_DATABASE_ERROR);
+    };
+    ipv4.cleanup();
+    ipv6.cleanup();
+
+    let addresses = match input {
+        Some(path) => match read_addresses(&path) {
+            Ok(addresses) => addresses,
+            Err(error) => {
+                tracing::error!("Failed to read {}: {error}", path.display());
+                return ExitCode::from(EXIT_DATABASE_ERROR);
+            }
+        },
+        None => bench::generate(&ipv4, &ipv6, count, seed),
+    };
+    if addresses.is_empty() {
+        tracing::error!("No addresses to look up");
+        return ExitCode::from(EXIT_USAGE);
+    }
+
+    let report = bench::run(&ipv4, &ipv6, &addresses, &backends);
+
+    let result = match output.format {
+        OutputFormat::Text => {
+            print!("{report}");
+            Ok(())
+        }
+        OutputFormat::Json => output::write_json(io::stdout().lock(), &report, output.pretty),
+        OutputFormat::Csv | OutputFormat::Tsv => report.write_records(
+            io::stdout().lock(),
+            output
+                .format
+                .delimiter()
+                .expect("CSV and TSV have delimiters"),
+        ),
+        OutputFormat::Table => {
+            output::write_table(io::stdout().lock(), report.columns(), &report.rows())
+        }
+    };
+    if let Err(error) = result {
+        tracing::error!("Failed to print the benchmark: {error}");
+        return ExitCode::from(EXIT_DATABASE_ERROR);
+    }
 
-    ipv6_map.search(input_addr).cloned()
+    ExitCode::SUCCESS
+}
+
+/// Print the distance between the countries of two addresses (specified in `arguments`) in the
+/// format of `output`.
+///
+/// Returns `EXIT_NOT_FOUND` if either address has no country (or only the unknown country), or
+/// `EXIT_DATABASE_ERROR` if the databases could not be read.
+fn print_distance(arguments: Arguments) -> ExitCode {
+    let Some(Command::Distance { from, to, output }) = arguments.command.clone() else {
+        unreachable!("`RunType::Distance` requires `Command::Distance`");
+    };
+
+    let database = match get_fallback(&arguments)
+        .and_then(|fallback| Ok(load_database(arguments)?.with_fallback(fallback)))
+    {
+        Ok(database) => database,
+        Err(_) => return ExitCode::from(EXIT_DATABASE_ERROR),
+    };
+
+    let locate = |ip: IpAddr| match database.lookup(ip) {
+        Ok(country) if country.code.as_ref() != UNKNOWN_COUNTRY_CODE => Some(Endpoint {
+            ip,
+            country: country.clone(),
+        }),
+        _ => {
+            tracing::error!("No country found for {ip}");
+            None
+        }
+    };
+    let (Some(from), Some(to)) = (locate(from), locate(to)) else {
+        return ExitCode::from(EXIT_NOT_FOUND);
+    };
+    let distance = Distance::new(from, to);
+
+    let result = match output.format {
+        OutputFormat::Text => {
+            print!("{distance}");
+            Ok(())
+        }
+        OutputFormat::Json => output::write_json(io::stdout().lock(), &distance, output.pretty),
+        OutputFormat::Csv | OutputFormat::Tsv => distance.write_records(
+            io::stdout().lock(),
+            output
+                .format
+                .delimiter()
+                .expect("CSV and TSV have delimiters"),
+        ),
+        OutputFormat::Table => {
+            output::write_table(io::stdout().lock(), distance.columns(), &distance.rows())
+        }
+    };
+    if let Err(error) = result {
+        tracing::error!("Failed to print the distance: {error}");
+        return ExitCode::from(EXIT_DATABASE_ERROR);
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Read the newline-separated addresses of the file at `path`, skipping blank lines and warning
+/// about lines that are not valid addresses.
+fn read_addresses(path: &Path) -> io::Result<Vec<IpAddr>> {
+    let mut addresses = Vec::new();
+
+    for line in io::BufReader::new(fs::File::open(path)?).lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        match line.parse() {
+            Ok(address) => addresses.push(address),
+            Err(_) => tracing::warn!("Skipping invalid address: {line}"),
+        }
+    }
+
+    Ok(addresses)
+}
+
+/// Load the databases (specified in `arguments`) once, then run each line read from stdin: looking
+/// up the addresses or hostnames on it, or running the command on it, until `:quit` or the end of
+/// input.
+///
+/// When stdin is a terminal, a prompt is printed before each line.
+fn run_repl(arguments: Arguments) -> ExitCode {
+    let Some(Command::Repl {
+        resolver,
+        all,
+        history_file,
+        no_history,
+        template,
+        output,
+    }) = arguments.command.clone()
+    else {
+        unreachable!("`RunType::Repl` requires `Command::Repl`");
+    };
+    let output = OutputOptions { template, ..output };
+
+    let fallback = match get_fallback(&arguments) {
+        Ok(fallback) => fallback,
+        Err(_) => return ExitCode::from(EXIT_DATABASE_ERROR),
+    };
+    let Some((stats, (ipv4, ipv6))) = stats::read_stats(arguments, false) else {
+        return ExitCode::from(EXIT_DATABASE_ERROR);
+    };
+    let database = GeoDatabase::new(ipv4, ipv6).with_fallback(fallback);
+    let sources = Sources {
+        resolver: Resolver {
+            server: resolver,
+            all,
+        },
+        rdap: None,
+    };
+
+    let history_path = match (no_history, history_file) {
+        (true, _) => None,
+        (false, Some(path)) => Some(path),
+        (false, None) => repl::default_history_path().map(PathBuf::into_boxed_path),
+    };
+    let mut history = History::load(history_path);
+
+    let interactive = io::stdin().is_terminal();
+    if interactive {
+        tracing::info!("Loaded the databases, type `:help` for commands");
+    }
+
+    let mut stdin = io::stdin().lock();
+    let mut buffer = String::new();
+    loop {
+        if interactive {
+            let mut stdout = io::stdout().lock();
+            let _ = write!(stdout, "{}", repl::PROMPT).and_then(|()| stdout.flush());
+        }
+
+        buffer.clear();
+        match stdin.read_line(&mut buffer) {
+            Ok(0) => break,
+            Ok(_) => (),
+            Err(error) => {
+                tracing::error!("Failed to read a line: {error}");
+                return ExitCode::from(EXIT_DATABASE_ERROR);
+            }
+        }
+
+        let line = match history.expand(buffer.trim()) {
+            Ok(line) => line,
+            Err(error) => {
+                tracing::error!("{error}");
+                continue;
+            }
+        };
+        if line != buffer.trim() {
+            println!("{line}");
+        }
+        if let Err(error) = history.push(&line) {
+            tracing::warn!("Failed to save history: {error}");
+        }
+
+        let result = match repl::parse(&line) {
+            Line::Empty => Ok(()),
+            Line::Lookup(inputs) => lookup_lines(
+                &database,
+                io::Cursor::new(inputs.join("\n")),
+                io::stdout().lock(),
+                NonZeroUsize::MIN,
+                output,
+                &CountryFilter::default(),
+                &sources,
+            )
+            .map(drop),
+            Line::Stats => stats::write_stats(&stats, output),
+            Line::Ranges(codes) if codes.is_empty() => {
+                tracing::error!("`:ranges` needs a country code, ex. `:ranges BE`");
+                Ok(())
+            }
+            Line::Ranges(codes) => match unknown_countries(&codes)[..] {
+                [] => print_country_ranges(&database, &codes, (true, true), false),
+                ref unknown => {
+                    tracing::error!("Unknown country codes: {}", unknown.join(", "));
+                    Ok(())
+                }
+            },
+            Line::History => history.write(io::stdout().lock()),
+            Line::Help => {
+                print!("{}", repl::HELP);
+                Ok(())
+            }
+            Line::Quit => break,
+            Line::Unknown(command) => {
+                tracing::error!("Unknown command `{command}`, type `:help` for commands");
+                Ok(())
+            }
+        };
+        if let Err(error) = result {
+            tracing::error!("Failed to print the result: {error}");
+            return ExitCode::from(EXIT_DATABASE_ERROR);
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Summarize the packet capture (specified in `arguments`) by the countries of its endpoints in
+/// the databases (specified in `arguments`), printing the summary.
+fn print_pcap(arguments: Arguments) -> ExitCode {
+    let Some(Command::Pcap { file, output }) = arguments.command.clone() else {
+        unreachable!("`RunType::Pcap` requires `Command::Pcap`");
+    };
+
+    let reader: Box<dyn Read> = if file.as_os_str() == "-" {
+        Box::new(io::stdin().lock())
+    } else {
+        match fs::File::open(&file) {
+            Ok(file) => Box::new(io::BufReader::new(file)),
+            Err(error) => {
+                tracing::error!("Failed to open {}: {error}", file.display());
+                return ExitCode::from(EXIT_DATABASE_ERROR);
+            }
+        }
+    };
+
+    let database = match get_fallback(&arguments)
+        .and_then(|fallback| Ok(load_database(arguments)?.with_fallback(fallback)))
+    {
+        Ok(database) => database,
+        Err(_) => return ExitCode::from(EXIT_DATABASE_ERROR),
+    };
+
+    let summary = match pcap::summarize(reader, &database) {
+        Ok(summary) => summary,
+        Err(error) => {
+            tracing::error!("Failed to read {}: {error}", file.display());
+            return ExitCode::from(EXIT_DATABASE_ERROR);
+        }
+    };
+
+    let result = match output.format {
+        OutputFormat::Text => {
+            print!("{summary}");
+            Ok(())
+        }
+        OutputFormat::Json => output::write_json(io::stdout().lock(), &summary, output.pretty),
+        OutputFormat::Csv | OutputFormat::Tsv => summary.write_records(
+            io::stdout().lock(),
+            output
+                .format
+                .delimiter()
+                .expect("CSV and TSV have delimiters"),
+        ),
+        OutputFormat::Table => {
+            output::write_table(io::stdout().lock(), summary.columns(), &summary.rows())
+        }
+    };
+    if let Err(error) = result {
+        tracing::error!("Failed to print the summary: {error}");
+        return ExitCode::from(EXIT_DATABASE_ERROR);
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Summarize the access log (specified in `arguments`) by the countries of its clients in the
+/// databases (specified in `arguments`), printing the summary and optionally writing it to a
+/// textfile.
+fn print_logsum(arguments: Arguments) -> ExitCode {
+    let Some(Command::Logsum {
+        file,
+        access_format,
+        textfile,
+        output,
+    }) = arguments.command.clone()
+    else {
+        unreachable!("`RunType::Logsum` requires `Command::Logsum`");
+    };
+
+    let reader: Box<dyn BufRead> = if file.as_os_str() == "-" {
+        Box::new(io::stdin().lock())
+    } else {
+        match fs::File::open(&file) {
+            Ok(file) => Box::new(io::BufReader::new(file)),
+            Err(error) => {
+                tracing::error!("Failed to open {}: {error}", file.display());
+                return ExitCode::from(EXIT_DATABASE_ERROR);
+            }
+        }
+    };
+
+    let database = match get_fallback(&arguments)
+        .and_then(|fallback| Ok(load_database(arguments)?.with_fallback(fallback)))
+    {
+        Ok(database) => database,
+        Err(_) => return ExitCode::from(EXIT_DATABASE_ERROR),
+    };
+
+    let summary = match logsum::summarize(reader, access_format, &database) {
+        Ok(summary) => summary,
+        Err(error) => {
+            tracing::error!("Failed to read {}: {error}", file.display());
+            return ExitCode::from(EXIT_DATABASE_ERROR);
+        }
+    };
+
+    if let Some(path) = textfile {
+        if let Err(error) = summary.write_textfile(&path) {
+            tracing::error!("Failed to write {}: {error}", path.display());
+            return ExitCode::from(EXIT_DATABASE_ERROR);
+        }
+        tracing::info!("Wrote {}", path.display());
+    }
+
+    let result = match output.format {
+        OutputFormat::Text => {
+            print!("{summary}");
+            Ok(())
+        }
+        OutputFormat::Json => output::write_json(io::stdout().lock(), &summary, output.pretty),
+        OutputFormat::Csv | OutputFormat::Tsv => summary.write_records(
+            io::stdout().lock(),
+            output
+                .format
+                .delimiter()
+                .expect("CSV and TSV have delimiters"),
+        ),
+        OutputFormat::Table => {
+            output::write_table(io::stdout().lock(), summary.columns(), &summary.rows())
+        }
+    };
+    if let Err(error) = result {
+        tracing::error!("Failed to print the summary: {error}");
+        return ExitCode::from(EXIT_DATABASE_ERROR);
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Run `ip_geo_server` with the arguments following `serve`, exiting with its exit code.
+fn run_server(arguments: Arguments) -> ExitCode {
+    let Some(Command::Serve { args }) = arguments.command else {
+        unreachable!("`RunType::Serve` requires `Command::Serve`");
+    };
+
+    let server = server_path();
+    match process::Command::new(&server).args(args).status() {
+        Ok(status) => match status.code() {
+            Some(code) => ExitCode::from(code.clamp(0, u8::MAX.into()) as u8),
+            None => ExitCode::FAILURE,
+        },
+        Err(error) => {
+            tracing::error!("Failed to run {}: {error}", server.to_string_lossy());
+            ExitCode::from(EXIT_DATABASE_ERROR)
+        }
+    }
+}
+
+/// Return the path to `ip_geo_server`: next to this program if it is there, or else just its
+/// name, to be found on `PATH`.
+fn server_path() -> OsString {
+    let name = format!("ip_geo_server{}", env::consts::EXE_SUFFIX);
+
+    env::current_exe()
+        .map(|path| path.with_file_name(&name))
+        .ok()
+        .filter(|path| path.is_file())
+        .map_or_else(|| name.into(), PathBuf::into_os_string)
 }
 
 /// Lossily converts a char to a byte.
@@ -90,6 +1573,797 @@ fn char_to_byte(char: char) -> u8 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use arguments::ColorChoice;
+    use bench::Backend;
+    use logging::LogFormat;
+
+    #[test]
+    fn test_watcher() {
+        use std::io::Write;
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        let mut watcher = Watcher::new(vec![file.path().into()]);
+        assert!(!watcher.changed());
+
+        writeln!(file, "1.2.3.4").unwrap();
+        file.flush().unwrap();
+        assert!(watcher.changed());
+        assert!(!watcher.changed());
+
+        let path = file.path().to_owned();
+        drop(file);
+        assert!(!path.exists());
+        assert!(watcher.changed());
+    }
+
+    #[test]
+    fn test_log_level() {
+        use tracing::level_filters::LevelFilter;
+
+        assert_eq!(logging::level(0, 0), LevelFilter::INFO);
+        assert_eq!(logging::level(1, 0), LevelFilter::DEBUG);
+        assert_eq!(logging::level(5, 0), LevelFilter::TRACE);
+        assert_eq!(logging::level(0, 1), LevelFilter::WARN);
+        assert_eq!(logging::level(0, 2), LevelFilter::ERROR);
+        assert_eq!(logging::level(0, 3), LevelFilter::OFF);
+    }
+
+    #[test]
+    fn test_distance() {
+        // Paris to London is about 344 km
+        let kilometers = distance::haversine((2.3522, 48.8566), (-0.1276, 51.5072));
+        assert!((kilometers - 344.0).abs() < 1.0, "{kilometers}");
+        assert_eq!(distance::haversine((4.5, 50.5), (4.5, 50.5)), 0.0);
+        // Antipodes are half of the Earth's circumference apart
+        let kilometers = distance::haversine((0.0, 0.0), (180.0, 0.0));
+        assert!((kilometers - 20_015.0).abs() < 1.0, "{kilometers}");
+
+        let countries = ip_geo::country_list::get_countries();
+        let endpoint = |ip: &str, code: &str| Endpoint {
+            ip: ip.parse().unwrap(),
+            country: countries.get(code).unwrap().clone(),
+        };
+
+        let distance = Distance::new(endpoint("1.1.1.1", "BE"), endpoint("2::1", "FR"));
+        assert!(distance.kilometers > 0.0);
+        assert!((distance.kilometers / distance.miles - 1.609344).abs() < 1e-9);
+        assert_eq!(
+            distance.rows()[0][..4],
+            ["1.1.1.1", "BE", "2::1", "FR"].map(String::from)
+        );
+        assert!(distance.to_string().contains("km"));
+
+        let same = Distance::new(endpoint("1.1.1.1", "BE"), endpoint("1.1.1.2", "BE"));
+        assert_eq!(same.kilometers, 0.0);
+        assert!(same.to_string().contains("same country"));
+    }
+
+    #[test]
+    fn test_lang() {
+        use std::io::Write;
+
+        let parse = |arguments: &[&str]| {
+            let arguments = ["ip_geo_cli"].iter().chain(arguments);
+            Arguments::try_parse_from(arguments).map(|arguments| arguments.lang)
+        };
+
+        assert_eq!(parse(&["lookup"]).unwrap(), None);
+        assert_eq!(
+            parse(&["lookup", "--lang", "pt-BR"]).unwrap(),
+            Some("pt-BR".into())
+        );
+        assert_eq!(
+            parse(&["--lang", "en", "lookup"]).unwrap(),
+            Some("en".into())
+        );
+        assert!(parse(&["lookup", "--lang", "tlh"]).is_err());
+
+        let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+        write!(temp_file, "16843009,50529027,BE\n67372036,67372036,FR\n").unwrap();
+        let path: Box<Path> = temp_file.path().into();
+
+        let name = |language: Option<&str>, address: Ipv4Addr| {
+            let mut map = read_ipv4(path.clone(), 2, None, language).unwrap();
+            map.search(address).unwrap().name.clone()
+        };
+
+        assert_eq!(name(None, Ipv4Addr::new(2, 2, 2, 2)).as_ref(), "Belgium");
+        assert_eq!(
+            name(Some("en"), Ipv4Addr::new(2, 2, 2, 2)).as_ref(),
+            "Belgium"
+        );
+        assert_eq!(
+            name(Some("de"), Ipv4Addr::new(2, 2, 2, 2)).as_ref(),
+            "Belgien"
+        );
+        assert_eq!(
+            name(Some("fr"), Ipv4Addr::new(4, 4, 4, 4)).as_ref(),
+            "France"
+        );
+    }
+
+    #[test]
+    fn test_stdin_conflict() {
+        let conflict = |arguments: &[&str]| {
+            let arguments = ["ip_geo_cli"].iter().chain(arguments);
+            stdin_conflict(&Arguments::parse_from(arguments))
+        };
+
+        assert_eq!(conflict(&["db", "stats"]), None);
+        assert_eq!(conflict(&["--IPv4-path", "-", "db", "stats"]), None);
+        assert_eq!(
+            conflict(&["--IPv6-path", "-", "lookup", "--input", "ips.txt"]),
+            None
+        );
+        assert_eq!(conflict(&["--IPv4-path", "-", "lookup", "1.2.3.4"]), None);
+
+        assert!(conflict(&["--IPv4-path", "-", "--IPv6-path", "-", "db", "stats"]).is_some());
+        assert!(conflict(&["--IPv4-path", "-", "lookup"]).is_some());
+        assert!(conflict(&["--IPv4-path", "-", "lookup", "-"]).is_some());
+        assert!(conflict(&["--IPv4-path", "-", "repl"]).is_some());
+        assert!(conflict(&["--IPv4-path", "-", "pcap", "-"]).is_some());
+        assert!(conflict(&["--IPv4-path", "-", "db", "verify"]).is_some());
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        let parse =
+            |arguments: &[&str]| Arguments::parse_from(["ip_geo_cli"].iter().chain(arguments));
+        let directory = tempfile::tempdir().unwrap();
+        let missing = directory.path().join("missing");
+        let missing = missing.to_str().unwrap();
+
+        assert_eq!(
+            lookup_all(parse(&["lookup", "--input", missing])),
+            ExitCode::from(EXIT_DATABASE_ERROR)
+        );
+        assert_eq!(
+            convert::convert_database(parse(&["db", "convert", "--to", "mmdb", "-o", missing])),
+            ExitCode::from(EXIT_USAGE)
+        );
+        assert!(!Path::new(missing).exists());
+    }
+
+    #[test]
+    fn test_validate_config() {
+        use std::io::Write;
+
+        let validate = |contents: &str| {
+            let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+            write!(temp_file, "{contents}").unwrap();
+            arguments::validate_config(temp_file.path())
+        };
+
+        let database = tempfile::NamedTempFile::new().unwrap();
+        let database = database.path().display();
+        assert!(validate(&format!("ipv4_path = \"{database}\"\nipv4_len = 10\n")).is_empty());
+
+        assert_eq!(
+            validate("ipv4_len = 10\nipv4_lenght = 10\n"),
+            ["Unknown key `ipv4_lenght` on line 2"]
+        );
+        assert!(validate("ipv4_len = \"ten\"\n")[0].contains("line 1, column 12"));
+        // A path in a fresh directory that is never created
+        let directory = tempfile::tempdir().unwrap();
+        let missing = directory.path().join("missing");
+        let missing = missing.display();
+        assert_eq!(
+            validate(&format!("ipv6_path = \"{missing}\"\n")),
+            [format!(
+                "`ipv6_path` refers to {missing}, which does not exist"
+            )]
+        );
+        assert_eq!(
+            validate(&format!(
+                "fallback = \"database\"\nfallback_ipv4_path = \"{database}\"\n"
+            )),
+            ["`fallback` is `database`, but `fallback_ipv6_path` is not set"]
+        );
+
+        // The default configuration is valid, and reads back as the defaults
+        let defaults: Arguments = toml::from_str(&arguments::default_config()).unwrap();
+        assert_eq!(defaults.ipv4_len, Some(200_000));
+        assert_eq!(defaults.fallback, Some(FallbackMode::None));
+        assert_eq!(defaults.fallback_ipv4_path, None);
+    }
+
+    #[test]
+    fn test_database_stats() {
+        use std::io::Write;
+
+        let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            temp_file,
+            "# comment\n16843009,16843263,BE\n16843265,16843266,FR\n"
+        )
+        .unwrap();
+        let path = temp_file.path();
+
+        let file = stats::DatabaseFile::read(path, Some('#')).unwrap();
+        assert_eq!(file.format, "integer");
+        assert_eq!(file.size, 52);
+
+        let map = ip_geo::ipv4::parse_ipv4_file(path.into(), 2, Some('#'));
+        let stats = stats::DatabaseStats::new(
+            &map,
+            file,
+            true,
+            |address| u32::from(address).into(),
+            0,
+            "addresses",
+        );
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.countries, 2);
+        assert_eq!(stats.covered, 257);
+        assert_eq!(stats.gaps, 1);
+        assert_eq!(stats.ranked_countries(), [("BE", 1), ("FR", 1)]);
+    }
+
+    #[test]
+    fn test_verify_database() {
+        use std::io::Write;
+
+        let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            temp_file,
+            "67372036,67372036,FR\n16843009,50529027,BE\n33686018,33686020,DE\n1,2,ZZ\n"
+        )
+        .unwrap();
+
+        let (entries, problems) = verify::verify_database(
+            temp_file.path(),
+            None,
+            ip_geo::ipv4::parse_ipv4_file_with_mode,
+        )
+        .unwrap();
+        assert_eq!(entries, 3);
+        assert_eq!(
+            problems,
+            [
+                "line 4: unrecognized country or region 'ZZ'",
+                "line 2: starts before the entry on line 1",
+                "1.1.1.1-3.3.3.3 (BE) overlaps 2.2.2.2-2.2.2.4 (DE)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_write_ranges() {
+        let ranges = [(Ipv4Addr::new(192, 0, 2, 0), Ipv4Addr::new(192, 0, 3, 127))];
+
+        let mut cidrs = Vec::new();
+        write_ranges(&mut cidrs, &ranges, false).unwrap();
+        assert_eq!(
+            String::from_utf8(cidrs).unwrap(),
+            "192.0.2.0/24\n192.0.3.0/25\n"
+        );
+
+        let mut raw = Vec::new();
+        write_ranges(&mut raw, &ranges, true).unwrap();
+        assert_eq!(String::from_utf8(raw).unwrap(), "192.0.2.0-192.0.3.127\n");
+    }
+
+    #[test]
+    fn test_bench() {
+        use ip_geo::IpAddrEntry;
+
+        let countries = ip_geo::country_list::get_countries();
+        let mut ipv4_map = IpAddrMap::new();
+        for (start, end, code) in [
+            (Ipv4Addr::new(1, 0, 0, 3), Ipv4Addr::new(1, 0, 2, 200), "BE"),
+            (
+                Ipv4Addr::new(1, 0, 2, 201),
+                Ipv4Addr::new(1, 0, 2, 201),
+                "FR",
+            ),
+            (
+                Ipv4Addr::new(9, 0, 0, 0),
+                Ipv4Addr::new(255, 255, 255, 255),
+                "CA",
+            ),
+        ] {
+            let country = countries.get(code).unwrap().clone();
+            ipv4_map.insert(IpAddrEntry::new(start, end, country).unwrap());
+        }
+        let mut ipv6_map = IpAddrMap::new();
+        let country = countries.get("CA").unwrap().clone();
+        ipv6_map.insert(
+            IpAddrEntry::new(Ipv6Addr::UNSPECIFIED, Ipv6Addr::from(u128::MAX), country).unwrap(),
+        );
+        ipv4_map.cleanup();
+        ipv6_map.cleanup();
+
+        let mut addresses = bench::generate(&ipv4_map, &ipv6_map, 1000, 7);
+        assert_eq!(addresses.len(), 1000);
+        assert_eq!(addresses, bench::generate(&ipv4_map, &ipv6_map, 1000, 7));
+        addresses.extend([
+            IpAddr::V4(Ipv4Addr::new(1, 0, 0, 2)),
+            IpAddr::V4(Ipv4Addr::new(1, 0, 2, 202)),
+        ]);
+
+        let backends = [Backend::Linear, Backend::BinarySearch, Backend::Trie];
+        let report = bench::run(&ipv4_map, &ipv6_map, &addresses, &backends);
+        let rows = report.rows();
+        assert_eq!(rows.len(), 3);
+        for (row, backend) in rows.iter().zip(backends) {
+            assert_eq!(row[0], backend.to_string());
+            assert_eq!(row[1], "1002");
+            assert_eq!(row[2], "1000");
+        }
+    }
+
+    #[test]
+    fn test_repl() {
+        assert_eq!(repl::parse("  "), Line::Empty);
+        assert_eq!(
+            repl::parse("1.2.3.4  example.com"),
+            Line::Lookup(vec!["1.2.3.4", "example.com"])
+        );
+        assert_eq!(repl::parse(":stats"), Line::Stats);
+        assert_eq!(repl::parse(":ranges BE nl"), Line::Ranges(vec!["BE", "nl"]));
+        assert_eq!(repl::parse(":q"), Line::Quit);
+        assert_eq!(repl::parse(":nope 1"), Line::Unknown(":nope"));
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let mut history = History::load(Some(file.path().into()));
+        assert!(history.expand("!!").is_err());
+        for line in ["1.2.3.4", "1.2.3.4", "", ":stats"] {
+            history.push(line).unwrap();
+        }
+        assert_eq!(history.lines(), ["1.2.3.4", ":stats"]);
+        assert_eq!(history.expand("!!").unwrap(), ":stats");
+        assert_eq!(history.expand("!1").unwrap(), "1.2.3.4");
+        assert!(history.expand("!3").is_err());
+        assert!(history.expand("!0").is_err());
+        assert_eq!(history.expand("5.6.7.8").unwrap(), "5.6.7.8");
+
+        let history = History::load(Some(file.path().into()));
+        assert_eq!(history.lines(), ["1.2.3.4", ":stats"]);
+    }
+
+    #[test]
+    fn test_pcap_summary() {
+        use ip_geo::IpAddrEntry;
+
+        let belgium = ip_geo::country_list::get_countries()
+            .get("BE")
+            .unwrap()
+            .clone();
+        let mut ipv4_map = IpAddrMap::new();
+        ipv4_map.insert(
+            IpAddrEntry::new(
+                Ipv4Addr::new(1, 0, 0, 0),
+                Ipv4Addr::new(1, 0, 0, 255),
+                belgium,
+            )
+            .unwrap(),
+        );
+        let database = GeoDatabase::new(ipv4_map, IpAddrMap::new());
+
+        // A big-endian capture of raw IP packets
+        let mut capture = Vec::new();
+        for field in [0xA1B2_C3D4_u32, 0x0002_0004, 0, 0, 65535, 101] {
+            capture.extend(field.to_be_bytes());
+        }
+        let packets: [(&[u8], [u8; 4], [u8; 4]); 3] = [
+            (&[0x45], [1, 0, 0, 1], [1, 0, 0, 2]),
+            (&[0x45], [1, 0, 0, 1], [10, 0, 0, 1]),
+            (&[0x00], [0; 4], [0; 4]),
+        ];
+        for (version, source, destination) in packets {
+            let mut packet = version.to_vec();
+            packet.resize(12, 0);
+            packet.extend(source);
+            packet.extend(destination);
+
+            for field in [0, 0, packet.len() as u32, 100] {
+                capture.extend(field.to_be_bytes());
+            }
+            capture.extend(packet);
+        }
+
+        let summary = pcap::summarize(capture.as_slice(), &database).unwrap();
+        assert_eq!(
+            summary.rows(),
+            [
+                ["BE", "Belgium", "2", "200", "2"],
+                ["-", "No country", "1", "100", "1"],
+            ]
+        );
+
+        let pcapng = [0x0A, 0x0D, 0x0D, 0x0A].repeat(6);
+        assert!(pcap::summarize(pcapng.as_slice(), &database).is_err());
+    }
+
+    #[test]
+    fn test_logsum() {
+        use ip_geo::IpAddrEntry;
+        use logsum::AccessLogFormat;
+
+        let belgium = ip_geo::country_list::get_countries()
+            .get("BE")
+            .unwrap()
+            .clone();
+        let mut ipv4_map = IpAddrMap::new();
+        ipv4_map.insert(
+            IpAddrEntry::new(
+                Ipv4Addr::new(1, 0, 0, 0),
+                Ipv4Addr::new(1, 0, 0, 255),
+                belgium,
+            )
+            .unwrap(),
+        );
+        let database = GeoDatabase::new(ipv4_map, IpAddrMap::new());
+
+        let log = concat!(
+            "1.0.0.1 - - [10/Oct/2000:13:55:36 -0700] \"GET / HTTP/1.0\" 200 2326 \"-\" \"curl\"\n",
+            "1.0.0.2 - - [10/Oct/2000:13:55:37 -0700] \"GET /\\\" HTTP/1.0\" 404 -\n",
+            "10.0.0.1 - - [10/Oct/2000:13:55:38 -0700] \"GET / HTTP/1.0\" 200 5\n",
+            "no address here\n",
+        );
+
+        let summary = logsum::summarize(log.as_bytes(), AccessLogFormat::Auto, &database).unwrap();
+        assert_eq!(summary.rows(), [["BE", "Belgium", "2", "2326"]]);
+
+        let prometheus = summary.render_prometheus();
+        assert!(prometheus
+            .contains("ip_geo_log_lines_total{country_code=\"BE\",country_name=\"Belgium\"} 2\n"));
+        assert!(prometheus.contains("ip_geo_log_lines_dropped_total 2\n"));
+        assert!(prometheus.contains(
+            "ip_geo_log_responses_total{country_code=\"BE\",country_name=\"Belgium\",status=\"4xx\"} 1\n"
+        ));
+
+        let summary = logsum::summarize(log.as_bytes(), AccessLogFormat::Plain, &database).unwrap();
+        assert_eq!(summary.rows(), [["BE", "Belgium", "2", "-"]]);
+    }
+
+    #[test]
+    fn test_output_template() {
+        use ip_geo::IpAddrEntry;
+        use output::Template;
+
+        assert!(Template::parse("{ip").is_err());
+        assert!(Template::parse("ip}").is_err());
+        assert!(Template::parse("{country}").is_err());
+        assert!(Template::parse(r"{ip}\").is_err());
+        assert!(Template::parse(r"\x").is_err());
+
+        let belgium = ip_geo::country_list::get_countries()
+            .get("BE")
+            .unwrap()
+            .clone();
+        let mut ipv4_map = IpAddrMap::new();
+        ipv4_map.insert(
+            IpAddrEntry::new(
+                Ipv4Addr::new(1, 0, 0, 0),
+                Ipv4Addr::new(1, 0, 0, 255),
+                belgium,
+            )
+            .unwrap(),
+        );
+        let database = GeoDatabase::new(ipv4_map, IpAddrMap::new());
+
+        let template =
+            output::parse_template(r"{{{ip}}}\t{code}\t{name}\t{continent}|{found}\\").unwrap();
+        let options = OutputOptions {
+            format: OutputFormat::Text,
+            pretty: false,
+            summary: false,
+            color: ColorChoice::Always,
+            flags: false,
+            template: Some(template),
+            null: false,
+        };
+        let mut output = Vec::new();
+        lookup_lines(
+            &database,
+            "1.0.0.1\n9.9.9.9\n".as_bytes(),
+            &mut output,
+            NonZeroUsize::MIN,
+            options,
+            &CountryFilter::default(),
+            &Sources::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{1.0.0.1}\tBE\tBelgium\tEU|true\\\n{9.9.9.9}\t\t\t|false\\\n"
+        );
+    }
+
+    #[test]
+    fn test_lookup_lines() {
+        use ip_geo::IpAddrEntry;
+
+        let belgium = ip_geo::country_list::get_countries()
+            .get("BE")
+            .unwrap()
+            .clone();
+        let mut ipv4_map = IpAddrMap::new();
+        ipv4_map.insert(
+            IpAddrEntry::new(
+                Ipv4Addr::new(1, 0, 0, 0),
+                Ipv4Addr::new(1, 0, 0, 255),
+                belgium,
+            )
+            .unwrap(),
+        );
+        let database = GeoDatabase::new(ipv4_map, IpAddrMap::new());
+
+        let input = "1.0.0.1\n\n9.9.9.9\nnot an address\n  1.0.0.2  \n";
+        let mut output = Vec::new();
+        let threads = NonZeroUsize::new(2).unwrap();
+        let all = CountryFilter::default();
+        let sources = Sources::default();
+        let text = OutputOptions {
+            format: OutputFormat::Text,
+            pretty: false,
+            summary: false,
+            color: ColorChoice::Never,
+            flags: false,
+            template: None,
+            null: false,
+        };
+        let outcome = lookup_lines(
+            &database,
+            input.as_bytes(),
+            &mut output,
+            threads,
+            text,
+            &all,
+            &sources,
+        )
+        .unwrap();
+
+        assert_eq!(outcome, Outcome::Invalid);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "BE Belgium\nNo country found!\nInvalid address: not an address\nBE Belgium\n"
+        );
+
+        // Results spanning several chunks and batches stay in input order
+        let input = "1.0.0.1\n9.9.9.9\n".repeat(3 * LOOKUP_CHUNK_LINES);
+        let mut output = Vec::new();
+        let outcome = lookup_lines(
+            &database,
+            input.as_bytes(),
+            &mut output,
+            threads,
+            text,
+            &all,
+            &sources,
+        )
+        .unwrap();
+        assert_eq!(outcome, Outcome::NotFound);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "BE Belgium\nNo country found!\n".repeat(3 * LOOKUP_CHUNK_LINES)
+        );
+
+        // One JSON object per line, or a single array with `--pretty`
+        let input = "1.0.0.1\n\n9.9.9.9\nnot an address\n";
+        for pretty in [false, true] {
+            let json = OutputOptions {
+                format: OutputFormat::Json,
+                pretty,
+                summary: false,
+                color: ColorChoice::Never,
+                flags: false,
+                template: None,
+                null: false,
+            };
+            let mut output = Vec::new();
+            lookup_lines(
+                &database,
+                input.as_bytes(),
+                &mut output,
+                threads,
+                json,
+                &all,
+                &sources,
+            )
+            .unwrap();
+
+            let results: Vec<serde_json::Value> = if pretty {
+                serde_json::from_slice(&output).unwrap()
+            } else {
+                output
+                    .split(|&byte| byte == b'\n')
+                    .filter(|line| !line.is_empty())
+                    .map(|line| serde_json::from_slice(line).unwrap())
+                    .collect()
+            };
+
+            assert_eq!(results.len(), 3);
+            assert_eq!(results[0]["ip"], "1.0.0.1");
+            assert_eq!(results[0]["country"]["code"], "BE");
+            assert_eq!(results[0]["country"]["continent"], "EU");
+            assert!(results[1]["country"].is_null());
+            assert_eq!(results[2]["error"], "Invalid address: not an address");
+        }
+
+        // A header row, then one quoted row per line
+        let input = "1.0.0.1\n9.9.9.9\nnot, an address\n";
+        let csv = OutputOptions {
+            format: OutputFormat::Csv,
+            pretty: false,
+            summary: false,
+            color: ColorChoice::Never,
+            flags: false,
+            template: None,
+            null: false,
+        };
+        let mut output = Vec::new();
+        lookup_lines(
+            &database,
+            input.as_bytes(),
+            &mut output,
+            threads,
+            csv,
+            &all,
+            &sources,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "ip,country_code,country_name,continent,longitude,latitude,error,source\n\
+             1.0.0.1,BE,Belgium,EU,4.668055555,50.641111111,,\n\
+             9.9.9.9,,,,,,,\n\
+             \"not, an address\",,,,,,\"Invalid address: not, an address\",\n"
+        );
+
+        // With `--null`, records are read and results written separated by NUL
+        let input = "1.0.0.1\0\09.9.9.9\nnot an address\0 1.0.0.2\n";
+        let mut output = Vec::new();
+        lookup_lines(
+            &database,
+            input.as_bytes(),
+            &mut output,
+            threads,
+            OutputOptions { null: true, ..text },
+            &all,
+            &sources,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "BE Belgium\0Invalid address: 9.9.9.9\nnot an address\0BE Belgium\0"
+        );
+
+        // Colored country codes and flags, only when asked for
+        let colored = OutputOptions {
+            color: ColorChoice::Always,
+            flags: true,
+            ..text
+        };
+        let mut output = Vec::new();
+        lookup_lines(
+            &database,
+            "1.0.0.1\n9.9.9.9\n".as_bytes(),
+            &mut output,
+            threads,
+            colored,
+            &all,
+            &sources,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\u{1F1E7}\u{1F1EA} \x1b[1;34mBE\x1b[0m Belgium\n\x1b[2mNo country found!\x1b[0m\n"
+        );
+
+        // Countries from RDAP are marked as such
+        let netherlands = ip_geo::country_list::get_countries().get("NL").cloned();
+        let mut output = Vec::new();
+        LookupResult::rdap("9.9.9.9", Ok(netherlands))
+            .render(&mut output, text)
+            .unwrap();
+        LookupResult::rdap("9.9.9.9", Ok(None))
+            .render(&mut output, csv)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "NL Netherlands (from RDAP)\n9.9.9.9,,,,,,,\n"
+        );
+
+        // Only results in included countries, and none in excluded countries
+        let input = "1.0.0.1\n9.9.9.9\nnot an address\n";
+        for (include, exclude, expected) in [
+            (vec!["be".into()], vec![], "BE Belgium\n"),
+            (
+                vec![],
+                vec!["BE".into()],
+                "No country found!\nInvalid address: not an address\n",
+            ),
+        ] {
+            let filter = CountryFilter { include, exclude };
+            let mut output = Vec::new();
+            lookup_lines(
+                &database,
+                input.as_bytes(),
+                &mut output,
+                threads,
+                text,
+                &filter,
+                &sources,
+            )
+            .unwrap();
+            assert_eq!(String::from_utf8(output).unwrap(), expected);
+        }
+
+        // Aligned columns, then the number of addresses in each country
+        let input = "1.0.0.1\n9.9.9.9\n1.0.0.2\n";
+        let table = OutputOptions {
+            format: OutputFormat::Table,
+            pretty: false,
+            summary: true,
+            color: ColorChoice::Never,
+            flags: false,
+            template: None,
+            null: false,
+        };
+        let mut output = Vec::new();
+        lookup_lines(
+            &database,
+            input.as_bytes(),
+            &mut output,
+            threads,
+            table,
+            &all,
+            &sources,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "IP       CODE  COUNTRY           CONTINENT\n\
+             1.0.0.1  BE    Belgium           EU\n\
+             9.9.9.9  -     No country found  -\n\
+             1.0.0.2  BE    Belgium           EU\n\
+             \n\
+             COUNT  CODE  COUNTRY\n\
+             2      BE    Belgium\n\
+             1      -     No country\n"
+        );
+
+        // Hostnames are resolved, here by a DNS server answering every query with `1.0.0.3`
+        let server = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let sources = Sources {
+            resolver: Resolver {
+                server: Some(server.local_addr().unwrap()),
+                all: true,
+            },
+            rdap: None,
+        };
+        let dns = thread::spawn(move || {
+            let mut query = [0; 512];
+            // One query for `A` records and one for `AAAA` records
+            for _ in 0..2 {
+                let (len, client) = server.recv_from(&mut query).unwrap();
+                let mut answer = query[..len].to_vec();
+                answer[2] |= 0x80;
+                answer[7] = 1;
+                // A pointer to the question's name, then an `A` record
+                answer.extend_from_slice(&[0xC0, 12, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4, 1, 0, 0, 3]);
+                server.send_to(&answer, client).unwrap();
+            }
+        });
+
+        let mut output = Vec::new();
+        let outcome = lookup_lines(
+            &database,
+            "example.com\n".as_bytes(),
+            &mut output,
+            threads,
+            text,
+            &all,
+            &sources,
+        )
+        .unwrap();
+        assert_eq!(outcome, Outcome::Found);
+        dns.join().unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "1.0.0.3 BE Belgium\n");
+    }
 
     #[test]
     fn test_find_ipv4() {
@@ -117,22 +2391,30 @@ mod tests {
         .unwrap();
         let path: Box<Path> = temp_file.path().into();
 
-        fn gen_args(addr: Ipv4Addr, path: Box<Path>) -> arguments::Arguments {
+        fn gen_args(path: Box<Path>) -> arguments::Arguments {
             Arguments {
+                command: None,
                 config_path: None,
-                ipv4_addr: Some(addr),
+                verbose: 0,
+                quiet: 0,
+                log_format: LogFormat::Text,
                 ipv4_path: Some(path),
                 ipv4_len: Some(2),
                 ipv4_comment: None,
-                ipv6_addr: None,
+                ipv4_url: None,
                 ipv6_path: None,
                 ipv6_len: None,
                 ipv6_comment: None,
+                ipv6_url: None,
+                fallback: None,
+                fallback_ipv4_path: None,
+                fallback_ipv6_path: None,
+                lang: None,
             }
         }
 
         fn get_code(addr: Ipv4Addr, path: Box<Path>) -> std::sync::Arc<str> {
-            find_ipv4(gen_args(addr, path)).unwrap().code
+            find_ipv4(gen_args(path), addr).unwrap().code
         }
 
         assert_eq!(get_code(middle_a, path.clone()), value_a);
@@ -162,22 +2444,30 @@ mod tests {
         .unwrap();
         let path: Box<Path> = temp_file.path().into();
 
-        fn gen_args(addr: Ipv6Addr, path: Box<Path>) -> arguments::Arguments {
+        fn gen_args(path: Box<Path>) -> arguments::Arguments {
             Arguments {
+                command: None,
                 config_path: None,
-                ipv4_addr: None,
+                verbose: 0,
+                quiet: 0,
+                log_format: LogFormat::Text,
                 ipv4_path: None,
                 ipv4_len: None,
                 ipv4_comment: None,
-                ipv6_addr: Some(addr),
+                ipv4_url: None,
                 ipv6_path: Some(path),
                 ipv6_len: Some(2),
                 ipv6_comment: None,
+                ipv6_url: None,
+                fallback: None,
+                fallback_ipv4_path: None,
+                fallback_ipv6_path: None,
+                lang: None,
             }
         }
 
         fn get_code(addr: Ipv6Addr, path: Box<Path>) -> std::sync::Arc<str> {
-            find_ipv6(gen_args(addr, path)).unwrap().code
+            find_ipv6(gen_args(path), addr).unwrap().code
         }
 
         assert_eq!(get_code(middle_a, path.clone()), value_a);
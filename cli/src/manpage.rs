@@ -0,0 +1,270 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+// Copyright © 2024 Jaxydog
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Generating a roff man page for the subcommands, options, configuration keys, and exit codes of
+//! the program.
+
+use std::fmt::Write;
+
+use clap::{Arg, Command};
+
+use crate::arguments;
+
+/// The exit codes of the program, and when they are used.
+const EXIT_CODES: [(&str, &str); 4] = [
+    ("0", "Success."),
+    (
+        "1",
+        "No country found, ex. for a single address with no country, or for an invalid address in \
+         a bulk lookup.",
+    ),
+    (
+        "2",
+        "Invalid command-line arguments or configuration, ex. a database read from stdin by a \
+         command that cannot, or an unknown country code.",
+    ),
+    (
+        "3",
+        "Failure to read, write, download, or verify a database or another file, ex. a failed \
+         download, or a database that differs from its manifest. `serve` exits with the exit \
+         code of `ip_geo_server` instead.",
+    ),
+];
+
+/// Return a roff man page for `command`, covering every visible subcommand and option, the keys
+/// of the configuration file, and the exit codes.
+pub fn generate(mut command: Command) -> String {
+    // Building the command adds the help and version options, and propagates global options
+    command.build();
+    let name = command.get_name().to_owned();
+    let version = command.get_version().unwrap_or_default().to_owned();
+
+    let mut page = String::new();
+    let _ = writeln!(
+        page,
+        ".TH {} 1 \"\" \"{} {}\" \"User Commands\"",
+        name.to_uppercase(),
+        escape(&name),
+        escape(&version)
+    );
+
+    let _ = writeln!(page, ".SH NAME");
+    let about = command.get_about().map(ToString::to_string);
+    let _ = writeln!(
+        page,
+        "{} \\- {}",
+        escape(&name),
+        escape(about.as_deref().unwrap_or_default())
+    );
+
+    let _ = writeln!(page, ".SH SYNOPSIS");
+    let _ = writeln!(page, "{}", synopsis(&command, &name));
+
+    let _ = writeln!(page, ".SH OPTIONS");
+    let _ = writeln!(
+        page,
+        "These options can be given before or after any subcommand."
+    );
+    for argument in command
+        .get_arguments()
+        .filter(|argument| is_visible(argument))
+    {
+        write_argument(&mut page, argument);
+    }
+
+    let _ = writeln!(page, ".SH COMMANDS");
+    write_subcommands(&mut page, &command, &name);
+
+    let _ = writeln!(page, ".SH CONFIGURATION");
+    let _ = writeln!(
+        page,
+        "Options can also be set in a TOML file, \\fI{name}.toml\\fR in the user's configuration \
+         directory (ex. \\fI~/.config\\fR) or the file given by \\fB\\-\\-config\\-path\\fR. \
+         Command\\-line options override the file. Its keys are:"
+    );
+    for argument in command
+        .get_arguments()
+        .filter(|argument| arguments::is_config_key(argument))
+    {
+        let _ = writeln!(page, ".TP");
+        let _ = writeln!(page, "\\fB{}\\fR", escape(argument.get_id().as_str()));
+        let _ = writeln!(page, "{}", help(argument));
+    }
+
+    let _ = writeln!(page, ".SH \"EXIT STATUS\"");
+    for (code, description) in EXIT_CODES {
+        let _ = writeln!(page, ".TP");
+        let _ = writeln!(page, "\\fB{code}\\fR");
+        let _ = writeln!(page, "{}", escape(description));
+    }
+
+    let _ = writeln!(page, ".SH \"SEE ALSO\"");
+    let _ = writeln!(page, "\\fBip_geo_server\\fR");
+
+    page
+}
+
+/// Write a section describing every visible subcommand of `command` (and theirs), whose
+/// invocation begins with `prefix`, to `page`.
+fn write_subcommands(page: &mut String, command: &Command, prefix: &str) {
+    for subcommand in command
+        .get_subcommands()
+        .filter(|subcommand| !subcommand.is_hide_set() && subcommand.get_name() != "help")
+    {
+        let invocation = format!("{prefix} {}", subcommand.get_name());
+
+        let _ = writeln!(page, ".SS \"{}\"", escape(&invocation));
+        let _ = writeln!(page, "{}", synopsis(subcommand, &invocation));
+        let about = subcommand
+            .get_long_about()
+            .or_else(|| subcommand.get_about())
+            .map(ToString::to_string);
+        if let Some(about) = about {
+            let _ = writeln!(page, ".PP");
+            let _ = writeln!(page, "{}", paragraphs(&about));
+        }
+
+        for argument in subcommand
+            .get_arguments()
+            .filter(|argument| is_visible(argument) && !argument.is_global_set())
+        {
+            write_argument(page, argument);
+        }
+
+        write_subcommands(page, subcommand, &invocation);
+    }
+}
+
+/// Return the synopsis of `command`, invoked as `invocation`.
+fn synopsis(command: &Command, invocation: &str) -> String {
+    let mut synopsis = format!("\\fB{}\\fR [\\fIOPTIONS\\fR]", escape(invocation));
+
+    for positional in command
+        .get_positionals()
+        .filter(|argument| is_visible(argument))
+    {
+        let value = value_name(positional);
+        let value = match positional.get_num_args() {
+            Some(range) if range.max_values() > 1 => format!("{value}..."),
+            _ => value,
+        };
+
+        let _ = if positional.is_required_set() {
+            write!(synopsis, " \\fI{value}\\fR")
+        } else {
+            write!(synopsis, " [\\fI{value}\\fR]")
+        };
+    }
+    if command.has_subcommands() {
+        let _ = write!(synopsis, " \\fICOMMAND\\fR");
+    }
+
+    synopsis
+}
+
+/// Write a tagged paragraph describing `argument` to `page`.
+fn write_argument(page: &mut String, argument: &Arg) {
+    let mut names = Vec::new();
+    if let Some(short) = argument.get_short() {
+        names.push(format!("\\fB\\-{short}\\fR"));
+    }
+    if let Some(long) = argument.get_long() {
+        names.push(format!("\\fB\\-\\-{}\\fR", escape(long)));
+    }
+    if names.is_empty() {
+        names.push(format!("\\fI{}\\fR", value_name(argument)));
+    } else if argument.get_action().takes_values() {
+        let last = names.len() - 1;
+        names[last] = format!("{} \\fI{}\\fR", names[last], value_name(argument));
+    }
+
+    let _ = writeln!(page, ".TP");
+    let _ = writeln!(page, "{}", names.join(", "));
+    let _ = writeln!(page, "{}", help(argument));
+}
+
+/// Return the help of `argument`, followed by its possible values and default, if any.
+fn help(argument: &Arg) -> String {
+    let mut help = argument
+        .get_long_help()
+        .or_else(|| argument.get_help())
+        .map(|help| paragraphs(&help.to_string()))
+        .unwrap_or_default();
+
+    let values: Vec<String> = argument
+        .get_possible_values()
+        .iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| format!("\\fB{}\\fR", escape(value.get_name())))
+        .collect();
+    if !values.is_empty() {
+        let _ = write!(help, "\n.br\nPossible values: {}.", values.join(", "));
+    }
+
+    // Flags default to being unset, which goes without saying
+    let defaults: Vec<String> = argument
+        .get_default_values()
+        .iter()
+        .filter(|_| argument.get_action().takes_values())
+        .map(|value| escape(&value.to_string_lossy()))
+        .collect();
+    if !defaults.is_empty() {
+        let _ = write!(help, "\n.br\nDefault: {}.", defaults.join(", "));
+    }
+
+    help
+}
+
+/// Return the name of the value of `argument`, ex. `ADDRESS`.
+fn value_name(argument: &Arg) -> String {
+    let name = match argument.get_value_names() {
+        Some([name, ..]) => name.to_string(),
+        _ => argument.get_id().as_str().to_uppercase(),
+    };
+
+    escape(&name)
+}
+
+/// Return whether `argument` should be documented.
+fn is_visible(argument: &Arg) -> bool {
+    !argument.is_hide_set()
+}
+
+/// Return `text` escaped for roff, with blank lines separating paragraphs.
+fn paragraphs(text: &str) -> String {
+    text.split("\n\n")
+        .map(|paragraph| escape(paragraph.trim()))
+        .collect::<Vec<_>>()
+        .join("\n.PP\n")
+}
+
+/// Return `text` escaped for roff, so that it is printed as written.
+fn escape(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let line = line.replace('\\', "\\e").replace('-', "\\-");
+            // Lines beginning with a period or apostrophe would be read as requests
+            if line.starts_with(['.', '\'']) {
+                format!("\\&{line}")
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,752 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+// Copyright © 2024 Jaxydog
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Printing results as text, JSON, CSV, TSV, or an aligned table.
+
+use std::{
+    collections::HashMap,
+    env,
+    io::{self, IsTerminal, Write},
+    sync::OnceLock,
+};
+
+use ip_geo::{continent::Continent, country::UNKNOWN_COUNTRY_CODE, country_list::Country, Error};
+use serde::Serialize;
+
+use crate::arguments::{ColorChoice, OutputFormat, OutputOptions};
+
+/// The header row of lookup results printed as CSV or TSV.
+const LOOKUP_COLUMNS: [&str; 8] = [
+    "ip",
+    "country_code",
+    "country_name",
+    "continent",
+    "longitude",
+    "latitude",
+    "error",
+    "source",
+];
+
+/// The header row of lookup results printed as a table.
+const TABLE_COLUMNS: [&str; 4] = ["IP", "CODE", "COUNTRY", "CONTINENT"];
+
+/// Separates the fields of table rows rendered by [`LookupResult::render`], which are only
+/// aligned once every row is known.
+const TABLE_SEPARATOR: char = '\u{1f}';
+
+/// Which lookup results are printed, by the code of their country.
+#[derive(Debug, Clone, Default)]
+pub struct CountryFilter {
+    /// If not empty, only results associated with one of these countries are printed.
+    pub include: Vec<Box<str>>,
+    /// Results associated with any of these countries are never printed.
+    pub exclude: Vec<Box<str>>,
+}
+
+impl CountryFilter {
+    /// Return whether `result` should be printed.
+    ///
+    /// Country codes are matched case-insensitively, and results without a country are only
+    /// printed if no countries are included.
+    pub fn allows(&self, result: &LookupResult) -> bool {
+        let matches = |codes: &[Box<str>]| {
+            result
+                .country_code()
+                .is_some_and(|code| codes.iter().any(|c| c.eq_ignore_ascii_case(code)))
+        };
+
+        (self.include.is_empty() || matches(&self.include)) && !matches(&self.exclude)
+    }
+}
+
+/// The result of looking up one address.
+#[derive(Serialize, Debug)]
+pub struct LookupResult<'a> {
+    /// The address, as it was given (or as it was resolved from `host`).
+    ip: &'a str,
+    /// The hostname that the address was resolved from, if it was given one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    host: Option<&'a str>,
+    /// The country associated with the address, if one was found.
+    country: Option<Country>,
+    /// Why no country could be found, if it was not because the address has no associated
+    /// country.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<Box<str>>,
+    /// Where the country came from, if not the databases (ex. `rdap`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<&'static str>,
+}
+
+/// The `source` of countries reported by RDAP, rather than found in the databases.
+const RDAP_SOURCE: &str = "rdap";
+
+impl<'a> LookupResult<'a> {
+    /// Create a new `LookupResult` from the result of looking up `ip`.
+    pub fn new(ip: &'a str, result: Result<Country, Error>) -> Self {
+        let (country, error) = match result {
+            Ok(country) => (Some(country), None),
+            Err(Error::NoValueFound) => (None, None),
+            Err(error) => (None, Some(error.to_string().into())),
+        };
+
+        Self {
+            ip,
+            host: None,
+            country,
+            error,
+            source: None,
+        }
+    }
+
+    /// Create a new `LookupResult` from the country RDAP reports for `ip`, after the databases
+    /// could not place it.
+    pub fn rdap(ip: &'a str, result: Result<Option<Country>, String>) -> Self {
+        let (country, error) = match result {
+            Ok(country) => (country, None),
+            Err(error) => (None, Some(error.into())),
+        };
+
+        Self {
+            ip,
+            host: None,
+            source: country.as_ref().map(|_| RDAP_SOURCE),
+            country,
+            error,
+        }
+    }
+
+    /// Record that the address was resolved from `host`.
+    pub const fn with_host(mut self, host: &'a str) -> Self {
+        self.host = Some(host);
+        self
+    }
+
+    /// Create a new `LookupResult` for input that is not a valid address.
+    pub fn invalid(ip: &'a str) -> Self {
+        Self {
+            ip,
+            host: None,
+            country: None,
+            error: Some(format!("Invalid address: {ip}").into()),
+            source: None,
+        }
+    }
+
+    /// Create a new `LookupResult` for a hostname that could not be resolved.
+    pub fn unresolved(host: &'a str, error: &io::Error) -> Self {
+        Self {
+            ip: host,
+            host: Some(host),
+            country: None,
+            error: Some(format!("Could not resolve {host}: {error}").into()),
+            source: None,
+        }
+    }
+
+    /// Return whether the input was a valid address that could be looked up.
+    pub const fn is_valid(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Return whether a country other than the unknown country (`??`) was found.
+    pub fn is_found(&self) -> bool {
+        self.country_code()
+            .is_some_and(|code| code != UNKNOWN_COUNTRY_CODE)
+    }
+
+    /// Return the code of the country associated with the address, if one was found.
+    pub fn country_code(&self) -> Option<&str> {
+        self.country.as_ref().map(|country| country.code.as_ref())
+    }
+
+    /// Write the result to `writer` in the format of `options`: a line of text (ex. `BE Belgium`,
+    /// preceded by the address if it was resolved from a hostname and followed by `(from RDAP)` if
+    /// the country came from RDAP), a line of JSON, an indented element of a JSON array preceded
+    /// by a comma, a row of CSV or TSV, or an unaligned table row.
+    ///
+    /// With `--null`, lines and rows (but not table rows) end with NUL instead of a newline.
+    pub fn render(&self, mut writer: impl Write, options: OutputOptions) -> io::Result<()> {
+        if let Some(template) = options.template {
+            template.render(&mut writer, self)?;
+            return end_record(writer, options);
+        }
+
+        match (options.format, options.pretty) {
+            (OutputFormat::Text, _) => self.render_text(writer, options),
+            (OutputFormat::Json, false) => {
+                serde_json::to_writer(&mut writer, self)?;
+                end_record(writer, options)
+            }
+            (OutputFormat::Json, true) => {
+                let json = serde_json::to_string_pretty(self)?;
+                write!(writer, ",\n  {}", json.replace('\n', "\n  "))
+            }
+            (OutputFormat::Csv, _) => self.render_record(writer, ',', options),
+            (OutputFormat::Tsv, _) => self.render_record(writer, '\t', options),
+            (OutputFormat::Table, _) => self.render_table_row(writer),
+        }
+    }
+
+    /// Write the result to `writer` as a line of text, colored and with flags if `options` asks
+    /// for them.
+    fn render_text(&self, mut writer: impl Write, options: OutputOptions) -> io::Result<()> {
+        let color = options.color.enabled();
+
+        if self.host.is_some() && self.is_valid() {
+            write!(writer, "{} ", self.ip)?;
+        }
+
+        match (&self.country, &self.error) {
+            (Some(country), _) => {
+                write_country(&mut writer, country, options)?;
+                if self.source == Some(RDAP_SOURCE) {
+                    write!(writer, " {}", paint("(from RDAP)", DIM, color))?;
+                }
+            }
+            (None, Some(error)) => write!(writer, "{}", paint(error, RED, color))?,
+            (None, None) => write!(writer, "{}", paint("No country found!", DIM, color))?,
+        }
+
+        end_record(writer, options)
+    }
+
+    /// Write the result to `writer` as a row of fields separated by `TABLE_SEPARATOR`, in the
+    /// order of `TABLE_COLUMNS`.
+    fn render_table_row(&self, mut writer: impl Write) -> io::Result<()> {
+        let country = self.country.as_ref();
+        let continent = country
+            .and_then(|country| Continent::from_country_code(&country.code))
+            .map_or("-", |continent| continent.code());
+        let name = match (country, &self.error) {
+            (Some(country), _) => &country.name,
+            (None, Some(error)) => error.as_ref(),
+            (None, None) => "No country found",
+        };
+
+        // Control characters (including the separator) would break the table's alignment
+        let ip: String = self
+            .ip
+            .chars()
+            .map(|char| {
+                if char.is_control() {
+                    char::REPLACEMENT_CHARACTER
+                } else {
+                    char
+                }
+            })
+            .collect();
+        let code = country.map_or("-", |country| &country.code);
+        let source = if self.source == Some(RDAP_SOURCE) {
+            " (from RDAP)"
+        } else {
+            ""
+        };
+
+        writeln!(
+            writer,
+            "{ip}{TABLE_SEPARATOR}{code}{TABLE_SEPARATOR}{name}{source}{TABLE_SEPARATOR}{continent}"
+        )
+    }
+
+    /// Write the result to `writer` as a row of values separated by `delimiter`, in the order of
+    /// `LOOKUP_COLUMNS`.
+    fn render_record(
+        &self,
+        mut writer: impl Write,
+        delimiter: char,
+        options: OutputOptions,
+    ) -> io::Result<()> {
+        let country = self.country.as_ref();
+        let continent = country
+            .and_then(|country| Continent::from_country_code(&country.code))
+            .map(|continent| continent.code());
+        let (longitude, latitude) = country
+            .map(|country| {
+                let (longitude, latitude) = country.coordinates;
+                (longitude.to_string(), latitude.to_string())
+            })
+            .unzip();
+
+        write_fields(
+            &mut writer,
+            delimiter,
+            &[
+                self.ip,
+                country.map_or("", |country| &country.code),
+                country.map_or("", |country| &country.name),
+                continent.unwrap_or_default(),
+                longitude.as_deref().unwrap_or_default(),
+                latitude.as_deref().unwrap_or_default(),
+                self.error.as_deref().unwrap_or_default(),
+                self.source.unwrap_or_default(),
+            ],
+        )?;
+        end_record(writer, options)
+    }
+}
+
+/// The placeholders of [`Template`]s, with the fields they are replaced by and a description for
+/// `--help`.
+pub const PLACEHOLDERS: [(&str, Field, &str); 11] = [
+    ("ip", Field::Ip, "the address"),
+    (
+        "host",
+        Field::Host,
+        "the hostname the address was resolved from",
+    ),
+    ("code", Field::Code, "the country code, ex. `BE`"),
+    ("name", Field::Name, "the country name, ex. `Belgium`"),
+    (
+        "continent",
+        Field::Continent,
+        "the continent code, ex. `EU`",
+    ),
+    (
+        "longitude",
+        Field::Longitude,
+        "the longitude of the country",
+    ),
+    ("latitude", Field::Latitude, "the latitude of the country"),
+    ("flag", Field::Flag, "the flag emoji of the country"),
+    (
+        "found",
+        Field::Found,
+        "`true` if a country was found, else `false`",
+    ),
+    (
+        "source",
+        Field::Source,
+        "where the country came from, if not the databases",
+    ),
+    ("error", Field::Error, "why no country could be found"),
+];
+
+/// A field of a lookup result that a [`Template`] can print.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Ip,
+    Host,
+    Code,
+    Name,
+    Continent,
+    Longitude,
+    Latitude,
+    Flag,
+    Found,
+    Source,
+    Error,
+}
+
+/// A part of a [`Template`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(Box<str>),
+    Field(Field),
+}
+
+/// A user-defined line of lookup output, ex. `{ip}\t{code}`, where each placeholder in braces is
+/// replaced by a field of the result (or nothing, if the result does not have it).
+///
+/// `{{` and `}}` print literal braces, and `\t`, `\n`, and `\\` print a tab, a newline, and a
+/// backslash. Each result is followed by a newline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    /// Parse `template`, returning a description of the first problem if it is not valid.
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars();
+
+        while let Some(char) = chars.next() {
+            match char {
+                '\\' => literal.push(match chars.next() {
+                    Some('t') => '\t',
+                    Some('n') => '\n',
+                    Some('\\') => '\\',
+                    Some(other) => return Err(format!("unknown escape `\\{other}`")),
+                    None => return Err("the template ends with a lone `\\`".to_owned()),
+                }),
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '}' => return Err("unmatched `}`, write `}}` for a literal brace".to_owned()),
+                '{' => {
+                    let rest = chars.as_str();
+                    let Some(end) = rest.find('}') else {
+                        return Err("unclosed `{`, write `{{` for a literal brace".to_owned());
+                    };
+                    let name = &rest[..end];
+                    let Some((_, field, _)) = PLACEHOLDERS.iter().find(|(key, ..)| *key == name)
+                    else {
+                        let names: Vec<&str> =
+                            PLACEHOLDERS.iter().map(|(name, ..)| *name).collect();
+                        return Err(format!(
+                            "unknown placeholder `{{{name}}}`, expected one of {{{}}}",
+                            names.join("}, {")
+                        ));
+                    };
+
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(literal.as_str().into()));
+                        literal.clear();
+                    }
+                    segments.push(Segment::Field(*field));
+                    chars = rest[end + 1..].chars();
+                }
+                _ => literal.push(char),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal.into()));
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Write `result` to `writer` following the template, without ending the line.
+    pub fn render(&self, mut writer: impl Write, result: &LookupResult) -> io::Result<()> {
+        let country = result.country.as_ref();
+
+        for segment in &self.segments {
+            let field = match segment {
+                Segment::Literal(literal) => {
+                    writer.write_all(literal.as_bytes())?;
+                    continue;
+                }
+                Segment::Field(field) => field,
+            };
+
+            match field {
+                Field::Ip => write!(writer, "{}", result.ip)?,
+                Field::Host => write!(writer, "{}", result.host.unwrap_or_default())?,
+                Field::Code => write!(writer, "{}", country.map_or("", |country| &country.code))?,
+                Field::Name => write!(writer, "{}", country.map_or("", |country| &country.name))?,
+                Field::Continent => write!(
+                    writer,
+                    "{}",
+                    country
+                        .and_then(|country| Continent::from_country_code(&country.code))
+                        .map_or("", |continent| continent.code())
+                )?,
+                Field::Longitude => {
+                    if let Some(country) = country {
+                        write!(writer, "{}", country.coordinates.0)?;
+                    }
+                }
+                Field::Latitude => {
+                    if let Some(country) = country {
+                        write!(writer, "{}", country.coordinates.1)?;
+                    }
+                }
+                Field::Flag => {
+                    if let Some(flag) = country.and_then(|country| flag(&country.code)) {
+                        write!(writer, "{flag}")?;
+                    }
+                }
+                Field::Found => write!(writer, "{}", result.is_found())?,
+                Field::Source => write!(writer, "{}", result.source.unwrap_or_default())?,
+                Field::Error => write!(writer, "{}", result.error.as_deref().unwrap_or_default())?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse a template for `--format`, for [`OutputOptions::template`].
+///
+/// The template is leaked, which keeps `OutputOptions` `Copy`, as it is parsed once per run.
+pub fn parse_template(template: &str) -> Result<&'static Template, String> {
+    Template::parse(template).map(|template| &*Box::leak(Box::new(template)))
+}
+
+/// The SGR parameters that colored country codes are chosen from, so that each country is always
+/// the same color: bold red, green, yellow, blue, magenta, and cyan.
+const COUNTRY_COLORS: [&str; 6] = ["1;31", "1;32", "1;33", "1;34", "1;35", "1;36"];
+
+/// The SGR parameter for errors: red.
+const RED: &str = "31";
+
+/// The SGR parameter for results without a country and other asides: faint.
+const DIM: &str = "2";
+
+impl ColorChoice {
+    /// Return whether output should be colored: always, never, or (for `auto`) if stdout is a
+    /// terminal and `NO_COLOR` is not set.
+    pub fn enabled(self) -> bool {
+        static AUTO: OnceLock<bool> = OnceLock::new();
+
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => *AUTO.get_or_init(|| {
+                io::stdout().is_terminal() && env::var_os("NO_COLOR").unwrap_or_default().is_empty()
+            }),
+        }
+    }
+}
+
+/// Return `text` wrapped in the ANSI escape sequences for the SGR parameters `style` if `color`,
+/// else `text` unchanged.
+fn paint(text: &str, style: &str, color: bool) -> String {
+    if color {
+        format!("\x1b[{style}m{text}\x1b[0m")
+    } else {
+        text.to_owned()
+    }
+}
+
+/// Return the flag emoji of the country with the ISO 3166-1 alpha-2 code `code`, ex. `🇧🇪` for
+/// `BE`, or `None` if it has none (ex. `??`).
+fn flag(code: &str) -> Option<String> {
+    if code == UNKNOWN_COUNTRY_CODE || code.len() != 2 {
+        return None;
+    }
+
+    // Each letter becomes its regional indicator symbol, which render as a flag in pairs
+    code.chars()
+        .map(|letter| {
+            letter
+                .is_ascii_alphabetic()
+                .then(|| {
+                    char::from_u32(
+                        0x1F1E6 + u32::from(letter.to_ascii_uppercase()) - u32::from('A'),
+                    )
+                })
+                .flatten()
+        })
+        .collect()
+}
+
+/// Write `country` to `writer` as its code and name (ex. `BE Belgium`), preceded by its flag with
+/// `--flags` and with its code colored if `options` colors output.
+pub fn write_country(
+    mut writer: impl Write,
+    country: &Country,
+    options: OutputOptions,
+) -> io::Result<()> {
+    if let Some(flag) = options.flags.then(|| flag(&country.code)).flatten() {
+        write!(writer, "{flag} ")?;
+    }
+
+    let style =
+        COUNTRY_COLORS[country.code.bytes().map(usize::from).sum::<usize>() % COUNTRY_COLORS.len()];
+    write!(
+        writer,
+        "{} {}",
+        paint(&country.code, style, options.color.enabled()),
+        country.name
+    )
+}
+
+/// Writes results rendered by [`LookupResult::render`], enclosing them in an array for
+/// `--output json --pretty`, beginning them with a header row for CSV and TSV, or collecting them
+/// into an aligned table.
+pub struct ResultWriter<W: Write> {
+    writer: W,
+    options: OutputOptions,
+    empty: bool,
+    /// The rows of the table, if results are printed as a table.
+    table: String,
+}
+
+impl<W: Write> ResultWriter<W> {
+    /// Create a new `ResultWriter`, opening the array or writing the header row if there is one.
+    pub fn new(mut writer: W, options: OutputOptions) -> io::Result<Self> {
+        if is_array(options) {
+            write!(writer, "[")?;
+        }
+        if let Some(delimiter) = options.format.delimiter() {
+            write_fields(&mut writer, delimiter, &LOOKUP_COLUMNS)?;
+            end_record(&mut writer, options)?;
+        }
+
+        Ok(Self {
+            writer,
+            options,
+            empty: true,
+            table: String::new(),
+        })
+    }
+
+    /// Write results rendered by [`LookupResult::render`] with the same options.
+    pub fn write_rendered(&mut self, rendered: &[u8]) -> io::Result<()> {
+        // Array elements are rendered after a comma, which the first element goes without
+        let rendered = match rendered.split_first() {
+            Some((b',', rest)) if self.empty && is_array(self.options) => rest,
+            _ => rendered,
+        };
+        self.empty &= rendered.is_empty();
+
+        if self.options.format == OutputFormat::Table {
+            // Rendered from `&str`s, so always valid UTF-8
+            self.table.push_str(&String::from_utf8_lossy(rendered));
+            return Ok(());
+        }
+
+        self.writer.write_all(rendered)
+    }
+
+    /// Close the array if there is one or print the table if there is one, and flush the writer.
+    pub fn finish(mut self) -> io::Result<()> {
+        if is_array(self.options) {
+            let newline = if self.empty { "" } else { "\n" };
+            writeln!(self.writer, "{newline}]")?;
+        }
+
+        if self.options.format == OutputFormat::Table {
+            let rows: Vec<Vec<&str>> = self
+                .table
+                .lines()
+                .map(|row| row.split(TABLE_SEPARATOR).collect())
+                .collect();
+            write_table(&mut self.writer, &TABLE_COLUMNS, &rows)?;
+
+            if self.options.summary {
+                writeln!(self.writer)?;
+                write_table(
+                    &mut self.writer,
+                    &["COUNT", "CODE", "COUNTRY"],
+                    &summarize(&rows),
+                )?;
+            }
+        }
+
+        self.writer.flush()
+    }
+}
+
+/// For the rows of a table of lookup results, return rows of the number of addresses in each
+/// country (and with no country), from most to least.
+fn summarize(rows: &[Vec<&str>]) -> Vec<Vec<String>> {
+    let mut counts: HashMap<(&str, &str), usize> = HashMap::new();
+    for row in rows {
+        if let [_, code, name, ..] = row[..] {
+            // Rows without a country name an error instead, which would each be counted alone
+            let name = if code == "-" { "No country" } else { name };
+            *counts.entry((code, name)).or_default() += 1;
+        }
+    }
+
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_unstable_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+
+    counts
+        .into_iter()
+        .map(|((code, name), count)| vec![count.to_string(), code.to_owned(), name.to_owned()])
+        .collect()
+}
+
+/// End a lookup result (or the header row before them) written to `writer`, with NUL if `options`
+/// asks for it or else a newline.
+fn end_record(mut writer: impl Write, options: OutputOptions) -> io::Result<()> {
+    match options.null {
+        true => writer.write_all(b"\0"),
+        false => writeln!(writer),
+    }
+}
+
+/// Return whether results are printed as a JSON array.
+const fn is_array(options: OutputOptions) -> bool {
+    matches!(options.format, OutputFormat::Json) && options.pretty
+}
+
+/// Write `value` to `writer` as a line of JSON, or indented JSON if `pretty`.
+pub fn write_json(mut writer: impl Write, value: &impl Serialize, pretty: bool) -> io::Result<()> {
+    if pretty {
+        serde_json::to_writer_pretty(&mut writer, value)?;
+    } else {
+        serde_json::to_writer(&mut writer, value)?;
+    }
+
+    writeln!(writer)
+}
+
+/// Write `fields` to `writer` as a row of values separated by `delimiter`, quoting fields that
+/// contain the delimiter, quotes, or line breaks.
+pub fn write_record(mut writer: impl Write, delimiter: char, fields: &[&str]) -> io::Result<()> {
+    write_fields(&mut writer, delimiter, fields)?;
+    writeln!(writer)
+}
+
+/// Write `fields` to `writer` like [`write_record`], without ending the row.
+fn write_fields(mut writer: impl Write, delimiter: char, fields: &[&str]) -> io::Result<()> {
+    for (index, field) in fields.iter().enumerate() {
+        if index > 0 {
+            write!(writer, "{delimiter}")?;
+        }
+
+        if field.contains([delimiter, '"', '\n', '\r']) {
+            write!(writer, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            writer.write_all(field.as_bytes())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write `header` and `rows` to `writer` as a table, padding each column to the width of its
+/// widest field.
+pub fn write_table<S: AsRef<str>>(
+    mut writer: impl Write,
+    header: &[&str],
+    rows: &[Vec<S>],
+) -> io::Result<()> {
+    let mut widths: Vec<usize> = header.iter().map(|field| field.chars().count()).collect();
+    for row in rows {
+        for (width, field) in widths.iter_mut().zip(row) {
+            *width = (*width).max(field.as_ref().chars().count());
+        }
+    }
+
+    write_table_row(&mut writer, header.iter().copied(), &widths)?;
+    for row in rows {
+        write_table_row(&mut writer, row.iter().map(AsRef::as_ref), &widths)?;
+    }
+
+    Ok(())
+}
+
+/// Write `fields` to `writer` as a row of a table, padding each field but the last to its
+/// column's width.
+fn write_table_row<'f>(
+    mut writer: impl Write,
+    fields: impl ExactSizeIterator<Item = &'f str>,
+    widths: &[usize],
+) -> io::Result<()> {
+    let last = fields.len().saturating_sub(1);
+    for (index, (field, &width)) in fields.zip(widths).enumerate() {
+        let separator = if index > 0 { "  " } else { "" };
+        let width = if index < last { width } else { 0 };
+
+        write!(writer, "{separator}{field:width$}")?;
+    }
+
+    writeln!(writer)
+}
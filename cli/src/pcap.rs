@@ -0,0 +1,364 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+// Copyright © 2024 Jaxydog
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Reading packet captures in the classic libpcap format, for `pcap`, and summarizing their
+//! traffic by the countries of their endpoints.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::{self, Display},
+    io::{self, ErrorKind, Read, Write},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+use ip_geo::GeoDatabase;
+use serde::Serialize;
+
+use crate::output;
+
+/// The magic number of captures with microsecond timestamps.
+const MAGIC_MICROS: u32 = 0xA1B2_C3D4;
+/// The magic number of captures with nanosecond timestamps.
+const MAGIC_NANOS: u32 = 0xA1B2_3C4D;
+/// The first four bytes of captures in the newer pcapng format.
+const MAGIC_PCAPNG: u32 = 0x0A0D_0D0A;
+
+/// The largest packet read, so that corrupt lengths do not exhaust memory.
+const MAX_PACKET_LEN: u32 = 256 * 1024 * 1024;
+
+/// Link types, as in the headers of captures, whose packets `endpoints` understands.
+mod link_type {
+    /// BSD loopback, starting with an address family.
+    pub const NULL: u32 = 0;
+    pub const ETHERNET: u32 = 1;
+    /// Raw IPv4 or IPv6, as written by some versions of libpcap.
+    pub const RAW_OLD: u32 = 12;
+    pub const RAW_OPENBSD: u32 = 14;
+    pub const RAW: u32 = 101;
+    /// Linux "cooked" captures, ex. `tcpdump -i any`.
+    pub const LINUX_SLL: u32 = 113;
+    pub const IPV4: u32 = 228;
+    pub const IPV6: u32 = 229;
+    pub const LINUX_SLL2: u32 = 276;
+}
+
+/// Ethertypes of IPv4, IPv6, and VLAN tags (which are skipped).
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86DD;
+const ETHERTYPE_VLANS: [u16; 3] = [0x8100, 0x88A8, 0x9100];
+
+/// A packet capture being read.
+pub struct Capture<R> {
+    reader: R,
+    big_endian: bool,
+    link_type: u32,
+}
+
+impl<R: Read> Capture<R> {
+    /// Read the header of the capture in `reader`.
+    ///
+    /// Errors if `reader` is not a capture in the classic libpcap format.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut header = [0; 24];
+        reader.read_exact(&mut header)?;
+
+        let magic = u32::from_le_bytes(header[..4].try_into().expect("Four bytes"));
+        let big_endian = match magic {
+            MAGIC_MICROS | MAGIC_NANOS => false,
+            _ if [MAGIC_MICROS, MAGIC_NANOS].contains(&magic.swap_bytes()) => true,
+            MAGIC_PCAPNG => {
+                return Err(invalid(
+                    "pcapng captures are not supported, convert them with `editcap -F pcap`",
+                ))
+            }
+            _ => return Err(invalid("not a pcap capture")),
+        };
+
+        let mut capture = Self {
+            reader,
+            big_endian,
+            link_type: 0,
+        };
+        // The link type shares its field with flags in its upper bits
+        capture.link_type = capture.u32(&header[20..]) & 0x0FFF_FFFF;
+
+        Ok(capture)
+    }
+
+    /// Return the link type of the capture's packets.
+    pub fn link_type(&self) -> u32 {
+        self.link_type
+    }
+
+    /// Read the next packet into `buffer`, returning its length on the wire (which may be longer
+    /// than what was captured), or `None` at the end of the capture.
+    pub fn next_packet(&mut self, buffer: &mut Vec<u8>) -> io::Result<Option<u32>> {
+        let mut header = [0; 16];
+        match self.reader.read_exact(&mut header) {
+            Ok(()) => (),
+            Err(error) if error.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(error) => return Err(error),
+        }
+
+        let captured = self.u32(&header[8..]);
+        let original = self.u32(&header[12..]);
+        if captured > MAX_PACKET_LEN {
+            return Err(invalid("packet length is implausibly large"));
+        }
+
+        buffer.resize(captured as usize, 0);
+        match self.reader.read_exact(buffer) {
+            Ok(()) => Ok(Some(original)),
+            // Captures cut short, ex. by stopping `tcpdump`, are common enough to accept
+            Err(error) if error.kind() == ErrorKind::UnexpectedEof => {
+                tracing::warn!("The capture ends partway through a packet, ignoring it");
+                Ok(None)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Read a `u32` from the start of `bytes` in the byte order of the capture.
+    fn u32(&self, bytes: &[u8]) -> u32 {
+        let bytes = bytes[..4].try_into().expect("Four bytes");
+        if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        }
+    }
+}
+
+/// Return an error for an invalid capture.
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+/// Return the source and destination addresses of `packet`, a packet of the given link type, if
+/// it is an IPv4 or IPv6 packet.
+pub fn endpoints(link_type: u32, packet: &[u8]) -> Option<(IpAddr, IpAddr)> {
+    let u16_at = |offset: usize| -> Option<u16> {
+        Some(u16::from_be_bytes(
+            packet.get(offset..offset + 2)?.try_into().ok()?,
+        ))
+    };
+
+    let (ethertype, offset) = match link_type {
+        link_type::NULL => {
+            // The family is in the byte order of the capturing machine, and IPv6 varies by OS
+            let family = packet.get(..4)?;
+            let family = u32::from_le_bytes(family.try_into().ok()?)
+                .min(u32::from_be_bytes(family.try_into().ok()?));
+            match family {
+                2 => (ETHERTYPE_IPV4, 4),
+                10 | 24 | 28 | 30 => (ETHERTYPE_IPV6, 4),
+                _ => return None,
+            }
+        }
+        link_type::ETHERNET => {
+            let mut offset = 12;
+            while ETHERTYPE_VLANS.contains(&u16_at(offset)?) {
+                offset += 4;
+            }
+            (u16_at(offset)?, offset + 2)
+        }
+        link_type::RAW_OLD | link_type::RAW_OPENBSD | link_type::RAW => {
+            match packet.first()? >> 4 {
+                4 => (ETHERTYPE_IPV4, 0),
+                6 => (ETHERTYPE_IPV6, 0),
+                _ => return None,
+            }
+        }
+        link_type::LINUX_SLL => (u16_at(14)?, 16),
+        link_type::LINUX_SLL2 => (u16_at(0)?, 20),
+        link_type::IPV4 => (ETHERTYPE_IPV4, 0),
+        link_type::IPV6 => (ETHERTYPE_IPV6, 0),
+        _ => return None,
+    };
+
+    let ip = packet.get(offset..)?;
+    match ethertype {
+        ETHERTYPE_IPV4 if ip.first()? >> 4 == 4 => {
+            let source: [u8; 4] = ip.get(12..16)?.try_into().ok()?;
+            let destination: [u8; 4] = ip.get(16..20)?.try_into().ok()?;
+            Some((
+                Ipv4Addr::from(source).into(),
+                Ipv4Addr::from(destination).into(),
+            ))
+        }
+        ETHERTYPE_IPV6 if ip.first()? >> 4 == 6 => {
+            let source: [u8; 16] = ip.get(8..24)?.try_into().ok()?;
+            let destination: [u8; 16] = ip.get(24..40)?.try_into().ok()?;
+            Some((
+                Ipv6Addr::from(source).into(),
+                Ipv6Addr::from(destination).into(),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// The traffic to and from the endpoints in one country.
+#[derive(Serialize, Debug, Clone)]
+struct CountryTraffic {
+    /// The country code, or `-` for endpoints without a country (ex. private addresses).
+    code: Box<str>,
+    name: Box<str>,
+    /// The number of packets with an endpoint in the country.
+    packets: u64,
+    /// The number of bytes of those packets, as on the wire.
+    bytes: u64,
+    /// The number of distinct addresses in the country.
+    endpoints: usize,
+}
+
+/// The traffic of a capture, by the countries of its endpoints.
+#[derive(Serialize, Debug, Clone)]
+pub struct PcapSummary {
+    packets: u64,
+    bytes: u64,
+    /// The number of packets that were IPv4 or IPv6, which are the only ones summarized.
+    ip_packets: u64,
+    /// The traffic of each country, most bytes first.
+    countries: Vec<CountryTraffic>,
+}
+
+/// The header row of summaries printed as CSV, TSV, or a table.
+const PCAP_COLUMNS: [&str; 5] = ["country_code", "country", "packets", "bytes", "endpoints"];
+
+/// Read every packet of the capture in `reader` and summarize its traffic by the countries of
+/// its endpoints in `database`.
+///
+/// Packets between two endpoints in the same country count once for it.
+pub fn summarize(reader: impl Read, database: &GeoDatabase) -> io::Result<PcapSummary> {
+    let mut capture = Capture::new(reader)?;
+    let link_type = capture.link_type();
+    tracing::debug!("Reading a capture of link type {link_type}");
+
+    let mut summary = PcapSummary {
+        packets: 0,
+        bytes: 0,
+        ip_packets: 0,
+        countries: Vec::new(),
+    };
+    let mut countries: BTreeMap<Box<str>, (CountryTraffic, HashSet<IpAddr>)> = BTreeMap::new();
+
+    let mut buffer = Vec::new();
+    while let Some(len) = capture.next_packet(&mut buffer)? {
+        summary.packets += 1;
+        summary.bytes += u64::from(len);
+
+        let Some((source, destination)) = endpoints(link_type, &buffer) else {
+            continue;
+        };
+        summary.ip_packets += 1;
+
+        let mut counted = None;
+        for address in [source, destination] {
+            let (code, name) = match database.lookup(address) {
+                Ok(country) => (country.code.as_ref(), country.name.as_ref()),
+                Err(_) => ("-", "No country"),
+            };
+
+            let (traffic, addresses) = countries.entry(code.into()).or_insert_with(|| {
+                let traffic = CountryTraffic {
+                    code: code.into(),
+                    name: name.into(),
+                    packets: 0,
+                    bytes: 0,
+                    endpoints: 0,
+                };
+                (traffic, HashSet::new())
+            });
+            addresses.insert(address);
+            if counted != Some(code) {
+                traffic.packets += 1;
+                traffic.bytes += u64::from(len);
+                counted = Some(code);
+            }
+        }
+    }
+
+    summary.countries = countries
+        .into_values()
+        .map(|(traffic, addresses)| CountryTraffic {
+            endpoints: addresses.len(),
+            ..traffic
+        })
+        .collect();
+    summary
+        .countries
+        .sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.code.cmp(&b.code)));
+
+    Ok(summary)
+}
+
+impl PcapSummary {
+    /// Return the header row, `PCAP_COLUMNS`.
+    pub fn columns(&self) -> &'static [&'static str] {
+        &PCAP_COLUMNS
+    }
+
+    /// Return a row of fields per country, in the order of `columns`.
+    pub fn rows(&self) -> Vec<Vec<String>> {
+        self.countries
+            .iter()
+            .map(|traffic| {
+                vec![
+                    traffic.code.to_string(),
+                    traffic.name.to_string(),
+                    traffic.packets.to_string(),
+                    traffic.bytes.to_string(),
+                    traffic.endpoints.to_string(),
+                ]
+            })
+            .collect()
+    }
+
+    /// Write the summary to `writer` as a header row and the rows of `rows`, separated by
+    /// `delimiter`.
+    pub fn write_records(&self, mut writer: impl Write, delimiter: char) -> io::Result<()> {
+        output::write_record(&mut writer, delimiter, self.columns())?;
+        for row in self.rows() {
+            let row: Vec<&str> = row.iter().map(String::as_str).collect();
+            output::write_record(&mut writer, delimiter, &row)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for PcapSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} packets ({} bytes), {} of them IPv4 or IPv6",
+            self.packets, self.bytes, self.ip_packets
+        )?;
+
+        for traffic in &self.countries {
+            writeln!(
+                f,
+                "{} ({}): {} packets, {} bytes, {} endpoints",
+                traffic.code, traffic.name, traffic.packets, traffic.bytes, traffic.endpoints
+            )?;
+        }
+
+        Ok(())
+    }
+}
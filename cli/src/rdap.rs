@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+// Copyright © 2024 Jaxydog
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Asking RDAP (the Registration Data Access Protocol) for the country that a regional internet
+//! registry assigned an address to, for addresses that the databases cannot place.
+
+use std::{collections::BTreeMap, net::IpAddr, sync::Arc};
+
+use ip_geo::{
+    country::UNKNOWN_COUNTRY_CODE,
+    country_list::{self, Country},
+    Error,
+};
+use serde::Deserialize;
+
+/// The RDAP bootstrap service that `--rdap-url` defaults to, which redirects queries to the
+/// registry responsible for the address.
+pub const DEFAULT_RDAP_URL: &str = "https://rdap.org/ip/";
+
+/// The part of an RDAP IP network object describing where it was assigned.
+#[derive(Deserialize, Debug)]
+struct Network {
+    /// The ISO 3166-1 alpha-2 code of the country the network was assigned to, if any.
+    country: Option<Box<str>>,
+}
+
+/// Asks an RDAP service for the countries of addresses.
+#[derive(Debug, Clone)]
+pub struct Rdap {
+    /// The URL that addresses are appended to, ex. `https://rdap.org/ip/`.
+    url: Box<str>,
+    countries: BTreeMap<Arc<str>, Country>,
+}
+
+impl Rdap {
+    /// Create a new `Rdap` asking the service at `url`, which addresses are appended to, and
+    /// naming countries in `language`, if given.
+    pub fn new(url: &str, language: Option<&str>) -> Self {
+        let mut countries = country_list::get_countries();
+        if let Some(language) = language {
+            countries = countries
+                .into_iter()
+                .map(|(code, country)| (code, country.localized(language)))
+                .collect();
+        }
+
+        Self {
+            url: url.into(),
+            countries,
+        }
+    }
+
+    /// Return whether the databases' `result` for an address should be replaced by the country
+    /// RDAP reports: if no country was found, or it was found as unknown (`??`).
+    pub fn should_ask(result: &Result<Country, Error>) -> bool {
+        match result {
+            Ok(country) => country.code.as_ref() == UNKNOWN_COUNTRY_CODE,
+            Err(Error::NoValueFound) => true,
+            Err(_) => false,
+        }
+    }
+
+    /// Return the country that the network containing `address` was assigned to, if the
+    /// registry reports one that is known.
+    pub fn country(&self, address: IpAddr) -> Result<Option<Country>, String> {
+        let url = format!("{}{address}", self.url);
+        tracing::debug!("Asking {url} for the country of {address}");
+
+        let response = ureq::get(&url)
+            .set("Accept", "application/rdap+json")
+            .call()
+            .map_err(|error| format!("RDAP query for {address} failed: {error}"))?;
+        let network: Network = serde_json::from_reader(response.into_reader())
+            .map_err(|error| format!("RDAP answered {address} with malformed JSON: {error}"))?;
+
+        Ok(network.country.and_then(|code| {
+            self.countries
+                .get(code.to_ascii_uppercase().as_str())
+                .filter(|country| country.code.as_ref() != UNKNOWN_COUNTRY_CODE)
+                .cloned()
+        }))
+    }
+}
@@ -0,0 +1,165 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+// Copyright © 2024 Jaxydog
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Parsing the lines of `repl` and keeping their history.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// The prompt printed before each line when stdin is a terminal.
+pub const PROMPT: &str = "ip_geo> ";
+
+/// The most lines of history kept.
+const HISTORY_LIMIT: usize = 1000;
+
+/// The commands of `repl`, printed by `:help`.
+pub const HELP: &str = "\
+<address or hostname>...  Look up the country of each address, or of each address a hostname
+                          resolves to
+:stats                    Describe the databases
+:ranges <code>...         Print the CIDR blocks assigned to each country, ex. `:ranges BE NL`
+:history                  Print the numbered lines of history
+!!                        Run the previous line again
+!<number>                 Run a line of history again, ex. `!3`
+:help                     Print this message
+:quit                     Exit, as does Ctrl-D
+";
+
+/// A line read by `repl`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Line<'a> {
+    /// A blank line.
+    Empty,
+    /// Addresses or hostnames to look up.
+    Lookup(Vec<&'a str>),
+    /// `:stats`
+    Stats,
+    /// `:ranges`, with the country codes to print the ranges of.
+    Ranges(Vec<&'a str>),
+    /// `:history`
+    History,
+    /// `:help`
+    Help,
+    /// `:quit` or `:exit`.
+    Quit,
+    /// A command that does not exist.
+    Unknown(&'a str),
+}
+
+/// Parse a line read by `repl`.
+pub fn parse(line: &str) -> Line<'_> {
+    let mut words = line.split_whitespace();
+    let Some(first) = words.next() else {
+        return Line::Empty;
+    };
+
+    let Some(command) = first.strip_prefix(':') else {
+        return Line::Lookup(line.split_whitespace().collect());
+    };
+    match command {
+        "stats" => Line::Stats,
+        "ranges" => Line::Ranges(words.collect()),
+        "history" => Line::History,
+        "help" | "h" | "?" => Line::Help,
+        "quit" | "q" | "exit" => Line::Quit,
+        _ => Line::Unknown(first),
+    }
+}
+
+/// The lines previously read by `repl`, saved to a file (if any) so that they outlive it.
+#[derive(Debug, Default)]
+pub struct History {
+    path: Option<Box<Path>>,
+    lines: Vec<String>,
+}
+
+impl History {
+    /// Read the history saved at `path`, if it exists, keeping only the last `HISTORY_LIMIT`
+    /// lines.
+    ///
+    /// Without a `path`, history is forgotten when `repl` exits.
+    pub fn load(path: Option<Box<Path>>) -> Self {
+        let mut lines: Vec<String> = path
+            .as_deref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|contents| contents.lines().map(String::from).collect())
+            .unwrap_or_default();
+        lines.drain(..lines.len().saturating_sub(HISTORY_LIMIT));
+
+        Self { path, lines }
+    }
+
+    /// Return the lines of history, oldest first.
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// Return `line` with `!!` replaced by the previous line or `!<number>` by that line of
+    /// history (counting from 1, as `:history` does), or an error if there is no such line.
+    pub fn expand(&self, line: &str) -> Result<String, String> {
+        let Some(reference) = line.strip_prefix('!') else {
+            return Ok(line.to_owned());
+        };
+
+        let found = match reference {
+            "!" => self.lines.last(),
+            number => number
+                .parse::<usize>()
+                .ok()
+                .and_then(|number| self.lines.get(number.checked_sub(1)?)),
+        };
+        found
+            .cloned()
+            .ok_or_else(|| format!("No such line of history: {line}"))
+    }
+
+    /// Add `line` to the history, unless it is blank or repeats the previous line, and append it
+    /// to the history file.
+    pub fn push(&mut self, line: &str) -> io::Result<()> {
+        if line.trim().is_empty() || self.lines.last().is_some_and(|last| last == line) {
+            return Ok(());
+        }
+        self.lines.push(line.to_owned());
+        if self.lines.len() > HISTORY_LIMIT {
+            self.lines.remove(0);
+        }
+
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{line}")
+    }
+
+    /// Write the numbered lines of history to `writer`, oldest first.
+    pub fn write(&self, mut writer: impl Write) -> io::Result<()> {
+        for (number, line) in self.lines.iter().enumerate() {
+            writeln!(writer, "{:>5}  {line}", number + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Return the default location of the history file, if the OS has a data directory.
+pub fn default_history_path() -> Option<PathBuf> {
+    Some(dirs::data_dir()?.join(concat!(env!("CARGO_PKG_NAME"), "_history")))
+}
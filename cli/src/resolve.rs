@@ -0,0 +1,244 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+// Copyright © 2024 Jaxydog
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Resolving hostnames to addresses before looking them up, with the system resolver or by asking a
+//! DNS server directly.
+
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket},
+    process,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The port that DNS servers listen on.
+const DNS_PORT: u16 = 53;
+
+/// How long to wait for a DNS server to answer.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The largest answer that will be read, which is the largest UDP message without EDNS.
+const MAX_ANSWER_SIZE: usize = 512;
+
+/// The length of a DNS message header.
+const HEADER_LEN: usize = 12;
+
+/// The `A` record type.
+const TYPE_A: u16 = 1;
+/// The `AAAA` record type.
+const TYPE_AAAA: u16 = 28;
+/// The `IN` (Internet) record class.
+const CLASS_IN: u16 = 1;
+
+/// The `NXDOMAIN` response code, for names that do not exist.
+const NAME_ERROR: u8 = 3;
+
+/// Resolves hostnames to the addresses to look up.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Resolver {
+    /// The DNS server to query for `A` and `AAAA` records, or the system resolver if unset.
+    pub server: Option<SocketAddr>,
+    /// Whether to return every address of a hostname, rather than only the first.
+    pub all: bool,
+}
+
+impl Resolver {
+    /// Return the addresses of `host` (or only the first, unless `self.all`), failing if it has
+    /// none.
+    pub fn resolve(&self, host: &str) -> io::Result<Vec<IpAddr>> {
+        let mut addresses = match self.server {
+            Some(server) => {
+                let mut addresses = query(server, host, TYPE_A)?;
+                addresses.extend(query(server, host, TYPE_AAAA)?);
+                addresses
+            }
+            None => (host, 0)
+                .to_socket_addrs()?
+                .map(|address| address.ip())
+                .collect(),
+        };
+
+        // The system resolver repeats addresses for each socket type
+        let mut seen = Vec::with_capacity(addresses.len());
+        addresses.retain(|address| {
+            let new = !seen.contains(address);
+            seen.push(*address);
+            new
+        });
+
+        if addresses.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "no addresses found",
+            ));
+        }
+        tracing::debug!("Resolved {host} to {addresses:?}");
+        if !self.all {
+            addresses.truncate(1);
+        }
+
+        Ok(addresses)
+    }
+}
+
+/// Parse the address of a DNS server, with or without a port, ex. `1.1.1.1` or `[::1]:5353`.
+pub fn parse_server(server: &str) -> Result<SocketAddr, String> {
+    server
+        .parse()
+        .or_else(|_| {
+            server
+                .parse::<IpAddr>()
+                .map(|address| SocketAddr::new(address, DNS_PORT))
+        })
+        .map_err(|_| format!("`{server}` is not an IP address, with or without a port"))
+}
+
+/// Return whether `input` could be a hostname, ex. `example.com`: dot-separated labels of ASCII
+/// letters, digits, hyphens, and underscores.
+pub fn is_hostname(input: &str) -> bool {
+    let input = input.strip_suffix('.').unwrap_or(input);
+
+    !input.is_empty()
+        && input.len() <= 253
+        && input.split('.').all(|label| {
+            (1..=63).contains(&label.len())
+                && label
+                    .chars()
+                    .all(|char| char.is_ascii_alphanumeric() || char == '-' || char == '_')
+        })
+}
+
+/// Ask `server` for the records of type `record_type` (`A` or `AAAA`) of `host`, returning the
+/// addresses they hold.
+///
+/// A name that does not exist has no addresses.
+fn query(server: SocketAddr, host: &str, record_type: u16) -> io::Result<Vec<IpAddr>> {
+    let local: SocketAddr = match server {
+        SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+        SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+    };
+    let socket = UdpSocket::bind(local)?;
+    socket.set_read_timeout(Some(TIMEOUT))?;
+    socket.connect(server)?;
+
+    // Nothing here needs to be unpredictable, only distinct between queries
+    let id = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.subsec_nanos())
+        .wrapping_add(process::id()) as u16;
+    socket.send(&build_query(id, host, record_type))?;
+
+    let mut answer = [0; MAX_ANSWER_SIZE];
+    let len = socket.recv(&mut answer)?;
+
+    parse_answer(&answer[..len], id, record_type).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{server} sent a malformed or failed answer"),
+        )
+    })
+}
+
+/// Build a recursive query for the records of type `record_type` of `host`.
+fn build_query(id: u16, host: &str, record_type: u16) -> Vec<u8> {
+    let mut query = Vec::with_capacity(HEADER_LEN + host.len() + 6);
+
+    query.extend_from_slice(&id.to_be_bytes());
+    // Recursion desired
+    query.extend_from_slice(&0x0100_u16.to_be_bytes());
+    // One question, and no answers or other records
+    query.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]);
+
+    for label in host.trim_end_matches('.').split('.') {
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    query.push(0);
+
+    query.extend_from_slice(&record_type.to_be_bytes());
+    query.extend_from_slice(&CLASS_IN.to_be_bytes());
+
+    query
+}
+
+/// Parse the addresses in the records of type `record_type` of an answer to the query `id`,
+/// skipping others (ex. `CNAME` records).
+///
+/// Returns `None` if the answer is malformed, answers another query, or reports an error other
+/// than a name that does not exist.
+fn parse_answer(answer: &[u8], id: u16, record_type: u16) -> Option<Vec<IpAddr>> {
+    let header = answer.get(..HEADER_LEN)?;
+    let is_response = header[2] & 0x80 != 0;
+    if u16::from_be_bytes([header[0], header[1]]) != id || !is_response {
+        return None;
+    }
+    match header[3] & 0x0F {
+        0 => (),
+        NAME_ERROR => return Some(Vec::new()),
+        _ => return None,
+    }
+
+    let questions = u16::from_be_bytes([header[4], header[5]]);
+    let answers = u16::from_be_bytes([header[6], header[7]]);
+
+    let mut position = HEADER_LEN;
+    for _ in 0..questions {
+        // The name, then its type and class
+        position = skip_name(answer, position)? + 4;
+    }
+
+    let mut addresses = Vec::new();
+    for _ in 0..answers {
+        position = skip_name(answer, position)?;
+        let fields = answer.get(position..position + 10)?;
+        let kind = u16::from_be_bytes([fields[0], fields[1]]);
+        let len = usize::from(u16::from_be_bytes([fields[8], fields[9]]));
+        position += 10;
+        let data = answer.get(position..position + len)?;
+        position += len;
+
+        if kind != record_type {
+            continue;
+        }
+        match (kind, <[u8; 4]>::try_from(data), <[u8; 16]>::try_from(data)) {
+            (TYPE_A, Ok(octets), _) => addresses.push(Ipv4Addr::from(octets).into()),
+            (TYPE_AAAA, _, Ok(octets)) => addresses.push(Ipv6Addr::from(octets).into()),
+            _ => return None,
+        }
+    }
+
+    Some(addresses)
+}
+
+/// Return the index just past the name starting at `start` of `message`, which may end in a
+/// compression pointer.
+fn skip_name(message: &[u8], start: usize) -> Option<usize> {
+    let mut position = start;
+
+    loop {
+        let len = usize::from(*message.get(position)?);
+
+        match len {
+            0 => return Some(position + 1),
+            // A pointer to the rest of the name elsewhere in the message
+            len if len & 0xC0 == 0xC0 => return Some(position + 2),
+            len if len & 0xC0 != 0 => return None,
+            len => position += 1 + len,
+        }
+    }
+}
@@ -0,0 +1,328 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+// Copyright © 2024 Jaxydog
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Describing the databases, for `db stats` and the REPL's `:stats`.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    fs,
+    io::{self, Write},
+    net::IpAddr,
+    path::Path,
+    process::ExitCode,
+};
+
+use ip_geo::{country_list::Country, IpAddrMap};
+use serde::Serialize;
+
+use crate::{
+    arguments::{Arguments, Command, DbCommand, OutputFormat, OutputOptions},
+    database_paths, first_entry_start, is_stdin, load_maps, output, stdin_contents, Maps,
+    EXIT_DATABASE_ERROR,
+};
+
+/// Print the format, size, entries, countries, coverage, and gaps of each of the databases
+/// (specified in `arguments`).
+pub fn print_stats(arguments: Arguments) -> ExitCode {
+    let Some(Command::Db(DbCommand::Stats { output, countries })) = arguments.command else {
+        unreachable!("`RunType::DbStats` requires `DbCommand::Stats`");
+    };
+
+    let Some((stats, _)) = read_stats(arguments, countries) else {
+        return ExitCode::from(EXIT_DATABASE_ERROR);
+    };
+
+    if let Err(error) = write_stats(&stats, output) {
+        tracing::error!("Failed to print the stats: {error}");
+        return ExitCode::from(EXIT_DATABASE_ERROR);
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Parse both databases (specified in `arguments`) and describe them (with the ranges of each
+/// country if `countries`), returning the parsed databases too.
+///
+/// Returns `None` if either database could not be read, after logging why.
+pub fn read_stats(arguments: Arguments, countries: bool) -> Option<(Stats, Maps)> {
+    let mut files = Vec::with_capacity(2);
+    for (label, path, comment) in database_paths(&arguments) {
+        match DatabaseFile::read(path, comment) {
+            Ok(file) => files.push(file),
+            Err(error) => {
+                tracing::error!("{label}: failed to read {}: {error}", path.display());
+                return None;
+            }
+        }
+    }
+    let [ipv4_file, ipv6_file] = files[..] else {
+        unreachable!("`database_paths` returns both databases");
+    };
+    let (ipv4, ipv6) = load_maps(arguments).ok()?;
+
+    let stats = Stats {
+        ipv4: DatabaseStats::new(
+            &ipv4,
+            ipv4_file,
+            countries,
+            |address| u32::from(address).into(),
+            0,
+            "addresses",
+        ),
+        // IPv6 addresses are too numerous to be meaningful, so count /64 networks instead
+        ipv6: DatabaseStats::new(&ipv6, ipv6_file, countries, u128::from, 64, "/64 networks"),
+    };
+
+    Some((stats, (ipv4, ipv6)))
+}
+
+/// Write `stats` to stdout in the format of `output`.
+pub fn write_stats(stats: &Stats, output: OutputOptions) -> io::Result<()> {
+    match output.format {
+        OutputFormat::Text => {
+            print!("{stats}");
+            Ok(())
+        }
+        OutputFormat::Json => output::write_json(io::stdout().lock(), stats, output.pretty),
+        OutputFormat::Csv | OutputFormat::Tsv => stats.write_records(
+            io::stdout().lock(),
+            output
+                .format
+                .delimiter()
+                .expect("CSV and TSV have delimiters"),
+        ),
+        OutputFormat::Table => {
+            output::write_table(io::stdout().lock(), stats.columns(), &stats.rows())
+        }
+    }
+}
+
+/// The size and detected format of a database file.
+#[derive(Debug, Clone, Copy)]
+pub struct DatabaseFile {
+    pub size: u64,
+    pub format: &'static str,
+}
+
+impl DatabaseFile {
+    /// Read the size of the database at `path` and detect its format from the first line that is
+    /// not a comment.
+    ///
+    /// The format is `integer` for addresses written as integers (as in Tor's databases),
+    /// `address` for addresses written as text, `empty` for files without entries, and `unknown`
+    /// otherwise.
+    pub fn read(path: &Path, comment: Option<char>) -> io::Result<Self> {
+        let size = match is_stdin(path) {
+            true => stdin_contents()?.len() as u64,
+            false => fs::metadata(path)?.len(),
+        };
+
+        let format = match first_entry_start(path, comment)? {
+            None => "empty",
+            Some(start) if start.parse::<u128>().is_ok() => "integer",
+            Some(start) if start.parse::<IpAddr>().is_ok() => "address",
+            Some(_) => "unknown",
+        };
+
+        Ok(Self { size, format })
+    }
+}
+
+/// The format, size, entries, countries, coverage, and gaps of each database.
+#[derive(Serialize, Debug)]
+pub struct Stats {
+    pub ipv4: DatabaseStats,
+    pub ipv6: DatabaseStats,
+}
+
+/// The format, size, entries, countries, coverage, and gaps of a database.
+#[derive(Serialize, Debug)]
+pub struct DatabaseStats {
+    pub format: &'static str,
+    /// The size of the database file, in bytes.
+    pub file_size: u64,
+    pub entries: usize,
+    pub countries: usize,
+    pub covered: u128,
+    pub covered_unit: &'static str,
+    /// The number of unassigned ranges between entries.
+    pub gaps: usize,
+    /// The number of entries per country code, if requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country_ranges: Option<BTreeMap<Box<str>, usize>>,
+}
+
+impl DatabaseStats {
+    /// Count the entries, countries, coverage, and gaps of `map`, where `to_int` converts an
+    /// address to an integer and coverage is counted in units of `2 ^ unit_bits` addresses.
+    pub fn new<A: Ord + Copy>(
+        map: &IpAddrMap<A, Country>,
+        file: DatabaseFile,
+        countries: bool,
+        to_int: impl Fn(A) -> u128,
+        unit_bits: u32,
+        covered_unit: &'static str,
+    ) -> Self {
+        let covered = map
+            .iter()
+            .map(|entry| {
+                (to_int(*entry.end()) >> unit_bits) - (to_int(*entry.start()) >> unit_bits) + 1
+            })
+            .fold(0, u128::saturating_add);
+
+        let gaps = map
+            .iter()
+            .zip(map.iter().skip(1))
+            .filter(|(previous, next)| {
+                to_int(*previous.end())
+                    .checked_add(1)
+                    .is_some_and(|after| after < to_int(*next.start()))
+            })
+            .count();
+
+        let mut country_ranges = BTreeMap::new();
+        for entry in map.iter() {
+            *country_ranges
+                .entry(Box::from(&*entry.value().code))
+                .or_insert(0) += 1;
+        }
+
+        Self {
+            format: file.format,
+            file_size: file.size,
+            entries: map.len(),
+            countries: country_ranges.len(),
+            covered,
+            covered_unit,
+            gaps,
+            country_ranges: if countries {
+                Some(country_ranges)
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Return the country codes and number of entries of each country, most entries first.
+    pub fn ranked_countries(&self) -> Vec<(&str, usize)> {
+        let mut countries: Vec<(&str, usize)> = self
+            .country_ranges
+            .iter()
+            .flatten()
+            .map(|(code, ranges)| (code.as_ref(), *ranges))
+            .collect();
+        countries.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+        countries
+    }
+}
+
+/// The header row of stats printed as CSV, TSV, or a table.
+const STATS_COLUMNS: [&str; 8] = [
+    "database",
+    "format",
+    "file_size",
+    "entries",
+    "countries",
+    "covered",
+    "covered_unit",
+    "gaps",
+];
+
+/// The header row of per-country stats printed as CSV, TSV, or a table.
+const STATS_COUNTRY_COLUMNS: [&str; 3] = ["database", "country_code", "ranges"];
+
+impl Stats {
+    /// Return each database with its label.
+    fn databases(&self) -> [(&'static str, &DatabaseStats); 2] {
+        [("ipv4", &self.ipv4), ("ipv6", &self.ipv6)]
+    }
+
+    /// Return the header row, which is `STATS_COUNTRY_COLUMNS` if per-country stats were
+    /// requested, and `STATS_COLUMNS` otherwise.
+    fn columns(&self) -> &'static [&'static str] {
+        if self.ipv4.country_ranges.is_some() {
+            &STATS_COUNTRY_COLUMNS
+        } else {
+            &STATS_COLUMNS
+        }
+    }
+
+    /// Return a row of fields per database (or per country of each database), in the order of
+    /// `columns`.
+    fn rows(&self) -> Vec<Vec<String>> {
+        let mut rows = Vec::new();
+
+        for (label, database) in self.databases() {
+            if database.country_ranges.is_some() {
+                for (code, ranges) in database.ranked_countries() {
+                    rows.push(vec![label.to_owned(), code.to_owned(), ranges.to_string()]);
+                }
+            } else {
+                rows.push(vec![
+                    label.to_owned(),
+                    database.format.to_owned(),
+                    database.file_size.to_string(),
+                    database.entries.to_string(),
+                    database.countries.to_string(),
+                    database.covered.to_string(),
+                    database.covered_unit.to_owned(),
+                    database.gaps.to_string(),
+                ]);
+            }
+        }
+
+        rows
+    }
+
+    /// Write the stats to `writer` as a header row and the rows of `rows`, separated by
+    /// `delimiter`.
+    fn write_records(&self, mut writer: impl Write, delimiter: char) -> io::Result<()> {
+        output::write_record(&mut writer, delimiter, self.columns())?;
+        for row in self.rows() {
+            let row: Vec<&str> = row.iter().map(String::as_str).collect();
+            output::write_record(&mut writer, delimiter, &row)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (label, database) in [("IPv4", &self.ipv4), ("IPv6", &self.ipv6)] {
+            writeln!(
+                f,
+                "{label} ({} format, {} bytes): {} entries, {} countries, {} {}, {} gaps",
+                database.format,
+                database.file_size,
+                database.entries,
+                database.countries,
+                database.covered,
+                database.covered_unit,
+                database.gaps
+            )?;
+            for (code, ranges) in database.ranked_countries() {
+                writeln!(f, "  {code}: {ranges} ranges")?;
+            }
+        }
+
+        Ok(())
+    }
+}
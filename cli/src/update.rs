@@ -0,0 +1,287 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+// Copyright © 2024 Jaxydog
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Downloading databases, for `db download` (MaxMind databases, with [`ip_geo::update`]) and
+//! `db update` (the databases at `--ipv4-url` and `--ipv6-url`).
+
+use std::{
+    env,
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+    process::{self, ExitCode},
+};
+
+use ip_geo::{
+    manifest::Manifest,
+    update::{MaxMindUpdater, UpdateOutcome},
+};
+
+use crate::{
+    arguments::{Arguments, Command, DbCommand},
+    database_paths,
+    verify::{verify_database, ParseFile},
+    EXIT_DATABASE_ERROR, EXIT_USAGE,
+};
+
+/// Download the MaxMind databases (specified in `arguments`).
+pub fn download_databases(arguments: Arguments) -> ExitCode {
+    let Some(Command::Db(DbCommand::Download {
+        account_id,
+        license_key,
+        editions,
+        directory,
+        keep,
+    })) = arguments.command
+    else {
+        unreachable!("`RunType::DbDownload` requires `DbCommand::Download`");
+    };
+
+    let Some(license_key) = license_key.or_else(|| {
+        env::var("MAXMIND_LICENSE_KEY")
+            .ok()
+            .map(String::into_boxed_str)
+    }) else {
+        tracing::error!("A license key is required, with `--license-key` or `MAXMIND_LICENSE_KEY`");
+        return ExitCode::from(EXIT_USAGE);
+    };
+
+    let updater = MaxMindUpdater::new(account_id, license_key, directory).keep(keep);
+    let mut code = ExitCode::SUCCESS;
+
+    for edition in editions.iter() {
+        match updater.update(edition) {
+            Ok(UpdateOutcome::Updated { path, sha256 }) => {
+                tracing::info!(
+                    "{edition}: downloaded {} (SHA-256 {sha256})",
+                    path.display()
+                )
+            }
+            Ok(UpdateOutcome::UpToDate { path }) => {
+                tracing::info!("{edition}: {} is up to date", path.display())
+            }
+            Err(error) => {
+                tracing::error!("{edition}: failed to download: {error}");
+                code = ExitCode::from(EXIT_DATABASE_ERROR);
+            }
+        }
+    }
+
+    code
+}
+
+/// Download the databases (specified in `arguments`) from their URLs, check them, and replace the
+/// local copies that changed, then signal a running server to reload them.
+///
+/// If any download fails or has problems, neither database is replaced.
+pub fn update_databases(arguments: Arguments) -> ExitCode {
+    let Some(Command::Db(DbCommand::Update {
+        reload_pid,
+        reload_url,
+        reload_token_file,
+    })) = &arguments.command
+    else {
+        unreachable!("`RunType::DbUpdate` requires `DbCommand::Update`");
+    };
+
+    let [ipv4, ipv6] = database_paths(&arguments);
+    let ipv4_url = arguments
+        .ipv4_url
+        .as_deref()
+        .expect("A URL to download the IPv4 GeoIP database from");
+    let ipv6_url = arguments
+        .ipv6_url
+        .as_deref()
+        .expect("A URL to download the IPv6 GeoIP database from");
+
+    let downloads = [
+        (
+            ipv4,
+            ipv4_url,
+            download_database(
+                ipv4.1,
+                ipv4_url,
+                ipv4.2,
+                ip_geo::ipv4::parse_ipv4_file_with_mode,
+            ),
+        ),
+        (
+            ipv6,
+            ipv6_url,
+            download_database(
+                ipv6.1,
+                ipv6_url,
+                ipv6.2,
+                ip_geo::ipv6::parse_ipv6_file_with_mode,
+            ),
+        ),
+    ];
+
+    if downloads.iter().any(|(_, _, result)| result.is_err()) {
+        for ((label, _, _), _, result) in downloads {
+            match result {
+                Ok(Some((download, _))) => {
+                    let _ = fs::remove_file(download);
+                }
+                Ok(None) => (),
+                Err(error) => tracing::error!("{label}: {error}"),
+            }
+        }
+        tracing::error!("No databases were replaced");
+        return ExitCode::from(EXIT_DATABASE_ERROR);
+    }
+
+    let mut code = ExitCode::SUCCESS;
+    let mut replaced = false;
+
+    for ((label, path, comment), url, result) in downloads {
+        let Ok(Some((download, entries))) = result else {
+            tracing::info!("{label}: {} is up to date", path.display());
+            continue;
+        };
+
+        // Renaming within a directory is atomic, so readers see either the old or new database
+        if let Err(error) = fs::rename(&download, path) {
+            tracing::error!("{label}: failed to replace {}: {error}", path.display());
+            let _ = fs::remove_file(download);
+            code = ExitCode::from(EXIT_DATABASE_ERROR);
+            continue;
+        }
+        tracing::info!(
+            "{label}: replaced {} ({entries} entries from {url})",
+            path.display()
+        );
+        replaced = true;
+
+        // Keep an existing manifest in step, so that `db verify` still passes
+        if Manifest::path_for(path).exists() {
+            let result =
+                Manifest::generate(path, comment, url).and_then(|manifest| manifest.write(path));
+            if let Err(error) = result {
+                tracing::error!("{label}: failed to update manifest: {error}");
+                code = ExitCode::from(EXIT_DATABASE_ERROR);
+            }
+        }
+    }
+
+    if !replaced {
+        return code;
+    }
+
+    if let Some(pid) = reload_pid {
+        match process::Command::new("kill")
+            .args(["-HUP", &pid.to_string()])
+            .status()
+        {
+            Ok(status) if status.success() => tracing::info!("Sent SIGHUP to process {pid}"),
+            Ok(status) => {
+                tracing::error!(
+                    "Failed to send SIGHUP to process {pid}: `kill` exited with {status}"
+                );
+                code = ExitCode::from(EXIT_DATABASE_ERROR);
+            }
+            Err(error) => {
+                tracing::error!("Failed to send SIGHUP to process {pid}: {error}");
+                code = ExitCode::from(EXIT_DATABASE_ERROR);
+            }
+        }
+    }
+
+    if let Some(url) = reload_url {
+        let mut request = ureq::post(url);
+        if let Some(token_file) = reload_token_file {
+            match fs::read_to_string(token_file) {
+                Ok(token) => {
+                    request = request.set("Authorization", &format!("Bearer {}", token.trim()))
+                }
+                Err(error) => {
+                    tracing::error!("Failed to read {}: {error}", token_file.display());
+                    return ExitCode::from(EXIT_DATABASE_ERROR);
+                }
+            }
+        }
+
+        match request.call() {
+            Ok(_) => tracing::info!("Reloaded the server at {url}"),
+            Err(error) => {
+                tracing::error!("Failed to reload the server at {url}: {error}");
+                code = ExitCode::from(EXIT_DATABASE_ERROR);
+            }
+        }
+    }
+
+    code
+}
+
+/// Download the database at `url` next to `path` (as `<path>.download`), returning the download
+/// and its number of entries if it differs from `path`, or `None` if it does not.
+///
+/// The download is deleted if it has any of the problems found by `db verify`, has no entries, or
+/// matches `path`.
+fn download_database<A: Ord + Copy + Display>(
+    path: &Path,
+    url: &str,
+    comment: Option<char>,
+    parse: ParseFile<A>,
+) -> Result<Option<(PathBuf, usize)>, String> {
+    let mut download = path.as_os_str().to_owned();
+    download.push(".download");
+    let download = PathBuf::from(download);
+
+    let result = fetch(url, &download).and_then(|()| {
+        let (entries, problems) = verify_database(&download, comment, parse)
+            .map_err(|error| format!("failed to parse {url}: {error}"))?;
+        if let Some(problem) = problems.first() {
+            return Err(format!(
+                "{url} has {} problems, ex. {problem}",
+                problems.len()
+            ));
+        }
+        if entries == 0 {
+            return Err(format!("{url} has no entries"));
+        }
+
+        let unchanged = fs::read(path)
+            .ok()
+            .is_some_and(|current| fs::read(&download).is_ok_and(|new| new == current));
+
+        Ok((!unchanged).then_some(entries))
+    });
+
+    match result {
+        Ok(Some(entries)) => Ok(Some((download, entries))),
+        Ok(None) | Err(_) => {
+            let _ = fs::remove_file(&download);
+            result.map(|_| None)
+        }
+    }
+}
+
+/// Download `url` to `destination`.
+fn fetch(url: &str, destination: &Path) -> Result<(), String> {
+    let response = ureq::get(url)
+        .call()
+        .map_err(|error| format!("failed to download {url}: {error}"))?;
+
+    fs::File::create(destination)
+        .and_then(|mut file| {
+            io::copy(&mut response.into_reader(), &mut file)?;
+            file.sync_all()
+        })
+        .map_err(|error| format!("failed to write {}: {error}", destination.display()))
+}
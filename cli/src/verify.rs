@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+// Copyright © 2024 Jaxydog
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Checking databases for malformed lines, unknown country codes, unsorted or overlapping ranges,
+//! and mismatched manifests, for `db verify` and before replacing a database with a download.
+
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    fs,
+    io::{self, BufRead},
+    net::IpAddr,
+    path::Path,
+    process::ExitCode,
+};
+
+use ip_geo::{
+    country_list::Country,
+    manifest::Manifest,
+    parse::{ParseMode, ParseReport},
+    Error, IpAddrMap,
+};
+
+use crate::{arguments::Arguments, database_paths, entry_start, EXIT_DATABASE_ERROR};
+
+/// Check each of the databases (specified in `arguments`) for malformed lines, unknown country
+/// codes, and unsorted or overlapping ranges, and against its manifest if it has one, printing each
+/// problem and failing if there are any.
+pub fn verify_databases(arguments: Arguments) -> ExitCode {
+    let [ipv4, ipv6] = database_paths(&arguments);
+    let results = [
+        (
+            ipv4,
+            verify_database(ipv4.1, ipv4.2, ip_geo::ipv4::parse_ipv4_file_with_mode),
+        ),
+        (
+            ipv6,
+            verify_database(ipv6.1, ipv6.2, ip_geo::ipv6::parse_ipv6_file_with_mode),
+        ),
+    ];
+
+    let mut code = ExitCode::SUCCESS;
+
+    for ((label, path, comment), result) in results {
+        let (entries, mut problems) = match result {
+            Ok(result) => result,
+            Err(error) => {
+                tracing::error!("{label}: {}: {error}", path.display());
+                code = ExitCode::from(EXIT_DATABASE_ERROR);
+                continue;
+            }
+        };
+
+        let manifest = if Manifest::path_for(path).exists() {
+            match Manifest::verify(path, comment) {
+                Ok(manifest) => format!(", matches its manifest from {}", manifest.source),
+                Err(error) => {
+                    problems.push(format!("manifest: {error}"));
+                    String::new()
+                }
+            }
+        } else {
+            ", no manifest".to_owned()
+        };
+
+        if problems.is_empty() {
+            tracing::info!(
+                "{label}: {}: OK ({entries} entries{manifest})",
+                path.display()
+            );
+            continue;
+        }
+
+        for problem in &problems {
+            tracing::error!("{label}: {}: {problem}", path.display());
+        }
+        tracing::error!("{label}: {} problems found", problems.len());
+        code = ExitCode::from(EXIT_DATABASE_ERROR);
+    }
+
+    code
+}
+
+/// The signature of `parse_ipv4_file_with_mode` and `parse_ipv6_file_with_mode`.
+pub type ParseFile<A> = fn(
+    Box<Path>,
+    usize,
+    Option<char>,
+    ParseMode,
+) -> Result<(IpAddrMap<A, Country>, ParseReport), Error>;
+
+/// Parse the database at `path` with `parse`, returning its number of entries and a description of
+/// each malformed line, unknown country code, entry that starts before the entry preceding it,
+/// and pair of overlapping entries.
+pub fn verify_database<A: Ord + Copy + Display>(
+    path: &Path,
+    comment: Option<char>,
+    parse: ParseFile<A>,
+) -> Result<(usize, Vec<String>), Error> {
+    let (map, report) = parse(path.into(), 0, comment, ParseMode::Lenient)?;
+
+    // Unknown country codes are reported as malformed lines
+    let mut problems: Vec<String> = report.malformed.iter().map(ToString::to_string).collect();
+    let malformed: HashSet<u64> = report.malformed.iter().map(|line| line.line).collect();
+
+    let mut previous: Option<(u64, u128)> = None;
+    for (index, contents) in io::BufReader::new(fs::File::open(path)?)
+        .lines()
+        .enumerate()
+    {
+        let line = index as u64 + 1;
+        let contents = contents?;
+        let Some(start) = entry_start(&contents, comment).and_then(parse_address) else {
+            continue;
+        };
+        if malformed.contains(&line) {
+            continue;
+        }
+
+        if let Some((previous_line, previous_start)) = previous {
+            if start < previous_start {
+                problems.push(format!(
+                    "line {line}: starts before the entry on line {previous_line}"
+                ));
+            }
+        }
+        previous = Some((line, start));
+    }
+
+    // The map is sorted, so any overlapping entries are adjacent
+    for (previous, next) in map.iter().zip(map.iter().skip(1)) {
+        if next.start() <= previous.end() {
+            problems.push(format!(
+                "{}-{} ({}) overlaps {}-{} ({})",
+                previous.start(),
+                previous.end(),
+                previous.value().code,
+                next.start(),
+                next.end(),
+                next.value().code
+            ));
+        }
+    }
+
+    Ok((map.len(), problems))
+}
+
+/// Parse an address written as an integer (as in Tor's databases) or as text into an integer.
+fn parse_address(address: &str) -> Option<u128> {
+    match address.parse::<IpAddr>() {
+        Ok(IpAddr::V4(address)) => Some(u32::from(address).into()),
+        Ok(IpAddr::V6(address)) => Some(address.into()),
+        Err(_) => address.parse().ok(),
+    }
+}
@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+// Copyright © 2024 Jaxydog
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Re-running commands when the files they read change, by polling their modification times and
+//! sizes.
+
+use std::{
+    fs,
+    path::Path,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+/// How often watched files are checked for changes.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The modification time and size of a file, or `None` if it could not be read.
+type Signature = Option<(SystemTime, u64)>;
+
+/// Watches files for changes.
+#[derive(Debug)]
+pub struct Watcher {
+    paths: Vec<Box<Path>>,
+    signatures: Vec<Signature>,
+}
+
+impl Watcher {
+    /// Create a new `Watcher` of the files at `paths`, as they are now.
+    pub fn new(paths: Vec<Box<Path>>) -> Self {
+        let signatures = paths.iter().map(|path| signature(path)).collect();
+
+        Self { paths, signatures }
+    }
+
+    /// Return the files being watched.
+    pub fn paths(&self) -> &[Box<Path>] {
+        &self.paths
+    }
+
+    /// Return whether any file has been modified, created, or removed since the last check.
+    pub fn changed(&mut self) -> bool {
+        let signatures: Vec<Signature> = self.paths.iter().map(|path| signature(path)).collect();
+        let changed = signatures != self.signatures;
+
+        self.signatures = signatures;
+        changed
+    }
+
+    /// Block until any file has been modified, created, or removed, checking every
+    /// `POLL_INTERVAL`.
+    pub fn wait(&mut self) {
+        while !self.changed() {
+            thread::sleep(POLL_INTERVAL);
+        }
+
+        // Wait for writers to finish, ex. editors saving in several steps
+        thread::sleep(POLL_INTERVAL);
+        self.changed();
+    }
+}
+
+/// Return the modification time and size of the file at `path`, if it can be read.
+fn signature(path: &Path) -> Signature {
+    let metadata = fs::metadata(path).ok()?;
+
+    Some((metadata.modified().ok()?, metadata.len()))
+}
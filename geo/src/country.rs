@@ -20,63 +20,96 @@ use std::{
     str::FromStr,
 };
 
-use crate::{wikidata, Error};
+use crate::{tzdata, wikidata, Error};
 
 /// Represents a country and its ISO 3166-1 alpha-2 code, alongside a Wikidata ID (if available).
 #[derive(Debug, Clone)]
 pub struct Country {
-    pub name: Box<str>,          // Ex. Belgium
-    pub code: Box<str>,          // Ex. BE
-    pub coordinates: (f64, f64), // Ex. (4.668055555, 50.641111111)
+    pub name: Box<str>,                           // Ex. Belgium
+    pub code: Box<str>,                           // Ex. BE
+    pub coordinates: (f64, f64),                  // Ex. (4.668055555, 50.641111111)
+    pub time_zone: Option<Box<str>>,              // Ex. Europe/Brussels
+    pub time_zone_source: Option<TimeZoneSource>, // Ex. Wikidata
+}
+
+/// Where the time zone of a `Country` came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeZoneSource {
+    /// The time zone of the country's capital on Wikidata.
+    Wikidata,
+    /// The nearest time zone in the local time zone database.
+    Tzdata,
 }
 
 impl Country {
     /// Create a new `Country`.
-    pub fn new(code: impl AsRef<str>, name: impl AsRef<str>, coordinates: (f64, f64)) -> Self {
+    pub fn new(
+        code: impl AsRef<str>,
+        name: impl AsRef<str>,
+        coordinates: (f64, f64),
+        time_zone: Option<&str>,
+    ) -> Self {
         Self {
             name: name.as_ref().into(),
             code: code.as_ref().into(),
             coordinates,
+            time_zone: time_zone.map(Into::into),
+            time_zone_source: None,
         }
     }
 
     /// Create a new `Country` from a `CountryPair` and a Wikidata query using `CountryPair.code`.
+    ///
+    /// If Wikidata has no time zone for the country, it falls back to the local time zone
+    /// database.
     pub fn from_pair(pair: &CountryPair) -> Self {
         let name = pair.name.clone();
         let code = pair.code.clone();
         let coordinates = wikidata::query_for_coords_by_code(&code);
+        let (time_zone, time_zone_source) = match wikidata::query_for_time_zone_by_code(&code) {
+            Some(time_zone) => (Some(time_zone), Some(TimeZoneSource::Wikidata)),
+            None => from_tzdata(&code, coordinates),
+        };
 
         Self {
             name,
             code,
             coordinates,
+            time_zone,
+            time_zone_source,
         }
     }
 
     /// Create a new `Country` from a `CountryPair` and a Wikidata query using `id`.
+    ///
+    /// The time zone is sourced from the local time zone database.
     pub fn from_pair_and_id(pair: &CountryPair, id: impl AsRef<str>) -> Self {
         let name = pair.name.clone();
         let code = pair.code.clone();
         let coordinates = wikidata::query_for_coords_by_id(id.as_ref());
+        let (time_zone, time_zone_source) = from_tzdata(&code, coordinates);
 
         Self {
             name,
             code,
             coordinates,
+            time_zone,
+            time_zone_source,
         }
     }
 
-    /// Formats contents as a valid entry of `CountryData` in a `HashMap`.
+    /// Formats contents as a valid entry of `CountryData` in a `BTreeMap`.
     ///
     /// Example usage:
     ///
     /// ```rust
     /// assert_eq!(
-    ///     Country::new("EX", "Example", (1.0, 1.0)).as_rust_map_entry(0).as_ref(),
+    ///     Country::new("EX", "Example", (1.0, 1.0), Some("Etc/UTC")).as_rust_map_entry(0).as_ref(),
     ///     r#"{let ex = Country {
     ///     name: "Example".into(),
     ///     code: "EX".into(),
     ///     coordinates: (1.0, -1.0),
+    ///     time_zone: Some("Etc/UTC".into()),
     /// }; (ex.code.clone(), ex)},
     /// "#
     /// ])
@@ -98,7 +131,7 @@ impl Country {
             str.lines().fold(String::new(), concat).into_boxed_str()
         }
 
-        let (code, name, coordinates) = self.contents_as_strings();
+        let (code, name, coordinates, time_zone) = self.contents_as_strings();
         let code_lower = match self.code.as_ref() {
             "??" => "unknown",
             _ => &format!("c_{}", self.code.to_lowercase()),
@@ -109,23 +142,25 @@ impl Country {
     name: {name},
     code: {code},
     coordinates: {coordinates},
+    time_zone: {time_zone},
 }}; ({code_lower}.code.clone(), {code_lower})}},"#
         );
 
         indent_string(&output, indent)
     }
 
-    /// Returns self as a tuple of four Strings holding string literals: `(code, name)`
+    /// Returns self as a tuple of four Strings holding Rust expressions:
+    /// `(code, name, coordinates, time_zone)`
     ///
     /// Example usage:
     ///
     /// ```
     /// assert_eq!(
-    ///     Country::new("EX", "Example", (1.0, 1.0)).contents_as_strings()
-    ///     ("\"EX\".into()", "\"Example\".into()", "(1.0, 1.0)")
+    ///     Country::new("EX", "Example", (1.0, 1.0), None).contents_as_strings()
+    ///     ("\"EX\".into()", "\"Example\".into()", "(1.0, 1.0)", "None")
     /// );
     /// ```
-    fn contents_as_strings(&self) -> (Box<str>, Box<str>, Box<str>) {
+    fn contents_as_strings(&self) -> (Box<str>, Box<str>, Box<str>, Box<str>) {
         /// Wraps a string in `"` and `.into()`.
         fn str_as_str<T: Display>(str: T) -> Box<str> {
             format!("\"{}\".into()", str).into_boxed_str()
@@ -146,12 +181,21 @@ impl Country {
             format!("({}, {})", fmt_f(tuple.0), fmt_f(tuple.1)).into_boxed_str()
         }
 
+        /// Wraps an optional string in `Some("...".into())`, or returns `None`.
+        fn option_as_str<T: Display>(option: Option<T>) -> Box<str> {
+            match option {
+                Some(str) => format!("Some({})", str_as_str(str)).into_boxed_str(),
+                None => "None".into(),
+            }
+        }
+
         let (code, name, coordinates) = self.as_tuple();
 
         (
             str_as_str(code),
             str_as_str(name),
             f_tuple_as_str(coordinates),
+            option_as_str(self.time_zone.as_ref()),
         )
     }
 
@@ -208,3 +252,12 @@ impl FromStr for CountryPair {
         Ok(Self::new(code, name))
     }
 }
+
+/// Find the time zone of a country in the local time zone database, along with its source if it
+/// has one.
+fn from_tzdata(code: &str, coordinates: (f64, f64)) -> (Option<Box<str>>, Option<TimeZoneSource>) {
+    let time_zone = tzdata::nearest_time_zone(code, coordinates);
+    let source = time_zone.as_ref().map(|_| TimeZoneSource::Tzdata);
+
+    (time_zone, source)
+}
@@ -0,0 +1,134 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+use std::{collections::HashMap, fs};
+
+use serde_json::Value;
+
+use crate::error::Error;
+
+/// The location of the iso-codes list of ISO 3166-1 countries.
+const ISO_3166_1_PATH: &str = "/usr/share/iso-codes/json/iso_3166-1.json";
+
+/// The location of the iso-codes pkg-config file, which holds its version.
+const PKG_CONFIG_PATH: &str = "/usr/share/pkgconfig/iso-codes.pc";
+
+/// The location of the iso-codes translations of ISO 3166-1 names, with `{}` for the language.
+const TRANSLATION_PATH: &str = "/usr/share/locale/{}/LC_MESSAGES/iso_3166-1.mo";
+
+/// The magic number of a GNU gettext message catalog, as read in its own byte order.
+const MO_MAGIC: u32 = 0x950412de;
+
+/// Returns the version of the local iso-codes, ex. "iso-codes 4.15.0".
+pub fn get_version() -> Result<Box<str>, Error> {
+    let pkg_config = fs::read_to_string(PKG_CONFIG_PATH)?;
+
+    pkg_config
+        .lines()
+        .find_map(|line| line.strip_prefix("Version: "))
+        .map(|version| format!("iso-codes {}", version.trim()).into())
+        .ok_or(Error::MissingResults)
+}
+
+/// Returns the English names of each country, keyed by its ISO 3166-1 alpha-2 code.
+///
+/// The common name comes first where there is one (ex. "South Korea" before "Korea, Republic
+/// of"), as it is closest to the names from `location(8)`, but is not always translated.
+pub fn get_names() -> Result<HashMap<Box<str>, Vec<Box<str>>>, Error> {
+    let list: Value = serde_json::from_str(&fs::read_to_string(ISO_3166_1_PATH)?)
+        .map_err(|_| Error::InvalidObject)?;
+
+    let mut names = HashMap::new();
+    for country in list["3166-1"].as_array().ok_or(Error::InvalidArray)? {
+        let code = country["alpha_2"].as_str().ok_or(Error::InvalidString)?;
+        let name = country["name"].as_str().ok_or(Error::InvalidString)?;
+
+        let names = names.entry(code.into()).or_insert_with(Vec::new);
+        if let Some(common_name) = country["common_name"].as_str() {
+            names.push(common_name.into());
+        }
+        names.push(name.into());
+    }
+
+    Ok(names)
+}
+
+/// Returns the translations of English names into `language` (ex. "pt_BR"), keyed by the English
+/// name, from the local iso-codes message catalog.
+pub fn get_translations(language: &str) -> Result<HashMap<Box<str>, Box<str>>, Error> {
+    let catalog = fs::read(TRANSLATION_PATH.replace("{}", language))?;
+
+    parse_mo(&catalog)
+}
+
+/// Parses a GNU gettext message catalog (a `.mo` file) into a map of original strings to their
+/// translations.
+///
+/// Skips the header entry, entries with a context, and all but the first form of plurals, none of
+/// which are used for country names.
+fn parse_mo(catalog: &[u8]) -> Result<HashMap<Box<str>, Box<str>>, Error> {
+    let word = |offset: usize| -> Result<[u8; 4], Error> {
+        catalog
+            .get(offset..offset + 4)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(Error::OutOfBounds)
+    };
+
+    // The catalog is in the byte order of the machine that wrote it
+    let read_u32: fn([u8; 4]) -> u32 = match u32::from_le_bytes(word(0)?) {
+        MO_MAGIC => u32::from_le_bytes,
+        _ if u32::from_be_bytes(word(0)?) == MO_MAGIC => u32::from_be_bytes,
+        _ => return Err(Error::InvalidObject),
+    };
+    let read_usize = |offset: usize| -> Result<usize, Error> {
+        word(offset).map(|bytes| read_u32(bytes) as usize)
+    };
+
+    // Read the `index`th string of the table of (length, offset) pairs starting at `table`
+    let read_string = |table: usize, index: usize| -> Result<&str, Error> {
+        let length = read_usize(table + index * 8)?;
+        let offset = read_usize(table + index * 8 + 4)?;
+        let bytes = catalog
+            .get(offset..offset + length)
+            .ok_or(Error::OutOfBounds)?;
+
+        Ok(core::str::from_utf8(bytes)?)
+    };
+
+    let count = read_usize(8)?;
+    let originals = read_usize(12)?;
+    let translations = read_usize(16)?;
+
+    let mut map = HashMap::with_capacity(count);
+    for index in 0..count {
+        let original = read_string(originals, index)?;
+
+        // The header has an empty original, and contexts are separated by an EOT
+        if original.is_empty() || original.contains('\u{4}') {
+            continue;
+        }
+
+        // Plural forms are separated by NUL
+        let original = original.split('\0').next().unwrap_or(original);
+        let translation = read_string(translations, index)?;
+        let translation = translation.split('\0').next().unwrap_or(translation);
+
+        map.insert(original.into(), translation.into());
+    }
+
+    Ok(map)
+}
@@ -20,14 +20,30 @@ use std::{collections::HashMap, process::Command, str::FromStr};
 use chrono::{SecondsFormat, Utc};
 
 mod country;
-use country::{Country, CountryPair};
+use country::{Country, CountryPair, TimeZoneSource};
 
 mod error;
 use error::Error;
 
+mod iso_codes;
+
+mod tzdata;
+
 mod wikidata;
 
 fn main() {
+    // `geo names` prints the table of localized names instead of the table of countries
+    if std::env::args().nth(1).as_deref() == Some("names") {
+        // Languages with iso-codes translations, as gettext locale names
+        let languages = [
+            "ar", "de", "es", "fr", "hi", "it", "ja", "ko", "nl", "pl", "pt", "pt_BR", "ru", "sv",
+            "tr", "uk", "zh_CN", "zh_TW",
+        ];
+
+        print_country_names_as_rust_array(&languages).unwrap();
+        return;
+    }
+
     // Tor's additions to the database from libloc
     let additional_countries = vec![CountryPair::new("??", "Unknown")];
 
@@ -65,14 +81,14 @@ fn print_country_list_as_code_and_name(countries: &[Country]) {
         .for_each(|c| println!("{} {}", c.code, c.name));
 }
 
-/// Formats prints a list of countries as valid Rust code that returns a `HashMap`.
+/// Formats prints a list of countries as valid Rust code that returns a `BTreeMap`.
 #[allow(dead_code)]
 fn print_country_list_as_rust_hashmap(countries: &[Country], indent: u8) {
-    let location_version = get_location_version().unwrap();
+    let sources = get_sources(countries);
     let date_time = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true); // Ex. 2024-07-21T04:11:07Z
 
     print!(
-        r#"// This file was @generated by ip_geo/geo using {location_version} and Wikidata at {date_time}. Do not edit!
+        r#"// This file was @generated by ip_geo/geo using {sources} at {date_time}. Do not edit!
 
 // SPDX-License-Identifier: AGPL-3.0-or-later
 //
@@ -91,7 +107,7 @@ fn print_country_list_as_rust_hashmap(countries: &[Country], indent: u8) {
 // You should have received a copy of the GNU Affero General Public License along with ip_geo. If
 // not, see <https://www.gnu.org/licenses/>.
 
-use std::{{collections::HashMap, sync::Arc}};
+use alloc::{{collections::BTreeMap, sync::Arc}};
 
 /// Represents a country or other geographic region.
 #[derive(Clone, Debug)]
@@ -108,11 +124,16 @@ pub struct Country {{
     ///
     /// Ex. `(4.668055555, 50.641111111)`.
     pub coordinates: (f64, f64),
+    /// An IANA time zone of the country, if it has one. Countries that span several time zones
+    /// only list one of them.
+    ///
+    /// Ex. "Europe/Brussels" for Belgium.
+    pub time_zone: Option<Arc<str>>,
 }}
 
 /// A map of countries, with the ISO 3166-1 alpha-2 code as the key.
 #[rustfmt::skip]
-pub fn get_countries() -> HashMap<Arc<str>, Country> {{HashMap::from([
+pub fn get_countries() -> BTreeMap<Arc<str>, Country> {{BTreeMap::from([
 "#
     );
 
@@ -134,6 +155,77 @@ pub fn get_countries() -> HashMap<Arc<str>, Country> {{HashMap::from([
     println!("])}}");
 }
 
+/// Formats and prints the names of countries in each of `languages` as valid Rust code, using the
+/// translations from iso-codes.
+fn print_country_names_as_rust_array(languages: &[&str]) -> Result<(), Error> {
+    let iso_codes_version = iso_codes::get_version()?;
+    let date_time = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true); // Ex. 2024-07-21T04:11:07Z
+
+    let translations = languages
+        .iter()
+        .map(|language| iso_codes::get_translations(language))
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let mut names: Vec<_> = iso_codes::get_names()?.into_iter().collect();
+    names.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+    let count = languages.len();
+    print!(
+        r#"// This file was @generated by ip_geo/geo using {iso_codes_version} at {date_time}. Do not edit!
+
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+/// The languages that country names are translated into, as gettext locale names.
+#[rustfmt::skip]
+pub const LANGUAGES: [&str; {count}] = {languages:?};
+
+/// The names of countries in each of `LANGUAGES`, where they have been translated, sorted by ISO
+/// 3166-1 alpha-2 code.
+#[rustfmt::skip]
+pub static NAMES: [(&str, [Option<&str>; {count}]); {}] = [
+"#,
+        names.len()
+    );
+
+    for (code, english) in names {
+        // Take the translation of the first English name that has one
+        let localized: Vec<String> = translations
+            .iter()
+            .map(|translation| {
+                match english.iter().find_map(|name| translation.get(name)) {
+                    // Quote by hand, as `Debug` escapes combining marks
+                    Some(name) => format!(
+                        "Some(\"{}\")",
+                        name.replace('\\', "\\\\").replace('"', "\\\"")
+                    ),
+                    None => "None".to_owned(),
+                }
+            })
+            .collect();
+
+        println!("    ({code:?}, [{}]),", localized.join(", "));
+    }
+
+    println!("];");
+
+    Ok(())
+}
+
 /// Returns a list of countries.
 ///
 /// List sourced from [`location(8)`](https://man-pages.ipfire.org/libloc/location.html)
@@ -150,7 +242,7 @@ fn get_country_list(
     let mut countries = Vec::with_capacity(input.len() + additional_countries.len());
 
     for line in input {
-        if line.len() == 0 {
+        if line.is_empty() {
             continue;
         }
 
@@ -170,7 +262,7 @@ fn get_country_list(
     // For a given `CountryPair`, create a `Country` from it using the appropriate method.
     let from_pair = move |pair: &CountryPair| match pair.code.as_ref() {
         // The pair has no associated country
-        "??" => Country::new(&pair.code, &pair.name, (0.0, 0.0)),
+        "??" => Country::new(&pair.code, &pair.name, (0.0, 0.0), None),
 
         // The pair is a real country or other geographic area
         _ => match nonstandard_countries.get(pair.code.as_ref()) {
@@ -187,6 +279,36 @@ fn get_country_list(
     Ok(countries.into_boxed_slice())
 }
 
+/// Describe the sources that `countries` were actually built from, for the header of the generated
+/// file.
+///
+/// Ex. "location 0.9.16, Wikidata (coordinates and 200 time zones), and tzdata 2025b (47 time
+/// zones)".
+fn get_sources(countries: &[Country]) -> String {
+    let count = |source| {
+        countries
+            .iter()
+            .filter(|country| country.time_zone_source == Some(source))
+            .count()
+    };
+    let from_wikidata = count(TimeZoneSource::Wikidata);
+    let from_tzdata = count(TimeZoneSource::Tzdata);
+
+    let location_version = get_location_version().unwrap();
+    let wikidata = match from_wikidata {
+        0 => "Wikidata (coordinates)".to_owned(),
+        _ => format!("Wikidata (coordinates and {from_wikidata} time zones)"),
+    };
+
+    match from_tzdata {
+        0 => format!("{location_version} and {wikidata}"),
+        _ => format!(
+            "{location_version}, {wikidata}, and {} ({from_tzdata} time zones)",
+            tzdata::get_version()
+        ),
+    }
+}
+
 fn get_location_version() -> Result<Box<str>, Error> {
     let lines = call("location --version")?;
 
@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+use std::{fs, str::FromStr};
+
+/// The location of the IANA time zone database's table of time zones by country.
+const ZONE_TAB_PATH: &str = "/usr/share/zoneinfo/zone.tab";
+
+/// The location of the IANA time zone database's compiled source, which starts with its version.
+const TZDATA_ZI_PATH: &str = "/usr/share/zoneinfo/tzdata.zi";
+
+/// Return the name and version of the local time zone database, ex. "tzdata 2025b", or just
+/// "tzdata" if its version is unknown.
+pub fn get_version() -> Box<str> {
+    // Ex. "# version 2025b"
+    let version = fs::read_to_string(TZDATA_ZI_PATH).ok().and_then(|tzdata| {
+        let version = tzdata.lines().next()?.strip_prefix("# version ")?;
+        Some(format!("tzdata {version}").into_boxed_str())
+    });
+
+    version.unwrap_or_else(|| "tzdata".into())
+}
+
+/// Find the IANA time zone whose principal location is nearest to `coordinates` in the country
+/// `code`, according to the local time zone database.
+///
+/// Used for countries whose time zone is not available from Wikidata.
+pub fn nearest_time_zone(code: &str, coordinates: (f64, f64)) -> Option<Box<str>> {
+    let zone_tab = fs::read_to_string(ZONE_TAB_PATH).ok()?;
+
+    let distance = |(longitude, latitude): (f64, f64)| {
+        (longitude - coordinates.0).powi(2) + (latitude - coordinates.1).powi(2)
+    };
+
+    zone_tab
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            // Ex. "BE\t+5050+00420\tEurope/Brussels"
+            let mut fields = line.split('\t');
+
+            if fields.next()? != code {
+                return None;
+            }
+
+            let location = parse_iso_6709(fields.next()?)?;
+            let zone = fields.next()?;
+
+            Some((distance(location), zone))
+        })
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, zone)| zone.into())
+}
+
+/// Parse a ISO 6709 sign-degrees-minutes(-seconds) string into `(longitude, latitude)`.
+///
+/// Ex. "+5050+00420" -> `(4.333333, 50.833333)`.
+fn parse_iso_6709(location: &str) -> Option<(f64, f64)> {
+    // Parse a signed number of degrees and minutes (and maybe seconds), ex. "+00420"
+    fn parse_part(part: &str, degree_digits: usize) -> Option<f64> {
+        let sign = match part.get(..1)? {
+            "+" => 1.0,
+            "-" => -1.0,
+            _ => return None,
+        };
+        let digits = part.get(1..)?;
+
+        let degrees = f64::from_str(digits.get(..degree_digits)?).ok()?;
+        let minutes = f64::from_str(digits.get(degree_digits..degree_digits + 2)?).ok()?;
+        let seconds = match digits.get(degree_digits + 2..) {
+            Some("") | None => 0.0,
+            Some(seconds) => f64::from_str(seconds).ok()?,
+        };
+
+        Some(sign * (degrees + minutes / 60.0 + seconds / 3600.0))
+    }
+
+    // The latitude ends where the longitude's sign begins
+    let split = location.get(1..)?.find(['+', '-'])? + 1;
+    let (latitude, longitude) = location.split_at(split);
+
+    Some((parse_part(longitude, 3)?, parse_part(latitude, 2)?))
+}
@@ -97,6 +97,36 @@ WHERE {{
     parse_coords(point).ok_or(Error::InvalidPoint).unwrap()
 }
 
+/// Query Wikidata for the IANA time zone of a country's capital based on a two-letter code.
+///
+/// Returns `None` if the capital's time zone has no IANA time zone ID on Wikidata.
+pub fn query_for_time_zone_by_code(code: &str) -> Option<Box<str>> {
+    let query = format!(
+        r#"
+SELECT DISTINCT
+  ?timeZone  # Ex. Europe/Brussels
+WHERE {{
+  VALUES ?inputCode {{
+    """{code}""" # Ex. BE
+  }}
+  
+  ?item p:P297 ?code.         # Get items with country codes
+  ?code ps:P297 ?inputCode.   # Match country code against `?inputCode`
+  ?item wdt:P36 ?capital.     # Get its capital
+  ?capital wdt:P421 ?zone.    # Get the time zone its capital is located in
+  ?zone wdt:P6687 ?timeZone.  # Get the IANA time zone ID of that time zone
+}}
+"#
+    );
+
+    let result = wikidata_query(&query).expect("the result of a Wikidata query");
+
+    result
+        .first()
+        .and_then(|result| get_str_value(result, "timeZone").ok())
+        .map(Into::into)
+}
+
 /// Get the internal string value of a given field that holds a string in a Serde JSON value.
 fn get_str_value<'st>(result: &'st Value, label: &str) -> Result<&'st str, Error> {
     get_value(result, label)?
@@ -0,0 +1,140 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+use std::{net::IpAddr, path::Path};
+
+use pyo3::{
+    exceptions::{PyFileNotFoundError, PyRuntimeError, PyValueError},
+    prelude::*,
+    types::PyDict,
+};
+
+/// Python bindings for `ip_geo::GeoDatabase`.
+///
+/// Example usage:
+///
+/// ```python
+/// from ip_geo import GeoDatabase
+///
+/// database = GeoDatabase("/usr/share/tor/geoip", "/usr/share/tor/geoip6")
+/// database.lookup("1.2.3.4")
+/// # {'code': 'AU', 'name': 'Australia', 'coordinates': (133.0, -25.0),
+/// #  'time_zone': 'Australia/Eucla', 'continent': 'OC', 'is_eu': False}
+/// ```
+#[pyclass(name = "GeoDatabase", module = "ip_geo", frozen)]
+struct PyGeoDatabase {
+    inner: ip_geo::GeoDatabase,
+}
+
+#[pymethods]
+impl PyGeoDatabase {
+    /// Parse the IPv4 and IPv6 databases at the given paths.
+    ///
+    /// Raises `FileNotFoundError` if either database does not exist.
+    #[new]
+    #[pyo3(signature = (
+        ipv4_path = "/usr/share/tor/geoip",
+        ipv6_path = "/usr/share/tor/geoip6",
+        ipv4_len = 200_000,
+        ipv6_len = 60_000,
+        comment = Some('#'),
+    ))]
+    fn new(
+        py: Python<'_>,
+        ipv4_path: &str,
+        ipv6_path: &str,
+        ipv4_len: usize,
+        ipv6_len: usize,
+        comment: Option<char>,
+    ) -> PyResult<Self> {
+        let ipv4_path = existing_path(ipv4_path)?;
+        let ipv6_path = existing_path(ipv6_path)?;
+
+        // Parsing the databases can take a while, so let other Python threads run meanwhile
+        let inner = py.allow_threads(|| {
+            ip_geo::GeoDatabase::new(
+                ip_geo::ipv4::parse_ipv4_file(ipv4_path, ipv4_len, comment),
+                ip_geo::ipv6::parse_ipv6_file(ipv6_path, ipv6_len, comment),
+            )
+        });
+
+        Ok(Self { inner })
+    }
+
+    /// Find the country associated with an IPv4 or IPv6 address.
+    ///
+    /// Returns a dictionary with the keys `code`, `name`, `coordinates`, `time_zone` and
+    /// `continent` (which may be `None`), and `is_eu`, or `None` if no country is associated with
+    /// the address. Raises `ValueError` if `address` is not a valid IP
+    /// address.
+    fn lookup<'py>(&self, py: Python<'py>, address: &str) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let address: IpAddr = address
+            .trim()
+            .parse()
+            .map_err(|_| PyValueError::new_err(format!("invalid IP address '{address}'")))?;
+
+        let country = match self.inner.lookup(address) {
+            Ok(country) => country,
+            Err(ip_geo::Error::NoValueFound) => return Ok(None),
+            Err(error) => return Err(PyRuntimeError::new_err(error.to_string())),
+        };
+
+        let dict = PyDict::new(py);
+        dict.set_item("code", country.code.as_ref())?;
+        dict.set_item("name", country.name.as_ref())?;
+        dict.set_item("coordinates", country.coordinates)?;
+        dict.set_item("time_zone", country.time_zone.as_deref())?;
+        dict.set_item("continent", country.continent().map(|c| c.code()))?;
+        dict.set_item("is_eu", country.is_eu())?;
+
+        Ok(Some(dict))
+    }
+
+    /// The number of IPv4 ranges in the database.
+    #[getter]
+    fn ipv4_len(&self) -> usize {
+        self.inner.ipv4().len()
+    }
+
+    /// The number of IPv6 ranges in the database.
+    #[getter]
+    fn ipv6_len(&self) -> usize {
+        self.inner.ipv6().len()
+    }
+}
+
+/// Convert `path` into a `Path`, raising `FileNotFoundError` if it does not exist.
+fn existing_path(path: &str) -> PyResult<Box<Path>> {
+    let path = Path::new(path);
+
+    match path.exists() {
+        true => Ok(path.into()),
+        false => Err(PyFileNotFoundError::new_err(format!(
+            "could not find database at '{}'",
+            path.display()
+        ))),
+    }
+}
+
+/// IP geolocation using Tor's IP geolocation databases.
+#[pymodule]
+#[pyo3(name = "ip_geo")]
+fn ip_geo_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyGeoDatabase>()?;
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Generating the types and service trait of the gRPC lookup service from `proto/ip_geo.proto`,
+//! when built with the `grpc` feature.
+
+fn main() {
+    #[cfg(feature = "grpc")]
+    compile_protos().expect("a valid `proto/ip_geo.proto`");
+}
+
+/// Compile `proto/ip_geo.proto` into Rust, using `protox` rather than requiring `protoc`.
+#[cfg(feature = "grpc")]
+fn compile_protos() -> Result<(), Box<dyn std::error::Error>> {
+    const PROTO: &str = "proto/ip_geo.proto";
+    println!("cargo::rerun-if-changed={PROTO}");
+
+    let descriptors = protox::compile([PROTO], ["proto"])?;
+    tonic_build::configure()
+        .build_client(false)
+        .compile_fds(descriptors)?;
+
+    Ok(())
+}
@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Logging every HTTP request through `tracing`, as text or JSON.
+
+use std::io::{self, IsTerminal};
+
+use clap::ValueEnum;
+use serde::Deserialize;
+use tracing::Level;
+use warp::log::Info;
+
+use crate::{
+    api::API_VERSION,
+    arguments::Arguments,
+    client::{self, TrustedProxies},
+};
+
+/// Represents the format of the access log.
+#[derive(ValueEnum, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessLogFormat {
+    /// Human-readable lines, ex. `INFO request method=GET path=/healthz status=200 ...`.
+    Text,
+    /// One JSON object per line.
+    Json,
+}
+
+/// For a given set of arguments, install the `tracing` subscriber that writes the access log to
+/// standard output.
+pub fn init(arguments: &Arguments) {
+    // Safety: `arguments::get_config()` implements default values
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(Level::INFO)
+        .with_target(false)
+        .with_ansi(io::stdout().is_terminal());
+
+    let result = match arguments.access_log_format.unwrap() {
+        AccessLogFormat::Text => subscriber.try_init(),
+        AccessLogFormat::Json => subscriber.json().flatten_event(true).try_init(),
+    };
+
+    if let Err(error) = result {
+        eprintln!("Could not set up the access log: {error}");
+    }
+}
+
+/// Record a single request, ex. as given to `warp::log::custom`.
+///
+/// Unless `log_lookups` is set, the addresses in lookup paths are replaced with `{address}`, ex.
+/// `/v0/ipv4/{address}`. The client is logged as named by a PROXY protocol header or by the
+/// `X-Forwarded-For` header of one of `proxies`, if any.
+pub fn record(info: Info, log_lookups: bool, proxies: &TrustedProxies) {
+    let path = match log_lookups {
+        true => info.path().into(),
+        false => redact(info.path()),
+    };
+    let forwarded_for = client::forwarded_for(info.request_headers());
+    let client = proxies
+        .resolve(client::peer(info.remote_addr()), forwarded_for.as_deref())
+        .map_or_else(|| "-".into(), |address| address.to_string());
+
+    tracing::info!(
+        method = %info.method(),
+        path = %path,
+        status = info.status().as_u16(),
+        latency_ms = info.elapsed().as_secs_f64() * 1000.0,
+        client = %client,
+        "request"
+    );
+}
+
+/// Replace the address in a lookup path with `{address}`, ex. `/v0/ipv4/1.2.3.4` becomes
+/// `/v0/ipv4/{address}`.
+fn redact(path: &str) -> Box<str> {
+    let redacted = ["ipv4", "ipv6"].into_iter().find_map(|family| {
+        let prefix = format!("/{API_VERSION}/{family}/");
+        path.starts_with(&prefix)
+            .then(|| format!("{prefix}{{address}}").into_boxed_str())
+    });
+
+    redacted.unwrap_or_else(|| path.into())
+}
@@ -0,0 +1,298 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Allowing or denying clients by address, per group of endpoints, ex. only serving `/metrics` to
+//! `10.0.0.0/8`.
+
+use std::{net::IpAddr, process, sync::Arc};
+
+use ip_geo::cidr::IpCidr;
+use serde::Deserialize;
+use warp::{reject::Reject, Filter, Rejection};
+
+use crate::{
+    arguments::Arguments,
+    auth::Endpoint,
+    client::{self, TrustedProxies},
+    metrics::Metrics,
+};
+
+/// A rule allowing or denying clients by address, as written in the configuration file.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AccessRule {
+    /// The groups of endpoints the rule applies to, or every group if unset.
+    #[serde(default)]
+    pub endpoints: Option<Vec<Endpoint>>,
+    /// The only networks that clients are allowed from, ex. `10.0.0.0/8`, if set.
+    #[serde(default)]
+    pub allow: Option<Vec<Box<str>>>,
+    /// The networks that clients are denied from, even if they are allowed by `allow`.
+    #[serde(default)]
+    pub deny: Option<Vec<Box<str>>>,
+}
+
+/// A parsed [`AccessRule`].
+#[derive(Debug)]
+struct Rule {
+    endpoints: Option<Vec<Endpoint>>,
+    allow: Option<Vec<IpCidr>>,
+    deny: Vec<IpCidr>,
+}
+
+impl Rule {
+    /// Return whether the rule allows `client` to make requests to `endpoint`.
+    fn allows(&self, endpoint: Endpoint, client: IpAddr) -> bool {
+        if self
+            .endpoints
+            .as_ref()
+            .is_some_and(|endpoints| !endpoints.contains(&endpoint))
+        {
+            return true;
+        }
+
+        let denied = self.deny.iter().any(|cidr| cidr.contains(client));
+        let allowed = match &self.allow {
+            Some(allow) => allow.iter().any(|cidr| cidr.contains(client)),
+            None => true,
+        };
+
+        allowed && !denied
+    }
+}
+
+/// Every rule allowing or denying clients by address.
+#[derive(Debug)]
+pub struct AccessControl {
+    rules: Box<[Rule]>,
+    /// The proxies trusted to name the clients they forward requests from.
+    proxies: Arc<TrustedProxies>,
+    metrics: Arc<Metrics>,
+}
+
+impl AccessControl {
+    /// Return whether every rule allows `client` to make requests to `endpoint`.
+    pub fn allows(&self, endpoint: Endpoint, client: IpAddr) -> bool {
+        // IPv4 clients of dual-stack listeners connect from IPv4-mapped IPv6 addresses
+        let client = client.to_canonical();
+        let allowed = self.rules.iter().all(|rule| rule.allows(endpoint, client));
+
+        if !allowed {
+            self.metrics.record_limited("forbidden");
+        }
+        allowed
+    }
+}
+
+/// The rejection of a request from a client whose address is not allowed.
+#[derive(Debug)]
+pub struct Forbidden;
+
+impl Reject for Forbidden {}
+
+/// Return a filter that rejects requests to `endpoint` from clients not allowed by `access` with
+/// [`Forbidden`].
+///
+/// Clients without an address, ex. on Unix sockets, are always allowed.
+pub fn require(
+    access: Option<Arc<AccessControl>>,
+    endpoint: Endpoint,
+) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    let proxies = access
+        .as_ref()
+        .map(|access| access.proxies.clone())
+        .unwrap_or_default();
+
+    client::address(proxies)
+        .and_then(move |client: Option<IpAddr>| {
+            let access = access.clone();
+
+            async move {
+                match (access, client) {
+                    (Some(access), Some(client)) if !access.allows(endpoint, client) => {
+                        Err(warp::reject::custom(Forbidden))
+                    }
+                    _ => Ok(()),
+                }
+            }
+        })
+        .untuple_one()
+}
+
+/// For a given set of arguments, return the rules allowing or denying clients by address, if any.
+///
+/// Exits if any network is not valid CIDR notation.
+pub fn get_access_control(
+    arguments: &Arguments,
+    proxies: &Arc<TrustedProxies>,
+    metrics: &Arc<Metrics>,
+) -> Option<Arc<AccessControl>> {
+    let rules = arguments.access_rules.as_ref()?;
+
+    let parse = |networks: &[Box<str>]| -> Vec<IpCidr> {
+        networks
+            .iter()
+            .map(|network| {
+                network.parse().unwrap_or_else(|error| {
+                    eprintln!("Invalid access rule: {error}");
+                    process::exit(1);
+                })
+            })
+            .collect()
+    };
+
+    let rules = rules
+        .iter()
+        .map(|rule| Rule {
+            endpoints: rule.endpoints.clone(),
+            allow: rule.allow.as_deref().map(parse),
+            deny: rule.deny.as_deref().map(parse).unwrap_or_default(),
+        })
+        .collect();
+
+    Some(Arc::new(AccessControl {
+        rules,
+        proxies: proxies.clone(),
+        metrics: metrics.clone(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::SocketAddr, time::Duration};
+
+    use ip_geo::{GeoDatabase, IpAddrMap};
+
+    use super::*;
+    use crate::metrics::{Aggregation, Format};
+
+    /// Return the access control configured by `config`, and the metrics it records to.
+    fn access_control(config: &str) -> (Arc<AccessControl>, Arc<Metrics>) {
+        let arguments: Arguments = toml::from_str(config).unwrap();
+        let database = GeoDatabase::new(IpAddrMap::new(), IpAddrMap::new());
+        let metrics = Arc::new(Metrics::new(
+            Aggregation::Country,
+            &database,
+            None,
+            Duration::ZERO,
+        ));
+        let proxies = client::get_trusted_proxies(&arguments);
+        let access = get_access_control(&arguments, &proxies, &metrics).unwrap();
+
+        (access, metrics)
+    }
+
+    fn ip(address: &str) -> IpAddr {
+        address.parse().unwrap()
+    }
+
+    #[test]
+    fn test_allows() {
+        let (access, metrics) = access_control(
+            r#"
+            [[access_rules]]
+            allow = ["10.0.0.0/8", "2001:db8::/32"]
+            deny = ["10.0.0.0/24", "2001:db8::1/128"]
+            "#,
+        );
+
+        assert!(access.allows(Endpoint::Lookup, ip("10.1.0.1")));
+        assert!(access.allows(Endpoint::Lookup, ip("2001:db8::2")));
+        assert!(!access.allows(Endpoint::Lookup, ip("192.0.2.1")));
+
+        // Denying overrides allowing
+        assert!(!access.allows(Endpoint::Lookup, ip("10.0.0.1")));
+        assert!(!access.allows(Endpoint::Metrics, ip("2001:db8::1")));
+
+        // IPv4 clients of dual-stack listeners match IPv4 networks
+        assert!(access.allows(Endpoint::Lookup, ip("::ffff:10.1.0.1")));
+        assert!(!access.allows(Endpoint::Lookup, ip("::ffff:10.0.0.1")));
+        assert!(!access.allows(Endpoint::Lookup, ip("::ffff:192.0.2.1")));
+
+        assert!(metrics
+            .render(Format::Text)
+            .contains("ip_geo_requests_limited_total{reason=\"forbidden\"} 5"));
+    }
+
+    #[test]
+    fn test_allows_endpoints() {
+        let (access, _) = access_control(
+            r#"
+            [[access_rules]]
+            endpoints = ["metrics"]
+            allow = ["10.0.0.0/8"]
+
+            [[access_rules]]
+            endpoints = ["admin", "events"]
+            allow = ["127.0.0.1/32"]
+
+            [[access_rules]]
+            deny = ["10.0.0.13/32"]
+            "#,
+        );
+
+        // Only rules for the endpoint apply
+        assert!(access.allows(Endpoint::Metrics, ip("10.0.0.1")));
+        assert!(!access.allows(Endpoint::Metrics, ip("127.0.0.1")));
+        assert!(access.allows(Endpoint::Lookup, ip("192.0.2.1")));
+        assert!(access.allows(Endpoint::Admin, ip("127.0.0.1")));
+        assert!(!access.allows(Endpoint::Events, ip("10.0.0.1")));
+
+        // Every rule that applies must allow the client
+        assert!(!access.allows(Endpoint::Metrics, ip("10.0.0.13")));
+        assert!(!access.allows(Endpoint::Lookup, ip("10.0.0.13")));
+    }
+
+    #[tokio::test]
+    async fn test_require() {
+        let (access, _) = access_control(
+            r#"
+            trusted_proxies = ["10.0.0.0/8"]
+
+            [[access_rules]]
+            allow = ["192.0.2.0/24"]
+            "#,
+        );
+        let filter = require(Some(access), Endpoint::Lookup).map(warp::reply);
+        let allowed = |peer: &str, forwarded_for: Option<&str>| {
+            let mut request = warp::test::request().remote_addr(SocketAddr::new(ip(peer), 4000));
+            if let Some(forwarded_for) = forwarded_for {
+                request = request.header("x-forwarded-for", forwarded_for);
+            }
+
+            async { request.filter(&filter).await.is_ok() }
+        };
+
+        assert!(allowed("192.0.2.1", None).await);
+        assert!(!allowed("198.51.100.1", None).await);
+
+        // Clients are resolved through trusted proxies
+        assert!(allowed("10.0.0.1", Some("192.0.2.1")).await);
+        assert!(!allowed("10.0.0.1", Some("198.51.100.1")).await);
+        assert!(allowed("10.0.0.1", Some("198.51.100.1, 10.0.0.2, 192.0.2.1")).await);
+        assert!(!allowed("10.0.0.1", None).await);
+
+        // But untrusted peers cannot name another client
+        assert!(!allowed("198.51.100.1", Some("192.0.2.1")).await);
+
+        // Clients without an address are always allowed
+        let filter = require(
+            Some(access_control("[[access_rules]]\nallow = []").0),
+            Endpoint::Lookup,
+        );
+        assert!(warp::test::request().filter(&filter).await.is_ok());
+    }
+}
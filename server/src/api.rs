@@ -15,67 +15,948 @@
 // You should have received a copy of the GNU Affero General Public License along with ip_geo. If
 // not, see <https://www.gnu.org/licenses/>.
 
-use std::net::{Ipv4Addr, Ipv6Addr};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener},
+    path::Path,
+    process,
+    sync::Arc,
+};
 
-use ip_geo::{country_list::Country, IpAddrMap};
-use serde::Serialize;
+use clap::ValueEnum;
+use ip_geo::country_list::Country;
+use serde::{Deserialize, Serialize};
 use warp::{
     http::StatusCode,
-    reply::{json, with_status, Json, WithStatus},
+    hyper::body::Bytes,
+    path::FullPath,
+    reply::{json, with_header, with_status, Json, WithStatus},
+    ws::Ws,
     Filter, Rejection, Reply,
 };
 
-use crate::parse::Maps;
+use crate::{
+    access_log,
+    acl::{self, Forbidden},
+    arguments::Arguments,
+    auth::{self, Endpoint, Unauthorized},
+    client,
+    events::{self, Events},
+    health::Readiness,
+    limit::{self, ConnectionLimit, Overloaded, RateLimited},
+    metrics::{Format, Metrics},
+    reload::{CachedDatabase, Databases, Reloader},
+    systemd,
+};
 
 pub static API_VERSION: &str = "v0";
 
-/// For a give Warp routes map, and a list of target `SocketAddr`s, print the targets and serve the
-/// routes on them.
+/// The OpenAPI 3 document describing the HTTP API, served at `/api/openapi.json`.
+static OPENAPI: &str = include_str!("../openapi.json");
+
+/// For a give Warp routes map, an `Option<TlsConfig>`, an `Option<Arc<ConnectionLimit>>`, an
+/// iterator of `Target`s and the endpoints to serve on each, and a `Shutdown`, serve the routes on
+/// the targets (over TLS if it is configured) in the background until shutdown, and print the
+/// targets.
+///
+/// Exits if any of the targets cannot be bound. Returns a `Vec` of the servers' `JoinHandle`s.
 macro_rules! serve {
-    ( $routes:expr, $( $target:expr ),+ ) => {
-        ::tokio::join!(
-            $({
-                println!("Serving on http://{}/{}/", $target, $crate::api::API_VERSION);
-                ::warp::serve($routes.clone()).run($target)
-            }),+
-        );
+    ( $routes:expr, $tls:expr, $connections:expr, $targets:expr, $shutdown:expr ) => {{
+        type Server = ::std::pin::Pin<
+            ::std::boxed::Box<dyn ::std::future::Future<Output = ()> + ::std::marker::Send>,
+        >;
+
+        $targets
+            .into_iter()
+            .map(|(target, selection): ($crate::api::Target, $crate::api::Selection)| {
+                let signal = $shutdown.clone().signalled();
+                let served = $crate::api::describe(&selection);
+                let routes = ::warp::Filter::and($crate::api::select(selection), $routes.clone());
+                let connections: ::std::option::Option<
+                    ::std::sync::Arc<$crate::limit::ConnectionLimit>,
+                > = $connections.clone();
+
+                let bound = match (&$tls, target) {
+                    (::std::option::Option::Some(tls), $crate::api::Target::Address(address)) => {
+                        let server = ::warp::serve(routes)
+                            .tls()
+                            .cert(&tls.cert)
+                            .key(&tls.key);
+                        let server = match &tls.client_auth {
+                            $crate::tls::ClientAuth::None => server,
+                            $crate::tls::ClientAuth::Optional(client_ca) => {
+                                server.client_auth_optional(client_ca)
+                            }
+                            $crate::tls::ClientAuth::Required(client_ca) => {
+                                server.client_auth_required(client_ca)
+                            }
+                        };
+
+                        server
+                            .try_bind_with_graceful_shutdown(address, signal)
+                            .map(|(address, server)| {
+                                let location = format!("https://{address}");
+                                (location, ::std::boxed::Box::pin(server) as Server)
+                            })
+                            .map_err(|error| format!("Could not listen on {address}: {error}"))
+                    }
+                    (::std::option::Option::Some(_), $crate::api::Target::ProxyProtocol(_)) => {
+                        eprintln!("Serving over TLS is not supported with the PROXY protocol");
+                        ::std::process::exit(1);
+                    }
+                    (::std::option::Option::Some(_), $crate::api::Target::Socket(_)) => {
+                        eprintln!("Serving over TLS is not supported on sockets passed by systemd");
+                        ::std::process::exit(1);
+                    }
+                    (::std::option::Option::None, $crate::api::Target::ProxyProtocol(address)) => {
+                        $crate::api::bind_tcp(address)
+                            .map(|(address, listener)| {
+                                let make_service = ::warp::hyper::service::make_service_fn(
+                                    move |connection: &$crate::limit::Limited<
+                                        $crate::client::ProxiedStream,
+                                    >| {
+                                        let service = $crate::client::WithPeer::new(
+                                            connection.get_ref().peer(),
+                                            ::warp::service(routes.clone()),
+                                        );
+                                        async move {
+                                            ::std::result::Result::Ok::<_, ::std::convert::Infallible>(service)
+                                        }
+                                    },
+                                );
+                                let server = ::warp::hyper::Server::builder(
+                                    ::warp::hyper::server::accept::from_stream(
+                                        $crate::client::accept_proxied(listener, connections),
+                                    ),
+                                )
+                                .serve(make_service)
+                                .with_graceful_shutdown(signal);
+                                let server = async move {
+                                    if let ::std::result::Result::Err(error) = server.await {
+                                        eprintln!("Error serving on {address}: {error}");
+                                    }
+                                };
+                                let location = format!("http://{address} (PROXY protocol)");
+                                (location, ::std::boxed::Box::pin(server) as Server)
+                            })
+                            .map_err(|error| format!("Could not listen on {address}: {error}"))
+                    }
+                    (
+                                        ::std::option::Option::None,
+                        target @ ($crate::api::Target::Address(_) | $crate::api::Target::Socket(_)),
+                    ) => {
+                        $crate::api::bind_socket(target)
+                            .map(|(address, listener)| {
+                                // Warp only knows the address of clients on listeners it binds
+                                // itself, so pass it along like the PROXY protocol's
+                                let make_service = ::warp::hyper::service::make_service_fn(
+                                    move |connection: &$crate::limit::Limited<
+                                        ::tokio::net::TcpStream,
+                                    >| {
+                                        let peer = connection.get_ref().peer_addr();
+                                        let routes = routes.clone();
+                                        async move {
+                                            peer.map(|peer| {
+                                                $crate::client::WithPeer::new(
+                                                    peer,
+                                                    ::warp::service(routes),
+                                                )
+                                            })
+                                        }
+                                    },
+                                );
+                                let server = ::warp::hyper::Server::builder(
+                                    ::warp::hyper::server::accept::from_stream(
+                                        $crate::limit::limit_connections(
+                                            ::tokio_stream::wrappers::TcpListenerStream::new(
+                                                listener,
+                                            ),
+                                            connections,
+                                        ),
+                                    ),
+                                )
+                                .serve(make_service)
+                                .with_graceful_shutdown(signal);
+                                let server = async move {
+                                    if let ::std::result::Result::Err(error) = server.await {
+                                        eprintln!("Error serving on {address}: {error}");
+                                    }
+                                };
+                                let location = format!("http://{address}");
+                                (location, ::std::boxed::Box::pin(server) as Server)
+                            })
+                    }
+                    #[cfg(unix)]
+                    (::std::option::Option::Some(_), $crate::api::Target::Unix { .. }) => {
+                        eprintln!("Serving over TLS is not supported on Unix sockets");
+                        ::std::process::exit(1);
+                    }
+                    #[cfg(unix)]
+                    (::std::option::Option::None, $crate::api::Target::Unix { path, mode }) => {
+                        let location = format!("unix:{}", path.display());
+                        $crate::api::bind_unix(&path, mode)
+                            .map(|listener| {
+                                let incoming = $crate::limit::limit_connections(
+                                    ::tokio_stream::wrappers::UnixListenerStream::new(listener),
+                                    connections,
+                                );
+                                let server = ::warp::serve(routes)
+                                    .serve_incoming_with_graceful_shutdown(incoming, signal);
+                                // Clean up after ourselves, as the socket would outlive the server
+                                let server = async move {
+                                    server.await;
+                                    let _ = ::std::fs::remove_file(&path);
+                                };
+                                (location.clone(), ::std::boxed::Box::pin(server) as Server)
+                            })
+                            .map_err(|error| format!("Could not listen on {location}: {error}"))
+                    }
+                };
+
+                let (location, server) = match bound {
+                    ::std::result::Result::Ok(bound) => bound,
+                    ::std::result::Result::Err(error) => {
+                        eprintln!("{error}");
+                        ::std::process::exit(1);
+                    }
+                };
+
+                println!("Serving {served} on {location}");
+                ::tokio::spawn(server)
+            })
+            .collect::<::std::vec::Vec<_>>()
+    }};
+}
+
+/// An additional address to serve the HTTP API on, serving only some of its endpoints.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Listener {
+    pub address: SocketAddr,
+    /// The groups of endpoints to serve, or every endpoint if unset. `/healthz`, `/readyz`, and
+    /// `/api/openapi.json` are always served.
+    #[serde(default)]
+    pub endpoints: Option<Vec<Endpoint>>,
+}
+
+/// The groups of endpoints served on a target, or `None` for every endpoint.
+pub type Selection = Option<Arc<[Endpoint]>>;
+
+/// Somewhere to serve the HTTP API.
+#[derive(Debug)]
+pub enum Target {
+    /// An address to bind to.
+    Address(SocketAddr),
+    /// An address to bind to, where every connection starts with a PROXY protocol header.
+    ProxyProtocol(SocketAddr),
+    /// A socket that is already bound, ex. passed by systemd socket activation.
+    Socket(TcpListener),
+    /// A Unix socket to bind to, and the permissions to give it (if set).
+    #[cfg(unix)]
+    Unix { path: Box<Path>, mode: Option<u32> },
+}
+
+/// For a given set of `Arguments`, return where to serve the HTTP API.
+///
+/// Sockets passed by systemd socket activation take precedence over any addresses. Additional
+/// listeners and a Unix socket replace the default addresses, but not those given to `--listen`.
+/// With `--proxy-protocol`, every address expects PROXY protocol headers.
+/// Exits if `--listen` is given an empty list, or if the Unix socket is invalid.
+pub fn get_targets(arguments: &Arguments) -> Vec<(Target, Selection)> {
+    if let Some(listeners) = systemd::listen_fds() {
+        return listeners
+            .into_iter()
+            .map(|listener| (Target::Socket(listener), None))
+            .collect();
+    }
+
+    let replaced = arguments.listeners.is_some() || arguments.unix_socket.is_some();
+    let addresses = match &arguments.listen {
+        Some(targets) if targets.is_empty() => {
+            eprintln!("At least one address to listen on is required");
+            process::exit(1);
+        }
+        Some(targets) => targets.clone(),
+        None if replaced => Vec::new(),
+        // Safety: `arguments::get_config()` implements default values
+        None => vec![
+            arguments.ipv4_pair.unwrap().into(),
+            arguments.ipv6_pair.unwrap().into(),
+        ],
+    };
+
+    // Safety: `arguments::get_config()` implements default values
+    let to_target = match arguments.proxy_protocol.unwrap() {
+        true => Target::ProxyProtocol,
+        false => Target::Address,
+    };
+
+    let mut targets: Vec<(Target, Selection)> = addresses
+        .into_iter()
+        .map(|address| (to_target(address), None))
+        .collect();
+    for listener in arguments.listeners.iter().flatten() {
+        let selection = listener.endpoints.as_deref().map(Arc::from);
+        targets.push((to_target(listener.address), selection));
+    }
+    if let Some(path) = &arguments.unix_socket {
+        let target = get_unix_target(path, arguments.unix_socket_mode.as_deref());
+        targets.push((target, None));
+    }
+
+    targets
+}
+
+/// Return a filter that rejects requests for endpoints outside of `selection` as not found.
+pub fn select(selection: Selection) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::path::full()
+        .and_then(move |path: FullPath| {
+            let selected = match (&selection, endpoint_of(path.as_str())) {
+                (Some(selection), Some(endpoint)) => selection.contains(&endpoint),
+                _ => true,
+            };
+
+            async move {
+                match selected {
+                    true => Ok(()),
+                    false => Err(warp::reject::not_found()),
+                }
+            }
+        })
+        .untuple_one()
+}
+
+/// Describe the endpoints in `selection`, ex. `metrics and lookup endpoints`.
+pub fn describe(selection: &Selection) -> String {
+    let Some(selection) = selection else {
+        return "every endpoint".into();
     };
+
+    let names: Vec<String> = selection
+        .iter()
+        .filter_map(ValueEnum::to_possible_value)
+        .map(|value| value.get_name().to_owned())
+        .collect();
+    format!("{} endpoints", names.join(" and "))
 }
 
-pub fn get_routes(maps: Maps) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
-    let search_ipv4 = move |ipv4_addr: Ipv4Addr| search_clean_ip_map(ipv4_addr, &maps.v4);
-    let search_ipv6 = move |ipv6_addr: Ipv6Addr| search_clean_ip_map(ipv6_addr, &maps.v6);
+/// Return the group of endpoints that `path` belongs to, or `None` for endpoints that are always
+/// served, ex. `/healthz`.
+fn endpoint_of(path: &str) -> Option<Endpoint> {
+    match path.trim_start_matches('/').split('/').next()? {
+        "metrics" => Some(Endpoint::Metrics),
+        "-" | "series" => Some(Endpoint::Admin),
+        "events" => Some(Endpoint::Events),
+        "whoami" => Some(Endpoint::Lookup),
+        segment if segment == API_VERSION => Some(Endpoint::Lookup),
+        _ => None,
+    }
+}
+
+/// Return a target for the Unix socket at `path`, with the octal permissions `mode` (if set).
+///
+/// Exits if `mode` is not valid octal permissions.
+#[cfg(unix)]
+fn get_unix_target(path: &Path, mode: Option<&str>) -> Target {
+    let mode = mode.map(|mode| match u32::from_str_radix(mode, 8) {
+        Ok(mode) if mode <= 0o7777 => mode,
+        _ => {
+            eprintln!("Invalid Unix socket mode `{mode}`, expected octal permissions, ex. `660`");
+            process::exit(1);
+        }
+    });
 
-    let ipv4 = warp::path!("ipv4" / Ipv4Addr).map(search_ipv4);
-    let ipv6 = warp::path!("ipv6" / Ipv6Addr).map(search_ipv6);
+    Target::Unix {
+        path: path.into(),
+        mode,
+    }
+}
 
-    warp::get().and(warp::path(API_VERSION)).and(ipv4.or(ipv6))
+/// Exit, because Unix sockets are not supported on this platform.
+#[cfg(not(unix))]
+fn get_unix_target(_path: &Path, _mode: Option<&str>) -> Target {
+    eprintln!("Serving on a Unix socket is only supported on Unix");
+    process::exit(1);
 }
 
-/// Search an IPv4 address map for an IP address.
+/// Bind `target`, an address or a socket passed by systemd, to serve without TLS, returning the
+/// address it was bound to and the listener.
 ///
-/// Assumes that the `IpAddrMap` is clean, otherwise it return an internal server error (code 500).
-fn search_clean_ip_map<A: Ord + Copy>(ip_addr: A, ip_map: &IpAddrMap<A, Country>) -> impl Reply {
-    fn success(country: &Country) -> WithStatus<Json> {
-        json_with_status(country, StatusCode::OK)
+/// Addresses are bound here rather than by Warp, so that their connections can be capped.
+pub fn bind_socket(target: Target) -> Result<(SocketAddr, tokio::net::TcpListener), String> {
+    match target {
+        Target::Address(address) => {
+            bind_tcp(address).map_err(|error| format!("Could not listen on {address}: {error}"))
+        }
+        Target::Socket(listener) => listener
+            .set_nonblocking(true)
+            .and_then(|()| listener.local_addr())
+            .and_then(|address| Ok((address, tokio::net::TcpListener::from_std(listener)?)))
+            .map_err(|error| format!("Could not use socket passed by systemd: {error}")),
+        _ => unreachable!("only addresses and sockets passed by systemd are bound here"),
     }
+}
 
-    fn error(error: ip_geo::Error) -> WithStatus<Json> {
-        match error {
-            ip_geo::Error::NoValueFound => json_str_error(
-                "no country associated with IP address",
-                StatusCode::NOT_FOUND,
-            ),
-            _ => {
-                eprintln!("Error 500: request resulted in error: '{error}'");
-                json_str_error(&error.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+/// Bind a TCP listener to `address`, returning the address it was bound to and the listener.
+pub fn bind_tcp(address: SocketAddr) -> std::io::Result<(SocketAddr, tokio::net::TcpListener)> {
+    let listener = TcpListener::bind(address)?;
+    listener.set_nonblocking(true)?;
+    let address = listener.local_addr()?;
+
+    Ok((address, tokio::net::TcpListener::from_std(listener)?))
+}
+
+/// Bind a Unix socket at `path`, replacing any socket left behind there, and give it the
+/// permissions `mode` (if set).
+#[cfg(unix)]
+pub fn bind_unix(path: &Path, mode: Option<u32>) -> std::io::Result<tokio::net::UnixListener> {
+    use std::{
+        fs::{self, Permissions},
+        os::unix::fs::{FileTypeExt, PermissionsExt},
+    };
+
+    // A socket left behind by a previous run would fail to bind, but never replace other files
+    if fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+        fs::remove_file(path)?;
+    }
+
+    let listener = tokio::net::UnixListener::bind(path)?;
+    if let Some(mode) = mode {
+        fs::set_permissions(path, Permissions::from_mode(mode))?;
+    }
+
+    Ok(listener)
+}
+
+/// For a given set of arguments, serve lookups in `databases` over gRPC in the background, if
+/// configured, capping its connections with `connections` (if set), exiting if the address cannot
+/// be bound.
+#[cfg(feature = "grpc")]
+pub async fn spawn_grpc_server(
+    arguments: &Arguments,
+    databases: Databases,
+    metrics: Arc<Metrics>,
+    connections: Option<Arc<ConnectionLimit>>,
+) {
+    let Some(address) = arguments.grpc_listen else {
+        return;
+    };
+
+    // Safety: `arguments::get_config()` implements default values
+    let batch_limit = arguments.batch_lookup_limit.unwrap();
+    let proxies = client::get_trusted_proxies(arguments);
+    let access = acl::get_access_control(arguments, &proxies, &metrics);
+    let credentials = auth::get_credentials(arguments);
+    let service = crate::grpc::Service::new(databases, metrics, access, credentials, batch_limit);
+
+    if let Err(error) = crate::grpc::spawn_server(address, service, connections).await {
+        eprintln!("Could not serve gRPC on http://{address}: {error}");
+        process::exit(1);
+    }
+}
+
+/// For a given set of arguments, exit if a gRPC address was configured, because this build cannot
+/// serve gRPC.
+#[cfg(not(feature = "grpc"))]
+pub async fn spawn_grpc_server(
+    arguments: &Arguments,
+    _databases: Databases,
+    _metrics: Arc<Metrics>,
+    _connections: Option<Arc<ConnectionLimit>>,
+) {
+    if arguments.grpc_listen.is_some() {
+        eprintln!("Serving gRPC requires building with the `grpc` feature");
+        process::exit(1);
+    }
+}
+
+/// The most bytes an address can take up in a batch request, including separators.
+const MAX_BATCH_ADDRESS_LEN: u64 = 64;
+
+pub fn get_routes(
+    arguments: &Arguments,
+    databases: Databases,
+    metrics: Arc<Metrics>,
+    events: Arc<Events>,
+    readiness: Readiness,
+    reloader: Reloader,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    // Safety: `arguments::get_config()` implements default values
+    let batch_limit = arguments.batch_lookup_limit.unwrap();
+    let credentials = auth::get_credentials(arguments);
+    let proxies = client::get_trusted_proxies(arguments);
+    let access = acl::get_access_control(arguments, &proxies, &metrics);
+    let limits = limit::get_limits(arguments, &proxies, &metrics);
+    // Safety: `arguments::get_config()` implements default values
+    let access_log = arguments
+        .access_log
+        .unwrap()
+        .then(|| arguments.access_log_lookups.unwrap());
+
+    let (ipv4_databases, ipv4_metrics) = (databases.clone(), metrics.clone());
+    let search_ipv4 = move |ipv4_addr: Ipv4Addr, query: HashMap<String, String>| {
+        let database = match select_database(&ipv4_databases, &query) {
+            Ok(database) => database,
+            Err(reply) => return reply,
+        };
+        ipv4_metrics.record_asn(ipv4_addr.into());
+        let result = ipv4_metrics.time_lookup(|| database.lookup(ipv4_addr.into(), &ipv4_metrics));
+        lookup_reply(ipv4_addr.into(), result, database.name(), &ipv4_metrics)
+    };
+    let (ipv6_databases, ipv6_metrics) = (databases.clone(), metrics.clone());
+    let search_ipv6 = move |ipv6_addr: Ipv6Addr, query: HashMap<String, String>| {
+        let database = match select_database(&ipv6_databases, &query) {
+            Ok(database) => database,
+            Err(reply) => return reply,
+        };
+        ipv6_metrics.record_asn(ipv6_addr.into());
+        let result = ipv6_metrics.time_lookup(|| database.lookup(ipv6_addr.into(), &ipv6_metrics));
+        lookup_reply(ipv6_addr.into(), result, database.name(), &ipv6_metrics)
+    };
+    let (query_databases, query_metrics) = (databases.clone(), metrics.clone());
+    let search_query = move |query: HashMap<String, String>| {
+        select_database(&query_databases, &query).map_or_else(
+            |reply| reply,
+            |database| query_lookup_reply(&query, &database, &query_metrics),
+        )
+    };
+    let (whoami_databases, whoami_metrics) = (databases.clone(), metrics.clone());
+    let search_whoami = move |client: Option<IpAddr>, query: HashMap<String, String>| {
+        let database = match select_database(&whoami_databases, &query) {
+            Ok(database) => database,
+            Err(reply) => return reply,
+        };
+        match client {
+            Some(client) => address_lookup_reply(client, &database, &whoami_metrics),
+            None => json_str_error("client address is unknown", StatusCode::BAD_REQUEST),
+        }
+    };
+    let batch_metrics = metrics.clone();
+    let search_batch = move |query: HashMap<String, String>, body: Bytes| {
+        select_database(&databases, &query).map_or_else(
+            |reply| reply,
+            |database| batch_lookup_reply(&body, batch_limit, &database, &batch_metrics),
+        )
+    };
+
+    let ipv4 = warp::path!("ipv4" / Ipv4Addr)
+        .and(warp::query::<HashMap<String, String>>())
+        .map(search_ipv4);
+    let ipv6 = warp::path!("ipv6" / Ipv6Addr)
+        .and(warp::query::<HashMap<String, String>>())
+        .map(search_ipv6);
+    let query = warp::path!("lookup")
+        .and(warp::query::<HashMap<String, String>>())
+        .map(search_query);
+    let batch = warp::path!("lookup")
+        .and(warp::query::<HashMap<String, String>>())
+        .and(warp::body::content_length_limit(
+            batch_limit as u64 * MAX_BATCH_ADDRESS_LEN,
+        ))
+        .and(warp::body::bytes())
+        .map(search_batch);
+    let api = warp::path(API_VERSION)
+        .and(acl::require(access.clone(), Endpoint::Lookup))
+        .and(auth::require(credentials.clone(), Endpoint::Lookup))
+        .and(limit::require(limits.clone()))
+        .and(
+            warp::get()
+                .and(ipv4.or(ipv6).or(query))
+                .or(warp::post().and(batch)),
+        )
+        // Hold the permit until the lookup is done
+        .map(|_permit, reply| reply);
+
+    let whoami = warp::path!("whoami")
+        .and(warp::get())
+        .and(acl::require(access.clone(), Endpoint::Lookup))
+        .and(auth::require(credentials.clone(), Endpoint::Lookup))
+        .and(limit::require(limits))
+        .and(client::address(proxies.clone()))
+        .and(warp::query::<HashMap<String, String>>())
+        .map(move |_permit, client, query| search_whoami(client, query));
+
+    let (reset_metrics, delete_metrics) = (metrics.clone(), metrics.clone());
+    let reset = warp::path!("-" / "reset")
+        .and(warp::post())
+        .and(acl::require(access.clone(), Endpoint::Admin))
+        .and(auth::require_always(credentials.clone(), Endpoint::Admin))
+        .map(move || {
+            reset_metrics.reset();
+            json_status("ok", StatusCode::OK)
+        });
+    let delete = warp::path!("series")
+        .and(warp::delete())
+        .and(acl::require(access.clone(), Endpoint::Admin))
+        .and(auth::require_always(credentials.clone(), Endpoint::Admin))
+        .and(warp::query::<HashMap<String, String>>())
+        .map(move |query: HashMap<String, String>| delete_series_reply(&query, &delete_metrics));
+
+    let reload = warp::path!("-" / "reload")
+        .and(warp::post())
+        .and(acl::require(access.clone(), Endpoint::Admin))
+        .and(auth::require_always(credentials.clone(), Endpoint::Admin))
+        .then(move || {
+            let reloader = reloader.clone();
+            async move {
+                match reloader.reload().await {
+                    Ok(summary) => json_with_status(&summary, StatusCode::OK),
+                    Err(error) => {
+                        json_str_error(&error.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+                    }
+                }
+            }
+        });
+
+    let stream = warp::path!("events")
+        .and(warp::get())
+        .and(acl::require(access.clone(), Endpoint::Events))
+        .and(auth::require_always(credentials.clone(), Endpoint::Events))
+        .and(warp::ws().map(Some).or(warp::any().map(|| None)).unify())
+        .map(move |ws: Option<Ws>| {
+            let events = events.clone();
+            match ws {
+                Some(ws) => ws
+                    .on_upgrade(move |socket| async move {
+                        events::serve_websocket(socket, &events).await;
+                    })
+                    .into_response(),
+                None => events::sse_reply(&events).into_response(),
             }
+        });
+
+    let metrics = warp::path!("metrics")
+        .and(acl::require(access, Endpoint::Metrics))
+        .and(auth::require(credentials, Endpoint::Metrics))
+        .and(warp::header::optional::<String>("accept"))
+        .map(move |accept: Option<String>| {
+            let format = Format::negotiate(accept.as_deref());
+            with_header(
+                metrics.render(format),
+                "Content-Type",
+                format.content_type(),
+            )
+        });
+
+    let healthz = warp::path!("healthz").map(|| json_status("ok", StatusCode::OK));
+    let readyz = warp::path!("readyz").map(move || match readiness.check() {
+        Ok(()) => json_status("ok", StatusCode::OK),
+        Err(error) => json_str_error(&error, StatusCode::SERVICE_UNAVAILABLE),
+    });
+
+    let openapi = warp::path!("api" / "openapi.json")
+        .map(|| with_header(OPENAPI, "Content-Type", "application/json"));
+
+    api.or(whoami)
+        .or(warp::get().and(metrics.or(healthz).or(readyz).or(openapi)))
+        .or(stream)
+        .or(reset)
+        .or(reload)
+        .or(delete)
+        .recover(forbidden_reply)
+        .recover(unauthorized_reply)
+        .recover(limited_reply)
+        .with(warp::log::custom(move |info| {
+            if let Some(log_lookups) = access_log {
+                access_log::record(info, log_lookups, &proxies);
+            }
+        }))
+}
+
+/// Convert a rejection of a client by its address into a JSON reply, with forbidden (code 403).
+/// Passes any other rejection through.
+async fn forbidden_reply(rejection: Rejection) -> Result<impl Reply, Rejection> {
+    match rejection.find() {
+        Some(Forbidden) => Ok(json_str_error(
+            "client address is not allowed",
+            StatusCode::FORBIDDEN,
+        )),
+        None => Err(rejection),
+    }
+}
+
+/// Convert a rejection for missing or invalid credentials into a JSON reply, with unauthorized
+/// (code 401) and a `WWW-Authenticate` challenge. Passes any other rejection through.
+async fn unauthorized_reply(rejection: Rejection) -> Result<impl Reply, Rejection> {
+    let Some(Unauthorized { challenge }) = rejection.find() else {
+        return Err(rejection);
+    };
+
+    Ok(with_header(
+        json_str_error("missing or invalid credentials", StatusCode::UNAUTHORIZED),
+        "WWW-Authenticate",
+        *challenge,
+    ))
+}
+
+/// Convert a rejection by a limit into a JSON reply: too many requests (code 429) with a
+/// `Retry-After` header for a client over its rate limit, or service unavailable (code 503) while
+/// too many lookup requests are in flight. Passes any other rejection through.
+async fn limited_reply(rejection: Rejection) -> Result<impl Reply, Rejection> {
+    if let Some(RateLimited { retry_after }) = rejection.find() {
+        // `Retry-After` is in whole seconds, so round up
+        let retry_after = retry_after
+            .as_secs()
+            .saturating_add(u64::from(retry_after.subsec_nanos() > 0));
+
+        return Ok(with_header(
+            json_str_error("too many requests", StatusCode::TOO_MANY_REQUESTS),
+            "Retry-After",
+            retry_after.to_string(),
+        ));
+    }
+
+    if rejection.find::<Overloaded>().is_some() {
+        return Ok(with_header(
+            json_str_error(
+                "too many lookups in flight",
+                StatusCode::SERVICE_UNAVAILABLE,
+            ),
+            "Retry-After",
+            "1".to_string(),
+        ));
+    }
+
+    Err(rejection)
+}
+
+/// Remove the series of the country in the `country` query parameter, ex. `?country=BE`, replying
+/// with how many were removed in the format of:
+///
+/// ```json
+/// {"deleted":3}
+/// ```
+///
+/// Returns bad request (code 400) if the parameter is missing.
+fn delete_series_reply(query: &HashMap<String, String>, metrics: &Metrics) -> WithStatus<Json> {
+    #[derive(Serialize)]
+    struct SerializableDeleted {
+        deleted: usize,
+    }
+
+    let Some(country) = query.get("country") else {
+        return json_str_error("missing country parameter", StatusCode::BAD_REQUEST);
+    };
+
+    let deleted = metrics.delete_country(&country.to_ascii_uppercase());
+    json_with_status(&SerializableDeleted { deleted }, StatusCode::OK)
+}
+
+/// Return the database named in the `database` query parameter, or the default database if it is
+/// missing.
+///
+/// Returns bad request (code 400) as an error if there is no database by that name.
+fn select_database(
+    databases: &Databases,
+    query: &HashMap<String, String>,
+) -> Result<CachedDatabase, WithStatus<Json>> {
+    let name = query.get("database").map(String::as_str);
+
+    databases
+        .select(name)
+        .ok_or_else(|| json_str_error("unknown database", StatusCode::BAD_REQUEST))
+}
+
+/// Convert the result of a lookup of `address` in the database named `database` (or the default
+/// database, if `None`) into a JSON reply.
+///
+/// Returns not found (code 404) if no country is associated with the address, or an internal
+/// server error (code 500) for any other error.
+///
+/// Records the lookup in `metrics`.
+fn lookup_reply(
+    address: IpAddr,
+    result: Result<Country, ip_geo::Error>,
+    database: Option<&str>,
+    metrics: &Metrics,
+) -> WithStatus<Json> {
+    match result {
+        Ok(country) => {
+            metrics.record_lookup(address, &country, database);
+            json_with_status(&country, StatusCode::OK)
         }
+        Err(error) => lookup_error_reply(error, database, metrics),
     }
+}
+
+/// The reply to a lookup by query string, naming the address that was looked up.
+///
+/// Serializes as:
+///
+/// ```json
+/// {"ip":"1.2.3.4","country_code":"BE","country_name":"Belgium","coordinates":[4.66,50.64]}
+/// ```
+#[derive(Serialize)]
+struct LookupResult {
+    ip: IpAddr,
+    country_code: Box<str>,
+    country_name: Box<str>,
+    coordinates: (f64, f64),
+}
 
-    match ip_map.try_search(ip_addr) {
-        Ok(country) => success(country),
-        Err(err) => error(err),
+impl LookupResult {
+    fn new(ip: IpAddr, country: &Country) -> Self {
+        Self {
+            ip,
+            country_code: country.code.as_ref().into(),
+            country_name: country.name.as_ref().into(),
+            coordinates: country.coordinates,
+        }
+    }
+}
+
+/// Look up the address in the `ip` parameter of a query string, and convert the result into a
+/// JSON reply.
+///
+/// Returns bad request (code 400) if the parameter is missing or not an IP address, otherwise
+/// behaves like [`lookup_reply`].
+fn query_lookup_reply(
+    query: &HashMap<String, String>,
+    database: &CachedDatabase,
+    metrics: &Metrics,
+) -> WithStatus<Json> {
+    let Some(address) = query.get("ip") else {
+        return json_str_error("missing `ip` query parameter", StatusCode::BAD_REQUEST);
+    };
+    let Ok(address) = address.parse::<IpAddr>() else {
+        return json_str_error("invalid IP address", StatusCode::BAD_REQUEST);
+    };
+
+    address_lookup_reply(address, database, metrics)
+}
+
+/// Look up `address`, and convert the result into a JSON reply naming the address, like
+/// [`LookupResult`].
+///
+/// Returns not found (code 404) if no country is associated with the address, or an internal
+/// server error (code 500) for any other error.
+///
+/// Records the lookup in `metrics`.
+fn address_lookup_reply(
+    address: IpAddr,
+    database: &CachedDatabase,
+    metrics: &Metrics,
+) -> WithStatus<Json> {
+    metrics.record_asn(address);
+
+    match metrics.time_lookup(|| database.lookup(address, metrics)) {
+        Ok(country) => {
+            metrics.record_lookup(address, &country, database.name());
+            json_with_status(&LookupResult::new(address, &country), StatusCode::OK)
+        }
+        Err(error) => lookup_error_reply(error, database.name(), metrics),
+    }
+}
+
+/// A single result of a batch lookup: either a successful lookup, or the reason it failed.
+///
+/// Serializes as a [`LookupResult`], or as:
+///
+/// ```json
+/// {"ip":"192.0.2.1","error":"no country associated with IP address"}
+/// ```
+#[derive(Serialize)]
+#[serde(untagged)]
+enum BatchLookupResult<'c> {
+    Found(LookupResult),
+    Failed { ip: &'c str, error: Box<str> },
+}
+
+/// Look up every address in a batch request body, and convert the results into a JSON array
+/// reply, in the same order as the addresses.
+///
+/// The body is either a JSON array of strings or one address per line. Returns bad request (code
+/// 400) if the body is neither, or payload too large (code 413) if it has more than `limit`
+/// addresses. Addresses that fail to be looked up are reported in their place in the array.
+///
+/// Records every lookup in `metrics`.
+fn batch_lookup_reply(
+    body: &[u8],
+    limit: usize,
+    database: &CachedDatabase,
+    metrics: &Metrics,
+) -> WithStatus<Json> {
+    let Ok(body) = std::str::from_utf8(body) else {
+        return json_str_error("request body is not UTF-8", StatusCode::BAD_REQUEST);
+    };
+
+    let addresses: Vec<Box<str>> = match body.trim_start().starts_with('[') {
+        true => match serde_json::from_str(body) {
+            Ok(addresses) => addresses,
+            Err(_) => {
+                return json_str_error(
+                    "request body is not a JSON array of strings",
+                    StatusCode::BAD_REQUEST,
+                )
+            }
+        },
+        false => body
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(Box::from)
+            .collect(),
+    };
+
+    if addresses.len() > limit {
+        let error = format!("too many addresses, the limit is {limit}");
+        return json_str_error(&error, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    let results: Vec<BatchLookupResult> = addresses
+        .iter()
+        .map(|ip| {
+            let failed = |error: &str| BatchLookupResult::Failed {
+                ip,
+                error: error.into(),
+            };
+
+            let Ok(address) = ip.parse::<IpAddr>() else {
+                return failed("invalid IP address");
+            };
+
+            metrics.record_asn(address);
+
+            match metrics.time_lookup(|| database.lookup(address, metrics)) {
+                Ok(country) => {
+                    metrics.record_lookup(address, &country, database.name());
+                    BatchLookupResult::Found(LookupResult::new(address, &country))
+                }
+                Err(ip_geo::Error::NoValueFound) => {
+                    metrics.record_miss(database.name());
+                    failed("no country associated with IP address")
+                }
+                Err(error) => failed(&error.to_string()),
+            }
+        })
+        .collect();
+
+    json_with_status(&results, StatusCode::OK)
+}
+
+/// Convert a failed lookup in the database named `database` (or the default database, if `None`)
+/// into a JSON reply.
+///
+/// Returns not found (code 404) if no country is associated with the address, or an internal
+/// server error (code 500) for any other error.
+///
+/// Records misses in `metrics`.
+fn lookup_error_reply(
+    error: ip_geo::Error,
+    database: Option<&str>,
+    metrics: &Metrics,
+) -> WithStatus<Json> {
+    match error {
+        ip_geo::Error::NoValueFound => {
+            metrics.record_miss(database);
+            json_str_error(
+                "no country associated with IP address",
+                StatusCode::NOT_FOUND,
+            )
+        }
+        _ => {
+            eprintln!("Error 500: request resulted in error: '{error}'");
+            json_str_error(&error.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+        }
     }
 }
 
@@ -95,6 +976,22 @@ fn json_str_error(error: &str, code: StatusCode) -> WithStatus<Json> {
     json_with_status(&SerializableError { error }, code)
 }
 
+/// Returns a JSON reply with a given status.
+///
+/// Returns JSON in the format of:
+///
+/// ```json
+/// {"status":"ok"}
+/// ```
+fn json_status(status: &str, code: StatusCode) -> WithStatus<Json> {
+    #[derive(Serialize)]
+    struct SerializableStatus<'s> {
+        status: &'s str,
+    }
+
+    json_with_status(&SerializableStatus { status }, code)
+}
+
 /// Returns a JSON reply with the given contents and status code.
 fn json_with_status(contents: &impl Serialize, code: StatusCode) -> WithStatus<Json> {
     with_status(json(contents), code)
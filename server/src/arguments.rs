@@ -18,17 +18,29 @@
 
 use std::{
     fs,
-    net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6},
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    num::{NonZeroU64, NonZeroUsize},
     path::Path,
+    process,
 };
 
-use clap::Parser;
+use clap::{ArgAction, CommandFactory, FromArgMatches, Parser, ValueEnum};
+use ip_geo::privacy::{DEFAULT_IPV4_PREFIX, DEFAULT_IPV6_PREFIX};
 use serde::Deserialize;
 
-use crate::error::Error;
+use crate::{
+    access_log::AccessLogFormat,
+    acl::AccessRule,
+    api::Listener,
+    auth::Endpoint,
+    error::Error,
+    ingest::{format::LogFormat, pattern::LogPattern},
+    metrics::{self, Aggregation, StaticLabel},
+    parse::NamedDatabase,
+};
 
 /// Represents the command-line arguments of the program.
-#[derive(Parser, Deserialize, Debug)]
+#[derive(Parser, Deserialize, Debug, Clone)]
 #[command(about, version, long_about = None)]
 pub struct Arguments {
     #[arg(short = 'f', long = "config-path")]
@@ -39,10 +51,80 @@ pub struct Arguments {
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub ipv4_pair: Option<SocketAddrV4>,
 
+    /// The addresses to serve the HTTP API on, in place of `ipv4_pair` and `ipv6_pair`. Can be
+    /// given more than once.
+    #[arg(short = 'l', long = "listen", value_delimiter = ',')]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub listen: Option<Vec<SocketAddr>>,
+
+    /// Additional addresses to serve the HTTP API on, each serving only some groups of endpoints,
+    /// ex. only `metrics` on a port that Prometheus scrapes. Replace `ipv4_pair` and `ipv6_pair`,
+    /// like `listen`. Only available in the configuration file.
+    #[arg(skip)]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub listeners: Option<Vec<Listener>>,
+
+    /// Rules allowing or denying clients by address, per group of endpoints, ex. only allowing
+    /// `metrics` from `10.0.0.0/8`. A request must be allowed by every rule that applies to it.
+    /// Only available in the configuration file.
+    #[arg(skip)]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub access_rules: Option<Vec<AccessRule>>,
+
+    /// A Unix socket to serve the HTTP API on, ex. `/run/ip_geo/http.sock`, in addition to
+    /// `listen` if it is given, or in place of `ipv4_pair` and `ipv6_pair` if not. Replaces any
+    /// socket left behind at the path, and is removed on shutdown. Only supported on Unix.
+    #[arg(long = "unix-socket")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub unix_socket: Option<Box<Path>>,
+
+    /// The permissions of `unix_socket`, in octal, ex. `660` to only allow its owner and group to
+    /// connect. Defaults to the permissions allowed by the umask.
+    #[arg(long = "unix-socket-mode")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub unix_socket_mode: Option<Box<str>>,
+
+    /// Expect every connection to the addresses the HTTP API is served on to start with a PROXY
+    /// protocol (v1 or v2) header, ex. from HAProxy, and treat the address it names as the client.
+    /// Connections without a valid header are dropped. Not supported with TLS.
+    #[arg(long = "proxy-protocol")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub proxy_protocol: Option<bool>,
+
+    /// The networks of reverse proxies whose `X-Forwarded-For` headers are trusted to name the
+    /// client, ex. `10.0.0.0/8`.
+    #[arg(long = "trusted-proxies", value_delimiter = ',')]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub trusted_proxies: Option<Vec<Box<str>>>,
+
+    /// How long to wait for in-flight requests to finish after SIGINT or SIGTERM, in seconds.
+    #[arg(long = "shutdown-timeout")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub shutdown_timeout: Option<u64>,
+
+    /// Serve the HTTP API. Disable to only push metrics, ex. with `remote_write_url`.
+    #[arg(long = "serve-http")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub serve_http: Option<bool>,
+
+    /// Perform every startup step (reading the configuration file, loading the databases, and
+    /// binding the addresses the HTTP API is served on), then report on each and exit instead of
+    /// serving, exiting with 1 if any failed. Only available on the command line.
+    #[arg(long = "check", action = ArgAction::SetTrue)]
+    #[serde(skip, default)]
+    pub check: Option<bool>,
+
     #[arg(long = "ipv4-db-path")]
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub ipv4_db_path: Option<Box<Path>>,
 
+    /// Additional databases, ex. `tor` or `internal-overrides`, selected by name with the
+    /// `database` query parameter of lookup endpoints. They share every other database setting.
+    /// Only available in the configuration file.
+    #[arg(skip)]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub databases: Option<Vec<NamedDatabase>>,
+
     #[arg(long = "ipv4-db-length")]
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub ipv4_db_len: Option<usize>,
@@ -66,6 +148,506 @@ pub struct Arguments {
     #[arg(long = "ipv6-db-comment")]
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub ipv6_db_comment: Option<char>,
+
+    /// Refuse to start if a database has no `.manifest` file. Databases with a manifest are always
+    /// checked against it before serving.
+    #[arg(long = "require-manifest")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub require_manifest: Option<bool>,
+
+    /// Truncate addresses to a network prefix before looking them up, so that full client
+    /// addresses are never used or logged.
+    #[arg(long = "truncate-addresses")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub truncate_addresses: Option<bool>,
+
+    /// The prefix length to truncate IPv4 addresses to, if truncation is enabled.
+    #[arg(long = "truncate-ipv4-prefix")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub truncate_ipv4_prefix: Option<u8>,
+
+    /// The prefix length to truncate IPv6 addresses to, if truncation is enabled.
+    #[arg(long = "truncate-ipv6-prefix")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub truncate_ipv6_prefix: Option<u8>,
+
+    /// What to return for addresses with no associated country.
+    #[arg(long = "fallback", value_enum)]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub fallback: Option<FallbackMode>,
+
+    /// The IPv4 database to consult when the fallback is `database`.
+    #[arg(long = "fallback-ipv4-db-path")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub fallback_ipv4_db_path: Option<Box<Path>>,
+
+    /// The IPv6 database to consult when the fallback is `database`.
+    #[arg(long = "fallback-ipv6-db-path")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub fallback_ipv6_db_path: Option<Box<Path>>,
+
+    /// The language to name countries in, ex. `de` or `pt-BR`, in lookups and metric labels,
+    /// falling back to English for countries that have not been translated into it. Defaults to
+    /// English.
+    #[arg(long = "lang")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub lang: Option<Box<str>>,
+
+    /// The PEM-encoded certificate chain to serve over TLS with. Requires `tls_key_path`.
+    #[arg(long = "tls-cert-path")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub tls_cert_path: Option<Box<Path>>,
+
+    /// The PEM-encoded private key to serve over TLS with. Requires `tls_cert_path`.
+    #[arg(long = "tls-key-path")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub tls_key_path: Option<Box<Path>>,
+
+    /// The PEM-encoded certificate authorities to verify client certificates against. By default,
+    /// client certificates are not requested.
+    #[arg(long = "tls-client-ca-path")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub tls_client_ca_path: Option<Box<Path>>,
+
+    /// Reject clients without a certificate signed by `tls_client_ca_path`, instead of only
+    /// rejecting clients with an invalid certificate.
+    #[arg(long = "tls-client-auth-required")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub tls_client_auth_required: Option<bool>,
+
+    /// The username that clients must present with basic authentication. Requires a password from
+    /// `auth_basic_password_file` or `auth_basic_password_env`.
+    #[arg(long = "auth-basic-username")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub auth_basic_username: Option<Box<str>>,
+
+    /// A file holding the password that clients must present with basic authentication.
+    #[arg(long = "auth-basic-password-file")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub auth_basic_password_file: Option<Box<Path>>,
+
+    /// An environment variable holding the password that clients must present with basic
+    /// authentication.
+    #[arg(long = "auth-basic-password-env")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub auth_basic_password_env: Option<Box<str>>,
+
+    /// A file holding the token that clients must present with bearer authentication.
+    #[arg(long = "auth-bearer-token-file")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub auth_bearer_token_file: Option<Box<Path>>,
+
+    /// An environment variable holding the token that clients must present with bearer
+    /// authentication.
+    #[arg(long = "auth-bearer-token-env")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub auth_bearer_token_env: Option<Box<str>>,
+
+    /// The endpoints that require credentials, if any are configured.
+    #[arg(long = "auth-endpoints", value_enum, value_delimiter = ',')]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub auth_endpoints: Option<Vec<Endpoint>>,
+
+    /// The oldest that the database can be before `/readyz` reports that the server is not ready,
+    /// in hours. By default, any age is acceptable.
+    #[arg(long = "readiness-max-age")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub readiness_max_age: Option<u64>,
+
+    /// The most addresses that can be looked up in a single batch request.
+    #[arg(long = "batch-lookup-limit")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub batch_lookup_limit: Option<usize>,
+
+    /// Log every HTTP request: its method, path, status, latency, and client.
+    #[arg(long = "access-log")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub access_log: Option<bool>,
+
+    /// Whether to write the access log as text or as JSON, one object per line.
+    #[arg(long = "access-log-format", value_enum)]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub access_log_format: Option<AccessLogFormat>,
+
+    /// Log the addresses looked up in lookup paths. Disable for privacy, replacing them with
+    /// `{address}`, ex. `/v0/ipv4/{address}`.
+    #[arg(long = "access-log-lookups")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub access_log_lookups: Option<bool>,
+
+    /// The most lookup requests per second each client can make, on average. Clients are told apart
+    /// by IPv4 address or IPv6 /64 network. Disabled if unset.
+    #[arg(long = "rate-limit")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub rate_limit: Option<f64>,
+
+    /// The most lookup requests each client can make at once, before being held to `rate_limit`.
+    #[arg(long = "rate-limit-burst")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub rate_limit_burst: Option<u32>,
+
+    /// The most lookup requests in flight at once, across every client, counting from when a
+    /// request is received until its reply is ready, not how long its connection stays open.
+    /// Requests past it are rejected with service unavailable (code 503). Unlimited if unset.
+    #[arg(long = "max-in-flight-lookups")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub max_in_flight_lookups: Option<NonZeroUsize>,
+
+    /// The most connections to the HTTP API and gRPC open at once, across every listener.
+    /// Connections past it are held, and no more accepted, until another closes. Unlimited if
+    /// unset. Not supported over TLS.
+    #[arg(long = "max-connections")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub max_connections: Option<NonZeroUsize>,
+
+    /// Whether to label metrics by country or by continent.
+    #[arg(long = "metrics-aggregation", value_enum)]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub metrics_aggregation: Option<Aggregation>,
+
+    /// Also count lookups by autonomous system, using the database at `asn_db_path`. Adds a series
+    /// for every autonomous system seen.
+    #[arg(long = "metrics-asn")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub metrics_asn: Option<bool>,
+
+    /// Attach the address of the latest lookup to lookup counters as an exemplar, truncated to
+    /// `truncate_ipv4_prefix` or `truncate_ipv6_prefix`. Only rendered in the OpenMetrics format.
+    #[arg(long = "metrics-exemplars")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub metrics_exemplars: Option<bool>,
+
+    /// The prefix of every metric name, ex. `edge_ip_geo_` for `edge_ip_geo_lookups_total`.
+    #[arg(long = "metrics-prefix")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub metrics_prefix: Option<Box<str>>,
+
+    /// Labels to apply to every metric, ex. `instance_role=edge,region=eu`. Each name can only be
+    /// given once, and not be one of the labels metrics already have, like `country_code`.
+    #[arg(long = "metrics-labels", value_delimiter = ',')]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub metrics_labels: Option<Vec<StaticLabel>>,
+
+    /// Label per-country metrics with a `geohash` of the center of the country, for Grafana Geomap
+    /// panels.
+    #[arg(long = "metrics-geohash")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub metrics_geohash: Option<bool>,
+
+    /// Label per-country metrics with the `latitude` and `longitude` of the center of the country.
+    #[arg(long = "metrics-coordinates")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub metrics_coordinates: Option<bool>,
+
+    /// Export `ip_geo_country_info`, labelling every known country with its name and coordinates,
+    /// for joining on `country_code` in PromQL.
+    #[arg(long = "metrics-country-info")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub metrics_country_info: Option<bool>,
+
+    /// Also export the rate per minute of every count by location as a gauge, ex.
+    /// `ip_geo_lookups_per_minute`, computed over a sliding window of this many seconds. For
+    /// consumers that cannot compute rates themselves. Disabled if unset.
+    #[arg(long = "metrics-rate-window")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub metrics_rate_window: Option<NonZeroU64>,
+
+    /// Only export individual series for this many countries, those with the most lookups and
+    /// ingested events, collapsing the rest into `country_code="other"`. Requires aggregating by
+    /// country. Unlimited if unset.
+    #[arg(long = "metrics-top-countries")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub metrics_top_countries: Option<usize>,
+
+    /// How often to rank countries for `metrics_top_countries`, in seconds.
+    #[arg(long = "metrics-top-countries-interval")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub metrics_top_countries_interval: Option<u64>,
+
+    /// A database of autonomous systems, in the tab-separated format of iptoasn.com (ex.
+    /// `ip2asn-combined.tsv`).
+    #[arg(long = "asn-db-path")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub asn_db_path: Option<Box<Path>>,
+
+    /// A snapshot (written by `ip_geo_cli db snapshot`) to serve instead of the IPv4 and IPv6
+    /// databases.
+    #[arg(long = "snapshot-path")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub snapshot_path: Option<Box<Path>>,
+
+    /// A MaxMind database (ex. `GeoLite2-Country.mmdb`) to serve instead of the IPv4 and IPv6
+    /// databases. If it is one of the editions kept up to date with `maxmind_account_id`, the
+    /// databases are reloaded whenever a new copy is downloaded.
+    #[arg(long = "mmdb-path")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub mmdb_path: Option<Box<Path>>,
+
+    /// Comma-separated list of log files to follow, counting the location of the client address
+    /// of each new line.
+    #[arg(long = "log-paths", value_delimiter = ',')]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub log_paths: Option<Vec<Box<Path>>>,
+
+    /// The format of followed log files.
+    #[arg(long = "log-format", value_enum)]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub log_format: Option<LogFormat>,
+
+    /// The name of a pattern from `log_patterns` to parse followed log files with, instead of
+    /// `log_format`.
+    #[arg(long = "log-pattern")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub log_pattern: Option<Box<str>>,
+
+    /// User-defined log formats. Only available in the configuration file.
+    #[arg(skip)]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub log_patterns: Option<Vec<LogPattern>>,
+
+    /// How often to check followed log files for new lines, in milliseconds.
+    #[arg(long = "log-poll-interval")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub log_poll_interval: Option<u64>,
+
+    /// The address to answer DNS queries on over UDP, ex. `0.0.0.0:53`. `TXT` queries for names
+    /// like `4.3.2.1.<dns_zone>` (the address `1.2.3.4` in reverse) are answered with the country
+    /// code, and `A` queries with `127.0.x.y`, the letters of the country code.
+    #[arg(long = "dns-udp")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub dns_udp: Option<SocketAddr>,
+
+    /// The zone to answer DNS queries in, ex. `geo.example.internal`.
+    #[arg(long = "dns-zone")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub dns_zone: Option<Box<str>>,
+
+    /// How long resolvers may cache DNS answers, in seconds.
+    #[arg(long = "dns-ttl")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub dns_ttl: Option<u32>,
+
+    /// The address to serve the gRPC lookup service (`server/proto/ip_geo.proto`) on over
+    /// plaintext HTTP/2, ex. `127.0.0.1:50051`. Requires the `grpc` feature.
+    #[arg(long = "grpc-listen")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub grpc_listen: Option<SocketAddr>,
+
+    /// The address to receive syslog messages on over UDP, ex. `0.0.0.0:514`.
+    #[arg(long = "syslog-udp")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub syslog_udp: Option<SocketAddr>,
+
+    /// The address to receive syslog messages on over TCP, ex. `0.0.0.0:601`.
+    #[arg(long = "syslog-tcp")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub syslog_tcp: Option<SocketAddr>,
+
+    /// The address to receive NetFlow v5, NetFlow v9, and IPFIX exports on over UDP, ex.
+    /// `0.0.0.0:2055`.
+    #[arg(long = "netflow-udp")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub netflow_udp: Option<SocketAddr>,
+
+    /// The address to receive sFlow v5 datagrams on over UDP, ex. `0.0.0.0:6343`.
+    #[arg(long = "sflow-udp")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub sflow_udp: Option<SocketAddr>,
+
+    /// The network interface to capture packets on, ex. `eth0`. Requires building with the `pcap`
+    /// feature.
+    #[arg(long = "capture-interface")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub capture_interface: Option<Box<str>>,
+
+    /// The BPF filter selecting which packets to capture, ex. `tcp port 443`.
+    #[arg(long = "capture-filter")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub capture_filter: Option<Box<str>>,
+
+    /// The systemd units whose journal to follow for failed SSH logins, ex. `ssh,sshd`. Requires
+    /// building with the `journald` feature.
+    #[arg(long = "journald-units", value_delimiter = ',')]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub journald_units: Option<Vec<Box<str>>>,
+
+    /// MaxMind account ID, used to download GeoLite2 databases.
+    /// A Prometheus remote write endpoint to periodically push every series to, ex.
+    /// `https://prometheus.example.com/api/v1/write`.
+    #[arg(long = "remote-write-url")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub remote_write_url: Option<Box<str>>,
+
+    /// How often to push to `remote_write_url`, in seconds.
+    #[arg(long = "remote-write-interval")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub remote_write_interval: Option<u64>,
+
+    /// A file holding a bearer token to authenticate to `remote_write_url` with.
+    #[arg(long = "remote-write-bearer-token-file")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub remote_write_bearer_token_file: Option<Box<Path>>,
+
+    /// An InfluxDB v2 server to periodically write every series to in the line protocol, ex.
+    /// `http://influxdb.example.com:8086`, or a UDP listener prefixed with `udp://`, ex.
+    /// `udp://telegraf:8089`.
+    #[arg(long = "influxdb-url")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub influxdb_url: Option<Box<str>>,
+
+    /// The organization to write to on an InfluxDB server. Required over HTTP.
+    #[arg(long = "influxdb-org")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub influxdb_org: Option<Box<str>>,
+
+    /// The bucket to write to on an InfluxDB server. Required over HTTP.
+    #[arg(long = "influxdb-bucket")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub influxdb_bucket: Option<Box<str>>,
+
+    /// A file holding an API token to authenticate to an InfluxDB server with.
+    #[arg(long = "influxdb-token-file")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub influxdb_token_file: Option<Box<Path>>,
+
+    /// How often to write to `influxdb_url`, in seconds.
+    #[arg(long = "influxdb-interval")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub influxdb_interval: Option<u64>,
+
+    /// An OpenTelemetry collector to periodically export every metric to with OTLP over gRPC, ex.
+    /// `http://localhost:4317`. Also exported to once more before exiting. Requires the `otlp`
+    /// feature.
+    #[arg(long = "otlp-endpoint")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub otlp_endpoint: Option<Box<str>>,
+
+    /// How often to export to `otlp_endpoint`, in seconds.
+    #[arg(long = "otlp-interval")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub otlp_interval: Option<u64>,
+
+    /// A Prometheus Pushgateway to periodically push every metric to, ex.
+    /// `http://pushgateway.example.com:9091`. Also pushed to once more before exiting, for
+    /// short-lived runs.
+    #[arg(long = "pushgateway-url")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub pushgateway_url: Option<Box<str>>,
+
+    /// The job to group metrics pushed to `pushgateway_url` under.
+    #[arg(long = "pushgateway-job")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub pushgateway_job: Option<Box<str>>,
+
+    /// More labels to group metrics pushed to `pushgateway_url` by, ex. `instance=edge-1`.
+    #[arg(long = "pushgateway-grouping", value_delimiter = ',')]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub pushgateway_grouping: Option<Vec<StaticLabel>>,
+
+    /// How often to push to `pushgateway_url`, in seconds.
+    #[arg(long = "pushgateway-interval")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub pushgateway_interval: Option<u64>,
+
+    /// A file to periodically write every metric to, for the node_exporter textfile collector,
+    /// ex. `/var/lib/node_exporter/textfile_collector/ip_geo.prom`. Also written once more before
+    /// exiting.
+    #[arg(long = "textfile-path")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub textfile_path: Option<Box<Path>>,
+
+    /// How often to write to `textfile_path`, in seconds.
+    #[arg(long = "textfile-interval")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub textfile_interval: Option<u64>,
+
+    /// A file to save every counter to periodically and once more before exiting, ex.
+    /// `/var/lib/ip_geo/state.json`, restoring them from it at startup so that totals survive
+    /// restarts. Counters reset on restart if unset.
+    #[arg(long = "state-path")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub state_path: Option<Box<Path>>,
+
+    /// How often to save counters to `state_path`, in seconds.
+    #[arg(long = "state-interval")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub state_interval: Option<u64>,
+
+    /// Whether to save and restore counters with `state_path`, ex. to let counters reset on
+    /// restart without removing it from a configuration file.
+    #[arg(long = "persist-counters")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub persist_counters: Option<bool>,
+
+    /// How many addresses to cache the lookups of, for sources that see the same addresses over
+    /// and over. The cache is emptied when the database is reloaded. Disabled if unset.
+    #[arg(long = "lookup-cache-size")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub lookup_cache_size: Option<NonZeroUsize>,
+
+    /// A StatsD or DogStatsD server to send per-location counts from ingestion sources to, ex.
+    /// `localhost:8125`. Counts are tagged in the DogStatsD format.
+    #[arg(long = "statsd-address")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub statsd_address: Option<Box<str>>,
+
+    /// The prefix of every metric name sent to `statsd_address`.
+    #[arg(long = "statsd-prefix")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub statsd_prefix: Option<Box<str>>,
+
+    #[arg(long = "maxmind-account-id")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub maxmind_account_id: Option<Box<str>>,
+
+    /// MaxMind license key, used to download GeoLite2 databases.
+    #[arg(long = "maxmind-license-key")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub maxmind_license_key: Option<Box<str>>,
+
+    /// Comma-separated list of MaxMind database editions to download.
+    #[arg(long = "maxmind-editions", value_delimiter = ',')]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub maxmind_editions: Option<Vec<Box<str>>>,
+
+    /// The directory to store downloaded MaxMind databases in.
+    #[arg(long = "maxmind-db-dir")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub maxmind_db_dir: Option<Box<Path>>,
+
+    /// The number of old copies of each MaxMind database to keep.
+    #[arg(long = "maxmind-keep")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub maxmind_keep: Option<usize>,
+
+    /// How often to check for new MaxMind databases, in hours.
+    #[arg(long = "maxmind-update-interval")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub maxmind_update_interval: Option<u64>,
+
+    /// A Tor network consensus to count relays by country from, as a path or an `http://` or
+    /// `https://` URL, ex. `/var/lib/tor/cached-consensus`. Exports `ip_geo_tor_relays` and
+    /// `ip_geo_tor_relay_bandwidth`.
+    #[arg(long = "tor-consensus")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub tor_consensus: Option<Box<str>>,
+
+    /// How often to read `tor_consensus` again, in seconds.
+    #[arg(long = "tor-consensus-interval")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub tor_consensus_interval: Option<u64>,
+}
+
+/// Represents what to return for addresses with no associated country.
+#[derive(ValueEnum, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FallbackMode {
+    /// Respond with not found (code 404).
+    None,
+    /// Respond with the placeholder "??" country.
+    Unknown,
+    /// Look the address up in the fallback databases.
+    Database,
 }
 
 /// Replaces missing command-line arguments with values pulled from the configuration file or
@@ -82,6 +664,7 @@ pub struct Arguments {
 /// 4. Mostly the same as paramter #3, but:
 ///     - `field` is of a type that must be cloned.
 ///     - `default` is a function, not a value.
+/// 5. A list of fields without defaults, which are of a type that must be cloned.
 ///
 /// Trailing commas are optional.
 ///
@@ -97,7 +680,8 @@ pub struct Arguments {
 ///     arguments,
 ///     from_config,
 ///     [(ipv4_pair, SocketAddrV4::new(Ipv4Addr::LOCALHOST, 26_000))],
-///     [(ipv4_db_path, || Path::new("/usr/share/tor/geoip").into())]
+///     [(ipv4_db_path, || Path::new("/usr/share/tor/geoip").into())],
+///     [maxmind_account_id]
 /// );
 /// ```
 macro_rules! fill_missing_arguments {
@@ -105,7 +689,8 @@ macro_rules! fill_missing_arguments {
         $arguments:expr,
         $from_config:expr,
         [ $( ( $field:ident, $default:expr $(,)? ) ),+  $(,)? ],
-        [ $( ( $clone_field:ident, $default_fn:expr $(,)? ) ),+  $(,)?] $(,)?
+        [ $( ( $clone_field:ident, $default_fn:expr $(,)? ) ),+  $(,)?],
+        [ $( $optional_field:ident ),* $(,)? ] $(,)?
     ) => {
         $crate::arguments::Arguments {
             $(
@@ -124,16 +709,38 @@ macro_rules! fill_missing_arguments {
                         .unwrap_or_else($default_fn)
                 ),
             )+
+            $(
+                $optional_field: $arguments
+                    .$optional_field
+                    .or_else(|| $from_config.and_then(|v| v.$optional_field.clone())),
+            )*
         }
     };
 }
 
+/// The prefix of the environment variables that set options.
+const ENV_PREFIX: &str = "IP_GEO_";
+
+/// Parse the command-line arguments, reading every option that is not given from an environment
+/// variable named after its configuration key, ex. `IP_GEO_IPV4_DB_PATH` for `ipv4_db_path`.
+///
+/// Command-line arguments override environment variables, which override the configuration file.
+pub fn parse() -> Arguments {
+    let command = Arguments::command().mut_args(|argument| {
+        let variable = format!("{ENV_PREFIX}{}", argument.get_id().as_str().to_uppercase());
+        // Some options are secrets, which `--help` should not print
+        argument.env(variable).hide_env_values(true)
+    });
+
+    Arguments::from_arg_matches(&command.get_matches()).unwrap_or_else(|error| error.exit())
+}
+
 /// For a given `Arguments` result from Clap, return `arguments` with defaults inserted.
 pub fn get_config(arguments: Arguments) -> Arguments {
     let from_config = get_config_file_arguments(&arguments).ok();
     let from_config = from_config.as_ref();
 
-    fill_missing_arguments!(
+    let arguments = fill_missing_arguments!(
         arguments,
         from_config,
         [
@@ -145,20 +752,133 @@ pub fn get_config(arguments: Arguments) -> Arguments {
                 SocketAddrV6::new(Ipv6Addr::LOCALHOST, 26_000, 0, 0)
             ),
             (ipv6_db_len, 60_000),
-            (ipv6_db_comment, '#')
+            (ipv6_db_comment, '#'),
+            (require_manifest, false),
+            (fallback, FallbackMode::None),
+            (serve_http, true),
+            (check, false),
+            (shutdown_timeout, 30),
+            (tls_client_auth_required, false),
+            (proxy_protocol, false),
+            (batch_lookup_limit, 1000),
+            (rate_limit_burst, 20),
+            (access_log, true),
+            (access_log_format, AccessLogFormat::Text),
+            (access_log_lookups, true),
+            (metrics_aggregation, Aggregation::Country),
+            (metrics_geohash, false),
+            (metrics_coordinates, false),
+            (metrics_country_info, false),
+            (metrics_asn, false),
+            (persist_counters, true),
+            (metrics_top_countries_interval, 300),
+            (metrics_exemplars, false),
+            (log_format, LogFormat::Auto),
+            (log_poll_interval, 1000),
+            (truncate_addresses, false),
+            (truncate_ipv4_prefix, DEFAULT_IPV4_PREFIX),
+            (truncate_ipv6_prefix, DEFAULT_IPV6_PREFIX),
+            (remote_write_interval, 15),
+            (influxdb_interval, 15),
+            (otlp_interval, 15),
+            (pushgateway_interval, 60),
+            (textfile_interval, 15),
+            (state_interval, 60),
+            (dns_ttl, 300),
+            (maxmind_keep, 1),
+            (maxmind_update_interval, 24),
+            (tor_consensus_interval, 3600)
         ],
         [
             (config_path, get_default_config_path),
             (ipv4_db_path, || Path::new("/usr/share/tor/geoip").into()),
-            (ipv6_db_path, || Path::new("/usr/share/tor/geoip6").into())
+            (ipv6_db_path, || Path::new("/usr/share/tor/geoip6").into()),
+            (capture_filter, || "ip or ip6".into()),
+            (metrics_prefix, || metrics::DEFAULT_PREFIX.into()),
+            (metrics_labels, Vec::new),
+            (statsd_prefix, || "ip_geo.".into()),
+            (pushgateway_job, || "ip_geo".into()),
+            (pushgateway_grouping, Vec::new),
+            (auth_endpoints, || vec![Endpoint::Metrics, Endpoint::Lookup]),
+            (maxmind_editions, || vec!["GeoLite2-Country".into()]),
+            (maxmind_db_dir, get_default_data_path)
+        ],
+        [
+            listen,
+            listeners,
+            access_rules,
+            unix_socket,
+            unix_socket_mode,
+            trusted_proxies,
+            snapshot_path,
+            mmdb_path,
+            asn_db_path,
+            lang,
+            tls_cert_path,
+            tls_key_path,
+            tls_client_ca_path,
+            auth_basic_username,
+            auth_basic_password_file,
+            auth_basic_password_env,
+            auth_bearer_token_file,
+            auth_bearer_token_env,
+            readiness_max_age,
+            log_paths,
+            log_pattern,
+            log_patterns,
+            databases,
+            dns_udp,
+            dns_zone,
+            grpc_listen,
+            syslog_udp,
+            syslog_tcp,
+            netflow_udp,
+            sflow_udp,
+            capture_interface,
+            journald_units,
+            fallback_ipv4_db_path,
+            fallback_ipv6_db_path,
+            remote_write_url,
+            remote_write_bearer_token_file,
+            influxdb_url,
+            influxdb_org,
+            influxdb_bucket,
+            influxdb_token_file,
+            otlp_endpoint,
+            pushgateway_url,
+            textfile_path,
+            state_path,
+            lookup_cache_size,
+            metrics_top_countries,
+            metrics_rate_window,
+            rate_limit,
+            max_in_flight_lookups,
+            max_connections,
+            statsd_address,
+            maxmind_account_id,
+            maxmind_license_key,
+            tor_consensus
         ]
-    )
+    );
+
+    // Safety: default values are implemented above
+    for (option, labels) in [
+        ("metrics_labels", &arguments.metrics_labels),
+        ("pushgateway_grouping", &arguments.pushgateway_grouping),
+    ] {
+        if let Some(name) = metrics::find_duplicate_label(labels.as_deref().unwrap()) {
+            eprintln!("`{option}` sets the label `{name}` more than once");
+            process::exit(1);
+        }
+    }
+
+    arguments
 }
 
 /// Read the config file for the program for config values.
 ///
 /// Values from the config file override defaults, but are overridden by command-line arguments.
-fn get_config_file_arguments(arguments: &Arguments) -> Result<Arguments, Error> {
+pub fn get_config_file_arguments(arguments: &Arguments) -> Result<Arguments, Error> {
     let config_path = arguments
         .config_path
         .clone()
@@ -182,3 +902,15 @@ fn get_default_config_path() -> Box<Path> {
         .with_extension("toml")
         .into_boxed_path()
 }
+
+/// Return the default location for storing downloaded databases.
+///
+/// # Panics
+///
+/// Panics if run on a platform without a standard data directory (if not MacOS, Windows, or Linux).
+fn get_default_data_path() -> Box<Path> {
+    dirs::data_dir()
+        .expect("an OS-specific data directory")
+        .join(env!("CARGO_PKG_NAME"))
+        .into_boxed_path()
+}
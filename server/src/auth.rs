@@ -0,0 +1,357 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Requiring credentials on HTTP endpoints, with basic or bearer authentication.
+
+use std::{env, fs, path::Path, process, sync::Arc};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::ValueEnum;
+use serde::Deserialize;
+use warp::{reject::Reject, Filter, Rejection};
+
+use crate::arguments::Arguments;
+
+/// Represents a group of endpoints, which can require credentials or be served on only some
+/// listeners.
+#[derive(ValueEnum, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Endpoint {
+    /// `/metrics`.
+    Metrics,
+    /// Every endpoint under `/v0/`, and `/whoami`.
+    Lookup,
+    /// `POST /-/reset`, `POST /-/reload`, and `DELETE /series`, which always require credentials.
+    Admin,
+    /// `/events`, which always requires credentials.
+    Events,
+}
+
+/// The credentials that clients must present to protected endpoints.
+#[derive(Debug)]
+pub struct Credentials {
+    /// The username and password for basic authentication, if enabled.
+    basic: Option<(Box<str>, Box<str>)>,
+    /// The token for bearer authentication, if enabled.
+    bearer: Option<Box<str>>,
+    /// The endpoints that require credentials.
+    endpoints: Box<[Endpoint]>,
+}
+
+impl Credentials {
+    /// Return whether credentials are required for `endpoint`.
+    pub fn protects(&self, endpoint: Endpoint) -> bool {
+        self.endpoints.contains(&endpoint)
+    }
+
+    /// Return whether `authorization`, the value of an `Authorization` header, holds valid
+    /// credentials.
+    pub fn verify(&self, authorization: Option<&str>) -> bool {
+        let Some((scheme, value)) = authorization.and_then(|header| header.split_once(' ')) else {
+            return false;
+        };
+        let value = value.trim();
+
+        if scheme.eq_ignore_ascii_case("basic") {
+            let Some((username, password)) = &self.basic else {
+                return false;
+            };
+            let Ok(decoded) = STANDARD.decode(value) else {
+                return false;
+            };
+            let Some((given_username, given_password)) = std::str::from_utf8(&decoded)
+                .ok()
+                .and_then(|decoded| decoded.split_once(':'))
+            else {
+                return false;
+            };
+
+            // Compare both, even if the username is wrong, to avoid leaking which was wrong
+            let username_matches = constant_time_eq(given_username, username);
+            let password_matches = constant_time_eq(given_password, password);
+            username_matches & password_matches
+        } else if scheme.eq_ignore_ascii_case("bearer") {
+            self.bearer
+                .as_ref()
+                .is_some_and(|token| constant_time_eq(value, token))
+        } else {
+            false
+        }
+    }
+
+    /// Return the `WWW-Authenticate` challenge to reply to unauthorized requests with.
+    fn challenge(&self) -> &'static str {
+        match self.basic {
+            Some(_) => r#"Basic realm="ip_geo", charset="UTF-8""#,
+            None => r#"Bearer realm="ip_geo""#,
+        }
+    }
+}
+
+/// The rejection of a request to a protected endpoint without valid credentials.
+#[derive(Debug)]
+pub struct Unauthorized {
+    /// The `WWW-Authenticate` challenge to reply with.
+    pub challenge: &'static str,
+}
+
+impl Reject for Unauthorized {}
+
+/// Return a filter that rejects requests without valid `credentials` with [`Unauthorized`], if
+/// `credentials` protects `endpoint`.
+pub fn require(
+    credentials: Option<Arc<Credentials>>,
+    endpoint: Endpoint,
+) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    let credentials = credentials.filter(|credentials| credentials.protects(endpoint));
+
+    warp::header::optional::<String>("authorization")
+        .and_then(move |authorization: Option<String>| {
+            let credentials = credentials.clone();
+
+            async move {
+                match credentials {
+                    Some(credentials) if !credentials.verify(authorization.as_deref()) => {
+                        Err(warp::reject::custom(Unauthorized {
+                            challenge: credentials.challenge(),
+                        }))
+                    }
+                    _ => Ok(()),
+                }
+            }
+        })
+        .untuple_one()
+}
+
+/// Return a filter that rejects requests to `endpoint` without valid `credentials` with
+/// [`Unauthorized`].
+///
+/// Administrative endpoints and `/events` are never served without credentials, so rejects every
+/// request as not found if there are none.
+pub fn require_always(
+    credentials: Option<Arc<Credentials>>,
+    endpoint: Endpoint,
+) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    let configured = credentials.is_some();
+
+    warp::any()
+        .and_then(move || async move {
+            match configured {
+                true => Ok(()),
+                false => Err(warp::reject::not_found()),
+            }
+        })
+        .untuple_one()
+        .and(require(credentials, endpoint))
+}
+
+/// For a given set of `Arguments`, return the credentials to require, if any.
+///
+/// Exits if the credentials are incomplete or cannot be read.
+pub fn get_credentials(arguments: &Arguments) -> Option<Arc<Credentials>> {
+    let password = read_secret(
+        "basic authentication password",
+        arguments.auth_basic_password_file.as_deref(),
+        arguments.auth_basic_password_env.as_deref(),
+    );
+    let basic = match (arguments.auth_basic_username.clone(), password) {
+        (Some(username), Some(password)) => Some((username, password)),
+        (None, None) => None,
+        _ => {
+            eprintln!("Basic authentication requires both a username and a password");
+            process::exit(1);
+        }
+    };
+
+    let bearer = read_secret(
+        "bearer token",
+        arguments.auth_bearer_token_file.as_deref(),
+        arguments.auth_bearer_token_env.as_deref(),
+    );
+
+    if basic.is_none() && bearer.is_none() {
+        return None;
+    }
+
+    // Safety: `arguments::get_config()` implements default values
+    let mut endpoints = arguments.auth_endpoints.clone().unwrap();
+    for endpoint in [Endpoint::Admin, Endpoint::Events] {
+        if !endpoints.contains(&endpoint) {
+            endpoints.push(endpoint);
+        }
+    }
+
+    Some(Arc::new(Credentials {
+        basic,
+        bearer,
+        endpoints: endpoints.into(),
+    }))
+}
+
+/// Read a secret from the file at `path` (ignoring trailing whitespace) or the environment
+/// variable `variable`, whichever is set.
+///
+/// Exits if both are set, or if the secret cannot be read.
+fn read_secret(name: &str, path: Option<&Path>, variable: Option<&str>) -> Option<Box<str>> {
+    let secret: Box<str> = match (path, variable) {
+        (None, None) => return None,
+        (Some(path), None) => match fs::read_to_string(path) {
+            Ok(secret) => secret.trim_end().into(),
+            Err(error) => {
+                eprintln!("Could not read {name} from {}: {error}", path.display());
+                process::exit(1);
+            }
+        },
+        (None, Some(variable)) => match env::var(variable) {
+            Ok(secret) => secret.into(),
+            Err(error) => {
+                eprintln!("Could not read {name} from ${variable}: {error}");
+                process::exit(1);
+            }
+        },
+        (Some(_), Some(_)) => {
+            eprintln!(
+                "The {name} can only be read from a file or an environment variable, not both"
+            );
+            process::exit(1);
+        }
+    };
+
+    if secret.is_empty() {
+        eprintln!("The {name} is empty");
+        process::exit(1);
+    }
+
+    Some(secret)
+}
+
+/// Compare two strings in time that depends only on their lengths, not their contents.
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    /// Return the credentials configured by `config`, with `secret` in the file at
+    /// `{secret_file}`.
+    fn credentials(config: &str, secret: &str) -> Arc<Credentials> {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(secret.as_bytes()).unwrap();
+
+        let config = config.replace("{secret_file}", file.path().to_str().unwrap());
+        let arguments: Arguments = toml::from_str(&config).unwrap();
+
+        get_credentials(&arguments).unwrap()
+    }
+
+    fn basic(credentials: &str) -> String {
+        format!("Basic {}", STANDARD.encode(credentials))
+    }
+
+    #[test]
+    fn test_verify_basic() {
+        let credentials = credentials(
+            r#"
+            auth_endpoints = ["lookup"]
+            auth_basic_username = "admin"
+            auth_basic_password_file = "{secret_file}"
+            "#,
+            "hunter:2\n",
+        );
+
+        assert!(credentials.verify(Some(&basic("admin:hunter:2"))));
+        assert!(!credentials.verify(None));
+
+        // Wrong usernames and passwords
+        assert!(!credentials.verify(Some(&basic("root:hunter:2"))));
+        assert!(!credentials.verify(Some(&basic("admin:hunter:3"))));
+        assert!(!credentials.verify(Some(&basic("admin:hunter"))));
+        assert!(!credentials.verify(Some(&basic("admin:"))));
+        assert!(!credentials.verify(Some(&basic("admin"))));
+
+        // Schemes are case-insensitive
+        let encoded = STANDARD.encode("admin:hunter:2");
+        assert!(credentials.verify(Some(&format!("basic {encoded}"))));
+        assert!(credentials.verify(Some(&format!("BASIC  {encoded} "))));
+
+        // Invalid base64 or UTF-8
+        assert!(!credentials.verify(Some("Basic YWRtaW46aHVudGVyOjI")));
+        assert!(!credentials.verify(Some("Basic !!!!")));
+        assert!(!credentials.verify(Some(&format!("Basic {}", STANDARD.encode(b"admin:\xff")))));
+        assert!(!credentials.verify(Some("Basic")));
+
+        // Bearer tokens are not accepted in place of basic authentication
+        assert!(!credentials.verify(Some("Bearer hunter:2")));
+        assert!(!credentials.verify(Some(&format!("Bearer {encoded}"))));
+        assert!(!credentials.verify(Some(&format!("Digest {encoded}"))));
+    }
+
+    #[test]
+    fn test_verify_bearer() {
+        let credentials = credentials(
+            r#"
+            auth_endpoints = ["metrics"]
+            auth_bearer_token_file = "{secret_file}"
+            "#,
+            "s3cret",
+        );
+
+        assert!(credentials.verify(Some("Bearer s3cret")));
+        assert!(credentials.verify(Some("bearer s3cret ")));
+        assert!(!credentials.verify(Some("Bearer s3cre")));
+        assert!(!credentials.verify(Some("Bearer s3crets")));
+        assert!(!credentials.verify(Some("Bearer")));
+        assert!(!credentials.verify(Some(&basic("s3cret:s3cret"))));
+    }
+
+    #[test]
+    fn test_protects() {
+        let credentials = credentials(
+            r#"
+            auth_endpoints = ["lookup"]
+            auth_bearer_token_file = "{secret_file}"
+            "#,
+            "s3cret",
+        );
+
+        // Administrative endpoints and `/events` are always protected
+        assert!(credentials.protects(Endpoint::Lookup));
+        assert!(credentials.protects(Endpoint::Admin));
+        assert!(credentials.protects(Endpoint::Events));
+        assert!(!credentials.protects(Endpoint::Metrics));
+
+        let arguments: Arguments = toml::from_str(r#"auth_endpoints = ["lookup"]"#).unwrap();
+        assert!(get_credentials(&arguments).is_none());
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq("", ""));
+        assert!(constant_time_eq("s3cret", "s3cret"));
+        assert!(!constant_time_eq("s3cret", "s3creT"));
+        assert!(!constant_time_eq("s3cret", "s3cre"));
+        assert!(!constant_time_eq("", "s"));
+    }
+}
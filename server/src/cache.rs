@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Caching the results of lookups, for sources that see the same addresses over and over.
+
+use std::{net::IpAddr, num::NonZeroUsize, sync::Mutex};
+
+use ip_geo::country_list::Country;
+use lru::LruCache;
+
+/// A least recently used cache of the countries of addresses.
+#[derive(Debug)]
+pub struct LookupCache {
+    entries: Mutex<LruCache<IpAddr, Country>>,
+}
+
+impl LookupCache {
+    /// Create a new, empty `LookupCache`, holding up to `capacity` addresses.
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    /// Return the most addresses the cache can hold.
+    pub fn capacity(&self) -> NonZeroUsize {
+        self.entries.lock().expect("an unpoisoned lock").cap()
+    }
+
+    /// Return the cached country of `address`, if any, marking it as recently used.
+    pub fn get(&self, address: IpAddr) -> Option<Country> {
+        let mut entries = self.entries.lock().expect("an unpoisoned lock");
+        entries.get(&address).cloned()
+    }
+
+    /// Cache the country of `address`, evicting the least recently used address if the cache is
+    /// full.
+    pub fn insert(&self, address: IpAddr, country: Country) {
+        let mut entries = self.entries.lock().expect("an unpoisoned lock");
+        entries.put(address, country);
+    }
+}
@@ -0,0 +1,303 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Reporting on startup without serving, with `--check`, for deployment pre-flight checks.
+
+use std::{
+    fs, io,
+    net::{SocketAddr, TcpListener, UdpSocket},
+    process,
+    time::Duration,
+};
+
+use ip_geo::GeoDatabase;
+
+use crate::{
+    api::{self, Selection, Target},
+    arguments::{self, Arguments},
+    error::Error,
+    parse::NamedDatabases,
+    tls::TlsConfig,
+};
+
+/// Everything loaded at startup that `--check` reports on.
+pub struct Startup<'a> {
+    pub database: &'a GeoDatabase,
+    pub load_duration: Duration,
+    pub named_databases: &'a NamedDatabases,
+    pub tls: Option<&'a TlsConfig>,
+}
+
+/// Print a report on the configuration file, every database in `startup`, whether each of
+/// `targets` and every other listener can be bound, and whether the packet capture and log files
+/// can be opened, then exit, with 1 if anything failed.
+///
+/// Every step that fails outright (ex. a database that cannot be parsed) has already exited by
+/// the time this is called, so only the configuration file, the listeners, the capture, and the
+/// log files can fail here. The configuration file is read again, as `arguments::get_config()`
+/// ignores one that cannot be.
+pub fn report(arguments: &Arguments, targets: Vec<(Target, Selection)>, startup: Startup) -> ! {
+    let mut failed = false;
+
+    // Safety: `arguments::get_config()` implements default values
+    let config_path = arguments.config_path.as_deref().unwrap();
+    match arguments::get_config_file_arguments(arguments) {
+        Ok(_) => println!("Configuration: read {}", config_path.display()),
+        Err(Error::Io(error)) if error.kind() == io::ErrorKind::NotFound => {
+            println!(
+                "Configuration: {} not found, using defaults",
+                config_path.display()
+            );
+        }
+        Err(Error::TomlDeserialize(error)) => {
+            failed = true;
+            println!(
+                "Configuration: could not parse {}: {error}",
+                config_path.display()
+            );
+        }
+        Err(error) => {
+            failed = true;
+            println!(
+                "Configuration: could not read {}: {error}",
+                config_path.display()
+            );
+        }
+    }
+
+    println!(
+        "Database: {} loaded in {:.2?}",
+        describe_database(startup.database),
+        startup.load_duration
+    );
+    for (name, database) in startup.named_databases {
+        println!("Database `{name}`: {}", describe_database(database));
+    }
+    if let Some(path) = &arguments.asn_db_path {
+        println!("ASN database: loaded {}", path.display());
+    }
+
+    // Safety: `arguments::get_config()` implements default values
+    match arguments.serve_http.unwrap() {
+        true => {
+            for (target, selection) in targets {
+                let served = api::describe(&selection);
+                match check_target(target, startup.tls.is_some()) {
+                    Ok(location) => println!("Listener: {location} ({served}) can be bound"),
+                    Err(error) => {
+                        failed = true;
+                        println!("Listener: {error}");
+                    }
+                }
+            }
+        }
+        false => println!("Listener: none, serving HTTP is disabled"),
+    }
+
+    for (step, result) in check_ingestion(arguments) {
+        match result {
+            Ok(report) => println!("{step}: {report}"),
+            Err(error) => {
+                failed = true;
+                println!("{step}: {error}");
+            }
+        }
+    }
+
+    match failed {
+        true => {
+            println!("Check failed");
+            process::exit(1);
+        }
+        false => {
+            println!("Check passed");
+            process::exit(0);
+        }
+    }
+}
+
+/// Describe the number of entries in `database`, ex. `200000 IPv4 and 60000 IPv6 entries`.
+fn describe_database(database: &GeoDatabase) -> String {
+    format!(
+        "{} IPv4 and {} IPv6 entries",
+        database.ipv4().len(),
+        database.ipv6().len()
+    )
+}
+
+/// Bind `target` and release it again, returning where it would be served or why it could not be,
+/// like `serve!`.
+fn check_target(target: Target, tls: bool) -> Result<String, String> {
+    match (tls, target) {
+        (true, Target::ProxyProtocol(_)) => {
+            Err("Serving over TLS is not supported with the PROXY protocol".into())
+        }
+        (true, Target::Socket(_)) => {
+            Err("Serving over TLS is not supported on sockets passed by systemd".into())
+        }
+        #[cfg(unix)]
+        (true, Target::Unix { .. }) => {
+            Err("Serving over TLS is not supported on Unix sockets".into())
+        }
+        (tls, Target::Address(address)) => {
+            let scheme = match tls {
+                true => "https",
+                false => "http",
+            };
+
+            TcpListener::bind(address)
+                .and_then(|listener| listener.local_addr())
+                .map(|address| format!("{scheme}://{address}"))
+                .map_err(|error| format!("Could not listen on {address}: {error}"))
+        }
+        (false, Target::ProxyProtocol(address)) => TcpListener::bind(address)
+            .and_then(|listener| listener.local_addr())
+            .map(|address| format!("http://{address} (PROXY protocol)"))
+            .map_err(|error| format!("Could not listen on {address}: {error}")),
+        (false, Target::Socket(listener)) => listener
+            .local_addr()
+            .map(|address| format!("http://{address}"))
+            .map_err(|error| format!("Could not use socket passed by systemd: {error}")),
+        #[cfg(unix)]
+        (false, Target::Unix { path, .. }) => {
+            let location = format!("unix:{}", path.display());
+
+            check_unix(&path)
+                .map(|()| location.clone())
+                .map_err(|error| format!("Could not listen on {location}: {error}"))
+        }
+    }
+}
+
+/// Check that a Unix socket could be bound at `path` by binding a temporary socket beside it, as
+/// `api::bind_unix` would replace a socket already there, ex. that of a running server.
+#[cfg(unix)]
+fn check_unix(path: &std::path::Path) -> io::Result<()> {
+    use std::{ffi::OsString, os::unix::fs::FileTypeExt};
+
+    // `api::bind_unix` only replaces sockets, never other files
+    if fs::symlink_metadata(path).is_ok_and(|metadata| !metadata.file_type().is_socket()) {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "a file that is not a socket is in the way",
+        ));
+    }
+
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file path"))?;
+    let mut temporary = OsString::from(".");
+    temporary.push(name);
+    temporary.push(format!(".check-{}", process::id()));
+    let temporary = path.with_file_name(temporary);
+
+    let listener = std::os::unix::net::UnixListener::bind(&temporary);
+    // Never leave the temporary socket behind, as nothing is served on it
+    let _ = fs::remove_file(&temporary);
+
+    listener.map(|_| ())
+}
+
+/// Check every listener besides the HTTP targets, the packet capture, and the log files to follow,
+/// returning the step each belongs to and where it would be served or why it could not be.
+fn check_ingestion(arguments: &Arguments) -> Vec<(&'static str, Result<String, String>)> {
+    let udp = |address: SocketAddr, served: &str| {
+        UdpSocket::bind(address)
+            .and_then(|socket| socket.local_addr())
+            .map(|address| format!("udp://{address} ({served}) can be bound"))
+            .map_err(|error| format!("Could not listen for {served} on udp://{address}: {error}"))
+    };
+    let tcp = |address: SocketAddr, scheme: &str, served: &str| {
+        TcpListener::bind(address)
+            .and_then(|listener| listener.local_addr())
+            .map(|address| format!("{scheme}://{address} ({served}) can be bound"))
+            .map_err(|error| {
+                format!("Could not listen for {served} on {scheme}://{address}: {error}")
+            })
+    };
+
+    let mut checks = Vec::new();
+
+    if let Some(address) = arguments.syslog_udp {
+        checks.push(("Listener", udp(address, "syslog")));
+    }
+    if let Some(address) = arguments.syslog_tcp {
+        checks.push(("Listener", tcp(address, "tcp", "syslog")));
+    }
+    for address in [arguments.netflow_udp, arguments.sflow_udp]
+        .into_iter()
+        .flatten()
+    {
+        checks.push(("Listener", udp(address, "flows")));
+    }
+    if let Some(address) = arguments.dns_udp {
+        let result = match arguments.dns_zone {
+            Some(_) => udp(address, "DNS"),
+            None => Err(
+                "Answering DNS queries requires a zone, ex. `--dns-zone geo.example.internal`"
+                    .into(),
+            ),
+        };
+        checks.push(("Listener", result));
+    }
+    if let Some(address) = arguments.grpc_listen {
+        #[cfg(feature = "grpc")]
+        checks.push(("Listener", tcp(address, "http", "gRPC")));
+        #[cfg(not(feature = "grpc"))]
+        checks.push((
+            "Listener",
+            Err(format!("Could not serve gRPC on http://{address}: requires building with the `grpc` feature")),
+        ));
+    }
+
+    if let Some(interface) = &arguments.capture_interface {
+        // Safety: `arguments::get_config()` implements default values
+        let filter = arguments.capture_filter.as_deref().unwrap();
+        checks.push(("Capture", check_capture(interface, filter)));
+    }
+
+    for path in arguments.log_paths.iter().flatten() {
+        let result = match fs::File::open(path) {
+            Ok(_) => Ok(format!("{} can be read", path.display())),
+            // Followers wait for files that do not exist yet
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(format!(
+                "{} does not exist yet, and will be followed once it does",
+                path.display()
+            )),
+            Err(error) => Err(format!("Could not read {}: {error}", path.display())),
+        };
+        checks.push(("Log file", result));
+    }
+
+    checks
+}
+
+/// Check that packets matching `filter` can be captured on `interface`.
+#[cfg(feature = "pcap")]
+fn check_capture(interface: &str, filter: &str) -> Result<String, String> {
+    crate::ingest::capture::open_capture(interface, filter)
+        .map(|_| format!("{interface} can be captured on, matching `{filter}`"))
+        .map_err(|error| format!("Could not capture packets on {interface}: {error}"))
+}
+
+/// Report that packets cannot be captured, because this build cannot capture packets.
+#[cfg(not(feature = "pcap"))]
+fn check_capture(interface: &str, _filter: &str) -> Result<String, String> {
+    Err(format!(
+        "Could not capture packets on {interface}: requires building with the `pcap` feature"
+    ))
+}
@@ -0,0 +1,601 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Working out the real address of a client behind a reverse proxy, from a PROXY protocol header
+//! or from the `X-Forwarded-For` headers of trusted proxies.
+
+use std::{
+    convert::Infallible,
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    pin::Pin,
+    process,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use ip_geo::cidr::IpCidr;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf},
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+    task::futures::TaskLocalFuture,
+};
+use tokio_stream::wrappers::ReceiverStream;
+use warp::{
+    http::{HeaderMap, Request},
+    hyper::{service::Service, Body},
+    Filter,
+};
+
+use crate::{
+    arguments::Arguments,
+    limit::{ConnectionLimit, Limited},
+};
+
+tokio::task_local! {
+    /// The address of the client of the connection being served, if Warp cannot know it: the one
+    /// named by its PROXY protocol header, or the peer of a socket passed by systemd.
+    static PROXIED_PEER: SocketAddr;
+}
+
+/// How long a connection has to send its PROXY protocol header before it is dropped.
+const PROXY_HEADER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The signature that every PROXY protocol v2 header starts with.
+const V2_SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+
+/// The longest a PROXY protocol v1 header can be, including the trailing CRLF.
+const MAX_V1_HEADER_LEN: usize = 107;
+
+/// The networks of reverse proxies whose `X-Forwarded-For` headers are trusted.
+#[derive(Debug, Default)]
+pub struct TrustedProxies {
+    networks: Box<[IpCidr]>,
+}
+
+impl TrustedProxies {
+    /// Return whether `address` belongs to a trusted proxy.
+    fn trusts(&self, address: IpAddr) -> bool {
+        self.networks.iter().any(|cidr| cidr.contains(address))
+    }
+
+    /// Return the address of the client of a request from `peer`, with the `X-Forwarded-For`
+    /// header `forwarded_for`.
+    ///
+    /// Each proxy appends the address it received the request from, so the addresses are read from
+    /// last to first for as long as they were appended by a trusted proxy. Earlier addresses could
+    /// have been made up by the client.
+    pub fn resolve(&self, peer: Option<IpAddr>, forwarded_for: Option<&str>) -> Option<IpAddr> {
+        let mut client = peer?.to_canonical();
+
+        for hop in forwarded_for
+            .into_iter()
+            .flat_map(|header| header.rsplit(','))
+        {
+            if !self.trusts(client) {
+                break;
+            }
+            match hop.trim().parse::<IpAddr>() {
+                Ok(hop) => client = hop.to_canonical(),
+                Err(_) => break,
+            }
+        }
+
+        Some(client)
+    }
+}
+
+/// For a given set of arguments, return the networks of trusted proxies.
+///
+/// Exits if any network is not valid CIDR notation.
+pub fn get_trusted_proxies(arguments: &Arguments) -> Arc<TrustedProxies> {
+    let networks = arguments
+        .trusted_proxies
+        .iter()
+        .flatten()
+        .map(|network| {
+            network.parse().unwrap_or_else(|error| {
+                eprintln!("Invalid trusted proxy: {error}");
+                process::exit(1);
+            })
+        })
+        .collect();
+
+    Arc::new(TrustedProxies { networks })
+}
+
+/// Return the address of the peer of the connection being served: the client named by its PROXY
+/// protocol header or the peer of a socket passed by systemd, if either, or else `remote`, ex.
+/// from `warp::addr::remote()`.
+///
+/// IPv4 clients of dual-stack listeners, ex. `[::]:26000`, connect from IPv4-mapped IPv6
+/// addresses, so those are converted back to IPv4.
+pub fn peer(remote: Option<SocketAddr>) -> Option<IpAddr> {
+    PROXIED_PEER
+        .try_with(|peer| *peer)
+        .ok()
+        .or(remote)
+        .map(|address| address.ip().to_canonical())
+}
+
+/// Return a filter that extracts the address of the client, if it is known, which it is not on
+/// Unix sockets.
+pub fn address(
+    proxies: Arc<TrustedProxies>,
+) -> impl Filter<Extract = (Option<IpAddr>,), Error = Infallible> + Clone {
+    warp::addr::remote()
+        .and(warp::header::headers_cloned())
+        .map(move |remote, headers: HeaderMap| {
+            proxies.resolve(peer(remote), forwarded_for(&headers).as_deref())
+        })
+}
+
+/// Join every `X-Forwarded-For` header of a request into a single list, in the order they were
+/// sent.
+///
+/// Some proxies add a header of their own rather than appending to the last one, so the first
+/// header alone may be entirely made up by the client. Headers that are not ASCII are kept as an
+/// invalid hop, which nothing before can be trusted past.
+pub fn forwarded_for(headers: &HeaderMap) -> Option<String> {
+    let hops: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .map(|header| header.to_str().unwrap_or("invalid"))
+        .collect();
+
+    (!hops.is_empty()).then(|| hops.join(","))
+}
+
+/// A connection that started with a PROXY protocol header, which has already been read.
+#[derive(Debug)]
+pub struct ProxiedStream {
+    stream: TcpStream,
+    /// The client named by the header, or the peer of the connection if the header did not name
+    /// one, ex. for health checks by the proxy itself.
+    peer: SocketAddr,
+}
+
+impl ProxiedStream {
+    pub fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+}
+
+impl AsyncRead for ProxiedStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for ProxiedStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_shutdown(cx)
+    }
+}
+
+/// Accept connections on `listener` in the background, holding each until `connections` (if
+/// capped) allows another, reading the PROXY protocol header of each before passing it on. Connections without a
+/// valid header are dropped.
+pub fn accept_proxied(
+    listener: TcpListener,
+    connections: Option<Arc<ConnectionLimit>>,
+) -> ReceiverStream<io::Result<Limited<ProxiedStream>>> {
+    let (sender, receiver) = mpsc::channel(64);
+
+    tokio::spawn(async move {
+        loop {
+            let mut stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(error) => {
+                    eprintln!("Could not accept connection: {error}");
+                    continue;
+                }
+            };
+            // Connections waiting for their header count against the cap too
+            let permit = match &connections {
+                Some(connections) => Some(connections.acquire().await),
+                None => None,
+            };
+            // The server has shut down
+            if sender.is_closed() {
+                break;
+            }
+
+            let sender = sender.clone();
+            tokio::spawn(async move {
+                let header = tokio::time::timeout(PROXY_HEADER_TIMEOUT, read_header(&mut stream));
+                let Ok(Ok(source)) = header.await else {
+                    return;
+                };
+                let Some(peer) = source.or_else(|| stream.peer_addr().ok()) else {
+                    return;
+                };
+
+                let stream = Limited::new(ProxiedStream { stream, peer }, permit);
+                let _ = sender.send(Ok(stream)).await;
+            });
+        }
+    });
+
+    ReceiverStream::new(receiver)
+}
+
+/// Read a PROXY protocol v1 or v2 header from the start of `stream`, returning the address of the
+/// client that it names, if any.
+///
+/// Returns an error if the stream does not start with a valid header.
+async fn read_header(stream: &mut (impl AsyncRead + Unpin)) -> io::Result<Option<SocketAddr>> {
+    let mut start = [0; V2_SIGNATURE.len()];
+    stream.read_exact(&mut start).await?;
+
+    if start == V2_SIGNATURE {
+        return read_v2_header(stream).await;
+    }
+    if !start.starts_with(b"PROXY ") {
+        return Err(invalid_header("missing PROXY protocol header"));
+    }
+
+    // The rest of a v1 header is read a byte at a time to avoid reading past its end
+    let mut header = start.to_vec();
+    while !header.ends_with(b"\r\n") {
+        if header.len() >= MAX_V1_HEADER_LEN {
+            return Err(invalid_header("PROXY protocol header is too long"));
+        }
+        header.push(stream.read_u8().await?);
+    }
+
+    parse_v1_header(&header)
+}
+
+/// Parse a PROXY protocol v1 header, ex. `PROXY TCP4 192.0.2.1 198.51.100.1 56324 443\r\n`,
+/// returning the address of the client that it names, if any.
+fn parse_v1_header(header: &[u8]) -> io::Result<Option<SocketAddr>> {
+    let header = std::str::from_utf8(header)
+        .map_err(|_| invalid_header("PROXY protocol header is not ASCII"))?;
+    let mut fields = header.trim_end().split(' ').skip(1);
+
+    let is_ipv6 = match fields.next() {
+        Some("UNKNOWN") => return Ok(None),
+        Some("TCP4") => false,
+        Some("TCP6") => true,
+        _ => return Err(invalid_header("unknown PROXY protocol family")),
+    };
+
+    let fields: Vec<&str> = fields.collect();
+    let [source, _destination, source_port, _destination_port] = fields[..] else {
+        return Err(invalid_header("malformed PROXY protocol header"));
+    };
+    let (Ok(source), Ok(source_port)) = (source.parse::<IpAddr>(), source_port.parse::<u16>())
+    else {
+        return Err(invalid_header("malformed PROXY protocol header"));
+    };
+    if source.is_ipv6() != is_ipv6 {
+        return Err(invalid_header(
+            "PROXY protocol address does not match its family",
+        ));
+    }
+
+    Ok(Some(SocketAddr::new(source, source_port)))
+}
+
+/// Read the rest of a PROXY protocol v2 header, after its signature, returning the address of the
+/// client that it names, if any.
+async fn read_v2_header(stream: &mut (impl AsyncRead + Unpin)) -> io::Result<Option<SocketAddr>> {
+    let mut fixed = [0; 4];
+    stream.read_exact(&mut fixed).await?;
+    let [version_command, family, len @ ..] = fixed;
+
+    if version_command >> 4 != 2 {
+        return Err(invalid_header("unsupported PROXY protocol version"));
+    }
+
+    let mut addresses = vec![0; u16::from_be_bytes(len).into()];
+    stream.read_exact(&mut addresses).await?;
+
+    match version_command & 0x0f {
+        // `LOCAL` connections, ex. health checks by the proxy itself, name no client
+        0x0 => return Ok(None),
+        // `PROXY` connections
+        0x1 => (),
+        _ => return Err(invalid_header("unknown PROXY protocol command")),
+    }
+
+    let port = |offset: usize| u16::from_be_bytes([addresses[offset], addresses[offset + 1]]);
+    match family >> 4 {
+        // Source and destination addresses, then source and destination ports
+        0x1 if addresses.len() >= 12 => {
+            let source: [u8; 4] = addresses[..4].try_into().expect("a four byte slice");
+            Ok(Some(SocketAddr::new(
+                Ipv4Addr::from(source).into(),
+                port(8),
+            )))
+        }
+        0x2 if addresses.len() >= 36 => {
+            let source: [u8; 16] = addresses[..16].try_into().expect("a sixteen byte slice");
+            Ok(Some(SocketAddr::new(
+                Ipv6Addr::from(source).into(),
+                port(32),
+            )))
+        }
+        0x1 | 0x2 => Err(invalid_header("PROXY protocol addresses are too short")),
+        // Unspecified or Unix socket addresses name no client that can be looked up
+        _ => Ok(None),
+    }
+}
+
+fn invalid_header(error: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+/// A service that serves a connection whose client Warp cannot know, ex. one named by a PROXY
+/// protocol header or the peer of a socket passed by systemd, making it available to [`peer`].
+#[derive(Debug, Clone)]
+pub struct WithPeer<S> {
+    peer: SocketAddr,
+    service: S,
+}
+
+impl<S> WithPeer<S> {
+    pub fn new(peer: SocketAddr, service: S) -> Self {
+        Self { peer, service }
+    }
+}
+
+impl<S: Service<Request<Body>>> Service<Request<Body>> for WithPeer<S> {
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = TaskLocalFuture<SocketAddr, S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        PROXIED_PEER.scope(self.peer, self.service.call(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use warp::http::HeaderValue;
+
+    use super::*;
+
+    /// Read a PROXY protocol header from the start of `bytes`.
+    async fn read(bytes: &[u8]) -> io::Result<Option<SocketAddr>> {
+        read_header(&mut &bytes[..]).await
+    }
+
+    /// Build a PROXY protocol v2 header of `command` and `family`, naming `addresses`.
+    fn v2_header(command: u8, family: u8, addresses: &[u8]) -> Vec<u8> {
+        let len = (addresses.len() as u16).to_be_bytes();
+        [
+            &V2_SIGNATURE[..],
+            &[0x20 | command, family],
+            &len,
+            addresses,
+        ]
+        .concat()
+    }
+
+    #[tokio::test]
+    async fn test_v1_header() {
+        assert_eq!(
+            read(b"PROXY TCP4 192.0.2.1 198.51.100.1 56324 443\r\nGET / HTTP/1.1")
+                .await
+                .unwrap(),
+            Some("192.0.2.1:56324".parse().unwrap())
+        );
+        assert_eq!(
+            read(b"PROXY TCP6 2001:db8::1 2001:db8::2 56324 443\r\n")
+                .await
+                .unwrap(),
+            Some("[2001:db8::1]:56324".parse().unwrap())
+        );
+        assert_eq!(read(b"PROXY UNKNOWN\r\n").await.unwrap(), None);
+        assert_eq!(
+            read(b"PROXY UNKNOWN 192.0.2.1 198.51.100.1 56324 443\r\n")
+                .await
+                .unwrap(),
+            None
+        );
+
+        for garbled in [
+            &b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n"[..],
+            b"PROXY UDP4 192.0.2.1 198.51.100.1 56324 443\r\n",
+            b"PROXY TCP4 192.0.2.1 198.51.100.1 56324\r\n",
+            b"PROXY TCP4 192.0.2.1  198.51.100.1 56324 443\r\n",
+            b"PROXY TCP4 192.0.2 198.51.100.1 56324 443\r\n",
+            b"PROXY TCP4 192.0.2.1 198.51.100.1 65536 443\r\n",
+            b"PROXY TCP4 2001:db8::1 2001:db8::2 56324 443\r\n",
+            b"PROXY TCP6 192.0.2.1 198.51.100.1 56324 443\r\n",
+            b"PROXY TCP4 \xff\xfe 198.51.100.1 56324 443\r\n",
+        ] {
+            let error = read(garbled).await.unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{garbled:?}");
+        }
+
+        // Headers without their CRLF, whether cut short or too long
+        let short = read(b"PROXY TCP4 192.0.2.1 198.51.100.1")
+            .await
+            .unwrap_err();
+        assert_eq!(short.kind(), io::ErrorKind::UnexpectedEof);
+        let long = [&b"PROXY TCP6 "[..], &[b'1'; 200]].concat();
+        assert_eq!(
+            read(&long).await.unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert!(read(b"PROXY").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_v2_header() {
+        // TCP over IPv4, with a TLV after the addresses
+        let ipv4 = [
+            [192, 0, 2, 1, 198, 51, 100, 1, 0xdc, 0x04, 0x01, 0xbb].as_slice(),
+            &[4, 0, 1, 0],
+        ]
+        .concat();
+        assert_eq!(
+            read(&v2_header(0x1, 0x11, &ipv4)).await.unwrap(),
+            Some("192.0.2.1:56324".parse().unwrap())
+        );
+
+        let ipv6 = [
+            "2001:db8::1"
+                .parse::<Ipv6Addr>()
+                .unwrap()
+                .octets()
+                .as_slice(),
+            &"2001:db8::2".parse::<Ipv6Addr>().unwrap().octets(),
+            &[0xdc, 0x04, 0x01, 0xbb],
+        ]
+        .concat();
+        assert_eq!(
+            read(&v2_header(0x1, 0x21, &ipv6)).await.unwrap(),
+            Some("[2001:db8::1]:56324".parse().unwrap())
+        );
+
+        // `LOCAL` connections, and unspecified or Unix socket addresses, name no client
+        assert_eq!(read(&v2_header(0x0, 0x11, &ipv4)).await.unwrap(), None);
+        assert_eq!(read(&v2_header(0x0, 0x00, &[])).await.unwrap(), None);
+        assert_eq!(read(&v2_header(0x1, 0x00, &[])).await.unwrap(), None);
+        assert_eq!(read(&v2_header(0x1, 0x31, &[0; 216])).await.unwrap(), None);
+
+        // Unknown versions and commands, and addresses too short for their family
+        let mut version_1 = v2_header(0x1, 0x11, &ipv4);
+        version_1[12] = 0x11;
+        assert!(read(&version_1).await.is_err());
+        assert!(read(&v2_header(0x2, 0x11, &ipv4)).await.is_err());
+        assert!(read(&v2_header(0x1, 0x11, &ipv4[..8])).await.is_err());
+        assert!(read(&v2_header(0x1, 0x21, &ipv4)).await.is_err());
+
+        // Headers cut short, in their fixed part or their addresses
+        let full = v2_header(0x1, 0x11, &ipv4);
+        for len in [4, 12, 14, full.len() - 1] {
+            assert_eq!(
+                read(&full[..len]).await.unwrap_err().kind(),
+                io::ErrorKind::UnexpectedEof,
+                "{len}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_resolve() {
+        let proxies = TrustedProxies {
+            networks: [
+                "10.0.0.0/8".parse().unwrap(),
+                "2001:db8::/32".parse().unwrap(),
+            ]
+            .into(),
+        };
+        let resolve = |peer: &str, forwarded_for: Option<&str>| {
+            proxies.resolve(Some(peer.parse().unwrap()), forwarded_for)
+        };
+        let address = |address: &str| Some(address.parse::<IpAddr>().unwrap());
+
+        // Untrusted peers are the client, whatever they claim
+        assert_eq!(
+            resolve("192.0.2.1", Some("198.51.100.1")),
+            address("192.0.2.1")
+        );
+        assert_eq!(resolve("10.0.0.1", None), address("10.0.0.1"));
+
+        // Hops are read from the last, for as long as they were appended by a trusted proxy
+        assert_eq!(
+            resolve("10.0.0.1", Some("198.51.100.1")),
+            address("198.51.100.1")
+        );
+        assert_eq!(
+            resolve("10.0.0.1", Some("203.0.113.1, 198.51.100.1, 10.0.0.2")),
+            address("198.51.100.1")
+        );
+        assert_eq!(
+            resolve("2001:db8::1", Some("198.51.100.1,10.0.0.2")),
+            address("198.51.100.1")
+        );
+        // Every hop was trusted, so the first is the client
+        assert_eq!(
+            resolve("10.0.0.1", Some("10.0.0.3, 10.0.0.2")),
+            address("10.0.0.3")
+        );
+
+        // Garbled hops stop the walk at the last trusted proxy
+        assert_eq!(
+            resolve("10.0.0.1", Some("198.51.100.1, unknown, 10.0.0.2")),
+            address("10.0.0.2")
+        );
+        assert_eq!(resolve("10.0.0.1", Some("")), address("10.0.0.1"));
+
+        // IPv4-mapped addresses are converted back to IPv4, for peers and hops alike
+        assert_eq!(
+            resolve("::ffff:10.0.0.1", Some("::ffff:198.51.100.1")),
+            address("198.51.100.1")
+        );
+
+        assert_eq!(proxies.resolve(None, Some("198.51.100.1")), None);
+    }
+
+    #[test]
+    fn test_forwarded_for() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(forwarded_for(&headers), None);
+
+        // A header sent by the client, then one added by a proxy
+        headers.append("x-forwarded-for", "198.51.100.1".parse().unwrap());
+        headers.append("x-forwarded-for", "192.0.2.1, 10.0.0.2".parse().unwrap());
+        assert_eq!(
+            forwarded_for(&headers).as_deref(),
+            Some("198.51.100.1,192.0.2.1, 10.0.0.2")
+        );
+
+        let proxies = TrustedProxies {
+            networks: ["10.0.0.0/8".parse().unwrap()].into(),
+        };
+        let peer = Some("10.0.0.1".parse().unwrap());
+        assert_eq!(
+            proxies.resolve(peer, forwarded_for(&headers).as_deref()),
+            Some("192.0.2.1".parse().unwrap())
+        );
+
+        // Headers that are not ASCII are never trusted past
+        headers.append("x-forwarded-for", HeaderValue::from_bytes(b"\xff").unwrap());
+        assert_eq!(
+            proxies.resolve(peer, forwarded_for(&headers).as_deref()),
+            Some("10.0.0.1".parse().unwrap())
+        );
+    }
+}
@@ -0,0 +1,523 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Answering DNS queries for the country of an address, DNSBL-style, ex. `TXT` queries for
+//! `4.3.2.1.geo.example.internal` answered with `"BE"`, for clients that can only look things up
+//! over DNS.
+
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    process,
+    sync::Arc,
+};
+
+use tokio::{net::UdpSocket, task::JoinHandle};
+
+use crate::{arguments::Arguments, metrics::Metrics, reload::SharedDatabase};
+
+/// The largest query that will be accepted, in bytes, which is the largest UDP message without
+/// EDNS.
+const MAX_QUERY_SIZE: usize = 512;
+
+/// The length of a DNS message header.
+const HEADER_LEN: usize = 12;
+
+/// The `A` record type.
+const TYPE_A: u16 = 1;
+/// The `TXT` record type.
+const TYPE_TXT: u16 = 16;
+/// The `IN` (Internet) record class.
+const CLASS_IN: u16 = 1;
+
+/// The response codes used in replies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResponseCode {
+    NoError = 0,
+    FormatError = 1,
+    NameError = 3,
+    NotImplemented = 4,
+    Refused = 5,
+}
+
+/// Answers queries for names in a zone with the country of the address they encode.
+#[derive(Debug)]
+struct Responder {
+    /// The labels of the zone, lowercase, ex. `["geo", "example", "internal"]`.
+    zone: Box<[Box<str>]>,
+    /// How long resolvers may cache answers, in seconds.
+    ttl: u32,
+    database: SharedDatabase,
+    metrics: Arc<Metrics>,
+}
+
+/// For a given set of arguments, start answering DNS queries on the configured address, if any,
+/// exiting if it cannot be bound or no zone is configured.
+pub async fn spawn_dns_server(
+    arguments: &Arguments,
+    database: SharedDatabase,
+    metrics: Arc<Metrics>,
+) -> Option<JoinHandle<()>> {
+    let address = arguments.dns_udp?;
+
+    let Some(zone) = &arguments.dns_zone else {
+        eprintln!("Answering DNS queries requires a zone, ex. `--dns-zone geo.example.internal`");
+        process::exit(1);
+    };
+
+    let responder = Responder {
+        zone: zone
+            .trim_end_matches('.')
+            .split('.')
+            .map(|label| label.to_ascii_lowercase().into())
+            .collect(),
+        // Safety: `arguments::get_config()` implements default values
+        ttl: arguments.dns_ttl.unwrap(),
+        database,
+        metrics,
+    };
+
+    match spawn_udp_server(address, responder).await {
+        Ok(server) => Some(server),
+        Err(error) => {
+            eprintln!("Could not answer DNS queries on udp://{address}: {error}");
+            process::exit(1);
+        }
+    }
+}
+
+/// Answer DNS queries on UDP at `address` with `responder`.
+async fn spawn_udp_server(
+    address: SocketAddr,
+    responder: Responder,
+) -> std::io::Result<JoinHandle<()>> {
+    let socket = UdpSocket::bind(address).await?;
+    println!("Answering DNS queries on udp://{address}");
+
+    Ok(tokio::spawn(async move {
+        let mut buffer = vec![0; MAX_QUERY_SIZE];
+
+        loop {
+            let (len, client) = match socket.recv_from(&mut buffer).await {
+                Ok(received) => received,
+                Err(error) => {
+                    eprintln!("Failed to receive DNS query: {error}");
+                    continue;
+                }
+            };
+
+            let Some(reply) = responder.reply(&buffer[..len]) else {
+                continue;
+            };
+
+            if let Err(error) = socket.send_to(&reply, client).await {
+                eprintln!("Failed to reply to DNS query from {client}: {error}");
+            }
+        }
+    }))
+}
+
+impl Responder {
+    /// Return the reply to `query`, or `None` if it should be ignored, ex. because it is itself a
+    /// reply or too short to reply to.
+    fn reply(&self, query: &[u8]) -> Option<Vec<u8>> {
+        if query.len() < HEADER_LEN {
+            return None;
+        }
+
+        let flags = u16::from_be_bytes([query[2], query[3]]);
+        let is_response = flags & 0x8000 != 0;
+        if is_response {
+            return None;
+        }
+
+        let opcode = (flags >> 11) & 0xF;
+        if opcode != 0 {
+            return Some(reply(query, &[], ResponseCode::NotImplemented, None));
+        }
+
+        let question_count = u16::from_be_bytes([query[4], query[5]]);
+        let question = match question_count {
+            1 => parse_question(query),
+            _ => None,
+        };
+        let Some(question) = question else {
+            return Some(reply(query, &[], ResponseCode::FormatError, None));
+        };
+        let section = question.section;
+
+        let Some(labels) = question
+            .labels
+            .strip_suffix(&*self.zone)
+            .filter(|_| question.class == CLASS_IN)
+        else {
+            return Some(reply(query, section, ResponseCode::Refused, None));
+        };
+        let Some(address) = parse_address(labels) else {
+            // The zone apex and the names above addresses exist, ex. `3.2.1.geo.example.internal`
+            // for `1.2.3.4`, they just have no records
+            let code = match is_partial_address(labels) {
+                true => ResponseCode::NoError,
+                false => ResponseCode::NameError,
+            };
+            return Some(reply(query, section, code, None));
+        };
+
+        let result = self
+            .metrics
+            .time_lookup(|| self.database.current().lookup(address, &self.metrics));
+        let Ok(country) = result else {
+            self.metrics.record_miss(None);
+            return Some(reply(query, section, ResponseCode::NameError, None));
+        };
+        self.metrics.record_lookup(address, &country, None);
+
+        let code = country.code.as_bytes();
+        let data = match question.record_type {
+            TYPE_TXT => Some([&[code.len() as u8], code].concat()),
+            // Encode the letters of two-letter country codes in the last two octets, ex.
+            // `127.0.66.69` for `BE`
+            TYPE_A if code.len() == 2 => Some(vec![127, 0, code[0], code[1]]),
+            _ => None,
+        };
+        let answer = data.map(|data| (question.record_type, self.ttl, data));
+
+        Some(reply(query, section, ResponseCode::NoError, answer))
+    }
+}
+
+/// Construct the reply to `query` with `code`, repeating its `question` section (if it was
+/// parsed) and with up to one answer record (its type, TTL, and data) for the name in it.
+fn reply(
+    query: &[u8],
+    question: &[u8],
+    code: ResponseCode,
+    answer: Option<(u16, u32, Vec<u8>)>,
+) -> Vec<u8> {
+    // A response, authoritative unless refused, with the recursion desired bit of the query
+    let authoritative = match code {
+        ResponseCode::Refused => 0,
+        _ => 0x0400,
+    };
+    let flags = u16::from_be_bytes([query[2], query[3]]);
+    let flags = 0x8000 | authoritative | (flags & 0x0100) | code as u16;
+
+    let mut message = Vec::with_capacity(HEADER_LEN + question.len() + 32);
+    message.extend_from_slice(&query[..2]);
+    message.extend_from_slice(&flags.to_be_bytes());
+    message.extend_from_slice(&u16::from(!question.is_empty()).to_be_bytes());
+    message.extend_from_slice(&u16::from(answer.is_some()).to_be_bytes());
+    message.extend_from_slice(&[0, 0, 0, 0]);
+    message.extend_from_slice(question);
+
+    if let Some((record_type, ttl, data)) = answer {
+        // A pointer to the name in the question
+        message.extend_from_slice(&[0xC0, HEADER_LEN as u8]);
+        message.extend_from_slice(&record_type.to_be_bytes());
+        message.extend_from_slice(&CLASS_IN.to_be_bytes());
+        message.extend_from_slice(&ttl.to_be_bytes());
+        message.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        message.extend_from_slice(&data);
+    }
+
+    message
+}
+
+/// The question of a query.
+#[derive(Debug)]
+struct Question<'q> {
+    /// The labels of the name, lowercase, ex. `["4", "3", "2", "1", "geo", "example", "internal"]`.
+    labels: Vec<Box<str>>,
+    /// The whole question section, to repeat in the reply.
+    section: &'q [u8],
+    record_type: u16,
+    class: u16,
+}
+
+/// Parse the single question of `query`, returning `None` if it is malformed.
+fn parse_question(query: &[u8]) -> Option<Question<'_>> {
+    let (labels, name_end) = parse_name(query, HEADER_LEN)?;
+    let fields = query.get(name_end..name_end + 4)?;
+
+    Some(Question {
+        labels,
+        section: &query[HEADER_LEN..name_end + 4],
+        record_type: u16::from_be_bytes([fields[0], fields[1]]),
+        class: u16::from_be_bytes([fields[2], fields[3]]),
+    })
+}
+
+/// Parse the uncompressed name starting at `start` of `message` into its lowercase labels,
+/// returning them and the index just past the name.
+///
+/// Returns `None` if the name is malformed or compressed, which never happens in questions.
+fn parse_name(message: &[u8], start: usize) -> Option<(Vec<Box<str>>, usize)> {
+    let mut labels = Vec::new();
+    let mut position = start;
+
+    loop {
+        let len = usize::from(*message.get(position)?);
+        position += 1;
+
+        if len == 0 {
+            return Some((labels, position));
+        }
+        // Compression pointers and reserved label types
+        if len & 0xC0 != 0 {
+            return None;
+        }
+
+        let label = std::str::from_utf8(message.get(position..position + len)?).ok()?;
+        labels.push(label.to_ascii_lowercase().into());
+        position += len;
+    }
+}
+
+/// Parse the labels before the zone into an address, either the four octets of an IPv4 address
+/// in reverse (ex. `4.3.2.1` for `1.2.3.4`) or the 32 nibbles of an IPv6 address in reverse, like
+/// `ip6.arpa`.
+///
+/// Every label must be written the one way `Ipv4Addr`'s or `Ipv6Addr`'s reverse name would write
+/// it, so that each address has exactly one name, ex. not `+4.3.2.1` or `04.3.2.1`.
+fn parse_address(labels: &[Box<str>]) -> Option<IpAddr> {
+    match labels.len() {
+        4 => {
+            let mut octets = [0; 4];
+            for (octet, label) in octets.iter_mut().zip(labels.iter().rev()) {
+                *octet = parse_octet(label)?;
+            }
+
+            Some(Ipv4Addr::from(octets).into())
+        }
+        32 => {
+            let mut address = 0_u128;
+            for label in labels.iter().rev() {
+                address = address << 4 | u128::from(parse_nibble(label)?);
+            }
+
+            Some(Ipv6Addr::from(address).into())
+        }
+        _ => None,
+    }
+}
+
+/// Return whether the labels before the zone are the end of the name of an address, ex. none at
+/// all for the zone apex, `2.1` for `1.2.3.4`, or `8.b.d.0.1.0.0.2` for `2001:db8::`.
+fn is_partial_address(labels: &[Box<str>]) -> bool {
+    let is_ipv4 = labels.len() < 4 && labels.iter().all(|label| parse_octet(label).is_some());
+    let is_ipv6 = labels.len() < 32 && labels.iter().all(|label| parse_nibble(label).is_some());
+
+    is_ipv4 || is_ipv6
+}
+
+/// Parse a decimal octet without a sign or leading zeros, ex. `4` but not `+4` or `04`.
+fn parse_octet(label: &str) -> Option<u8> {
+    let is_canonical = label.bytes().all(|digit| digit.is_ascii_digit())
+        && (label == "0" || !label.starts_with('0'));
+
+    label.parse().ok().filter(|_| is_canonical)
+}
+
+/// Parse a single hexadecimal digit, ex. `f`. Labels are already lowercase.
+fn parse_nibble(label: &str) -> Option<u8> {
+    let [digit] = label.as_bytes() else {
+        return None;
+    };
+
+    char::from(*digit).to_digit(16).map(|nibble| nibble as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ip_geo::{country_list::get_countries, GeoDatabase, IpAddrEntry, IpAddrMap};
+
+    use super::*;
+    use crate::metrics::Aggregation;
+
+    /// A responder for `geo.example.internal`, where `192.0.2.0/24` is in Belgium.
+    fn responder() -> Responder {
+        let belgium = get_countries().get("BE").unwrap().clone();
+        let mut ipv4_map = IpAddrMap::new();
+        ipv4_map.insert(
+            IpAddrEntry::new(
+                Ipv4Addr::new(192, 0, 2, 0),
+                Ipv4Addr::new(192, 0, 2, 255),
+                belgium,
+            )
+            .unwrap(),
+        );
+        let database = GeoDatabase::new(ipv4_map, IpAddrMap::new());
+        let metrics = Metrics::new(Aggregation::Country, &database, None, Duration::ZERO);
+
+        Responder {
+            zone: ["geo".into(), "example".into(), "internal".into()].into(),
+            ttl: 300,
+            database: SharedDatabase::new(None, database.into(), None),
+            metrics: metrics.into(),
+        }
+    }
+
+    /// Build a query with the ID `0x1234` and recursion desired for `name` and `record_type`.
+    fn query(name: &str, record_type: u16) -> Vec<u8> {
+        let mut message = vec![0x12, 0x34, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0];
+        for label in name.split('.').filter(|label| !label.is_empty()) {
+            message.push(label.len() as u8);
+            message.extend_from_slice(label.as_bytes());
+        }
+        message.push(0);
+        message.extend_from_slice(&record_type.to_be_bytes());
+        message.extend_from_slice(&CLASS_IN.to_be_bytes());
+
+        message
+    }
+
+    /// Return the response code and the data of the single answer (if any) of `reply`.
+    fn answer(reply: &[u8]) -> (u8, Option<&[u8]>) {
+        let code = reply[3] & 0xF;
+        let answers = u16::from_be_bytes([reply[6], reply[7]]);
+        let (_, question_end) = parse_name(reply, HEADER_LEN).unwrap();
+
+        let data = (answers == 1).then(|| {
+            // The name pointer, type, class, TTL, and data length
+            let data_start = question_end + 4 + 12;
+            &reply[data_start..]
+        });
+
+        (code, data)
+    }
+
+    #[test]
+    fn test_reply() {
+        let responder = responder();
+
+        let reply = responder
+            .reply(&query("1.2.0.192.geo.example.internal", TYPE_TXT))
+            .unwrap();
+        // The same ID, a response, authoritative, and with recursion desired repeated
+        assert_eq!(reply[..4], [0x12, 0x34, 0x85, 0x00]);
+        // One question and one answer
+        assert_eq!(reply[4..12], [0, 1, 0, 1, 0, 0, 0, 0]);
+        assert_eq!(answer(&reply), (0, Some(&b"\x02BE"[..])));
+        // The answer points to the question, and can be cached for the TTL
+        let answer_start = reply.len() - 3 - 12;
+        assert_eq!(reply[answer_start..answer_start + 2], [0xC0, 12]);
+        assert_eq!(
+            reply[answer_start + 6..answer_start + 10],
+            300_u32.to_be_bytes()
+        );
+
+        // Names are case-insensitive
+        let reply = responder
+            .reply(&query("1.2.0.192.GEO.Example.internal", TYPE_A))
+            .unwrap();
+        assert_eq!(answer(&reply), (0, Some(&[127, 0, b'B', b'E'][..])));
+
+        // Other record types exist, but have no data
+        let reply = responder
+            .reply(&query("1.2.0.192.geo.example.internal", 28))
+            .unwrap();
+        assert_eq!(answer(&reply), (0, None));
+
+        // Addresses without a country
+        let reply = responder
+            .reply(&query("1.0.0.10.geo.example.internal", TYPE_TXT))
+            .unwrap();
+        assert_eq!(answer(&reply), (ResponseCode::NameError as u8, None));
+        let ipv6 = "1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2";
+        let reply = responder
+            .reply(&query(&format!("{ipv6}.geo.example.internal"), TYPE_TXT))
+            .unwrap();
+        assert_eq!(answer(&reply), (ResponseCode::NameError as u8, None));
+    }
+
+    #[test]
+    fn test_reply_names() {
+        let responder = responder();
+        let code = |name: &str| {
+            let reply = responder.reply(&query(name, TYPE_TXT)).unwrap();
+            let (code, data) = answer(&reply);
+            (code, data.map(<[u8]>::to_vec))
+        };
+
+        // The zone apex and the names above addresses have no records, but they exist
+        for name in [
+            "geo.example.internal",
+            "192.geo.example.internal",
+            "2.0.192.geo.example.internal",
+            "8.b.d.0.1.0.0.2.geo.example.internal",
+        ] {
+            assert_eq!(code(name), (0, None), "{name}");
+        }
+
+        // Octets written any way but the one way, and other names that can never be addresses
+        for name in [
+            "1.2.0.+192.geo.example.internal",
+            "1.2.00.192.geo.example.internal",
+            "01.2.0.192.geo.example.internal",
+            "1.2.0.256.geo.example.internal",
+            "1.2.0.192.0.geo.example.internal",
+            "00.geo.example.internal",
+            "www.geo.example.internal",
+        ] {
+            assert_eq!(code(name), (ResponseCode::NameError as u8, None), "{name}");
+        }
+
+        // Names outside of the zone
+        for name in ["1.2.0.192.example.internal", "example.internal", ""] {
+            assert_eq!(code(name), (ResponseCode::Refused as u8, None), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_reply_malformed() {
+        let responder = responder();
+        let query = query("1.2.0.192.geo.example.internal", TYPE_TXT);
+
+        // Too short to reply to, or a response
+        assert_eq!(responder.reply(&query[..HEADER_LEN - 1]), None);
+        let mut response = query.clone();
+        response[2] |= 0x80;
+        assert_eq!(responder.reply(&response), None);
+
+        // Questions cut short, or compressed
+        for len in [HEADER_LEN, HEADER_LEN + 5, query.len() - 1] {
+            let reply = responder.reply(&query[..len]).unwrap();
+            assert_eq!(reply[3] & 0xF, ResponseCode::FormatError as u8);
+            assert_eq!(reply[4..6], [0, 0]);
+        }
+        let mut compressed = query[..HEADER_LEN].to_vec();
+        compressed.extend_from_slice(&[0xC0, 12, 0, 16, 0, 1]);
+        let reply = responder.reply(&compressed).unwrap();
+        assert_eq!(reply[3] & 0xF, ResponseCode::FormatError as u8);
+
+        // Two questions
+        let mut two = query.clone();
+        two[5] = 2;
+        assert_eq!(
+            responder.reply(&two).unwrap()[3] & 0xF,
+            ResponseCode::FormatError as u8
+        );
+
+        // Anything but a standard query
+        let mut status = query.clone();
+        status[2] |= 2 << 3;
+        let reply = responder.reply(&status).unwrap();
+        assert_eq!(reply[3] & 0xF, ResponseCode::NotImplemented as u8);
+        assert_eq!(reply[4..6], [0, 0]);
+    }
+}
@@ -15,6 +15,8 @@
 // You should have received a copy of the GNU Affero General Public License along with ip_geo. If
 // not, see <https://www.gnu.org/licenses/>.
 
+use std::path::Path;
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("failed to deserialize TOML data")]
@@ -22,4 +24,19 @@ pub enum Error {
 
     #[error("failed to perform I/O action")]
     Io(#[from] std::io::Error),
+
+    #[error("could not load {}: {source}", path.display())]
+    LoadDatabase {
+        path: Box<Path>,
+        source: ip_geo::Error,
+    },
+
+    #[error("reload task failed: {0}")]
+    ReloadTask(#[from] tokio::task::JoinError),
+
+    #[error("refusing to serve {}: {source}", path.display())]
+    VerifyDatabase {
+        path: Box<Path>,
+        source: ip_geo::Error,
+    },
 }
@@ -0,0 +1,159 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Streaming the addresses geolocated by ingestion sources as they are processed, over
+//! server-sent events or WebSockets, ex. for live "attack map" dashboards.
+
+use std::{
+    convert::Infallible,
+    net::IpAddr,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use futures_util::{stream, SinkExt, Stream, StreamExt};
+use ip_geo::{country_list::Country, privacy::Truncation};
+use serde::Serialize;
+use tokio::sync::broadcast::{self, error::RecvError};
+use warp::{
+    sse,
+    ws::{Message, WebSocket},
+};
+
+use crate::{arguments::Arguments, shutdown::Shutdown};
+
+/// How many events to buffer for each subscriber before it misses some.
+const CAPACITY: usize = 1024;
+
+/// An address geolocated by an ingestion source.
+///
+/// Serializes as:
+///
+/// ```json
+/// {"timestamp":1700000000000,"ip":"192.0.2.0","country_code":"BE","coordinates":[4.66,50.64],"source":"log"}
+/// ```
+#[derive(Serialize)]
+struct Event<'c> {
+    /// Milliseconds since the Unix epoch.
+    timestamp: u128,
+    /// The address, truncated to a network prefix.
+    ip: IpAddr,
+    country_code: &'c str,
+    coordinates: (f64, f64),
+    /// The ingestion source the address was found by, ex. `log` or `flow`.
+    source: &'static str,
+}
+
+/// Broadcasts events to every subscriber.
+#[derive(Debug)]
+pub struct Events {
+    sender: broadcast::Sender<Arc<str>>,
+    truncation: Truncation,
+    shutdown: Shutdown,
+}
+
+impl Events {
+    /// Broadcast that `address` was geolocated to `country` by `source`, if anyone is subscribed.
+    pub fn publish(&self, address: IpAddr, country: &Country, source: &'static str) {
+        if self.sender.receiver_count() == 0 {
+            return;
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let event = Event {
+            timestamp,
+            ip: self.truncation.apply(address),
+            country_code: &country.code,
+            coordinates: country.coordinates,
+            source,
+        };
+
+        if let Ok(event) = serde_json::to_string(&event) {
+            let _ = self.sender.send(event.into());
+        }
+    }
+
+    /// Return a stream of every event from now on, as JSON, until shutdown.
+    ///
+    /// Events missed by a subscriber that falls too far behind are skipped.
+    pub fn subscribe(&self) -> impl Stream<Item = Arc<str>> + Send + 'static {
+        let events = stream::unfold(self.sender.subscribe(), |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, receiver)),
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        });
+
+        events.take_until(self.shutdown.clone().signalled())
+    }
+}
+
+/// For a given set of arguments, return the broadcaster of events, which stop streaming on
+/// `shutdown`.
+pub fn get_events(arguments: &Arguments, shutdown: Shutdown) -> Arc<Events> {
+    // Safety: `arguments::get_config()` implements default values
+    let truncation = Truncation::new(
+        arguments.truncate_ipv4_prefix.unwrap(),
+        arguments.truncate_ipv6_prefix.unwrap(),
+    );
+
+    Arc::new(Events {
+        sender: broadcast::channel(CAPACITY).0,
+        truncation,
+        shutdown,
+    })
+}
+
+/// Return a server-sent events reply streaming every event.
+pub fn sse_reply(events: &Events) -> impl warp::Reply {
+    let events = events
+        .subscribe()
+        .map(|event| Ok::<_, Infallible>(sse::Event::default().data(&*event)));
+
+    sse::reply(sse::keep_alive().stream(events))
+}
+
+/// Send every event to `socket` as a text message, until either side closes it.
+pub async fn serve_websocket(socket: WebSocket, events: &Events) {
+    let (mut sender, mut receiver) = socket.split();
+    let mut events = Box::pin(events.subscribe());
+
+    loop {
+        tokio::select! {
+            event = events.next() => {
+                let Some(event) = event else {
+                    let _ = sender.send(Message::close()).await;
+                    break;
+                };
+                if sender.send(Message::text(&*event)).await.is_err() {
+                    break;
+                }
+            }
+            message = receiver.next() => match message {
+                // Messages from the client are ignored, other than to close the socket
+                Some(Ok(message)) if !message.is_close() => (),
+                _ => break,
+            },
+        }
+    }
+}
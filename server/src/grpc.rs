@@ -0,0 +1,406 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Serving lookups over gRPC, as the `ip_geo.v0.Geo` service described by
+//! `server/proto/ip_geo.proto`, for clients that would rather use generated stubs than JSON.
+
+use std::{
+    io,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+use ip_geo::country_list::Country;
+use tokio::{net::TcpListener, task::JoinHandle};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{transport::Server, Request, Response, Status};
+
+use crate::{
+    acl::AccessControl,
+    auth::{Credentials, Endpoint},
+    limit::{limit_connections, ConnectionLimit},
+    metrics::Metrics,
+    reload::{CachedDatabase, Databases},
+};
+
+use proto::{
+    geo_server::{Geo, GeoServer},
+    lookup_response, BulkLookupRequest, BulkLookupResponse, LookupRequest, LookupResponse,
+    StatsRequest, StatsResponse,
+};
+
+/// The messages and service trait generated from `server/proto/ip_geo.proto`.
+pub mod proto {
+    tonic::include_proto!("ip_geo.v0");
+}
+
+/// The largest request message that will be accepted, in bytes.
+const MAX_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
+
+/// Answers calls to the `ip_geo.v0.Geo` service.
+#[derive(Clone, Debug)]
+pub struct Service {
+    databases: Databases,
+    metrics: Arc<Metrics>,
+    /// The clients to allow, if restricted, as for the equivalent HTTP endpoints.
+    access: Option<Arc<AccessControl>>,
+    /// The credentials to require, if any, as for the equivalent HTTP endpoints.
+    credentials: Option<Arc<Credentials>>,
+    /// The most addresses that a single `BulkLookup` may look up.
+    batch_limit: usize,
+}
+
+impl Service {
+    /// Create a new `Service`, looking up addresses in `databases` and recording them in
+    /// `metrics`.
+    pub fn new(
+        databases: Databases,
+        metrics: Arc<Metrics>,
+        access: Option<Arc<AccessControl>>,
+        credentials: Option<Arc<Credentials>>,
+        batch_limit: usize,
+    ) -> Self {
+        Self {
+            databases,
+            metrics,
+            access,
+            credentials,
+            batch_limit,
+        }
+    }
+
+    /// Return why `request` cannot call methods protected like `endpoint`, if its client is not
+    /// allowed or its `authorization` metadata does not hold valid credentials.
+    ///
+    /// `Lookup` and `BulkLookup` are protected like `/v0/`, and `Stats` like `/metrics`.
+    fn reject<T>(&self, request: &Request<T>, endpoint: Endpoint) -> Option<Status> {
+        let client = request.remote_addr().map(|address| address.ip());
+        if let (Some(access), Some(client)) = (&self.access, client) {
+            if !access.allows(endpoint, client) {
+                return Some(Status::permission_denied("client address is not allowed"));
+            }
+        }
+
+        if let Some(credentials) = &self.credentials {
+            let authorization = request
+                .metadata()
+                .get("authorization")
+                .and_then(|value| value.to_str().ok());
+            if credentials.protects(endpoint) && !credentials.verify(authorization) {
+                return Some(Status::unauthenticated("missing or invalid credentials"));
+            }
+        }
+
+        None
+    }
+
+    /// Return the database named `name`, or the default database if `name` is empty, failing if
+    /// there is no such database.
+    fn select_database(&self, name: &str) -> Result<CachedDatabase, Box<Status>> {
+        let name = (!name.is_empty()).then_some(name);
+
+        self.databases
+            .select(name)
+            .ok_or_else(|| Status::invalid_argument("unknown database").into())
+    }
+
+    /// Look up `address` in `database`, recording the lookup in `self.metrics`.
+    fn lookup_address(
+        &self,
+        address: IpAddr,
+        database: &CachedDatabase,
+    ) -> Result<Country, ip_geo::Error> {
+        let metrics = &self.metrics;
+        metrics.record_asn(address);
+
+        let result = metrics.time_lookup(|| database.lookup(address, metrics));
+        match &result {
+            Ok(country) => metrics.record_lookup(address, country, database.name()),
+            Err(ip_geo::Error::NoValueFound) => metrics.record_miss(database.name()),
+            Err(_) => (),
+        }
+
+        result
+    }
+}
+
+#[tonic::async_trait]
+impl Geo for Service {
+    /// Look up the country of a single address.
+    async fn lookup(
+        &self,
+        request: Request<LookupRequest>,
+    ) -> Result<Response<LookupResponse>, Status> {
+        if let Some(status) = self.reject(&request, Endpoint::Lookup) {
+            return Err(status);
+        }
+        let LookupRequest { ip, database } = request.into_inner();
+        let database = self.select_database(&database).map_err(|status| *status)?;
+
+        let Ok(address) = ip.parse::<IpAddr>() else {
+            return Err(Status::invalid_argument("invalid IP address"));
+        };
+
+        match self.lookup_address(address, &database) {
+            Ok(country) => Ok(Response::new(LookupResponse {
+                ip,
+                result: Some(lookup_response::Result::Country(country.into())),
+            })),
+            Err(ip_geo::Error::NoValueFound) => {
+                Err(Status::not_found("no country associated with IP address"))
+            }
+            Err(error) => {
+                eprintln!("gRPC INTERNAL: request resulted in error: '{error}'");
+                Err(Status::internal(error.to_string()))
+            }
+        }
+    }
+
+    /// Look up the countries of several addresses, in the same order.
+    async fn bulk_lookup(
+        &self,
+        request: Request<BulkLookupRequest>,
+    ) -> Result<Response<BulkLookupResponse>, Status> {
+        if let Some(status) = self.reject(&request, Endpoint::Lookup) {
+            return Err(status);
+        }
+        let BulkLookupRequest { ips, database } = request.into_inner();
+        let database = self.select_database(&database).map_err(|status| *status)?;
+
+        if ips.len() > self.batch_limit {
+            let message = format!("too many addresses, the limit is {}", self.batch_limit);
+            return Err(Status::resource_exhausted(message));
+        }
+
+        let results = ips
+            .into_iter()
+            .map(|ip| {
+                let result = match ip.parse::<IpAddr>() {
+                    Ok(address) => match self.lookup_address(address, &database) {
+                        Ok(country) => lookup_response::Result::Country(country.into()),
+                        Err(ip_geo::Error::NoValueFound) => lookup_response::Result::Error(
+                            "no country associated with IP address".into(),
+                        ),
+                        Err(error) => lookup_response::Result::Error(error.to_string()),
+                    },
+                    Err(_) => lookup_response::Result::Error("invalid IP address".into()),
+                };
+
+                LookupResponse {
+                    ip,
+                    result: Some(result),
+                }
+            })
+            .collect();
+
+        Ok(Response::new(BulkLookupResponse { results }))
+    }
+
+    /// Describe the default database and the lookups served so far.
+    async fn stats(
+        &self,
+        request: Request<StatsRequest>,
+    ) -> Result<Response<StatsResponse>, Status> {
+        if let Some(status) = self.reject(&request, Endpoint::Metrics) {
+            return Err(status);
+        }
+
+        let database = self.databases.default_database().current();
+        let (lookups, misses) = self.metrics.lookup_totals();
+
+        Ok(Response::new(StatsResponse {
+            ipv4_entries: database.database().ipv4().len() as u64,
+            ipv6_entries: database.database().ipv6().len() as u64,
+            database_timestamp: self.metrics.database_timestamp().unwrap_or_default(),
+            lookups,
+            misses,
+        }))
+    }
+}
+
+impl From<Country> for proto::Country {
+    fn from(country: Country) -> Self {
+        Self {
+            code: country.code.to_string(),
+            name: country.name.to_string(),
+            longitude: country.coordinates.0,
+            latitude: country.coordinates.1,
+            time_zone: country.time_zone.as_deref().unwrap_or_default().to_string(),
+        }
+    }
+}
+
+/// Serve `service` over HTTP/2 without TLS at `address`, capping its connections with
+/// `connections` (if set).
+pub async fn spawn_server(
+    address: SocketAddr,
+    service: Service,
+    connections: Option<Arc<ConnectionLimit>>,
+) -> io::Result<JoinHandle<()>> {
+    let listener = TcpListener::bind(address).await?;
+    println!("Serving gRPC on http://{address}");
+
+    let service = GeoServer::new(service).max_decoding_message_size(MAX_MESSAGE_SIZE);
+    let server = Server::builder()
+        .add_service(service)
+        .serve_with_incoming(limit_connections(
+            TcpListenerStream::new(listener),
+            connections,
+        ));
+
+    Ok(tokio::spawn(async move {
+        if let Err(error) = server.await {
+            eprintln!("Error serving gRPC on http://{address}: {error}");
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::Ipv4Addr, time::Duration};
+
+    use ip_geo::{country_list::get_countries, GeoDatabase, IpAddrEntry, IpAddrMap};
+    use tonic::Code;
+
+    use super::*;
+    use crate::{metrics::Aggregation, reload::SharedDatabase};
+
+    /// A service with `192.0.2.0/24` in Belgium, and a database named `other` with it in France.
+    fn service() -> Service {
+        let database = |code: &str| {
+            let country = get_countries().get(code).unwrap().clone();
+            let mut ipv4_map = IpAddrMap::new();
+            ipv4_map.insert(
+                IpAddrEntry::new(
+                    Ipv4Addr::new(192, 0, 2, 0),
+                    Ipv4Addr::new(192, 0, 2, 255),
+                    country,
+                )
+                .unwrap(),
+            );
+            Arc::new(GeoDatabase::new(ipv4_map, IpAddrMap::new()))
+        };
+
+        let default = database("BE");
+        let metrics = Metrics::new(Aggregation::Country, &default, None, Duration::ZERO);
+        let databases = Databases::new(
+            SharedDatabase::new(None, default, None),
+            vec![("other".into(), database("FR"))],
+            None,
+        );
+
+        Service::new(databases, metrics.into(), None, None, 2)
+    }
+
+    /// Look up `ip` in `database` with `service`.
+    async fn lookup(service: &Service, ip: &str, database: &str) -> Result<LookupResponse, Status> {
+        let request = Request::new(LookupRequest {
+            ip: ip.into(),
+            database: database.into(),
+        });
+
+        service.lookup(request).await.map(Response::into_inner)
+    }
+
+    /// Return the country code of `response`, or its error.
+    fn code(response: &LookupResponse) -> Result<&str, &str> {
+        match response.result.as_ref().unwrap() {
+            lookup_response::Result::Country(country) => Ok(&country.code),
+            lookup_response::Result::Error(error) => Err(error),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lookup() {
+        let service = service();
+
+        let response = lookup(&service, "192.0.2.1", "").await.unwrap();
+        assert_eq!(response.ip, "192.0.2.1");
+        let lookup_response::Result::Country(country) = response.result.unwrap() else {
+            panic!("expected a country");
+        };
+        assert_eq!(country.code, "BE");
+        assert_eq!(country.name, "Belgium");
+        assert_eq!(country.time_zone, "Europe/Brussels");
+
+        let response = lookup(&service, "192.0.2.1", "other").await.unwrap();
+        assert_eq!(code(&response), Ok("FR"));
+
+        for (ip, database, expected) in [
+            ("198.51.100.1", "", Code::NotFound),
+            ("192.0.2", "", Code::InvalidArgument),
+            ("192.0.2.1", "missing", Code::InvalidArgument),
+        ] {
+            let status = lookup(&service, ip, database).await.unwrap_err();
+            assert_eq!(status.code(), expected, "{ip} in {database:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bulk_lookup() {
+        let service = service();
+        let bulk_lookup = |ips: &[&str]| {
+            let request = Request::new(BulkLookupRequest {
+                ips: ips.iter().map(|&ip| ip.into()).collect(),
+                database: String::new(),
+            });
+            service.bulk_lookup(request)
+        };
+
+        // Failures are reported in place, in the same order
+        let response = bulk_lookup(&["198.51.100.1", "192.0.2.1"])
+            .await
+            .unwrap()
+            .into_inner();
+        let results = response.results.iter().map(code).collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            [Err("no country associated with IP address"), Ok("BE")]
+        );
+        assert_eq!(response.results[1].ip, "192.0.2.1");
+
+        let response = bulk_lookup(&["invalid"]).await.unwrap().into_inner();
+        assert_eq!(code(&response.results[0]), Err("invalid IP address"));
+
+        let status = bulk_lookup(&["192.0.2.1"; 3]).await.unwrap_err();
+        assert_eq!(status.code(), Code::ResourceExhausted);
+    }
+
+    #[tokio::test]
+    async fn test_stats() {
+        let service = service();
+        lookup(&service, "192.0.2.1", "").await.unwrap();
+        lookup(&service, "192.0.2.2", "other").await.unwrap();
+        lookup(&service, "198.51.100.1", "").await.unwrap_err();
+
+        let stats = service
+            .stats(Request::new(StatsRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(
+            stats,
+            StatsResponse {
+                ipv4_entries: 1,
+                ipv6_entries: 0,
+                database_timestamp: 0,
+                lookups: 2,
+                misses: 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Liveness and readiness checks, ex. for Kubernetes probes.
+
+use std::{
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use ip_geo::GeoDatabase;
+
+/// Decides whether the server is ready to serve lookups.
+#[derive(Clone, Debug)]
+pub struct Readiness {
+    /// What is known about the currently loaded database.
+    state: Arc<RwLock<DatabaseState>>,
+    /// The oldest that the database can be while still being ready, if any age is acceptable.
+    max_age: Option<Duration>,
+}
+
+/// What is known about a loaded database.
+#[derive(Clone, Copy, Debug)]
+struct DatabaseState {
+    /// Whether both the IPv4 and IPv6 databases have entries.
+    loaded: bool,
+    /// The time that the database was generated or last modified, in seconds since the Unix epoch.
+    database_timestamp: Option<u64>,
+}
+
+impl DatabaseState {
+    fn new(database: &GeoDatabase, database_timestamp: Option<u64>) -> Self {
+        Self {
+            loaded: !database.ipv4().is_empty() && !database.ipv6().is_empty(),
+            database_timestamp,
+        }
+    }
+}
+
+impl Readiness {
+    /// Create a new `Readiness` for `database`, which was generated or last modified at
+    /// `database_timestamp`.
+    pub fn new(
+        database: &GeoDatabase,
+        database_timestamp: Option<u64>,
+        max_age: Option<Duration>,
+    ) -> Self {
+        Self {
+            state: Arc::new(RwLock::new(DatabaseState::new(
+                database,
+                database_timestamp,
+            ))),
+            max_age,
+        }
+    }
+
+    /// Check readiness against `database` from now on, which was generated or last modified at
+    /// `database_timestamp`.
+    pub fn update(&self, database: &GeoDatabase, database_timestamp: Option<u64>) {
+        *self.state.write().expect("an unpoisoned lock") =
+            DatabaseState::new(database, database_timestamp);
+    }
+
+    /// Return `Ok` if the server is ready, or the reason that it is not.
+    pub fn check(&self) -> Result<(), Box<str>> {
+        let state = *self.state.read().expect("an unpoisoned lock");
+
+        if !state.loaded {
+            return Err("the IPv4 or IPv6 database has no entries".into());
+        }
+
+        let Some(max_age) = self.max_age else {
+            return Ok(());
+        };
+        let Some(timestamp) = state.database_timestamp else {
+            return Err("the age of the database is unknown".into());
+        };
+
+        let age = SystemTime::now()
+            .duration_since(UNIX_EPOCH + Duration::from_secs(timestamp))
+            .unwrap_or_default();
+
+        if age > max_age {
+            return Err(format!(
+                "the database is {} hours old, older than the maximum of {} hours",
+                age.as_secs() / 3600,
+                max_age.as_secs() / 3600
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,413 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Sources of addresses to geolocate and count, such as log files.
+
+use std::{
+    net::{IpAddr, SocketAddr},
+    process,
+    sync::Arc,
+};
+
+use ip_geo::country_list::Country;
+
+use crate::{
+    arguments::Arguments,
+    events::Events,
+    metrics::{Labels, Metrics},
+    reload::SharedDatabase,
+    sink::statsd::StatsdClient,
+};
+
+#[cfg(feature = "pcap")]
+pub mod capture;
+
+pub mod flow;
+use flow::FlowRecord;
+
+pub mod format;
+use format::{LogEntry, LogFormat};
+
+#[cfg(feature = "journald")]
+pub mod journald;
+
+pub mod pattern;
+use pattern::CompiledPattern;
+
+pub mod syslog;
+
+pub mod tail;
+
+/// Geolocates addresses found by ingestion sources and records them in `Metrics`, and optionally
+/// sends them to a StatsD server and streams them to subscribers of `/events`.
+#[derive(Clone, Debug)]
+pub struct Pipeline {
+    database: SharedDatabase,
+    metrics: Arc<Metrics>,
+    log_parser: LogParser,
+    statsd: Option<Arc<StatsdClient>>,
+    events: Option<Arc<Events>>,
+}
+
+/// Represents how log lines are parsed, with either a built-in or user-defined format.
+#[derive(Clone, Debug)]
+pub enum LogParser {
+    Builtin(LogFormat),
+    Custom(CompiledPattern),
+}
+
+impl LogParser {
+    /// Parse a log line, returning `None` if it has no client address.
+    pub fn parse(&self, line: &str) -> Option<LogEntry> {
+        match self {
+            Self::Builtin(format) => format.parse(line),
+            Self::Custom(pattern) => pattern.parse(line),
+        }
+    }
+}
+
+impl Pipeline {
+    /// Create a new `Pipeline` that looks addresses up in `database` and records them in
+    /// `metrics`, parsing log lines with `log_parser`.
+    pub fn new(database: SharedDatabase, metrics: Arc<Metrics>, log_parser: LogParser) -> Self {
+        Self {
+            database,
+            metrics,
+            log_parser,
+            statsd: None,
+            events: None,
+        }
+    }
+
+    /// Also send every count to `statsd`.
+    ///
+    /// Disabled with `None`.
+    pub fn with_statsd(mut self, statsd: Option<Arc<StatsdClient>>) -> Self {
+        self.statsd = statsd;
+        self
+    }
+
+    /// Also publish every geolocated address to `events`.
+    pub fn with_events(mut self, events: Arc<Events>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Publish that `address` was geolocated to `country` by `source`, if events are enabled.
+    fn publish(&self, address: IpAddr, country: &Country, source: &'static str) {
+        if let Some(events) = &self.events {
+            events.publish(address, country, source);
+        }
+    }
+
+    /// Count `bytes` sent from (`inbound`) or to (`outbound`) the location in `labels`.
+    fn count_bytes(&self, mut labels: Labels, direction: &'static str, bytes: u64) {
+        labels.push(("direction", direction.into()));
+        self.count_statsd("bytes", bytes, &labels);
+        self.metrics.bytes.inc_by(labels, bytes);
+    }
+
+    /// Increment the StatsD counter `name` by `value`, if a StatsD server is configured.
+    fn count_statsd(&self, name: &str, value: u64, labels: &Labels) {
+        if let Some(statsd) = &self.statsd {
+            statsd.count(name, value, labels);
+        }
+    }
+
+    /// Parse a log line, geolocate its client address, and count the line, along with its
+    /// response status and size if the format has them.
+    ///
+    /// Lines without an address, or with an address with no associated country, are counted as
+    /// dropped.
+    pub fn record_log_line(&self, line: &str) {
+        let Some(entry) = self.log_parser.parse(line) else {
+            self.metrics.log_lines_dropped.inc(Vec::new());
+            self.count_statsd("log_lines_dropped", 1, &Vec::new());
+            return;
+        };
+
+        let database = self.database.current();
+        let Ok(country) = database.lookup(entry.address, &self.metrics) else {
+            self.metrics.log_lines_dropped.inc(Vec::new());
+            self.count_statsd("log_lines_dropped", 1, &Vec::new());
+            return;
+        };
+
+        self.publish(entry.address, &country, "log");
+        let labels = self.metrics.labels(&country);
+
+        if let Some(status) = entry.status {
+            let mut labels = labels.clone();
+            labels.push(("status", format!("{}xx", status / 100).into()));
+            self.count_statsd("log_responses", 1, &labels);
+            self.metrics.log_responses.inc(labels);
+        }
+
+        if let Some(bytes) = entry.bytes {
+            self.count_statsd("log_response_bytes", bytes, &labels);
+            self.metrics
+                .log_response_bytes
+                .inc_by(labels.clone(), bytes);
+            self.count_bytes(labels.clone(), "outbound", bytes);
+        }
+
+        self.count_statsd("log_lines", 1, &labels);
+        self.metrics.log_lines.inc(labels);
+    }
+
+    /// Geolocate the first address in the message of a syslog message, and count the message.
+    ///
+    /// Messages without an address, or with an address with no associated country, are counted
+    /// as dropped.
+    pub fn record_syslog_message(&self, message: &str) {
+        let database = self.database.current();
+        let found = extract_address(syslog::message_body(message)).and_then(|address| {
+            let country = database.lookup(address, &self.metrics).ok()?;
+            Some((address, country))
+        });
+
+        match found {
+            Some((address, country)) => {
+                self.publish(address, &country, "syslog");
+                let labels = self.metrics.labels(&country);
+                self.count_statsd("syslog_messages", 1, &labels);
+                self.metrics.syslog_messages.inc(labels);
+            }
+            None => {
+                self.metrics.syslog_messages_dropped.inc(Vec::new());
+                self.count_statsd("syslog_messages_dropped", 1, &Vec::new());
+            }
+        }
+    }
+
+    /// Geolocate both addresses of a flow, counting its bytes and packets for each address with a
+    /// location.
+    pub fn record_flow(&self, record: &FlowRecord) {
+        // Bytes from the source are sent from its location, and bytes to the destination are sent
+        // to its location
+        let addresses = [
+            (record.source, "source", "inbound"),
+            (record.destination, "destination", "outbound"),
+        ];
+
+        let database = self.database.current();
+        for (address, direction, bytes_direction) in addresses {
+            let Ok(country) = database.lookup(address, &self.metrics) else {
+                continue;
+            };
+
+            self.publish(address, &country, "flow");
+            let mut labels = self.metrics.labels(&country);
+            self.count_bytes(labels.clone(), bytes_direction, record.bytes);
+            labels.push(("direction", direction.into()));
+
+            self.count_statsd("flow_bytes", record.bytes, &labels);
+            self.count_statsd("flow_packets", record.packets, &labels);
+            self.metrics.flow_bytes.inc_by(labels.clone(), record.bytes);
+            self.metrics.flow_packets.inc_by(labels, record.packets);
+        }
+    }
+
+    /// Count a flow export datagram that could not be decoded.
+    pub fn record_invalid_flow_datagram(&self) {
+        self.metrics.flow_datagrams_invalid.inc(Vec::new());
+        self.count_statsd("flow_datagrams_invalid", 1, &Vec::new());
+    }
+
+    /// Geolocate the remote endpoint of a captured packet, and count the packet and its bytes.
+    #[cfg(feature = "pcap")]
+    pub fn record_captured_packet(
+        &self,
+        remote: IpAddr,
+        direction: capture::Direction,
+        bytes: u64,
+    ) {
+        let database = self.database.current();
+        let Ok(country) = database.lookup(remote, &self.metrics) else {
+            return;
+        };
+
+        self.publish(remote, &country, "capture");
+        let mut labels = self.metrics.labels(&country);
+        self.count_bytes(labels.clone(), direction.as_str(), bytes);
+        labels.push(("direction", direction.as_str().into()));
+
+        self.count_statsd("capture_bytes", bytes, &labels);
+        self.count_statsd("capture_packets", 1, &labels);
+        self.metrics.capture_bytes.inc_by(labels.clone(), bytes);
+        self.metrics.capture_packets.inc(labels);
+    }
+
+    /// Geolocate the address in a journal message and count it, if it reports a failed SSH
+    /// authentication attempt.
+    #[cfg(feature = "journald")]
+    pub fn record_journal_message(&self, message: &str) {
+        if !journald::is_ssh_failure(message) {
+            return;
+        }
+
+        let database = self.database.current();
+        let found = extract_address(message).and_then(|address| {
+            let country = database.lookup(address, &self.metrics).ok()?;
+            Some((address, country))
+        });
+
+        match found {
+            Some((address, country)) => {
+                self.publish(address, &country, "journald");
+                let labels = self.metrics.labels(&country);
+                self.count_statsd("ssh_failures", 1, &labels);
+                self.metrics.ssh_failures.inc(labels);
+            }
+            None => {
+                self.metrics.ssh_failures_dropped.inc(Vec::new());
+                self.count_statsd("ssh_failures_dropped", 1, &Vec::new());
+            }
+        }
+    }
+}
+
+/// For a given set of arguments, start capturing packets on the configured interface, exiting if
+/// it cannot be opened.
+#[cfg(feature = "pcap")]
+pub fn spawn_packet_capture(arguments: &Arguments, pipeline: &Pipeline) {
+    let Some(interface) = &arguments.capture_interface else {
+        return;
+    };
+
+    // Safety: `arguments::get_config()` implements default values
+    let filter = arguments.capture_filter.as_deref().unwrap();
+
+    match capture::spawn_packet_capture(interface, filter, pipeline) {
+        Ok(_) => println!("Capturing packets on {interface} matching `{filter}`"),
+        Err(error) => {
+            eprintln!("Could not capture packets on {interface}: {error}");
+            process::exit(1);
+        }
+    }
+}
+
+/// For a given set of arguments, exit if an interface was configured to be captured on, because
+/// this build cannot capture packets.
+#[cfg(not(feature = "pcap"))]
+pub fn spawn_packet_capture(arguments: &Arguments, _pipeline: &Pipeline) {
+    if arguments.capture_interface.is_some() {
+        eprintln!("Capturing packets requires building with the `pcap` feature");
+        process::exit(1);
+    }
+}
+
+/// For a given set of arguments, start following the journal of the configured units, exiting if
+/// `journalctl` cannot be started.
+#[cfg(feature = "journald")]
+pub fn spawn_journal_reader(arguments: &Arguments, pipeline: &Pipeline) {
+    let Some(units) = &arguments.journald_units else {
+        return;
+    };
+
+    if let Err(error) = journald::spawn_journal_reader(units, pipeline) {
+        eprintln!("Could not start journalctl: {error}");
+        process::exit(1);
+    }
+}
+
+/// For a given set of arguments, exit if any units were configured to be followed, because this
+/// build cannot follow the journal.
+#[cfg(not(feature = "journald"))]
+pub fn spawn_journal_reader(arguments: &Arguments, _pipeline: &Pipeline) {
+    if arguments.journald_units.is_some() {
+        eprintln!("Following the journal requires building with the `journald` feature");
+        process::exit(1);
+    }
+}
+
+/// For a given set of arguments, start listening for syslog messages on the configured addresses,
+/// exiting if any cannot be bound.
+pub async fn spawn_syslog_listeners(arguments: &Arguments, pipeline: &Pipeline) {
+    if let Some(address) = arguments.syslog_udp {
+        if let Err(error) = syslog::spawn_udp_listener(address, pipeline.clone()).await {
+            eprintln!("Could not listen for syslog on udp://{address}: {error}");
+            process::exit(1);
+        }
+    }
+
+    if let Some(address) = arguments.syslog_tcp {
+        if let Err(error) = syslog::spawn_tcp_listener(address, pipeline.clone()).await {
+            eprintln!("Could not listen for syslog on tcp://{address}: {error}");
+            process::exit(1);
+        }
+    }
+}
+
+/// For a given set of arguments, start collecting flows on the configured addresses, exiting if
+/// any cannot be bound.
+pub async fn spawn_flow_collectors(arguments: &Arguments, pipeline: &Pipeline) {
+    for address in [arguments.netflow_udp, arguments.sflow_udp]
+        .into_iter()
+        .flatten()
+    {
+        if let Err(error) = flow::spawn_flow_collector(address, pipeline.clone()).await {
+            eprintln!("Could not listen for flows on udp://{address}: {error}");
+            process::exit(1);
+        }
+    }
+}
+
+/// For a given set of arguments, return the parser for followed log files, exiting if the selected
+/// pattern does not exist or is invalid.
+pub fn get_log_parser(arguments: &Arguments) -> LogParser {
+    let Some(name) = &arguments.log_pattern else {
+        // Safety: `arguments::get_config()` implements default values
+        return LogParser::Builtin(arguments.log_format.unwrap());
+    };
+
+    let pattern = arguments
+        .log_patterns
+        .iter()
+        .flatten()
+        .find(|p| &p.name == name);
+
+    let Some(pattern) = pattern else {
+        eprintln!("No log pattern named '{name}' in the configuration file");
+        process::exit(1);
+    };
+
+    match CompiledPattern::compile(pattern) {
+        Ok(pattern) => LogParser::Custom(pattern),
+        Err(error) => {
+            eprintln!("Invalid log pattern '{name}': {error}");
+            process::exit(1);
+        }
+    }
+}
+
+/// Return the first whitespace-separated word of a line that is an IP address.
+///
+/// Surrounding quotes and punctuation, ports (ex. `[2001:db8::1]:443` or `192.0.2.1:443`), and
+/// keys (ex. `SRC=192.0.2.1`) are ignored.
+pub fn extract_address(line: &str) -> Option<IpAddr> {
+    line.split_ascii_whitespace().find_map(|word| {
+        let word = word.rsplit('=').next().unwrap_or(word);
+        let word =
+            word.trim_matches(|c| matches!(c, '"' | '\'' | ',' | ';' | '(' | ')' | '<' | '>'));
+
+        word.parse()
+            .ok()
+            .or_else(|| word.parse::<SocketAddr>().ok().map(|a| a.ip()))
+            .or_else(|| word.trim_matches(['[', ']']).parse().ok())
+    })
+}
@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Capturing packets on a network interface with libpcap.
+
+use std::{
+    net::IpAddr,
+    thread::{self, JoinHandle},
+};
+
+use pcap::{Active, Capture, Device, Linktype};
+
+use super::{flow, Pipeline};
+
+/// How much of each packet to capture, in bytes. Only the headers are needed.
+const SNAPLEN: i32 = 128;
+
+/// How long to wait for packets before checking again, in milliseconds.
+const READ_TIMEOUT: i32 = 1000;
+
+/// Represents whether a captured packet was received or sent by this host.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Inbound,
+    Outbound,
+}
+
+impl Direction {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Inbound => "inbound",
+            Self::Outbound => "outbound",
+        }
+    }
+}
+
+/// Spawn a thread that captures packets matching the BPF `filter` on `interface`, recording the
+/// remote endpoint of each in `pipeline`.
+///
+/// Fails if the interface cannot be opened (ex. without `CAP_NET_RAW`) or the filter is invalid.
+pub fn spawn_packet_capture(
+    interface: &str,
+    filter: &str,
+    pipeline: &Pipeline,
+) -> Result<JoinHandle<()>, pcap::Error> {
+    let local_addresses: Box<[IpAddr]> = Device::list()?
+        .into_iter()
+        .filter(|device| device.name == interface)
+        .flat_map(|device| device.addresses)
+        .map(|address| address.addr)
+        .collect();
+
+    let mut capture = open_capture(interface, filter)?;
+
+    let linktype = capture.get_datalink();
+    let pipeline = pipeline.clone();
+
+    Ok(thread::Builder::new()
+        .name(format!("capture {interface}"))
+        .spawn(move || loop {
+            let packet = match capture.next_packet() {
+                Ok(packet) => packet,
+                Err(pcap::Error::TimeoutExpired) => continue,
+                Err(error) => {
+                    eprintln!("Stopped capturing packets: {error}");
+                    return;
+                }
+            };
+
+            let Some((source, destination)) = parse_packet(linktype, packet.data) else {
+                continue;
+            };
+
+            let (remote, direction) = match is_local(source, &local_addresses) {
+                true => (destination, Direction::Outbound),
+                false => (source, Direction::Inbound),
+            };
+
+            pipeline.record_captured_packet(remote, direction, u64::from(packet.header.len));
+        })
+        .expect("a new thread"))
+}
+
+/// Open a capture of packets matching the BPF `filter` on `interface`.
+///
+/// Fails if the interface cannot be opened (ex. without `CAP_NET_RAW`) or the filter is invalid.
+pub fn open_capture(interface: &str, filter: &str) -> Result<Capture<Active>, pcap::Error> {
+    let mut capture = Capture::from_device(interface)?
+        .snaplen(SNAPLEN)
+        .timeout(READ_TIMEOUT)
+        .immediate_mode(true)
+        .open()?;
+    capture.filter(filter, true)?;
+
+    Ok(capture)
+}
+
+/// Parse the source and destination addresses of a captured packet, if it is IPv4 or IPv6.
+fn parse_packet(linktype: Linktype, data: &[u8]) -> Option<(IpAddr, IpAddr)> {
+    match linktype {
+        Linktype::ETHERNET => flow::parse_ethernet_frame(data),
+        Linktype::RAW | Linktype::IPV4 | Linktype::IPV6 => flow::parse_ip_packet(data),
+        // Linux "cooked" captures, ex. on the `any` interface
+        Linktype::LINUX_SLL => flow::parse_ip_packet(data.get(16..)?),
+        Linktype::LINUX_SLL2 => flow::parse_ip_packet(data.get(20..)?),
+        // BSD loopback
+        Linktype::NULL | Linktype::LOOP => flow::parse_ip_packet(data.get(4..)?),
+        _ => None,
+    }
+}
+
+/// Return whether `address` belongs to this host or its local network, such that the other end of
+/// a packet is the remote endpoint.
+fn is_local(address: IpAddr, local_addresses: &[IpAddr]) -> bool {
+    if local_addresses.contains(&address) {
+        return true;
+    }
+
+    match address {
+        IpAddr::V4(address) => {
+            address.is_private()
+                || address.is_loopback()
+                || address.is_link_local()
+                || address.is_unspecified()
+        }
+        IpAddr::V6(address) => {
+            let first_segment = address.segments()[0];
+
+            address.is_loopback()
+                || address.is_unspecified()
+                // Unique local (fc00::/7) and link-local (fe80::/10)
+                || first_segment & 0xfe00 == 0xfc00
+                || first_segment & 0xffc0 == 0xfe80
+        }
+    }
+}
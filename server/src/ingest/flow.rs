@@ -0,0 +1,490 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Collecting flow records exported by routers and switches over UDP, ex. NetFlow, IPFIX, or sFlow.
+
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+};
+
+use tokio::{net::UdpSocket, task::JoinHandle};
+
+use super::Pipeline;
+
+pub mod ipfix;
+
+pub mod netflow;
+
+pub mod sflow;
+
+/// The largest datagram that can be received.
+const MAX_DATAGRAM_SIZE: usize = 65535;
+
+/// The most templates that will be remembered across every exporter.
+const MAX_TEMPLATES: usize = 4096;
+
+/// Template field types (information elements) that describe a flow.
+mod field {
+    pub const BYTES: u16 = 1;
+    pub const PACKETS: u16 = 2;
+    pub const IPV4_SOURCE: u16 = 8;
+    pub const IPV4_DESTINATION: u16 = 12;
+    pub const OUT_BYTES: u16 = 23;
+    pub const OUT_PACKETS: u16 = 24;
+    pub const IPV6_SOURCE: u16 = 27;
+    pub const IPV6_DESTINATION: u16 = 28;
+    pub const SAMPLING_INTERVAL: u16 = 34;
+    pub const SAMPLER_RANDOM_INTERVAL: u16 = 50;
+}
+
+/// Represents a single flow, summarizing the traffic from one address to another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlowRecord {
+    pub source: IpAddr,
+    pub destination: IpAddr,
+    pub bytes: u64,
+    pub packets: u64,
+}
+
+/// Represents the ways that decoding a flow export datagram can fail.
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum FlowError {
+    #[error("datagram ended unexpectedly")]
+    Truncated,
+
+    #[error("unsupported flow export version {0}")]
+    UnsupportedVersion(u16),
+
+    #[error("invalid {0}")]
+    Malformed(&'static str),
+}
+
+/// Listen for flow exports (in any supported format) on UDP at `address`, recording each flow in `pipeline`.
+pub async fn spawn_flow_collector(
+    address: SocketAddr,
+    pipeline: Pipeline,
+) -> std::io::Result<JoinHandle<()>> {
+    let socket = UdpSocket::bind(address).await?;
+    println!("Listening for flows on udp://{address}");
+
+    Ok(tokio::spawn(async move {
+        let mut buffer = vec![0; MAX_DATAGRAM_SIZE];
+        let mut state = ExportState::default();
+
+        loop {
+            let (len, exporter) = match socket.recv_from(&mut buffer).await {
+                Ok(received) => received,
+                Err(error) => {
+                    eprintln!("Failed to receive flow export: {error}");
+                    continue;
+                }
+            };
+
+            match decode(&buffer[..len], exporter.ip(), &mut state) {
+                Ok(records) => records
+                    .iter()
+                    .for_each(|record| pipeline.record_flow(record)),
+                Err(_) => pipeline.record_invalid_flow_datagram(),
+            }
+        }
+    }))
+}
+
+/// Decode every flow in a datagram, according to its version.
+///
+/// Templates defined by the datagram are remembered in `state`, so that later datagrams from
+/// `exporter` can be decoded.
+fn decode(
+    datagram: &[u8],
+    exporter: IpAddr,
+    state: &mut ExportState,
+) -> Result<Vec<FlowRecord>, FlowError> {
+    match Reader::new(datagram).u16()? {
+        // sFlow versions are four bytes
+        0 => sflow::decode(datagram),
+        5 => netflow::decode_v5(datagram),
+        9 => netflow::decode_v9(datagram, exporter, state),
+        10 => ipfix::decode(datagram, exporter, state),
+        version => Err(FlowError::UnsupportedVersion(version)),
+    }
+}
+
+/// Identifies a template, which is only unique to the exporter and observation domain (or source
+/// ID) that defined it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct TemplateKey {
+    exporter: IpAddr,
+    domain: u32,
+    id: u16,
+}
+
+impl TemplateKey {
+    /// Return the exporter and observation domain that defined the template.
+    const fn domain(&self) -> (IpAddr, u32) {
+        (self.exporter, self.domain)
+    }
+}
+
+/// What every exporter has announced about its exports: templates, and the sampling intervals
+/// reported by options records.
+#[derive(Debug, Default)]
+struct ExportState {
+    templates: HashMap<TemplateKey, Template>,
+    sampling_intervals: HashMap<(IpAddr, u32), u64>,
+}
+
+impl ExportState {
+    /// Define (or redefine) a template, unless too many templates are already known.
+    fn insert_template(&mut self, key: TemplateKey, template: Template) {
+        if self.templates.len() < MAX_TEMPLATES || self.templates.contains_key(&key) {
+            self.templates.insert(key, template);
+        }
+    }
+
+    /// Forget a template that the exporter has withdrawn.
+    fn remove_template(&mut self, key: &TemplateKey) {
+        self.templates.remove(key);
+    }
+
+    /// Decode every record in a data set, pushing flows onto `records` and remembering any
+    /// sampling interval reported by options records.
+    ///
+    /// Sets whose template has not been received yet are skipped, as is any trailing padding.
+    fn decode_data_set(&mut self, key: TemplateKey, set: &[u8], records: &mut Vec<FlowRecord>) {
+        let Some(template) = self.templates.get(&key) else {
+            return;
+        };
+
+        let mut reader = Reader::new(set);
+
+        while reader.bytes.len() >= template.min_length {
+            let Ok(values) = template.decode(&mut reader) else {
+                break;
+            };
+
+            match template.kind {
+                TemplateKind::Data => {
+                    let sampling_interval = self.sampling_intervals.get(&key.domain()).copied();
+                    records.extend(values.into_record(sampling_interval));
+                }
+                TemplateKind::Options => {
+                    let Some(interval) = values.sampling_interval else {
+                        continue;
+                    };
+
+                    if self.sampling_intervals.len() < MAX_TEMPLATES
+                        || self.sampling_intervals.contains_key(&key.domain())
+                    {
+                        self.sampling_intervals.insert(key.domain(), interval);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Represents whether a template describes flows or options, such as the exporter's sampling
+/// configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TemplateKind {
+    Data,
+    Options,
+}
+
+/// Describes a single field of a template.
+#[derive(Clone, Copy, Debug)]
+struct Field {
+    kind: u16,
+    /// Whether `kind` is specific to a vendor, rather than a standard field type.
+    enterprise: bool,
+    /// The length of the field, or `None` if its length is given by each record.
+    length: Option<u16>,
+}
+
+impl Field {
+    /// The field length that marks an IPFIX field as variable-length.
+    const VARIABLE_LENGTH: u16 = 65535;
+
+    /// Create a new standard `Field`.
+    const fn new(kind: u16, length: u16) -> Self {
+        Self {
+            kind,
+            enterprise: false,
+            length: Some(length),
+        }
+    }
+}
+
+/// Describes the layout of the records in a data set.
+#[derive(Clone, Debug)]
+struct Template {
+    kind: TemplateKind,
+    fields: Box<[Field]>,
+    /// The shortest possible length of a record, in bytes.
+    min_length: usize,
+}
+
+impl Template {
+    /// Create a new `Template`, failing if it describes empty records.
+    fn new(kind: TemplateKind, fields: Box<[Field]>) -> Result<Self, FlowError> {
+        // Variable-length fields are at least one byte, for their length
+        let min_length = fields
+            .iter()
+            .map(|field| field.length.map_or(1, usize::from))
+            .sum();
+
+        match min_length {
+            0 => Err(FlowError::Malformed("template")),
+            min_length => Ok(Self {
+                kind,
+                fields,
+                min_length,
+            }),
+        }
+    }
+
+    /// Decode a single record from the front of `reader`.
+    fn decode(&self, reader: &mut Reader) -> Result<Values, FlowError> {
+        let mut values = Values::default();
+
+        for field in self.fields.iter() {
+            let length = match field.length {
+                Some(length) => usize::from(length),
+                // Lengths under 255 are one byte, otherwise 255 is followed by two bytes
+                None => match reader.u8()? {
+                    255 => usize::from(reader.u16()?),
+                    length => usize::from(length),
+                },
+            };
+            let value = reader.take(length)?;
+
+            if !field.enterprise {
+                values.set(field.kind, value);
+            }
+        }
+
+        Ok(values)
+    }
+}
+
+/// The values of the fields in a record that are relevant to geolocating traffic.
+#[derive(Clone, Copy, Debug, Default)]
+struct Values {
+    source: Option<IpAddr>,
+    destination: Option<IpAddr>,
+    bytes: Option<u64>,
+    packets: Option<u64>,
+    out_bytes: Option<u64>,
+    out_packets: Option<u64>,
+    sampling_interval: Option<u64>,
+}
+
+impl Values {
+    /// Record the value of a field, if it is relevant.
+    fn set(&mut self, kind: u16, value: &[u8]) {
+        match kind {
+            field::BYTES => self.bytes = parse_uint(value),
+            field::PACKETS => self.packets = parse_uint(value),
+            field::OUT_BYTES => self.out_bytes = parse_uint(value),
+            field::OUT_PACKETS => self.out_packets = parse_uint(value),
+            field::IPV4_SOURCE | field::IPV6_SOURCE => self.source = parse_address(value),
+            field::IPV4_DESTINATION | field::IPV6_DESTINATION => {
+                self.destination = parse_address(value)
+            }
+            field::SAMPLING_INTERVAL | field::SAMPLER_RANDOM_INTERVAL => {
+                self.sampling_interval = parse_uint(value).filter(|&interval| interval > 0)
+            }
+            _ => (),
+        }
+    }
+
+    /// Return the flow described by the record, if it has both addresses.
+    ///
+    /// Counts are scaled by the record's own sampling interval, or else `sampling_interval`.
+    fn into_record(self, sampling_interval: Option<u64>) -> Option<FlowRecord> {
+        let sampling_interval = self.sampling_interval.or(sampling_interval).unwrap_or(1);
+        let scale = |count: Option<u64>| count.unwrap_or(0).saturating_mul(sampling_interval);
+
+        Some(FlowRecord {
+            source: self.source?,
+            destination: self.destination?,
+            bytes: scale(self.bytes.or(self.out_bytes)),
+            packets: scale(self.packets.or(self.out_packets)),
+        })
+    }
+}
+
+/// Parse a big-endian unsigned integer of up to eight bytes.
+fn parse_uint(value: &[u8]) -> Option<u64> {
+    (value.len() <= 8).then(|| {
+        value
+            .iter()
+            .fold(0, |total, &byte| (total << 8) | u64::from(byte))
+    })
+}
+
+/// Parse an IPv4 or IPv6 address from its four or sixteen bytes.
+fn parse_address(value: &[u8]) -> Option<IpAddr> {
+    if let Ok(octets) = <[u8; 4]>::try_from(value) {
+        return Some(octets.into());
+    }
+
+    <[u8; 16]>::try_from(value).ok().map(IpAddr::from)
+}
+
+/// Parse the source and destination addresses of an Ethernet frame, if it holds an IPv4 or IPv6
+/// packet. Only the headers are needed.
+pub fn parse_ethernet_frame(frame: &[u8]) -> Option<(IpAddr, IpAddr)> {
+    let ethertype_at = |offset: usize| {
+        let bytes = frame.get(offset..offset + 2)?;
+        Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+    };
+
+    let mut ethertype = ethertype_at(12)?;
+    let mut offset = 14;
+
+    // 802.1Q and 802.1ad VLAN tags
+    while matches!(ethertype, 0x8100 | 0x88a8) {
+        ethertype = ethertype_at(offset + 2)?;
+        offset += 4;
+    }
+
+    match ethertype {
+        0x0800 | 0x86dd => parse_ip_packet(frame.get(offset..)?),
+        _ => None,
+    }
+}
+
+/// Parse the source and destination addresses of an IPv4 or IPv6 packet. Only the header is
+/// needed.
+pub fn parse_ip_packet(packet: &[u8]) -> Option<(IpAddr, IpAddr)> {
+    let (source, destination) = match packet.first()? >> 4 {
+        4 => (packet.get(12..16)?, packet.get(16..20)?),
+        6 => (packet.get(8..24)?, packet.get(24..40)?),
+        _ => return None,
+    };
+
+    Some((parse_address(source)?, parse_address(destination)?))
+}
+
+/// Reads big-endian values from the front of a byte slice.
+#[derive(Clone, Copy, Debug)]
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    const fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    const fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Return the next `len` bytes.
+    fn take(&mut self, len: usize) -> Result<&'a [u8], FlowError> {
+        if self.bytes.len() < len {
+            return Err(FlowError::Truncated);
+        }
+
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+
+        Ok(taken)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), FlowError> {
+        self.take(len).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, FlowError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, FlowError> {
+        // Safety: `take()` returns exactly two bytes
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, FlowError> {
+        // Safety: `take()` returns exactly four bytes
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        let exporter = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let mut state = ExportState::default();
+
+        assert_eq!(decode(&[], exporter, &mut state), Err(FlowError::Truncated));
+        assert_eq!(
+            decode(&[0], exporter, &mut state),
+            Err(FlowError::Truncated)
+        );
+        assert_eq!(
+            decode(&[0, 7, 0, 0], exporter, &mut state),
+            Err(FlowError::UnsupportedVersion(7))
+        );
+        // A NetFlow v5 header without any flows
+        assert_eq!(
+            decode(
+                &[[0, 5].as_slice(), &[0; 22]].concat(),
+                exporter,
+                &mut state
+            ),
+            Ok(vec![])
+        );
+    }
+
+    #[test]
+    fn test_parse_ethernet_frame() {
+        let ipv4 = [[0x45].as_slice(), &[0; 11], &[1; 4], &[2; 4]].concat();
+        let frame = [[0; 12].as_slice(), &[0x08, 0x00], &ipv4].concat();
+        let expected = Some(([1; 4].into(), [2; 4].into()));
+
+        assert_eq!(parse_ethernet_frame(&frame), expected);
+
+        // Behind an 802.1ad tag and an 802.1Q tag
+        let tagged = [
+            [0; 12].as_slice(),
+            &[0x88, 0xa8, 0, 1, 0x81, 0x00, 0, 2, 0x08, 0x00],
+            &ipv4,
+        ]
+        .concat();
+        assert_eq!(parse_ethernet_frame(&tagged), expected);
+
+        // ARP, and a truncated IPv4 header
+        let arp = [[0; 12].as_slice(), &[0x08, 0x06], &ipv4].concat();
+        assert_eq!(parse_ethernet_frame(&arp), None);
+        assert_eq!(parse_ethernet_frame(&frame[..frame.len() - 1]), None);
+
+        let ipv6 = [[0x60].as_slice(), &[0; 7], &[1; 16], &[2; 16]].concat();
+        assert_eq!(
+            parse_ip_packet(&ipv6),
+            Some(([1; 16].into(), [2; 16].into()))
+        );
+    }
+}
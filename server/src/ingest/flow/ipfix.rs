@@ -0,0 +1,368 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Decoding IPFIX ([RFC 7011](https://www.rfc-editor.org/rfc/rfc7011)) exports.
+
+use std::net::IpAddr;
+
+use super::{
+    ExportState, Field, FlowError, FlowRecord, Reader, Template, TemplateKey, TemplateKind,
+};
+
+/// The set ID of template sets.
+const TEMPLATE_SET: u16 = 2;
+
+/// The set ID of options template sets.
+const OPTIONS_TEMPLATE_SET: u16 = 3;
+
+/// The bit of a field type that marks it as enterprise-specific, followed by an enterprise number.
+const ENTERPRISE_BIT: u16 = 0x8000;
+
+/// Decode every flow in an IPFIX message, remembering any templates it defines or withdraws.
+///
+/// Data sets whose template has not been received yet are skipped.
+pub(super) fn decode(
+    message: &[u8],
+    exporter: IpAddr,
+    state: &mut ExportState,
+) -> Result<Vec<FlowRecord>, FlowError> {
+    let mut reader = Reader::new(message);
+
+    reader.skip(2)?; // Version
+    let length = usize::from(reader.u16()?);
+    reader.skip(8)?; // Export time, sequence number
+    let domain = reader.u32()?;
+
+    // The message may be followed by unrelated bytes, ex. when received over a stream
+    let mut reader = Reader::new(
+        length
+            .checked_sub(16)
+            .and_then(|length| reader.bytes.get(..length))
+            .ok_or(FlowError::Malformed("message length"))?,
+    );
+
+    let mut records = Vec::new();
+
+    while !reader.is_empty() {
+        let id = reader.u16()?;
+        let length = usize::from(reader.u16()?)
+            .checked_sub(4)
+            .ok_or(FlowError::Malformed("set length"))?;
+        let set = reader.take(length)?;
+
+        let key = |id| TemplateKey {
+            exporter,
+            domain,
+            id,
+        };
+
+        match id {
+            TEMPLATE_SET | OPTIONS_TEMPLATE_SET => {
+                let kind = match id {
+                    TEMPLATE_SET => TemplateKind::Data,
+                    _ => TemplateKind::Options,
+                };
+
+                for (id, template) in read_templates(set, kind)? {
+                    match template {
+                        Some(template) => state.insert_template(key(id), template),
+                        None => state.remove_template(&key(id)),
+                    }
+                }
+            }
+            // Reserved
+            0..=255 => (),
+            id => state.decode_data_set(key(id), set, &mut records),
+        }
+    }
+
+    Ok(records)
+}
+
+/// Read every template in a template or options template set, ignoring any trailing padding.
+///
+/// Withdrawn templates are returned as `None`.
+fn read_templates(
+    set: &[u8],
+    kind: TemplateKind,
+) -> Result<Vec<(u16, Option<Template>)>, FlowError> {
+    let mut reader = Reader::new(set);
+    let mut templates = Vec::new();
+
+    while reader.bytes.len() >= 4 {
+        let id = reader.u16()?;
+        let field_count = reader.u16()?;
+
+        if field_count == 0 {
+            templates.push((id, None));
+            continue;
+        }
+
+        if kind == TemplateKind::Options {
+            reader.skip(2)?; // Scope field count, which are included in the field count
+        }
+
+        let fields = (0..field_count)
+            .map(|_| read_field(&mut reader))
+            .collect::<Result<_, _>>()?;
+
+        templates.push((id, Some(Template::new(kind, fields)?)));
+    }
+
+    Ok(templates)
+}
+
+/// Read a field specifier, ex. `(type, length)` or `(type | ENTERPRISE_BIT, length, enterprise)`.
+fn read_field(reader: &mut Reader) -> Result<Field, FlowError> {
+    let kind = reader.u16()?;
+    let length = reader.u16()?;
+
+    let enterprise = kind & ENTERPRISE_BIT != 0;
+    if enterprise {
+        reader.skip(4)?; // Enterprise number
+    }
+
+    Ok(Field {
+        kind: kind & !ENTERPRISE_BIT,
+        enterprise,
+        length: (length != Field::VARIABLE_LENGTH).then_some(length),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    const EXPORTER: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    /// Concatenate big-endian `u16`s.
+    fn u16s(values: &[u16]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_be_bytes())
+            .collect()
+    }
+
+    /// Build an IPFIX message in observation domain 1 from `(set ID, contents)` pairs.
+    fn message(sets: impl IntoIterator<Item = (u16, Vec<u8>)>) -> Vec<u8> {
+        let body: Vec<u8> = sets
+            .into_iter()
+            .flat_map(|(id, set)| [u16s(&[id, set.len() as u16 + 4]), set].concat())
+            .collect();
+
+        [
+            u16s(&[10, body.len() as u16 + 16]),
+            vec![0; 8],
+            vec![0, 0, 0, 1],
+            body,
+        ]
+        .concat()
+    }
+
+    /// A template set defining template 256: source, destination, bytes, and packets.
+    fn flow_template() -> (u16, Vec<u8>) {
+        (TEMPLATE_SET, u16s(&[256, 4, 8, 4, 12, 4, 1, 4, 2, 4]))
+    }
+
+    /// A record of template 256.
+    fn flow(source: [u8; 4], destination: [u8; 4], bytes: u32, packets: u32) -> Vec<u8> {
+        [
+            &source[..],
+            &destination,
+            &bytes.to_be_bytes(),
+            &packets.to_be_bytes(),
+        ]
+        .concat()
+    }
+
+    fn record(source: [u8; 4], destination: [u8; 4], bytes: u64, packets: u64) -> FlowRecord {
+        FlowRecord {
+            source: source.into(),
+            destination: destination.into(),
+            bytes,
+            packets,
+        }
+    }
+
+    #[test]
+    fn test_decode() {
+        let mut state = ExportState::default();
+        let data = (
+            256,
+            [flow([1; 4], [2; 4], 1500, 3), flow([3; 4], [4; 4], 40, 1)].concat(),
+        );
+
+        // Data sets are skipped until their template arrives
+        assert_eq!(
+            decode(&message([data.clone()]), EXPORTER, &mut state),
+            Ok(vec![])
+        );
+        assert_eq!(
+            decode(
+                &message([flow_template(), data.clone()]),
+                EXPORTER,
+                &mut state
+            ),
+            Ok(vec![
+                record([1; 4], [2; 4], 1500, 3),
+                record([3; 4], [4; 4], 40, 1)
+            ])
+        );
+        assert_eq!(
+            decode(&message([data.clone()]), EXPORTER, &mut state)
+                .unwrap()
+                .len(),
+            2
+        );
+
+        // Templates belong to the exporter that defined them
+        let other = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+        assert_eq!(
+            decode(&message([data.clone()]), other, &mut state),
+            Ok(vec![])
+        );
+
+        // Withdrawn templates are forgotten
+        let withdrawal = (TEMPLATE_SET, u16s(&[256, 0]));
+        assert_eq!(
+            decode(&message([withdrawal, data]), EXPORTER, &mut state),
+            Ok(vec![])
+        );
+    }
+
+    #[test]
+    fn test_truncated() {
+        let mut state = ExportState::default();
+        let full = message([flow_template(), (256, flow([1; 4], [2; 4], 1, 1))]);
+
+        assert_eq!(
+            decode(&full[..10], EXPORTER, &mut state),
+            Err(FlowError::Truncated)
+        );
+        assert_eq!(
+            decode(&full[..full.len() - 1], EXPORTER, &mut state),
+            Err(FlowError::Malformed("message length"))
+        );
+
+        // A template cut off partway through its fields
+        let template = (TEMPLATE_SET, u16s(&[257, 4, 8, 4, 12, 4]));
+        assert_eq!(
+            decode(&message([template]), EXPORTER, &mut state),
+            Err(FlowError::Truncated)
+        );
+
+        // A set that claims to run past the end of the message
+        let mut overlong = message([flow_template()]);
+        overlong[18..20].copy_from_slice(&100_u16.to_be_bytes());
+        assert_eq!(
+            decode(&overlong, EXPORTER, &mut state),
+            Err(FlowError::Truncated)
+        );
+
+        // A set shorter than its own header
+        let mut short = message([flow_template()]);
+        short[18..20].copy_from_slice(&2_u16.to_be_bytes());
+        assert_eq!(
+            decode(&short, EXPORTER, &mut state),
+            Err(FlowError::Malformed("set length"))
+        );
+
+        // Records cut off partway through are dropped, but earlier ones are kept
+        let data = [flow([1; 4], [2; 4], 1, 1), flow([3; 4], [4; 4], 1, 1)].concat();
+        let data = (256, data[..data.len() - 3].to_vec());
+        assert_eq!(
+            decode(&message([flow_template(), data]), EXPORTER, &mut state),
+            Ok(vec![record([1; 4], [2; 4], 1, 1)])
+        );
+    }
+
+    #[test]
+    fn test_variable_length_fields() {
+        let mut state = ExportState::default();
+
+        // Source, destination, an enterprise-specific field of type 1 (which is not bytes), a
+        // variable-length interface name, a variable-length enterprise field, and bytes
+        let template = [
+            u16s(&[258, 6, 8, 4, 12, 4, ENTERPRISE_BIT | 1, 4]),
+            vec![0, 0, 0, 9],
+            u16s(&[
+                82,
+                Field::VARIABLE_LENGTH,
+                ENTERPRISE_BIT | 2,
+                Field::VARIABLE_LENGTH,
+            ]),
+            vec![0, 0, 0, 9],
+            u16s(&[1, 8]),
+        ]
+        .concat();
+
+        let data = [
+            vec![1; 4],
+            vec![2; 4],
+            vec![0xff; 4],
+            // A short length, in one byte
+            vec![4],
+            b"eth0".to_vec(),
+            // A long length, in three bytes
+            vec![255, 1, 44],
+            vec![b'x'; 300],
+            1234_u64.to_be_bytes().to_vec(),
+        ]
+        .concat();
+
+        assert_eq!(
+            decode(
+                &message([(TEMPLATE_SET, template), (258, data)]),
+                EXPORTER,
+                &mut state
+            ),
+            Ok(vec![record([1; 4], [2; 4], 1234, 0)])
+        );
+    }
+
+    #[test]
+    fn test_options_template() {
+        let mut state = ExportState::default();
+
+        // Scoped to the observation domain ID (149), with the sampling interval (34)
+        let options_template = (OPTIONS_TEMPLATE_SET, u16s(&[259, 2, 1, 149, 4, 34, 4]));
+        let options = (259, [1_u32.to_be_bytes(), 100_u32.to_be_bytes()].concat());
+        let data = (256, flow([1; 4], [2; 4], 1500, 3));
+
+        assert_eq!(
+            decode(
+                &message([options_template, options, flow_template(), data.clone()]),
+                EXPORTER,
+                &mut state
+            ),
+            Ok(vec![record([1; 4], [2; 4], 150_000, 300)])
+        );
+
+        // The sampling interval applies to later messages, but not to other exporters
+        assert_eq!(
+            decode(&message([data.clone()]), EXPORTER, &mut state),
+            Ok(vec![record([1; 4], [2; 4], 150_000, 300)])
+        );
+        let other = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+        assert_eq!(
+            decode(&message([flow_template(), data]), other, &mut state),
+            Ok(vec![record([1; 4], [2; 4], 1500, 3)])
+        );
+    }
+}
@@ -0,0 +1,315 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Decoding Cisco NetFlow [version
+//! 5](https://www.cisco.com/c/en/us/td/docs/net_mgmt/netflow_collection_engine/3-6/user/guide/format.html)
+//! and [version 9](https://www.rfc-editor.org/rfc/rfc3954) exports.
+
+use std::net::{IpAddr, Ipv4Addr};
+
+use super::{
+    ExportState, Field, FlowError, FlowRecord, Reader, Template, TemplateKey, TemplateKind,
+};
+
+/// The length of a NetFlow v5 flow record, in bytes.
+const V5_RECORD_LENGTH: usize = 48;
+
+/// Decode every flow in a NetFlow v5 datagram.
+///
+/// Counts are scaled by the sampling interval in the header, if any.
+pub(super) fn decode_v5(datagram: &[u8]) -> Result<Vec<FlowRecord>, FlowError> {
+    let mut reader = Reader::new(datagram);
+
+    reader.skip(2)?; // Version
+    let count = reader.u16()?;
+    reader.skip(18)?; // Uptime, time, sequence number, engine type and ID
+                      // The top two bits are the sampling mode
+    let sampling_interval = u64::from(reader.u16()? & 0x3fff).max(1);
+
+    (0..count)
+        .map(|_| {
+            let mut record = Reader::new(reader.take(V5_RECORD_LENGTH)?);
+
+            let source = Ipv4Addr::from(record.u32()?);
+            let destination = Ipv4Addr::from(record.u32()?);
+            record.skip(8)?; // Next hop, input and output interfaces
+            let packets = u64::from(record.u32()?);
+            let bytes = u64::from(record.u32()?);
+
+            Ok(FlowRecord {
+                source: source.into(),
+                destination: destination.into(),
+                bytes: bytes * sampling_interval,
+                packets: packets * sampling_interval,
+            })
+        })
+        .collect()
+}
+
+/// Decode every flow in a NetFlow v9 datagram, remembering any templates it defines.
+///
+/// Flowsets whose template has not been received yet are skipped.
+pub(super) fn decode_v9(
+    datagram: &[u8],
+    exporter: IpAddr,
+    state: &mut ExportState,
+) -> Result<Vec<FlowRecord>, FlowError> {
+    let mut reader = Reader::new(datagram);
+
+    reader.skip(16)?; // Version, count, uptime, time, sequence number
+    let source_id = reader.u32()?;
+
+    let mut records = Vec::new();
+
+    while !reader.is_empty() {
+        let id = reader.u16()?;
+        let length = usize::from(reader.u16()?)
+            .checked_sub(4)
+            .ok_or(FlowError::Malformed("flowset length"))?;
+        let flowset = reader.take(length)?;
+
+        let key = |id| TemplateKey {
+            exporter,
+            domain: source_id,
+            id,
+        };
+
+        match id {
+            0 => {
+                for (id, template) in read_templates(flowset)? {
+                    state.insert_template(key(id), template);
+                }
+            }
+            1 => {
+                for (id, template) in read_options_templates(flowset)? {
+                    state.insert_template(key(id), template);
+                }
+            }
+            // Reserved
+            2..=255 => (),
+            id => state.decode_data_set(key(id), flowset, &mut records),
+        }
+    }
+
+    Ok(records)
+}
+
+/// Read every template in a template flowset, ignoring any trailing padding.
+fn read_templates(flowset: &[u8]) -> Result<Vec<(u16, Template)>, FlowError> {
+    let mut reader = Reader::new(flowset);
+    let mut templates = Vec::new();
+
+    while reader.bytes.len() >= 4 {
+        let id = reader.u16()?;
+        let field_count = reader.u16()?;
+
+        let fields = read_fields(&mut reader, usize::from(field_count))?;
+        templates.push((id, Template::new(TemplateKind::Data, fields)?));
+    }
+
+    Ok(templates)
+}
+
+/// Read every options template in an options template flowset, ignoring any trailing padding.
+fn read_options_templates(flowset: &[u8]) -> Result<Vec<(u16, Template)>, FlowError> {
+    let mut reader = Reader::new(flowset);
+    let mut templates = Vec::new();
+
+    while reader.bytes.len() >= 6 {
+        let id = reader.u16()?;
+        // Both are lengths in bytes, rather than field counts
+        let scope_length = usize::from(reader.u16()?);
+        let options_length = usize::from(reader.u16()?);
+
+        let fields = read_fields(&mut reader, (scope_length + options_length) / 4)?;
+        templates.push((id, Template::new(TemplateKind::Options, fields)?));
+    }
+
+    Ok(templates)
+}
+
+/// Read `count` `(type, length)` pairs.
+fn read_fields(reader: &mut Reader, count: usize) -> Result<Box<[Field]>, FlowError> {
+    (0..count)
+        .map(|_| Ok(Field::new(reader.u16()?, reader.u16()?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORTER: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    /// Concatenate big-endian `u16`s.
+    fn u16s(values: &[u16]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_be_bytes())
+            .collect()
+    }
+
+    /// Build a NetFlow v9 datagram from source ID 1 from `(flowset ID, contents)` pairs.
+    fn datagram(flowsets: impl IntoIterator<Item = (u16, Vec<u8>)>) -> Vec<u8> {
+        let flowsets: Vec<_> = flowsets.into_iter().collect();
+        let body: Vec<u8> = flowsets
+            .iter()
+            .flat_map(|(id, set)| [u16s(&[*id, set.len() as u16 + 4]), set.clone()].concat())
+            .collect();
+
+        [
+            u16s(&[9, flowsets.len() as u16]),
+            vec![0; 12],
+            vec![0, 0, 0, 1],
+            body,
+        ]
+        .concat()
+    }
+
+    /// A template flowset defining template 256: source, destination, bytes, and packets.
+    fn flow_template() -> (u16, Vec<u8>) {
+        (0, u16s(&[256, 4, 8, 4, 12, 4, 1, 4, 2, 4]))
+    }
+
+    /// A record of template 256.
+    fn flow(source: [u8; 4], destination: [u8; 4], bytes: u32, packets: u32) -> Vec<u8> {
+        [
+            &source[..],
+            &destination,
+            &bytes.to_be_bytes(),
+            &packets.to_be_bytes(),
+        ]
+        .concat()
+    }
+
+    fn record(source: [u8; 4], destination: [u8; 4], bytes: u64, packets: u64) -> FlowRecord {
+        FlowRecord {
+            source: source.into(),
+            destination: destination.into(),
+            bytes,
+            packets,
+        }
+    }
+
+    #[test]
+    fn test_decode_v9() {
+        let mut state = ExportState::default();
+        // Padded to a multiple of four bytes
+        let data = (256, [flow([1; 4], [2; 4], 1500, 3), vec![0; 3]].concat());
+
+        // Flowsets are skipped until their template arrives
+        assert_eq!(
+            decode_v9(&datagram([data.clone()]), EXPORTER, &mut state),
+            Ok(vec![])
+        );
+        assert_eq!(
+            decode_v9(
+                &datagram([data.clone(), flow_template()]),
+                EXPORTER,
+                &mut state
+            ),
+            Ok(vec![])
+        );
+        assert_eq!(
+            decode_v9(&datagram([data]), EXPORTER, &mut state),
+            Ok(vec![record([1; 4], [2; 4], 1500, 3)])
+        );
+    }
+
+    #[test]
+    fn test_decode_v9_truncated() {
+        let mut state = ExportState::default();
+        let full = datagram([flow_template(), (256, flow([1; 4], [2; 4], 1, 1))]);
+
+        assert_eq!(
+            decode_v9(&full[..12], EXPORTER, &mut state),
+            Err(FlowError::Truncated)
+        );
+        assert_eq!(
+            decode_v9(&full[..full.len() - 1], EXPORTER, &mut state),
+            Err(FlowError::Truncated)
+        );
+
+        // A template cut off partway through its fields
+        let template = (0, u16s(&[257, 4, 8, 4, 12, 4]));
+        assert_eq!(
+            decode_v9(&datagram([template]), EXPORTER, &mut state),
+            Err(FlowError::Truncated)
+        );
+
+        // A flowset shorter than its own header
+        let mut short = datagram([flow_template()]);
+        short[22..24].copy_from_slice(&3_u16.to_be_bytes());
+        assert_eq!(
+            decode_v9(&short, EXPORTER, &mut state),
+            Err(FlowError::Malformed("flowset length"))
+        );
+
+        // A template without fields
+        let empty = (0, u16s(&[258, 0]));
+        assert_eq!(
+            decode_v9(&datagram([empty]), EXPORTER, &mut state),
+            Err(FlowError::Malformed("template"))
+        );
+    }
+
+    #[test]
+    fn test_decode_v9_options_template() {
+        let mut state = ExportState::default();
+
+        // Scoped to the system (1, which is not bytes here), with the sampling interval (34),
+        // padded to a multiple of four bytes
+        let options_template = (1, [u16s(&[259, 4, 4, 1, 4, 34, 4]), vec![0; 2]].concat());
+        let options = (259, [7_u32.to_be_bytes(), 10_u32.to_be_bytes()].concat());
+        let data = (256, flow([1; 4], [2; 4], 1500, 3));
+
+        assert_eq!(
+            decode_v9(
+                &datagram([options_template, options, flow_template(), data]),
+                EXPORTER,
+                &mut state
+            ),
+            Ok(vec![record([1; 4], [2; 4], 15_000, 30)])
+        );
+    }
+
+    #[test]
+    fn test_decode_v5() {
+        let header = |count: u16, sampling: u16| {
+            [u16s(&[5, count]), vec![0; 18], u16s(&[sampling])].concat()
+        };
+        let flow = [
+            vec![1; 4],
+            vec![2; 4],
+            vec![0; 8],
+            5_u32.to_be_bytes().to_vec(),
+            700_u32.to_be_bytes().to_vec(),
+            vec![0; 24],
+        ]
+        .concat();
+
+        // Sampled one in ten, in the low 14 bits
+        assert_eq!(
+            decode_v5(&[header(1, 0x4000 | 10), flow.clone()].concat()),
+            Ok(vec![record([1; 4], [2; 4], 7000, 50)])
+        );
+        assert_eq!(
+            decode_v5(&[header(2, 0), flow].concat()),
+            Err(FlowError::Truncated)
+        );
+    }
+}
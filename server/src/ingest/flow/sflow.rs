@@ -0,0 +1,325 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Decoding [sFlow version 5](https://sflow.org/sflow_version_5.txt) datagrams.
+//!
+//! Unlike NetFlow and IPFIX, sFlow exports individual sampled packets, so each is scaled up by its
+//! sampling rate to estimate the traffic it represents.
+
+use std::net::IpAddr;
+
+use super::{parse_address, parse_ethernet_frame, parse_ip_packet, FlowError, FlowRecord, Reader};
+
+/// The sample format of flow samples.
+const FLOW_SAMPLE: u32 = 1;
+
+/// The sample format of expanded flow samples, used for large interface indices.
+const EXPANDED_FLOW_SAMPLE: u32 = 3;
+
+/// The record format of raw packet headers.
+const RAW_PACKET_HEADER: u32 = 1;
+
+/// The record format of sampled IPv4 packets.
+const SAMPLED_IPV4: u32 = 3;
+
+/// The record format of sampled IPv6 packets.
+const SAMPLED_IPV6: u32 = 4;
+
+/// Decode every flow sample in an sFlow datagram. Counter samples are skipped.
+pub(super) fn decode(datagram: &[u8]) -> Result<Vec<FlowRecord>, FlowError> {
+    let mut reader = Reader::new(datagram);
+
+    let version = reader.u32()?;
+    if version != 5 {
+        return Err(FlowError::UnsupportedVersion(
+            version.try_into().unwrap_or(u16::MAX),
+        ));
+    }
+
+    // Agent address
+    match reader.u32()? {
+        1 => reader.skip(4)?,
+        2 => reader.skip(16)?,
+        _ => return Err(FlowError::Malformed("agent address")),
+    }
+    reader.skip(12)?; // Sub-agent ID, sequence number, uptime
+    let sample_count = reader.u32()?;
+
+    let mut records = Vec::new();
+
+    for _ in 0..sample_count {
+        let format = reader.u32()?;
+        let length = reader.u32()?;
+        let sample = reader.take(usize::try_from(length).unwrap_or(usize::MAX))?;
+
+        // Vendor-specific samples have a non-zero enterprise in the top 20 bits
+        let record = match format {
+            FLOW_SAMPLE => decode_flow_sample(sample, false)?,
+            EXPANDED_FLOW_SAMPLE => decode_flow_sample(sample, true)?,
+            _ => None,
+        };
+
+        records.extend(record);
+    }
+
+    Ok(records)
+}
+
+/// Decode a flow sample into the traffic it represents, if any of its records has addresses.
+fn decode_flow_sample(sample: &[u8], expanded: bool) -> Result<Option<FlowRecord>, FlowError> {
+    let mut reader = Reader::new(sample);
+
+    reader.skip(4)?; // Sequence number
+    reader.skip(if expanded { 8 } else { 4 })?; // Source ID
+    let sampling_rate = u64::from(reader.u32()?.max(1));
+    reader.skip(8)?; // Sample pool, drops
+    reader.skip(if expanded { 16 } else { 8 })?; // Input and output interfaces
+    let record_count = reader.u32()?;
+
+    for _ in 0..record_count {
+        let format = reader.u32()?;
+        let length = reader.u32()?;
+        let record = reader.take(usize::try_from(length).unwrap_or(usize::MAX))?;
+
+        let packet = match format {
+            RAW_PACKET_HEADER => decode_raw_packet_header(record),
+            SAMPLED_IPV4 => decode_sampled_ip(record, 4),
+            SAMPLED_IPV6 => decode_sampled_ip(record, 16),
+            _ => None,
+        };
+
+        // Several records can describe the same packet, so only count the first
+        if let Some((source, destination, length)) = packet {
+            return Ok(Some(FlowRecord {
+                source,
+                destination,
+                bytes: length.saturating_mul(sampling_rate),
+                packets: sampling_rate,
+            }));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Decode the addresses and length of a sampled IPv4 or IPv6 packet, given the length of its
+/// addresses.
+fn decode_sampled_ip(record: &[u8], address_len: usize) -> Option<(IpAddr, IpAddr, u64)> {
+    let mut reader = Reader::new(record);
+
+    let length = u64::from(reader.u32().ok()?);
+    reader.skip(4).ok()?; // Protocol
+    let source = parse_address(reader.take(address_len).ok()?)?;
+    let destination = parse_address(reader.take(address_len).ok()?)?;
+
+    Some((source, destination, length))
+}
+
+/// Decode the addresses and frame length of a sampled packet from its (possibly truncated)
+/// headers, if it is IPv4 or IPv6.
+fn decode_raw_packet_header(record: &[u8]) -> Option<(IpAddr, IpAddr, u64)> {
+    let mut reader = Reader::new(record);
+
+    let protocol = reader.u32().ok()?;
+    let frame_length = u64::from(reader.u32().ok()?);
+    reader.skip(4).ok()?; // Bytes stripped from the frame
+    let header_length = usize::try_from(reader.u32().ok()?).ok()?;
+    let header = reader.take(header_length).ok()?;
+
+    let addresses = match protocol {
+        1 => parse_ethernet_frame(header)?,
+        11 | 12 => parse_ip_packet(header)?,
+        _ => return None,
+    };
+
+    Some((addresses.0, addresses.1, frame_length))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Concatenate big-endian `u32`s.
+    fn u32s(values: &[u32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_be_bytes())
+            .collect()
+    }
+
+    /// Prefix `contents` with its format and length, as samples and records are.
+    fn tagged(format: u32, contents: Vec<u8>) -> Vec<u8> {
+        [u32s(&[format, contents.len() as u32]), contents].concat()
+    }
+
+    /// Build an sFlow datagram from an IPv4 agent holding `samples`.
+    fn datagram(samples: &[Vec<u8>]) -> Vec<u8> {
+        [
+            u32s(&[5, 1]),
+            vec![192, 0, 2, 1],
+            u32s(&[0, 1, 1000, samples.len() as u32]),
+            samples.concat(),
+        ]
+        .concat()
+    }
+
+    /// A compact flow sample sampling one in `sampling_rate` packets, holding `records`.
+    fn flow_sample(sampling_rate: u32, records: &[Vec<u8>]) -> Vec<u8> {
+        let header = u32s(&[1, 7, sampling_rate, 0, 0, 1, 2, records.len() as u32]);
+        tagged(FLOW_SAMPLE, [header, records.concat()].concat())
+    }
+
+    /// An expanded flow sample sampling one in `sampling_rate` packets, holding `records`.
+    fn expanded_flow_sample(sampling_rate: u32, records: &[Vec<u8>]) -> Vec<u8> {
+        let header = u32s(&[1, 0, 70_000, sampling_rate, 0, 0, 0, 70_000, 0, 70_001]);
+        let header = [header, u32s(&[records.len() as u32])].concat();
+        tagged(EXPANDED_FLOW_SAMPLE, [header, records.concat()].concat())
+    }
+
+    /// A sampled IPv4 record of a `length` byte packet from 1.1.1.1 to 2.2.2.2.
+    fn sampled_ipv4(length: u32) -> Vec<u8> {
+        let contents = [u32s(&[length, 6]), vec![1; 4], vec![2; 4], u32s(&[0; 4])].concat();
+        tagged(SAMPLED_IPV4, contents)
+    }
+
+    fn record(bytes: u64, packets: u64) -> FlowRecord {
+        FlowRecord {
+            source: [1; 4].into(),
+            destination: [2; 4].into(),
+            bytes,
+            packets,
+        }
+    }
+
+    #[test]
+    fn test_decode() {
+        let counter_sample = tagged(2, u32s(&[1, 1, 0]));
+        let samples = [
+            counter_sample,
+            flow_sample(1, &[sampled_ipv4(100)]),
+            expanded_flow_sample(1, &[sampled_ipv4(200)]),
+        ];
+
+        // Counter samples are skipped
+        assert_eq!(
+            decode(&datagram(&samples)),
+            Ok(vec![record(100, 1), record(200, 1)])
+        );
+
+        // IPv6 agents
+        let ipv6_agent = [u32s(&[5, 2]), vec![0; 16], u32s(&[0, 1, 1000, 0])].concat();
+        assert_eq!(decode(&ipv6_agent), Ok(vec![]));
+
+        assert_eq!(decode(&u32s(&[4])), Err(FlowError::UnsupportedVersion(4)));
+        assert_eq!(
+            decode(&u32s(&[5, 3, 0, 0, 0, 0])),
+            Err(FlowError::Malformed("agent address"))
+        );
+    }
+
+    #[test]
+    fn test_scaled_counts() {
+        assert_eq!(
+            decode(&datagram(&[flow_sample(512, &[sampled_ipv4(1500)])])),
+            Ok(vec![record(768_000, 512)])
+        );
+        assert_eq!(
+            decode(&datagram(&[expanded_flow_sample(
+                64,
+                &[sampled_ipv4(1500)]
+            )])),
+            Ok(vec![record(96_000, 64)])
+        );
+        // An unset sampling rate counts the packet once
+        assert_eq!(
+            decode(&datagram(&[flow_sample(0, &[sampled_ipv4(1500)])])),
+            Ok(vec![record(1500, 1)])
+        );
+    }
+
+    #[test]
+    fn test_records() {
+        // An Ethernet frame holding an IPv4 packet, from its raw header
+        let ipv4 = [[0x45].as_slice(), &[0; 11], &[1; 4], &[2; 4]].concat();
+        let frame = [[0; 12].as_slice(), &[0x08, 0x00], &ipv4].concat();
+        let raw = tagged(
+            RAW_PACKET_HEADER,
+            [u32s(&[1, 1514, 4, frame.len() as u32]), frame].concat(),
+        );
+        assert_eq!(
+            decode(&datagram(&[flow_sample(10, &[raw])])),
+            Ok(vec![record(15_140, 10)])
+        );
+
+        // A sampled IPv6 packet
+        let ipv6 = tagged(
+            SAMPLED_IPV6,
+            [u32s(&[80, 17]), vec![1; 16], vec![2; 16], u32s(&[0; 4])].concat(),
+        );
+        let expected = FlowRecord {
+            source: [1; 16].into(),
+            destination: [2; 16].into(),
+            bytes: 80,
+            packets: 1,
+        };
+        assert_eq!(
+            decode(&datagram(&[flow_sample(1, &[ipv6])])),
+            Ok(vec![expected])
+        );
+
+        // Unknown records are skipped, and only the first record with addresses is counted
+        let unknown = tagged(1001, u32s(&[0; 3]));
+        assert_eq!(
+            decode(&datagram(&[flow_sample(
+                1,
+                &[unknown, sampled_ipv4(100), sampled_ipv4(200)]
+            )])),
+            Ok(vec![record(100, 1)])
+        );
+
+        // Samples without addresses describe no flow
+        let arp = [[0; 12].as_slice(), &[0x08, 0x06], &[0; 28]].concat();
+        let raw = tagged(
+            RAW_PACKET_HEADER,
+            [u32s(&[1, 64, 0, arp.len() as u32]), arp].concat(),
+        );
+        assert_eq!(decode(&datagram(&[flow_sample(1, &[raw])])), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_truncated() {
+        let full = datagram(&[flow_sample(1, &[sampled_ipv4(100)])]);
+
+        assert_eq!(decode(&full[..6]), Err(FlowError::Truncated));
+        assert_eq!(decode(&full[..full.len() - 1]), Err(FlowError::Truncated));
+
+        // A datagram that claims more samples than it holds
+        let mut missing = full.clone();
+        missing[27] = 2;
+        assert_eq!(decode(&missing), Err(FlowError::Truncated));
+
+        // A sample whose records run past its end
+        let mut sample = flow_sample(1, &[sampled_ipv4(100)]);
+        sample.truncate(sample.len() - 4);
+        sample[7] -= 4;
+        assert_eq!(decode(&datagram(&[sample])), Err(FlowError::Truncated));
+
+        // A record too short for its addresses describes no flow
+        let short = tagged(SAMPLED_IPV4, u32s(&[100, 6, 0x0101_0101]));
+        assert_eq!(decode(&datagram(&[flow_sample(1, &[short])])), Ok(vec![]));
+    }
+}
@@ -0,0 +1,198 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Parsing the fields of interest out of log lines.
+
+use std::net::IpAddr;
+
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use super::extract_address;
+
+/// The fields of interest in a log line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LogEntry {
+    /// The client address.
+    pub address: IpAddr,
+    /// The HTTP status code of the response, if the format has one.
+    pub status: Option<u16>,
+    /// The size of the response in bytes, if the format has one.
+    pub bytes: Option<u64>,
+}
+
+/// Represents the formats that followed log files can be parsed as.
+#[derive(ValueEnum, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Try `combined`, then fall back to `plain` for lines that don't match.
+    Auto,
+    /// The nginx/Apache common or combined access log format:
+    /// `%h %l %u %t "%r" %>s %b ["%{Referer}i" "%{User-agent}i"]`.
+    Combined,
+    /// Any format, using the first IP address in the line as the client address.
+    Plain,
+}
+
+impl LogFormat {
+    /// Parse a log line, returning `None` if it has no client address.
+    pub fn parse(&self, line: &str) -> Option<LogEntry> {
+        match self {
+            Self::Auto => parse_combined(line).or_else(|| parse_plain(line)),
+            Self::Combined => parse_combined(line),
+            Self::Plain => parse_plain(line),
+        }
+    }
+}
+
+/// Parse a line using the first IP address in it as the client address.
+fn parse_plain(line: &str) -> Option<LogEntry> {
+    Some(LogEntry {
+        address: extract_address(line)?,
+        status: None,
+        bytes: None,
+    })
+}
+
+/// Parse a line in the common or combined access log format.
+///
+/// Ex. `192.0.2.1 - frank [10/Oct/2000:13:55:36 -0700] "GET / HTTP/1.0" 200 2326 "-" "curl/8.0"`.
+fn parse_combined(line: &str) -> Option<LogEntry> {
+    let (address, rest) = line.split_once(' ')?;
+    let address = address.parse().ok()?;
+
+    // Skip the identity, user, and timestamp, ex. `- frank [10/Oct/2000:13:55:36 -0700] `
+    let (_, rest) = rest.split_once("] \"")?;
+
+    // Skip the request line, which may contain escaped quotes
+    let mut escaped = false;
+    let end = rest.find(|c| {
+        let end = c == '"' && !escaped;
+        escaped = c == '\\' && !escaped;
+        end
+    })?;
+
+    let mut fields = rest.get(end + 1..)?.split_ascii_whitespace();
+    let status = fields.next()?.parse().ok()?;
+    let bytes = match fields.next()? {
+        "-" => 0,
+        bytes => bytes.parse().ok()?,
+    };
+
+    Some(LogEntry {
+        address,
+        status: Some(status),
+        bytes: Some(bytes),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(address: &str, status: Option<u16>, bytes: Option<u64>) -> Option<LogEntry> {
+        Some(LogEntry {
+            address: address.parse().unwrap(),
+            status,
+            bytes,
+        })
+    }
+
+    #[test]
+    fn test_parse_combined() {
+        // Common
+        assert_eq!(
+            parse_combined(
+                r#"192.0.2.1 - frank [10/Oct/2000:13:55:36 -0700] "GET / HTTP/1.0" 200 2326"#
+            ),
+            entry("192.0.2.1", Some(200), Some(2326))
+        );
+
+        // Combined
+        assert_eq!(
+            parse_combined(
+                r#"192.0.2.1 - - [10/Oct/2000:13:55:36 -0700] "GET /a HTTP/1.1" 404 153 "https://example.com/" "curl/8.0""#
+            ),
+            entry("192.0.2.1", Some(404), Some(153))
+        );
+
+        // Escaped quotes in the request line
+        assert_eq!(
+            parse_combined(
+                r#"192.0.2.1 - - [10/Oct/2000:13:55:36 -0700] "GET /\"a\" 200 1 \\" 301 0 "-" "curl/8.0""#
+            ),
+            entry("192.0.2.1", Some(301), Some(0))
+        );
+
+        // No body
+        assert_eq!(
+            parse_combined(r#"192.0.2.1 - - [10/Oct/2000:13:55:36 -0700] "HEAD / HTTP/1.1" 304 -"#),
+            entry("192.0.2.1", Some(304), Some(0))
+        );
+
+        // IPv6 clients
+        assert_eq!(
+            parse_combined(
+                r#"2001:db8::1 - - [10/Oct/2000:13:55:36 -0700] "GET / HTTP/2.0" 200 12 "-" "-""#
+            ),
+            entry("2001:db8::1", Some(200), Some(12))
+        );
+    }
+
+    #[test]
+    fn test_parse_combined_malformed() {
+        for line in [
+            "",
+            "192.0.2.1",
+            // Not an address
+            r#"example.com - - [10/Oct/2000:13:55:36 -0700] "GET / HTTP/1.1" 200 12"#,
+            // No timestamp
+            r#"192.0.2.1 - - "GET / HTTP/1.1" 200 12"#,
+            // Unterminated request line
+            r#"192.0.2.1 - - [10/Oct/2000:13:55:36 -0700] "GET / HTTP/1.1 200 12"#,
+            r#"192.0.2.1 - - [10/Oct/2000:13:55:36 -0700] "GET / HTTP/1.1\" 200 12"#,
+            // No status or size
+            r#"192.0.2.1 - - [10/Oct/2000:13:55:36 -0700] "GET / HTTP/1.1""#,
+            r#"192.0.2.1 - - [10/Oct/2000:13:55:36 -0700] "GET / HTTP/1.1" 200"#,
+            // Not numbers
+            r#"192.0.2.1 - - [10/Oct/2000:13:55:36 -0700] "GET / HTTP/1.1" OK 12"#,
+            r#"192.0.2.1 - - [10/Oct/2000:13:55:36 -0700] "GET / HTTP/1.1" 200 twelve"#,
+            r#"192.0.2.1 - - [10/Oct/2000:13:55:36 -0700] "GET / HTTP/1.1" 200 -12"#,
+        ] {
+            assert_eq!(parse_combined(line), None, "{line}");
+        }
+    }
+
+    #[test]
+    fn test_parse_auto() {
+        // Falls back to the first address in the line
+        assert_eq!(
+            LogFormat::Auto.parse("Accepted publickey for root from 192.0.2.7 port 22"),
+            entry("192.0.2.7", None, None)
+        );
+        assert_eq!(
+            LogFormat::Combined.parse("Accepted publickey for root from 192.0.2.7 port 22"),
+            None
+        );
+        assert_eq!(
+            LogFormat::Auto
+                .parse(r#"192.0.2.1 - - [10/Oct/2000:13:55:36 -0700] "GET / HTTP/1.1" 200 12"#),
+            entry("192.0.2.1", Some(200), Some(12))
+        );
+        assert_eq!(LogFormat::Plain.parse("no address here"), None);
+    }
+}
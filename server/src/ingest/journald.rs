@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Following the systemd journal of selected units, with `journalctl`.
+
+use std::{
+    io::{self, BufRead, BufReader},
+    process::{Command, Stdio},
+    thread::{self, JoinHandle},
+};
+
+use super::Pipeline;
+
+/// Spawn a thread that follows the journal entries of `units` from now on, recording each message
+/// in `pipeline`.
+///
+/// Fails if `journalctl` cannot be started.
+pub fn spawn_journal_reader(units: &[Box<str>], pipeline: &Pipeline) -> io::Result<JoinHandle<()>> {
+    let mut command = Command::new("journalctl");
+    command
+        .args(["--follow", "--lines=0", "--output=cat"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped());
+
+    for unit in units {
+        command.arg(format!("--unit={unit}"));
+    }
+
+    let mut child = command.spawn()?;
+    // Safety: `Stdio::piped()` above
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    let pipeline = pipeline.clone();
+
+    thread::Builder::new()
+        .name("journalctl".into())
+        .spawn(move || {
+            for message in stdout.lines() {
+                match message {
+                    Ok(message) => pipeline.record_journal_message(&message),
+                    Err(error) => eprintln!("Could not read from journalctl: {error}"),
+                }
+            }
+
+            match child.wait() {
+                Ok(status) => eprintln!("journalctl exited ({status}), no longer following"),
+                Err(error) => eprintln!("Could not wait for journalctl: {error}"),
+            }
+        })
+}
+
+/// Return whether a message from sshd reports a failed authentication attempt.
+///
+/// sshd writes exactly one `Failed <method> for ...` message per rejected attempt, ex. `Failed
+/// password for invalid user admin from 192.0.2.1 port 22 ssh2`.
+pub fn is_ssh_failure(message: &str) -> bool {
+    message.starts_with("Failed ") && message.contains(" from ")
+}
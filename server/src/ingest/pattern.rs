@@ -0,0 +1,321 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! User-defined log formats, declared in the configuration file.
+//!
+//! Example configuration:
+//!
+//! ```toml
+//! log_pattern = "haproxy"
+//!
+//! [[log_patterns]]
+//! name = "haproxy"
+//! regex = '^.*haproxy\[\d+\]: (?P<remote_addr>[^:\s]+):\d+ .* (?P<status>\d{3}) (?P<bytes>\d+) '
+//!
+//! [[log_patterns]]
+//! name = "app"
+//! format = '$time [$level] $remote_addr "$request" $status $bytes'
+//!
+//! [[log_patterns]]
+//! name = "caddy"
+//! json = { address = "request.remote_ip", status = "status", bytes = "size" }
+//! ```
+
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{extract_address, format::LogEntry};
+
+/// The capture group or variable names recognized as the client address.
+const ADDRESS_NAMES: [&str; 3] = ["remote_addr", "address", "client_ip"];
+
+/// The capture group or variable names recognized as the response status.
+const STATUS_NAMES: [&str; 1] = ["status"];
+
+/// The capture group or variable names recognized as the response size.
+const BYTES_NAMES: [&str; 3] = ["bytes", "body_bytes_sent", "bytes_sent"];
+
+/// A named, user-defined log format.
+#[derive(Deserialize, Debug, Clone)]
+pub struct LogPattern {
+    /// The name used to select the pattern, ex. "haproxy".
+    pub name: Box<str>,
+    #[serde(flatten)]
+    pub kind: PatternKind,
+}
+
+/// Represents the ways that a log format can be declared.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum PatternKind {
+    /// A regular expression with named capture groups, ex. `(?P<remote_addr>\S+)`.
+    Regex(Box<str>),
+    /// A format string with `$variable` placeholders, ex. `$remote_addr $status $bytes`.
+    Format(Box<str>),
+    /// JSON objects, with the path (ex. `request.remote_ip`) to each field.
+    Json(JsonFields),
+}
+
+/// The paths to the fields of interest in a JSON log line.
+///
+/// Paths are separated by `.`, ex. `request.remote_ip`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct JsonFields {
+    pub address: Box<str>,
+    pub status: Option<Box<str>>,
+    pub bytes: Option<Box<str>>,
+}
+
+/// A `LogPattern` prepared for parsing lines.
+#[derive(Debug, Clone)]
+pub enum CompiledPattern {
+    Regex(Regex),
+    Json(JsonFields),
+}
+
+impl CompiledPattern {
+    /// Prepare a pattern for parsing lines, failing if it holds an invalid regular expression.
+    pub fn compile(pattern: &LogPattern) -> Result<Self, regex::Error> {
+        match &pattern.kind {
+            PatternKind::Regex(regex) => Ok(Self::Regex(Regex::new(regex)?)),
+            PatternKind::Format(format) => Ok(Self::Regex(Regex::new(&format_to_regex(format))?)),
+            PatternKind::Json(fields) => Ok(Self::Json(fields.clone())),
+        }
+    }
+
+    /// Parse a log line, returning `None` if it doesn't match or has no client address.
+    pub fn parse(&self, line: &str) -> Option<LogEntry> {
+        match self {
+            Self::Regex(regex) => {
+                let captures = regex.captures(line)?;
+                let group = |names: &[&str]| names.iter().find_map(|&n| captures.name(n));
+
+                Some(LogEntry {
+                    address: extract_address(group(&ADDRESS_NAMES)?.as_str())?,
+                    status: group(&STATUS_NAMES).and_then(|m| m.as_str().parse().ok()),
+                    bytes: group(&BYTES_NAMES).map(|m| m.as_str().parse().unwrap_or(0)),
+                })
+            }
+            Self::Json(fields) => {
+                let value: Value = serde_json::from_str(line).ok()?;
+                let field = |path: &str| {
+                    path.split('.')
+                        .try_fold(&value, |value, key| value.get(key))
+                };
+
+                let address = match field(&fields.address)? {
+                    Value::String(address) => extract_address(address)?,
+                    _ => return None,
+                };
+                let number = |path: &Option<Box<str>>| match field(path.as_deref()?)? {
+                    Value::Number(number) => number.as_u64(),
+                    Value::String(number) => number.parse().ok(),
+                    _ => None,
+                };
+
+                Some(LogEntry {
+                    address,
+                    status: number(&fields.status).and_then(|s| s.try_into().ok()),
+                    bytes: number(&fields.bytes),
+                })
+            }
+        }
+    }
+}
+
+/// Convert a format string with `$variable` placeholders into a regular expression.
+///
+/// Recognized variables become named capture groups, others are matched but not captured.
+/// Variables inside quotes match anything but a quote, variables inside brackets match anything
+/// but a closing bracket, and all others match anything but whitespace.
+fn format_to_regex(format: &str) -> String {
+    let mut regex = String::from("^");
+    let mut rest = format;
+
+    while let Some(start) = rest.find('$') {
+        let (literal, variable) = rest.split_at(start);
+        regex.push_str(&regex::escape(literal));
+
+        let variable = &variable[1..];
+        let len = variable
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(variable.len());
+        let (name, after) = variable.split_at(len);
+
+        let value = match literal.chars().last() {
+            Some('"') => r#"[^"]*"#,
+            Some('[') => r"[^\]]*",
+            _ => r"\S+",
+        };
+
+        let recognized = [&ADDRESS_NAMES[..], &STATUS_NAMES, &BYTES_NAMES]
+            .concat()
+            .contains(&name);
+
+        if name.is_empty() {
+            regex.push_str(r"\$");
+        } else if recognized && !regex.contains(&format!("(?P<{name}>")) {
+            regex.push_str(&format!("(?P<{name}>{value})"));
+        } else {
+            regex.push_str(&format!("(?:{value})"));
+        }
+
+        rest = after;
+    }
+
+    regex.push_str(&regex::escape(rest));
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile(kind: PatternKind) -> CompiledPattern {
+        CompiledPattern::compile(&LogPattern {
+            name: "test".into(),
+            kind,
+        })
+        .unwrap()
+    }
+
+    fn entry(address: &str, status: Option<u16>, bytes: Option<u64>) -> Option<LogEntry> {
+        Some(LogEntry {
+            address: address.parse().unwrap(),
+            status,
+            bytes,
+        })
+    }
+
+    #[test]
+    fn test_format_to_regex() {
+        assert_eq!(
+            format_to_regex(r#"$remote_addr [$time] "$request" $status"#),
+            r#"^(?P<remote_addr>\S+) \[(?:[^\]]*)\] "(?:[^"]*)" (?P<status>\S+)"#
+        );
+
+        // Recognized variables are only captured once, since capture group names must be unique
+        assert_eq!(
+            format_to_regex("$remote_addr $bytes $remote_addr $bytes"),
+            r"^(?P<remote_addr>\S+) (?P<bytes>\S+) (?:\S+) (?:\S+)"
+        );
+
+        // A `$` without a name is matched literally
+        assert_eq!(format_to_regex("$ $bytes $"), r"^\$ (?P<bytes>\S+) \$");
+        assert_eq!(format_to_regex("1.50$"), r"^1\.50\$");
+    }
+
+    #[test]
+    fn test_parse_format() {
+        let pattern = compile(PatternKind::Format(
+            r#"$time [$level] $remote_addr "$request" $status $bytes"#.into(),
+        ));
+
+        // Quoted and bracketed variables can hold spaces
+        assert_eq!(
+            pattern.parse(r#"12:00 [warn notice] 192.0.2.1 "GET / HTTP/1.1" 200 512"#),
+            entry("192.0.2.1", Some(200), Some(512))
+        );
+        assert_eq!(
+            pattern.parse(r#"12:00 [info] 2001:db8::1 "GET / HTTP/1.1" - -"#),
+            entry("2001:db8::1", None, Some(0))
+        );
+        assert_eq!(
+            pattern.parse(r#"12:00 [info] example.com "GET / HTTP/1.1" 200 512"#),
+            None
+        );
+        assert_eq!(pattern.parse("12:00 [info] 192.0.2.1 GET 200 512"), None);
+
+        let pattern = compile(PatternKind::Format(
+            "$remote_addr $remote_addr cost $ $bytes".into(),
+        ));
+        assert_eq!(
+            pattern.parse("192.0.2.1 198.51.100.1 cost $ 3"),
+            entry("192.0.2.1", None, Some(3))
+        );
+        assert_eq!(pattern.parse("192.0.2.1 198.51.100.1 cost 3"), None);
+    }
+
+    #[test]
+    fn test_parse_regex() {
+        let pattern = compile(PatternKind::Regex(
+            r"^(?P<client_ip>[^:\s]+):\d+ (?P<status>\S+) (?P<bytes_sent>\S+)".into(),
+        ));
+
+        assert_eq!(
+            pattern.parse("192.0.2.1:4000 503 12"),
+            entry("192.0.2.1", Some(503), Some(12))
+        );
+        assert_eq!(
+            pattern.parse("192.0.2.1:4000 - -"),
+            entry("192.0.2.1", None, Some(0))
+        );
+        assert_eq!(pattern.parse("192.0.2.1 503 12"), None);
+
+        assert!(CompiledPattern::compile(&LogPattern {
+            name: "test".into(),
+            kind: PatternKind::Regex("(?P<status>".into()),
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let pattern = compile(PatternKind::Json(JsonFields {
+            address: "request.remote_ip".into(),
+            status: Some("status".into()),
+            bytes: Some("response.size".into()),
+        }));
+
+        // Numbers or strings of numbers
+        assert_eq!(
+            pattern.parse(
+                r#"{"request": {"remote_ip": "192.0.2.1"}, "status": 200, "response": {"size": 512}}"#
+            ),
+            entry("192.0.2.1", Some(200), Some(512))
+        );
+        assert_eq!(
+            pattern.parse(
+                r#"{"request": {"remote_ip": "2001:db8::1"}, "status": "404", "response": {"size": "0"}}"#
+            ),
+            entry("2001:db8::1", Some(404), Some(0))
+        );
+
+        // Missing or invalid numbers
+        assert_eq!(
+            pattern.parse(r#"{"request": {"remote_ip": "192.0.2.1"}, "status": 70000}"#),
+            entry("192.0.2.1", None, None)
+        );
+        assert_eq!(
+            pattern.parse(
+                r#"{"request": {"remote_ip": "192.0.2.1"}, "status": "OK", "response": {"size": -1}}"#
+            ),
+            entry("192.0.2.1", None, None)
+        );
+
+        // Missing or invalid addresses
+        for line in [
+            r#"{"remote_ip": "192.0.2.1", "status": 200}"#,
+            r#"{"request": {"remote_ip": 3221225985}}"#,
+            r#"{"request": "192.0.2.1"}"#,
+            "192.0.2.1 not json",
+        ] {
+            assert_eq!(pattern.parse(line), None, "{line}");
+        }
+    }
+}
@@ -0,0 +1,314 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Receiving syslog messages over UDP and TCP, in either the BSD ([RFC
+//! 3164](https://www.rfc-editor.org/rfc/rfc3164)) or the newer ([RFC
+//! 5424](https://www.rfc-editor.org/rfc/rfc5424)) format.
+
+use std::net::SocketAddr;
+
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, BufReader},
+    net::{TcpListener, TcpStream, UdpSocket},
+    task::JoinHandle,
+};
+
+use super::Pipeline;
+
+/// The largest message that will be accepted, in bytes.
+const MAX_MESSAGE_SIZE: usize = 64 * 1024;
+
+/// Listen for syslog messages on UDP at `address`, recording each in `pipeline`.
+pub async fn spawn_udp_listener(
+    address: SocketAddr,
+    pipeline: Pipeline,
+) -> std::io::Result<JoinHandle<()>> {
+    let socket = UdpSocket::bind(address).await?;
+    println!("Listening for syslog on udp://{address}");
+
+    Ok(tokio::spawn(async move {
+        let mut buffer = vec![0; MAX_MESSAGE_SIZE];
+
+        loop {
+            match socket.recv(&mut buffer).await {
+                Ok(len) => pipeline.record_syslog_message(&String::from_utf8_lossy(&buffer[..len])),
+                Err(error) => eprintln!("Failed to receive syslog message: {error}"),
+            }
+        }
+    }))
+}
+
+/// Listen for syslog connections on TCP at `address`, recording each message in `pipeline`.
+pub async fn spawn_tcp_listener(
+    address: SocketAddr,
+    pipeline: Pipeline,
+) -> std::io::Result<JoinHandle<()>> {
+    let listener = TcpListener::bind(address).await?;
+    println!("Listening for syslog on tcp://{address}");
+
+    Ok(tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(handle_connection(stream, pipeline.clone()));
+                }
+                Err(error) => eprintln!("Failed to accept syslog connection: {error}"),
+            }
+        }
+    }))
+}
+
+/// Read messages from a TCP connection until it closes.
+///
+/// Supports both octet-counted (ex. `11 <34>1 - ...`) and newline-delimited framing, per [RFC
+/// 6587](https://www.rfc-editor.org/rfc/rfc6587).
+async fn handle_connection(stream: TcpStream, pipeline: Pipeline) {
+    let mut reader = BufReader::new(stream);
+    let mut buffer = Vec::new();
+
+    loop {
+        match read_message(&mut reader, &mut buffer).await {
+            Ok(true) => pipeline.record_syslog_message(&String::from_utf8_lossy(&buffer)),
+            Ok(false) => return,
+            Err(error) => {
+                eprintln!("Failed to read syslog message: {error}");
+                return;
+            }
+        }
+    }
+}
+
+/// Read the next message into `buffer`, in whichever framing it uses, returning `false` once the
+/// connection closes.
+async fn read_message<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    buffer: &mut Vec<u8>,
+) -> std::io::Result<bool> {
+    let octet_counted = match reader.fill_buf().await {
+        Ok([]) | Err(_) => return Ok(false),
+        Ok([first, ..]) => first.is_ascii_digit(),
+    };
+
+    buffer.clear();
+
+    if octet_counted {
+        read_octet_counted(reader, buffer).await?;
+    } else {
+        reader
+            .take(MAX_MESSAGE_SIZE as u64)
+            .read_until(b'\n', buffer)
+            .await?;
+    }
+
+    Ok(true)
+}
+
+/// Read a message prefixed with its length, ex. `11 <34>1 - ...`.
+async fn read_octet_counted<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    buffer: &mut Vec<u8>,
+) -> std::io::Result<()> {
+    let invalid = || std::io::Error::new(std::io::ErrorKind::InvalidData, "invalid message length");
+
+    reader.take(8).read_until(b' ', buffer).await?;
+    let len: usize = std::str::from_utf8(buffer)
+        .ok()
+        .and_then(|len| len.trim_end().parse().ok())
+        .filter(|&len| len <= MAX_MESSAGE_SIZE)
+        .ok_or_else(invalid)?;
+
+    buffer.clear();
+    buffer.resize(len, 0);
+    reader.read_exact(buffer).await?;
+
+    Ok(())
+}
+
+/// Return the free-form message of a syslog message, without its header or structured data.
+///
+/// This avoids mistaking the address of the sending device (in the hostname field) for an address
+/// in the message. Messages in neither format are returned unchanged.
+///
+/// Ex. `<34>Oct 11 22:14:15 192.0.2.1 sshd[12]: Failed password from 198.51.100.7` ->
+/// `sshd[12]: Failed password from 198.51.100.7`.
+pub fn message_body(message: &str) -> &str {
+    let message = message.trim_end_matches(['\r', '\n', '\0']);
+
+    // Ex. `<34>`
+    let Some((_, rest)) = message.strip_prefix('<').and_then(|m| m.split_once('>')) else {
+        return message;
+    };
+
+    // RFC 5424: `1 TIMESTAMP HOSTNAME APP-NAME PROCID MSGID STRUCTURED-DATA MSG`
+    if let Some(rest) = rest.strip_prefix("1 ") {
+        let rest = (0..5).fold(rest, |rest, _| rest.split_once(' ').map_or("", |(_, r)| r));
+
+        return skip_structured_data(rest);
+    }
+
+    // RFC 3164: `Mmm dd hh:mm:ss HOSTNAME TAG: MSG`
+    let bytes = rest.as_bytes();
+    if bytes.len() > 16 && bytes[3] == b' ' && bytes[6] == b' ' && bytes[15] == b' ' {
+        return rest[16..].split_once(' ').map_or("", |(_, r)| r);
+    }
+
+    rest
+}
+
+/// Skip the structured data of an RFC 5424 message, ex. `[id key="value"] ` or `- `.
+fn skip_structured_data(mut rest: &str) -> &str {
+    if let Some(rest) = rest.strip_prefix('-') {
+        return rest.trim_start_matches(' ');
+    }
+
+    while rest.starts_with('[') {
+        let mut escaped = false;
+        let Some(end) = rest.find(|c| {
+            let end = c == ']' && !escaped;
+            escaped = c == '\\' && !escaped;
+            end
+        }) else {
+            return "";
+        };
+
+        rest = &rest[end + 1..];
+    }
+
+    rest.trim_start_matches(' ')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Read every message from `stream`, then the error that ended it, if any.
+    async fn read_all(mut stream: &[u8]) -> (Vec<String>, Option<std::io::ErrorKind>) {
+        let mut messages = Vec::new();
+        let mut buffer = Vec::new();
+
+        loop {
+            match read_message(&mut stream, &mut buffer).await {
+                Ok(true) => messages.push(String::from_utf8_lossy(&buffer).into_owned()),
+                Ok(false) => return (messages, None),
+                Err(error) => return (messages, Some(error.kind())),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_message() {
+        // Newline framing, with or without a final newline
+        assert_eq!(
+            read_all(b"<34>one\n<34>two\n<34>three").await,
+            (
+                vec!["<34>one\n".into(), "<34>two\n".into(), "<34>three".into()],
+                None
+            )
+        );
+
+        // Octet counting, which may hold newlines, mixed with newline framing
+        assert_eq!(
+            read_all(b"9 <34>a\nb c0 <34>d\n").await,
+            (
+                vec!["<34>a\nb c".into(), String::new(), "<34>d\n".into()],
+                None
+            )
+        );
+
+        // Long messages are split rather than buffered without bound
+        let long = format!("<34>{}\n", "a".repeat(MAX_MESSAGE_SIZE));
+        let (messages, error) = read_all(long.as_bytes()).await;
+        assert_eq!(error, None);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].len(), MAX_MESSAGE_SIZE);
+        assert_eq!(messages[0].clone() + &messages[1], long);
+    }
+
+    #[tokio::test]
+    async fn test_read_octet_counted_invalid() {
+        let invalid = Some(std::io::ErrorKind::InvalidData);
+
+        // Oversized
+        let oversized = format!("{} <34>a", MAX_MESSAGE_SIZE + 1);
+        assert_eq!(read_all(oversized.as_bytes()).await, (Vec::new(), invalid));
+        assert_eq!(read_all(b"123456789 <34>a").await, (Vec::new(), invalid));
+
+        // Garbage
+        assert_eq!(read_all(b"12a <34>a").await, (Vec::new(), invalid));
+        assert_eq!(read_all(b"1e3 <34>a").await, (Vec::new(), invalid));
+
+        // Shorter than its length
+        assert_eq!(
+            read_all(b"4").await,
+            (Vec::new(), Some(std::io::ErrorKind::UnexpectedEof))
+        );
+        assert_eq!(
+            read_all(b"5 <34>a5 <34>").await,
+            (
+                vec!["<34>a".into()],
+                Some(std::io::ErrorKind::UnexpectedEof)
+            )
+        );
+    }
+
+    #[test]
+    fn test_message_body() {
+        // RFC 3164
+        assert_eq!(
+            message_body(
+                "<34>Oct 11 22:14:15 192.0.2.1 sshd[12]: Failed password from 198.51.100.7\n"
+            ),
+            "sshd[12]: Failed password from 198.51.100.7"
+        );
+        assert_eq!(
+            message_body("<34>Oct  1 02:04:05 host su: 'su root' failed\r\n"),
+            "su: 'su root' failed"
+        );
+
+        // RFC 5424, without structured data
+        assert_eq!(
+            message_body("<165>1 2003-10-11T22:14:15.003Z 192.0.2.1 evntslog - ID47 - login from 198.51.100.7"),
+            "login from 198.51.100.7"
+        );
+
+        // RFC 5424, with structured data, which may hold escaped brackets
+        assert_eq!(
+            message_body(
+                r#"<165>1 2003-10-11T22:14:15.003Z host app 12 ID47 [a x="192.0.2.1"][b y="\]"] sent 198.51.100.7"#
+            ),
+            "sent 198.51.100.7"
+        );
+        assert_eq!(
+            message_body(r#"<165>1 2003-10-11T22:14:15.003Z host app 12 ID47 [a x="192.0.2.1""#),
+            ""
+        );
+
+        // RFC 5424, with a truncated header
+        assert_eq!(message_body("<165>1 2003-10-11T22:14:15.003Z host"), "");
+
+        // Neither format
+        assert_eq!(
+            message_body("<34>sshd: from 198.51.100.7"),
+            "sshd: from 198.51.100.7"
+        );
+        assert_eq!(
+            message_body("sshd: from 198.51.100.7\n"),
+            "sshd: from 198.51.100.7"
+        );
+        assert_eq!(message_body("<34"), "<34");
+    }
+}
@@ -0,0 +1,305 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Following log files with `tail -F` semantics.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Seek, SeekFrom},
+    path::Path,
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use super::Pipeline;
+
+/// Spawn a thread for each log file that follows it, recording every new line in `pipeline`.
+pub fn spawn_log_followers(
+    paths: &[Box<Path>],
+    interval: Duration,
+    pipeline: &Pipeline,
+) -> Vec<JoinHandle<()>> {
+    paths
+        .iter()
+        .map(|path| {
+            let path = path.clone();
+            let pipeline = pipeline.clone();
+
+            thread::Builder::new()
+                .name(format!("tail {}", path.display()))
+                .spawn(move || {
+                    let mut follower = Follower::new(path);
+
+                    loop {
+                        follower.poll(|line| pipeline.record_log_line(line));
+                        thread::sleep(interval);
+                    }
+                })
+                .expect("a new thread")
+        })
+        .collect()
+}
+
+/// Identifies a file independently of its path, so that rotation can be detected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct FileIdentity {
+    device: u64,
+    inode: u64,
+}
+
+impl FileIdentity {
+    /// Return the identity of a file from its metadata.
+    #[cfg(unix)]
+    fn from_metadata(metadata: &fs::Metadata) -> Option<Self> {
+        use std::os::unix::fs::MetadataExt;
+
+        Some(Self {
+            device: metadata.dev(),
+            inode: metadata.ino(),
+        })
+    }
+
+    /// Return the identity of a file from its metadata.
+    ///
+    /// Not supported on this platform, so only truncation is detected.
+    #[cfg(not(unix))]
+    fn from_metadata(_metadata: &fs::Metadata) -> Option<Self> {
+        None
+    }
+}
+
+/// Follows a file by path, like `tail -F`.
+///
+/// Starts from the end of the file. If the file is rotated (replaced by a new file) or truncated,
+/// the remainder of the old file is read before reading the new file from the beginning. If the
+/// file does not exist, it is retried on every poll.
+///
+/// Lines are not required to be UTF-8, invalid sequences are replaced with `U+FFFD`.
+#[derive(Debug)]
+struct Follower {
+    path: Box<Path>,
+    file: Option<OpenFile>,
+    /// Whether to read the next opened file from the beginning, rather than the end.
+    from_start: bool,
+}
+
+/// A followed file that is currently open.
+#[derive(Debug)]
+struct OpenFile {
+    reader: BufReader<File>,
+    identity: Option<FileIdentity>,
+    position: u64,
+    /// The start of a line that has not been completely written yet.
+    partial: Vec<u8>,
+}
+
+impl Follower {
+    /// Create a new `Follower` for `path`. Does not open the file until the first poll.
+    fn new(path: Box<Path>) -> Self {
+        Self {
+            path,
+            file: None,
+            from_start: false,
+        }
+    }
+
+    /// Read every complete line written since the last poll, passing each to `on_line`.
+    fn poll(&mut self, mut on_line: impl FnMut(&str)) {
+        if self.file.is_none() {
+            match self.open() {
+                Ok(file) => self.file = Some(file),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                    // Anything created from now on is new
+                    self.from_start = true;
+                    return;
+                }
+                Err(error) => {
+                    eprintln!("Could not open {}: {error}", self.path.display());
+                    return;
+                }
+            }
+        }
+
+        // Safety: opened above
+        let file = self.file.as_mut().unwrap();
+
+        if let Err(error) = file.read_lines(&mut on_line) {
+            eprintln!("Could not read {}: {error}", self.path.display());
+        }
+
+        // Once the current file is drained, check whether it has been replaced or truncated
+        let (replaced, truncated) = match fs::metadata(&self.path) {
+            Ok(metadata) => (
+                FileIdentity::from_metadata(&metadata) != file.identity,
+                metadata.len() < file.position,
+            ),
+            Err(_) => (true, false),
+        };
+
+        if replaced {
+            // Lines may have been written to the old file between draining it and checking
+            if let Err(error) = file.read_lines(&mut on_line) {
+                eprintln!("Could not read {}: {error}", self.path.display());
+            }
+        }
+        if replaced || truncated {
+            self.file = None;
+            self.from_start = true;
+        }
+    }
+
+    /// Open the file, seeking to its end unless `self.from_start` is set.
+    fn open(&self) -> io::Result<OpenFile> {
+        let mut file = File::open(&self.path)?;
+        let identity = FileIdentity::from_metadata(&file.metadata()?);
+
+        let position = if self.from_start {
+            0
+        } else {
+            file.seek(SeekFrom::End(0))?
+        };
+
+        Ok(OpenFile {
+            reader: BufReader::new(file),
+            identity,
+            position,
+            partial: Vec::new(),
+        })
+    }
+}
+
+impl OpenFile {
+    /// Read every complete line until the end of the file, passing each to `on_line`.
+    fn read_lines(&mut self, on_line: &mut impl FnMut(&str)) -> io::Result<()> {
+        loop {
+            let read = self.reader.read_until(b'\n', &mut self.partial)?;
+            if read == 0 {
+                return Ok(());
+            }
+
+            self.position += read as u64;
+
+            // Wait for the rest of a partially written line
+            if !self.partial.ends_with(b"\n") {
+                continue;
+            }
+
+            let line = String::from_utf8_lossy(&self.partial);
+            on_line(line.trim_end_matches(['\r', '\n']));
+            self.partial.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    /// Append `contents` to the file at `path`, creating it if necessary.
+    fn append(path: &Path, contents: &[u8]) {
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        file.write_all(contents).unwrap();
+    }
+
+    /// Poll `follower`, returning every line it read.
+    fn poll(follower: &mut Follower) -> Vec<String> {
+        let mut lines = Vec::new();
+        follower.poll(|line| lines.push(line.to_owned()));
+        lines
+    }
+
+    #[test]
+    fn test_follow() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("access.log");
+        append(&path, b"before\n");
+
+        // Existing lines are skipped
+        let mut follower = Follower::new(path.as_path().into());
+        assert!(poll(&mut follower).is_empty());
+
+        append(&path, b"first\r\nsec");
+        assert_eq!(poll(&mut follower), ["first"]);
+        append(&path, b"ond\n");
+        assert_eq!(poll(&mut follower), ["second"]);
+
+        // Invalid UTF-8 is replaced, and the lines after it are still read
+        append(&path, b"caf\xe9\nthird\n");
+        assert_eq!(poll(&mut follower), ["caf\u{FFFD}", "third"]);
+    }
+
+    #[test]
+    fn test_follow_missing() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("access.log");
+
+        let mut follower = Follower::new(path.as_path().into());
+        assert!(poll(&mut follower).is_empty());
+
+        // Files created after the first poll are read from the beginning
+        append(&path, b"first\n");
+        assert_eq!(poll(&mut follower), ["first"]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_follow_rotated() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("access.log");
+        let rotated = directory.path().join("access.log.1");
+        append(&path, b"");
+
+        let mut follower = Follower::new(path.as_path().into());
+        assert!(poll(&mut follower).is_empty());
+        append(&path, b"first\n");
+
+        // The rest of the old file is read before the new file, including lines written to it
+        // after it was renamed
+        fs::rename(&path, &rotated).unwrap();
+        append(&rotated, b"second\n");
+        append(&path, b"third\n");
+        assert_eq!(poll(&mut follower), ["first", "second"]);
+        assert_eq!(poll(&mut follower), ["third"]);
+
+        append(&path, b"fourth\n");
+        assert_eq!(poll(&mut follower), ["fourth"]);
+    }
+
+    #[test]
+    fn test_follow_truncated() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("access.log");
+        append(&path, b"");
+
+        let mut follower = Follower::new(path.as_path().into());
+        assert!(poll(&mut follower).is_empty());
+        append(&path, b"first line\n");
+        assert_eq!(poll(&mut follower), ["first line"]);
+
+        // Truncated in place, ex. by `logrotate`'s `copytruncate`
+        fs::write(&path, b"").unwrap();
+        assert!(poll(&mut follower).is_empty());
+        append(&path, b"second\n");
+        assert_eq!(poll(&mut follower), ["second"]);
+    }
+}
@@ -0,0 +1,445 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Limiting how many lookups clients can make, with per-client rate limits and a cap on lookup
+//! requests in flight at once, and how many connections they can open, with a cap on connections
+//! open at once.
+
+use std::{
+    collections::HashMap,
+    io,
+    net::IpAddr,
+    num::NonZeroUsize,
+    pin::Pin,
+    process,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use futures_util::{Stream, StreamExt};
+use ip_geo::privacy::Truncation;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    sync::{OwnedSemaphorePermit, Semaphore},
+};
+use warp::{reject::Reject, Filter, Rejection};
+
+use crate::{
+    arguments::Arguments,
+    client::{self, TrustedProxies},
+    metrics::Metrics,
+};
+
+/// How clients are told apart: by IPv4 address, or by IPv6 /64 network, which a single client
+/// typically has all of.
+const CLIENT_TRUNCATION: Truncation = Truncation::new(32, 64);
+
+/// How many clients to track before forgetting those whose buckets have refilled.
+const MIN_TRACKED_CLIENTS: usize = 1024;
+
+/// The limits applied to lookups.
+#[derive(Debug)]
+pub struct Limits {
+    rate: Option<RateLimiter>,
+    /// Permits for lookup requests in flight, if capped.
+    in_flight: Option<Arc<Semaphore>>,
+    /// The proxies trusted to name the clients they forward requests from.
+    proxies: Arc<TrustedProxies>,
+    metrics: Arc<Metrics>,
+}
+
+/// Limits the rate of requests from each client with a token bucket.
+#[derive(Debug)]
+struct RateLimiter {
+    /// The tokens added to every bucket per second.
+    rate: f64,
+    /// The most tokens a bucket can hold, and so the most requests a client can make at once.
+    burst: f64,
+    buckets: Mutex<Buckets>,
+}
+
+/// The token buckets of recently seen clients.
+#[derive(Debug)]
+struct Buckets {
+    /// The number of tokens of each client, as of when it was last updated.
+    tokens: HashMap<IpAddr, (f64, Instant)>,
+    /// How many clients to track before pruning.
+    prune_at: usize,
+}
+
+impl RateLimiter {
+    fn new(rate: f64, burst: u32) -> Self {
+        Self {
+            rate,
+            burst: burst.max(1) as f64,
+            buckets: Mutex::new(Buckets {
+                tokens: HashMap::new(),
+                prune_at: MIN_TRACKED_CLIENTS,
+            }),
+        }
+    }
+
+    /// Take a token from the bucket of `client`, returning how long until one is available if the
+    /// bucket is empty.
+    fn take(&self, client: IpAddr) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().expect("an unpoisoned lock");
+
+        // Forget clients whose buckets have refilled, which behave the same as new clients
+        if buckets.tokens.len() >= buckets.prune_at {
+            // Tiny rates take longer to refill than a `Duration` can hold
+            let refill =
+                Duration::try_from_secs_f64(self.burst / self.rate).unwrap_or(Duration::MAX);
+            buckets
+                .tokens
+                .retain(|_, (_, updated)| now.duration_since(*updated) < refill);
+            buckets.prune_at = (buckets.tokens.len() * 2).max(MIN_TRACKED_CLIENTS);
+        }
+
+        let (tokens, updated) = buckets
+            .tokens
+            .entry(CLIENT_TRUNCATION.apply(client))
+            .or_insert((self.burst, now));
+
+        *tokens =
+            (*tokens + now.duration_since(*updated).as_secs_f64() * self.rate).min(self.burst);
+        *updated = now;
+
+        if *tokens < 1.0 {
+            let retry_after = Duration::try_from_secs_f64((1.0 - *tokens) / self.rate);
+            return Err(retry_after.unwrap_or(Duration::MAX));
+        }
+
+        *tokens -= 1.0;
+        Ok(())
+    }
+}
+
+/// The rejection of a request from a client that exceeded its rate limit.
+#[derive(Debug)]
+pub struct RateLimited {
+    /// How long until the client can make another request.
+    pub retry_after: Duration,
+}
+
+impl Reject for RateLimited {}
+
+/// The rejection of a request while too many lookup requests are already in flight.
+#[derive(Debug)]
+pub struct Overloaded;
+
+impl Reject for Overloaded {}
+
+/// For a given set of arguments, return the limits to apply to lookups, if any.
+pub fn get_limits(
+    arguments: &Arguments,
+    proxies: &Arc<TrustedProxies>,
+    metrics: &Arc<Metrics>,
+) -> Option<Arc<Limits>> {
+    // Safety: `arguments::get_config()` implements default values
+    let burst = arguments.rate_limit_burst.unwrap();
+
+    let rate = arguments
+        .rate_limit
+        .filter(|rate| *rate > 0.0)
+        .map(|rate| RateLimiter::new(rate, burst));
+    let in_flight = arguments
+        .max_in_flight_lookups
+        .map(|max| Arc::new(Semaphore::new(NonZeroUsize::get(max))));
+
+    if rate.is_none() && in_flight.is_none() {
+        return None;
+    }
+
+    Some(Arc::new(Limits {
+        rate,
+        in_flight,
+        proxies: proxies.clone(),
+        metrics: metrics.clone(),
+    }))
+}
+
+/// Return a filter that rejects requests from clients over their rate limit with [`RateLimited`],
+/// and requests while too many lookup requests are in flight with [`Overloaded`].
+///
+/// Extracts a permit for the lookup, if lookups are capped, which must be held until the lookup
+/// is done. Clients are only rate limited if their address is known, which it is not on Unix
+/// sockets.
+pub fn require(
+    limits: Option<Arc<Limits>>,
+) -> impl Filter<Extract = (Option<OwnedSemaphorePermit>,), Error = Rejection> + Clone {
+    let proxies = limits
+        .as_ref()
+        .map(|limits| limits.proxies.clone())
+        .unwrap_or_default();
+
+    client::address(proxies).and_then(move |client: Option<IpAddr>| {
+        let limits = limits.clone();
+
+        async move {
+            let Some(limits) = limits else {
+                return Ok(None);
+            };
+
+            if let (Some(rate), Some(client)) = (&limits.rate, client) {
+                if let Err(retry_after) = rate.take(client) {
+                    limits.metrics.record_limited("rate");
+                    return Err(warp::reject::custom(RateLimited { retry_after }));
+                }
+            }
+
+            match &limits.in_flight {
+                Some(in_flight) => match in_flight.clone().try_acquire_owned() {
+                    Ok(permit) => Ok(Some(permit)),
+                    Err(_) => {
+                        limits.metrics.record_limited("in_flight");
+                        Err(warp::reject::custom(Overloaded))
+                    }
+                },
+                None => Ok(None),
+            }
+        }
+    })
+}
+
+/// Caps the connections open at once, across every listener.
+#[derive(Debug)]
+pub struct ConnectionLimit {
+    /// Permits for open connections.
+    permits: Arc<Semaphore>,
+    metrics: Arc<Metrics>,
+}
+
+impl ConnectionLimit {
+    /// Wait until another connection can be opened, returning the permit to hold until it is
+    /// closed.
+    pub async fn acquire(&self) -> OwnedSemaphorePermit {
+        if let Ok(permit) = self.permits.clone().try_acquire_owned() {
+            return permit;
+        }
+
+        self.metrics.record_limited("connections");
+        self.permits
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("a semaphore that is never closed")
+    }
+}
+
+/// For a given set of arguments, return the cap on connections open at once, if any.
+///
+/// Exits if the HTTP API is served over TLS, whose connections are accepted by Warp itself.
+pub fn get_connection_limit(
+    arguments: &Arguments,
+    metrics: &Arc<Metrics>,
+) -> Option<Arc<ConnectionLimit>> {
+    let max = arguments.max_connections?;
+
+    if arguments.tls_cert_path.is_some() {
+        eprintln!("Capping connections is not supported over TLS");
+        process::exit(1);
+    }
+
+    Some(Arc::new(ConnectionLimit {
+        permits: Arc::new(Semaphore::new(max.get())),
+        metrics: metrics.clone(),
+    }))
+}
+
+/// A connection that holds a permit of a [`ConnectionLimit`] (if capped) until it is closed.
+#[derive(Debug)]
+pub struct Limited<S> {
+    stream: S,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl<S> Limited<S> {
+    pub fn new(stream: S, permit: Option<OwnedSemaphorePermit>) -> Self {
+        Self {
+            stream,
+            _permit: permit,
+        }
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for Limited<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for Limited<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_shutdown(cx)
+    }
+}
+
+#[cfg(feature = "grpc")]
+impl<S: tonic::transport::server::Connected> tonic::transport::server::Connected for Limited<S> {
+    type ConnectInfo = S::ConnectInfo;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        self.stream.connect_info()
+    }
+}
+
+/// Return the connections of `incoming`, only passing each on once `limit` (if any) allows another
+/// connection to be opened.
+///
+/// Connections past the cap are held until another closes, rather than closed, and no more are
+/// accepted in the meantime.
+pub fn limit_connections<S, T, E>(
+    incoming: S,
+    limit: Option<Arc<ConnectionLimit>>,
+) -> impl Stream<Item = Result<Limited<T>, E>>
+where
+    S: Stream<Item = Result<T, E>> + Unpin,
+{
+    futures_util::stream::unfold((incoming, limit), |(mut incoming, limit)| async move {
+        let connection = incoming.next().await?;
+        let permit = match &limit {
+            Some(limit) => Some(limit.acquire().await),
+            None => None,
+        };
+
+        let connection = connection.map(|stream| Limited::new(stream, permit));
+        Some((connection, (incoming, limit)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ip_geo::{GeoDatabase, IpAddrMap};
+
+    use super::*;
+    use crate::metrics::Aggregation;
+
+    /// Return the address of the `index`th client, ex. `10.0.0.1` for 1.
+    fn client(index: u32) -> IpAddr {
+        std::net::Ipv4Addr::from(0x0A00_0000 + index).into()
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let limiter = RateLimiter::new(100.0, 2);
+
+        // A full bucket allows a burst, then rejects until a token is added
+        assert_eq!(limiter.take(client(1)), Ok(()));
+        assert_eq!(limiter.take(client(1)), Ok(()));
+        let retry_after = limiter.take(client(1)).unwrap_err();
+        assert!(retry_after <= Duration::from_millis(10), "{retry_after:?}");
+
+        // Clients have their own buckets, shared by every address of an IPv6 /64 network
+        assert_eq!(limiter.take(client(2)), Ok(()));
+        let network = |address: &str| address.parse::<IpAddr>().unwrap();
+        assert_eq!(limiter.take(network("2001:db8::1")), Ok(()));
+        assert_eq!(limiter.take(network("2001:db8::ffff")), Ok(()));
+        assert!(limiter.take(network("2001:db8::2")).is_err());
+        assert_eq!(limiter.take(network("2001:db8:0:1::1")), Ok(()));
+
+        // Buckets refill over time
+        std::thread::sleep(retry_after + Duration::from_millis(1));
+        assert_eq!(limiter.take(client(1)), Ok(()));
+        assert!(limiter.take(client(1)).is_err());
+    }
+
+    #[test]
+    fn test_rate_limit_tiny() {
+        // Too slow to ever refill within a `Duration`, which must not panic (and poison the lock)
+        let limiter = RateLimiter::new(1e-20, 1);
+        assert_eq!(limiter.take(client(0)), Ok(()));
+        assert_eq!(limiter.take(client(0)), Err(Duration::MAX));
+
+        for index in 1..=MIN_TRACKED_CLIENTS as u32 {
+            let _ = limiter.take(client(index));
+        }
+        assert_eq!(limiter.take(client(0)), Err(Duration::MAX));
+    }
+
+    #[test]
+    fn test_rate_limit_pruning() {
+        let limiter = RateLimiter::new(1000.0, 1);
+        let tracked = || limiter.buckets.lock().unwrap().tokens.len();
+
+        for index in 0..MIN_TRACKED_CLIENTS as u32 {
+            assert_eq!(limiter.take(client(index)), Ok(()));
+        }
+        assert_eq!(tracked(), MIN_TRACKED_CLIENTS);
+
+        // Once every bucket has refilled, they are forgotten when the next client is seen
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(limiter.take(client(0)), Ok(()));
+        assert_eq!(tracked(), 1);
+
+        // Clients whose buckets have not refilled are kept, and remain limited
+        assert!(limiter.take(client(0)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_connection_limit() {
+        let database = GeoDatabase::new(IpAddrMap::new(), IpAddrMap::new());
+        let metrics = Arc::new(Metrics::new(
+            Aggregation::Country,
+            &database,
+            None,
+            Duration::ZERO,
+        ));
+        let limit = Arc::new(ConnectionLimit {
+            permits: Arc::new(Semaphore::new(2)),
+            metrics,
+        });
+
+        let incoming = futures_util::stream::iter((0..4).map(Ok::<_, io::Error>));
+        let mut connections = Box::pin(limit_connections(incoming, Some(limit.clone())));
+        let first = connections.next().await.unwrap().unwrap();
+        let second = connections.next().await.unwrap().unwrap();
+        assert_eq!((*first.get_ref(), *second.get_ref()), (0, 1));
+
+        // A third connection waits until another closes
+        let next = tokio::time::timeout(Duration::from_millis(20), connections.next()).await;
+        assert!(next.is_err());
+        drop(first);
+        let third = connections.next().await.unwrap().unwrap();
+        assert_eq!(*third.get_ref(), 2);
+        assert_eq!(limit.permits.available_permits(), 0);
+
+        drop((second, third));
+        assert_eq!(limit.permits.available_permits(), 2);
+    }
+}
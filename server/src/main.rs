@@ -15,33 +15,223 @@
 // You should have received a copy of the GNU Affero General Public License along with ip_geo. If
 // not, see <https://www.gnu.org/licenses/>.
 
-use clap::Parser;
+use std::time::{Duration, Instant};
+
+mod access_log;
+
+mod acl;
 
 #[macro_use]
 mod api;
 
 mod arguments;
-use arguments::Arguments;
+
+mod auth;
+
+mod cache;
+
+mod check;
+
+mod client;
+
+mod dns;
 
 mod error;
 
+mod events;
+
+#[cfg(feature = "grpc")]
+mod grpc;
+
+mod health;
+
+mod ingest;
+
+mod limit;
+
+mod metrics;
+
 mod parse;
 
+mod protobuf;
+
+mod reload;
+
+mod shutdown;
+
+mod sink;
+
+mod systemd;
+
+mod tls;
+
+mod tor;
+
+mod update;
+
 #[tokio::main]
 pub async fn main() {
     // Parse options
-    let arguments = arguments::get_config(Arguments::parse());
+    let arguments = arguments::get_config(arguments::parse());
+    access_log::init(&arguments);
 
-    // Safety: `arguments::get_config()` implements default values
-    let ipv4_target = arguments.ipv4_pair.unwrap();
-    let ipv6_target = arguments.ipv6_pair.unwrap();
+    let targets = api::get_targets(&arguments);
 
     // Parse databases
-    let maps = parse::parse_ip_maps(&arguments);
+    let start = Instant::now();
+    let database = parse::parse_database(&arguments);
+    let load_duration = start.elapsed();
+    let database_timestamp = parse::database_timestamp(&arguments, &database);
+
+    let named_databases = parse::parse_named_databases(&arguments);
+    let asn_database = parse::parse_asn_database(&arguments);
+    let metrics = metrics::get_metrics(
+        &arguments,
+        &database,
+        asn_database,
+        database_timestamp,
+        load_duration,
+    );
+
+    // Report on startup and exit instead of serving, with `--check`
+    // Safety: `arguments::get_config()` implements default values
+    if arguments.check.unwrap() {
+        // Exit on invalid credentials, trusted proxies, or access rules, like serving would
+        let proxies = client::get_trusted_proxies(&arguments);
+        acl::get_access_control(&arguments, &proxies, &metrics);
+        auth::get_credentials(&arguments);
+        limit::get_connection_limit(&arguments, &metrics);
+
+        let tls = tls::get_tls_config(&arguments);
+        let startup = check::Startup {
+            database: &database,
+            load_duration,
+            named_databases: &named_databases,
+            tls: tls.as_ref(),
+        };
+        check::report(&arguments, targets, startup);
+    }
+
+    metrics::spawn_rate_sampler(&metrics);
+
+    // Restore counters from the previous run, saving them in the background
+    let state = sink::spawn_state_writer(&arguments, &metrics);
+
+    // Stream geolocated addresses to `/events` until SIGINT or SIGTERM
+    let shutdown = shutdown::Shutdown::listen();
+    let events = events::get_events(&arguments, shutdown.clone());
+
+    // Follow log files in the background
+    let log_parser = ingest::get_log_parser(&arguments);
+    let shared_database =
+        reload::SharedDatabase::new(None, database.clone(), arguments.lookup_cache_size);
+    let databases = reload::Databases::new(
+        shared_database.clone(),
+        named_databases,
+        arguments.lookup_cache_size,
+    );
+    let pipeline = ingest::Pipeline::new(shared_database.clone(), metrics.clone(), log_parser)
+        .with_statsd(sink::get_statsd_client(&arguments))
+        .with_events(events.clone());
+    if let Some(paths) = &arguments.log_paths {
+        // Safety: `arguments::get_config()` implements default values
+        let interval = Duration::from_millis(arguments.log_poll_interval.unwrap());
+        ingest::tail::spawn_log_followers(paths, interval, &pipeline);
+    }
+
+    // Receive syslog messages in the background
+    ingest::spawn_syslog_listeners(&arguments, &pipeline).await;
+
+    // Collect flows in the background
+    ingest::spawn_flow_collectors(&arguments, &pipeline).await;
+
+    // Capture packets in the background
+    ingest::spawn_packet_capture(&arguments, &pipeline);
+
+    // Follow the journal in the background
+    ingest::spawn_journal_reader(&arguments, &pipeline);
+
+    // Answer DNS queries in the background
+    dns::spawn_dns_server(&arguments, shared_database.clone(), metrics.clone()).await;
+
+    // Serve gRPC lookups in the background, sharing the cap on connections with the HTTP API
+    let connections = limit::get_connection_limit(&arguments, &metrics);
+    api::spawn_grpc_server(
+        &arguments,
+        databases.clone(),
+        metrics.clone(),
+        connections.clone(),
+    )
+    .await;
+
+    // Push metrics in the background
+    sink::spawn_remote_writer(&arguments, &metrics);
+    sink::spawn_influxdb_writer(&arguments, &metrics);
+    let pushgateway = sink::spawn_pushgateway_pusher(&arguments, &metrics);
+    let textfile = sink::spawn_textfile_writer(&arguments, &metrics);
+    #[cfg(feature = "otlp")]
+    let otlp = sink::spawn_otlp_exporter(&arguments, &metrics);
+    #[cfg(not(feature = "otlp"))]
+    sink::spawn_otlp_exporter(&arguments, &metrics);
 
     // Construct routes
-    let routes = api::get_routes(maps);
+    // Check readiness against the database as loaded
+    let max_age = arguments
+        .readiness_max_age
+        .map(|hours| Duration::from_secs(hours * 60 * 60));
+    let readiness = health::Readiness::new(&database, database_timestamp, max_age);
+
+    let reloader = reload::Reloader::new(
+        arguments.clone(),
+        database_timestamp,
+        databases.clone(),
+        metrics.clone(),
+        readiness.clone(),
+    );
+
+    // Keep downloaded databases up to date in the background
+    update::spawn_maxmind_updater(&arguments, reloader.clone());
+
+    // Count Tor relays by country in the background
+    tor::spawn_relay_counter(&arguments, shared_database, metrics.clone());
+
+    let routes = api::get_routes(
+        &arguments,
+        databases,
+        metrics.clone(),
+        events,
+        readiness,
+        reloader.clone(),
+    );
+
+    // Serve routes, over TLS if configured, until SIGINT or SIGTERM
+    let tls = tls::get_tls_config(&arguments);
+    // Safety: `arguments::get_config()` implements default values
+    let servers = match arguments.serve_http.unwrap() {
+        true => serve!(routes, tls, connections, targets, shutdown),
+        false => Vec::new(),
+    };
+    systemd::notify_ready();
+
+    // Reload the configuration and databases on SIGHUP
+    // Safety: `arguments::get_config()` implements default values
+    let timeout = Duration::from_secs(arguments.shutdown_timeout.unwrap());
+    reload::spawn_reloader(reloader);
+
+    shutdown::drain(servers, shutdown, timeout).await;
 
-    // Serve routes
-    serve!(routes, ipv4_target, ipv6_target);
+    // Push the final value of every metric
+    if let Some(pushgateway) = pushgateway {
+        sink::push_final(metrics.clone(), move |metrics| pushgateway.push(metrics)).await;
+    }
+    if let Some(textfile) = textfile {
+        sink::push_final(metrics.clone(), move |metrics| textfile.write(metrics)).await;
+    }
+    if let Some(state) = state {
+        sink::push_final(metrics.clone(), move |metrics| state.write(metrics)).await;
+    }
+    #[cfg(feature = "otlp")]
+    if let Some(otlp) = otlp {
+        sink::push_final(metrics, move |metrics| otlp.export(metrics)).await;
+    }
 }
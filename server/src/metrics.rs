@@ -0,0 +1,1793 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! A minimal registry of counters, gauges, and histograms, rendered in the [Prometheus text exposition
+//! format](https://prometheus.io/docs/instrumenting/exposition_formats/).
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    fmt::{Display, Write},
+    net::IpAddr,
+    ops::{AddAssign, Sub},
+    process,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use clap::ValueEnum;
+use ip_geo::{
+    asn::AsnDatabase,
+    country_list::{get_countries, Country},
+    privacy::Truncation,
+    tor::RelayStats,
+    GeoDatabase,
+};
+
+use crate::arguments::Arguments;
+use serde::Deserialize;
+
+/// The content type of the Prometheus text exposition format.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// The content type of the OpenMetrics text format.
+pub const OPENMETRICS_CONTENT_TYPE: &str =
+    "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Represents the format that metrics are rendered in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// The Prometheus text exposition format.
+    Text,
+    /// The OpenMetrics text format, which supports exemplars.
+    OpenMetrics,
+}
+
+impl Format {
+    /// Choose a format from the value of an `Accept` header, preferring OpenMetrics if the client
+    /// accepts it.
+    pub fn negotiate(accept: Option<&str>) -> Self {
+        let accepts_openmetrics = accept.is_some_and(|accept| {
+            accept.split(',').any(|range| {
+                let mut parameters = range.split(';').map(str::trim);
+                let media_type = parameters.next().unwrap_or_default();
+
+                // Ex. "application/openmetrics-text;version=1.0.0;q=0.5"
+                media_type.eq_ignore_ascii_case("application/openmetrics-text")
+                    && !parameters.any(|parameter| {
+                        parameter
+                            .strip_prefix("q=")
+                            .and_then(|quality| quality.parse::<f64>().ok())
+                            .is_some_and(|quality| quality <= 0.0)
+                    })
+            })
+        });
+
+        match accepts_openmetrics {
+            true => Self::OpenMetrics,
+            false => Self::Text,
+        }
+    }
+
+    /// Return the content type of this format.
+    pub const fn content_type(self) -> &'static str {
+        match self {
+            Self::Text => CONTENT_TYPE,
+            Self::OpenMetrics => OPENMETRICS_CONTENT_TYPE,
+        }
+    }
+}
+
+/// The prefix of every metric name, unless configured otherwise.
+pub const DEFAULT_PREFIX: &str = "ip_geo_";
+
+/// A set of label names and values, ex. `[("country_code", "BE")]`.
+pub type Labels = Vec<(&'static str, Arc<str>)>;
+
+/// Represents the granularity of per-country metrics.
+#[derive(ValueEnum, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Aggregation {
+    /// Label metrics by country.
+    Country,
+    /// Label metrics by continent, reducing cardinality.
+    Continent,
+}
+
+impl Aggregation {
+    /// Return the labels identifying `country` at this granularity.
+    pub fn labels(&self, country: &Country) -> Labels {
+        match self {
+            Self::Country => {
+                let mut labels = vec![
+                    ("country_code", country.code.clone()),
+                    ("country_name", country.name.clone()),
+                ];
+
+                if let Some(time_zone) = &country.time_zone {
+                    labels.push(("timezone", time_zone.clone()));
+                }
+
+                labels
+            }
+            Self::Continent => match country.continent() {
+                Some(continent) => vec![
+                    ("continent_code", continent.code().into()),
+                    ("continent_name", continent.name().into()),
+                ],
+                None => vec![
+                    ("continent_code", country.code.clone()),
+                    ("continent_name", country.name.clone()),
+                ],
+            },
+        }
+    }
+}
+
+/// The names of the labels that exported series already have, or that the exposition formats
+/// reserve, which static labels cannot reuse.
+const RESERVED_LABEL_NAMES: &[&str] = &[
+    "as_org",
+    "asn",
+    "continent_code",
+    "continent_name",
+    "country_code",
+    "country_name",
+    "database",
+    "direction",
+    "family",
+    "geohash",
+    "ip_prefix",
+    "latitude",
+    "le",
+    "longitude",
+    "quantile",
+    "reason",
+    "result",
+    "status",
+    "timezone",
+];
+
+/// A label applied to every exported series, ex. `instance_role="edge"`.
+///
+/// Parsed from `name=value`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(try_from = "String")]
+pub struct StaticLabel {
+    name: Box<str>,
+    value: Box<str>,
+}
+
+impl FromStr for StaticLabel {
+    type Err = String;
+
+    fn from_str(label: &str) -> Result<Self, Self::Err> {
+        let Some((name, value)) = label.split_once('=') else {
+            return Err(format!("expected `name=value`, found `{label}`"));
+        };
+
+        // Label names cannot contain colons, unlike metric names
+        if !is_valid_name(name) || name.contains(':') || name.starts_with("__") {
+            return Err(format!("`{name}` is not a valid label name"));
+        }
+        if RESERVED_LABEL_NAMES.contains(&name) {
+            return Err(format!("`{name}` is already a label of exported series"));
+        }
+
+        Ok(Self {
+            name: name.into(),
+            value: value.into(),
+        })
+    }
+}
+
+impl StaticLabel {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+/// Return the first name given to more than one of `labels`, if any.
+pub fn find_duplicate_label(labels: &[StaticLabel]) -> Option<&str> {
+    let mut names = BTreeSet::new();
+
+    labels
+        .iter()
+        .map(StaticLabel::name)
+        .find(|name| !names.insert(*name))
+}
+
+impl TryFrom<String> for StaticLabel {
+    type Error = String;
+
+    fn try_from(label: String) -> Result<Self, Self::Error> {
+        label.parse()
+    }
+}
+
+/// Return whether `name` is a valid metric name (or prefix of one), per the Prometheus data
+/// model: `[a-zA-Z_:][a-zA-Z0-9_:]*`.
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == ':')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+}
+
+/// A family of counters sharing a name, distinguished by their labels.
+#[derive(Debug)]
+pub struct CounterFamily {
+    name: &'static str,
+    help: &'static str,
+    values: Mutex<BTreeMap<Labels, Counter>>,
+}
+
+/// A single counter of a `CounterFamily`.
+#[derive(Debug, Default)]
+struct Counter {
+    value: u64,
+    /// The labels and value of the latest exemplar, if any, ex. `([("ip_prefix", "1.2.3.0/24")], 1)`.
+    exemplar: Option<(Labels, u64)>,
+}
+
+impl CounterFamily {
+    /// Create a new, empty `CounterFamily`.
+    pub const fn new(name: &'static str, help: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            values: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Increment the counter with the given labels by one.
+    pub fn inc(&self, labels: Labels) {
+        self.inc_by(labels, 1);
+    }
+
+    /// Increment the counter with the given labels by `value`.
+    pub fn inc_by(&self, labels: Labels, value: u64) {
+        let mut values = self.values.lock().expect("an unpoisoned lock");
+        values.entry(labels).or_default().value += value;
+    }
+
+    /// Increment the counter with the given labels by one, replacing its exemplar with
+    /// `exemplar`.
+    pub fn inc_with_exemplar(&self, labels: Labels, exemplar: Labels) {
+        let mut values = self.values.lock().expect("an unpoisoned lock");
+        let counter = values.entry(labels).or_default();
+        counter.value += 1;
+        counter.exemplar = Some((exemplar, 1));
+    }
+
+    /// Add the value of every counter to the total of its country, if it is labelled with one.
+    fn add_country_totals(&self, totals: &mut HashMap<Arc<str>, u64>) {
+        let values = self.values.lock().expect("an unpoisoned lock");
+
+        for (labels, counter) in values.iter() {
+            if let Some(code) = country_code(labels) {
+                *totals.entry(code.clone()).or_default() += counter.value;
+            }
+        }
+    }
+
+    /// Return the name of the family, ex. `ip_geo_lookups_total`.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Remove every counter.
+    pub fn clear(&self) {
+        self.values.lock().expect("an unpoisoned lock").clear();
+    }
+
+    /// Remove every counter labelled with the country `code`, returning how many were removed.
+    pub fn remove_country(&self, code: &str) -> usize {
+        let mut values = self.values.lock().expect("an unpoisoned lock");
+        let before = values.len();
+        values.retain(|labels, _| country_code(labels).map(|value| &**value) != Some(code));
+
+        before - values.len()
+    }
+
+    /// Return the sum of every counter.
+    #[cfg(feature = "grpc")]
+    pub fn total(&self) -> u64 {
+        let values = self.values.lock().expect("an unpoisoned lock");
+        values.values().map(|counter| counter.value).sum()
+    }
+
+    /// Return the current value of every counter.
+    pub fn snapshot(&self) -> BTreeMap<Labels, u64> {
+        let values = self.values.lock().expect("an unpoisoned lock");
+
+        values
+            .iter()
+            .map(|(labels, counter)| (labels.clone(), counter.value))
+            .collect()
+    }
+
+    /// Append the family to `output` in the text exposition format.
+    fn render(&self, output: &mut Exposition) {
+        write_header(output, self.name, self.help, Kind::Counter);
+
+        let ranking = output.top_countries.clone();
+        let entered = ranking
+            .as_ref()
+            .and_then(|ranking| ranking.entered.get(self.name));
+
+        let values = self.values.lock().expect("an unpoisoned lock");
+        let samples = values.iter().map(|(labels, counter)| {
+            let entered = entered.and_then(|entered| entered.get(labels)).copied();
+
+            (labels, counter.value, counter.exemplar.as_ref(), entered)
+        });
+
+        write_country_samples(output, self.name, samples);
+    }
+
+    /// Append the rate of every counter per minute to `output` as a gauge, since `baseline` was
+    /// taken `elapsed` ago, ex. `ip_geo_lookups_per_minute` for `ip_geo_lookups_total`.
+    fn render_rate(
+        &self,
+        output: &mut Exposition,
+        baseline: &BTreeMap<Labels, u64>,
+        elapsed: Duration,
+    ) {
+        let name = format!("{}_per_minute", self.name.trim_end_matches("_total"));
+        let help = format!(
+            "The rate of {} per minute, over a sliding window.",
+            output.rename(self.name)
+        );
+        write_header(output, &name, &help, Kind::Gauge);
+
+        let minutes = elapsed.as_secs_f64() / 60.0;
+        let values = self.values.lock().expect("an unpoisoned lock");
+        let samples = values.iter().map(|(labels, counter)| {
+            let previous = baseline.get(labels).copied().unwrap_or_default();
+            let rate = counter.value.saturating_sub(previous) as f64 / minutes;
+
+            (labels, rate, None, None)
+        });
+
+        write_country_samples(output, &name, samples);
+    }
+}
+
+/// Append `samples` of the metric `name` to `output`.
+///
+/// If only the top countries are exported, samples of any other country are summed into samples
+/// labelled `country_code="other"`, without exemplars. Samples of a top country may also carry the
+/// value they had when it entered the top, which stays summed into `other` and is subtracted from
+/// the sample.
+fn write_country_samples<'l, V: AddAssign + Sub<Output = V> + Copy + Default + Display>(
+    output: &mut Exposition,
+    name: &str,
+    samples: impl Iterator<Item = (&'l Labels, V, Option<&'l (Labels, u64)>, Option<V>)>,
+) {
+    let mut other = BTreeMap::<Labels, V>::new();
+
+    for (labels, mut value, exemplar, entered) in samples {
+        if let (Some(ranking), Some(code)) = (&output.top_countries, country_code(labels)) {
+            if !ranking.codes.contains(code) {
+                *other.entry(other_labels(labels)).or_default() += value;
+                continue;
+            }
+
+            if let Some(entered) = entered {
+                *other.entry(other_labels(labels)).or_default() += entered;
+                value = value - entered;
+            }
+        }
+
+        write_sample_with_exemplar(output, name, labels, value, exemplar);
+    }
+
+    for (labels, value) in other {
+        write_sample(output, name, &labels, value);
+    }
+}
+
+/// Return the value of the `country_code` label, if there is one.
+fn country_code(labels: &Labels) -> Option<&Arc<str>> {
+    labels
+        .iter()
+        .find(|(name, _)| *name == "country_code")
+        .map(|(_, code)| code)
+}
+
+/// Return `labels` with the labels identifying its country replaced by those of the `other`
+/// country, ex. `[("country_code", "BE"), ("country_name", "Belgium"), ("status", "2xx")]` becomes
+/// `[("country_code", "other"), ("country_name", "Other"), ("status", "2xx")]`.
+fn other_labels(labels: &Labels) -> Labels {
+    labels
+        .iter()
+        .filter_map(|(name, value)| match *name {
+            "country_code" => Some((*name, "other".into())),
+            "country_name" => Some((*name, "Other".into())),
+            "timezone" | "geohash" | "latitude" | "longitude" => None,
+            _ => Some((*name, value.clone())),
+        })
+        .collect()
+}
+
+/// The countries that have had the most counts, which are exported individually, while the counts
+/// of every other country are collapsed into `country_code="other"`.
+#[derive(Debug)]
+struct TopCountries {
+    /// How many countries to export individually.
+    count: usize,
+    /// How often to rank the countries again.
+    interval: Duration,
+    /// The latest ranking and when it was made, if it is still up to date.
+    current: Mutex<Option<(Arc<Ranking>, Option<Instant>)>>,
+}
+
+/// A ranking of [`TopCountries`].
+///
+/// Counts never move out of `other`, so that its counters never decrease: a counter of a country
+/// that enters the top keeps its value at that time summed into `other`, and only the counts since
+/// are exported under its own label, restarting from zero like any counter reset. When the country
+/// leaves the top, its whole value is summed into `other` again.
+#[derive(Debug, Default)]
+struct Ranking {
+    /// The codes of the countries in the top.
+    codes: BTreeSet<Arc<str>>,
+    /// The value of every counter of a top country when it entered the top, by family name and
+    /// labels, if not zero.
+    entered: HashMap<&'static str, HashMap<Labels, u64>>,
+}
+
+/// Snapshots of counters, to export their rates over a sliding window.
+#[derive(Debug)]
+struct RateWindow {
+    /// How far back rates are computed over.
+    window: Duration,
+    /// Snapshots within the window, oldest first.
+    samples: Mutex<VecDeque<RateSample>>,
+}
+
+/// A snapshot of the counters whose rates are exported.
+#[derive(Debug)]
+struct RateSample {
+    taken: Instant,
+    /// The values of every rated family, in the order of `Metrics::rated_families`.
+    values: Vec<BTreeMap<Labels, u64>>,
+}
+
+/// How many snapshots are taken per rate window.
+const RATE_SAMPLES_PER_WINDOW: u32 = 12;
+
+/// The length of `geohash` labels. Five characters is roughly 5 km, much finer than the center of a
+/// country needs.
+const GEOHASH_PRECISION: usize = 5;
+
+/// For a given set of arguments, construct the `Metrics` for `database`, which was generated or last
+/// modified at `database_timestamp` and took `load_duration` to load.
+///
+/// If per-ASN metrics are enabled, lookups are also counted by their autonomous system in
+/// `asn_database`.
+///
+/// Exits if the configured prefix is not a valid metric name.
+pub fn get_metrics(
+    arguments: &Arguments,
+    database: &GeoDatabase,
+    asn_database: Option<Arc<AsnDatabase>>,
+    database_timestamp: Option<u64>,
+    load_duration: Duration,
+) -> Arc<Metrics> {
+    // Safety: `arguments::get_config()` implements default values
+    let prefix = arguments.metrics_prefix.clone().unwrap();
+    if !prefix.is_empty() && !is_valid_name(&prefix) {
+        eprintln!("`{prefix}` is not a valid metric name prefix");
+        process::exit(1);
+    }
+
+    let aggregation = arguments.metrics_aggregation.unwrap();
+    if arguments.metrics_top_countries.is_some() && aggregation != Aggregation::Country {
+        eprintln!("Exporting only the top countries requires aggregating metrics by country");
+        process::exit(1);
+    }
+
+    let metrics = Metrics::new(aggregation, database, database_timestamp, load_duration)
+        .with_prefix(prefix)
+        .with_static_labels(arguments.metrics_labels.clone().unwrap().into())
+        .with_geohash(arguments.metrics_geohash.unwrap())
+        .with_coordinates(arguments.metrics_coordinates.unwrap())
+        .with_language(arguments.lang.clone())
+        .with_country_info(arguments.metrics_country_info.unwrap())
+        .with_asn_database(asn_database.filter(|_| arguments.metrics_asn.unwrap()))
+        .with_lookup_cache(arguments.lookup_cache_size.is_some())
+        .with_rate_window(
+            arguments
+                .metrics_rate_window
+                .map(|window| Duration::from_secs(window.get())),
+        )
+        .with_top_countries(arguments.metrics_top_countries.map(|count| {
+            (
+                count,
+                Duration::from_secs(arguments.metrics_top_countries_interval.unwrap()),
+            )
+        }))
+        .with_exemplars(arguments.metrics_exemplars.unwrap().then(|| {
+            Truncation::new(
+                arguments.truncate_ipv4_prefix.unwrap(),
+                arguments.truncate_ipv6_prefix.unwrap(),
+            )
+        }));
+
+    Arc::new(metrics)
+}
+
+/// Take snapshots of counts for windowed rates in the background, if they are exported.
+pub fn spawn_rate_sampler(metrics: &Arc<Metrics>) {
+    let Some(rates) = &metrics.rates else {
+        return;
+    };
+
+    let period = (rates.window / RATE_SAMPLES_PER_WINDOW).max(Duration::from_secs(1));
+    let metrics = metrics.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+
+        loop {
+            interval.tick().await;
+            metrics.sample_rates();
+        }
+    });
+}
+
+/// The upper bounds of the buckets of lookup durations, in seconds.
+const LOOKUP_DURATION_BUCKETS: &[f64] = &[
+    0.000_000_1,
+    0.000_000_25,
+    0.000_000_5,
+    0.000_001,
+    0.000_002_5,
+    0.000_005,
+    0.000_01,
+    0.000_025,
+    0.000_05,
+    0.000_1,
+    0.001,
+];
+
+/// A histogram, counting observations into buckets by their upper bounds.
+#[derive(Debug)]
+pub struct Histogram {
+    name: &'static str,
+    help: &'static str,
+    buckets: &'static [f64],
+    state: Mutex<HistogramState>,
+}
+
+#[derive(Debug, Default)]
+struct HistogramState {
+    /// The number of observations in each bucket (and not the buckets below it), followed by the
+    /// number above every bucket.
+    counts: Vec<u64>,
+    sum: f64,
+}
+
+impl Histogram {
+    /// Create a new, empty `Histogram`, with buckets at the given ascending upper bounds.
+    pub fn new(name: &'static str, help: &'static str, buckets: &'static [f64]) -> Self {
+        Self {
+            name,
+            help,
+            buckets,
+            state: Mutex::new(HistogramState {
+                counts: vec![0; buckets.len() + 1],
+                sum: 0.0,
+            }),
+        }
+    }
+
+    /// Record a single observation.
+    pub fn observe(&self, value: f64) {
+        let bucket = self.buckets.partition_point(|&bound| bound < value);
+
+        let mut state = self.state.lock().expect("an unpoisoned lock");
+        state.counts[bucket] += 1;
+        state.sum += value;
+    }
+
+    /// Remove every observation.
+    pub fn clear(&self) {
+        let mut state = self.state.lock().expect("an unpoisoned lock");
+        state.counts.fill(0);
+        state.sum = 0.0;
+    }
+
+    fn render(&self, output: &mut Exposition) {
+        write_header(output, self.name, self.help, Kind::Histogram);
+
+        let state = self.state.lock().expect("an unpoisoned lock");
+        let bucket_name = format!("{}_bucket", self.name);
+        let mut cumulative = 0;
+
+        let bounds = self.buckets.iter().map(f64::to_string);
+        for (bound, count) in bounds.chain(["+Inf".into()]).zip(&state.counts) {
+            cumulative += count;
+            write_sample(output, &bucket_name, &[("le", bound.into())], cumulative);
+        }
+
+        write_sample(output, &format!("{}_sum", self.name), &[], state.sum);
+        write_sample(output, &format!("{}_count", self.name), &[], cumulative);
+    }
+}
+
+/// The metrics exported by the server.
+#[derive(Debug)]
+pub struct Metrics {
+    aggregation: Aggregation,
+    /// The prefix of every metric name, in place of [`DEFAULT_PREFIX`].
+    prefix: Box<str>,
+    /// Labels applied to every series.
+    static_labels: Box<[StaticLabel]>,
+    /// Whether to label per-country metrics with the geohash of the country.
+    geohash: bool,
+    /// Whether to label per-country metrics with the coordinates of the country.
+    coordinates: bool,
+    /// How to truncate the addresses of lookups into exemplars, if they are attached.
+    exemplars: Option<Truncation>,
+    /// The labels of `ip_geo_country_info` for every known country, if it is exported.
+    country_info: Option<Vec<Labels>>,
+    /// The language to name countries in, if not English.
+    language: Option<Box<str>>,
+    /// The database to count lookups by autonomous system with, if enabled.
+    asn_database: Option<Arc<AsnDatabase>>,
+    /// Whether lookups are cached, and so whether cache hits and misses are exported.
+    lookup_cache: bool,
+    /// The countries exported individually, if limited.
+    top_countries: Option<TopCountries>,
+    /// The snapshots to compute windowed rates from, if they are exported.
+    rates: Option<RateWindow>,
+    /// What is known about the currently loaded database.
+    database: Mutex<DatabaseInfo>,
+    /// The number of times the database was reloaded, by result.
+    database_reloads: CounterFamily,
+    /// The relays in the latest Tor network consensus, by country code, if one was read.
+    tor_relays: Mutex<Option<BTreeMap<Arc<str>, RelayStats>>>,
+    lookups: CounterFamily,
+    misses: CounterFamily,
+    asn_lookups: CounterFamily,
+    cache_hits: CounterFamily,
+    cache_misses: CounterFamily,
+    /// Lookup requests rejected by rate or in-flight limits, or connections held back by the
+    /// connection limit, by reason.
+    requests_limited: CounterFamily,
+    lookup_duration: Histogram,
+    /// Log lines with a geolocated address, by location.
+    pub log_lines: CounterFamily,
+    /// Log lines without an address, or with an address with no associated country.
+    pub log_lines_dropped: CounterFamily,
+    /// Responses in access logs, by location and status class (ex. `2xx`).
+    pub log_responses: CounterFamily,
+    /// The size of responses in access logs, by location.
+    pub log_response_bytes: CounterFamily,
+    /// Syslog messages with a geolocated address, by location.
+    pub syslog_messages: CounterFamily,
+    /// Syslog messages without an address, or with an address with no associated country.
+    pub syslog_messages_dropped: CounterFamily,
+    /// Bytes in collected flows, by location and direction.
+    pub flow_bytes: CounterFamily,
+    /// Packets in collected flows, by location and direction.
+    pub flow_packets: CounterFamily,
+    /// Flow export datagrams that could not be decoded.
+    pub flow_datagrams_invalid: CounterFamily,
+    /// Bytes sent from (`inbound`) or to (`outbound`) each location, by every ingestion source
+    /// with byte counts.
+    pub bytes: CounterFamily,
+    /// Bytes in captured packets, by the location of their remote endpoint and direction.
+    #[cfg(feature = "pcap")]
+    pub capture_bytes: CounterFamily,
+    /// Captured packets, by the location of their remote endpoint and direction.
+    #[cfg(feature = "pcap")]
+    pub capture_packets: CounterFamily,
+    /// Failed SSH authentication attempts in the journal, by location.
+    #[cfg(feature = "journald")]
+    pub ssh_failures: CounterFamily,
+    /// Failed SSH authentication attempts without an address with a location.
+    #[cfg(feature = "journald")]
+    pub ssh_failures_dropped: CounterFamily,
+}
+
+impl Metrics {
+    /// Create a new `Metrics`, labelling per-country metrics according to `aggregation`.
+    ///
+    /// `database` was generated or last modified at `database_timestamp`, and took
+    /// `load_duration` to load.
+    pub fn new(
+        aggregation: Aggregation,
+        database: &GeoDatabase,
+        database_timestamp: Option<u64>,
+        load_duration: Duration,
+    ) -> Self {
+        Self {
+            aggregation,
+            prefix: DEFAULT_PREFIX.into(),
+            static_labels: Box::new([]),
+            geohash: false,
+            coordinates: false,
+            exemplars: None,
+            country_info: None,
+            language: None,
+            asn_database: None,
+            lookup_cache: false,
+            top_countries: None,
+            rates: None,
+            database: Mutex::new(DatabaseInfo::new(
+                database,
+                database_timestamp,
+                load_duration,
+            )),
+            database_reloads: CounterFamily::new(
+                "ip_geo_database_reloads_total",
+                "The number of times the database was reloaded, by result.",
+            ),
+            tor_relays: Mutex::new(None),
+            lookups: CounterFamily::new(
+                "ip_geo_lookups_total",
+                "The number of successful lookups, by location.",
+            ),
+            misses: CounterFamily::new(
+                "ip_geo_lookup_misses_total",
+                "The number of lookups that found no location.",
+            ),
+            asn_lookups: CounterFamily::new(
+                "ip_geo_asn_lookups_total",
+                "The number of lookups, by autonomous system.",
+            ),
+            cache_hits: CounterFamily::new(
+                "ip_geo_cache_hits_total",
+                "The number of lookups answered from the lookup cache.",
+            ),
+            cache_misses: CounterFamily::new(
+                "ip_geo_cache_misses_total",
+                "The number of lookups not found in the lookup cache.",
+            ),
+            requests_limited: CounterFamily::new(
+                "ip_geo_requests_limited_total",
+                "The number of requests rejected by a rate or in-flight limit or an access rule, or connections held back by a connection limit, by reason.",
+            ),
+            lookup_duration: Histogram::new(
+                "ip_geo_lookup_duration_seconds",
+                "How long lookups took to search the database.",
+                LOOKUP_DURATION_BUCKETS,
+            ),
+            log_lines: CounterFamily::new(
+                "ip_geo_log_lines_total",
+                "The number of followed log lines, by the location of their client address.",
+            ),
+            log_lines_dropped: CounterFamily::new(
+                "ip_geo_log_lines_dropped_total",
+                "The number of followed log lines without a client address with a location.",
+            ),
+            log_responses: CounterFamily::new(
+                "ip_geo_log_responses_total",
+                "The number of responses in followed access logs, by location and status class.",
+            ),
+            log_response_bytes: CounterFamily::new(
+                "ip_geo_log_response_bytes_total",
+                "The size of responses in followed access logs in bytes, by location.",
+            ),
+            syslog_messages: CounterFamily::new(
+                "ip_geo_syslog_messages_total",
+                "The number of received syslog messages, by the location of their first address.",
+            ),
+            syslog_messages_dropped: CounterFamily::new(
+                "ip_geo_syslog_messages_dropped_total",
+                "The number of received syslog messages without an address with a location.",
+            ),
+            flow_bytes: CounterFamily::new(
+                "ip_geo_flow_bytes_total",
+                "The number of bytes in collected flows, by location and direction.",
+            ),
+            flow_packets: CounterFamily::new(
+                "ip_geo_flow_packets_total",
+                "The number of packets in collected flows, by location and direction.",
+            ),
+            flow_datagrams_invalid: CounterFamily::new(
+                "ip_geo_flow_datagrams_invalid_total",
+                "The number of flow export datagrams that could not be decoded.",
+            ),
+            bytes: CounterFamily::new(
+                "ip_geo_bytes_total",
+                "The number of bytes sent from or to each location in flows, access logs, and captured packets, by direction.",
+            ),
+            #[cfg(feature = "pcap")]
+            capture_bytes: CounterFamily::new(
+                "ip_geo_capture_bytes_total",
+                "The number of bytes in captured packets, by remote location and direction.",
+            ),
+            #[cfg(feature = "pcap")]
+            capture_packets: CounterFamily::new(
+                "ip_geo_capture_packets_total",
+                "The number of captured packets, by remote location and direction.",
+            ),
+            #[cfg(feature = "journald")]
+            ssh_failures: CounterFamily::new(
+                "ip_geo_ssh_failures_total",
+                "The number of failed SSH authentication attempts, by location.",
+            ),
+            #[cfg(feature = "journald")]
+            ssh_failures_dropped: CounterFamily::new(
+                "ip_geo_ssh_failures_dropped_total",
+                "The number of failed SSH authentication attempts without an address with a location.",
+            ),
+        }
+    }
+
+    /// Name every metric with `prefix` in place of [`DEFAULT_PREFIX`], ex. `edge_ip_geo_` for
+    /// `edge_ip_geo_lookups_total`.
+    pub fn with_prefix(mut self, prefix: Box<str>) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Apply `static_labels` to every series, before any other labels.
+    pub fn with_static_labels(mut self, static_labels: Box<[StaticLabel]>) -> Self {
+        self.static_labels = static_labels;
+        self
+    }
+
+    /// Label per-country metrics with the geohash of the center of the country, for Grafana
+    /// Geomap panels.
+    ///
+    /// Has no effect when aggregating by continent.
+    pub fn with_geohash(mut self, geohash: bool) -> Self {
+        self.geohash = geohash;
+        self
+    }
+
+    /// Label per-country metrics with the `latitude` and `longitude` of the center of the country.
+    ///
+    /// Has no effect when aggregating by continent.
+    pub fn with_coordinates(mut self, coordinates: bool) -> Self {
+        self.coordinates = coordinates;
+        self
+    }
+
+    /// Name countries in `language` in `ip_geo_country_info`, rather than in English.
+    ///
+    /// Disabled with `None`. Must be set before [`Self::with_country_info`].
+    pub fn with_language(mut self, language: Option<Box<str>>) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Export `ip_geo_country_info`, which has a series for every known country labelled with its
+    /// code, name, and coordinates, so that PromQL can join on `country_code` to place countries
+    /// on a map.
+    ///
+    /// Includes geohashes if [`Self::with_geohash`] was enabled first, and localized names if
+    /// [`Self::with_language`] was.
+    pub fn with_country_info(mut self, country_info: bool) -> Self {
+        self.country_info = country_info.then(|| {
+            get_countries()
+                .into_values()
+                .map(|country| match self.language.as_deref() {
+                    Some(language) => country.localized(language),
+                    None => country,
+                })
+                .map(|country| {
+                    let (longitude, latitude) = country.coordinates;
+                    let mut labels = vec![
+                        ("country_code", country.code.clone()),
+                        ("country_name", country.name.clone()),
+                        ("latitude", latitude.to_string().into()),
+                        ("longitude", longitude.to_string().into()),
+                    ];
+
+                    if self.geohash {
+                        labels.push(("geohash", country.geohash(GEOHASH_PRECISION).into()));
+                    }
+
+                    labels
+                })
+                .collect()
+        });
+        self
+    }
+
+    /// Attach an exemplar to lookup counters, holding the address of the latest lookup truncated
+    /// with `truncation`. Exemplars are only rendered in the OpenMetrics format.
+    ///
+    /// Disabled with `None`.
+    pub fn with_exemplars(mut self, truncation: Option<Truncation>) -> Self {
+        self.exemplars = truncation;
+        self
+    }
+
+    /// Count lookups by the autonomous system in `asn_database` that announces the address, in
+    /// addition to by location.
+    ///
+    /// Disabled with `None`, because it adds a series for every autonomous system seen.
+    pub fn with_asn_database(mut self, asn_database: Option<Arc<AsnDatabase>>) -> Self {
+        self.asn_database = asn_database;
+        self
+    }
+
+    /// Export the hits and misses of the lookup cache.
+    pub fn with_lookup_cache(mut self, lookup_cache: bool) -> Self {
+        self.lookup_cache = lookup_cache;
+        self
+    }
+
+    /// Only export individual series for the `count` countries with the most counts, ranked again
+    /// every `interval`, collapsing the rest into `country_code="other"`.
+    ///
+    /// Countries are ranked by their `country_code` label, so this does nothing when aggregating by
+    /// continent.
+    ///
+    /// Disabled with `None`.
+    pub fn with_top_countries(mut self, top_countries: Option<(usize, Duration)>) -> Self {
+        self.top_countries = top_countries.map(|(count, interval)| TopCountries {
+            count,
+            interval,
+            current: Mutex::new(None),
+        });
+        self
+    }
+
+    /// Return the codes of the countries to export individually, ranking them again if they are
+    /// out of date, or `None` if every country is exported.
+    ///
+    /// Countries are ranked by their lookups and ingested events, not bytes.
+    fn top_countries(&self) -> Option<Arc<Ranking>> {
+        let top_countries = self.top_countries.as_ref()?;
+        let mut current = top_countries.current.lock().expect("an unpoisoned lock");
+
+        if let Some((ranking, Some(ranked))) = &*current {
+            if ranked.elapsed() < top_countries.interval {
+                return Some(ranking.clone());
+            }
+        }
+        let previous = current.take().map(|(ranking, _)| ranking);
+
+        let mut totals = HashMap::new();
+        self.lookups.add_country_totals(&mut totals);
+        self.log_lines.add_country_totals(&mut totals);
+        self.syslog_messages.add_country_totals(&mut totals);
+        self.flow_packets.add_country_totals(&mut totals);
+        #[cfg(feature = "pcap")]
+        self.capture_packets.add_country_totals(&mut totals);
+        #[cfg(feature = "journald")]
+        self.ssh_failures.add_country_totals(&mut totals);
+
+        let mut totals = totals.into_iter().collect::<Vec<_>>();
+        totals.sort_unstable_by(|(a_code, a), (b_code, b)| b.cmp(a).then(a_code.cmp(b_code)));
+
+        let codes = totals
+            .into_iter()
+            .take(top_countries.count)
+            .map(|(code, _)| code)
+            .collect::<BTreeSet<_>>();
+
+        // Before the first ranking, nothing has been summed into `other` yet
+        let mut entered = HashMap::new();
+        if let Some(previous) = previous {
+            for family in self.counter_families() {
+                let previous_entered = previous.entered.get(family.name());
+                let mut family_entered = HashMap::new();
+
+                for (labels, value) in family.snapshot() {
+                    let Some(code) = country_code(&labels).filter(|code| codes.contains(*code))
+                    else {
+                        continue;
+                    };
+
+                    let value = if previous.codes.contains(code) {
+                        previous_entered
+                            .and_then(|entered| entered.get(&labels))
+                            .copied()
+                            .unwrap_or_default()
+                    } else {
+                        value
+                    };
+                    if value != 0 {
+                        family_entered.insert(labels, value);
+                    }
+                }
+
+                if !family_entered.is_empty() {
+                    entered.insert(family.name(), family_entered);
+                }
+            }
+        }
+
+        let ranking = Arc::new(Ranking { codes, entered });
+        *current = Some((ranking.clone(), Some(Instant::now())));
+
+        Some(ranking)
+    }
+
+    /// Export the rate per minute of every count by location as a gauge, computed over the last
+    /// `window` from snapshots taken by [`spawn_rate_sampler`].
+    ///
+    /// Disabled with `None`.
+    pub fn with_rate_window(mut self, window: Option<Duration>) -> Self {
+        self.rates = window.map(|window| RateWindow {
+            window,
+            samples: Mutex::new(VecDeque::new()),
+        });
+        self
+    }
+
+    /// Return every family of counters, exported or not.
+    pub fn counter_families(&self) -> Vec<&CounterFamily> {
+        vec![
+            &self.database_reloads,
+            &self.lookups,
+            &self.misses,
+            &self.asn_lookups,
+            &self.cache_hits,
+            &self.cache_misses,
+            &self.requests_limited,
+            &self.log_lines,
+            &self.log_lines_dropped,
+            &self.log_responses,
+            &self.log_response_bytes,
+            &self.syslog_messages,
+            &self.syslog_messages_dropped,
+            &self.flow_bytes,
+            &self.flow_packets,
+            &self.flow_datagrams_invalid,
+            &self.bytes,
+            #[cfg(feature = "pcap")]
+            &self.capture_bytes,
+            #[cfg(feature = "pcap")]
+            &self.capture_packets,
+            #[cfg(feature = "journald")]
+            &self.ssh_failures,
+            #[cfg(feature = "journald")]
+            &self.ssh_failures_dropped,
+        ]
+    }
+
+    /// Return the families of counts by location, whose rates are exported if enabled.
+    fn rated_families(&self) -> Vec<&CounterFamily> {
+        vec![
+            &self.lookups,
+            &self.log_lines,
+            &self.log_responses,
+            &self.syslog_messages,
+            &self.flow_bytes,
+            &self.flow_packets,
+            &self.bytes,
+            #[cfg(feature = "pcap")]
+            &self.capture_bytes,
+            #[cfg(feature = "pcap")]
+            &self.capture_packets,
+            #[cfg(feature = "journald")]
+            &self.ssh_failures,
+        ]
+    }
+
+    /// Take a snapshot of every rated family, discarding snapshots that fell out of the window.
+    fn sample_rates(&self) {
+        let Some(rates) = &self.rates else {
+            return;
+        };
+
+        let sample = RateSample {
+            taken: Instant::now(),
+            values: self
+                .rated_families()
+                .into_iter()
+                .map(CounterFamily::snapshot)
+                .collect(),
+        };
+
+        let now = sample.taken;
+        let mut samples = rates.samples.lock().expect("an unpoisoned lock");
+        samples.push_back(sample);
+
+        while samples
+            .front()
+            .is_some_and(|sample| now.duration_since(sample.taken) > rates.window)
+        {
+            samples.pop_front();
+        }
+    }
+
+    /// Return the labels identifying `country`, according to the configured aggregation.
+    pub fn labels(&self, country: &Country) -> Labels {
+        let mut labels = self.aggregation.labels(country);
+
+        if self.aggregation != Aggregation::Country {
+            return labels;
+        }
+
+        if self.geohash {
+            labels.push(("geohash", country.geohash(GEOHASH_PRECISION).into()));
+        }
+
+        if self.coordinates {
+            let (longitude, latitude) = country.coordinates;
+            labels.push(("latitude", latitude.to_string().into()));
+            labels.push(("longitude", longitude.to_string().into()));
+        }
+
+        labels
+    }
+
+    /// Record a successful lookup of `address` in the database named `database`, or the default
+    /// database if `None`.
+    ///
+    /// Lookups in named databases are labelled with their name, ex. `database="tor"`.
+    pub fn record_lookup(&self, address: IpAddr, country: &Country, database: Option<&str>) {
+        let mut labels = self.labels(country);
+        if let Some(database) = database {
+            labels.insert(0, ("database", database.into()));
+        }
+
+        let Some(truncation) = self.exemplars else {
+            self.lookups.inc(labels);
+            return;
+        };
+
+        let prefix_len = match address {
+            IpAddr::V4(_) => truncation.ipv4_prefix,
+            IpAddr::V6(_) => truncation.ipv6_prefix,
+        };
+        let prefix = format!("{}/{prefix_len}", truncation.apply(address));
+
+        self.lookups
+            .inc_with_exemplar(labels, vec![("ip_prefix", prefix.into())]);
+    }
+
+    /// Replace what is known about the database with `database`, which was generated or last
+    /// modified at `database_timestamp` and took `load_duration` to load.
+    pub fn record_database(
+        &self,
+        database: &GeoDatabase,
+        database_timestamp: Option<u64>,
+        load_duration: Duration,
+    ) {
+        *self.database.lock().expect("an unpoisoned lock") =
+            DatabaseInfo::new(database, database_timestamp, load_duration);
+    }
+
+    /// Record the relays in the latest Tor network consensus, by country code.
+    pub fn record_tor_relays(&self, relays: BTreeMap<Arc<str>, RelayStats>) {
+        *self.tor_relays.lock().expect("an unpoisoned lock") = Some(relays);
+    }
+
+    /// Record an attempt to reload the database.
+    pub fn record_reload(&self, succeeded: bool) {
+        let result = match succeeded {
+            true => "success",
+            false => "failure",
+        };
+
+        self.database_reloads.inc(vec![("result", result.into())]);
+    }
+
+    /// Record a lookup of `address` by its autonomous system, if enabled and known.
+    pub fn record_asn(&self, address: IpAddr) {
+        let Some(system) = self
+            .asn_database
+            .as_ref()
+            .and_then(|database| database.lookup(address).ok())
+        else {
+            return;
+        };
+
+        self.asn_lookups.inc(vec![
+            ("asn", system.number.to_string().into()),
+            ("as_org", system.organization.clone()),
+        ]);
+    }
+
+    /// Record a lookup that found no country in the database named `database`, or the default
+    /// database if `None`.
+    pub fn record_miss(&self, database: Option<&str>) {
+        let labels = database.map(|database| vec![("database", database.into())]);
+        self.misses.inc(labels.unwrap_or_default());
+    }
+
+    /// Record a lookup answered from the lookup cache.
+    pub fn record_cache_hit(&self) {
+        self.cache_hits.inc(Vec::new());
+    }
+
+    /// Record a lookup not found in the lookup cache.
+    pub fn record_cache_miss(&self) {
+        self.cache_misses.inc(Vec::new());
+    }
+
+    /// Record a request rejected (or a connection held back) by a limit or an access rule, ex.
+    /// `rate`, `in_flight`, `connections`, or `forbidden`.
+    pub fn record_limited(&self, reason: &'static str) {
+        self.requests_limited.inc(vec![("reason", reason.into())]);
+    }
+
+    /// Return the number of successful lookups and the number of lookups that found no country.
+    #[cfg(feature = "grpc")]
+    pub fn lookup_totals(&self) -> (u64, u64) {
+        (self.lookups.total(), self.misses.total())
+    }
+
+    /// Return when the database was generated or last modified, in seconds since the Unix epoch,
+    /// if known.
+    #[cfg(feature = "grpc")]
+    pub fn database_timestamp(&self) -> Option<u64> {
+        self.database.lock().expect("an unpoisoned lock").timestamp
+    }
+
+    /// Reset every counter and histogram to zero, ex. after changing labels.
+    pub fn reset(&self) {
+        for family in self.counter_families() {
+            family.clear();
+        }
+        self.lookup_duration.clear();
+        self.forget_history();
+
+        if let Some(top_countries) = &self.top_countries {
+            *top_countries.current.lock().expect("an unpoisoned lock") = None;
+        }
+    }
+
+    /// Remove every series labelled with the country `code` (ex. `BE`), returning how many were
+    /// removed.
+    pub fn delete_country(&self, code: &str) -> usize {
+        let removed = self
+            .counter_families()
+            .into_iter()
+            .map(|family| family.remove_country(code))
+            .sum();
+        self.forget_history();
+
+        removed
+    }
+
+    /// Discard the snapshots of windowed rates and rank the top countries again, since neither
+    /// reflects the counters after they are reset or removed.
+    fn forget_history(&self) {
+        if let Some(rates) = &self.rates {
+            rates.samples.lock().expect("an unpoisoned lock").clear();
+            self.sample_rates();
+        }
+        if let Some(top_countries) = &self.top_countries {
+            let mut current = top_countries.current.lock().expect("an unpoisoned lock");
+            if let Some((_, ranked)) = &mut *current {
+                *ranked = None;
+            }
+        }
+    }
+
+    /// Perform a lookup, recording how long it took.
+    pub fn time_lookup<T>(&self, lookup: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = lookup();
+        self.lookup_duration.observe(start.elapsed().as_secs_f64());
+
+        result
+    }
+
+    /// Render every metric in `format`.
+    pub fn render(&self, format: Format) -> String {
+        let mut output = Exposition {
+            text: String::new(),
+            families: None,
+            format,
+            prefix: &self.prefix,
+            static_labels: &self.static_labels,
+            top_countries: self.top_countries(),
+        };
+
+        self.expose(&mut output);
+
+        if format == Format::OpenMetrics {
+            output.text.push_str("# EOF\n");
+        }
+
+        output.text
+    }
+
+    /// Collect the current value of every series, ex. for pushing them elsewhere.
+    pub fn collect(&self) -> Vec<Series> {
+        self.collect_families()
+            .into_iter()
+            .flat_map(|family| family.series)
+            .collect()
+    }
+
+    /// Collect the current value of every series, grouped by metric.
+    pub fn collect_families(&self) -> Vec<Family> {
+        let mut output = Exposition {
+            text: String::new(),
+            families: Some(Vec::new()),
+            format: Format::Text,
+            prefix: &self.prefix,
+            static_labels: &self.static_labels,
+            top_countries: self.top_countries(),
+        };
+
+        self.expose(&mut output);
+
+        output.families.unwrap_or_default()
+    }
+
+    /// Write every metric to `output`.
+    fn expose(&self, output: &mut Exposition) {
+        self.lookups.render(output);
+        self.misses.render(output);
+        if self.asn_database.is_some() {
+            self.asn_lookups.render(output);
+        }
+        if self.lookup_cache {
+            self.cache_hits.render(output);
+            self.cache_misses.render(output);
+        }
+        self.lookup_duration.render(output);
+        self.requests_limited.render(output);
+        self.log_lines.render(output);
+        self.log_lines_dropped.render(output);
+        self.log_responses.render(output);
+        self.log_response_bytes.render(output);
+        self.syslog_messages.render(output);
+        self.syslog_messages_dropped.render(output);
+        self.flow_bytes.render(output);
+        self.flow_packets.render(output);
+        self.flow_datagrams_invalid.render(output);
+        self.bytes.render(output);
+        #[cfg(feature = "pcap")]
+        {
+            self.capture_bytes.render(output);
+            self.capture_packets.render(output);
+        }
+        #[cfg(feature = "journald")]
+        {
+            self.ssh_failures.render(output);
+            self.ssh_failures_dropped.render(output);
+        }
+
+        if let Some(rates) = &self.rates {
+            let samples = rates.samples.lock().expect("an unpoisoned lock");
+
+            if let Some(baseline) = samples.front() {
+                let elapsed = baseline.taken.elapsed();
+
+                if !elapsed.is_zero() {
+                    let families = self.rated_families().into_iter();
+                    for (family, baseline) in families.zip(&baseline.values) {
+                        family.render_rate(output, baseline, elapsed);
+                    }
+                }
+            }
+        }
+
+        if let Some(country_info) = &self.country_info {
+            let name = "ip_geo_country_info";
+            write_header(
+                output,
+                name,
+                "The name and coordinates of every known country, always 1.",
+                Kind::Gauge,
+            );
+
+            for labels in country_info {
+                write_sample(output, name, labels, 1);
+            }
+        }
+
+        let database = *self.database.lock().expect("an unpoisoned lock");
+        self.database_reloads.render(output);
+
+        let name = "ip_geo_database_entries";
+        write_header(
+            output,
+            name,
+            "The number of address ranges in the database, by address family.",
+            Kind::Gauge,
+        );
+        let (ipv4_entries, ipv6_entries) = database.entries;
+        write_sample(output, name, &[("family", "ipv4".into())], ipv4_entries);
+        write_sample(output, name, &[("family", "ipv6".into())], ipv6_entries);
+
+        let name = "ip_geo_database_load_duration_seconds";
+        write_header(
+            output,
+            name,
+            "How long the database took to load, at startup or its latest reload.",
+            Kind::Gauge,
+        );
+        write_sample(output, name, &[], database.load_duration.as_secs_f64());
+
+        if let Some(timestamp) = database.timestamp {
+            let name = "ip_geo_database_timestamp_seconds";
+            write_header(
+                output,
+                name,
+                "When the database was generated or last modified, in seconds since the Unix epoch.",
+                Kind::Gauge,
+            );
+            write_sample(output, name, &[], timestamp);
+
+            let age = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |now| now.as_secs().saturating_sub(timestamp));
+
+            let name = "ip_geo_database_age_seconds";
+            write_header(
+                output,
+                name,
+                "How long ago the database was generated or last modified.",
+                Kind::Gauge,
+            );
+            write_sample(output, name, &[], age);
+        }
+
+        if let Some(relays) = &*self.tor_relays.lock().expect("an unpoisoned lock") {
+            let name = "ip_geo_tor_relays";
+            write_header(
+                output,
+                name,
+                "The number of relays in the latest Tor network consensus, by country.",
+                Kind::Gauge,
+            );
+            for (code, stats) in relays {
+                write_sample(
+                    output,
+                    name,
+                    &[("country_code", code.clone())],
+                    stats.relays,
+                );
+            }
+
+            let name = "ip_geo_tor_relay_bandwidth";
+            write_header(
+                output,
+                name,
+                "The total consensus weight of relays in the latest Tor network consensus, by country.",
+                Kind::Gauge,
+            );
+            for (code, stats) in relays {
+                write_sample(
+                    output,
+                    name,
+                    &[("country_code", code.clone())],
+                    stats.bandwidth,
+                );
+            }
+        }
+    }
+}
+
+/// What is known about the currently loaded database.
+#[derive(Clone, Copy, Debug)]
+struct DatabaseInfo {
+    /// The time that the database was generated or last modified, if known.
+    timestamp: Option<u64>,
+    /// How long the database took to load.
+    load_duration: Duration,
+    /// The number of IPv4 and IPv6 entries in the database.
+    entries: (usize, usize),
+}
+
+impl DatabaseInfo {
+    fn new(database: &GeoDatabase, timestamp: Option<u64>, load_duration: Duration) -> Self {
+        Self {
+            timestamp,
+            load_duration,
+            entries: (database.ipv4().len(), database.ipv6().len()),
+        }
+    }
+}
+
+/// The type of a metric.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+impl Kind {
+    /// Return the name of the type in the text exposition format, ex. `counter`.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Counter => "counter",
+            Self::Gauge => "gauge",
+            Self::Histogram => "histogram",
+        }
+    }
+}
+
+/// The samples of a single metric, as collected by [`Metrics::collect_families`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Family {
+    /// The name of the metric, including its prefix, ex. `ip_geo_lookups_total`.
+    ///
+    /// Histograms are named without the suffixes of their samples, ex.
+    /// `ip_geo_lookup_duration_seconds` for `ip_geo_lookup_duration_seconds_bucket`.
+    pub name: Box<str>,
+    pub help: Box<str>,
+    pub kind: Kind,
+    pub series: Vec<Series>,
+}
+
+/// A single sample of a series, as collected by [`Metrics::collect`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series {
+    /// The name of the metric, including its prefix, ex. `ip_geo_lookups_total`.
+    pub name: Box<str>,
+    /// The labels of the series, including static labels, ex. `[("country_code", "BE")]`.
+    pub labels: Vec<(Box<str>, Box<str>)>,
+    pub value: f64,
+}
+
+/// Metrics being rendered in the text exposition format, renamed with a prefix and labelled with
+/// static labels.
+struct Exposition<'m> {
+    text: String,
+    /// The metrics written so far, if collecting them instead of rendering text.
+    families: Option<Vec<Family>>,
+    format: Format,
+    prefix: &'m str,
+    static_labels: &'m [StaticLabel],
+    /// The countries to export individually, if not every country.
+    top_countries: Option<Arc<Ranking>>,
+}
+
+impl Exposition<'_> {
+    /// Return `name` with the configured prefix in place of [`DEFAULT_PREFIX`].
+    fn rename<'n>(&self, name: &'n str) -> Cow<'n, str> {
+        match name.strip_prefix(DEFAULT_PREFIX) {
+            Some(name) if self.prefix != DEFAULT_PREFIX => format!("{}{name}", self.prefix).into(),
+            _ => name.into(),
+        }
+    }
+}
+
+/// Write the `HELP` and `TYPE` lines of a metric.
+///
+/// In the OpenMetrics format, counters are named without their `_total` suffix, which only their
+/// samples have.
+fn write_header(output: &mut Exposition, name: &str, help: &str, kind: Kind) {
+    if output.families.is_some() {
+        let name = output.rename(name).into();
+        if let Some(families) = &mut output.families {
+            families.push(Family {
+                name,
+                help: help.into(),
+                kind,
+                series: Vec::new(),
+            });
+        }
+        return;
+    }
+
+    let name = match (output.format, kind) {
+        (Format::OpenMetrics, Kind::Counter) => name.strip_suffix("_total").unwrap_or(name),
+        _ => name,
+    };
+    let name = output.rename(name);
+    let kind = kind.as_str();
+
+    writeln!(output.text, "# HELP {name} {help}").expect("string concatenation");
+    writeln!(output.text, "# TYPE {name} {kind}").expect("string concatenation");
+}
+
+/// Write a single sample, ex. `ip_geo_lookups_total{country_code="BE"} 3`.
+fn write_sample(
+    output: &mut Exposition,
+    name: &str,
+    labels: &[(&'static str, Arc<str>)],
+    value: impl Display,
+) {
+    write_sample_with_exemplar(output, name, labels, value, None);
+}
+
+/// Write a single sample, followed by its exemplar if there is one and the format supports them,
+/// ex. `ip_geo_lookups_total{country_code="BE"} 3 # {ip_prefix="1.2.3.0/24"} 1`.
+fn write_sample_with_exemplar(
+    output: &mut Exposition,
+    name: &str,
+    labels: &[(&'static str, Arc<str>)],
+    value: impl Display,
+    exemplar: Option<&(Labels, u64)>,
+) {
+    let name = output.rename(name);
+
+    if let Some(families) = &mut output.families {
+        let labels = output
+            .static_labels
+            .iter()
+            .map(|label| (&*label.name, &*label.value))
+            .chain(labels.iter().map(|(name, value)| (*name, &**value)))
+            .map(|(name, value)| (name.into(), value.into()))
+            .collect();
+
+        let family = families
+            .last_mut()
+            .expect("a header written before every sample");
+        family.series.push(Series {
+            name: name.into(),
+            labels,
+            value: value.to_string().parse().unwrap_or(f64::NAN),
+        });
+        return;
+    }
+
+    output.text.push_str(&name);
+
+    let labels = output
+        .static_labels
+        .iter()
+        .map(|label| (&*label.name, &*label.value))
+        .chain(labels.iter().map(|(name, value)| (*name, &**value)))
+        .map(|(name, value)| format!("{name}=\"{}\"", escape(value)))
+        .collect::<Vec<_>>();
+
+    if !labels.is_empty() {
+        write!(output.text, "{{{}}}", labels.join(",")).expect("string concatenation");
+    }
+
+    write!(output.text, " {value}").expect("string concatenation");
+
+    if let (Format::OpenMetrics, Some((exemplar_labels, exemplar_value))) =
+        (output.format, exemplar)
+    {
+        let exemplar_labels = exemplar_labels
+            .iter()
+            .map(|(name, value)| format!("{name}=\"{}\"", escape(value)))
+            .collect::<Vec<_>>();
+
+        write!(
+            output.text,
+            " # {{{}}} {exemplar_value}",
+            exemplar_labels.join(",")
+        )
+        .expect("string concatenation");
+    }
+
+    output.text.push('\n');
+}
+
+/// Escape a label value, per the text exposition format.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('"', "\\\"")
+        .replace('\n', r"\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use ip_geo::{country_list::get_countries, IpAddrMap};
+
+    use super::*;
+
+    #[test]
+    fn test_static_labels() {
+        let label = "instance_role=edge".parse::<StaticLabel>().unwrap();
+        assert_eq!((label.name(), label.value()), ("instance_role", "edge"));
+        assert_eq!("region=".parse::<StaticLabel>().unwrap().value(), "");
+
+        for label in [
+            "region",
+            "=eu",
+            "1region=eu",
+            "region:zone=eu",
+            "__name__=eu",
+        ] {
+            assert!(label.parse::<StaticLabel>().is_err(), "{label}");
+        }
+        for label in [
+            "country_code=BE",
+            "continent_code=EU",
+            "le=1",
+            "quantile=0.5",
+        ] {
+            assert!(label.parse::<StaticLabel>().is_err(), "{label}");
+        }
+
+        let labels = |labels: &[&str]| {
+            labels
+                .iter()
+                .map(|label| label.parse().unwrap())
+                .collect::<Vec<StaticLabel>>()
+        };
+        assert_eq!(find_duplicate_label(&labels(&[])), None);
+        assert_eq!(find_duplicate_label(&labels(&["env=a", "region=eu"])), None);
+        assert_eq!(
+            find_duplicate_label(&labels(&["env=a", "region=eu", "env=b"])),
+            Some("env")
+        );
+        assert_eq!(
+            find_duplicate_label(&labels(&["env=a", "env=a"])),
+            Some("env")
+        );
+    }
+
+    #[test]
+    fn test_top_countries() {
+        let database = GeoDatabase::new(IpAddrMap::new(), IpAddrMap::new());
+        let metrics = Metrics::new(Aggregation::Country, &database, None, Duration::ZERO)
+            .with_top_countries(Some((1, Duration::ZERO)));
+        let countries = get_countries();
+        let lookup = |code: &str, count: usize| {
+            for _ in 0..count {
+                metrics.record_lookup([192, 0, 2, 1].into(), &countries[code], None);
+            }
+        };
+        // Rank the countries again and return the exported lookups by country
+        let lookups = || {
+            metrics
+                .collect()
+                .into_iter()
+                .filter(|series| &*series.name == "ip_geo_lookups_total")
+                .map(|series| {
+                    let (_, code) = series
+                        .labels
+                        .iter()
+                        .find(|(name, _)| &**name == "country_code")
+                        .expect("a country code label");
+
+                    (code.to_string(), series.value)
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+        let expected = |lookups: &[(&str, f64)]| {
+            lookups
+                .iter()
+                .map(|&(code, value)| (code.to_string(), value))
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        lookup("BE", 2);
+        lookup("FR", 1);
+        assert_eq!(lookups(), expected(&[("BE", 2.0), ("other", 1.0)]));
+
+        // France overtakes Belgium, whose lookups are summed into `other` along with those France
+        // had before entering the top
+        lookup("FR", 2);
+        assert_eq!(lookups(), expected(&[("FR", 0.0), ("other", 5.0)]));
+
+        // Belgium takes the top back and France is summed into `other` again with all of its
+        // lookups
+        lookup("BE", 2);
+        assert_eq!(lookups(), expected(&[("BE", 0.0), ("other", 7.0)]));
+        lookup("BE", 1);
+        lookup("FR", 3);
+        assert_eq!(lookups(), expected(&[("FR", 0.0), ("other", 11.0)]));
+        lookup("FR", 1);
+        lookup("CA", 1);
+        assert_eq!(lookups(), expected(&[("FR", 1.0), ("other", 12.0)]));
+
+        // Removing a country ranks the rest again, keeping the lookups France had when it entered
+        // the top in `other`
+        assert_eq!(metrics.delete_country("BE"), 1);
+        assert_eq!(lookups(), expected(&[("FR", 1.0), ("other", 7.0)]));
+
+        // Until the counters are reset
+        metrics.reset();
+        lookup("CA", 1);
+        assert_eq!(lookups(), expected(&[("CA", 1.0)]));
+    }
+}
@@ -16,61 +16,314 @@
 // not, see <https://www.gnu.org/licenses/>.
 
 use std::{
+    fs,
+    io::BufReader,
     net::{Ipv4Addr, Ipv6Addr},
+    path::Path,
+    process,
     sync::Arc,
+    time::UNIX_EPOCH,
 };
 
-use ip_geo::{country_list::Country, IpAddrMap};
+use ip_geo::{
+    asn::AsnDatabase,
+    country::{is_supported_language, DEFAULT_LANGUAGE},
+    country_list::Country,
+    country_names::LANGUAGES,
+    database::Fallback,
+    manifest::Manifest,
+    parse::ParseMode,
+    privacy::Truncation,
+    GeoDatabase, IpAddrMap,
+};
+use serde::Deserialize;
+
+use crate::{
+    arguments::{Arguments, FallbackMode},
+    error::Error,
+};
+
+/// An additional database, served alongside the default one and selected by name.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NamedDatabase {
+    /// The name used to select the database, ex. `tor`.
+    pub name: Box<str>,
+    pub ipv4_db_path: Box<Path>,
+    pub ipv6_db_path: Box<Path>,
+}
+
+/// Every parsed additional database, along with its name, in the order they were configured.
+pub type NamedDatabases = Vec<(Box<str>, Arc<GeoDatabase>)>;
+
+/// For a given set of `Arguments`, parse the specified IPv4 and IPv6 databases into a
+/// `GeoDatabase`, configured with the specified truncation and fallback, exiting if they cannot be
+/// parsed.
+///
+/// If a snapshot or MaxMind database is specified, it is read instead of the IPv4 and IPv6
+/// databases.
+pub fn parse_database(arguments: &Arguments) -> Arc<GeoDatabase> {
+    if let Some(language) = arguments.lang.as_deref() {
+        if !is_supported_language(language) {
+            eprintln!(
+                "No country names in `{language}`, expected one of {DEFAULT_LANGUAGE}, {}",
+                LANGUAGES.join(", ")
+            );
+            process::exit(1);
+        }
+    }
+
+    if arguments.snapshot_path.is_some() && arguments.mmdb_path.is_some() {
+        eprintln!("Only one of `snapshot_path` and `mmdb_path` can be set");
+        process::exit(1);
+    }
+
+    match try_parse_database(arguments) {
+        Ok(database) => database,
+        Err(error) => {
+            eprintln!("Could not parse database: {error}");
+            process::exit(1);
+        }
+    }
+}
 
-use crate::arguments::Arguments;
+/// Like [`parse_database`], but returns an error instead of exiting if the databases cannot be
+/// parsed.
+pub fn try_parse_database(arguments: &Arguments) -> Result<Arc<GeoDatabase>, Error> {
+    // Safety: `arguments::get_config()` implements default values
+    let database = match (&arguments.snapshot_path, &arguments.mmdb_path) {
+        (Some(path), _) => read_snapshot(path)?,
+        (None, Some(path)) => read_mmdb(path)?,
+        (None, None) => GeoDatabase::new(
+            parse_ipv4(arguments, arguments.ipv4_db_path.as_deref().unwrap())?,
+            parse_ipv6(arguments, arguments.ipv6_db_path.as_deref().unwrap())?,
+        ),
+    };
 
-/// For a given set of `Arguments`, parse the specified IPv4 and IPv6 databases into `IpAddrMap`s
-/// and return them in a struct holding them as `Arc`s.
-pub fn parse_ip_maps(arguments: &Arguments) -> Maps {
-    Maps::new(parse_ipv4(arguments), parse_ipv6(arguments))
+    configure_database(arguments, database)
 }
 
-/// A simple struct for passing around `IpAddrMaps`.
-pub struct Maps {
-    pub v4: Arc<IpAddrMap<Ipv4Addr, Country>>,
-    pub v6: Arc<IpAddrMap<Ipv6Addr, Country>>,
+/// For a given set of `Arguments`, parse every additional named database, configured like the
+/// default database, exiting if any cannot be parsed or if two share a name.
+pub fn parse_named_databases(arguments: &Arguments) -> NamedDatabases {
+    let named = arguments.databases.as_deref().unwrap_or_default();
+
+    for (index, database) in named.iter().enumerate() {
+        if named[..index]
+            .iter()
+            .any(|other| other.name == database.name)
+        {
+            eprintln!("Multiple databases are named `{}`", database.name);
+            process::exit(1);
+        }
+    }
+
+    match try_parse_named_databases(arguments) {
+        Ok(databases) => databases,
+        Err(error) => {
+            eprintln!("Could not parse database: {error}");
+            process::exit(1);
+        }
+    }
+}
+
+/// Like [`parse_named_databases`], but returns an error instead of exiting if the databases cannot
+/// be parsed, and does not check their names.
+pub fn try_parse_named_databases(arguments: &Arguments) -> Result<NamedDatabases, Error> {
+    let named = arguments.databases.as_deref().unwrap_or_default();
+
+    named
+        .iter()
+        .map(|named| {
+            let database = GeoDatabase::new(
+                parse_ipv4(arguments, &named.ipv4_db_path)?,
+                parse_ipv6(arguments, &named.ipv6_db_path)?,
+            );
+
+            Ok((named.name.clone(), configure_database(arguments, database)?))
+        })
+        .collect()
+}
+
+/// For a given set of `Arguments`, apply the specified fallback, language, and truncation to
+/// `database`.
+fn configure_database(
+    arguments: &Arguments,
+    database: GeoDatabase,
+) -> Result<Arc<GeoDatabase>, Error> {
+    let mut database = database.with_fallback(get_fallback(arguments)?);
+    if let Some(language) = arguments.lang.as_deref() {
+        database = database.with_language(language);
+    }
+
+    // Safety: `arguments::get_config()` implements default values
+    if !arguments.truncate_addresses.unwrap() {
+        return Ok(Arc::new(database));
+    }
+
+    Ok(Arc::new(database.with_truncation(Truncation::new(
+        arguments.truncate_ipv4_prefix.unwrap(),
+        arguments.truncate_ipv6_prefix.unwrap(),
+    ))))
 }
 
-impl Maps {
-    /// Create a new `Maps` from IPv4 and IPv6 `IpAddrMap`s.
-    pub fn new(
-        ipv4_map: IpAddrMap<Ipv4Addr, Country>,
-        ipv6_map: IpAddrMap<Ipv6Addr, Country>,
-    ) -> Self {
-        Self {
-            v4: Arc::new(ipv4_map),
-            v6: Arc::new(ipv6_map),
+/// For a given set of `Arguments`, parse the specified database of autonomous systems, if any,
+/// exiting if it cannot be parsed.
+pub fn parse_asn_database(arguments: &Arguments) -> Option<Arc<AsnDatabase>> {
+    let path = arguments.asn_db_path.as_deref()?;
+
+    match AsnDatabase::from_file(path) {
+        Ok(database) => Some(Arc::new(database)),
+        Err(error) => {
+            eprintln!(
+                "Could not parse ASN database at {}: {error}",
+                path.display()
+            );
+            process::exit(1);
         }
     }
 }
 
-/// For a given set of arguments, parse and return the IPv4 database into a clean `IpAddrMap`.
-fn parse_ipv4(arguments: &Arguments) -> IpAddrMap<Ipv4Addr, Country> {
+/// For a given set of `Arguments`, return when `database` was generated, in seconds since the Unix
+/// epoch.
+///
+/// Uses the metadata of snapshots and MaxMind databases, or otherwise the modification time of the oldest of the IPv4
+/// and IPv6 databases.
+pub fn database_timestamp(arguments: &Arguments, database: &GeoDatabase) -> Option<u64> {
+    if let Some(metadata) = database.metadata() {
+        return Some(metadata.generated);
+    }
+
+    // Safety: `arguments::get_config()` implements default values
+    [
+        arguments.ipv4_db_path.as_deref().unwrap(),
+        arguments.ipv6_db_path.as_deref().unwrap(),
+    ]
+    .into_iter()
+    .map(|path| {
+        let modified = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()?;
+        modified
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|age| age.as_secs())
+    })
+    .collect::<Option<Vec<_>>>()?
+    .into_iter()
+    .min()
+}
+
+/// For a given set of arguments, construct the `Fallback` for addresses with no associated
+/// country, parsing the secondary databases if necessary.
+fn get_fallback(arguments: &Arguments) -> Result<Fallback, Error> {
+    // Safety: `arguments::get_config()` implements default values
+    Ok(match arguments.fallback.unwrap() {
+        FallbackMode::None => Fallback::NoValue,
+        FallbackMode::Unknown => Fallback::unknown(),
+        FallbackMode::Database => {
+            let ipv4_map = match arguments.fallback_ipv4_db_path.as_deref() {
+                Some(path) => parse_ipv4(arguments, path)?,
+                None => IpAddrMap::new(),
+            };
+            let ipv6_map = match arguments.fallback_ipv6_db_path.as_deref() {
+                Some(path) => parse_ipv6(arguments, path)?,
+                None => IpAddrMap::new(),
+            };
+
+            Fallback::Database(Box::new(GeoDatabase::new(ipv4_map, ipv6_map)))
+        }
+    })
+}
+
+/// For a given set of arguments, parse and return the IPv4 database at `path` into an
+/// `IpAddrMap`, logging any malformed entries that were skipped.
+fn parse_ipv4(arguments: &Arguments, path: &Path) -> Result<IpAddrMap<Ipv4Addr, Country>, Error> {
     // Safety: `arguments::get_config()` implements default values
-    let path = arguments.ipv4_db_path.clone().unwrap();
     let file_length = arguments.ipv4_db_len.unwrap();
     let comment = arguments.ipv4_db_comment;
 
-    let mut map = ip_geo::ipv4::parse_ipv4_file(path, file_length, comment);
-    map.cleanup();
+    verify_database(path, comment, arguments.require_manifest.unwrap())?;
+
+    let (map, report) = ip_geo::ipv4::parse_ipv4_file_with_mode(
+        path.into(),
+        file_length,
+        comment,
+        ParseMode::Lenient,
+    )
+    .map_err(|source| load_error(path, source))?;
+
+    for malformed in report.malformed {
+        eprintln!("Skipped malformed IPv4 database entry on {malformed}");
+    }
 
-    map
+    Ok(map)
 }
 
-/// For a given set of arguments, parse and return the IPv6 database into an `IpAddrMap`.
-fn parse_ipv6(arguments: &Arguments) -> IpAddrMap<Ipv6Addr, Country> {
+/// For a given set of arguments, parse and return the IPv6 database at `path` into an
+/// `IpAddrMap`, logging any malformed entries that were skipped.
+fn parse_ipv6(arguments: &Arguments, path: &Path) -> Result<IpAddrMap<Ipv6Addr, Country>, Error> {
     // Safety: `arguments::get_config()` implements default values
-    let path = arguments.ipv6_db_path.clone().unwrap();
     let file_length = arguments.ipv6_db_len.unwrap();
     let comment = arguments.ipv6_db_comment;
 
-    let mut map = ip_geo::ipv6::parse_ipv6_file(path, file_length, comment);
-    map.cleanup();
+    verify_database(path, comment, arguments.require_manifest.unwrap())?;
 
-    map
+    let (map, report) = ip_geo::ipv6::parse_ipv6_file_with_mode(
+        path.into(),
+        file_length,
+        comment,
+        ParseMode::Lenient,
+    )
+    .map_err(|source| load_error(path, source))?;
+
+    for malformed in report.malformed {
+        eprintln!("Skipped malformed IPv6 database entry on {malformed}");
+    }
+
+    Ok(map)
+}
+
+/// Read a `GeoDatabase` from a snapshot.
+fn read_snapshot(path: &Path) -> Result<GeoDatabase, Error> {
+    fs::File::open(path)
+        .map_err(ip_geo::Error::from)
+        .and_then(|file| GeoDatabase::read_snapshot(BufReader::new(file)))
+        .map_err(|source| load_error(path, source))
+}
+
+/// Read a `GeoDatabase` from a MaxMind database.
+fn read_mmdb(path: &Path) -> Result<GeoDatabase, Error> {
+    fs::File::open(path)
+        .map_err(ip_geo::Error::from)
+        .and_then(|file| GeoDatabase::read_mmdb(BufReader::new(file)))
+        .map_err(|source| load_error(path, source))
+}
+
+/// Check a database against its manifest, if it has one, returning an error if they do not match.
+///
+/// If `require_manifest` is set, also returns an error if the database has no manifest.
+fn verify_database(
+    path: &Path,
+    comment: Option<char>,
+    require_manifest: bool,
+) -> Result<(), Error> {
+    if !require_manifest && !Manifest::path_for(path).exists() {
+        return Ok(());
+    }
+
+    match Manifest::verify(path, comment) {
+        Ok(_) => Ok(()),
+        Err(source) => Err(Error::VerifyDatabase {
+            path: path.into(),
+            source,
+        }),
+    }
+}
+
+/// Construct an error for the database at `path` that failed to load.
+fn load_error(path: &Path, source: ip_geo::Error) -> Error {
+    Error::LoadDatabase {
+        path: path.into(),
+        source,
+    }
 }
@@ -0,0 +1,60 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Hand-encoding [protobuf](https://protobuf.dev/programming-guides/encoding/) messages, which the
+//! push protocols are built on.
+
+/// The protobuf wire types.
+#[derive(Clone, Copy)]
+pub enum WireType {
+    Varint = 0,
+    Fixed64 = 1,
+    LengthDelimited = 2,
+}
+
+/// Append the key of field number `field`.
+pub fn put_key(buffer: &mut Vec<u8>, field: u64, wire_type: WireType) {
+    put_varint(buffer, (field << 3) | wire_type as u64);
+}
+
+/// Append `value` as a base 128 varint.
+pub fn put_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+
+    buffer.push(value as u8);
+}
+
+/// Append `value` as fixed-width field number `field`, ex. a `fixed64`.
+pub fn put_fixed64(buffer: &mut Vec<u8>, field: u64, value: u64) {
+    put_key(buffer, field, WireType::Fixed64);
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Append `value` as field number `field`, a `double`.
+pub fn put_double(buffer: &mut Vec<u8>, field: u64, value: f64) {
+    put_fixed64(buffer, field, value.to_bits());
+}
+
+/// Append `bytes` as length-delimited field number `field`, ex. a string or an embedded message.
+pub fn put_bytes(buffer: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    put_key(buffer, field, WireType::LengthDelimited);
+    put_varint(buffer, bytes.len() as u64);
+    buffer.extend_from_slice(bytes);
+}
@@ -0,0 +1,384 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Reloading the configuration file and databases on SIGHUP or `POST /-/reload`.
+
+use std::{
+    collections::BTreeMap,
+    net::IpAddr,
+    num::NonZeroUsize,
+    sync::{Arc, RwLock},
+    time::Instant,
+};
+
+use ip_geo::{country_list::Country, GeoDatabase};
+use serde::Serialize;
+use tokio::task::JoinHandle;
+
+use crate::{
+    arguments::{self, Arguments},
+    cache::LookupCache,
+    error::Error,
+    health::Readiness,
+    metrics::Metrics,
+    parse::{self, NamedDatabases},
+};
+
+/// The currently loaded database, which can be replaced while the server is running.
+#[derive(Clone, Debug)]
+pub struct SharedDatabase {
+    current: Arc<RwLock<CachedDatabase>>,
+}
+
+impl SharedDatabase {
+    /// Create a new `SharedDatabase`, initially holding `database`, caching up to `cache_capacity`
+    /// lookups (if set).
+    ///
+    /// `name` is the name of an additional database, or `None` for the default database.
+    pub fn new(
+        name: Option<Arc<str>>,
+        database: Arc<GeoDatabase>,
+        cache_capacity: Option<NonZeroUsize>,
+    ) -> Self {
+        let database = CachedDatabase {
+            name,
+            database,
+            cache: cache_capacity.map(|capacity| Arc::new(LookupCache::new(capacity))),
+        };
+
+        Self {
+            current: Arc::new(RwLock::new(database)),
+        }
+    }
+
+    /// Return the currently loaded database.
+    ///
+    /// The returned database is unaffected by later reloads, so a lookup always sees one
+    /// consistent database.
+    pub fn current(&self) -> CachedDatabase {
+        self.current.read().expect("an unpoisoned lock").clone()
+    }
+
+    /// Replace the currently loaded database with `database`, starting over with an empty cache.
+    fn replace(&self, database: Arc<GeoDatabase>) {
+        let mut current = self.current.write().expect("an unpoisoned lock");
+
+        let cache = current
+            .cache
+            .as_ref()
+            .map(|cache| Arc::new(LookupCache::new(cache.capacity())));
+        *current = CachedDatabase {
+            name: current.name.clone(),
+            database,
+            cache,
+        };
+    }
+}
+
+/// Every database served: the default database, and any additional named databases.
+#[derive(Clone, Debug)]
+pub struct Databases {
+    default: SharedDatabase,
+    named: Arc<BTreeMap<Box<str>, SharedDatabase>>,
+}
+
+impl Databases {
+    /// Create a new `Databases`, serving `default` and every database in `named`, caching up to
+    /// `cache_capacity` lookups of each named database (if set).
+    pub fn new(
+        default: SharedDatabase,
+        named: NamedDatabases,
+        cache_capacity: Option<NonZeroUsize>,
+    ) -> Self {
+        let named = named
+            .into_iter()
+            .map(|(name, database)| {
+                let shared =
+                    SharedDatabase::new(Some(name.as_ref().into()), database, cache_capacity);
+                (name, shared)
+            })
+            .collect();
+
+        Self {
+            default,
+            named: Arc::new(named),
+        }
+    }
+
+    /// Return the default database.
+    pub fn default_database(&self) -> &SharedDatabase {
+        &self.default
+    }
+
+    /// Return the currently loaded database named `name`, or the default database if `None`.
+    ///
+    /// Returns `None` if there is no database named `name`.
+    pub fn select(&self, name: Option<&str>) -> Option<CachedDatabase> {
+        match name {
+            Some(name) => self.named.get(name).map(SharedDatabase::current),
+            None => Some(self.default.current()),
+        }
+    }
+}
+
+/// A database, along with the cache of its lookups (if enabled).
+#[derive(Clone, Debug)]
+pub struct CachedDatabase {
+    /// The name of an additional database, or `None` for the default database.
+    name: Option<Arc<str>>,
+    database: Arc<GeoDatabase>,
+    cache: Option<Arc<LookupCache>>,
+}
+
+impl CachedDatabase {
+    /// Return the name of the database, or `None` for the default database.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Return the database itself.
+    pub fn database(&self) -> &GeoDatabase {
+        &self.database
+    }
+
+    /// For a given IPv4 or IPv6 address, find the country associated with it, checking the cache
+    /// first and recording whether it was cached in `metrics`.
+    ///
+    /// Only successful lookups are cached.
+    pub fn lookup(&self, address: IpAddr, metrics: &Metrics) -> Result<Country, ip_geo::Error> {
+        let Some(cache) = &self.cache else {
+            return self.database.lookup(address).cloned();
+        };
+
+        if let Some(country) = cache.get(address) {
+            metrics.record_cache_hit();
+            return Ok(country);
+        }
+        metrics.record_cache_miss();
+
+        let country = self.database.lookup(address)?.clone();
+        cache.insert(address, country.clone());
+
+        Ok(country)
+    }
+}
+
+/// Reloads the configuration file and databases, on SIGHUP or on request.
+#[derive(Clone, Debug)]
+pub struct Reloader {
+    /// The arguments the current databases were loaded with, and when the default database was
+    /// generated. Held for the duration of a reload, so that reloads never overlap.
+    state: Arc<tokio::sync::Mutex<(Arguments, Option<u64>)>>,
+    databases: Databases,
+    metrics: Arc<Metrics>,
+    readiness: Readiness,
+}
+
+/// What changed in a successful reload.
+///
+/// Serializes as:
+///
+/// ```json
+/// {"load_duration_seconds":0.42,"changed_settings":["ipv4_db_path"],"ipv4_entries":{"old":1000,"new":1002},...}
+/// ```
+#[derive(Serialize, Debug)]
+pub struct ReloadSummary {
+    load_duration_seconds: f64,
+    /// The names of the settings that determine the databases that changed.
+    changed_settings: Vec<&'static str>,
+    ipv4_entries: Change<usize>,
+    ipv6_entries: Change<usize>,
+    /// When the default database was generated or last modified, in seconds since the Unix epoch.
+    timestamp: Change<Option<u64>>,
+}
+
+/// The value of something before and after a reload.
+#[derive(Serialize, Debug)]
+pub struct Change<T> {
+    old: T,
+    new: T,
+}
+
+impl Reloader {
+    /// Create a new `Reloader`, reloading into `databases` and updating `metrics` and `readiness`
+    /// to match the default database.
+    ///
+    /// `arguments` are the arguments the current databases were loaded with, and `timestamp` is
+    /// when the default database was generated or last modified.
+    pub fn new(
+        arguments: Arguments,
+        timestamp: Option<u64>,
+        databases: Databases,
+        metrics: Arc<Metrics>,
+        readiness: Readiness,
+    ) -> Self {
+        Self {
+            state: Arc::new(tokio::sync::Mutex::new((arguments, timestamp))),
+            databases,
+            metrics,
+            readiness,
+        }
+    }
+
+    /// Re-read the configuration file (applying environment variables over it, like at startup)
+    /// and reload every database, returning what changed.
+    ///
+    /// Only the settings that determine the databases take effect; any others require a restart,
+    /// as does adding or removing named databases. If the new databases cannot be loaded, the
+    /// current databases are kept.
+    pub async fn reload(&self) -> Result<ReloadSummary, Error> {
+        let mut state = self.state.lock().await;
+        println!("Reloading configuration and databases");
+
+        let result = tokio::task::spawn_blocking(|| {
+            let arguments = arguments::get_config(arguments::parse());
+
+            let start = Instant::now();
+            let result = parse::try_parse_database(&arguments);
+            let load_duration = start.elapsed();
+
+            let named = parse::try_parse_named_databases(&arguments);
+            result.and_then(|new_database| {
+                let timestamp = parse::database_timestamp(&arguments, &new_database);
+                Ok((arguments, new_database, named?, timestamp, load_duration))
+            })
+        })
+        .await
+        .map_err(Error::from)
+        .and_then(|result| result);
+
+        let (new_arguments, new_database, new_named, timestamp, load_duration) = match result {
+            Ok(reloaded) => reloaded,
+            Err(error) => {
+                eprintln!("Could not reload database, keeping the current one: {error}");
+                self.metrics.record_reload(false);
+                return Err(error);
+            }
+        };
+
+        let (arguments, old_timestamp) = &mut *state;
+        let changed_settings = changed_settings(arguments, &new_arguments);
+        if !changed_settings.is_empty() {
+            println!("Changed settings: {}", changed_settings.join(", "));
+        }
+
+        let database = self.databases.default_database();
+        let old_database = database.current();
+        let old_database = old_database.database();
+        let summary = ReloadSummary {
+            load_duration_seconds: load_duration.as_secs_f64(),
+            changed_settings,
+            ipv4_entries: Change {
+                old: old_database.ipv4().len(),
+                new: new_database.ipv4().len(),
+            },
+            ipv6_entries: Change {
+                old: old_database.ipv6().len(),
+                new: new_database.ipv6().len(),
+            },
+            timestamp: Change {
+                old: *old_timestamp,
+                new: timestamp,
+            },
+        };
+        println!(
+            "Reloaded database in {:.2}s: {} -> {} IPv4 entries, {} -> {} IPv6 entries",
+            summary.load_duration_seconds,
+            summary.ipv4_entries.old,
+            summary.ipv4_entries.new,
+            summary.ipv6_entries.old,
+            summary.ipv6_entries.new,
+        );
+
+        self.metrics
+            .record_database(&new_database, timestamp, load_duration);
+        self.metrics.record_reload(true);
+        self.readiness.update(&new_database, timestamp);
+        database.replace(new_database);
+
+        for (name, new_database) in new_named {
+            match self.databases.named.get(&name) {
+                Some(database) => database.replace(new_database),
+                None => println!("Adding database `{name}` requires a restart"),
+            }
+        }
+
+        *arguments = new_arguments;
+        *old_timestamp = timestamp;
+
+        Ok(summary)
+    }
+}
+
+/// Spawn a task that reloads with `reloader` on every SIGHUP.
+#[cfg(unix)]
+pub fn spawn_reloader(reloader: Reloader) -> Option<JoinHandle<()>> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(error) => {
+            eprintln!("Could not listen for SIGHUP, reloading is disabled: {error}");
+            return None;
+        }
+    };
+
+    Some(tokio::spawn(async move {
+        while hangup.recv().await.is_some() {
+            // Failures are already logged, and the current databases kept
+            let _ = reloader.reload().await;
+        }
+    }))
+}
+
+/// Reloading on SIGHUP is not supported outside of Unix.
+#[cfg(not(unix))]
+pub fn spawn_reloader(_reloader: Reloader) -> Option<JoinHandle<()>> {
+    None
+}
+
+/// Return the names of the settings that determine the databases that differ between `old` and
+/// `new`.
+fn changed_settings(old: &Arguments, new: &Arguments) -> Vec<&'static str> {
+    macro_rules! changed {
+        ( $( $field:ident ),+ $(,)? ) => {
+            [ $( (stringify!($field), old.$field != new.$field) ),+ ]
+                .into_iter()
+                .filter_map(|(name, changed)| changed.then_some(name))
+                .collect()
+        };
+    }
+
+    changed!(
+        ipv4_db_path,
+        ipv4_db_len,
+        ipv4_db_comment,
+        ipv6_db_path,
+        ipv6_db_len,
+        ipv6_db_comment,
+        snapshot_path,
+        mmdb_path,
+        require_manifest,
+        truncate_addresses,
+        truncate_ipv4_prefix,
+        truncate_ipv6_prefix,
+        fallback,
+        fallback_ipv4_db_path,
+        fallback_ipv6_db_path,
+        databases,
+    )
+}
@@ -0,0 +1,103 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Shutting down gracefully on SIGINT or SIGTERM.
+
+use std::time::Duration;
+
+use tokio::{sync::watch, task::JoinHandle};
+
+/// Resolves once the server has been asked to shut down.
+#[derive(Clone, Debug)]
+pub struct Shutdown {
+    receiver: watch::Receiver<bool>,
+}
+
+impl Shutdown {
+    /// Listen for SIGINT and SIGTERM (only SIGINT, if not on Unix) in the background.
+    pub fn listen() -> Self {
+        let (sender, receiver) = watch::channel(false);
+
+        tokio::spawn(async move {
+            wait_for_signal().await;
+            eprintln!("Shutting down");
+            let _ = sender.send(true);
+        });
+
+        Self { receiver }
+    }
+
+    /// Wait until the server has been asked to shut down.
+    pub async fn signalled(mut self) {
+        // The sender only closes without sending if signal handling failed, in which case the
+        // server should keep running
+        if self
+            .receiver
+            .wait_for(|signalled| *signalled)
+            .await
+            .is_err()
+        {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+/// Wait until the server has been asked to shut down, then wait up to `timeout` for `servers` to
+/// finish their in-flight requests.
+pub async fn drain(servers: Vec<JoinHandle<()>>, shutdown: Shutdown, timeout: Duration) {
+    shutdown.signalled().await;
+
+    let finished = tokio::time::timeout(timeout, async {
+        for server in servers {
+            let _ = server.await;
+        }
+    })
+    .await;
+
+    if finished.is_err() {
+        eprintln!(
+            "Timed out after {}s waiting for in-flight requests",
+            timeout.as_secs()
+        );
+    }
+}
+
+/// Wait for SIGINT or SIGTERM.
+#[cfg(unix)]
+async fn wait_for_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut terminate = match signal(SignalKind::terminate()) {
+        Ok(terminate) => terminate,
+        Err(error) => {
+            eprintln!("Could not listen for SIGTERM: {error}");
+            let _ = tokio::signal::ctrl_c().await;
+            return;
+        }
+    };
+
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate.recv() => {}
+    }
+}
+
+/// Wait for SIGINT.
+#[cfg(not(unix))]
+async fn wait_for_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
@@ -0,0 +1,280 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Destinations that metrics are pushed to, as opposed to being scraped.
+
+use std::{fmt::Display, fs, path::Path, process, sync::Arc, time::Duration};
+
+use tokio::task::JoinHandle;
+
+use crate::{arguments::Arguments, metrics::Metrics};
+
+pub mod pushgateway;
+use pushgateway::Pushgateway;
+
+pub mod influxdb;
+use influxdb::InfluxWriter;
+
+#[cfg(feature = "otlp")]
+pub mod otlp;
+
+pub mod remote_write;
+
+pub mod state;
+use state::StateFile;
+
+pub mod statsd;
+use statsd::StatsdClient;
+
+pub mod textfile;
+use textfile::TextfileWriter;
+
+/// For a given set of arguments, spawn a task that periodically pushes every series to the
+/// configured remote write endpoint, if any.
+pub fn spawn_remote_writer(
+    arguments: &Arguments,
+    metrics: &Arc<Metrics>,
+) -> Option<JoinHandle<()>> {
+    let url = arguments.remote_write_url.clone()?;
+    let token = arguments
+        .remote_write_bearer_token_file
+        .as_deref()
+        .map(read_token);
+
+    // Safety: `arguments::get_config()` implements default values
+    let interval = Duration::from_secs(arguments.remote_write_interval.unwrap());
+
+    let writer = remote_write::RemoteWriter::new(url, token);
+    Some(spawn_periodic(interval, metrics.clone(), move |metrics| {
+        writer.push(&metrics.collect())
+    }))
+}
+
+/// For a given set of arguments, spawn a task that periodically writes every series to the
+/// configured InfluxDB server or UDP listener, if any, exiting if it is misconfigured.
+pub fn spawn_influxdb_writer(
+    arguments: &Arguments,
+    metrics: &Arc<Metrics>,
+) -> Option<JoinHandle<()>> {
+    let url = arguments.influxdb_url.as_deref()?;
+
+    // Safety: `arguments::get_config()` implements default values
+    let interval = Duration::from_secs(arguments.influxdb_interval.unwrap());
+
+    let writer = match url.strip_prefix("udp://") {
+        Some(address) => InfluxWriter::udp(address).unwrap_or_else(|error| {
+            eprintln!("Could not send to InfluxDB UDP listener at {address}: {error}");
+            process::exit(1);
+        }),
+        None => {
+            let (Some(org), Some(bucket)) = (&arguments.influxdb_org, &arguments.influxdb_bucket)
+            else {
+                eprintln!(
+                    "Writing to InfluxDB over HTTP requires `influxdb_org` and `influxdb_bucket`"
+                );
+                process::exit(1);
+            };
+            let token = arguments.influxdb_token_file.as_deref().map(read_token);
+
+            InfluxWriter::http(url, org.clone(), bucket.clone(), token)
+        }
+    };
+
+    // Shared, because sockets cannot be cloned infallibly
+    let writer = Arc::new(writer);
+    Some(spawn_periodic(interval, metrics.clone(), move |metrics| {
+        writer.write(&metrics.collect())
+    }))
+}
+
+/// For a given set of arguments, spawn a task that periodically exports every metric to the
+/// configured OpenTelemetry collector, if any, exiting if its endpoint is invalid.
+///
+/// Returns the exporter, to export with one last time before exiting.
+#[cfg(feature = "otlp")]
+pub fn spawn_otlp_exporter(
+    arguments: &Arguments,
+    metrics: &Arc<Metrics>,
+) -> Option<otlp::OtlpExporter> {
+    let endpoint = arguments.otlp_endpoint.as_deref()?;
+
+    // Safety: `arguments::get_config()` implements default values
+    let interval = Duration::from_secs(arguments.otlp_interval.unwrap());
+
+    let exporter = otlp::OtlpExporter::new(endpoint).unwrap_or_else(|error| {
+        eprintln!("Invalid OTLP endpoint: {error}");
+        process::exit(1);
+    });
+    let periodic_exporter = exporter.clone();
+    spawn_periodic(interval, metrics.clone(), move |metrics| {
+        periodic_exporter.export(metrics)
+    });
+
+    Some(exporter)
+}
+
+/// For a given set of arguments, exit if an OpenTelemetry collector was configured, because this
+/// build cannot export to one.
+#[cfg(not(feature = "otlp"))]
+pub fn spawn_otlp_exporter(arguments: &Arguments, _metrics: &Arc<Metrics>) {
+    if arguments.otlp_endpoint.is_some() {
+        eprintln!("Exporting with OTLP requires building with the `otlp` feature");
+        process::exit(1);
+    }
+}
+
+/// For a given set of arguments, spawn a task that periodically pushes every metric to the
+/// configured Pushgateway, if any.
+///
+/// Returns the `Pushgateway`, to push to one last time before exiting.
+pub fn spawn_pushgateway_pusher(
+    arguments: &Arguments,
+    metrics: &Arc<Metrics>,
+) -> Option<Pushgateway> {
+    let url = arguments.pushgateway_url.as_deref()?;
+
+    // Safety: `arguments::get_config()` implements default values
+    let job = arguments.pushgateway_job.as_deref().unwrap();
+    let grouping = arguments.pushgateway_grouping.as_deref().unwrap();
+    let interval = Duration::from_secs(arguments.pushgateway_interval.unwrap());
+
+    let pushgateway = Pushgateway::new(url, job, grouping);
+    let pusher = pushgateway.clone();
+    spawn_periodic(interval, metrics.clone(), move |metrics| {
+        pusher.push(metrics)
+    });
+
+    Some(pushgateway)
+}
+
+/// For a given set of arguments, return a client for the configured StatsD server, if any,
+/// exiting if it cannot be reached.
+pub fn get_statsd_client(arguments: &Arguments) -> Option<Arc<StatsdClient>> {
+    let address = arguments.statsd_address.as_deref()?;
+
+    // Safety: `arguments::get_config()` implements default values
+    let prefix = arguments.statsd_prefix.as_deref().unwrap();
+    let static_labels = arguments.metrics_labels.as_deref().unwrap();
+
+    match StatsdClient::new(address, prefix, static_labels) {
+        Ok(client) => Some(Arc::new(client)),
+        Err(error) => {
+            eprintln!("Could not send to StatsD server at {address}: {error}");
+            process::exit(1);
+        }
+    }
+}
+
+/// For a given set of arguments, spawn a task that periodically writes every metric to the
+/// configured textfile collector file, if any.
+///
+/// Returns the `TextfileWriter`, to write with one last time before exiting.
+pub fn spawn_textfile_writer(
+    arguments: &Arguments,
+    metrics: &Arc<Metrics>,
+) -> Option<TextfileWriter> {
+    let path = arguments.textfile_path.clone()?;
+
+    // Safety: `arguments::get_config()` implements default values
+    let interval = Duration::from_secs(arguments.textfile_interval.unwrap());
+
+    let writer = TextfileWriter::new(path);
+    let periodic_writer = writer.clone();
+    spawn_periodic(interval, metrics.clone(), move |metrics| {
+        periodic_writer.write(metrics)
+    });
+
+    Some(writer)
+}
+
+/// For a given set of arguments, restore counters from the configured state file, if any, then
+/// spawn a task that periodically saves them to it.
+///
+/// Returns the state file, to save the final value of every counter to before exiting.
+pub fn spawn_state_writer(arguments: &Arguments, metrics: &Arc<Metrics>) -> Option<StateFile> {
+    let path = arguments.state_path.clone()?;
+
+    // Safety: `arguments::get_config()` implements default values
+    if !arguments.persist_counters.unwrap() {
+        return None;
+    }
+    let interval = Duration::from_secs(arguments.state_interval.unwrap());
+
+    let state = StateFile::new(path);
+    if let Err(error) = state.restore(metrics) {
+        eprintln!("Could not restore counters from the state file: {error}");
+        eprintln!("Remove it, or pass `--persist-counters false`, to start from zero");
+        process::exit(1);
+    }
+
+    let periodic_state = state.clone();
+    spawn_periodic(interval, metrics.clone(), move |metrics| {
+        periodic_state.write(metrics)
+    });
+
+    Some(state)
+}
+
+/// Read a token from the file at `path`, ignoring trailing whitespace, exiting if it cannot be
+/// read.
+fn read_token(path: &Path) -> Box<str> {
+    match fs::read_to_string(path) {
+        Ok(token) => token.trim_end().into(),
+        Err(error) => {
+            eprintln!("Could not read token from {}: {error}", path.display());
+            process::exit(1);
+        }
+    }
+}
+
+/// Call `push` with `metrics` one last time, ex. at the end of a short-lived run, logging any
+/// errors.
+pub async fn push_final<E: Display + Send + 'static>(
+    metrics: Arc<Metrics>,
+    push: impl FnOnce(&Metrics) -> Result<(), E> + Send + 'static,
+) {
+    match tokio::task::spawn_blocking(move || push(&metrics)).await {
+        Ok(Ok(())) => {}
+        Ok(Err(error)) => eprintln!("Could not push metrics: {error}"),
+        Err(error) => eprintln!("Metrics push task failed: {error}"),
+    }
+}
+
+/// Spawn a task that calls `push` with `metrics` every `interval`, logging any errors.
+///
+/// `push` may block, so it is run on a blocking thread.
+fn spawn_periodic<E: Display + Send + 'static>(
+    interval: Duration,
+    metrics: Arc<Metrics>,
+    push: impl Fn(&Metrics) -> Result<(), E> + Clone + Send + 'static,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+
+        loop {
+            interval.tick().await;
+
+            let push = push.clone();
+            let metrics = metrics.clone();
+            match tokio::task::spawn_blocking(move || push(&metrics)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(error)) => eprintln!("Could not push metrics: {error}"),
+                Err(error) => eprintln!("Metrics push task failed: {error}"),
+            }
+        }
+    })
+}
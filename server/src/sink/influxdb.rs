@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Writing series in the [InfluxDB line
+//! protocol](https://docs.influxdata.com/influxdb/v2/reference/syntax/line-protocol/), either to
+//! the InfluxDB v2 HTTP API or to a UDP listener, such as Telegraf's `socket_listener`.
+
+use std::{
+    fmt::Write,
+    io,
+    net::{Ipv4Addr, Ipv6Addr, ToSocketAddrs, UdpSocket},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::metrics::Series;
+
+/// The largest UDP datagram to send, small enough to avoid fragmentation on most links.
+const MAX_DATAGRAM_SIZE: usize = 1400;
+
+/// Represents failures to write to InfluxDB.
+#[derive(thiserror::Error, Debug)]
+pub enum WriteError {
+    #[error("{0}")]
+    Http(#[from] Box<ureq::Error>),
+
+    #[error("{0}")]
+    Udp(#[from] io::Error),
+}
+
+/// Writes series in the line protocol.
+#[derive(Debug)]
+pub enum InfluxWriter {
+    /// Writes to the `/api/v2/write` endpoint of an InfluxDB v2 server.
+    Http {
+        /// The URL of the endpoint, ex. `http://influxdb:8086/api/v2/write`.
+        url: Box<str>,
+        org: Box<str>,
+        bucket: Box<str>,
+        /// The API token to authenticate with, if any.
+        token: Option<Box<str>>,
+    },
+    /// Sends lines to a UDP listener, several lines per datagram.
+    Udp(UdpSocket),
+}
+
+impl InfluxWriter {
+    /// Create a new `InfluxWriter` writing to the InfluxDB v2 server at `base_url` (ex.
+    /// `http://influxdb:8086`), into `bucket` of `org`, authenticating with `token` (if any).
+    pub fn http(base_url: &str, org: Box<str>, bucket: Box<str>, token: Option<Box<str>>) -> Self {
+        Self::Http {
+            url: format!("{}/api/v2/write", base_url.trim_end_matches('/')).into(),
+            org,
+            bucket,
+            token,
+        }
+    }
+
+    /// Create a new `InfluxWriter` sending to the UDP listener at `address`, ex.
+    /// `telegraf:8089`.
+    pub fn udp(address: &str) -> io::Result<Self> {
+        let address = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "address did not resolve"))?;
+
+        let socket = match address.is_ipv4() {
+            true => UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?,
+            false => UdpSocket::bind((Ipv6Addr::UNSPECIFIED, 0))?,
+        };
+        socket.connect(address)?;
+
+        Ok(Self::Udp(socket))
+    }
+
+    /// Write `series`, all sampled now.
+    pub fn write(&self, series: &[Series]) -> Result<(), WriteError> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |now| now.as_nanos());
+        // The line protocol cannot represent NaN or infinite values
+        let lines = series
+            .iter()
+            .filter(|series| series.value.is_finite())
+            .map(|series| encode_line(series, timestamp));
+
+        match self {
+            Self::Http {
+                url,
+                org,
+                bucket,
+                token,
+            } => {
+                let body = lines.collect::<String>();
+
+                let mut request = ureq::post(url)
+                    .query("org", org)
+                    .query("bucket", bucket)
+                    .query("precision", "ns")
+                    .set("Content-Type", "text/plain; charset=utf-8")
+                    .set(
+                        "User-Agent",
+                        concat!("ip_geo_server/", env!("CARGO_PKG_VERSION")),
+                    );
+
+                if let Some(token) = token {
+                    request = request.set("Authorization", &format!("Token {token}"));
+                }
+
+                request.send_string(&body).map_err(Box::new)?;
+            }
+            Self::Udp(socket) => {
+                let mut datagram = String::new();
+
+                for line in lines {
+                    if !datagram.is_empty() && datagram.len() + line.len() > MAX_DATAGRAM_SIZE {
+                        socket.send(datagram.as_bytes())?;
+                        datagram.clear();
+                    }
+
+                    datagram.push_str(&line);
+                }
+
+                if !datagram.is_empty() {
+                    socket.send(datagram.as_bytes())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Encode `series` as a line, with its value as the `value` field at `timestamp` (nanoseconds since
+/// the Unix epoch).
+///
+/// Ex. `ip_geo_lookups_total,country_code=BE,country_name=Belgium value=3 1700000000000000000\n`.
+fn encode_line(series: &Series, timestamp: u128) -> String {
+    let mut line = escape(&series.name, &[',', ' ']);
+
+    for (name, value) in &series.labels {
+        // Tags cannot be empty
+        if value.is_empty() {
+            continue;
+        }
+
+        line.push(',');
+        line.push_str(&escape(name, &[',', '=', ' ']));
+        line.push('=');
+        line.push_str(&escape(value, &[',', '=', ' ']));
+    }
+
+    let _ = writeln!(line, " value={:?} {timestamp}", series.value);
+    line
+}
+
+/// Escape every character in `special` (and backslashes) with a backslash.
+fn escape(text: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if c == '\\' || special.contains(&c) {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
@@ -0,0 +1,536 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Exporting metrics with the [OpenTelemetry protocol](https://opentelemetry.io/docs/specs/otlp/)
+//! (OTLP) over gRPC, as an `ExportMetricsServiceRequest`.
+
+use std::{
+    io,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use h2::client;
+use http::{HeaderMap, Request, StatusCode};
+use tokio::{net::TcpStream, runtime::Handle};
+
+use crate::metrics::{Family, Kind, Metrics, Series};
+use crate::protobuf::{put_bytes, put_double, put_fixed64, put_key, put_varint, WireType};
+
+/// The gRPC method that metrics are exported with.
+const EXPORT_PATH: &str = "/opentelemetry.proto.collector.metrics.v1.MetricsService/Export";
+
+/// `AGGREGATION_TEMPORALITY_CUMULATIVE`: every value counts from `start_time_unix_nano`.
+const CUMULATIVE: u64 = 2;
+
+/// Represents failures to export metrics.
+#[derive(thiserror::Error, Debug)]
+pub enum ExportError {
+    #[error("could not connect: {0}")]
+    Connect(#[from] io::Error),
+
+    #[error("{0}")]
+    Http2(#[from] h2::Error),
+
+    #[error("{0}")]
+    Request(#[from] http::Error),
+
+    #[error("the collector responded with HTTP status {0}")]
+    HttpStatus(StatusCode),
+
+    #[error("the collector responded with gRPC status {code}: {message}")]
+    GrpcStatus { code: Box<str>, message: Box<str> },
+}
+
+/// Exports metrics to an OpenTelemetry collector.
+#[derive(Clone, Debug)]
+pub struct OtlpExporter {
+    /// The host and port of the collector, ex. `localhost:4317`.
+    authority: Box<str>,
+    /// When the exporter was created, in nanoseconds since the Unix epoch, which every cumulative
+    /// value counts from.
+    start_time: u64,
+}
+
+impl OtlpExporter {
+    /// Create a new `OtlpExporter`, exporting to the collector at `endpoint`, ex.
+    /// `http://localhost:4317`.
+    ///
+    /// Returns an error if `endpoint` is not a plaintext `http://` URL.
+    pub fn new(endpoint: &str) -> Result<Self, String> {
+        let Some(authority) = endpoint.strip_prefix("http://") else {
+            return Err(format!(
+                "expected a `http://` URL, found `{endpoint}` (TLS is not supported)"
+            ));
+        };
+
+        let authority = authority.trim_end_matches('/');
+        if authority.is_empty() || authority.contains('/') {
+            return Err(format!("expected a host and port, found `{authority}`"));
+        }
+
+        Ok(Self {
+            authority: authority.into(),
+            start_time: now(),
+        })
+    }
+
+    /// Export the current value of every metric.
+    ///
+    /// Blocks until the collector responds, so this must be called from a blocking thread.
+    pub fn export(&self, metrics: &Metrics) -> Result<(), ExportError> {
+        let message = encode_export_request(&metrics.collect_families(), self.start_time, now());
+        Handle::current().block_on(self.send(message))
+    }
+
+    /// Call `Export` with `message`, a serialized `ExportMetricsServiceRequest`.
+    async fn send(&self, message: Vec<u8>) -> Result<(), ExportError> {
+        let stream = TcpStream::connect(&*self.authority).await?;
+        let (client, connection) = client::handshake(stream).await?;
+        tokio::spawn(async move {
+            let _ = connection.await;
+        });
+
+        // Messages are prefixed with whether they are compressed and their length
+        let mut body = Vec::with_capacity(message.len() + 5);
+        body.push(0);
+        body.extend_from_slice(&(message.len() as u32).to_be_bytes());
+        body.extend_from_slice(&message);
+
+        let request = Request::post(format!("http://{}{EXPORT_PATH}", self.authority))
+            .header("content-type", "application/grpc")
+            .header("te", "trailers")
+            .header(
+                "user-agent",
+                concat!("ip_geo_server/", env!("CARGO_PKG_VERSION")),
+            )
+            .body(())?;
+
+        let mut client = client.ready().await?;
+        let (response, mut request_body) = client.send_request(request, false)?;
+        request_body.send_data(body.into(), true)?;
+
+        let (parts, mut response_body) = response.await?.into_parts();
+        if parts.status != StatusCode::OK {
+            return Err(ExportError::HttpStatus(parts.status));
+        }
+
+        // The response message is empty on success, so only the status matters
+        while let Some(data) = response_body.data().await {
+            let _ = response_body.flow_control().release_capacity(data?.len());
+        }
+        let trailers = response_body.trailers().await?;
+
+        // Errors without a message may be sent as headers alone
+        let headers = trailers.as_ref().unwrap_or(&parts.headers);
+        match header(headers, "grpc-status") {
+            Some("0") => Ok(()),
+            code => Err(ExportError::GrpcStatus {
+                code: code.unwrap_or("missing").into(),
+                message: header(headers, "grpc-message").unwrap_or_default().into(),
+            }),
+        }
+    }
+}
+
+/// Return the value of the header `name`, if it is present and valid UTF-8.
+fn header<'h>(headers: &'h HeaderMap, name: &str) -> Option<&'h str> {
+    headers.get(name)?.to_str().ok()
+}
+
+/// Return the current time, in nanoseconds since the Unix epoch.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_nanos() as u64)
+}
+
+/// Encode `families` into an `ExportMetricsServiceRequest`, with every value at `time`, counting
+/// from `start_time` (both in nanoseconds since the Unix epoch).
+///
+/// ```protobuf
+/// message ExportMetricsServiceRequest { repeated ResourceMetrics resource_metrics = 1; }
+/// message ResourceMetrics { Resource resource = 1; repeated ScopeMetrics scope_metrics = 2; }
+/// message Resource { repeated KeyValue attributes = 1; }
+/// message ScopeMetrics { InstrumentationScope scope = 1; repeated Metric metrics = 2; }
+/// message InstrumentationScope { string name = 1; string version = 2; }
+/// ```
+fn encode_export_request(families: &[Family], start_time: u64, time: u64) -> Vec<u8> {
+    let mut resource = Vec::new();
+    put_bytes(
+        &mut resource,
+        1,
+        &encode_attribute("service.name", "ip_geo_server"),
+    );
+
+    let mut scope = Vec::new();
+    put_bytes(&mut scope, 1, b"ip_geo_server");
+    put_bytes(&mut scope, 2, env!("CARGO_PKG_VERSION").as_bytes());
+
+    let mut scope_metrics = Vec::new();
+    put_bytes(&mut scope_metrics, 1, &scope);
+    for family in families {
+        put_bytes(
+            &mut scope_metrics,
+            2,
+            &encode_metric(family, start_time, time),
+        );
+    }
+
+    let mut resource_metrics = Vec::new();
+    put_bytes(&mut resource_metrics, 1, &resource);
+    put_bytes(&mut resource_metrics, 2, &scope_metrics);
+
+    let mut request = Vec::new();
+    put_bytes(&mut request, 1, &resource_metrics);
+    request
+}
+
+/// Encode `family` into a `Metric`.
+///
+/// Counters are named without their `_total` suffix, per OpenTelemetry's conventions.
+///
+/// ```protobuf
+/// message Metric {
+///   string name = 1;
+///   string description = 2;
+///   oneof data { Gauge gauge = 5; Sum sum = 7; Histogram histogram = 9; }
+/// }
+/// message Gauge { repeated NumberDataPoint data_points = 1; }
+/// message Sum {
+///   repeated NumberDataPoint data_points = 1;
+///   AggregationTemporality aggregation_temporality = 2;
+///   bool is_monotonic = 3;
+/// }
+/// message Histogram {
+///   repeated HistogramDataPoint data_points = 1;
+///   AggregationTemporality aggregation_temporality = 2;
+/// }
+/// ```
+fn encode_metric(family: &Family, start_time: u64, time: u64) -> Vec<u8> {
+    let name = match family.kind {
+        Kind::Counter => family.name.strip_suffix("_total").unwrap_or(&family.name),
+        Kind::Gauge | Kind::Histogram => &family.name,
+    };
+
+    let mut metric = Vec::new();
+    put_bytes(&mut metric, 1, name.as_bytes());
+    put_bytes(&mut metric, 2, family.help.as_bytes());
+
+    let mut data = Vec::new();
+    match family.kind {
+        Kind::Counter => {
+            for series in &family.series {
+                let point = encode_number_data_point(series, Some(start_time), time);
+                put_bytes(&mut data, 1, &point);
+            }
+            put_key(&mut data, 2, WireType::Varint);
+            put_varint(&mut data, CUMULATIVE);
+            put_key(&mut data, 3, WireType::Varint);
+            put_varint(&mut data, 1);
+
+            put_bytes(&mut metric, 7, &data);
+        }
+        Kind::Gauge => {
+            for series in &family.series {
+                let point = encode_number_data_point(series, None, time);
+                put_bytes(&mut data, 1, &point);
+            }
+
+            put_bytes(&mut metric, 5, &data);
+        }
+        Kind::Histogram => {
+            let point = encode_histogram_data_point(family, start_time, time);
+            put_bytes(&mut data, 1, &point);
+            put_key(&mut data, 2, WireType::Varint);
+            put_varint(&mut data, CUMULATIVE);
+
+            put_bytes(&mut metric, 9, &data);
+        }
+    }
+
+    metric
+}
+
+/// Encode `series` into a `NumberDataPoint`.
+///
+/// ```protobuf
+/// message NumberDataPoint {
+///   repeated KeyValue attributes = 7;
+///   fixed64 start_time_unix_nano = 2;
+///   fixed64 time_unix_nano = 3;
+///   double as_double = 4;
+/// }
+/// ```
+fn encode_number_data_point(series: &Series, start_time: Option<u64>, time: u64) -> Vec<u8> {
+    let mut point = Vec::new();
+
+    for (name, value) in &series.labels {
+        put_bytes(&mut point, 7, &encode_attribute(name, value));
+    }
+    if let Some(start_time) = start_time {
+        put_fixed64(&mut point, 2, start_time);
+    }
+    put_fixed64(&mut point, 3, time);
+    put_double(&mut point, 4, series.value);
+
+    point
+}
+
+/// Encode the samples of a histogram, as rendered in the text exposition format (`_bucket`,
+/// `_sum`, and `_count`), into a `HistogramDataPoint`.
+///
+/// Histograms have a single set of labels, so they always have a single data point.
+///
+/// ```protobuf
+/// message HistogramDataPoint {
+///   repeated KeyValue attributes = 9;
+///   fixed64 start_time_unix_nano = 2;
+///   fixed64 time_unix_nano = 3;
+///   fixed64 count = 4;
+///   optional double sum = 5;
+///   repeated fixed64 bucket_counts = 6;
+///   repeated double explicit_bounds = 7;
+/// }
+/// ```
+fn encode_histogram_data_point(family: &Family, start_time: u64, time: u64) -> Vec<u8> {
+    let mut point = Vec::new();
+    let mut bucket_counts = Vec::new();
+    let mut explicit_bounds = Vec::new();
+    let mut cumulative = 0.0;
+
+    for series in &family.series {
+        let suffix = series.name.strip_prefix(&*family.name).unwrap_or_default();
+
+        match suffix {
+            "_bucket" => {
+                // Buckets are rendered cumulatively, but exported individually
+                let bucket_count = (series.value - cumulative) as u64;
+                cumulative = series.value;
+                bucket_counts.extend_from_slice(&bucket_count.to_le_bytes());
+
+                let bound = series
+                    .labels
+                    .iter()
+                    .find(|(name, _)| &**name == "le")
+                    .and_then(|(_, bound)| bound.parse::<f64>().ok())
+                    .filter(|bound| bound.is_finite());
+                if let Some(bound) = bound {
+                    explicit_bounds.extend_from_slice(&bound.to_le_bytes());
+                }
+            }
+            "_sum" => put_double(&mut point, 5, series.value),
+            "_count" => {
+                for (name, value) in &series.labels {
+                    put_bytes(&mut point, 9, &encode_attribute(name, value));
+                }
+                put_fixed64(&mut point, 4, series.value as u64);
+            }
+            _ => {}
+        }
+    }
+
+    put_fixed64(&mut point, 2, start_time);
+    put_fixed64(&mut point, 3, time);
+    put_bytes(&mut point, 6, &bucket_counts);
+    put_bytes(&mut point, 7, &explicit_bounds);
+
+    point
+}
+
+/// Encode a `KeyValue` with a string value.
+///
+/// ```protobuf
+/// message KeyValue { string key = 1; AnyValue value = 2; }
+/// message AnyValue { oneof value { string string_value = 1; } }
+/// ```
+fn encode_attribute(key: &str, value: &str) -> Vec<u8> {
+    let mut any_value = Vec::new();
+    put_bytes(&mut any_value, 1, value.as_bytes());
+
+    let mut key_value = Vec::new();
+    put_bytes(&mut key_value, 1, key.as_bytes());
+    put_bytes(&mut key_value, 2, &any_value);
+    key_value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(name: &str, labels: &[(&str, &str)], value: f64) -> Series {
+        Series {
+            name: name.into(),
+            labels: labels
+                .iter()
+                .map(|&(name, value)| (name.into(), value.into()))
+                .collect(),
+            value,
+        }
+    }
+
+    fn family(name: &str, kind: Kind, series: Vec<Series>) -> Family {
+        Family {
+            name: name.into(),
+            help: "h".into(),
+            kind,
+            series,
+        }
+    }
+
+    #[test]
+    fn test_encode_attribute() {
+        assert_eq!(
+            encode_attribute("k", "v"),
+            [0x0a, 0x01, b'k', 0x12, 0x03, 0x0a, 0x01, b'v']
+        );
+    }
+
+    #[test]
+    fn test_encode_metric() {
+        let counter = family(
+            "c_total",
+            Kind::Counter,
+            vec![series("c_total", &[("k", "v")], 2.0)],
+        );
+
+        #[rustfmt::skip]
+        assert_eq!(
+            encode_metric(&counter, 1, 2),
+            [
+                // Metric.name, without `_total`, and Metric.description
+                0x0a, 0x01, b'c',
+                0x12, 0x01, b'h',
+                // Metric.sum
+                0x3a, 0x2b,
+                    // Sum.data_points
+                    0x0a, 0x25,
+                        // NumberDataPoint.attributes
+                        0x3a, 0x08, 0x0a, 0x01, b'k', 0x12, 0x03, 0x0a, 0x01, b'v',
+                        // NumberDataPoint.start_time_unix_nano and time_unix_nano
+                        0x11, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x19, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        // NumberDataPoint.as_double
+                        0x21, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+                    // Sum.aggregation_temporality, cumulative, and Sum.is_monotonic
+                    0x10, 0x02,
+                    0x18, 0x01,
+            ]
+        );
+
+        let gauge = family("g", Kind::Gauge, vec![series("g", &[], 0.5)]);
+
+        #[rustfmt::skip]
+        assert_eq!(
+            encode_metric(&gauge, 1, 2),
+            [
+                0x0a, 0x01, b'g',
+                0x12, 0x01, b'h',
+                // Metric.gauge
+                0x2a, 0x14,
+                    // Gauge.data_points, without a start time
+                    0x0a, 0x12,
+                        0x19, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x21, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xe0, 0x3f,
+            ]
+        );
+
+        let histogram = family(
+            "d",
+            Kind::Histogram,
+            vec![
+                series("d_bucket", &[("le", "1")], 1.0),
+                series("d_bucket", &[("le", "+Inf")], 3.0),
+                series("d_sum", &[], 4.5),
+                series("d_count", &[], 3.0),
+            ],
+        );
+
+        #[rustfmt::skip]
+        assert_eq!(
+            encode_metric(&histogram, 1, 2),
+            [
+                0x0a, 0x01, b'd',
+                0x12, 0x01, b'h',
+                // Metric.histogram
+                0x4a, 0x44,
+                    // Histogram.data_points
+                    0x0a, 0x40,
+                        // HistogramDataPoint.sum and count
+                        0x29, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x12, 0x40,
+                        0x21, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x11, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        0x19, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        // HistogramDataPoint.bucket_counts, not cumulative
+                        0x32, 0x10,
+                            0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                            0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                        // HistogramDataPoint.explicit_bounds, without `+Inf`
+                        0x3a, 0x08,
+                            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xf0, 0x3f,
+                    // Histogram.aggregation_temporality, cumulative
+                    0x10, 0x02,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_encode_export_request() {
+        let gauge = family("g", Kind::Gauge, vec![series("g", &[], 0.5)]);
+        let metric = encode_metric(&gauge, 1, 2);
+        let version = env!("CARGO_PKG_VERSION").as_bytes();
+
+        // InstrumentationScope
+        let scope = [
+            &[0x0a, 0x0d][..],
+            b"ip_geo_server",
+            &[0x12, version.len() as u8],
+            version,
+        ]
+        .concat();
+        // ScopeMetrics
+        let scope_metrics = [
+            &[0x0a, scope.len() as u8][..],
+            &scope,
+            &[0x12, metric.len() as u8],
+            &metric,
+        ]
+        .concat();
+
+        #[rustfmt::skip]
+        let resource: &[u8] = &[
+            // Resource.attributes
+            0x0a, 0x1f,
+                0x0a, 0x0c, b's', b'e', b'r', b'v', b'i', b'c', b'e', b'.', b'n', b'a', b'm', b'e',
+                0x12, 0x0f,
+                    0x0a, 0x0d,
+                        b'i', b'p', b'_', b'g', b'e', b'o', b'_', b's', b'e', b'r', b'v', b'e', b'r',
+        ];
+        // ResourceMetrics
+        let resource_metrics = [
+            &[0x0a, resource.len() as u8][..],
+            resource,
+            &[0x12, scope_metrics.len() as u8],
+            &scope_metrics,
+        ]
+        .concat();
+
+        assert_eq!(
+            encode_export_request(&[gauge], 1, 2),
+            [&[0x0a, resource_metrics.len() as u8][..], &resource_metrics].concat()
+        );
+    }
+}
@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Pushing metrics to a [Prometheus Pushgateway](https://github.com/prometheus/pushgateway).
+
+use base64::{engine::general_purpose::URL_SAFE, Engine};
+
+use crate::metrics::{self, Format, Metrics, StaticLabel};
+
+/// Pushes metrics to a group of a Pushgateway.
+#[derive(Clone, Debug)]
+pub struct Pushgateway {
+    /// The URL of the group, ex. `http://pushgateway:9091/metrics/job@base64/aXBfZ2Vv`.
+    url: Box<str>,
+}
+
+impl Pushgateway {
+    /// Create a new `Pushgateway`, pushing to the group identified by `job` and `grouping` on the
+    /// Pushgateway at `base_url`.
+    pub fn new(base_url: &str, job: &str, grouping: &[StaticLabel]) -> Self {
+        let mut url = format!("{}/metrics", base_url.trim_end_matches('/'));
+
+        // Encode every value in base64, so that they can hold slashes
+        for (name, value) in [("job", job)]
+            .into_iter()
+            .chain(grouping.iter().map(|label| (label.name(), label.value())))
+        {
+            url.push_str(&format!("/{name}@base64/{}", encode(value)));
+        }
+
+        Self { url: url.into() }
+    }
+
+    /// Replace every metric in the group with the current value of `metrics`.
+    pub fn push(&self, metrics: &Metrics) -> Result<(), Box<ureq::Error>> {
+        ureq::put(&self.url)
+            .set("Content-Type", metrics::CONTENT_TYPE)
+            .send_string(&metrics.render(Format::Text))?;
+
+        Ok(())
+    }
+}
+
+/// Encode a label value for the URL of a group, in which empty values are written as `=`.
+fn encode(value: &str) -> String {
+    match value.is_empty() {
+        true => "=".into(),
+        false => URL_SAFE.encode(value),
+    }
+}
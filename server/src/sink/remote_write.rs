@@ -0,0 +1,156 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Pushing series with the [Prometheus remote write
+//! protocol](https://prometheus.io/docs/specs/remote_write_spec/) (version 1.0): a snappy-compressed
+//! protobuf `WriteRequest`, sent by HTTP POST.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::metrics::Series;
+use crate::protobuf::{put_bytes, put_double, put_key, put_varint, WireType};
+
+/// Pushes series to a remote write endpoint.
+#[derive(Clone, Debug)]
+pub struct RemoteWriter {
+    url: Box<str>,
+    /// The token to authenticate with, if any.
+    token: Option<Box<str>>,
+}
+
+impl RemoteWriter {
+    /// Create a new `RemoteWriter`, pushing to `url` and authenticating with `token` (if any) as a
+    /// bearer token.
+    pub fn new(url: Box<str>, token: Option<Box<str>>) -> Self {
+        Self { url, token }
+    }
+
+    /// Push `series`, all sampled now.
+    pub fn push(&self, series: &[Series]) -> Result<(), Box<ureq::Error>> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |now| now.as_millis() as i64);
+
+        let body = snap::raw::Encoder::new()
+            .compress_vec(&encode_write_request(series, timestamp))
+            .expect("a body smaller than snappy's maximum input");
+
+        let mut request = ureq::post(&self.url)
+            .set("Content-Encoding", "snappy")
+            .set("Content-Type", "application/x-protobuf")
+            .set(
+                "User-Agent",
+                concat!("ip_geo_server/", env!("CARGO_PKG_VERSION")),
+            )
+            .set("X-Prometheus-Remote-Write-Version", "0.1.0");
+
+        if let Some(token) = &self.token {
+            request = request.set("Authorization", &format!("Bearer {token}"));
+        }
+
+        request.send_bytes(&body)?;
+        Ok(())
+    }
+}
+
+/// Encode `series` into a protobuf `WriteRequest`, with every sample at `timestamp` (milliseconds
+/// since the Unix epoch).
+///
+/// ```protobuf
+/// message WriteRequest { repeated TimeSeries timeseries = 1; }
+/// message TimeSeries { repeated Label labels = 1; repeated Sample samples = 2; }
+/// message Label { string name = 1; string value = 2; }
+/// message Sample { double value = 1; int64 timestamp = 2; }
+/// ```
+fn encode_write_request(series: &[Series], timestamp: i64) -> Vec<u8> {
+    let mut request = Vec::new();
+    let mut time_series = Vec::new();
+    let mut message = Vec::new();
+
+    for series in series {
+        time_series.clear();
+
+        // Labels must be sorted by name, and the metric name is the `__name__` label
+        let mut labels = series
+            .labels
+            .iter()
+            .map(|(name, value)| (&**name, &**value))
+            .chain([("__name__", &*series.name)])
+            .collect::<Vec<_>>();
+        labels.sort_unstable_by_key(|(name, _)| *name);
+
+        for (name, value) in labels {
+            message.clear();
+            put_bytes(&mut message, 1, name.as_bytes());
+            put_bytes(&mut message, 2, value.as_bytes());
+            put_bytes(&mut time_series, 1, &message);
+        }
+
+        message.clear();
+        put_double(&mut message, 1, series.value);
+        put_key(&mut message, 2, WireType::Varint);
+        put_varint(&mut message, timestamp as u64);
+        put_bytes(&mut time_series, 2, &message);
+
+        put_bytes(&mut request, 1, &time_series);
+    }
+
+    request
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_write_request() {
+        let series = Series {
+            name: "m".into(),
+            labels: vec![("b".into(), "2".into()), ("a".into(), "1".into())],
+            value: 1.5,
+        };
+
+        #[rustfmt::skip]
+        let expected: &[u8] = &[
+            // WriteRequest.timeseries
+            0x0a, 0x31,
+                // TimeSeries.labels, sorted by name with `__name__` first
+                0x0a, 0x0d,
+                    0x0a, 0x08, b'_', b'_', b'n', b'a', b'm', b'e', b'_', b'_',
+                    0x12, 0x01, b'm',
+                0x0a, 0x06, 0x0a, 0x01, b'a', 0x12, 0x01, b'1',
+                0x0a, 0x06, 0x0a, 0x01, b'b', 0x12, 0x01, b'2',
+                // TimeSeries.samples
+                0x12, 0x10,
+                    // Sample.value, a little-endian double
+                    0x09, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xf8, 0x3f,
+                    // Sample.timestamp, an int64 varint
+                    0x10, 0x80, 0xd0, 0x95, 0xff, 0xbc, 0x31,
+        ];
+
+        assert_eq!(
+            encode_write_request(std::slice::from_ref(&series), 1_700_000_000_000),
+            expected
+        );
+
+        // Every series is a separate `TimeSeries`
+        let twice = encode_write_request(&[series.clone(), series], 1_700_000_000_000);
+        assert_eq!(twice, [expected, expected].concat());
+
+        assert!(encode_write_request(&[], 0).is_empty());
+    }
+}
@@ -0,0 +1,159 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Persisting counters to a state file, so that totals survive restarts.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs, io,
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::metrics::{Labels, Metrics};
+
+/// The version of the state file format, incremented on incompatible changes.
+const VERSION: u32 = 1;
+
+/// Represents failures to save or restore counters.
+#[derive(thiserror::Error, Debug)]
+pub enum StateError {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+
+    #[error("{0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("unsupported state file version {0}, expected {VERSION}")]
+    Version(u32),
+}
+
+/// The contents of a state file.
+#[derive(Serialize, Deserialize, Debug)]
+struct State {
+    version: u32,
+    /// Every counter by the name of its family, ex. `ip_geo_lookups_total`.
+    counters: BTreeMap<String, Vec<StoredCounter>>,
+}
+
+/// The labels and value of a single counter.
+#[derive(Serialize, Deserialize, Debug)]
+struct StoredCounter {
+    /// The labels of the counter, in order, ex. `[["country_code", "BE"]]`.
+    labels: Vec<(String, String)>,
+    value: u64,
+}
+
+/// Saves and restores the value of every counter.
+#[derive(Clone, Debug)]
+pub struct StateFile {
+    path: Box<Path>,
+}
+
+impl StateFile {
+    /// Create a new `StateFile`, saving to `path`.
+    pub fn new(path: Box<Path>) -> Self {
+        Self { path }
+    }
+
+    /// Add the counters saved in the file to `metrics`, returning how many were restored.
+    ///
+    /// Restores nothing if the file does not exist yet. Counters of families that are not
+    /// exported, ex. because of a disabled feature, are ignored.
+    pub fn restore(&self, metrics: &Metrics) -> Result<usize, StateError> {
+        let contents = match fs::read(&self.path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(error) => return Err(error.into()),
+        };
+
+        let mut state: State = serde_json::from_slice(&contents)?;
+        if state.version != VERSION {
+            return Err(StateError::Version(state.version));
+        }
+
+        // Label names are `&'static str`, but only a handful of them exist
+        let mut names = HashMap::<String, &'static str>::new();
+        let mut restored = 0;
+
+        for family in metrics.counter_families() {
+            let Some(counters) = state.counters.remove(family.name()) else {
+                continue;
+            };
+
+            for counter in counters {
+                let labels: Labels = counter
+                    .labels
+                    .into_iter()
+                    .map(|(name, value)| {
+                        let name = *names
+                            .entry(name)
+                            .or_insert_with_key(|name| Box::leak(name.clone().into_boxed_str()));
+
+                        (name, value.into())
+                    })
+                    .collect();
+
+                family.inc_by(labels, counter.value);
+                restored += 1;
+            }
+        }
+
+        Ok(restored)
+    }
+
+    /// Replace the contents of the file with the current value of every counter in `metrics`.
+    ///
+    /// Writes to a temporary file next to it first and renames it into place, so that a crash
+    /// never leaves a partially written file behind.
+    pub fn write(&self, metrics: &Metrics) -> Result<(), StateError> {
+        let counters = metrics
+            .counter_families()
+            .into_iter()
+            .map(|family| {
+                let counters = family
+                    .snapshot()
+                    .into_iter()
+                    .map(|(labels, value)| StoredCounter {
+                        labels: labels
+                            .into_iter()
+                            .map(|(name, value)| (name.to_owned(), value.as_ref().to_owned()))
+                            .collect(),
+                        value,
+                    })
+                    .collect();
+
+                (family.name().to_owned(), counters)
+            })
+            .filter(|(_, counters): &(_, Vec<_>)| !counters.is_empty())
+            .collect();
+
+        let state = State {
+            version: VERSION,
+            counters,
+        };
+
+        let mut temporary = self.path.as_os_str().to_owned();
+        temporary.push(".tmp");
+
+        fs::write(&temporary, serde_json::to_vec(&state)?)?;
+        fs::rename(&temporary, &self.path)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Emitting counters to a [StatsD](https://github.com/statsd/statsd) or
+//! [DogStatsD](https://docs.datadoghq.com/developers/dogstatsd/) server.
+
+use std::{
+    fmt::Write,
+    io,
+    net::{Ipv4Addr, Ipv6Addr, ToSocketAddrs, UdpSocket},
+};
+
+use crate::metrics::{Labels, StaticLabel};
+
+/// Sends counter increments to a StatsD server over UDP, tagged in the DogStatsD format.
+#[derive(Debug)]
+pub struct StatsdClient {
+    socket: UdpSocket,
+    /// The prefix of every metric name, ex. `ip_geo.`.
+    prefix: Box<str>,
+    /// The tags applied to every metric, formatted ahead of time, ex. `instance_role:edge`.
+    static_tags: Box<str>,
+}
+
+impl StatsdClient {
+    /// Create a new `StatsdClient`, sending to the server at `address` (ex. `localhost:8125`),
+    /// naming every metric with `prefix`, and tagging every metric with `static_labels`.
+    pub fn new(address: &str, prefix: &str, static_labels: &[StaticLabel]) -> io::Result<Self> {
+        let address = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "address did not resolve"))?;
+
+        let socket = match address.is_ipv4() {
+            true => UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?,
+            false => UdpSocket::bind((Ipv6Addr::UNSPECIFIED, 0))?,
+        };
+        socket.connect(address)?;
+        // Never hold up ingestion, dropping counts instead
+        socket.set_nonblocking(true)?;
+
+        let mut static_tags = String::new();
+        for label in static_labels {
+            push_tag(&mut static_tags, label.name(), label.value());
+        }
+
+        Ok(Self {
+            socket,
+            prefix: prefix.into(),
+            static_tags: static_tags.into(),
+        })
+    }
+
+    /// Increment the counter `name` by `value`, tagged with `labels`.
+    ///
+    /// Like StatsD itself, this is best-effort: counts that cannot be sent are dropped.
+    pub fn count(&self, name: &str, value: u64, labels: &Labels) {
+        // Ex. "ip_geo.log_lines:1|c|#country_code:BE,country_name:Belgium"
+        let mut message = format!("{}{name}:{value}|c", self.prefix);
+
+        let mut tags = self.static_tags.to_string();
+        for (name, value) in labels {
+            push_tag(&mut tags, name, value);
+        }
+
+        if !tags.is_empty() {
+            let _ = write!(message, "|#{tags}");
+        }
+
+        let _ = self.socket.send(message.as_bytes());
+    }
+}
+
+/// Append `name:value` to a comma-separated list of tags.
+///
+/// Characters that delimit parts of a message are replaced, ex. `Korea, Republic of` becomes
+/// `Korea_ Republic of`.
+fn push_tag(tags: &mut String, name: &str, value: &str) {
+    if !tags.is_empty() {
+        tags.push(',');
+    }
+
+    let value = value.replace([',', '|', '#', '\n'], "_");
+    let _ = write!(tags, "{name}:{value}");
+}
@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Writing metrics to a file for the [node_exporter textfile
+//! collector](https://github.com/prometheus/node_exporter#textfile-collector).
+
+use std::{fs, io, path::Path};
+
+use crate::metrics::{Format, Metrics};
+
+/// Writes metrics to a `.prom` file.
+#[derive(Clone, Debug)]
+pub struct TextfileWriter {
+    path: Box<Path>,
+}
+
+impl TextfileWriter {
+    /// Create a new `TextfileWriter`, writing to `path`.
+    pub fn new(path: Box<Path>) -> Self {
+        Self { path }
+    }
+
+    /// Replace the contents of the file with the current value of `metrics`.
+    ///
+    /// Writes to a temporary file next to it first and renames it into place, so that the
+    /// collector never reads a partially written file.
+    pub fn write(&self, metrics: &Metrics) -> io::Result<()> {
+        // The collector only reads files ending in `.prom`, so it ignores the temporary file
+        let mut temporary = self.path.as_os_str().to_owned();
+        temporary.push(".tmp");
+
+        fs::write(&temporary, metrics.render(Format::Text))?;
+        fs::rename(&temporary, &self.path)
+    }
+}
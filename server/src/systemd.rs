@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Integration with systemd: readiness notification and socket activation.
+//!
+//! Implements the `sd_notify(3)` and `sd_listen_fds(3)` protocols directly, to avoid depending on
+//! libsystemd.
+
+use std::net::TcpListener;
+
+/// Tell the service manager that the server has finished starting up, if it is expecting to be
+/// told (`Type=notify`).
+pub fn notify_ready() {
+    notify("READY=1");
+}
+
+/// Send `state` to the service manager over `$NOTIFY_SOCKET`, if it is set. Failures are logged,
+/// but otherwise ignored.
+#[cfg(unix)]
+fn notify(state: &str) {
+    use std::{env, os::unix::net::UnixDatagram};
+
+    let Some(path) = env::var_os("NOTIFY_SOCKET") else {
+        return;
+    };
+
+    let sent = UnixDatagram::unbound().and_then(|socket| {
+        // Sockets starting with '@' are in the abstract namespace
+        #[cfg(target_os = "linux")]
+        if let Some(name) = path.as_encoded_bytes().strip_prefix(b"@") {
+            use std::os::{linux::net::SocketAddrExt, unix::net::SocketAddr};
+
+            let address = SocketAddr::from_abstract_name(name)?;
+            return socket.send_to_addr(state.as_bytes(), &address);
+        }
+
+        socket.send_to(state.as_bytes(), &path)
+    });
+
+    if let Err(error) = sent {
+        eprintln!("Could not notify systemd: {error}");
+    }
+}
+
+/// Send `state` to the service manager. Not supported outside of Unix.
+#[cfg(not(unix))]
+fn notify(_state: &str) {}
+
+/// Return the TCP sockets passed to the server by socket activation, if any.
+///
+/// Exits if a passed socket cannot be used.
+#[cfg(unix)]
+pub fn listen_fds() -> Option<Vec<TcpListener>> {
+    use std::{env, os::fd::FromRawFd, process};
+
+    /// The first file descriptor passed by socket activation, after standard input, output, and
+    /// error.
+    const LISTEN_FDS_START: i32 = 3;
+
+    // The sockets are only for this process, not a parent that passed on its environment
+    let pid: u32 = env::var("LISTEN_PID").ok()?.parse().ok()?;
+    if pid != process::id() {
+        return None;
+    }
+
+    let count: i32 = env::var("LISTEN_FDS").ok()?.parse().ok()?;
+    let listeners = (LISTEN_FDS_START..LISTEN_FDS_START + count)
+        .map(|fd| {
+            // Safety: systemd passes ownership of these file descriptors to this process, and
+            // nothing else in this process uses them
+            let listener = unsafe { TcpListener::from_raw_fd(fd) };
+
+            if let Err(error) = listener.local_addr() {
+                eprintln!("Could not use socket {fd} passed by systemd: {error}");
+                process::exit(1);
+            }
+
+            listener
+        })
+        .collect::<Vec<_>>();
+
+    Some(listeners).filter(|listeners| !listeners.is_empty())
+}
+
+/// Return the TCP sockets passed to the server by socket activation. Not supported outside of
+/// Unix.
+#[cfg(not(unix))]
+pub fn listen_fds() -> Option<Vec<TcpListener>> {
+    None
+}
@@ -0,0 +1,93 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Serving the HTTP API over TLS.
+
+use std::{fs, path::Path, process};
+
+use crate::arguments::Arguments;
+
+/// The certificates and keys to serve the HTTP API with over TLS.
+#[derive(Clone, Debug)]
+pub struct TlsConfig {
+    /// The PEM-encoded certificate chain of the server.
+    pub cert: Box<[u8]>,
+    /// The PEM-encoded private key of the server.
+    pub key: Box<[u8]>,
+    /// Whether and how clients are verified by their certificates.
+    pub client_auth: ClientAuth,
+}
+
+/// Represents how clients are verified by their certificates.
+#[derive(Clone, Debug)]
+pub enum ClientAuth {
+    /// Clients are not asked for certificates.
+    None,
+    /// Clients without a certificate are accepted, but those with one must be signed by one of
+    /// these PEM-encoded certificate authorities.
+    Optional(Box<[u8]>),
+    /// Clients must have a certificate signed by one of these PEM-encoded certificate authorities.
+    Required(Box<[u8]>),
+}
+
+/// For a given set of `Arguments`, return the configuration to serve over TLS with, if any.
+///
+/// Exits if only one of the certificate and key are set, or if any of the files cannot be read.
+pub fn get_tls_config(arguments: &Arguments) -> Option<TlsConfig> {
+    let (cert_path, key_path) = match (&arguments.tls_cert_path, &arguments.tls_key_path) {
+        (Some(cert_path), Some(key_path)) => (cert_path, key_path),
+        (None, None) => {
+            if arguments.tls_client_ca_path.is_some() {
+                eprintln!(
+                    "Verifying client certificates requires `--tls-cert-path` and `--tls-key-path`"
+                );
+                process::exit(1);
+            }
+
+            return None;
+        }
+        _ => {
+            eprintln!("Serving over TLS requires both `--tls-cert-path` and `--tls-key-path`");
+            process::exit(1);
+        }
+    };
+
+    let client_ca = arguments.tls_client_ca_path.as_deref().map(read);
+    // Safety: `arguments::get_config()` implements default values
+    let client_auth = match (client_ca, arguments.tls_client_auth_required.unwrap()) {
+        (Some(client_ca), true) => ClientAuth::Required(client_ca),
+        (Some(client_ca), false) => ClientAuth::Optional(client_ca),
+        (None, _) => ClientAuth::None,
+    };
+
+    Some(TlsConfig {
+        cert: read(cert_path),
+        key: read(key_path),
+        client_auth,
+    })
+}
+
+/// Read the file at `path`, exiting if it cannot be read.
+fn read(path: &Path) -> Box<[u8]> {
+    match fs::read(path) {
+        Ok(contents) => contents.into(),
+        Err(error) => {
+            eprintln!("Could not read {}: {error}", path.display());
+            process::exit(1);
+        }
+    }
+}
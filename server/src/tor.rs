@@ -0,0 +1,145 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Counting the relays of a Tor network consensus by country, for `ip_geo_tor_relays` and
+//! `ip_geo_tor_relay_bandwidth`.
+
+use std::{fs, io, sync::Arc, time::Duration};
+
+use ip_geo::tor::{parse_consensus, relays_by_country};
+use tokio::task::JoinHandle;
+
+use crate::{arguments::Arguments, metrics::Metrics, reload::SharedDatabase};
+
+/// Represents failures to read a consensus.
+#[derive(thiserror::Error, Debug)]
+pub enum ReadError {
+    #[error("{0}")]
+    Http(#[from] Box<ureq::Error>),
+
+    #[error("{0}")]
+    Io(#[from] io::Error),
+}
+
+/// If a consensus is configured, spawn a task that periodically reads it and records its relays
+/// by country in `metrics`.
+///
+/// Relays are located with the database current at the time, so a reloaded database is reflected
+/// from the next read.
+pub fn spawn_relay_counter(
+    arguments: &Arguments,
+    database: SharedDatabase,
+    metrics: Arc<Metrics>,
+) -> Option<JoinHandle<()>> {
+    let source = arguments.tor_consensus.clone()?;
+
+    // Safety: `arguments::get_config()` implements default values
+    let interval = Duration::from_secs(arguments.tor_consensus_interval.unwrap());
+
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+
+        loop {
+            interval.tick().await;
+
+            let source = source.clone();
+            let consensus = match tokio::task::spawn_blocking(move || read_consensus(&source)).await
+            {
+                Ok(Ok(consensus)) => consensus,
+                Ok(Err(error)) => {
+                    eprintln!("Could not read the Tor consensus: {error}");
+                    continue;
+                }
+                Err(error) => {
+                    eprintln!("Tor consensus task failed: {error}");
+                    continue;
+                }
+            };
+
+            let relays = parse_consensus(&consensus);
+            metrics.record_tor_relays(relays_by_country(&relays, database.current().database()));
+        }
+    }))
+}
+
+/// Read a consensus from `source`, either an `http://` or `https://` URL or a path.
+fn read_consensus(source: &str) -> Result<String, ReadError> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        let response = ureq::get(source).call().map_err(Box::new)?;
+
+        return Ok(response.into_string()?);
+    }
+
+    Ok(fs::read_to_string(source)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, net::Ipv4Addr};
+
+    use ip_geo::{country_list::get_countries, GeoDatabase, IpAddrEntry, IpAddrMap};
+
+    use super::*;
+    use crate::metrics::{Aggregation, Format};
+
+    #[test]
+    fn test_read_consensus() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(
+            b"network-status-version 3
+r a AAAA BBBB 2024-08-06 12:00:00 192.0.2.1 9001 0
+w Bandwidth=10
+r b CCCC DDDD 2024-08-06 12:00:00 192.0.2.2 9001 0
+w Bandwidth=20
+r c EEEE FFFF 2024-08-06 12:00:00 198.51.100.1 9001 0
+w Bandwidth=40
+directory-footer
+",
+        )
+        .unwrap();
+
+        let consensus = read_consensus(file.path().to_str().unwrap()).unwrap();
+        assert!(read_consensus("/nonexistent/cached-consensus").is_err());
+
+        let mut ipv4_map = IpAddrMap::new();
+        ipv4_map.insert(
+            IpAddrEntry::new(
+                Ipv4Addr::new(192, 0, 2, 0),
+                Ipv4Addr::new(192, 0, 2, 255),
+                get_countries().get("BE").unwrap().clone(),
+            )
+            .unwrap(),
+        );
+        let database = GeoDatabase::new(ipv4_map, IpAddrMap::new());
+        let metrics = Metrics::new(Aggregation::Country, &database, None, Duration::ZERO);
+        assert!(!metrics.render(Format::Text).contains("ip_geo_tor_relays"));
+
+        let relays = parse_consensus(&consensus);
+        metrics.record_tor_relays(relays_by_country(&relays, &database));
+        let text = metrics.render(Format::Text);
+
+        for line in [
+            "# TYPE ip_geo_tor_relays gauge",
+            "ip_geo_tor_relays{country_code=\"BE\"} 2",
+            "ip_geo_tor_relays{country_code=\"??\"} 1",
+            "ip_geo_tor_relay_bandwidth{country_code=\"BE\"} 30",
+            "ip_geo_tor_relay_bandwidth{country_code=\"??\"} 40",
+        ] {
+            assert!(text.lines().any(|l| l == line), "{line} in {text}");
+        }
+    }
+}
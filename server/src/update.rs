@@ -0,0 +1,95 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+use std::{fs, path::PathBuf, time::Duration};
+
+use ip_geo::update::{MaxMindUpdater, UpdateOutcome};
+use tokio::task::JoinHandle;
+
+use crate::{arguments::Arguments, reload::Reloader};
+
+/// If MaxMind credentials are configured, spawn a task that periodically downloads the configured
+/// GeoLite2 databases, reloading with `reloader` whenever the database at `mmdb_path` is updated.
+pub fn spawn_maxmind_updater(arguments: &Arguments, reloader: Reloader) -> Option<JoinHandle<()>> {
+    let account_id = arguments.maxmind_account_id.as_ref()?;
+    let license_key = arguments.maxmind_license_key.as_ref()?;
+
+    // Safety: `arguments::get_config()` implements default values
+    let directory = arguments.maxmind_db_dir.clone().unwrap();
+    let editions = arguments.maxmind_editions.clone().unwrap();
+    let keep = arguments.maxmind_keep.unwrap();
+    let interval = Duration::from_secs(arguments.maxmind_update_interval.unwrap() * 60 * 60);
+    let mmdb_path = arguments.mmdb_path.clone();
+
+    let updater = MaxMindUpdater::new(account_id, license_key, directory).keep(keep);
+
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+
+        loop {
+            interval.tick().await;
+
+            let updater = updater.clone();
+            let editions = editions.clone();
+
+            let result = tokio::task::spawn_blocking(move || update_all(&updater, &editions)).await;
+
+            let updated = match result {
+                Ok(updated) => updated,
+                Err(error) => {
+                    eprintln!("MaxMind update task failed: {error}");
+                    continue;
+                }
+            };
+
+            let is_served = |path: &PathBuf| {
+                mmdb_path.as_deref().is_some_and(|mmdb_path| {
+                    mmdb_path == path.as_path()
+                        || matches!(
+                            (fs::canonicalize(mmdb_path), fs::canonicalize(path)),
+                            (Ok(a), Ok(b)) if a == b
+                        )
+                })
+            };
+            if updated.iter().any(is_served) {
+                // Failures are already logged, and the current databases kept
+                let _ = reloader.reload().await;
+            }
+        }
+    }))
+}
+
+/// Update each edition in turn, logging the outcome, and returning the paths of those that were
+/// updated.
+fn update_all(updater: &MaxMindUpdater, editions: &[Box<str>]) -> Vec<PathBuf> {
+    let mut updated = Vec::new();
+
+    for edition in editions {
+        match updater.update(edition) {
+            Ok(UpdateOutcome::Updated { path, sha256 }) => {
+                println!("Updated {edition} at {} (SHA-256 {sha256})", path.display());
+                updated.push(path);
+            }
+            Ok(UpdateOutcome::UpToDate { path }) => {
+                println!("{edition} at {} is up to date", path.display())
+            }
+            Err(error) => eprintln!("Failed to update {edition}: {error}"),
+        }
+    }
+
+    updated
+}
@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Looking up the autonomous system (AS) that announces an IP address.
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    path::Path,
+    sync::Arc,
+};
+
+use crate::{parse::MalformedLine, Error, IpAddrEntry, IpAddrMap};
+
+/// An autonomous system, ex. `AS13335 CLOUDFLARENET`.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct AutonomousSystem {
+    /// The AS number, ex. `13335`.
+    pub number: u32,
+    /// A description of the organization operating the AS, ex. "CLOUDFLARENET".
+    pub organization: Arc<str>,
+}
+
+/// Stores the autonomous systems announcing IPv4 and IPv6 address ranges.
+///
+/// Example usage:
+///
+/// ```rust
+/// use std::net::IpAddr;
+/// use ip_geo::asn::AsnDatabase;
+///
+/// let tsv = "\
+/// 1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET
+/// 1.0.1.0\t1.0.3.255\t0\tNone\tNot routed
+/// 2001:db8::\t2001:db8::ffff\t64496\tZZ\tEXAMPLE
+/// ";
+/// let database = AsnDatabase::parse(tsv.as_bytes()).unwrap();
+///
+/// let cloudflare = database.lookup("1.0.0.1".parse().unwrap()).unwrap();
+/// assert_eq!(cloudflare.number, 13335);
+/// assert_eq!(&*cloudflare.organization, "CLOUDFLARENET");
+///
+/// assert_eq!(database.lookup("2001:db8::1".parse().unwrap()).unwrap().number, 64496);
+/// assert!(database.lookup("1.0.2.1".parse().unwrap()).is_err());
+/// ```
+#[derive(Debug, Default)]
+pub struct AsnDatabase {
+    ipv4: IpAddrMap<Ipv4Addr, AutonomousSystem>,
+    ipv6: IpAddrMap<Ipv6Addr, AutonomousSystem>,
+}
+
+impl AsnDatabase {
+    /// Parse a database of autonomous systems in the tab-separated format of
+    /// [iptoasn.com](https://iptoasn.com/) (ex. `ip2asn-combined.tsv`), with lines in the format of
+    /// `<start> <end> <as_number> <country_code> <description>`.
+    ///
+    /// Ranges that are not routed (with AS number 0) are left out.
+    pub fn parse(reader: impl BufRead) -> Result<Self, Error> {
+        let mut database = Self::default();
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let malformed =
+                |reason| Error::MalformedLine(MalformedLine::new(index as u64 + 1, reason));
+
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split('\t');
+            let mut next_field = |name: &'static str| fields.next().ok_or_else(|| malformed(name));
+
+            let start = next_field("missing start address")?;
+            let end = next_field("missing end address")?;
+            let number = next_field("missing AS number")?;
+            next_field("missing country code")?;
+            let organization = next_field("missing description")?;
+
+            let number: u32 = number.parse().map_err(|_| malformed("invalid AS number"))?;
+            if number == 0 {
+                continue;
+            }
+
+            let system = AutonomousSystem {
+                number,
+                organization: organization.into(),
+            };
+
+            let start = start
+                .parse()
+                .map_err(|_| malformed("invalid start address"))?;
+            let end = end.parse().map_err(|_| malformed("invalid end address"))?;
+
+            match (start, end) {
+                (IpAddr::V4(start), IpAddr::V4(end)) => {
+                    database.ipv4.insert(IpAddrEntry::new(start, end, system)?)
+                }
+                (IpAddr::V6(start), IpAddr::V6(end)) => {
+                    database.ipv6.insert(IpAddrEntry::new(start, end, system)?)
+                }
+                _ => return Err(malformed("start and end addresses of different families")),
+            }
+        }
+
+        database.ipv4.cleanup();
+        database.ipv6.cleanup();
+
+        Ok(database)
+    }
+
+    /// Parse a database of autonomous systems from a file, in the format described by
+    /// [`Self::parse`].
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        Self::parse(BufReader::new(File::open(path)?))
+    }
+
+    /// Return the autonomous system announcing `address`.
+    pub fn lookup(&self, address: IpAddr) -> Result<&AutonomousSystem, Error> {
+        match address {
+            IpAddr::V4(address) => self.ipv4.try_search(address),
+            IpAddr::V6(address) => self.ipv6.try_search(address),
+        }
+    }
+
+    /// Return the IPv4 ranges of the database.
+    pub const fn ipv4(&self) -> &IpAddrMap<Ipv4Addr, AutonomousSystem> {
+        &self.ipv4
+    }
+
+    /// Return the IPv6 ranges of the database.
+    pub const fn ipv6(&self) -> &IpAddrMap<Ipv6Addr, AutonomousSystem> {
+        &self.ipv6
+    }
+}
@@ -0,0 +1,295 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+use alloc::vec::Vec;
+use core::{
+    fmt::Display,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+use crate::{Error, IpAddrEntry};
+
+/// An IP address type that can be converted to and from its bits, for working with CIDR blocks.
+pub trait Address: Ord + Copy + Display {
+    /// The number of bits in the address.
+    const BITS: u32;
+
+    /// Return the address as an integer, zero-extended to a `u128`.
+    fn to_u128(self) -> u128;
+
+    /// Create an address from an integer. Bits beyond `Self::BITS` are ignored.
+    fn from_u128(bits: u128) -> Self;
+}
+
+impl Address for Ipv4Addr {
+    const BITS: u32 = 32;
+
+    fn to_u128(self) -> u128 {
+        self.to_bits().into()
+    }
+
+    fn from_u128(bits: u128) -> Self {
+        Self::from_bits(bits as u32)
+    }
+}
+
+impl Address for Ipv6Addr {
+    const BITS: u32 = 128;
+
+    fn to_u128(self) -> u128 {
+        self.to_bits()
+    }
+
+    fn from_u128(bits: u128) -> Self {
+        Self::from_bits(bits)
+    }
+}
+
+/// An inclusive range of IP addresses: `(start, end)`.
+pub type AddrRange<A> = (A, A);
+
+/// A block of IP addresses in CIDR notation, ex. `192.0.2.0/24`.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Cidr<A: Address> {
+    /// The first address in the block.
+    pub address: A,
+    /// The number of leading bits shared by every address in the block.
+    pub prefix_len: u8,
+}
+
+impl<A: Address> Cidr<A> {
+    /// Return whether `address` is in the block.
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// use std::net::Ipv4Addr;
+    /// use ip_geo::cidr::Cidr;
+    ///
+    /// let cidr: Cidr<Ipv4Addr> = "10.0.0.0/8".parse().unwrap();
+    ///
+    /// assert!(cidr.contains(Ipv4Addr::new(10, 1, 2, 3)));
+    /// assert!(!cidr.contains(Ipv4Addr::new(192, 0, 2, 1)));
+    /// ```
+    pub fn contains(&self, address: A) -> bool {
+        let host_bits = A::BITS - u32::from(self.prefix_len);
+        // Shifting out every bit of a `u128` overflows, but every address is in a `/0`
+        match address.to_u128().checked_shr(host_bits) {
+            Some(network) => Some(network) == self.address.to_u128().checked_shr(host_bits),
+            None => true,
+        }
+    }
+}
+
+impl<A: Address> Display for Cidr<A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix_len)
+    }
+}
+
+impl<A: Address + FromStr> FromStr for Cidr<A> {
+    type Err = Error;
+
+    /// Parse a block in CIDR notation, ex. `192.0.2.0/24`, or a single address, ex. `192.0.2.1`.
+    ///
+    /// Bits of the address past the prefix length are cleared, so `192.0.2.1/24` is parsed as
+    /// `192.0.2.0/24`.
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// use std::net::Ipv6Addr;
+    /// use ip_geo::cidr::Cidr;
+    ///
+    /// let cidr: Cidr<Ipv6Addr> = "2001:db8::1/32".parse().unwrap();
+    ///
+    /// assert_eq!(cidr.to_string(), "2001:db8::/32");
+    /// assert!("2001:db8::/129".parse::<Cidr<Ipv6Addr>>().is_err());
+    /// ```
+    fn from_str(cidr: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidCidr(cidr.into());
+
+        let (address, prefix_len) = match cidr.split_once('/') {
+            Some((address, prefix_len)) => {
+                let prefix_len: u8 = prefix_len.parse().map_err(|_| invalid())?;
+                (address, prefix_len)
+            }
+            None => (cidr, A::BITS as u8),
+        };
+        if u32::from(prefix_len) > A::BITS {
+            return Err(invalid());
+        }
+        let address: A = address.parse().map_err(|_| invalid())?;
+
+        // Keep only the leading `prefix_len` bits, where shifting by every bit of a `u128`
+        // overflows
+        let host_bits = A::BITS - u32::from(prefix_len);
+        let mask = u128::MAX.checked_shl(host_bits).unwrap_or(0);
+
+        Ok(Self {
+            address: A::from_u128(address.to_u128() & mask),
+            prefix_len,
+        })
+    }
+}
+
+/// A block of either IPv4 or IPv6 addresses in CIDR notation.
+///
+/// Example usage:
+///
+/// ```rust
+/// use std::net::IpAddr;
+/// use ip_geo::cidr::IpCidr;
+///
+/// let cidr: IpCidr = "fd00::/8".parse().unwrap();
+///
+/// assert!(cidr.contains("fd12::1".parse().unwrap()));
+/// assert!(!cidr.contains("10.0.0.1".parse().unwrap()));
+/// ```
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IpCidr {
+    V4(Cidr<Ipv4Addr>),
+    V6(Cidr<Ipv6Addr>),
+}
+
+impl IpCidr {
+    /// Return whether `address` is in the block. Addresses of the other family never are.
+    pub fn contains(&self, address: IpAddr) -> bool {
+        match (self, address) {
+            (Self::V4(cidr), IpAddr::V4(address)) => cidr.contains(address),
+            (Self::V6(cidr), IpAddr::V6(address)) => cidr.contains(address),
+            _ => false,
+        }
+    }
+}
+
+impl Display for IpCidr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::V4(cidr) => cidr.fmt(f),
+            Self::V6(cidr) => cidr.fmt(f),
+        }
+    }
+}
+
+impl FromStr for IpCidr {
+    type Err = Error;
+
+    /// Parse a block of IPv4 or IPv6 addresses in CIDR notation, like [`Cidr::from_str`].
+    fn from_str(cidr: &str) -> Result<Self, Self::Err> {
+        match cidr.contains(':') {
+            true => cidr.parse().map(Self::V6),
+            false => cidr.parse().map(Self::V4),
+        }
+    }
+}
+
+/// Split an inclusive range of addresses into the smallest list of CIDR blocks that covers it
+/// exactly.
+///
+/// Example usage:
+///
+/// ```rust
+/// use std::net::Ipv4Addr;
+/// use ip_geo::cidr::range_to_cidrs;
+///
+/// let cidrs = range_to_cidrs(Ipv4Addr::new(192, 0, 2, 0), Ipv4Addr::new(192, 0, 3, 127));
+/// let cidrs: Vec<String> = cidrs.iter().map(ToString::to_string).collect();
+///
+/// assert_eq!(cidrs, ["192.0.2.0/24", "192.0.3.0/25"]);
+/// ```
+pub fn range_to_cidrs<A: Address>(start: A, end: A) -> Vec<Cidr<A>> {
+    let mut cidrs = Vec::new();
+
+    let mut start = start.to_u128();
+    let end = end.to_u128();
+
+    while start <= end {
+        // The largest block that is aligned at `start`
+        let alignment = start.trailing_zeros().min(A::BITS);
+
+        // The largest block that does not extend beyond `end`, where `end - start + 1` may
+        // overflow for the whole IPv6 address space
+        let remaining = end - start;
+        let fits = match remaining.checked_add(1) {
+            Some(len) => len.ilog2(),
+            None => 128,
+        };
+
+        let size = alignment.min(fits);
+
+        cidrs.push(Cidr {
+            address: A::from_u128(start),
+            prefix_len: (A::BITS - size) as u8,
+        });
+
+        // Stop at the end of the range, without overflowing past the last address
+        match 1u128.checked_shl(size) {
+            Some(block) if block <= remaining => start += block,
+            _ => break,
+        }
+    }
+
+    cidrs
+}
+
+/// Merge sorted, non-overlapping inclusive ranges where one range ends directly before the next
+/// begins.
+///
+/// Example usage:
+///
+/// ```rust
+/// use std::net::Ipv4Addr;
+/// use ip_geo::cidr::merge_ranges;
+///
+/// let merged = merge_ranges([
+///     (Ipv4Addr::new(1, 0, 0, 0), Ipv4Addr::new(1, 0, 0, 255)),
+///     (Ipv4Addr::new(1, 0, 1, 0), Ipv4Addr::new(1, 0, 1, 255)),
+///     (Ipv4Addr::new(1, 0, 3, 0), Ipv4Addr::new(1, 0, 3, 255)),
+/// ]);
+///
+/// assert_eq!(
+///     merged,
+///     [
+///         (Ipv4Addr::new(1, 0, 0, 0), Ipv4Addr::new(1, 0, 1, 255)),
+///         (Ipv4Addr::new(1, 0, 3, 0), Ipv4Addr::new(1, 0, 3, 255)),
+///     ]
+/// );
+/// ```
+pub fn merge_ranges<A: Address>(
+    ranges: impl IntoIterator<Item = AddrRange<A>>,
+) -> Vec<AddrRange<A>> {
+    let mut merged: Vec<AddrRange<A>> = Vec::new();
+
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if last.1.to_u128().checked_add(1) == Some(start.to_u128()) => last.1 = end,
+            _ => merged.push((start, end)),
+        }
+    }
+
+    merged
+}
+
+impl<A: Address, T> IpAddrEntry<A, T> {
+    /// Split the stored IP address range into the smallest list of CIDR blocks that covers it
+    /// exactly.
+    pub fn cidrs(&self) -> Vec<Cidr<A>> {
+        range_to_cidrs(*self.start(), *self.end())
+    }
+}
@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Grouping countries by continent and European Union membership.
+
+use core::fmt::Display;
+
+use crate::country_list::Country;
+
+/// The codes of the member states of the European Union.
+const EU_MEMBERS: [&str; 27] = [
+    "AT", "BE", "BG", "CY", "CZ", "DE", "DK", "EE", "ES", "FI", "FR", "GR", "HR", "HU", "IE", "IT",
+    "LT", "LU", "LV", "MT", "NL", "PL", "PT", "RO", "SE", "SI", "SK",
+];
+
+/// Represents the seven continents.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Continent {
+    Africa,
+    Antarctica,
+    Asia,
+    Europe,
+    NorthAmerica,
+    Oceania,
+    SouthAmerica,
+}
+
+impl Continent {
+    /// Every continent, in alphabetical order.
+    pub const ALL: [Self; 7] = [
+        Self::Africa,
+        Self::Antarctica,
+        Self::Asia,
+        Self::Europe,
+        Self::NorthAmerica,
+        Self::Oceania,
+        Self::SouthAmerica,
+    ];
+
+    /// Return the continent of a country from its ISO 3166-1 alpha-2 code, if it has one.
+    ///
+    /// Regional codes are assigned to the continent of their region (ex. "EU" is in Europe, "AP"
+    /// is in Asia).
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// use ip_geo::continent::Continent;
+    ///
+    /// assert_eq!(Continent::from_country_code("BE"), Some(Continent::Europe));
+    /// assert_eq!(Continent::from_country_code("??"), None);
+    /// ```
+    #[rustfmt::skip]
+    pub fn from_country_code(code: &str) -> Option<Self> {
+        match code {
+            "AO" | "BF" | "BI" | "BJ" | "BW" | "CD" | "CF" | "CG" | "CI" | "CM" | "CV" | "DJ" |
+            "DZ" | "EG" | "EH" | "ER" | "ET" | "GA" | "GH" | "GM" | "GN" | "GQ" | "GW" | "KE" |
+            "KM" | "LR" | "LS" | "LY" | "MA" | "MG" | "ML" | "MR" | "MU" | "MW" | "MZ" | "NA" |
+            "NE" | "NG" | "RE" | "RW" | "SC" | "SD" | "SH" | "SL" | "SN" | "SO" | "SS" | "ST" |
+            "SZ" | "TD" | "TG" | "TN" | "TZ" | "UG" | "YT" | "ZA" | "ZM" | "ZW"
+            => Some(Self::Africa),
+            "AQ" | "BV" | "GS" | "HM" | "TF"
+            => Some(Self::Antarctica),
+            "AE" | "AF" | "AM" | "AP" | "AZ" | "BD" | "BH" | "BN" | "BT" | "CC" | "CN" | "CX" |
+            "GE" | "HK" | "ID" | "IL" | "IN" | "IO" | "IQ" | "IR" | "JO" | "JP" | "KG" | "KH" |
+            "KP" | "KR" | "KW" | "KZ" | "LA" | "LB" | "LK" | "MM" | "MN" | "MO" | "MV" | "MY" |
+            "NP" | "OM" | "PH" | "PK" | "PS" | "QA" | "SA" | "SG" | "SY" | "TH" | "TJ" | "TL" |
+            "TM" | "TR" | "TW" | "UZ" | "VN" | "YE"
+            => Some(Self::Asia),
+            "AD" | "AL" | "AT" | "AX" | "BA" | "BE" | "BG" | "BY" | "CH" | "CS" | "CY" | "CZ" |
+            "DE" | "DK" | "EE" | "ES" | "EU" | "FI" | "FO" | "FR" | "FX" | "GB" | "GG" | "GI" |
+            "GR" | "HR" | "HU" | "IE" | "IM" | "IS" | "IT" | "JE" | "LI" | "LT" | "LU" | "LV" |
+            "MC" | "MD" | "ME" | "MK" | "MT" | "NL" | "NO" | "PL" | "PT" | "RO" | "RS" | "RU" |
+            "SE" | "SI" | "SJ" | "SK" | "SM" | "UA" | "VA"
+            => Some(Self::Europe),
+            "AG" | "AI" | "AN" | "AW" | "BB" | "BL" | "BM" | "BQ" | "BS" | "BZ" | "CA" | "CR" |
+            "CU" | "CW" | "DM" | "DO" | "GD" | "GL" | "GP" | "GT" | "HN" | "HT" | "JM" | "KN" |
+            "KY" | "LC" | "MF" | "MQ" | "MS" | "MX" | "NI" | "PA" | "PM" | "PR" | "SV" | "SX" |
+            "TC" | "TT" | "US" | "VC" | "VG" | "VI"
+            => Some(Self::NorthAmerica),
+            "AS" | "AU" | "CK" | "FJ" | "FM" | "GU" | "KI" | "MH" | "MP" | "NC" | "NF" | "NR" |
+            "NU" | "NZ" | "PF" | "PG" | "PN" | "PW" | "SB" | "TK" | "TO" | "TV" | "UM" | "VU" |
+            "WF" | "WS"
+            => Some(Self::Oceania),
+            "AR" | "BO" | "BR" | "CL" | "CO" | "EC" | "FK" | "GF" | "GY" | "PE" | "PY" | "SR" |
+            "UY" | "VE"
+            => Some(Self::SouthAmerica),
+            _ => None,
+        }
+    }
+
+    /// Return the two letter code of the continent.
+    ///
+    /// Ex. "EU" for Europe.
+    pub const fn code(&self) -> &'static str {
+        match self {
+            Self::Africa => "AF",
+            Self::Antarctica => "AN",
+            Self::Asia => "AS",
+            Self::Europe => "EU",
+            Self::NorthAmerica => "NA",
+            Self::Oceania => "OC",
+            Self::SouthAmerica => "SA",
+        }
+    }
+
+    /// Return the full name of the continent.
+    ///
+    /// Ex. "North America".
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Africa => "Africa",
+            Self::Antarctica => "Antarctica",
+            Self::Asia => "Asia",
+            Self::Europe => "Europe",
+            Self::NorthAmerica => "North America",
+            Self::Oceania => "Oceania",
+            Self::SouthAmerica => "South America",
+        }
+    }
+}
+
+impl Display for Continent {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl Country {
+    /// Return the continent of the country, if it has one.
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// use ip_geo::{continent::Continent, country_list::get_countries};
+    ///
+    /// let countries = get_countries();
+    ///
+    /// assert_eq!(countries.get("BR").unwrap().continent(), Some(Continent::SouthAmerica));
+    /// assert_eq!(countries.get("??").unwrap().continent(), None);
+    /// ```
+    pub fn continent(&self) -> Option<Continent> {
+        Continent::from_country_code(&self.code)
+    }
+
+    /// Returns true if the country is a member state of the European Union, or is the code for
+    /// the European Union itself ("EU").
+    ///
+    /// Territories of member states with their own codes (ex. "RE" for Réunion) are not included.
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// use ip_geo::country_list::get_countries;
+    ///
+    /// let countries = get_countries();
+    ///
+    /// assert!(countries.get("BE").unwrap().is_eu());
+    /// assert!(!countries.get("CH").unwrap().is_eu());
+    /// ```
+    pub fn is_eu(&self) -> bool {
+        Self::is_eu_code(&self.code)
+    }
+
+    /// Returns true if a country code is that of a member state of the European Union, or "EU".
+    pub(crate) fn is_eu_code(code: &str) -> bool {
+        code == "EU" || EU_MEMBERS.contains(&code)
+    }
+}
@@ -15,11 +15,174 @@
 // You should have received a copy of the GNU Affero General Public License along with ip_geo. If
 // not, see <https://www.gnu.org/licenses/>.
 
-use std::sync::Arc;
+use alloc::{boxed::Box, collections::BTreeMap, string::ToString, sync::Arc};
 
 use serde::Serialize;
 
-use crate::country_list::Country;
+use crate::{
+    continent::Continent,
+    country_list::Country,
+    country_names::{LANGUAGES, NAMES},
+    IpAddrMap,
+};
+
+/// The country code used for addresses that have no associated country.
+pub const UNKNOWN_COUNTRY_CODE: &str = "??";
+
+/// The language of the names in `country_list`, which every other language falls back to.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Return the index into `country_names::LANGUAGES` of the translation that best matches
+/// `language`, a gettext locale name or BCP 47 tag (ex. "pt_BR" or "pt-BR").
+///
+/// A language with a region that has no translation of its own falls back to the language without
+/// it, or to any of its regions (ex. "de-AT" to "de", or "zh" to "zh_CN"). Returns `None` for
+/// English, as it is the language of `country_list`, and for languages with no translation.
+///
+/// Example usage:
+///
+/// ```rust
+/// use ip_geo::{country::find_language, country_names::LANGUAGES};
+///
+/// assert_eq!(LANGUAGES[find_language("pt-BR").unwrap()], "pt_BR");
+/// assert_eq!(LANGUAGES[find_language("de_AT").unwrap()], "de");
+/// assert_eq!(LANGUAGES[find_language("zh").unwrap()], "zh_CN");
+/// assert_eq!(find_language("en"), None);
+/// ```
+pub fn find_language(language: &str) -> Option<usize> {
+    let language = language.replace('-', "_");
+    let base = language.split('_').next().unwrap_or(&language);
+
+    let position = |matches: &dyn Fn(&str) -> bool| LANGUAGES.iter().position(|l| matches(l));
+
+    position(&|l| l.eq_ignore_ascii_case(&language))
+        .or_else(|| position(&|l| l.eq_ignore_ascii_case(base)))
+        .or_else(|| {
+            position(&|l| {
+                l.split_once('_')
+                    .is_some_and(|(l, _)| l.eq_ignore_ascii_case(base))
+            })
+        })
+}
+
+/// Return whether country names can be given in `language`, either because it is English or
+/// because `find_language` finds a translation for it.
+///
+/// Example usage:
+///
+/// ```rust
+/// use ip_geo::country::is_supported_language;
+///
+/// assert!(is_supported_language("en-US"));
+/// assert!(is_supported_language("fr"));
+/// assert!(!is_supported_language("tlh"));
+/// ```
+pub fn is_supported_language(language: &str) -> bool {
+    let base = language.split(['-', '_']).next().unwrap_or(language);
+
+    base.eq_ignore_ascii_case(DEFAULT_LANGUAGE) || find_language(language).is_some()
+}
+
+impl Country {
+    /// Return the placeholder country for addresses that have no associated country, with the
+    /// code `UNKNOWN_COUNTRY_CODE`.
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// use ip_geo::country_list::{get_countries, Country};
+    ///
+    /// assert_eq!(&Country::unknown(), get_countries().get("??").unwrap());
+    /// ```
+    pub fn unknown() -> Self {
+        Self {
+            name: "Unknown".into(),
+            code: UNKNOWN_COUNTRY_CODE.into(),
+            coordinates: (0.0, 0.0),
+            time_zone: None,
+        }
+    }
+
+    /// Return the name of the country in `language` (see `find_language`), or `None` if it has
+    /// not been translated into it, in which case `name` is the English name to fall back to.
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// use ip_geo::country_list::get_countries;
+    ///
+    /// let belgium = get_countries().remove("BE").unwrap();
+    ///
+    /// assert_eq!(belgium.localized_name("de"), Some("Belgien"));
+    /// assert_eq!(belgium.localized_name("en"), None);
+    /// ```
+    pub fn localized_name(&self, language: &str) -> Option<&'static str> {
+        let language = find_language(language)?;
+        let index = NAMES
+            .binary_search_by(|(code, _)| (*code).cmp(&self.code))
+            .ok()?;
+
+        NAMES[index].1[language]
+    }
+
+    /// Return the country with its name in `language`, falling back to the English name if it has
+    /// not been translated into it.
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// use ip_geo::country_list::get_countries;
+    ///
+    /// let countries = get_countries();
+    /// let belgium = countries.get("BE").unwrap().clone();
+    /// let unknown = countries.get("??").unwrap().clone();
+    ///
+    /// assert_eq!(belgium.localized("fr").name.as_ref(), "Belgique");
+    /// assert_eq!(unknown.localized("fr").name.as_ref(), "Unknown");
+    /// ```
+    pub fn localized(self, language: &str) -> Self {
+        match self.localized_name(language) {
+            Some(name) => Self {
+                name: name.into(),
+                ..self
+            },
+            None => self,
+        }
+    }
+}
+
+impl<A: Ord + Copy> IpAddrMap<A, Country> {
+    /// Rename every country in the map to its name in `language` (see `Country::localized`),
+    /// sharing one name between all entries of each country.
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// use std::net::Ipv4Addr;
+    /// use ip_geo::{country_list::get_countries, IpAddrEntry, IpAddrMap};
+    ///
+    /// let belgium = get_countries().get("BE").unwrap().clone();
+    /// let mut map = IpAddrMap::new();
+    /// map.insert(
+    ///     IpAddrEntry::new(Ipv4Addr::new(1, 1, 1, 1), Ipv4Addr::new(3, 3, 3, 3), belgium).unwrap(),
+    /// );
+    /// map.localize("nl");
+    ///
+    /// assert_eq!(map.search(Ipv4Addr::new(2, 2, 2, 2)).unwrap().name.as_ref(), "België");
+    /// ```
+    pub fn localize(&mut self, language: &str) {
+        let mut names: BTreeMap<Arc<str>, Arc<str>> = BTreeMap::new();
+
+        for country in self.values_mut() {
+            let name = names.entry(country.code.clone()).or_insert_with(|| {
+                country
+                    .localized_name(language)
+                    .map_or_else(|| country.name.clone(), Arc::from)
+            });
+            country.name = name.clone();
+        }
+    }
+}
 
 impl PartialEq for Country {
     fn eq(&self, other: &Self) -> bool {
@@ -32,14 +195,30 @@ struct SerializableCountry {
     code: Box<str>,
     name: Box<str>,
     coordinates: (f64, f64),
+    #[serde(skip_serializing_if = "Option::is_none")]
+    time_zone: Option<Box<str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    continent: Option<&'static str>,
+    is_eu: bool,
 }
 
 impl SerializableCountry {
-    fn new(code: Box<str>, name: Box<str>, coordinates: (f64, f64)) -> Self {
+    fn new(
+        code: Box<str>,
+        name: Box<str>,
+        coordinates: (f64, f64),
+        time_zone: Option<Box<str>>,
+    ) -> Self {
+        let continent = Continent::from_country_code(&code).map(|c| c.code());
+        let is_eu = Country::is_eu_code(&code);
+
         Self {
             code,
             name,
             coordinates,
+            time_zone,
+            continent,
+            is_eu,
         }
     }
 }
@@ -48,7 +227,12 @@ impl From<Country> for SerializableCountry {
     fn from(value: Country) -> Self {
         let to_box = |s: Arc<str>| s.to_string().into_boxed_str();
 
-        SerializableCountry::new(to_box(value.code), to_box(value.name), value.coordinates)
+        SerializableCountry::new(
+            to_box(value.code),
+            to_box(value.name),
+            value.coordinates,
+            value.time_zone.map(to_box),
+        )
     }
 }
 
@@ -56,7 +240,12 @@ impl From<&Country> for SerializableCountry {
     fn from(value: &Country) -> Self {
         let to_box = |s: &Arc<str>| s.clone().to_string().into_boxed_str();
 
-        SerializableCountry::new(to_box(&value.code), to_box(&value.name), value.coordinates)
+        SerializableCountry::new(
+            to_box(&value.code),
+            to_box(&value.name),
+            value.coordinates,
+            value.time_zone.as_ref().map(to_box),
+        )
     }
 }
 
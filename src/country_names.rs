@@ -0,0 +1,277 @@
+// This file was @generated by ip_geo/geo using iso-codes 4.15.0 at 2026-10-15T13:02:11Z. Do not edit!
+
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+/// The languages that country names are translated into, as gettext locale names.
+#[rustfmt::skip]
+pub const LANGUAGES: [&str; 18] = ["ar", "de", "es", "fr", "hi", "it", "ja", "ko", "nl", "pl", "pt", "pt_BR", "ru", "sv", "tr", "uk", "zh_CN", "zh_TW"];
+
+/// The names of countries in each of `LANGUAGES`, where they have been translated, sorted by ISO
+/// 3166-1 alpha-2 code.
+#[rustfmt::skip]
+pub static NAMES: [(&str, [Option<&str>; 18]); 249] = [
+    ("AD", [Some("أندورا"), Some("Andorra"), Some("Andorra"), Some("Andorre"), Some("अण्डोरा"), Some("Andorra"), Some("アンドラ"), Some("안도라"), Some("Andorra"), Some("Andora"), Some("Andorra"), Some("Andorra"), Some("Андорра"), Some("Andorra"), Some("Andorra"), Some("Андорра"), Some("安道尔"), Some("安道爾")]),
+    ("AE", [Some("الإمارات العربيّة المتحدّة"), Some("Vereinigte Arabische Emirate"), Some("Emiratos Árabes Unidos"), Some("Émirats arabes unis"), Some("संयुक्त अरब अमीरात"), Some("Emirati Arabi Uniti"), Some("アラブ首長国連邦"), Some("아랍에미리트"), Some("Verenigde Arabische Emiraten"), Some("Zjednoczone Emiraty Arabskie"), Some("Emirados Árabes Unidos"), Some("Emirados Árabes Unidos"), Some("Объединённые Арабские Эмираты"), Some("Förenade Arabemiraten"), Some("Birleşik Arap Emirlikleri"), Some("Об’єднані Арабські Емірати"), Some("阿联酋"), Some("阿拉伯聯合大公國")]),
+    ("AF", [Some("أفغانستان"), Some("Afghanistan"), Some("Afganistán"), Some("Afghanistan"), Some("अफ़्गानिस्तान"), Some("Afghanistan"), Some("アフガニスタン"), Some("아프가니스탄"), Some("Afghanistan"), Some("Afganistan"), Some("Afeganistão"), Some("Afeganistão"), Some("Афганистан"), Some("Afghanistan"), Some("Afganistan"), Some("Афганістан"), Some("阿富汗"), Some("阿富汗")]),
+    ("AG", [Some("أنتيغوا و باربودا"), Some("Antigua und Barbuda"), Some("Antigua y Barbuda"), Some("Antigua-et-Barbuda"), Some("अण्टीगुआ और बारबूडा"), Some("Antigua e Barbuda"), Some("アンティグア・バーブーダ"), Some("앤티가 바부다"), Some("Antigua en Barbuda"), Some("Antigua i Barbuda"), Some("Antígua e Barbuda"), Some("Antígua e Barbuda"), Some("Антигуа и Барбуда"), Some("Antigua och Barbuda"), Some("Antigua ve Barbuda"), Some("Антигуа і Барбуда"), Some("安提瓜和巴布达"), Some("安地卡及巴布達")]),
+    ("AI", [Some("أنغويلا"), Some("Anguilla"), Some("Anguila"), Some("Anguilla"), Some("अंगुइला"), Some("Anguilla"), Some("アングイラ"), Some("앵귈라"), Some("Anguilla"), Some("Anguilla"), Some("Anguilla"), Some("Anguila"), Some("Ангвилла"), Some("Anguilla"), Some("Anguilla"), Some("Ангілья"), Some("安圭拉"), Some("安圭拉")]),
+    ("AL", [Some("ألبانيا"), Some("Albanien"), Some("Albania"), Some("Albanie"), Some("अल्बानिया"), Some("Albania"), Some("アルバニア"), Some("알바니아"), Some("Albanië"), Some("Albania"), Some("Albânia"), Some("Albânia"), Some("Албания"), Some("Albanien"), Some("Arnavutluk"), Some("Албанія"), Some("阿尔巴尼亚"), Some("阿爾巴尼亞")]),
+    ("AM", [Some("أرمينيا"), Some("Armenien"), Some("Armenia"), Some("Arménie"), Some("आर्मीनिया"), Some("Armenia"), Some("アルメニア"), Some("아르메니아"), Some("Armenië"), Some("Armenia"), Some("Arménia"), Some("Armênia"), Some("Армения"), Some("Armenien"), Some("Ermenistan"), Some("Вірменія"), Some("亚美尼亚"), Some("亞美尼亞")]),
+    ("AO", [Some("أنغولا"), Some("Angola"), Some("Angola"), Some("Angola"), Some("अंगोला"), Some("Angola"), Some("アンゴラ"), Some("앙골라"), Some("Angola"), Some("Angola"), Some("Angola"), Some("Angola"), Some("Ангола"), Some("Angola"), Some("Angola"), Some("Ангола"), Some("安哥拉"), Some("安哥拉")]),
+    ("AQ", [Some("القطب الجنوبي"), Some("Antarktis"), Some("Antártida"), Some("Antarctique"), Some("अंटार्कटिका"), Some("Antartide"), Some("南極大陸"), Some("남극"), Some("Antarctica"), Some("Antarktyka"), Some("Antártida"), Some("Antártida"), Some("Антарктика"), Some("Antarktis"), Some("Antarktika"), Some("Антарктида"), Some("南极洲"), Some("南極洲")]),
+    ("AR", [Some("الأرجنتين"), Some("Argentinien"), Some("Argentina"), Some("Argentine"), Some("अर्जेण्टीना"), Some("Argentina"), Some("アルゼンチン"), Some("아르헨티나"), Some("Argentinië"), Some("Argentyna"), Some("Argentina"), Some("Argentina"), Some("Аргентина"), Some("Argentina"), Some("Arjantin"), Some("Аргентина"), Some("阿根廷"), Some("阿根廷")]),
+    ("AS", [Some("صاموا الأمريكيّة"), Some("Amerikanisch-Samoa"), Some("Samoa Estadounidense"), Some("Samoa américaines"), Some("अमेरिकी समोआ"), Some("Samoa americane"), Some("米領サモア"), Some("아메리칸사모아"), Some("Amerikaans-Samoa"), Some("Samoa Amerykańskie"), Some("Samoa Americana"), Some("Samoa Americana"), Some("Американские Самоа"), Some("Amerikanska Samoa"), Some("Amerikan Samoası"), Some("Американське Самоа"), Some("美属萨摩亚"), Some("美屬薩摩亞")]),
+    ("AT", [Some("النّمسا"), Some("Österreich"), Some("Austria"), Some("Autriche"), Some("ऑस्ट्रिया"), Some("Austria"), Some("オーストリア"), Some("오스트리아"), Some("Oostenrijk"), Some("Austria"), Some("Áustria"), Some("Áustria"), Some("Австрия"), Some("Österrike"), Some("Avusturya"), Some("Австрія"), Some("奥地利"), Some("奧地利")]),
+    ("AU", [Some("أستراليا"), Some("Australien"), Some("Australia"), Some("Australie"), Some("ऑस्ट्रेलिया"), Some("Australia"), Some("オーストラリア連邦"), Some("오스트레일리아"), Some("Australië"), Some("Australia"), Some("Austrália"), Some("Austrália"), Some("Австралия"), Some("Australien"), Some("Avustralya"), Some("Австралія"), Some("澳大利亚"), Some("澳大利亞")]),
+    ("AW", [Some("أروبا"), Some("Aruba"), Some("Aruba"), Some("Aruba"), Some("अरूबा"), Some("Aruba"), Some("アルーバ"), Some("아루바"), Some("Aruba"), Some("Aruba"), Some("Aruba"), Some("Aruba"), Some("Аруба"), Some("Aruba"), Some("Aruba"), Some("Аруба"), Some("阿鲁巴"), Some("阿路巴")]),
+    ("AX", [Some("جزر آلاند"), Some("Åland-Inseln"), Some("Islas Äland"), Some("Åland, Îles"), Some("ऑलैण्ड द्वीपसमूह"), Some("Isole Åland"), Some("オーランド諸島"), Some("올란드 제도"), Some("Ålandseilanden"), Some("Wyspy Alandzkie"), Some("Ilhas Alanda"), Some("Ilhas Åland"), Some("Аландские острова"), Some("Åland"), Some("Åland Adaları"), Some("Аландські острови"), Some("奥兰群岛"), Some("奧蘭群島")]),
+    ("AZ", [Some("أذربيجان"), Some("Aserbaidschan"), Some("Azerbaiyán"), Some("Azerbaïdjan"), Some("अज़रबैजान"), Some("Azerbaigian"), Some("アゼルバイジャン"), Some("아제르바이잔"), Some("Azerbeidzjan"), Some("Azerbejdżan"), Some("Azerbaijão"), Some("Azerbaidjão"), Some("Азербайджан"), Some("Azerbajdzjan"), Some("Azerbaycan"), Some("Азербайджан"), Some("阿塞拜疆"), Some("亞塞拜然")]),
+    ("BA", [Some("البوسنة و الهرسك"), Some("Bosnien und Herzegowina"), Some("Bosnia y Herzegovina"), Some("Bosnie-Herzégovine"), Some("बॉस्निया और हर्ज़ेगोविना"), Some("Bosnia-Erzegovina"), Some("ボスニア・ヘルツェゴビナ"), Some("보스니아 헤르체고비나"), Some("Bosnië en Herzegovina"), Some("Bośnia i Hercegowina"), Some("Bósnia e Herzegovina"), Some("Bósnia-Herzegóvina"), Some("Босния и Герцеговина"), Some("Bosnien-Hercegovina"), Some("Bosna-Hersek"), Some("Боснія і Герцеговина"), Some("波斯尼亚和黑塞哥维那"), Some("波士尼亞及赫塞哥維納")]),
+    ("BB", [Some("بربادوس"), Some("Barbados"), Some("Barbados"), Some("Barbade"), Some("बारबाडोस"), Some("Barbados"), Some("バルバドス"), Some("바베이도스"), Some("Barbados"), Some("Barbados"), Some("Barbados"), Some("Barbados"), Some("Барбадос"), Some("Barbados"), Some("Barbados"), Some("Барбадос"), Some("巴巴多斯"), Some("巴貝多")]),
+    ("BD", [Some("بنغلادش"), Some("Bangladesch"), Some("Bangladés"), Some("Bangladesh"), Some("बांग्लादेश"), Some("Bangladesh"), Some("バングラデシュ"), Some("방글라데시"), Some("Bangladesh"), Some("Bangladesz"), Some("Bangladeche"), Some("Bangladesh"), Some("Бангладеш"), Some("Bangladesh"), Some("Bangladeş"), Some("Бангладеш"), Some("孟加拉"), Some("孟加拉")]),
+    ("BE", [Some("بلجيكا"), Some("Belgien"), Some("Bélgica"), Some("Belgique"), Some("बेल्जियम"), Some("Belgio"), Some("ベルギー"), Some("벨기에"), Some("België"), Some("Belgia"), Some("Bélgica"), Some("Bélgica"), Some("Бельгия"), Some("Belgien"), Some("Belçika"), Some("Бельгія"), Some("比利时"), Some("比利時")]),
+    ("BF", [Some("بوركينا فاصو"), Some("Burkina Faso"), Some("Burquina Faso"), Some("Burkina Faso"), Some("बुर्किना फासो"), Some("Burkina Faso"), Some("ブルキナファソ"), Some("부르키나파소"), Some("Burkina Faso"), Some("Burkina Faso"), Some("Burkina Faso"), Some("Burquina"), Some("Буркина-Фасо"), Some("Burkina Faso"), Some("Burkina Faso"), Some("Буркіна-Фасо"), Some("布基纳法索"), Some("布吉納法索")]),
+    ("BG", [Some("بلغاريا"), Some("Bulgarien"), Some("Bulgaria"), Some("Bulgarie"), Some("बुल्गारिया"), Some("Bulgaria"), Some("ブルガリア"), Some("불가리아"), Some("Bulgarije"), Some("Bułgaria"), Some("Bulgária"), Some("Bulgária"), Some("Болгария"), Some("Bulgarien"), Some("Bulgaristan"), Some("Болгарія"), Some("保加利亚"), Some("保加利亞")]),
+    ("BH", [Some("البحرين"), Some("Bahrain"), Some("Baréin"), Some("Bahreïn"), Some("बहरीन"), Some("Bahrein"), Some("バーレーン"), Some("바레인"), Some("Bahrein"), Some("Bahrajn"), Some("Barém"), Some("Barein"), Some("Бахрейн"), Some("Bahrain"), Some("Bahreyn"), Some("Бахрейн"), Some("巴林"), Some("巴林")]),
+    ("BI", [Some("بوروندي"), Some("Burundi"), Some("Burundi"), Some("Burundi"), Some("बुरुण्डी"), Some("Burundi"), Some("ブルンジ"), Some("부룬디"), Some("Burundi"), Some("Burundi"), Some("Burundi"), Some("Burundi"), Some("Бурунди"), Some("Burundi"), Some("Burundi"), Some("Бурунді"), Some("布隆迪"), Some("蒲隆地")]),
+    ("BJ", [Some("بنين"), Some("Benin"), Some("Benín"), Some("Bénin"), Some("बेनिन"), Some("Benin"), Some("ベナン"), Some("베냉"), Some("Benin"), Some("Benin"), Some("Benim"), Some("Benin"), Some("Бенин"), Some("Benin"), Some("Benin"), Some("Бенін"), Some("贝宁"), Some("貝南")]),
+    ("BL", [Some("سان بارتليمي"), Some("Saint-Barthélemy"), Some("San Bartolomé"), Some("Saint-Barthélemy"), Some("सेंट बार्थेलेमी"), Some("Saint-Barthélemy"), Some("サンバルテルミ"), Some("생바르텔레미"), Some("Saint-Barthélemy"), Some("Saint-Barthélemy"), Some("Saint Barthélemy"), Some("São Bartolomeu"), Some("Сен-Бартельми"), Some("Saint-Barthélemy"), Some("Saint Barthélemy"), Some("Сен-Бартельмі"), Some("圣巴泰勒米岛"), Some("聖巴瑟米")]),
+    ("BM", [Some("برمودا"), Some("Bermuda"), Some("Islas Bermudas"), Some("Bermudes"), Some("बरमूडा"), Some("Bermuda"), Some("バーミューダ"), Some("버뮤다"), Some("Bermuda"), Some("Bermudy"), Some("Bermudas"), Some("Bermuda"), Some("Бермуды"), Some("Bermuda"), Some("Bermuda"), Some("Бермудські острови"), Some("百慕大"), Some("百慕達")]),
+    ("BN", [Some("بروناي دار السّلام"), Some("Brunei Darussalam"), Some("Brunei Darussalam"), Some("Brunéi Darussalam"), Some("ब्रुनेई दरउस्सलाम"), Some("Brunei"), Some("ブルネイ・ダルサラーム国"), Some("브루나이 다루살람"), Some("Brunei"), Some("Państwo Brunei"), Some("Brunei"), Some("Brunei"), Some("Бруней Даруссалам"), Some("Brunei"), Some("Brunei Krallığı"), Some("Бруней"), Some("文莱"), Some("汶萊")]),
+    ("BO", [Some("بوليفيا"), Some("Bolivien"), Some("Bolivia"), Some("Bolivie"), Some("बोलिविया"), Some("Bolivia"), Some("ボリビア"), Some("볼리비아"), Some("Bolivia"), Some("Boliwia"), Some("Bolívia"), Some("Bolívia"), Some("Боливия"), Some("Bolivia"), Some("Bolivya"), Some("Болівія"), Some("波利维亚"), Some("玻利維亞")]),
+    ("BQ", [Some("بونير وسانت يوستاتيوس وسابا"), Some("Bonaire, Sint Eustatius und Saba"), Some("Islas BES (Caribe Neerlandés)"), Some("Bonaire, Saint-Eustache et Saba"), Some("बोनैर, सिंट यूस्टेटीयस एंड साबा"), Some("Paesi Bassi caraibici"), Some("ボネール、シントユースタティウス及びサバ"), Some("보네르, 신트외스타티위스, 사바 섬"), Some("Bonaire, Sint Eustatius en Saba"), Some("Bonaire, Sint Eustatius i Saba"), Some("Bonaire, Santo Eustáquio e Saba"), Some("Bonaire, Saba e Santo Eustáquio"), Some("Бонайре, Синт-Эстатиус и Саба"), Some("Bonaire, Sint Eustatius och Saba"), Some("Bonaire, Sint Eustatius ve Saba"), Some("Бонайре, Сінт-Естатіус і Саба"), Some("博奈尔、圣尤斯特歇斯岛和萨巴"), Some("波內赫、聖尤斯特歇斯及薩巴")]),
+    ("BR", [Some("البرازيل"), Some("Brasilien"), Some("Brasil"), Some("Brésil"), Some("ब्राज़ील"), Some("Brasile"), Some("ブラジル"), Some("브라질"), Some("Brazilië"), Some("Brazylia"), Some("Brasil"), Some("Brasil"), Some("Бразилия"), Some("Brasilien"), Some("Brezilya"), Some("Бразилія"), Some("巴西"), Some("巴西")]),
+    ("BS", [Some("جزر البهاما"), Some("Bahamas"), Some("Bahamas"), Some("Bahamas"), Some("बहामास"), Some("Bahamas"), Some("バハマ"), Some("바하마"), Some("Bahama's"), Some("Bahamy"), Some("Bahamas"), Some("Bahamas"), Some("Багамы"), Some("Bahamas"), Some("Bahamalar"), Some("Багамські острови"), Some("巴哈马"), Some("巴哈馬")]),
+    ("BT", [Some("بوتان"), Some("Bhutan"), Some("Bután"), Some("Bhoutan"), Some("भूटान"), Some("Bhutan"), Some("ブータン"), Some("부탄"), Some("Bhutan"), Some("Bhutan"), Some("Butão"), Some("Butão"), Some("Бутан"), Some("Bhutan"), Some("Bhutan"), Some("Бутан"), Some("不丹"), Some("不丹")]),
+    ("BV", [Some("جزيرة بوفي"), Some("Bouvet-Insel"), Some("Isla Bouvet"), Some("île Bouvet"), Some("बोउवेट आइलैंड"), Some("Isola Bouvet"), Some("ブーベ島"), Some("부베 섬"), Some("Bouveteiland"), Some("Wyspa Bouveta"), Some("Ilha Bouvet"), Some("Ilha Bouvet"), Some("Остров Буве"), Some("Bouvetön"), Some("Bouvet Adası"), Some("Острів Буве"), Some("布维群岛"), Some("布威島")]),
+    ("BW", [Some("بوتسوانا"), Some("Botsuana"), Some("Botsuana"), Some("Botswana"), Some("बोत्सवाना"), Some("Botswana"), Some("ボツワナ"), Some("보츠와나"), Some("Botswana"), Some("Botswana"), Some("Botsuana"), Some("Botsuana"), Some("Ботсвана"), Some("Botswana"), Some("Botsvana"), Some("Ботсвана"), Some("博兹瓦那"), Some("波札那")]),
+    ("BY", [Some("روسيا البيضاء"), Some("Belarus"), Some("Bielorrusia"), Some("Bélarus"), Some("बेलारूस"), Some("Bielorussia"), Some("ベラルーシ"), Some("벨라루스"), Some("Wit-Rusland"), Some("Białoruś"), Some("Bielorússia"), Some("Bielo-Rússia"), Some("Беларусь"), Some("Vitryssland"), Some("Belarus"), Some("Білорусь"), Some("白俄罗斯"), Some("白俄羅斯")]),
+    ("BZ", [Some("بيليز"), Some("Belize"), Some("Belice"), Some("Belize"), Some("बेलीज़"), Some("Belize"), Some("ベリーズ"), Some("벨리즈"), Some("Belize"), Some("Belize"), Some("Belize"), Some("Belize"), Some("Белиз"), Some("Belize"), Some("Belize"), Some("Беліз"), Some("伯利兹"), Some("貝里斯")]),
+    ("CA", [Some("كندا"), Some("Kanada"), Some("Canadá"), Some("Canada"), Some("कनाडा"), Some("Canada"), Some("カナダ"), Some("캐나다"), Some("Canada"), Some("Kanada"), Some("Canadá"), Some("Canadá"), Some("Канада"), Some("Kanada"), Some("Kanada"), Some("Канада"), Some("加拿大"), Some("加拿大")]),
+    ("CC", [Some("جزر الكوكوس"), Some("Kokos-(Keeling-)Inseln"), Some("Islas Cocos (Keeling)"), Some("Cocos (Keeling), Îles"), Some("कोकोस (कीलिंग) द्वीपसमूह"), Some("Isole Cocos (Keeling)"), Some("ココス (キーリング) 諸島"), Some("코코스 제도"), Some("Cocoseilanden (Keelingeilanden)"), Some("Wyspy Kokosowe (Wyspy Keelinga)"), Some("Ilhas Cocos"), Some("Ilhas Cocos"), Some("Кокосовые острова"), Some("Kokosöarna"), Some("Cocos (Keeling) Adaları"), Some("Кокосові (Кілінг) острови"), Some("科科斯群岛"), Some("科科斯 (基林) 群島")]),
+    ("CD", [Some("الكونغو، جمهوريّة الكونغو الدّيموقراطيّة"), Some("Demokratische Republik Kongo"), Some("Congo, República Democrática del"), Some("République démocratique du Congo"), Some("कांगो, द डेमोक्रेटिक रिपब्लिक ऑफ द"), Some("Repubblica democratica del Congo"), Some("コンゴ民主共和国"), Some("콩고 민주 공화국"), Some("Congo, Democratische Republiek"), Some("Kongo, Demokratyczna Republika Konga"), Some("Congo, República Democrática do"), Some("Congo, República Democrática do"), Some("Демократическая Республика Конго"), Some("Kongo, demokratiska republiken"), Some("Kongo Demokratik Cumhuriyeti"), Some("Конго, демократична республіка"), Some("刚果民主共和国"), Some("剛果民主共和國")]),
+    ("CF", [Some("جمهورية إفريقيّا الوسطى"), Some("Zentralafrikanische Republik"), Some("República Centroafricana"), Some("République centrafricaine"), Some("मध्य अफ़्रीकी गणराज्य"), Some("Repubblica Centrafricana"), Some("中央アフリカ共和国"), Some("중앙아프리카 공화국"), Some("Centraal-Afrikaanse Republiek"), Some("Republika Środkowoafrykańska"), Some("República Centro-Africana"), Some("República Centro-Africana"), Some("Центрально-африканская республика"), Some("Centralafrikanska republiken"), Some("Orta Afrika Cumhuriyeti"), Some("Центральноафриканська Республіка"), Some("中非"), Some("中非共和國")]),
+    ("CG", [Some("الكونغو"), Some("Kongo"), Some("Congo"), Some("République du Congo"), Some("कॉंगो"), Some("Congo"), Some("コンゴ"), Some("콩고"), Some("Congo"), Some("Kongo"), Some("Congo"), Some("Congo"), Some("Конго"), Some("Kongo"), Some("Kongo"), Some("Конго"), Some("刚果"), Some("剛果")]),
+    ("CH", [Some("سويسرا"), Some("Schweiz"), Some("Suiza"), Some("Suisse"), Some("स्विट्ज़रलैण्ड"), Some("Svizzera"), Some("スイス"), Some("스위스"), Some("Zwitserland"), Some("Szwajcaria"), Some("Suíça"), Some("Suíça"), Some("Швейцария"), Some("Schweiz"), Some("İsviçre"), Some("Швейцарія"), Some("瑞士"), Some("瑞士")]),
+    ("CI", [Some("ساحل العاج"), Some("Côte d'Ivoire"), Some("Costa de Marfíl"), Some("Côte d'Ivoire"), Some("कोयटे डी वोयरे"), Some("Costa d'Avorio"), Some("コートジボワール"), Some("코트디부아르"), Some("Ivoorkust"), Some("Wybrzeże Kości Słoniowej"), Some("Costa do Marfim"), Some("Costa do Marfim"), Some("Кот-д'Ивуар"), Some("Elfenbenskusten"), Some("Fildişi Sahili"), Some("Кот-д'Івуар"), Some("科特迪瓦"), Some("象牙海岸")]),
+    ("CK", [Some("جزر كوك"), Some("Cookinseln"), Some("Islas Cook"), Some("îles Cook"), Some("कुक द्वीपसमूह"), Some("Isole Cook"), Some("クック諸島"), Some("쿡 제도"), Some("Cookeilanden"), Some("Wyspy Cooka"), Some("Ilhas Cook"), Some("Ilhas Cook"), Some("Острова Кука"), Some("Cooköarna"), Some("Cook Adaları"), Some("Острови Кука"), Some("库克群岛"), Some("庫克群島")]),
+    ("CL", [Some("تشيلي"), Some("Chile"), Some("Chile"), Some("Chili"), Some("चिली"), Some("Cile"), Some("チリ"), Some("칠레"), Some("Chili"), Some("Chile"), Some("Chile"), Some("Chile"), Some("Чили"), Some("Chile"), Some("Şili"), Some("Чилі"), Some("智利"), Some("智利")]),
+    ("CM", [Some("الكاميرون"), Some("Kamerun"), Some("Camerún"), Some("Cameroun"), Some("कैमरुन"), Some("Camerun"), Some("カメルーン"), Some("카메룬"), Some("Kameroen"), Some("Kamerun"), Some("Camarões"), Some("Camarões"), Some("Камерун"), Some("Kamerun"), Some("Kamerun"), Some("Камерун"), Some("喀麦隆"), Some("喀麥隆")]),
+    ("CN", [Some("الصّين"), Some("China"), Some("China"), Some("Chine"), Some("चीन"), Some("Cina"), Some("中国"), Some("중국"), Some("China"), Some("Chiny"), Some("China"), Some("China"), Some("Китай"), Some("Kina"), Some("Çin"), Some("Китай"), Some("中国"), Some("中國")]),
+    ("CO", [Some("كولومبيا"), Some("Kolumbien"), Some("Colombia"), Some("Colombie"), Some("कोलम्बिया"), Some("Colombia"), Some("コロンビア"), Some("콜롬비아"), Some("Colombia"), Some("Kolumbia"), Some("Colômbia"), Some("Colômbia"), Some("Колумбия"), Some("Colombia"), Some("Kolombiya"), Some("Колумбія"), Some("哥伦比亚"), Some("哥倫比亞")]),
+    ("CR", [Some("كوستاريكا"), Some("Costa Rica"), Some("Costa Rica"), Some("Costa Rica"), Some("कोस्टा रीका"), Some("Costa Rica"), Some("コスタリカ"), Some("코스타리카"), Some("Costa Rica"), Some("Kostaryka"), Some("Costa Rica"), Some("Costa Rica"), Some("Коста-Рика"), Some("Costa Rica"), Some("Kosta Rika"), Some("Коста-Рика"), Some("哥斯达黎加"), Some("哥斯大黎加")]),
+    ("CU", [Some("كوبا"), Some("Kuba"), Some("Cuba"), Some("Cuba"), Some("क्यूबा"), Some("Cuba"), Some("キューバ"), Some("쿠바"), Some("Cuba"), Some("Kuba"), Some("Cuba"), Some("Cuba"), Some("Куба"), Some("Kuba"), Some("Küba"), Some("Куба"), Some("古巴"), Some("古巴")]),
+    ("CV", [Some("الرأس الأخضر"), Some("Kap Verde"), Some("Cabo Verde"), Some("Cap-Vert"), Some("काबो वर्डे"), Some("Capo Verde"), Some("カーボヴェルデ"), Some("카보베르데"), Some("Kaapverdië"), Some("Republika Zielonego Przylądka"), Some("Cabo Verde"), Some("Cabo Verde"), Some("Кабо-Верде"), Some("Kap Verde"), Some("Yeşil Burun Adaları"), Some("Кабо-Верде"), Some("佛得角"), Some("維德角")]),
+    ("CW", [Some("جزر كوراكاو"), Some("Curaçao"), Some("Curazao"), Some("Curaçao"), Some("कुराकाओ"), Some("Curaçao"), Some("キュラソー"), Some("퀴라소"), Some("Curaçao"), Some("Curaçao"), Some("Curação"), Some("Curaçao"), Some("Кюрасао"), Some("Curaçao"), Some("Curaçao"), Some("Кюрасао"), Some("库拉索"), Some("古拉索")]),
+    ("CX", [Some("جزر الكريسماس"), Some("Weihnachtsinseln"), Some("Isla de Navidad"), Some("Christmas, Île"), Some("क्रिसमस आइलैन्ड"), Some("Isola di Natale"), Some("クリスマス島"), Some("크리스마스 섬"), Some("Christmaseiland"), Some("Wyspa Bożego Narodzenia"), Some("Ilha Natal"), Some("Ilha Christmas"), Some("Остров Рождества"), Some("Julön"), Some("Christmas Adası"), Some("Острів Різдва"), Some("圣诞岛"), Some("聖誕島")]),
+    ("CY", [Some("قبرص"), Some("Zypern"), Some("Chipre"), Some("Chypre"), Some("साइप्रस"), Some("Cipro"), Some("キプロス"), Some("키프로스"), Some("Cyprus"), Some("Cypr"), Some("Chipre"), Some("Chipre"), Some("Кипр"), Some("Cypern"), Some("Kıbrıs"), Some("Кіпр"), Some("塞浦路斯"), Some("賽普勒斯")]),
+    ("CZ", [Some("التشيك"), Some("Tschechien"), Some("Chequia"), Some("Tchéquie"), Some("चेकिया"), Some("Cechia"), None, Some("체코"), Some("Tsjechië"), Some("Czechy"), Some("Chéquia"), Some("Chéquia"), Some("Чехия"), Some("Tjeckien"), Some("Çekya"), Some("Чехія"), Some("捷克"), Some("捷克")]),
+    ("DE", [Some("ألمانيا"), Some("Deutschland"), Some("Alemania"), Some("Allemagne"), Some("जर्मनी"), Some("Germania"), Some("ドイツ"), Some("독일"), Some("Duitsland"), Some("Niemcy"), Some("Alemanha"), Some("Alemanha"), Some("Германия"), Some("Tyskland"), Some("Almanya"), Some("Німеччина"), Some("德国"), Some("德國")]),
+    ("DJ", [Some("جيبوتي"), Some("Dschibuti"), Some("Yibuti"), Some("Djibouti"), Some("जिबूती"), Some("Gibuti"), Some("ジブチ"), Some("지부티"), Some("Djibouti"), Some("Dżibuti"), Some("Djibouti"), Some("Djibuti"), Some("Джибути"), Some("Djibouti"), Some("Cibuti"), Some("Джибуті"), Some("吉布提"), Some("吉布地")]),
+    ("DK", [Some("الدّنمارك"), Some("Dänemark"), Some("Dinamarca"), Some("Danemark"), Some("डेनमार्क"), Some("Danimarca"), Some("デンマーク"), Some("덴마크"), Some("Denemarken"), Some("Dania"), Some("Dinamarca"), Some("Dinamarca"), Some("Дания"), Some("Danmark"), Some("Danimarka"), Some("Данія"), Some("丹麦"), Some("丹麥")]),
+    ("DM", [Some("دومينيكا"), Some("Dominica"), Some("Dominica"), Some("Dominique"), Some("डोमिनिका"), Some("Dominica"), Some("ドミニカ"), Some("도미니카 연방"), Some("Dominica"), Some("Dominika"), Some("Dominica"), Some("Domínica"), Some("Доминика"), Some("Dominica"), Some("Dominika"), Some("Домініка"), Some("多米尼克"), Some("多米尼克")]),
+    ("DO", [Some("جمهوريّة الدّومينيكان"), Some("Dominikanische Republik"), Some("República Dominicana"), Some("République dominicaine"), Some("डोमिनिकन गणराज्य"), Some("Repubblica Dominicana"), Some("ドミニカ共和国"), Some("도미니카 공화국"), Some("Dominicaanse Republiek"), Some("Republika Dominikańska"), Some("República Dominicana"), Some("República Dominicana"), Some("Доминиканская республика"), Some("Dominikanska republiken"), Some("Dominik Cumhuriyeti"), Some("Домініканська республіка"), Some("多米尼加共和国"), Some("多明尼加共和國")]),
+    ("DZ", [Some("الجزائر"), Some("Algerien"), Some("Algeria"), Some("Algérie"), Some("अल्जीरिया"), Some("Algeria"), Some("アルジェリア"), Some("알제리"), Some("Algerije"), Some("Algieria"), Some("Argélia"), Some("Argélia"), Some("Алжир"), Some("Algeriet"), Some("Cezayir"), Some("Алжир"), Some("阿尔及利亚"), Some("阿爾及利亞")]),
+    ("EC", [Some("الإكوادور"), Some("Ecuador"), Some("Ecuador"), Some("Équateur"), Some("ईक्वाडोर"), Some("Ecuador"), Some("エクアドル"), Some("에콰도르"), Some("Ecuador"), Some("Ekwador"), Some("Equador"), Some("Equador"), Some("Эквадор"), Some("Ecuador"), Some("Ekvador"), Some("Еквадор"), Some("厄瓜多尔"), Some("厄瓜多")]),
+    ("EE", [Some("إستونيا"), Some("Estland"), Some("Estonia"), Some("Estonie"), Some("एस्टोनिया"), Some("Estonia"), Some("エストニア"), Some("에스토니아"), Some("Estland"), Some("Estonia"), Some("Estónia"), Some("Estônia"), Some("Эстония"), Some("Estland"), Some("Estonya"), Some("Естонія"), Some("爱沙尼亚"), Some("愛沙尼亞")]),
+    ("EG", [Some("مصر"), Some("Ägypten"), Some("Egipto"), Some("Égypte"), Some("मिस्र"), Some("Egitto"), Some("エジプト"), Some("이집트"), Some("Egypte"), Some("Egipt"), Some("Egito"), Some("Egito"), Some("Египет"), Some("Egypten"), Some("Mısır"), Some("Єгипет"), Some("埃及"), Some("埃及")]),
+    ("EH", [Some("الصّحراء الغربيّة"), Some("Westsahara"), Some("Sahara Occidental"), Some("Sahara occidental"), Some("पश्चिमी सहारा"), Some("Sahara occidentale"), Some("西サハラ"), Some("서사하라"), Some("Westelijke Sahara"), Some("Sahara Zachodnia"), Some("Saara Ocidental"), Some("Saara Ocidental"), Some("Западная Сахара"), Some("Västsahara"), Some("Batı Sahra"), Some("Західна Сахара"), Some("西撒哈拉"), Some("西撒哈拉")]),
+    ("ER", [Some("إريتريا"), Some("Eritrea"), Some("Eritrea"), Some("Érythrée"), Some("इरित्रिया"), Some("Eritrea"), Some("エリトリア国"), Some("에리트레아"), Some("Eritrea"), Some("Erytrea"), Some("Eritreia"), Some("Eritréia"), Some("Эритрея"), Some("Eritrea"), Some("Eritre"), Some("Еритрея"), Some("厄立特里亚"), Some("厄利垂亞")]),
+    ("ES", [Some("إسبانيا"), Some("Spanien"), Some("España"), Some("Espagne"), Some("स्पेन"), Some("Spagna"), Some("スペイン"), Some("스페인"), Some("Spanje"), Some("Hiszpania"), Some("Espanha"), Some("Espanha"), Some("Испания"), Some("Spanien"), Some("İspanya"), Some("Іспанія"), Some("西班牙"), Some("西班牙")]),
+    ("ET", [Some("إثيوبيا"), Some("Äthiopien"), Some("Etiopía"), Some("Éthiopie"), Some("इथियोपिया"), Some("Etiopia"), Some("エチオピア"), Some("에티오피아"), Some("Ethiopië"), Some("Etiopia"), Some("Etiópia"), Some("Etiópia"), Some("Эфиопия"), Some("Etiopien"), Some("Etiyopya"), Some("Ефіопія"), Some("埃塞俄比亚"), Some("衣索比亞")]),
+    ("FI", [Some("فنلندا"), Some("Finnland"), Some("Finlandia"), Some("Finlande"), Some("फ़िनलैण्ड"), Some("Finlandia"), Some("フィンランド"), Some("핀란드"), Some("Finland"), Some("Finlandia"), Some("Finlândia"), Some("Finlândia"), Some("Финляндия"), Some("Finland"), Some("Finlandiya"), Some("Фінляндія"), Some("芬兰"), Some("芬蘭")]),
+    ("FJ", [Some("فيجي"), Some("Fidschi"), Some("Fiyi"), Some("Fidji"), Some("फ़िजी"), Some("Figi"), Some("フィジー"), Some("피지"), Some("Fiji"), Some("Fidżi"), Some("Fiji"), Some("Fiji"), Some("Фиджи"), Some("Fiji"), Some("Fiji"), Some("Фіджі"), Some("斐济"), Some("斐濟")]),
+    ("FK", [Some("جزر فولكلاند (مالفيناس)"), Some("Falklandinseln (Malwinen)"), Some("Islas Falkland (Malvinas)"), Some("Malouines, Îles (Falkland)"), Some("फॉकलैंड आइलैंड्स (मालविनास)"), Some("Isole Falkland (Malvine)"), Some("フォークランド諸島 (マルビナス)"), Some("포클랜드 제도 (말비나스)"), Some("Falklandeilanden (Malvinas)"), Some("Falklandy (Malwiny)"), Some("Ilhas Falkland (Malvinas)"), Some("Ilhas Malvinas (Falkland)"), Some("Фолклендские (Мальвинские) острова"), Some("Falklandsöarna (Malvinas)"), Some("Falkland Adaları (Malvinas)"), Some("Фолклендські острови (Британія)"), Some("福克兰群岛(马尔维纳斯)"), Some("福克蘭群島 (馬維娜斯)")]),
+    ("FM", [Some("ميكرونيزيا، ولايات ميكرونيزيا الموحّدة"), Some("Mikronesien, Föderierte Staaten von"), Some("Micronesia, Estados Federados de"), Some("Micronésie, États fédérés de"), Some("माइक्रोनीसिया, फेडेरेटड स्टेट्स ऑफ"), Some("Micronesia"), Some("ミクロネシア連邦"), Some("미크로네시아 연방"), Some("Micronesia"), Some("Mikronezja"), Some("Micronésia, Estados Federados da"), Some("Micronésia, Estados Federados da"), Some("Федеративные Штаты Микронезии"), Some("Mikronesien, federala staterna"), Some("Mikronezya Federe Devletleri"), Some("Мікронезія, федеративні штати"), Some("密克罗尼西亚"), Some("密克羅尼西亞聯邦")]),
+    ("FO", [Some("جزر الفارو"), Some("Färöer-Inseln"), Some("Islas Feroe"), Some("îles Féroé"), Some("फ़रो द्वीपसमूह"), Some("Isole Fær Øer"), Some("フェロー諸島"), Some("페로 제도"), Some("Faeröer"), Some("Wyspy Owcze"), Some("Ilhas Faroé"), Some("Ilhas Faroe"), Some("Фарерские острова"), Some("Färöarna"), Some("Faroe Adaları"), Some("Фарерські острови"), Some("法罗群岛"), Some("法羅群島")]),
+    ("FR", [Some("فرنسا"), Some("Frankreich"), Some("Francia"), Some("France"), Some("फ़्रान्स"), Some("Francia"), Some("フランス"), Some("프랑스"), Some("Frankrijk"), Some("Francja"), Some("França"), Some("França"), Some("Франция"), Some("Frankrike"), Some("Fransa"), Some("Франція"), Some("法国"), Some("法國")]),
+    ("GA", [Some("الغابون"), Some("Gabun"), Some("Gabón"), Some("Gabon"), Some("गबॉन"), Some("Gabon"), Some("ガボン"), Some("가봉"), Some("Gabon"), Some("Gabon"), Some("Gabão"), Some("Gabão"), Some("Габон"), Some("Gabon"), Some("Gabon"), Some("Габон"), Some("加蓬"), Some("加彭")]),
+    ("GB", [Some("المملكة المتّحدة"), Some("Vereinigtes Königreich"), Some("Reino Unido"), Some("Royaume-Uni"), Some("यूनाइटेड किंगडम"), Some("Regno Unito"), Some("英国"), Some("영국"), Some("Verenigd Koninkrijk"), Some("Wielka Brytania"), Some("Reino Unido"), Some("Reino Unido"), Some("Соединённое Королевство"), Some("Förenade kungariket"), Some("Birleşik Krallık"), Some("Велика Британія"), Some("英国"), Some("英國")]),
+    ("GD", [Some("غرينادا"), Some("Grenada"), Some("Granada"), Some("Grenade"), Some("ग्रेनाडा"), Some("Grenada"), Some("グレナダ"), Some("그레나다"), Some("Grenada"), Some("Grenada"), Some("Granada"), Some("Granada"), Some("Гренада"), Some("Grenada"), Some("Grenada"), Some("Гренада"), Some("格林纳达"), Some("格瑞那達")]),
+    ("GE", [Some("جورجيا"), Some("Georgien"), Some("Georgia"), Some("Géorgie"), Some("जॉर्जिया"), Some("Georgia"), Some("グルジア"), Some("조지아"), Some("Georgia"), Some("Gruzja"), Some("Geórgia"), Some("Geórgia"), Some("Грузия"), Some("Georgien"), Some("Gürcistan"), Some("Грузія"), Some("格鲁吉亚"), Some("喬治亞")]),
+    ("GF", [Some("غيانا الفرنسيّة"), Some("Französisch-Guyana"), Some("Guayana Francesa"), Some("Guyane française"), Some("फ़्रान्सीसी गुयाना"), Some("Guyana francese"), Some("仏領ギアナ"), Some("프랑스령 기아나"), Some("Frans-Guyana"), Some("Gujana Francuska"), Some("Guiana Francesa"), Some("Guiana Francesa"), Some("Французская Гвиана"), Some("Franska Guyana"), Some("Fransız Guyanası"), Some("Французька Гвіана"), Some("法属圭亚那"), Some("法屬蓋亞那")]),
+    ("GG", [Some("جزيرة جويرزني"), Some("Guernsey"), Some("Guernsey"), Some("Guernesey"), Some("ग्वेर्नसे"), Some("Guernsey"), Some("ガーンジー"), Some("건지 섬"), Some("Guernsey"), Some("Guernsey"), Some("Guernsey"), Some("Guernsey"), Some("Гернси"), Some("Guernsey"), Some("Guernsey"), Some("Острів Гернсі"), Some("根西岛"), Some("根息島")]),
+    ("GH", [Some("غانا"), Some("Ghana"), Some("Ghana"), Some("Ghana"), Some("घाना"), Some("Ghana"), Some("ガーナ"), Some("가나"), Some("Ghana"), Some("Ghana"), Some("Gana"), Some("Gana"), Some("Гана"), Some("Ghana"), Some("Gana"), Some("Гана"), Some("加纳"), Some("迦納")]),
+    ("GI", [Some("جبل طارق"), Some("Gibraltar"), Some("Gibraltar"), Some("Gibraltar"), Some("जिब्राल्टर"), Some("Gibilterra"), Some("ジブラルタル"), Some("지브롤터"), Some("Gibraltar"), Some("Gibraltar"), Some("Gibraltar"), Some("Gibraltar"), Some("Гибралтар"), Some("Gibraltar"), Some("Cebelitarık"), Some("Гібралтар"), Some("直布罗陀"), Some("直布羅陀")]),
+    ("GL", [Some("غرينلاند"), Some("Grönland"), Some("Groenlandia"), Some("Groënland"), Some("ग्रीनलैण्ड"), Some("Groenlandia"), Some("グリーンランド"), Some("그린란드"), Some("Groenland"), Some("Grenlandia"), Some("Gronelândia"), Some("Groenlândia"), Some("Гренландия"), Some("Grönland"), Some("Grönland"), Some("Ґренландія"), Some("格陵兰"), Some("格陵蘭")]),
+    ("GM", [Some("غامبيا"), Some("Gambia"), Some("Gambia"), Some("Gambie"), Some("गाम्बिया"), Some("Gambia"), Some("ガンビア"), Some("감비아"), Some("Gambia"), Some("Gambia"), Some("Gâmbia"), Some("Gâmbia"), Some("Гамбия"), Some("Gambia"), Some("Gambiya"), Some("Гамбія"), Some("冈比亚"), Some("甘比亞")]),
+    ("GN", [Some("غينيا"), Some("Guinea"), Some("Guinea"), Some("Guinée"), Some("गिनी"), Some("Guinea"), Some("ギニア"), Some("기니"), Some("Guinee"), Some("Gwinea"), Some("Guiné"), Some("Guiné"), Some("Гвинея"), Some("Guinea"), Some("Gine"), Some("Гвінея"), Some("几内亚"), Some("幾內亞")]),
+    ("GP", [Some("جوادالوبّي"), Some("Guadeloupe"), Some("Guadalupe"), Some("Guadeloupe"), Some("गुआदेलूप"), Some("Guadalupa"), Some("グアドループ"), Some("과들루프"), Some("Guadeloupe"), Some("Gwadelupa"), Some("Guadalupe"), Some("Guadalupe"), Some("Гваделупа"), Some("Guadeloupe"), Some("Guadeloupe"), Some("Гваделупа"), Some("瓜德罗普"), Some("瓜地洛普")]),
+    ("GQ", [Some("غينيا الاستوائيّة"), Some("Äquatorialguinea"), Some("Guinea Ecuatorial"), Some("Guinée Équatoriale"), Some("भूमध्यरेखीय गिनी"), Some("Guinea equatoriale"), Some("赤道ギニア"), Some("적도 기니"), Some("Equatoriaal-Guinea"), Some("Gwinea Równikowa"), Some("Guiné Equatorial"), Some("Guiné Equatorial"), Some("Экваториальная Гвинея"), Some("Ekvatorialguinea"), Some("Ekvator Ginesi"), Some("Екваторіальна Гвінея"), Some("赤道几内亚"), Some("赤道幾內亞")]),
+    ("GR", [Some("اليونان"), Some("Griechenland"), Some("Grecia"), Some("Grèce"), Some("यूनान"), Some("Grecia"), Some("ギリシャ"), Some("그리스"), Some("Griekenland"), Some("Grecja"), Some("Grécia"), Some("Grécia"), Some("Греция"), Some("Grekland"), Some("Yunanistan"), Some("Греція"), Some("希腊"), Some("希臘")]),
+    ("GS", [Some("جورجيا الجنوبيّة و جزر ساندويتش الجنوبيّة"), Some("South Georgia und die Südlichen Sandwichinseln"), Some("Islas Georgias del Sur y Sándwich del Sur"), Some("Géorgie du Sud et les îles Sandwich du Sud"), Some("दक्षिण जॉर्जिया एवं दक्षिण सैंडविच द्वीप समूह"), Some("Georgia del Sud e Isole Sandwich Australi"), Some("サウスジョージア及びサウスサンドウィッチ諸島"), Some("사우스조지아 사우스샌드위치 제도"), Some("Zuid-Georgia en de Zuidelijke Sandwicheilanden"), Some("Georgia Południowa i Sandwich Południowy"), Some("Ilhas Geórgia do Sul e Sandwich do Sul"), Some("Geórgia do Sul e Ilhas Sandwich do Sul"), Some("Южная Джорджия и Южные Сандвичевы острова"), Some("Sydgeorgien och södra Sandwichöarna"), Some("Güney Georgia ve Güney Sandwich Adaları"), Some("Південна Джорджія та Південні Сандвічеві острови"), Some("南乔治亚岛和南桑德韦奇岛"), Some("南喬治亞及南三明治群島")]),
+    ("GT", [Some("غواتيمالا"), Some("Guatemala"), Some("Guatemala"), Some("Guatemala"), Some("ग्वाटेमाला"), Some("Guatemala"), Some("グアテマラ"), Some("과테말라"), Some("Guatemala"), Some("Gwatemala"), Some("Guatemala"), Some("Guatemala"), Some("Гватемала"), Some("Guatemala"), Some("Guatemala"), Some("Гватемала"), Some("瓜地马拉"), Some("瓜地馬拉")]),
+    ("GU", [Some("جوام"), Some("Guam"), Some("Guam"), Some("Guam"), Some("गुआम"), Some("Guam"), Some("グアム"), Some("괌"), Some("Guam"), Some("Guam"), Some("Guam"), Some("Guam"), Some("Гуам"), Some("Guam"), Some("Guam"), Some("Гуам"), Some("关岛"), Some("關島")]),
+    ("GW", [Some("غينيا بيساو"), Some("Guinea-Bissau"), Some("Guinea-Bisáu"), Some("Guinée-Bissau"), Some("गिनी-बिसाऊ"), Some("Guinea-Bissau"), Some("ギニアビサウ"), Some("기니비사우"), Some("Guinee-Bissau"), Some("Gwinea Bissau"), Some("Guiné-Bissáu"), Some("Guiné-Bissau"), Some("Гвинея-Бисау"), Some("Guinea-Bissau"), Some("Gine-Bissau"), Some("Гвінея-Бісау"), Some("几内亚比绍"), Some("幾內亞比索")]),
+    ("GY", [Some("غويانا"), Some("Guyana"), Some("Guyana"), Some("Guyana"), Some("गयाना"), Some("Guyana"), Some("ガイアナ"), Some("가이아나"), Some("Guyana"), Some("Gujana"), Some("Guiana"), Some("Guiana"), Some("Гайана"), Some("Guyana"), Some("Guyana"), Some("Гаяна"), Some("圭亚那"), Some("蓋亞那")]),
+    ("HK", [Some("هونغ كونغ"), Some("Hongkong"), Some("Hong Kong"), Some("Hong Kong"), Some("हांगकांग"), Some("Hong Kong"), Some("香港"), Some("홍콩"), Some("Hongkong"), Some("Hongkong"), Some("Hong Kong"), Some("Hong Kong"), Some("Гонконг"), Some("Hongkong"), Some("Hong Kong"), Some("Гонконг"), Some("香港"), Some("香港")]),
+    ("HM", [Some("جزيرة هيرد وجزر مَكْدونالد"), Some("Heard und McDonaldinseln"), Some("Islas Heard y McDonald"), Some("îles Heard-et-MacDonald"), Some("हर्ड द्वीप और मैकडोनाल्ड द्वीप"), Some("Isole Heard e McDonald"), Some("ハード島及びマクドナルド諸島"), Some("허드 맥도널드 제도"), Some("Heardeiland en McDonaldeilanden"), Some("Wyspy Heard i McDonalda"), Some("Ilha Heard e Ilhas McDonald"), Some("Ilha Heard e Ilhas McDonald"), Some("Остров Херд и острова МакДональд"), Some("Heardön och McDonaldöarna"), Some("Heard Adası ve McDonald Adaları"), Some("Острів Герд і острови Макдональд"), Some("赫德岛与麦克唐纳群岛"), Some("赫德島及麥當勞群島")]),
+    ("HN", [Some("هندوراس"), Some("Honduras"), Some("Honduras"), Some("Honduras"), Some("हौण्डुरस"), Some("Honduras"), Some("ホンジュラス"), Some("온두라스"), Some("Honduras"), Some("Honduras"), Some("Honduras"), Some("Honduras"), Some("Гондурас"), Some("Honduras"), Some("Honduras"), Some("Гондурас"), Some("洪都拉斯"), Some("宏都拉斯")]),
+    ("HR", [Some("كرواتيا"), Some("Kroatien"), Some("Croacia"), Some("Croatie"), Some("क्रोएशिया"), Some("Croazia"), Some("クロアチア"), Some("크로아티아"), Some("Kroatië"), Some("Chorwacja"), Some("Croácia"), Some("Croácia"), Some("Хорватия"), Some("Kroatien"), Some("Hırvatistan"), Some("Хорватія"), Some("克罗地亚"), Some("克羅埃西亞")]),
+    ("HT", [Some("هايتي"), Some("Haiti"), Some("Haití"), Some("Haïti"), Some("हैती"), Some("Haiti"), Some("ハイチ"), Some("아이티"), Some("Haïti"), Some("Haiti"), Some("Haiti"), Some("Haiti"), Some("Гаити"), Some("Haiti"), Some("Haiti"), Some("Гаїті"), Some("海地"), Some("海地")]),
+    ("HU", [Some("المجر (هنغاريا)"), Some("Ungarn"), Some("Hungría"), Some("Hongrie"), Some("हंगरी"), Some("Ungheria"), Some("ハンガリー"), Some("헝가리"), Some("Hongarije"), Some("Węgry"), Some("Hungria"), Some("Hungria"), Some("Венгрия"), Some("Ungern"), Some("Macaristan"), Some("Угорщина"), Some("匈牙利"), Some("匈牙利")]),
+    ("ID", [Some("إندونيسيا"), Some("Indonesien"), Some("Indonesia"), Some("Indonésie"), Some("इंडोनेशिया"), Some("Indonesia"), Some("インドネシア"), Some("인도네시아"), Some("Indonesië"), Some("Indonezja"), Some("Indonésia"), Some("Indonésia"), Some("Индонезия"), Some("Indonesien"), Some("Endonezya"), Some("Індонезія"), Some("印度尼西亚"), Some("印度尼西亞")]),
+    ("IE", [Some("أيرلندا"), Some("Irland"), Some("Irlanda"), Some("Irlande"), Some("आयरलैण्ड"), Some("Irlanda"), Some("アイルランド"), Some("아일랜드"), Some("Ierland"), Some("Irlandia"), Some("Irlanda"), Some("Irlanda"), Some("Ирландия"), Some("Irland"), Some("İrlanda"), Some("Ірландія"), Some("爱尔兰"), Some("愛爾蘭")]),
+    ("IL", [Some("إسرائيل"), Some("Israel"), Some("Israel"), Some("Israël"), Some("इज़राइल"), Some("Israele"), Some("イスラエル"), Some("이스라엘"), Some("Israël"), Some("Izrael"), Some("Israel"), Some("Israel"), Some("Израиль"), Some("Israel"), Some("İsrail"), Some("Ізраїль"), Some("以色列"), Some("以色列")]),
+    ("IM", [Some("آيزل أف مان"), Some("Insel Man"), Some("Isla de Man"), Some("Île de Man"), Some("मनुष्य का टापू"), Some("Isola di Man"), Some("マン島"), Some("맨 섬"), Some("Eiland Man"), Some("Wyspa Man"), Some("Ilha de Man"), Some("Ilha de Man"), Some("Остров Мэн"), Some("Isle of Man"), Some("Man Adası"), Some("Острів Мен"), Some("曼岛"), Some("曼島")]),
+    ("IN", [Some("الهند"), Some("Indien"), Some("India"), Some("Inde"), Some("भारत"), Some("India"), Some("インド"), Some("인도"), Some("India"), Some("Indie"), Some("Índia"), Some("Índia"), Some("Индия"), Some("Indien"), Some("Hindistan"), Some("Індія"), Some("印度"), Some("印度")]),
+    ("IO", [Some("مقاطعة المحيط الهندي البريطانيّة"), Some("Britisches Territorium im Indischen Ozean"), Some("Territorio Británico del Océano Índico"), Some("Territoire britannique de l'océan Indien"), Some("ब्रिटिश हिंद महासागर क्षेत्र"), Some("Territorio britannico dell'Oceano Indiano"), Some("英国インド洋領土"), Some("영국령 인도양 지역"), Some("Brits Indische Oceaanterritorium"), Some("Brytyjskie Terytorium Oceanu Indyjskiego"), Some("Território Britânico do Oceano Índico"), Some("Território Britânico do Oceano Índico"), Some("Британская территория Индийского океана"), Some("Brittiskt territorium i Indiska Oceanen"), Some("Britanya Hint Okyanusu Toprakları"), Some("Британська територія в Індійському океані"), Some("英属印度洋领地"), Some("英屬印度洋領地")]),
+    ("IQ", [Some("العراق"), Some("Irak"), Some("Irak"), Some("Irak"), Some("इराक़"), Some("Iraq"), Some("イラク"), Some("이라크"), Some("Irak"), Some("Irak"), Some("Iraque"), Some("Iraque"), Some("Ирак"), Some("Irak"), Some("Irak"), Some("Ірак"), Some("伊拉克"), Some("伊拉克")]),
+    ("IR", [Some("إيران، الجمهوريّة الإسلاميّة الإيرانيّة"), Some("Iran"), Some("Irán, República islámica de"), Some("Iran, République islamique d'"), Some("ईरान, इस्लामिक रिपब्लिक ऑफ"), Some("Iran"), Some("イラン・イスラム共和国"), Some("이란 이슬람 공화국"), Some("Iran"), Some("Iran"), Some("Irão, República Islâmica do"), Some("Irã, República Islâmica do"), Some("Иран"), Some("Iran"), Some("İran"), Some("Іран"), Some("伊朗"), Some("伊朗")]),
+    ("IS", [Some("آيسلندا"), Some("Island"), Some("Islandia"), Some("Islande"), Some("आइसलैण्ड"), Some("Islanda"), Some("アイスランド"), Some("아이슬란드"), Some("IJsland"), Some("Islandia"), Some("Islândia"), Some("Islândia"), Some("Исландия"), Some("Island"), Some("İzlanda"), Some("Ісландія"), Some("冰岛"), Some("冰島")]),
+    ("IT", [Some("إيطاليا"), Some("Italien"), Some("Italia"), Some("Italie"), Some("इटली"), Some("Italia"), Some("イタリア"), Some("이탈리아"), Some("Italië"), Some("Włochy"), Some("Itália"), Some("Itália"), Some("Италия"), Some("Italien"), Some("İtalya"), Some("Італія"), Some("意大利"), Some("義大利")]),
+    ("JE", [Some("جيرسي"), Some("Jersey"), Some("Jersey"), Some("Jersey"), Some("जर्सी"), Some("Jersey"), Some("ジャージー"), Some("저지 섬"), Some("Jersey"), Some("Jersey"), Some("Jersey"), Some("Jersey"), Some("Джерси"), Some("Jersey"), Some("Jersey"), Some("Джерсі"), Some("泽西岛"), Some("澤西島")]),
+    ("JM", [Some("جامايكا"), Some("Jamaika"), Some("Jamaica"), Some("Jamaïque"), Some("जमैका"), Some("Giamaica"), Some("ジャマイカ"), Some("자메이카"), Some("Jamaica"), Some("Jamajka"), Some("Jamaica"), Some("Jamaica"), Some("Ямайка"), Some("Jamaica"), Some("Jamaika"), Some("Ямайка"), Some("牙买加"), Some("牙買加")]),
+    ("JO", [Some("الأردن"), Some("Jordanien"), Some("Jordania"), Some("Jordanie"), Some("जॉर्डन"), Some("Giordania"), Some("ヨルダン"), Some("요르단"), Some("Jordanië"), Some("Jordania"), Some("Jordânia"), Some("Jordânia"), Some("Иордания"), Some("Jordanien"), Some("Ürdün"), Some("Йорданія"), Some("约旦"), Some("約旦")]),
+    ("JP", [Some("اليابان"), Some("Japan"), Some("Japón"), Some("Japon"), Some("जापान"), Some("Giappone"), Some("日本"), Some("일본"), Some("Japan"), Some("Japonia"), Some("Japão"), Some("Japão"), Some("Япония"), Some("Japan"), Some("Japonya"), Some("Японія"), Some("日本"), Some("日本")]),
+    ("KE", [Some("كينيا"), Some("Kenia"), Some("Kenia"), Some("Kenya"), Some("कीनिया"), Some("Kenya"), Some("ケニア"), Some("케냐"), Some("Kenia"), Some("Kenia"), Some("Quénia"), Some("Quênia"), Some("Кения"), Some("Kenya"), Some("Kenya"), Some("Кенія"), Some("肯尼亚"), Some("肯亞")]),
+    ("KG", [Some("قيرغزستان"), Some("Kirgisistan"), Some("Kirguistán"), Some("Kirghizistan"), Some("किर्गिज़स्तान"), Some("Kirghizistan"), Some("キルギスタン"), Some("키르기스스탄"), Some("Kirgizië"), Some("Kirgistan"), Some("Quirguistão"), Some("Quirguistão"), Some("Киргизия"), Some("Kirgizistan"), Some("Kırgızistan"), Some("Киргизстан"), Some("吉尔吉斯坦"), Some("吉爾吉斯")]),
+    ("KH", [Some("كمبوديا"), Some("Kambodscha"), Some("Camboya"), Some("Cambodge"), Some("कम्बोडिया"), Some("Cambogia"), Some("カンボジア"), Some("캄보디아"), Some("Cambodja"), Some("Kambodża"), Some("Camboja"), Some("Camboja"), Some("Камбоджа"), Some("Kambodja"), Some("Kamboçya"), Some("Камбоджа"), Some("柬埔塞"), Some("柬埔寨")]),
+    ("KI", [Some("كيريباتي"), Some("Kiribati"), Some("Kiribati"), Some("Kiribati"), Some("किरिबाती"), Some("Kiribati"), Some("キリバス"), Some("키리바시"), Some("Kiribati"), Some("Kiribati"), Some("Kiribati"), Some("Kiribati"), Some("Кирибати"), Some("Kiribati"), Some("Kiribati"), Some("Кірибаті"), Some("基里巴斯"), Some("吉里巴斯")]),
+    ("KM", [Some("جزر القمر"), Some("Komoren"), Some("Comores, Islas"), Some("Comores"), Some("कोमोरोस"), Some("Comore"), Some("コモロ"), Some("코모로"), Some("Comoren"), Some("Komory"), Some("Comores"), Some("Comores"), Some("Коморы"), Some("Comorerna"), Some("Komorlar"), Some("Коморські острови"), Some("科摩罗"), Some("葛摩")]),
+    ("KN", [Some("سانت كيتس و نيفس"), Some("St. Kitts und Nevis"), Some("San Cristóbal y Nieves"), Some("Saint-Christophe-et-Niévès"), Some("सन्त किट्स और नेविस"), Some("Saint Kitts e Nevis"), Some("セントクリストファー・ネーヴィス"), Some("세인트키츠 네비스"), Some("Saint Kitts en Nevis"), Some("Saint Kitts i Nevis"), Some("São Cristóvão e Nevis"), Some("São Cristóvão e Névis"), Some("Сент-Китс и Невис"), Some("Sankt Kitts och Nevis"), Some("Saint Kitts ve Nevis"), Some("Сент-Кіттс і Невіс"), Some("圣基茨和尼维斯"), Some("聖克里斯多福及尼維斯")]),
+    ("KP", [Some("كوريا، جمهورية كوريا الشّعبيّة الدّيموقراطيّة"), Some("Nordkorea"), Some("Corea, República Democrática Popular de"), Some("Corée du Nord"), Some("उत्तर कोरिया"), Some("Corea del Nord"), Some("朝鮮民主主義人民共和国"), Some("조선민주주의인민공화국"), Some("Noord-Korea"), Some("Korea Północna"), Some("Coreia do Norte"), Some("Coreia do Norte"), Some("Северная Корея"), Some("Nordkorea"), Some("Kuzey Kore"), Some("Північна Корея"), Some("朝鲜"), Some("北韓")]),
+    ("KR", [Some("كوريا، جمهوريّة كوريا"), Some("Südkorea"), Some("Corea, República de"), Some("Corée du Sud"), Some("दक्षिण कोरिया"), Some("Corea del Sud"), Some("大韓民国 (韓国)"), Some("대한민국"), Some("Zuid-Korea"), Some("Korea Południowa"), Some("Coreia do Sul"), Some("Coreia do Sul"), Some("Южная Корея"), Some("Sydkorea"), Some("Güney Kore"), Some("Південна Корея"), Some("韩国"), Some("南韓")]),
+    ("KW", [Some("الكويت"), Some("Kuwait"), Some("Kuwait"), Some("Koweït"), Some("कुवैत"), Some("Kuwait"), Some("クウェート"), Some("쿠웨이트"), Some("Koeweit"), Some("Kuwejt"), Some("Kuwait"), Some("Kuwait"), Some("Кувейт"), Some("Kuwait"), Some("Kuveyt"), Some("Кувейт"), Some("科威特"), Some("科威特")]),
+    ("KY", [Some("جزر الكيمان"), Some("Cayman-Inseln"), Some("Islas Caimán"), Some("îles Caïmans"), Some("केमन द्वीपसमूह"), Some("Isole Cayman"), Some("ケイマン諸島"), Some("케이맨 제도"), Some("Kaaimaneilanden"), Some("Kajmany"), Some("Ilhas Caimão"), Some("Ilhas Cayman"), Some("Каймановы острова"), Some("Caymanöarna"), Some("Cayman Adaları"), Some("Кайманові острови"), Some("开曼群岛"), Some("開曼群島")]),
+    ("KZ", [Some("كازاخستان"), Some("Kasachstan"), Some("Kazajistán"), Some("Kazakhstan"), Some("कज़ाख़िस्तान"), Some("Kazakistan"), Some("カザフスタン"), Some("카자흐스탄"), Some("Kazachstan"), Some("Kazachstan"), Some("Cazaquistão"), Some("Cazaquistão"), Some("Казахстан"), Some("Kazakstan"), Some("Kazakistan"), Some("Казахстан"), Some("哈萨克斯坦"), Some("哈薩克")]),
+    ("LA", [Some("جمهوريّة لاو الدّيموقراطيّة الشّعبيّة"), Some("Laos"), Some("República Democrática Popular de Lao"), Some("Lao, République démocratique populaire"), Some("लाओ पीपल्स डेमोक्रेटिक रिपब्लिक"), Some("Laos"), Some("ラオス人民民主共和国"), Some("라오 인민 민주주의 공화국"), Some("Laos"), Some("Laos"), Some("República Democrática Popular do Laos"), Some("República Popular Democrática do Laos"), Some("Лаосская Народно-Демократическая Республика"), Some("Laos"), Some("Laos"), Some("Лаос"), Some("老挝"), Some("寮國")]),
+    ("LB", [Some("لبنان"), Some("Libanon"), Some("Líbano"), Some("Liban"), Some("लेबनान"), Some("Libano"), Some("レバノン"), Some("레바논"), Some("Libanon"), Some("Liban"), Some("Líbano"), Some("Líbano"), Some("Ливан"), Some("Libanon"), Some("Lübnan"), Some("Ліван"), Some("黎巴嫩"), Some("黎巴嫩")]),
+    ("LC", [Some("سانت لوسيا"), Some("St. Lucia"), Some("Santa Lucía"), Some("Sainte-Lucie"), Some("सेंट लूसिया"), Some("Saint Lucia"), Some("セントルシア"), Some("세인트루시아"), Some("Saint Lucia"), Some("Saint Lucia"), Some("Santa Lúcia"), Some("Santa Lúcia"), Some("Сент-Люсия"), Some("Sankt Lucia"), Some("Saint Lucia"), Some("Сент-Люсія"), Some("圣路西亚"), Some("聖露西亞")]),
+    ("LI", [Some("ليشتنشتاين"), Some("Liechtenstein"), Some("Liechtenstein"), Some("Liechtenstein"), Some("लिक्टेन्स्टाइन"), Some("Liechtenstein"), Some("リヒテンシュタイン"), Some("리히텐슈타인"), Some("Liechtenstein"), Some("Liechtenstein"), Some("Liechtenstein"), Some("Liechtenstein"), Some("Лихтенштейн"), Some("Liechtenstein"), Some("Lihtenştayn"), Some("Ліхтенштейн"), Some("列支敦士登"), Some("列支敦斯登")]),
+    ("LK", [Some("سريلانكا"), Some("Sri Lanka"), Some("Sri Lanka"), Some("Sri Lanka"), Some("श्रीलंका"), Some("Sri Lanka"), Some("スリランカ"), Some("스리랑카"), Some("Sri Lanka"), Some("Sri Lanka"), Some("Sri Lanka"), Some("Sri Lanka"), Some("Шри-Ланка"), Some("Sri Lanka"), Some("Sri Lanka"), Some("Шрі-Ланка"), Some("斯里兰卡"), Some("斯里蘭卡")]),
+    ("LR", [Some("ليبيريا"), Some("Liberia"), Some("Liberia"), Some("Libéria"), Some("लाइबेरिया"), Some("Liberia"), Some("リベリア"), Some("라이베리아"), Some("Liberia"), Some("Liberia"), Some("Libéria"), Some("Libéria"), Some("Либерия"), Some("Liberia"), Some("Liberya"), Some("Ліберія"), Some("利比里亚"), Some("賴比瑞亞")]),
+    ("LS", [Some("ليسوتو"), Some("Lesotho"), Some("Lesoto"), Some("Lesotho"), Some("लेसोथो"), Some("Lesotho"), Some("レソト"), Some("레소토"), Some("Lesotho"), Some("Lesotho"), Some("Lesoto"), Some("Lesoto"), Some("Лесото"), Some("Lesotho"), Some("Lesoto"), Some("Лесото"), Some("莱索托"), Some("賴索托")]),
+    ("LT", [Some("لثوانيا"), Some("Litauen"), Some("Lituania"), Some("Lituanie"), Some("लिथुआनिया"), Some("Lituania"), Some("リトアニア"), Some("리투아니아"), Some("Litouwen"), Some("Litwa"), Some("Lituânia"), Some("Lituânia"), Some("Литва"), Some("Litauen"), Some("Litvanya"), Some("Литва"), Some("立陶宛"), Some("立陶宛")]),
+    ("LU", [Some("لوكسمبورغ"), Some("Luxemburg"), Some("Luxemburgo"), Some("Luxembourg"), Some("लक्ज़मबर्ग"), Some("Lussemburgo"), Some("ルクセンブルク"), Some("룩셈부르크"), Some("Luxemburg"), Some("Luksemburg"), Some("Luxemburgo"), Some("Luxemburgo"), Some("Люксембург"), Some("Luxemburg"), Some("Lüksemburg"), Some("Люксембург"), Some("卢森堡"), Some("盧森堡")]),
+    ("LV", [Some("لاتفيا"), Some("Lettland"), Some("Letonia"), Some("Lettonie"), Some("लातविया"), Some("Lettonia"), Some("ラトビア"), Some("라트비아"), Some("Letland"), Some("Łotwa"), Some("Letónia"), Some("Letônia"), Some("Латвия"), Some("Lettland"), Some("Letonya"), Some("Латвія"), Some("拉脱维亚"), Some("拉脫維亞")]),
+    ("LY", [Some("ليبيا"), Some("Libyen"), Some("Libia"), Some("Libye"), Some("लीबिया"), Some("Libia"), Some("リビア"), Some("리비아"), Some("Libië"), Some("Libia"), Some("Líbia"), Some("Líbia"), Some("Ливия"), Some("Libyen"), Some("Libya"), Some("Лівія"), Some("利比亚"), Some("利比亞")]),
+    ("MA", [Some("المغرب"), Some("Marokko"), Some("Marruecos"), Some("Maroc"), Some("मोरक्को"), Some("Marocco"), Some("モロッコ"), Some("모로코"), Some("Marokko"), Some("Maroko"), Some("Marrocos"), Some("Marrocos"), Some("Марокко"), Some("Marocko"), Some("Fas"), Some("Марокко"), Some("摩洛哥"), Some("摩洛哥")]),
+    ("MC", [Some("موناكو"), Some("Monaco"), Some("Mónaco"), Some("Monaco"), Some("मोनैको"), Some("Monaco"), Some("モナコ"), Some("모나코"), Some("Monaco"), Some("Monako"), Some("Mónaco"), Some("Mônaco"), Some("Монако"), Some("Monaco"), Some("Monako"), Some("Монако"), Some("摩纳哥"), Some("摩納哥")]),
+    ("MD", [Some("المالديف"), Some("Moldau"), Some("Moldavia"), Some("Moldavie"), Some("मॉल्डोवा"), Some("Moldavia"), Some("モルドバ"), Some("몰도바"), Some("Moldavië"), Some("Mołdawia"), Some("Moldávia"), Some("Moldávia"), Some("Молдавия"), Some("Moldavien"), Some("Moldova"), Some("Молдова"), Some("摩尔多瓦"), Some("摩爾多瓦")]),
+    ("ME", [Some("المنتنيغرو"), Some("Montenegro"), Some("Montenegro"), Some("Monténégro"), Some("मॉन्टेनीग्रो"), Some("Montenegro"), Some("モンテネグロ"), Some("몬테네그로"), Some("Montenegro"), Some("Czarnogóra"), Some("Montenegro"), Some("Montenegro"), Some("Черногория"), Some("Montenegro"), Some("Karadağ"), Some("Чорногорія"), Some("黑山"), Some("蒙特內哥羅")]),
+    ("MF", [Some("سانت مارتين (القطاع الفرنسي)"), Some("Saint Martin (Französischer Teil)"), Some("San Martín (zona francesa)"), Some("Saint-Martin (partie française)"), Some("सेंट मार्टिन (फ्रेंच भाग)"), Some("Saint-Martin (Francia)"), Some("サンマルタン (仏領)"), Some("생마르탱 (프랑스령)"), Some("Sint-Maarten (Frans deel)"), Some("Saint-Martin (część francuska)"), Some("São Martin (Território Francês)"), Some("São Martim (parte francesa)"), Some("Сен-Мартен (Франция)"), Some("Saint Martin (franska delen)"), Some("Saint Martin (Fransız kısmı)"), Some("Сен-Мартен (французька частина)"), Some("法属圣马丁"), Some("聖馬丁 (法屬)")]),
+    ("MG", [Some("مدغشقر"), Some("Madagaskar"), Some("Madagascar"), Some("Madagascar"), Some("मेडागास्कर"), Some("Madagascar"), Some("マダガスカル"), Some("마다가스카르"), Some("Madagaskar"), Some("Madagaskar"), Some("Madagáscar"), Some("Madagascar"), Some("Мадагаскар"), Some("Madagaskar"), Some("Madagaskar"), Some("Мадагаскар"), Some("马达加斯加"), Some("馬達加斯加")]),
+    ("MH", [Some("جزر المارشال"), Some("Marshallinseln"), Some("Islas Marshall"), Some("Îles Marshall"), Some("मार्शल आइलैंड्स"), Some("Isole Marshall"), Some("マーシャル諸島"), Some("마셜 제도"), Some("Marshalleilanden"), Some("Wyspy Marshalla"), Some("Ilhas Marshall"), Some("Ilhas Marshall"), Some("Маршалловы острова"), Some("Marshallöarna"), Some("Marşal Adaları"), Some("Маршаллові острови"), Some("马绍尔群岛"), Some("馬紹爾群島")]),
+    ("MK", [Some("مقدونيا الشمالية"), Some("Nordmazedonien"), Some("Macedonia del Norte"), Some("Macédoine du Nord"), Some("उत्तर मैसेडोनिया"), Some("Macedonia del Nord"), None, Some("북마케도니아"), Some("Noord-Macedonië"), Some("Macedonia Północna"), Some("Macedónia do Norte"), Some("Macedônia do Norte"), Some("Северная Македония"), Some("Nordmakedonien"), Some("Kuzey Makedonya"), Some("Північна Македонія"), Some("北马其顿"), Some("北馬其頓")]),
+    ("ML", [Some("مالي"), Some("Mali"), Some("Malí"), Some("Mali"), Some("माली"), Some("Mali"), Some("マリ"), Some("말리"), Some("Mali"), Some("Mali"), Some("Mali"), Some("Mali"), Some("Мали"), Some("Mali"), Some("Mali"), Some("Малі"), Some("马里"), Some("馬利")]),
+    ("MM", [Some("ميانمار"), Some("Myanmar"), Some("Birmania"), Some("Birmanie"), Some("म्यान्मार"), Some("Birmania"), Some("ミャンマー"), Some("미얀마"), Some("Myanmar"), Some("Mjanma"), Some("Birmânia"), Some("Myanmar"), Some("Мьянма"), Some("Myanmar"), Some("Myanmar"), Some("М’янма"), Some("缅甸"), Some("緬甸")]),
+    ("MN", [Some("منغوليا"), Some("Mongolei"), Some("Mongolia"), Some("Mongolie"), Some("मंगोलिया"), Some("Mongolia"), Some("モンゴル国"), Some("몽골"), Some("Mongolië"), Some("Mongolia"), Some("Mongólia"), Some("Mongólia"), Some("Монголия"), Some("Mongoliet"), Some("Moğolistan"), Some("Монголія"), Some("蒙古"), Some("蒙古")]),
+    ("MO", [Some("مكّاو"), Some("Macao"), Some("Macao"), Some("Macau"), Some("मकाउ"), Some("Macao"), Some("マカオ"), Some("마카오"), Some("Macau"), Some("Makau"), Some("Macau"), Some("Macau"), Some("Макао"), Some("Macao"), Some("Makao"), Some("Макао"), Some("澳门"), Some("澳門")]),
+    ("MP", [Some("جزر ماريانا الشّماليّة"), Some("Nördliche Marianen"), Some("Islas Marianas del Norte"), Some("Îles Mariannes du Nord"), Some("उत्तरी मारियाना द्वीप"), Some("Isole Marianne Settentrionali"), Some("北マリアナ諸島"), Some("북마리아나 제도"), Some("Noordelijke Marianen"), Some("Mariany Północne"), Some("Ilhas Marianas do Norte"), Some("Ilhas Marianas do Norte"), Some("Острова северной Марианы"), Some("Nordmarianerna"), Some("Kuzey Mariana Adaları"), Some("Північні Маріанські Острови"), Some("北马里亚纳群岛"), Some("北馬里亞納群島")]),
+    ("MQ", [Some("مارتينيك"), Some("Martinique"), Some("Martinica"), Some("Martinique"), Some("मार्टीनिक"), Some("Martinica"), Some("マルティニーク"), Some("마르티니크"), Some("Martinique"), Some("Martynika"), Some("Martinica"), Some("Martinica"), Some("Мартиника"), Some("Martinique"), Some("Martinique"), Some("Мартиніка"), Some("马提尼克"), Some("馬丁尼克")]),
+    ("MR", [Some("موريتانيا"), Some("Mauretanien"), Some("Mauritania"), Some("Mauritanie"), Some("मॉरीतानिया"), Some("Mauritania"), Some("モーリタニア"), Some("모리타니"), Some("Mauritanië"), Some("Mauretania"), Some("Mauritânia"), Some("Mauritânia"), Some("Мавритания"), Some("Mauretanien"), Some("Moritanya"), Some("Мавританія"), Some("毛里塔尼亚"), Some("茅利塔尼亞")]),
+    ("MS", [Some("مونتسيرات"), Some("Montserrat"), Some("Montserrat"), Some("Montserrat"), Some("मॉण्टसेराट"), Some("Montserrat"), Some("モントセラト"), Some("몬트세랫"), Some("Montserrat"), Some("Montserrat"), Some("Monserrate"), Some("Montserrat"), Some("Монтсеррат"), Some("Montserrat"), Some("Montserrat"), Some("Монтсеррат"), Some("蒙塞拉特岛"), Some("蒙塞拉特島")]),
+    ("MT", [Some("مالطة"), Some("Malta"), Some("Malta"), Some("Malte"), Some("माल्टा"), Some("Malta"), Some("マルタ"), Some("몰타"), Some("Malta"), Some("Malta"), Some("Malta"), Some("Malta"), Some("Мальта"), Some("Malta"), Some("Malta"), Some("Мальта"), Some("马尔他"), Some("馬爾他")]),
+    ("MU", [Some("موريشيوس"), Some("Mauritius"), Some("Mauricio"), Some("Maurice"), Some("मॉरिशस"), Some("Maurizio"), Some("モーリシャス"), Some("모리셔스"), Some("Mauritius"), Some("Mauritius"), Some("Maurícia"), Some("Maurício"), Some("Маврикий"), Some("Mauritius"), Some("Mauritius"), Some("Маврикій"), Some("毛里求斯"), Some("模里西斯")]),
+    ("MV", [Some("جزر المالديف"), Some("Malediven"), Some("Islas Maldivas"), Some("Maldives"), Some("मालदीव"), Some("Maldive"), Some("モルディブ"), Some("몰디브"), Some("Maldiven"), Some("Malediwy"), Some("Maldivas"), Some("Maldivas"), Some("Мальдивы"), Some("Maldiverna"), Some("Maldivler"), Some("Мальдіви"), Some("马尔代夫"), Some("馬爾地夫")]),
+    ("MW", [Some("ملاوي"), Some("Malawi"), Some("Malaui"), Some("Malawi"), Some("मलावी"), Some("Malawi"), Some("マラウイ"), Some("말라위"), Some("Malawi"), Some("Malawi"), Some("Malawi"), Some("Malaui"), Some("Малави"), Some("Malawi"), Some("Malavi"), Some("Малаві"), Some("马拉维"), Some("馬拉威")]),
+    ("MX", [Some("المكسيك"), Some("Mexiko"), Some("México"), Some("Mexique"), Some("मेक्सिको"), Some("Messico"), Some("メキシコ"), Some("멕시코"), Some("Mexico"), Some("Meksyk"), Some("México"), Some("México"), Some("Мексика"), Some("Mexiko"), Some("Meksika"), Some("Мексика"), Some("墨西哥"), Some("墨西哥")]),
+    ("MY", [Some("ماليزيا"), Some("Malaysia"), Some("Malasia"), Some("Malaisie"), Some("मलेशिया"), Some("Malaysia"), Some("マレーシア"), Some("말레이시아"), Some("Maleisië"), Some("Malezja"), Some("Malásia"), Some("Malásia"), Some("Малайзия"), Some("Malaysia"), Some("Malezya"), Some("Малайзія"), Some("马来西亚"), Some("馬來西亞")]),
+    ("MZ", [Some("موزمبيق"), Some("Mosambik"), Some("Mozambique"), Some("Mozambique"), Some("मोज़ाम्बीक"), Some("Mozambico"), Some("モザンビーク"), Some("모잠비크"), Some("Mozambique"), Some("Mozambik"), Some("Moçambique"), Some("Moçambique"), Some("Мозамбик"), Some("Moçambique"), Some("Mozambik"), Some("Мозамбік"), Some("莫桑比克"), Some("莫三比克")]),
+    ("NA", [Some("ناميبيا"), Some("Namibia"), Some("Namibia"), Some("Namibie"), Some("नामीबिया"), Some("Namibia"), Some("ナミビア"), Some("나미비아"), Some("Namibië"), Some("Namibia"), Some("Namíbia"), Some("Namíbia"), Some("Намибия"), Some("Namibia"), Some("Namibya"), Some("Намібія"), Some("纳米比亚"), Some("納米比亞")]),
+    ("NC", [Some("نيو قلدونيا"), Some("Neukaledonien"), Some("Nueva Caledonia"), Some("Nouvelle-Calédonie"), Some("नया कैलेडोनिया"), Some("Nuova Caledonia"), Some("ニューカレドニア"), Some("누벨칼레도니"), Some("Nieuw-Caledonië"), Some("Nowa Kaledonia"), Some("Nova Caledónia"), Some("Nova Caledônia"), Some("Новая Каледония"), Some("Nya Kaledonien"), Some("Yeni Kaledonya"), Some("Нова Каледонія"), Some("新喀里多尼亚"), Some("新喀里多尼亞")]),
+    ("NE", [Some("النّيجر"), Some("Niger"), Some("Niger"), Some("Niger"), Some("नाइजर"), Some("Niger"), Some("ニジェール"), Some("니제르"), Some("Niger"), Some("Niger"), Some("Níger"), Some("Níger"), Some("Нигер"), Some("Niger"), Some("Nijer"), Some("Нігер"), Some("尼日尔"), Some("尼日")]),
+    ("NF", [Some("جزيرة نورفولك"), Some("Norfolkinsel"), Some("Isla Norfolk"), Some("île Norfolk"), Some("नॉर्फ़ोक द्वीप"), Some("Isola Norfolk"), Some("ノーフォーク島"), Some("노퍽 섬"), Some("Norfolk"), Some("Wyspy Norfolk"), Some("Ilha Norfolk"), Some("Ilha Norfolk"), Some("Остров Норфолк"), Some("Norfolköarna"), Some("Norfolk Adası"), Some("Острів Норфолк"), Some("诺福克岛"), Some("諾福克島")]),
+    ("NG", [Some("نيجيريا"), Some("Nigeria"), Some("Nigeria"), Some("Nigeria"), Some("नाईजीरिया"), Some("Nigeria"), Some("ナイジェリア"), Some("나이지리아"), Some("Nigeria"), Some("Nigeria"), Some("Nigéria"), Some("Nigéria"), Some("Нигерия"), Some("Nigeria"), Some("Nijerya"), Some("Нігерія"), Some("尼日利亚"), Some("奈及利亞")]),
+    ("NI", [Some("نيكاراجوا"), Some("Nicaragua"), Some("Nicaragua"), Some("Nicaragua"), Some("निकारागुआ"), Some("Nicaragua"), Some("ニカラグア"), Some("니카라과"), Some("Nicaragua"), Some("Nikaragua"), Some("Nicarágua"), Some("Nicarágua"), Some("Никарагуа"), Some("Nicaragua"), Some("Nikaragua"), Some("Нікарагуа"), Some("尼加拉瓜"), Some("尼加拉瓜")]),
+    ("NL", [Some("هولندا"), Some("Niederlande"), Some("Países Bajos"), Some("Pays-Bas"), Some("नीदरलैण्ड"), Some("Paesi Bassi"), Some("オランダ"), Some("네덜란드"), Some("Nederland"), Some("Holandia"), Some("Países Baixos"), Some("Países Baixos"), Some("Нидерланды"), Some("Nederländerna"), Some("Hollanda"), Some("Нідерланди"), Some("荷兰"), Some("荷蘭")]),
+    ("NO", [Some("النّرويج"), Some("Norwegen"), Some("Noruega"), Some("Norvège"), Some("नॉर्वे"), Some("Norvegia"), Some("ノルウェー"), Some("노르웨이"), Some("Noorwegen"), Some("Norwegia"), Some("Noruega"), Some("Noruega"), Some("Норвегия"), Some("Norge"), Some("Norveç"), Some("Норвегія"), Some("挪威"), Some("挪威")]),
+    ("NP", [Some("نيبال"), Some("Nepal"), Some("Nepal"), Some("Népal"), Some("नेपाल"), Some("Nepal"), Some("ネパール"), Some("네팔"), Some("Nepal"), Some("Nepal"), Some("Nepal"), Some("Nepal"), Some("Непал"), Some("Nepal"), Some("Nepal"), Some("Непал"), Some("尼泊尔"), Some("尼泊爾")]),
+    ("NR", [Some("ناورو"), Some("Nauru"), Some("Nauru"), Some("Nauru"), Some("नौरु"), Some("Nauru"), Some("ナウル"), Some("나우루"), Some("Nauru"), Some("Nauru"), Some("Nauru"), Some("Nauru"), Some("Науру"), Some("Nauru"), Some("Nauru"), Some("науру"), Some("瑙鲁"), Some("諾魯")]),
+    ("NU", [Some("نيوي"), Some("Niue"), Some("Niue"), Some("Nioue"), Some("निउए"), Some("Niue"), Some("ニウエ"), Some("니우에"), Some("Niue"), Some("Niue"), Some("Niue"), Some("Niue"), Some("Ниуэ"), Some("Niue"), Some("Niue"), Some("Ніуе"), Some("纽埃"), Some("紐埃")]),
+    ("NZ", [Some("نيوزيلاندا"), Some("Neuseeland"), Some("Nueva Zelanda"), Some("Nouvelle-Zélande"), Some("न्यूज़ीलैण्ड"), Some("Nuova Zelanda"), Some("ニュージーランド"), Some("뉴질랜드"), Some("Nieuw-Zeeland"), Some("Nowa Zelandia"), Some("Nova Zelândia"), Some("Nova Zelândia"), Some("Новая Зеландия"), Some("Nya Zeeland"), Some("Yeni Zelanda"), Some("Нова Зеландія"), Some("新西兰"), Some("紐西蘭")]),
+    ("OM", [Some("عمان"), Some("Oman"), Some("Omán"), Some("Oman"), Some("ओमान"), Some("Oman"), Some("オマーン"), Some("오만"), Some("Oman"), Some("Oman"), Some("Omã"), Some("Omã"), Some("Оман"), Some("Oman"), Some("Umman"), Some("Оман"), Some("阿曼"), Some("阿曼")]),
+    ("PA", [Some("بنما"), Some("Panama"), Some("Panamá"), Some("Panama"), Some("पनामा"), Some("Panama"), Some("パナマ"), Some("파나마"), Some("Panama"), Some("Panama"), Some("Panamá"), Some("Panamá"), Some("Панама"), Some("Panama"), Some("Panama"), Some("Панама"), Some("巴拿马"), Some("巴拿馬")]),
+    ("PE", [Some("البيرو"), Some("Peru"), Some("Perú"), Some("Pérou"), Some("पेरू"), Some("Perù"), Some("ペルー"), Some("페루"), Some("Peru"), Some("Peru"), Some("Peru"), Some("Peru"), Some("Перу"), Some("Peru"), Some("Peru"), Some("Перу"), Some("秘鲁"), Some("祕魯")]),
+    ("PF", [Some("بولينيسيا الفرنسيّة"), Some("Französisch-Polynesien"), Some("Polinesia Francesa"), Some("Polynésie française"), Some("फ़्रान्सी पॉलिनेशिया"), Some("Polinesia francese"), Some("仏領ポリネシア"), Some("프랑스령 폴리네시아"), Some("Frans-Polynesië"), Some("Polinezja Francuska"), Some("Polinésia Francesa"), Some("Polinésia Francesa"), Some("Французская Полинезия"), Some("Franska Polynesien"), Some("Fransız Polinezyası"), Some("Французька Полінезія"), Some("法属玻利尼西亚"), Some("法屬玻里尼西亞")]),
+    ("PG", [Some("بابوا غينيا الجديدة"), Some("Papua-Neuguinea"), Some("Papúa Nueva Guinea"), Some("Papouasie-Nouvelle-Guinée"), Some("पापुआ न्यू गिनी"), Some("Papua Nuova Guinea"), Some("パプアニューギニア"), Some("파푸아뉴기니"), Some("Papoea-Nieuw-Guinea"), Some("Papua-Nowa Gwinea"), Some("Papua Nova Guiné"), Some("Papua-Nova Guiné"), Some("Папуа — Новая Гвинея"), Some("Papua Nya Guinea"), Some("Papua Yeni Gine"), Some("Папуа Нова Гвінея"), Some("巴布亚新几内亚"), Some("巴布亞紐幾內亞")]),
+    ("PH", [Some("الفلبّين"), Some("Philippinen"), Some("Filipinas"), Some("Philippines"), Some("फ़िलीपीन्स"), Some("Filippine"), Some("フィリピン"), Some("필리핀"), Some("Filipijnen"), Some("Filipiny"), Some("Filipinas"), Some("Filipinas"), Some("Филиппины"), Some("Filippinerna"), Some("Filipinler"), Some("Філіппіни"), Some("菲律宾"), Some("菲律賓")]),
+    ("PK", [Some("باكستان"), Some("Pakistan"), Some("Pakistán"), Some("Pakistan"), Some("पाकिस्तान"), Some("Pakistan"), Some("パキスタン"), Some("파키스탄"), Some("Pakistan"), Some("Pakistan"), Some("Paquistão"), Some("Paquistão"), Some("Пакистан"), Some("Pakistan"), Some("Pakistan"), Some("Пакистан"), Some("巴基斯坦"), Some("巴基斯坦")]),
+    ("PL", [Some("بولندا"), Some("Polen"), Some("Polonia"), Some("Pologne"), Some("पोलैंड"), Some("Polonia"), Some("ポーランド"), Some("폴란드"), Some("Polen"), Some("Polska"), Some("Polónia"), Some("Polônia"), Some("Польша"), Some("Polen"), Some("Polonya"), Some("Польща"), Some("波兰"), Some("波蘭")]),
+    ("PM", [Some("سانت بيير و ميكيلون"), Some("St. Pierre und Miquelon"), Some("San Pedro y Miquelon"), Some("Saint-Pierre-et-Miquelon"), Some("साँ-प्येर और मीकेलों"), Some("Saint-Pierre e Miquelon"), Some("サンピエール及びミクロン"), Some("생피에르 미클롱"), Some("Saint-Pierre en Miquelon"), Some("Saint-Pierre i Miquelon"), Some("Saint Pierre e Miquelon"), Some("São Pedro e Miquelon"), Some("Сен-Пьер и Микелон"), Some("Sankt Pierre och Miquelon"), Some("Saint Pierre ve Miquelon"), Some("Сен-П'єр і Мікелон"), Some("圣皮埃尔和密克隆"), Some("聖皮耶及密克隆群島")]),
+    ("PN", [Some("بتكيرن"), Some("Pitcairn"), Some("Pitcairn"), Some("Îles Pitcairn"), Some("पिटकायर्न"), Some("Pitcairn"), Some("ピトケアン"), Some("핏케언 제도"), Some("Pitcairneilanden"), Some("Pitcairn"), Some("Pitcairn"), Some("Pitcairn"), Some("Питкэрн"), Some("Pitcairn"), Some("Pitcairn"), Some("Піткерн"), Some("皮特克恩"), Some("皮特肯島")]),
+    ("PR", [Some("بورتوريكو"), Some("Puerto Rico"), Some("Puerto Rico"), Some("Porto Rico"), Some("प्युर्तो रिको"), Some("Portorico"), Some("プエルトリコ"), Some("푸에르토리코"), Some("Puerto Rico"), Some("Portoryko"), Some("Porto Rico"), Some("Porto Rico"), Some("Пуэрто-Рико"), Some("Puerto Rico"), Some("Porto Riko"), Some("Пуерто-Рико"), Some("波多黎各"), Some("波多黎各")]),
+    ("PS", [Some("دولة فلسطين"), Some("Palästina, Staat"), Some("Palestina, Estado de"), Some("Palestine, État de"), Some("पैलेस्टाइन, स्टेट ऑफ़"), Some("Palestina, Stato di"), Some("パレスチナ"), Some("팔레스타인"), Some("Palestina, Staat"), Some("Palestyna (państwo)"), Some("Palestina, Estado da"), Some("Palestina, Estado da"), Some("Палестина"), Some("Staten Palestina"), Some("Filistin Devleti"), Some("Палестина, Держава"), Some("巴勒斯坦"), Some("巴勒斯坦")]),
+    ("PT", [Some("البرتغال"), Some("Portugal"), Some("Portugal"), Some("Portugal"), Some("पुर्तगाल"), Some("Portogallo"), Some("ポルトガル"), Some("포르투갈"), Some("Portugal"), Some("Portugalia"), Some("Portugal"), Some("Portugal"), Some("Португалия"), Some("Portugal"), Some("Portekiz"), Some("Португалія"), Some("葡萄牙"), Some("葡萄牙")]),
+    ("PW", [Some("بالاو"), Some("Palau"), Some("Palaos"), Some("Palaos"), Some("पलाउ"), Some("Palau"), Some("パラオ"), Some("팔라우"), Some("Palau"), Some("Palau"), Some("Palau"), Some("Palau"), Some("Палау"), Some("Palau"), Some("Palau"), Some("Палау"), Some("帕劳"), Some("帛琉")]),
+    ("PY", [Some("الباراغواي"), Some("Paraguay"), Some("Paraguay"), Some("Paraguay"), Some("पैराग्वे"), Some("Paraguay"), Some("パラグアイ"), Some("파라과이"), Some("Paraguay"), Some("Paragwaj"), Some("Paraguai"), Some("Paraguai"), Some("Парагвай"), Some("Paraguay"), Some("Paraguay"), Some("Парагвай"), Some("巴拉圭"), Some("巴拉圭")]),
+    ("QA", [Some("قطر"), Some("Katar"), Some("Catar"), Some("Qatar"), Some("क़तर"), Some("Qatar"), Some("カタール"), Some("카타르"), Some("Qatar"), Some("Katar"), Some("Catar"), Some("Catar"), Some("Катар"), Some("Qatar"), Some("Katar"), Some("Катар"), Some("卡塔尔"), Some("卡達")]),
+    ("RE", [Some("ريونيون"), Some("Réunion"), Some("Reunión"), Some("Réunion, Île de la"), Some("रेयूनियों"), Some("Riunione"), Some("レユニオン"), Some("레위니옹"), Some("Réunion"), Some("Reunion"), Some("Ilha Reunião"), Some("Reunião"), Some("Реюньон"), Some("Réunion"), Some("Réunion"), Some("Реюньйон"), Some("留尼汪"), Some("留尼旺島")]),
+    ("RO", [Some("رومانيا"), Some("Rumänien"), Some("Rumanía"), Some("Roumanie"), Some("रोमानिया"), Some("Romania"), Some("ルーマニア"), Some("루마니아"), Some("Roemenië"), Some("Rumunia"), Some("Roménia"), Some("Romênia"), Some("Румыния"), Some("Rumänien"), Some("Romanya"), Some("Румунія"), Some("罗马尼亚"), Some("羅馬尼亞")]),
+    ("RS", [Some("صربية"), Some("Serbien"), Some("Serbia"), Some("Serbie"), Some("सर्बिया"), Some("Serbia"), Some("セルビア"), Some("세르비아"), Some("Servië"), Some("Serbia"), Some("Sérvia"), Some("Sérvia"), Some("Сербия"), Some("Serbien"), Some("Sırbistan"), Some("Сербія"), Some("塞尔维亚"), Some("塞爾維亞")]),
+    ("RU", [Some("الاتّحاد الرّوسي"), Some("Russische Föderation"), Some("Federación Rusa"), Some("Russie, Fédération de"), Some("रशियन फेडेरशन"), Some("Russia"), Some("ロシア連邦"), Some("러시아 연방"), Some("Rusland"), Some("Federacja Rosyjska"), Some("Federação Russa"), Some("Federação Russa"), Some("Российская Федерация"), Some("Ryska federationen"), Some("Rusya Federasyonu"), Some("Російська Федерація"), Some("俄罗斯"), Some("俄羅斯聯邦")]),
+    ("RW", [Some("رواندا"), Some("Ruanda"), Some("Ruanda"), Some("Rwanda"), Some("रवाण्डा"), Some("Ruanda"), Some("ルワンダ"), Some("르완다"), Some("Rwanda"), Some("Ruanda"), Some("Ruanda"), Some("Ruanda"), Some("Руанда"), Some("Rwanda"), Some("Ruanda"), Some("Руанда"), Some("卢旺达"), Some("盧安達")]),
+    ("SA", [Some("السّعوديّة"), Some("Saudi-Arabien"), Some("Arabia Saudí"), Some("Arabie saoudite"), Some("सउदी अरब"), Some("Arabia Saudita"), Some("サウジアラビア"), Some("사우디아라비아"), Some("Saoedi-Arabië"), Some("Arabia Saudyjska"), Some("Arábia Saudita"), Some("Arábia Saudita"), Some("Саудовская Аравия"), Some("Saudiarabien"), Some("Suudi Arabistan"), Some("Саудівська Аравія"), Some("沙特阿拉伯"), Some("沙烏地阿拉伯")]),
+    ("SB", [Some("جزر سولومن"), Some("Salomoninseln"), Some("Islas Salomón"), Some("Salomon, Îles"), Some("सोलोमन द्वीपसमूह"), Some("Isole Salomone"), Some("ソロモン諸島"), Some("솔로몬 제도"), Some("Salomonseilanden"), Some("Wyspy Salomona"), Some("Ilhas Salomão"), Some("Ilhas Salomão"), Some("Соломоновы Острова"), Some("Salomonöarna"), Some("Solomon Adaları"), Some("Соломонові Острови"), Some("所罗门群岛"), Some("索羅門群島")]),
+    ("SC", [Some("السّيشل"), Some("Seychellen"), Some("Seychelles"), Some("Seychelles"), Some("सेशेल्स"), Some("Seychelles"), Some("セーシェル"), Some("세이셸"), Some("Seychellen"), Some("Seszele"), Some("Seychelles"), Some("Seychelles"), Some("Сейшелы"), Some("Seychellerna"), Some("Seyşeller"), Some("Сейшели"), Some("塞舌尔"), Some("塞席爾")]),
+    ("SD", [Some("السّودان"), Some("Sudan"), Some("Sudán"), Some("Soudan"), Some("सूडान"), Some("Sudan"), Some("スーダン"), Some("수단"), Some("Soedan"), Some("Sudan"), Some("Sudão"), Some("Sudão"), Some("Судан"), Some("Sudan"), Some("Sudan"), Some("Судан"), Some("苏丹"), Some("蘇丹")]),
+    ("SE", [Some("السّويد"), Some("Schweden"), Some("Suecia"), Some("Suède"), Some("स्वीडन"), Some("Svezia"), Some("スウェーデン"), Some("스웨덴"), Some("Zweden"), Some("Szwecja"), Some("Suécia"), Some("Suécia"), Some("Швеция"), Some("Sverige"), Some("İsveç"), Some("Швеція"), Some("瑞典"), Some("瑞典")]),
+    ("SG", [Some("سنغافورة"), Some("Singapur"), Some("Singapur"), Some("Singapour"), Some("सिंगापुर"), Some("Singapore"), Some("シンガポール"), Some("싱가포르"), Some("Singapore"), Some("Singapur"), Some("Singapura"), Some("Cingapura"), Some("Сингапур"), Some("Singapore"), Some("Singapur"), Some("Сінгапур"), Some("新加坡"), Some("新加坡")]),
+    ("SH", [Some("ساينت هيلينا، تريستان دا كونا"), Some("St. Helena, Ascension und Tristan da Cunha"), Some("Santa Elena, Ascensión y Tristán de Acuña"), Some("Sainte-Hélène, Ascension et Tristan da Cunha"), Some("सेंट हेलेना, असेंशन और त्रिस्तान दा कुन्हा"), Some("Sant'Elena, Ascensione e Tristan da Cunha"), Some("セントヘレナ、アセンション及びトリスタン・ダ・クーニャ"), Some("세인트헬레나 어센션 트리스탄다쿠냐"), Some("Sint-Helena, Ascension en Tristan da Cunha"), Some("Wyspa Świętej Heleny, Wyspa Wniebowstąpienia i Tristan da Cunha"), Some("Santa Helena, Ascensão e Tristão da Cunha"), Some("Santa Helena, Ascensão e Tristão da Cunha"), Some("Остров Святой Елены, Остров Вознесения и Тристан-да-Кунья"), Some("Saint Helena, Ascension och Tristan da Cunha"), Some("Saint Helena, Ascension ve Tristan da Cunha"), Some("Острови Святої Єлени, Вознесіння і Тристан-да-Кунья"), Some("圣赫勒拿-阿森松-特里斯坦达库尼亚"), Some("聖赫倫那島、阿森松島及崔斯坦達庫尼亞群島")]),
+    ("SI", [Some("سلوفينيا"), Some("Slowenien"), Some("Eslovenia"), Some("Slovénie"), Some("स्लोवेनिया"), Some("Slovenia"), Some("スロベニア"), Some("슬로베니아"), Some("Slovenië"), Some("Słowenia"), Some("Eslovénia"), Some("Eslovênia"), Some("Словения"), Some("Slovenien"), Some("Slovenya"), Some("Словенія"), Some("斯洛文尼亚"), Some("斯洛維尼亞")]),
+    ("SJ", [Some("سفالبارد و جان ماين"), Some("Svalbard und Jan Mayen"), Some("Svalbard y Jan Mayen"), Some("Svalbard et île Jan Mayen"), Some("स्वालबार्ड एन्ड जैन माएन"), Some("Svalbard e Jan Mayen"), Some("スヴァールバル及びヤンマイエン"), Some("스발바르 얀마옌 제도"), Some("Spitsbergen en Jan Mayen"), Some("Svalbard i Jan Mayen"), Some("Svalbard e Jan Mayen"), Some("Svalbard e a Ilha de Jan Mayen"), Some("Шпицберген и Ян-Майен"), Some("Svalbard och Jan Mayen"), Some("Svalbard ve Jan Mayen"), Some("Острови Свальбард і Ян Маєн"), Some("斯瓦尔巴特和扬马延岛"), Some("冷岸群島及央棉")]),
+    ("SK", [Some("سلوفاكيا"), Some("Slowakei"), Some("Eslovaquia"), Some("Slovaquie"), Some("स्लोवाकिया"), Some("Slovacchia"), Some("スロバキア"), Some("슬로바키아"), Some("Slowakije"), Some("Słowacja"), Some("Eslováquia"), Some("Eslováquia"), Some("Словакия"), Some("Slovakien"), Some("Slovakya"), Some("Словаччина"), Some("斯洛伐克"), Some("斯洛伐克")]),
+    ("SL", [Some("سيراليون"), Some("Sierra Leone"), Some("Sierra Leona"), Some("Sierra Leone"), Some("सिएरा लियोन"), Some("Sierra Leone"), Some("シエラレオネ"), Some("시에라리온"), Some("Sierra Leone"), Some("Sierra Leone"), Some("Serra Leoa"), Some("Serra Leoa"), Some("Сьерра-Леоне"), Some("Sierra Leone"), Some("Sierra Leone"), Some("Сьєрра-Леоне"), Some("塞拉利昂"), Some("獅子山")]),
+    ("SM", [Some("سان مارينو"), Some("San Marino"), Some("San Marino"), Some("Saint-Marin"), Some("सान मारिनो"), Some("San Marino"), Some("サンマリノ"), Some("산마리노"), Some("San Marino"), Some("San Marino"), Some("San Marino"), Some("São Marino"), Some("Сан-Марино"), Some("San Marino"), Some("San Marino"), Some("Сан-Марино"), Some("圣马力诺市"), Some("聖馬利諾")]),
+    ("SN", [Some("السّنغال"), Some("Senegal"), Some("Senegal"), Some("Sénégal"), Some("सेनेगल"), Some("Senegal"), Some("セネガル"), Some("세네갈"), Some("Senegal"), Some("Senegal"), Some("Senegal"), Some("Senegal"), Some("Сенегал"), Some("Senegal"), Some("Senegal"), Some("Сенегал"), Some("塞内加尔"), Some("塞內加爾")]),
+    ("SO", [Some("الصّومال"), Some("Somalia"), Some("Somalia"), Some("Somalie"), Some("सोमालिया"), Some("Somalia"), Some("ソマリア"), Some("소말리아"), Some("Somalië"), Some("Somalia"), Some("Somália"), Some("Somália"), Some("Сомали"), Some("Somalia"), Some("Somali"), Some("Сомалі"), Some("索马里"), Some("索馬利亞")]),
+    ("SR", [Some("سورينام"), Some("Suriname"), Some("Surinám"), Some("Surinam"), Some("सूरीनाम"), Some("Suriname"), Some("スリナム"), Some("수리남"), Some("Suriname"), Some("Surinam"), Some("Suriname"), Some("Suriname"), Some("Суринам"), Some("Surinam"), Some("Surinam"), Some("Суринам"), Some("苏里南"), Some("蘇利南")]),
+    ("SS", [Some("جنوب السّودان"), Some("Südsudan"), Some("Sudán del Sur"), Some("Soudan du Sud"), Some("दक्षिण सूडान"), Some("Sudan del sud"), Some("南スーダン"), Some("남수단"), Some("Zuid-Soedan"), Some("Sudan Południowy"), Some("Sudão do Sul"), Some("Sudão do Sul"), Some("Южный Судан"), Some("Sydsudan"), Some("Güney Sudan"), Some("Південний Судан"), Some("南苏丹"), Some("南蘇丹")]),
+    ("ST", [Some("ساو تومي و برنسبي"), Some("São Tomé und Príncipe"), Some("Santo Tomé y Príncipe"), Some("Sao Tomé-et-Principe"), Some("साओ तोमे और प्रिन्सिपी"), Some("São Tomé e Príncipe"), Some("サントメ・プリンシペ"), Some("상투메 프린시페"), Some("Sao Tomé en Principe"), Some("Wyspy Świętego Tomasza i Książęca"), Some("São Tomé e Príncipe"), Some("São Tomé e Príncipe"), Some("Сан-Томе и Принсипи"), Some("São Tomé och Príncipe"), Some("Sao Tome ve Principe"), Some("Сан-Томе і Принсіпі"), Some("圣多美和普林西比"), Some("聖多美及普林西比")]),
+    ("SV", [Some("السّلفادور"), Some("El Salvador"), Some("El Salvador"), Some("Salvador"), Some("अल साल्वाडोर"), Some("El Salvador"), Some("エルサルバドル"), Some("엘살바도르"), Some("El Salvador"), Some("Salwador"), Some("El Salvador"), Some("El Salvador"), Some("Сальвадор"), Some("El Salvador"), Some("El Salvador"), Some("Сальвадор"), Some("萨尔瓦多"), Some("薩爾瓦多")]),
+    ("SX", [Some("سانت مارتن (الجزء الهولندي)"), Some("Saint-Martin (Niederländischer Teil)"), Some("Isla de San Martín (zona holandsea)"), Some("Saint-Martin (partie néerlandaise)"), Some("सेंट मार्टिन (डच भाग)"), Some("Sint Maarten (Olanda)"), Some("サンマルタン (オランダ領)"), Some("신트마르턴 (네덜란드령)"), Some("Sint Maarten (Nederlands deel)"), Some("Sint Maarten (część holenderska)"), Some("São Martinho (Países Baixos)"), Some("São Martim (parte holandesa)"), Some("Синт-Мартен (голландская часть)"), Some("Sint Maarten (nederländska delen)"), Some("Sint Maarten (Hollanda kısmı)"), Some("Сінт-Мартен (голландська частина)"), Some("荷属圣马丁"), Some("聖馬丁 (荷屬)")]),
+    ("SY", [Some("الجمهوريّة العربيّة السّوريّة"), Some("Syrien"), Some("República árabe de Siria"), Some("Syrienne, République arabe"), Some("सीरियन अरब रिपब्लिक"), Some("Siria"), Some("シリア・アラブ共和国"), Some("시리아 아랍 공화국"), Some("Syrië"), Some("Syria"), Some("República Árabe Síria"), Some("República Árabe da Síria"), Some("Сирийская Арабская Республика"), Some("Syrien"), Some("Suriye"), Some("Сирія"), Some("叙利亚"), Some("敘利亞")]),
+    ("SZ", [Some("إسواتيني"), Some("Eswatini"), Some("Esuatini"), Some("Eswatini"), Some("एस्वाटिनी"), Some("Eswatini"), None, Some("에스와티니"), Some("Eswatini"), Some("Eswatini"), Some("Suazilândia"), Some("Suazilândia"), Some("Эсватини"), Some("Swaziland"), Some("Eswatini"), Some("Есватіні"), Some("斯威士兰"), Some("史瓦帝尼")]),
+    ("TC", [Some("جزر التّرك و الكايكوس"), Some("Turks- und Caicosinseln"), Some("Islas Turcas y Caicos"), Some("îles Turques-et-Caïques"), Some("तुर्क और केकोस द्वीपसमूह"), Some("Isole Turks e Caicos"), Some("タークス及びカイコス諸島"), Some("터크스 케이커스 제도"), Some("Turks- en Caicoseilanden"), Some("Turks i Caicos"), Some("Ilhas Turcas e Caicos"), Some("Ilhas Turks e Caicos"), Some("Острова Туркс и Каикос"), Some("Turks- och Caicosöarna"), Some("Turks ve Caicos Adaları"), Some("Острови Теркс і Кайкос"), Some("特克斯和凯科斯群岛"), Some("土克凱可群島")]),
+    ("TD", [Some("تشاد"), Some("Tschad"), Some("Chad"), Some("Tchad"), Some("चाड"), Some("Ciad"), Some("チャド"), Some("차드"), Some("Tsjaad"), Some("Czad"), Some("Chade"), Some("Chade"), Some("Чад"), Some("Tchad"), Some("Çad"), Some("Чад"), Some("乍得"), Some("查德")]),
+    ("TF", [Some("المقاطعات الفرنسيّة الجنوبيّة"), Some("Französische Süd- und Antarktisgebiete"), Some("Territorios Franceses del Sur"), Some("Terres australes françaises"), Some("फ्रेंच साउदर्न टेरीटरीज़"), Some("Territori francesi meridionali"), Some("フランス南方領土"), Some("프랑스령 남 자치구역"), Some("Franse Zuidelijke Gebieden"), Some("Francuskie Terytoria Południowe"), Some("Territórios Franceses do Sul"), Some("Territórios Franceses do Sul"), Some("Французские южные территории"), Some("Franska sydterritorierna"), Some("Fransız Güney Bölgeleri"), Some("Французькі Південні Території"), Some("法属南半球领地"), Some("法屬南部領地")]),
+    ("TG", [Some("توغو"), Some("Togo"), Some("Togo"), Some("Togo"), Some("टोगो"), Some("Togo"), Some("トーゴ"), Some("토고"), Some("Togo"), Some("Togo"), Some("Togo"), Some("Togo"), Some("Того"), Some("Togo"), Some("Togo"), Some("Того"), Some("多哥"), Some("多哥")]),
+    ("TH", [Some("تايلاند"), Some("Thailand"), Some("Tailandia"), Some("Thaïlande"), Some("थाईलैण्ड"), Some("Thailandia"), Some("タイ"), Some("태국"), Some("Thailand"), Some("Tajlandia"), Some("Tailândia"), Some("Tailândia"), Some("Таиланд"), Some("Thailand"), Some("Tayland"), Some("Таїланд"), Some("泰国"), Some("泰國")]),
+    ("TJ", [Some("طاجيكستان"), Some("Tadschikistan"), Some("Tayikistán"), Some("Tadjikistan"), Some("ताजिकिस्तान"), Some("Tagikistan"), Some("タジキスタン"), Some("타지키스탄"), Some("Tadzjikistan"), Some("Tadżykistan"), Some("Tajiquistão"), Some("Tadjiquistão"), Some("Таджикистан"), Some("Tadzjikistan"), Some("Tacikistan"), Some("Таджикистан"), Some("塔吉克斯坦"), Some("塔吉克")]),
+    ("TK", [Some("جزر توكيلو"), Some("Tokelau"), Some("Tokelau"), Some("Tokelau"), Some("टोकेलाऊ"), Some("Tokelau"), Some("トケラウ"), Some("토켈라우"), Some("Tokelau"), Some("Tokelau"), Some("Tokelau"), Some("Toquelau"), Some("Токелау"), Some("Tokelau"), Some("Tokelau"), Some("токелау"), Some("托克劳"), Some("托克勞")]),
+    ("TL", [Some("تيمور-ليستي"), Some("Timor-Leste"), Some("Timor Oriental"), Some("Timor oriental"), Some("तिमोर-लेस्टे"), Some("Timor Est"), Some("東ティモール"), Some("동티모르"), Some("Oost-Timor"), Some("Timor Wschodni"), Some("Timor-Leste"), Some("Timor Leste"), Some("Восточный Тимор"), Some("Östtimor"), Some("Timor-Leste"), Some("Східний Тимор"), Some("东帝汶"), Some("東帝汶")]),
+    ("TM", [Some("تركمانستان"), Some("Turkmenistan"), Some("Turkmenistán"), Some("Turkménistan"), Some("तुर्कमेनिस्तान"), Some("Turkmenistan"), Some("トルクメニスタン"), Some("투르크메니스탄"), Some("Turkmenistan"), Some("Turkmenistan"), Some("Turquemenistão"), Some("Turcomenistão"), Some("Туркменистан"), Some("Turkmenistan"), Some("Türkmenistan"), Some("Туркменістан"), Some("土库曼斯坦"), Some("土庫曼")]),
+    ("TN", [Some("تونس"), Some("Tunesien"), Some("Tunez"), Some("Tunisie"), Some("ट्यूनिशिया"), Some("Tunisia"), Some("チュニジア"), Some("튀니지"), Some("Tunesië"), Some("Tunezja"), Some("Tunísia"), Some("Tunísia"), Some("Тунис"), Some("Tunisien"), Some("Tunus"), Some("Туніс"), Some("突尼斯"), Some("突尼西亞")]),
+    ("TO", [Some("تونغا"), Some("Tonga"), Some("Tonga"), Some("Tonga"), Some("टोंगा"), Some("Tonga"), Some("トンガ"), Some("통가"), Some("Tonga"), Some("Tonga"), Some("Tonga"), Some("Tonga"), Some("Тонга"), Some("Tonga"), Some("Tonga"), Some("Тонга"), Some("汤加"), Some("東加")]),
+    ("TR", [None, Some("Türkei"), None, None, None, None, None, Some("튀르키예"), Some("Turkije"), Some("Turcja"), Some("Turquia"), Some("Turquia"), None, Some("Turkiet"), Some("Türkiye"), Some("Туреччина"), Some("土耳其"), Some("土耳其")]),
+    ("TT", [Some("ترينيداد و توباغو"), Some("Trinidad und Tobago"), Some("Trinidad y Tobago"), Some("Trinité-et-Tobago"), Some("त्रिनिदाद और टोबैगो"), Some("Trinidad e Tobago"), Some("トリニダード・トバゴ"), Some("트리니다드 토바고"), Some("Trinidad en Tobago"), Some("Trynidad i Tobago"), Some("Trindade e Tobago"), Some("Trinidade e Tobago"), Some("Тринидад и Тобаго"), Some("Trinidad och Tobago"), Some("Trinidad ve Tobago"), Some("Тринідад і Тобаго"), Some("特里尼达和多巴哥"), Some("千里達及托巴哥")]),
+    ("TV", [Some("توفالو"), Some("Tuvalu"), Some("Tuvalu"), Some("Tuvalu"), Some("तुवालू"), Some("Tuvalu"), Some("ツバル"), Some("투발루"), Some("Tuvalu"), Some("Tuvalu"), Some("Tuvalu"), Some("Tuvalu"), Some("Тувалу"), Some("Tuvalu"), Some("Tuvalu"), Some("тувалу"), Some("图瓦卢"), Some("吐瓦魯")]),
+    ("TW", [Some("تايوان"), Some("Taiwan"), Some("Taiwán"), Some("Taïwan"), Some("ताइवान"), Some("Taiwan"), Some("台湾"), Some("타이완"), Some("Taiwan"), Some("Tajwan"), Some("Taiwan"), Some("Taiwan"), Some("Тайвань"), Some("Taiwan"), Some("Tayvan"), Some("Тайвань"), Some("台湾"), Some("臺灣")]),
+    ("TZ", [Some("تنزانيا"), Some("Tansania"), Some("Tanzania"), Some("Tanzanie"), Some("तंज़ानिया"), Some("Tanzania"), Some("タンザニア"), Some("탄자니아"), Some("Tanzania"), Some("Tanzania"), Some("Tanzânia"), Some("Tanzânia"), Some("Танзания"), Some("Tanzania"), Some("Tanzanya"), Some("Танзанія"), Some("坦桑尼亚"), Some("坦尚尼亞")]),
+    ("UA", [Some("أوكرانيا"), Some("Ukraine"), Some("Ucrania"), Some("Ukraine"), Some("युक्रेन"), Some("Ucraina"), Some("ウクライナ"), Some("우크라이나"), Some("Oekraïne"), Some("Ukraina"), Some("Ucrânia"), Some("Ucrânia"), Some("Украина"), Some("Ukraina"), Some("Ukrayna"), Some("Україна"), Some("乌克兰"), Some("烏克蘭")]),
+    ("UG", [Some("أوغندا"), Some("Uganda"), Some("Uganda"), Some("Ouganda"), Some("युगाण्डा"), Some("Uganda"), Some("ウガンダ"), Some("우간다"), Some("Oeganda"), Some("Uganda"), Some("Uganda"), Some("Uganda"), Some("Уганда"), Some("Uganda"), Some("Uganda"), Some("Уганда"), Some("乌干达"), Some("烏干達")]),
+    ("UM", [Some("جزر الولايات المتّحدة الصّغرى النّائية"), Some("United States Minor Outlying Islands"), Some("Islas Ultramarinas Menores de Estados Unidos"), Some("Îles mineures éloignées des États-Unis"), Some("संयुक्त राज्य अमेरिका के छोटे दूरस्थ द्वीपसमूह"), Some("Isole minori esterne degli Stati Uniti d'America"), Some("アメリカ合衆国外諸島"), Some("미국령 군소 제도"), Some("Kleine afgelegen eilanden van de Verenigde Staten"), Some("Dalekie Wyspy Mniejsze Stanów Zjednoczonych"), Some("Ilhas Menores Distantes dos Estados Unidos"), Some("Ilhas Menores Distantes dos Estados Unidos"), Some("Соединенные штаты Малых Удаленных островов"), Some("Förenta staternas mindre öar i Oceanien och Västindien"), Some("Amerika Birleşik Devletleri Küçük Dış Adaları"), Some("Зовнішні малі острови США"), Some("美国本土外小岛屿"), Some("美屬邊疆群島")]),
+    ("US", [Some("الولايات المتّحدة"), Some("Vereinigte Staaten"), Some("Estados Unidos"), Some("États-Unis"), Some("संयुक्त राज्य"), Some("Stati Uniti"), Some("米国"), Some("미국"), Some("Verenigde Staten"), Some("Stany Zjednoczone"), Some("Estados Unidos"), Some("Estados Unidos"), Some("Соединённые штаты"), Some("USA"), Some("Amerika Birleşik Devletleri"), Some("США"), Some("美国"), Some("美國")]),
+    ("UY", [Some("الأوروغواي"), Some("Uruguay"), Some("Uruguay"), Some("Uruguay"), Some("उरुग्वे"), Some("Uruguay"), Some("ウルグアイ"), Some("우루과이"), Some("Uruguay"), Some("Urugwaj"), Some("Uruguai"), Some("Uruguai"), Some("Уругвай"), Some("Uruguay"), Some("Uruguay"), Some("Уругвай"), Some("乌拉圭"), Some("烏拉圭")]),
+    ("UZ", [Some("أوزبكستان"), Some("Usbekistan"), Some("Uzbekistán"), Some("Ouzbékistan"), Some("उज़्बेकिस्तान"), Some("Uzbekistan"), Some("ウズベキスタン"), Some("우즈베키스탄"), Some("Oezbekistan"), Some("Uzbekistan"), Some("Uzbequistão"), Some("Uzbequistão"), Some("Узбекистан"), Some("Uzbekistan"), Some("Özbekistan"), Some("Узбекистан"), Some("乌兹别克斯坦"), Some("烏茲別克")]),
+    ("VA", [Some("المقعد المقدّس (ولاية مدينة الفاتيكان)"), Some("Heiliger Stuhl (Staat Vatikanstadt)"), Some("Santa Sede (Ciudad Estado del Vaticano)"), Some("Saint-Siège (état de la cité du Vatican)"), Some("होली सी (वैटिकन सिटी स्टेट)"), Some("Santa Sede (Stato della Città del Vaticano)"), Some("聖庁 (バチカン市国)"), Some("바티칸 시티 (Holy See)"), Some("Vaticaanstad, Staat"), Some("Państwo Watykańskie (Stolica Apostolska)"), Some("Santa Sé (Estado da Cidade do Vaticano)"), Some("Santa Sé (Cidade-Estado do Vaticano)"), Some("Государство-город Ватикан"), Some("Vatikanstaten"), Some("Holy See (Vatikan Şehir Devleti)"), Some("Святий Престол (Ватикан, Місто-Держава)"), Some("梵地冈"), Some("教廷 (梵蒂岡城市國)")]),
+    ("VC", [Some("سانت فنسنت و جزر الغرينادين"), Some("St. Vincent und die Grenadinen"), Some("San Vicente y las Granadinas"), Some("Saint-Vincent-et-les-Grenadines"), Some("सन्त विन्सेण्ट और ग्रेनाडाइन्स"), Some("Saint Vincent e Grenadine"), Some("セントビンセント及びグレナディーン諸島"), Some("세인트빈센트 그레나딘"), Some("Saint Vincent en de Grenadines"), Some("Saint Vincent i Grenadyny"), Some("São Vicente e Granadinas"), Some("São Vicente e Granadinas"), Some("Сент-Винсент и Гренадины"), Some("Sankt Vincent och Grenadinerna"), Some("Saint Vincent ve Grenadinler"), Some("Сент-Вінсент і Гренадини"), Some("圣文森特和格林纳丁斯"), Some("聖文森及格瑞納丁")]),
+    ("VE", [Some("فنزويلّا"), Some("Venezuela"), Some("Venezuela"), Some("Vénézuela"), Some("वेनेज़ुएला"), Some("Venezuela"), Some("ベネズエラ"), Some("베네수엘라"), Some("Venezuela"), Some("Wenezuela"), Some("Venezuela"), Some("Venezuela"), Some("Венесуэла"), Some("Venezuela"), Some("Venezuela"), Some("Венесуела"), Some("委内瑞拉"), Some("委內瑞拉")]),
+    ("VG", [Some("فيرجن، جزر فيرجن البريطانيّة"), Some("Britische Jungferninseln"), Some("Islas Vírgenes, Británicas"), Some("Îles Vierges britanniques"), Some("वर्जिन आइलैंड्स, ब्रिटिश"), Some("Isole Vergini, Regno Unito"), Some("英領ヴァージン諸島"), Some("버진 제도, 영국령"), Some("Maagdeneilanden, Britse"), Some("Brytyjskie Wyspy Dziewicze"), Some("Ilhas Virgens, Britânicas"), Some("Ilhas Virgens Britânicas"), Some("Виргинские острова (Британия)"), Some("Jungfruöarna, brittiska"), Some("İngiliz Virgin Adaları"), Some("Віргінські острови (Британія)"), Some("英属维尔京群岛"), Some("英屬維京群島")]),
+    ("VI", [Some("فيرجن، جزر فيرجن الأميركيّة"), Some("Amerikanische Jungferninseln"), Some("Islas Vírgenes, de EEUU"), Some("Îles Vierges, États-Unis"), Some("वर्जिन आइलैंड्स, यू.एस."), Some("Isole Vergini, U.S.A."), Some("米領ヴァージン諸島"), Some("버진 제도, 미국령"), Some("Maagdeneilanden, Amerikaanse"), Some("Wyspy Dziewicze Stanów Zjednoczonych"), Some("Ilhas Virgens, Estados Unidos"), Some("Ilhas Virgens dos Estados Unidos"), Some("Виргинские острова (США)"), Some("Jungfruöarna, amerikanska"), Some("Virgin Adaları, A.B.D."), Some("Віргінські острови (США)"), Some("美属维尔京群岛"), Some("美屬維京群島")]),
+    ("VN", [Some("الفيتنام"), Some("Vietnam"), Some("Vietnam"), Some("Viêt Nam"), Some("वियतनाम"), Some("Vietnam"), Some("ベトナム"), Some("베트남"), Some("Vietnam"), Some("Wietnam"), Some("Vietname"), Some("Vietnã"), Some("Вьетнам"), Some("Vietnam"), Some("Vietnam"), Some("В'єтнам"), Some("越南"), Some("越南")]),
+    ("VU", [Some("فانواتو"), Some("Vanuatu"), Some("Vanuatu"), Some("Vanuatu"), Some("वानूआटू"), Some("Vanuatu"), Some("バヌアツ"), Some("바누아투"), Some("Vanuatu"), Some("Vanuatu"), Some("Vanuatu"), Some("Vanuatu"), Some("Вануату"), Some("Vanuatu"), Some("Vanuatu"), Some("Вануату"), Some("瓦努阿图"), Some("萬那杜")]),
+    ("WF", [Some("واليس و فوتونا"), Some("Wallis und Futuna"), Some("Wallis y Futuna"), Some("Wallis et Futuna"), Some("वालिस और फ्यूटुना"), Some("Wallis e Futuna"), Some("ワリー及びフテュナ"), Some("왈리스 퓌튀나"), Some("Wallis en Futuna"), Some("Wallis i Futuna"), Some("Wallis e Futuna"), Some("Wallis e Futuna"), Some("Уоллес и Футана"), Some("Wallis och Futuna"), Some("Wallis ve Futuna Adaları"), Some("Волліс і Футуна"), Some("瓦利斯和富图纳"), Some("沃里斯及伏塔那群島")]),
+    ("WS", [Some("صاموا"), Some("Samoa"), Some("Samoa"), Some("Samoa"), Some("समोआ"), Some("Samoa"), Some("サモア"), Some("사모아"), Some("Samoa"), Some("Samoa"), Some("Samoa"), Some("Samoa"), Some("Самоа"), Some("Samoa"), Some("Samoa"), Some("Самоа"), Some("萨摩亚"), Some("薩摩亞")]),
+    ("YE", [Some("اليمن"), Some("Jemen"), Some("Yemen"), Some("Yémen"), Some("यमन"), Some("Yemen"), Some("イエメン"), Some("예멘"), Some("Jemen"), Some("Jemen"), Some("Iémen"), Some("Iêmen"), Some("Йемен"), Some("Yemen"), Some("Yemen"), Some("Ємен"), Some("也门"), Some("葉門")]),
+    ("YT", [Some("مايوت"), Some("Mayotte"), Some("Mayotte"), Some("Mayotte"), Some("मेयोट"), Some("Mayotte"), Some("マヨット"), Some("마요트"), Some("Mayotte"), Some("Majotta"), Some("Mayotte"), Some("Maiote"), Some("Майот"), Some("Mayotte"), Some("Mayotte"), Some("Майотта"), Some("马约特"), Some("馬約特")]),
+    ("ZA", [Some("جنوب إفريقيا"), Some("Südafrika"), Some("Sudáfrica"), Some("Afrique du Sud"), Some("दक्षिण अफ़्रीका"), Some("Sudafrica"), Some("南アフリカ"), Some("남아프리카 공화국"), Some("Zuid-Afrika"), Some("Południowa Afryka"), Some("África do Sul"), Some("África do Sul"), Some("Южная Африка"), Some("Sydafrika"), Some("Güney Afrika"), Some("Південна Африка"), Some("南非"), Some("南非")]),
+    ("ZM", [Some("زامبيا"), Some("Sambia"), Some("Zambia"), Some("Zambie"), Some("ज़ाम्बिया"), Some("Zambia"), Some("ザンビア"), Some("잠비아"), Some("Zambia"), Some("Zambia"), Some("Zâmbia"), Some("Zâmbia"), Some("Замбия"), Some("Zambia"), Some("Zambiya"), Some("Замбія"), Some("赞比亚"), Some("尚比亞")]),
+    ("ZW", [Some("زمبابوي"), Some("Simbabwe"), Some("Zimbabue"), Some("Zimbabwe"), Some("ज़िम्बाब्वे"), Some("Zimbabwe"), Some("ジンバブエ"), Some("짐바브웨"), Some("Zimbabwe"), Some("Zimbabwe"), Some("Zimbábue"), Some("Zimbábue"), Some("Зимбабве"), Some("Zimbabwe"), Some("Zimbabve"), Some("Зімбабве"), Some("津巴布韦"), Some("辛巴威")]),
+];
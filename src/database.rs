@@ -0,0 +1,460 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use alloc::{boxed::Box, vec::Vec};
+
+#[cfg(feature = "std")]
+use crate::snapshot::DatabaseMetadata;
+use crate::{
+    cidr::{merge_ranges, AddrRange, Address},
+    country_list::Country,
+    privacy::Truncation,
+    Error, IpAddrMap,
+};
+
+/// Holds a clean IPv4 and IPv6 `IpAddrMap`, allowing lookups of either kind of address.
+///
+/// Example usage:
+///
+/// ```rust
+/// use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+/// use ip_geo::{country_list::get_countries, GeoDatabase, IpAddrEntry, IpAddrMap};
+///
+/// let countries = get_countries();
+/// let belgium = countries.get("BE").unwrap().clone();
+/// let canada = countries.get("CA").unwrap().clone();
+///
+/// let mut ipv4_map = IpAddrMap::new();
+/// ipv4_map.insert(
+///     IpAddrEntry::new(Ipv4Addr::new(1, 1, 1, 1), Ipv4Addr::new(3, 3, 3, 3), belgium).unwrap(),
+/// );
+///
+/// let mut ipv6_map = IpAddrMap::new();
+/// ipv6_map.insert(
+///     IpAddrEntry::new(Ipv6Addr::new(1, 0, 0, 0, 0, 0, 0, 0), Ipv6Addr::new(3, 0, 0, 0, 0, 0, 0, 0), canada)
+///         .unwrap(),
+/// );
+///
+/// let database = GeoDatabase::new(ipv4_map, ipv6_map);
+///
+/// assert_eq!(database.lookup(IpAddr::V4(Ipv4Addr::new(2, 2, 2, 2))).unwrap().code, "BE".into());
+/// assert_eq!(database.lookup("2::".parse().unwrap()).unwrap().code, "CA".into());
+/// assert!(database.lookup("9.9.9.9".parse().unwrap()).is_err());
+/// ```
+#[derive(Debug)]
+pub struct GeoDatabase {
+    ipv4: IpAddrMap<Ipv4Addr, Country>,
+    ipv6: IpAddrMap<Ipv6Addr, Country>,
+    truncation: Option<Truncation>,
+    fallback: Fallback,
+    #[cfg(feature = "std")]
+    metadata: Option<DatabaseMetadata>,
+}
+
+/// What a `GeoDatabase` returns when no country is associated with an address.
+#[derive(Debug, Default)]
+pub enum Fallback {
+    /// Return `Error::NoValueFound`.
+    #[default]
+    NoValue,
+    /// Return a placeholder country, `Country::unknown()`.
+    Unknown(Country),
+    /// Look the address up in a secondary database, which applies its own fallback.
+    Database(Box<GeoDatabase>),
+}
+
+impl Fallback {
+    /// Create a `Fallback::Unknown` holding `Country::unknown()`.
+    pub fn unknown() -> Self {
+        Self::Unknown(Country::unknown())
+    }
+}
+
+impl GeoDatabase {
+    /// Create a new `GeoDatabase` from IPv4 and IPv6 `IpAddrMap`s, cleaning them if necessary.
+    pub fn new(
+        mut ipv4_map: IpAddrMap<Ipv4Addr, Country>,
+        mut ipv6_map: IpAddrMap<Ipv6Addr, Country>,
+    ) -> Self {
+        ipv4_map.cleanup();
+        ipv6_map.cleanup();
+
+        Self {
+            ipv4: ipv4_map,
+            ipv6: ipv6_map,
+            truncation: None,
+            fallback: Fallback::NoValue,
+            #[cfg(feature = "std")]
+            metadata: None,
+        }
+    }
+
+    /// Truncate every address to a network prefix before looking it up, so that lookups never
+    /// depend on (or record) the full address.
+    ///
+    /// Ranges that split a truncated network resolve to the country of the network's first
+    /// address.
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// use std::net::Ipv4Addr;
+    /// use ip_geo::{
+    ///     country_list::get_countries, privacy::Truncation, GeoDatabase, IpAddrEntry, IpAddrMap,
+    /// };
+    ///
+    /// let belgium = get_countries().get("BE").unwrap().clone();
+    /// let mut ipv4_map = IpAddrMap::new();
+    /// ipv4_map.insert(
+    ///     IpAddrEntry::new(Ipv4Addr::new(1, 0, 0, 0), Ipv4Addr::new(1, 0, 0, 127), belgium).unwrap(),
+    /// );
+    /// let database = GeoDatabase::new(ipv4_map, IpAddrMap::new()).with_truncation(Truncation::default());
+    ///
+    /// assert_eq!(database.lookup("1.0.0.200".parse().unwrap()).unwrap().code, "BE".into());
+    /// ```
+    pub fn with_truncation(mut self, truncation: Truncation) -> Self {
+        self.truncation = Some(truncation);
+        self
+    }
+
+    /// Set what lookups return when no country is associated with an address, instead of
+    /// `Error::NoValueFound`.
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// use std::net::Ipv4Addr;
+    /// use ip_geo::{
+    ///     country_list::get_countries, database::Fallback, GeoDatabase, IpAddrEntry, IpAddrMap,
+    /// };
+    ///
+    /// let belgium = get_countries().get("BE").unwrap().clone();
+    /// let mut ipv4_map = IpAddrMap::new();
+    /// ipv4_map.insert(
+    ///     IpAddrEntry::new(Ipv4Addr::new(1, 0, 0, 0), Ipv4Addr::new(1, 0, 0, 255), belgium).unwrap(),
+    /// );
+    /// let secondary = GeoDatabase::new(ipv4_map, IpAddrMap::new()).with_fallback(Fallback::unknown());
+    ///
+    /// let database = GeoDatabase::new(IpAddrMap::new(), IpAddrMap::new())
+    ///     .with_fallback(Fallback::Database(Box::new(secondary)));
+    ///
+    /// assert_eq!(database.lookup("1.0.0.1".parse().unwrap()).unwrap().code, "BE".into());
+    /// assert_eq!(database.lookup("9.9.9.9".parse().unwrap()).unwrap().code, "??".into());
+    /// ```
+    pub fn with_fallback(mut self, fallback: Fallback) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    /// Name every country in `language` (see `Country::localized`), including those of the
+    /// fallback, falling back to English for countries that have not been translated into it.
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// use std::net::Ipv4Addr;
+    /// use ip_geo::{
+    ///     country_list::get_countries, database::Fallback, GeoDatabase, IpAddrEntry, IpAddrMap,
+    /// };
+    ///
+    /// let belgium = get_countries().get("BE").unwrap().clone();
+    /// let mut ipv4_map = IpAddrMap::new();
+    /// ipv4_map.insert(
+    ///     IpAddrEntry::new(Ipv4Addr::new(1, 0, 0, 0), Ipv4Addr::new(1, 0, 0, 255), belgium).unwrap(),
+    /// );
+    /// let database = GeoDatabase::new(ipv4_map, IpAddrMap::new())
+    ///     .with_fallback(Fallback::unknown())
+    ///     .with_language("es");
+    ///
+    /// assert_eq!(database.lookup("1.0.0.1".parse().unwrap()).unwrap().name.as_ref(), "Bélgica");
+    /// assert_eq!(database.lookup("9.9.9.9".parse().unwrap()).unwrap().name.as_ref(), "Unknown");
+    /// ```
+    pub fn with_language(mut self, language: &str) -> Self {
+        self.ipv4.localize(language);
+        self.ipv6.localize(language);
+        self.fallback = match self.fallback {
+            Fallback::Unknown(country) => Fallback::Unknown(country.localized(language)),
+            Fallback::Database(database) => {
+                Fallback::Database(Box::new(database.with_language(language)))
+            }
+            Fallback::NoValue => Fallback::NoValue,
+        };
+
+        self
+    }
+
+    /// Return what lookups return when no country is associated with an address.
+    pub const fn fallback(&self) -> &Fallback {
+        &self.fallback
+    }
+
+    /// Return the prefix lengths that addresses are truncated to before lookup, if any.
+    pub const fn truncation(&self) -> Option<&Truncation> {
+        self.truncation.as_ref()
+    }
+
+    /// Attach metadata describing where the database came from.
+    #[cfg(feature = "std")]
+    pub fn with_metadata(mut self, metadata: DatabaseMetadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Return the metadata describing where the database came from, if it has any.
+    ///
+    /// Databases read from a snapshot always have metadata.
+    #[cfg(feature = "std")]
+    pub const fn metadata(&self) -> Option<&DatabaseMetadata> {
+        self.metadata.as_ref()
+    }
+
+    /// For a given IPv4 or IPv6 address, find the country associated with it.
+    pub fn lookup(&self, address: IpAddr) -> Result<&Country, Error> {
+        match address {
+            IpAddr::V4(address) => self.lookup_ipv4(address),
+            IpAddr::V6(address) => self.lookup_ipv6(address),
+        }
+    }
+
+    /// For a given IPv4 address, find the country associated with it.
+    pub fn lookup_ipv4(&self, address: Ipv4Addr) -> Result<&Country, Error> {
+        let address = match self.truncation {
+            Some(truncation) => truncation.apply_ipv4(address),
+            None => address,
+        };
+
+        match self.ipv4.try_search(address) {
+            Err(Error::NoValueFound) => self.fall_back(address.into()),
+            result => result,
+        }
+    }
+
+    /// For a given IPv6 address, find the country associated with it.
+    pub fn lookup_ipv6(&self, address: Ipv6Addr) -> Result<&Country, Error> {
+        let address = match self.truncation {
+            Some(truncation) => truncation.apply_ipv6(address),
+            None => address,
+        };
+
+        match self.ipv6.try_search(address) {
+            Err(Error::NoValueFound) => self.fall_back(address.into()),
+            result => result,
+        }
+    }
+
+    /// Apply the database's `Fallback` for an address with no associated country.
+    fn fall_back(&self, address: IpAddr) -> Result<&Country, Error> {
+        match &self.fallback {
+            Fallback::NoValue => Err(Error::NoValueFound),
+            Fallback::Unknown(country) => Ok(country),
+            Fallback::Database(database) => database.lookup(address),
+        }
+    }
+
+    /// For a given IPv4 or IPv6 address, find the country associated with it, recording how it
+    /// was found.
+    ///
+    /// Intended for debugging unexpected lookup results, prefer `Self::lookup()` otherwise.
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// use std::net::Ipv4Addr;
+    /// use ip_geo::{
+    ///     country_list::get_countries,
+    ///     database::LookupSource,
+    ///     GeoDatabase, IpAddrEntry, IpAddrMap,
+    /// };
+    ///
+    /// let countries = get_countries();
+    /// let entry = |start, end, code| {
+    ///     IpAddrEntry::new(start, end, countries.get(code).unwrap().clone()).unwrap()
+    /// };
+    ///
+    /// let mut ipv4_map = IpAddrMap::new();
+    /// ipv4_map.insert(entry(Ipv4Addr::new(1, 0, 0, 0), Ipv4Addr::new(1, 0, 0, 255), "BE"));
+    /// ipv4_map.insert(entry(Ipv4Addr::new(1, 0, 1, 0), Ipv4Addr::new(1, 0, 1, 255), "NL"));
+    /// ipv4_map.insert(entry(Ipv4Addr::new(1, 0, 2, 0), Ipv4Addr::new(1, 0, 2, 255), "CA"));
+    /// let database = GeoDatabase::new(ipv4_map, IpAddrMap::new());
+    ///
+    /// let trace = database.lookup_traced("1.0.2.3".parse().unwrap());
+    /// let matched = trace.matched.unwrap();
+    ///
+    /// assert_eq!(trace.source, LookupSource::Ipv4);
+    /// assert_eq!(trace.steps, 2);
+    /// assert_eq!(matched.index, 2);
+    /// assert_eq!(matched.start, Ipv4Addr::new(1, 0, 2, 0));
+    /// assert_eq!(matched.country.code, "CA".into());
+    ///
+    /// let trace = database.lookup_traced("9.9.9.9".parse().unwrap());
+    /// assert!(trace.matched.is_none());
+    /// assert_eq!(trace.insertion_index, Some(3));
+    /// ```
+    pub fn lookup_traced(&self, address: IpAddr) -> LookupTrace<'_> {
+        /// Search a map, converting the matched entry to use `IpAddr`s.
+        fn trace<A: Ord + Copy + Into<IpAddr>>(
+            map: &IpAddrMap<A, Country>,
+            address: A,
+        ) -> (Option<TracedEntry<'_>>, Option<usize>, u32) {
+            let (result, steps) = map.search_index_counted(address);
+
+            match result {
+                Ok(index) => {
+                    // Safety: `search_index_counted()` only returns indexes of existing entries
+                    let entry = map.get_from_index_as_ref(index).unwrap();
+                    let traced = TracedEntry {
+                        index,
+                        start: (*entry.start()).into(),
+                        end: (*entry.end()).into(),
+                        country: entry.value(),
+                    };
+
+                    (Some(traced), None, steps)
+                }
+                Err(index) => (None, Some(index), steps),
+            }
+        }
+
+        let address = match self.truncation {
+            Some(truncation) => truncation.apply(address),
+            None => address,
+        };
+
+        let (source, entries, (matched, insertion_index, steps)) = match address {
+            IpAddr::V4(v4) => (LookupSource::Ipv4, self.ipv4.len(), trace(&self.ipv4, v4)),
+            IpAddr::V6(v6) => (LookupSource::Ipv6, self.ipv6.len(), trace(&self.ipv6, v6)),
+        };
+
+        let fallback = match matched {
+            Some(_) => None,
+            None => self.fall_back(address).ok(),
+        };
+
+        LookupTrace {
+            address,
+            source,
+            entries,
+            steps,
+            matched,
+            insertion_index,
+            fallback,
+        }
+    }
+
+    /// Return a reference to the IPv4 `IpAddrMap`.
+    pub const fn ipv4(&self) -> &IpAddrMap<Ipv4Addr, Country> {
+        &self.ipv4
+    }
+
+    /// Return a reference to the IPv6 `IpAddrMap`.
+    pub const fn ipv6(&self) -> &IpAddrMap<Ipv6Addr, Country> {
+        &self.ipv6
+    }
+
+    /// Return every range associated with any of the given country codes, merging contiguous
+    /// ranges.
+    ///
+    /// Country codes are matched case-insensitively.
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// use std::net::Ipv4Addr;
+    /// use ip_geo::{country_list::get_countries, GeoDatabase, IpAddrEntry, IpAddrMap};
+    ///
+    /// let countries = get_countries();
+    /// let entry = |start, end, code| {
+    ///     IpAddrEntry::new(start, end, countries.get(code).unwrap().clone()).unwrap()
+    /// };
+    ///
+    /// let mut ipv4_map = IpAddrMap::new();
+    /// ipv4_map.insert(entry(Ipv4Addr::new(1, 0, 0, 0), Ipv4Addr::new(1, 0, 0, 255), "BE"));
+    /// ipv4_map.insert(entry(Ipv4Addr::new(1, 0, 1, 0), Ipv4Addr::new(1, 0, 1, 255), "NL"));
+    /// ipv4_map.insert(entry(Ipv4Addr::new(1, 0, 2, 0), Ipv4Addr::new(1, 0, 2, 255), "CA"));
+    /// let database = GeoDatabase::new(ipv4_map, IpAddrMap::new());
+    ///
+    /// let (ipv4_ranges, ipv6_ranges) = database.ranges_for_countries(&["be", "NL"]);
+    ///
+    /// assert_eq!(ipv4_ranges, [(Ipv4Addr::new(1, 0, 0, 0), Ipv4Addr::new(1, 0, 1, 255))]);
+    /// assert!(ipv6_ranges.is_empty());
+    /// ```
+    pub fn ranges_for_countries(
+        &self,
+        codes: &[&str],
+    ) -> (Vec<AddrRange<Ipv4Addr>>, Vec<AddrRange<Ipv6Addr>>) {
+        fn filter<A: Address>(map: &IpAddrMap<A, Country>, codes: &[&str]) -> Vec<AddrRange<A>> {
+            let ranges = map
+                .iter()
+                .filter(|e| {
+                    codes
+                        .iter()
+                        .any(|c| c.eq_ignore_ascii_case(&e.value().code))
+                })
+                .map(|e| (*e.start(), *e.end()));
+
+            merge_ranges(ranges)
+        }
+
+        (filter(&self.ipv4, codes), filter(&self.ipv6, codes))
+    }
+}
+
+/// Which of a `GeoDatabase`'s maps a lookup searched.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LookupSource {
+    /// The IPv4 `IpAddrMap`.
+    Ipv4,
+    /// The IPv6 `IpAddrMap`.
+    Ipv6,
+}
+
+/// A record of how `GeoDatabase::lookup_traced()` found (or failed to find) a country.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LookupTrace<'a> {
+    /// The address that was looked up, after truncation.
+    pub address: IpAddr,
+    /// The map that was searched.
+    pub source: LookupSource,
+    /// The number of entries in the searched map.
+    pub entries: usize,
+    /// The number of entries compared during the binary search.
+    pub steps: u32,
+    /// The entry that contains the address, if any.
+    pub matched: Option<TracedEntry<'a>>,
+    /// If no entry contains the address, the index that an entry for it would be inserted at.
+    ///
+    /// The entries on either side of this index are the nearest ranges to the address.
+    pub insertion_index: Option<usize>,
+    /// If no entry contains the address, the country returned by the database's `Fallback`, if
+    /// any.
+    pub fallback: Option<&'a Country>,
+}
+
+/// An entry matched by `GeoDatabase::lookup_traced()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TracedEntry<'a> {
+    /// The index of the entry in the searched map.
+    pub index: usize,
+    /// The first address of the entry's range.
+    pub start: IpAddr,
+    /// The last address of the entry's range.
+    pub end: IpAddr,
+    /// The country associated with the entry.
+    pub country: &'a Country,
+}
@@ -0,0 +1,153 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Comparing two versions of a database, ex. to review an update before deploying it.
+
+use alloc::{sync::Arc, vec::Vec};
+
+use crate::{
+    cidr::{AddrRange, Address},
+    country_list::Country,
+    IpAddrMap,
+};
+
+/// How the country of a range of addresses changed between two versions of a database.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+    /// The range is associated with a country (by its code) only in the new database.
+    Added(Arc<str>),
+    /// The range is associated with a country (by its code) only in the old database.
+    Removed(Arc<str>),
+    /// The range is associated with different countries (by their codes) in each database.
+    Reassigned { from: Arc<str>, to: Arc<str> },
+}
+
+/// A range of addresses whose country changed between two versions of a database.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangeChange<A: Address> {
+    /// The first and last addresses of the range.
+    pub range: AddrRange<A>,
+    pub change: Change,
+}
+
+/// Return every range of addresses whose country differs between `old` and `new`, in order, with
+/// adjacent ranges that changed in the same way merged together.
+///
+/// Example usage:
+///
+/// ```rust
+/// use std::net::Ipv4Addr;
+/// use ip_geo::{
+///     country_list::get_countries,
+///     diff::{diff, Change, RangeChange},
+///     IpAddrEntry, IpAddrMap,
+/// };
+///
+/// let countries = get_countries();
+/// let entry = |start: [u8; 4], end: [u8; 4], code: &str| {
+///     let country = countries.get(code).unwrap().clone();
+///     IpAddrEntry::new(Ipv4Addr::from(start), Ipv4Addr::from(end), country).unwrap()
+/// };
+///
+/// let mut old = IpAddrMap::new();
+/// old.insert(entry([1, 0, 0, 0], [1, 0, 0, 255], "BE"));
+/// old.insert(entry([2, 0, 0, 0], [2, 0, 0, 255], "FR"));
+/// old.cleanup();
+///
+/// let mut new = IpAddrMap::new();
+/// new.insert(entry([1, 0, 0, 0], [1, 0, 0, 127], "BE"));
+/// new.insert(entry([1, 0, 0, 128], [1, 0, 0, 255], "DE"));
+/// new.insert(entry([3, 0, 0, 0], [3, 0, 0, 255], "CA"));
+/// new.cleanup();
+///
+/// assert_eq!(
+///     diff(&old, &new),
+///     [
+///         RangeChange {
+///             range: (Ipv4Addr::new(1, 0, 0, 128), Ipv4Addr::new(1, 0, 0, 255)),
+///             change: Change::Reassigned { from: "BE".into(), to: "DE".into() },
+///         },
+///         RangeChange {
+///             range: (Ipv4Addr::new(2, 0, 0, 0), Ipv4Addr::new(2, 0, 0, 255)),
+///             change: Change::Removed("FR".into()),
+///         },
+///         RangeChange {
+///             range: (Ipv4Addr::new(3, 0, 0, 0), Ipv4Addr::new(3, 0, 0, 255)),
+///             change: Change::Added("CA".into()),
+///         },
+///     ]
+/// );
+/// ```
+pub fn diff<A: Address>(
+    old: &IpAddrMap<A, Country>,
+    new: &IpAddrMap<A, Country>,
+) -> Vec<RangeChange<A>> {
+    // Every address where either database might start or stop associating a country, such that
+    // both databases are constant between consecutive boundaries
+    let mut boundaries: Vec<u128> = Vec::with_capacity((old.len() + new.len()) * 2);
+    let last = A::from_u128(u128::MAX).to_u128();
+    let mut reaches_end = false;
+    for entry in old.iter().chain(new.iter()) {
+        boundaries.push(entry.start().to_u128());
+        let end = entry.end().to_u128();
+        if end == last {
+            reaches_end = true;
+        } else {
+            boundaries.push(end + 1);
+        }
+    }
+    boundaries.sort_unstable();
+    boundaries.dedup();
+
+    let code = |map: &IpAddrMap<A, Country>, address: A| {
+        map.try_search(address)
+            .ok()
+            .map(|country| country.code.clone())
+    };
+
+    let mut changes: Vec<RangeChange<A>> = Vec::new();
+    for (index, &start) in boundaries.iter().enumerate() {
+        let end = match boundaries.get(index + 1) {
+            Some(next) => next - 1,
+            None if reaches_end => last,
+            None => break,
+        };
+        let (start, end) = (A::from_u128(start), A::from_u128(end));
+
+        let change = match (code(old, start), code(new, start)) {
+            (None, Some(to)) => Change::Added(to),
+            (Some(from), None) => Change::Removed(from),
+            (Some(from), Some(to)) if from != to => Change::Reassigned { from, to },
+            _ => continue,
+        };
+
+        match changes.last_mut() {
+            Some(last)
+                if last.change == change
+                    && last.range.1.to_u128().checked_add(1) == Some(start.to_u128()) =>
+            {
+                last.range.1 = end;
+            }
+            _ => changes.push(RangeChange {
+                range: (start, end),
+                change,
+            }),
+        }
+    }
+
+    changes
+}
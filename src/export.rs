@@ -0,0 +1,484 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+    fmt::Display,
+    io::{self, Write},
+};
+
+use crate::{
+    cidr::{range_to_cidrs, AddrRange, Address},
+    country_list::Country,
+    GeoDatabase, IpAddrMap,
+};
+
+/// Represents the nftables address families that a set can be created for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NftablesFamily {
+    /// Both IPv4 and IPv6, in separate sets.
+    Inet,
+    /// Only IPv4.
+    Ip,
+    /// Only IPv6.
+    Ip6,
+}
+
+impl NftablesFamily {
+    /// Return the name of the family as used by `nft`.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Inet => "inet",
+            Self::Ip => "ip",
+            Self::Ip6 => "ip6",
+        }
+    }
+
+    const fn has_ipv4(&self) -> bool {
+        matches!(self, Self::Inet | Self::Ip)
+    }
+
+    const fn has_ipv6(&self) -> bool {
+        matches!(self, Self::Inet | Self::Ip6)
+    }
+}
+
+/// Represents what an nftables ruleset does with traffic from the selected countries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NftablesPolicy {
+    /// Drop incoming traffic from the selected countries.
+    Block,
+    /// Drop incoming traffic from everywhere but the selected countries, except for loopback
+    /// traffic, established connections, and IPv6 neighbor discovery.
+    Allow,
+}
+
+/// The hook, priority, and policy of the chain of an nftables ruleset.
+const NFTABLES_CHAIN_HOOK: &str = "type filter hook input priority filter; policy accept;";
+
+impl GeoDatabase {
+    /// Write every range in the database as an [HAProxy map
+    /// file](https://docs.haproxy.org/2.9/configuration.html#map), with lines in the format of
+    /// `<cidr> <country_code>`.
+    ///
+    /// IPv4 ranges are written before IPv6 ranges.
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// use std::net::Ipv4Addr;
+    /// use ip_geo::{country_list::get_countries, GeoDatabase, IpAddrEntry, IpAddrMap};
+    ///
+    /// let belgium = get_countries().get("BE").unwrap().clone();
+    /// let mut ipv4_map = IpAddrMap::new();
+    /// ipv4_map.insert(
+    ///     IpAddrEntry::new(Ipv4Addr::new(192, 0, 2, 0), Ipv4Addr::new(192, 0, 3, 127), belgium)
+    ///         .unwrap(),
+    /// );
+    /// let database = GeoDatabase::new(ipv4_map, IpAddrMap::new());
+    ///
+    /// let mut map = Vec::new();
+    /// database.export_haproxy_map(&mut map).unwrap();
+    ///
+    /// assert_eq!(String::from_utf8(map).unwrap(), "192.0.2.0/24 BE\n192.0.3.0/25 BE\n");
+    /// ```
+    pub fn export_haproxy_map(&self, mut writer: impl Write) -> io::Result<()> {
+        fn write_map<A: Address>(
+            writer: &mut impl Write,
+            map: &IpAddrMap<A, Country>,
+        ) -> io::Result<()> {
+            for entry in map.iter() {
+                for cidr in entry.cidrs() {
+                    writeln!(writer, "{cidr} {}", entry.value().code)?;
+                }
+            }
+
+            Ok(())
+        }
+
+        write_map(&mut writer, self.ipv4())?;
+        write_map(&mut writer, self.ipv6())?;
+
+        writer.flush()
+    }
+
+    /// Write an nftables table holding interval sets of every range associated with any of the
+    /// given country codes, loadable with `nft -f`.
+    ///
+    /// The sets are named `<set_name>_v4` (of type `ipv4_addr`) and `<set_name>_v6` (of type
+    /// `ipv6_addr`), depending on `family`.
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// use std::net::Ipv4Addr;
+    /// use ip_geo::{
+    ///     country_list::get_countries, export::NftablesFamily, GeoDatabase, IpAddrEntry, IpAddrMap,
+    /// };
+    ///
+    /// let belgium = get_countries().get("BE").unwrap().clone();
+    /// let mut ipv4_map = IpAddrMap::new();
+    /// ipv4_map.insert(
+    ///     IpAddrEntry::new(Ipv4Addr::new(192, 0, 2, 0), Ipv4Addr::new(192, 0, 3, 127), belgium)
+    ///         .unwrap(),
+    /// );
+    /// let database = GeoDatabase::new(ipv4_map, IpAddrMap::new());
+    ///
+    /// let mut output = Vec::new();
+    /// database
+    ///     .export_nftables_set(&mut output, &["BE"], NftablesFamily::Ip, "filter", "blocked")
+    ///     .unwrap();
+    ///
+    /// assert_eq!(
+    ///     String::from_utf8(output).unwrap(),
+    ///     "\
+    /// table ip filter {
+    /// \tset blocked_v4 {
+    /// \t\ttype ipv4_addr
+    /// \t\tflags interval
+    /// \t\telements = {
+    /// \t\t\t192.0.2.0/24,
+    /// \t\t\t192.0.3.0/25,
+    /// \t\t}
+    /// \t}
+    /// }
+    /// "
+    /// );
+    /// ```
+    pub fn export_nftables_set(
+        &self,
+        mut writer: impl Write,
+        codes: &[&str],
+        family: NftablesFamily,
+        table: &str,
+        set_name: &str,
+    ) -> io::Result<()> {
+        writeln!(writer, "table {} {table} {{", family.as_str())?;
+        self.write_nftables_sets(&mut writer, codes, family, set_name)?;
+        writeln!(writer, "}}")?;
+
+        writer.flush()
+    }
+
+    /// Write an nftables ruleset, loadable with `nft -f`, holding the sets of
+    /// [`Self::export_nftables_set`] and a chain named `<set_name>_input` hooked into incoming
+    /// traffic, which blocks or allows the given countries according to `policy`.
+    ///
+    /// The sets and chain are declared and flushed before they are filled, so loading the ruleset
+    /// again replaces them, without touching anything else in `table`.
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// use std::net::Ipv4Addr;
+    /// use ip_geo::{
+    ///     country_list::get_countries,
+    ///     export::{NftablesFamily, NftablesPolicy},
+    ///     GeoDatabase, IpAddrEntry, IpAddrMap,
+    /// };
+    ///
+    /// let belgium = get_countries().get("BE").unwrap().clone();
+    /// let mut ipv4_map = IpAddrMap::new();
+    /// ipv4_map.insert(
+    ///     IpAddrEntry::new(Ipv4Addr::new(192, 0, 2, 0), Ipv4Addr::new(192, 0, 2, 255), belgium)
+    ///         .unwrap(),
+    /// );
+    /// let database = GeoDatabase::new(ipv4_map, IpAddrMap::new());
+    ///
+    /// let mut output = Vec::new();
+    /// database
+    ///     .export_nftables_ruleset(
+    ///         &mut output,
+    ///         &["BE"],
+    ///         NftablesFamily::Ip,
+    ///         "filter",
+    ///         "blocked",
+    ///         NftablesPolicy::Block,
+    ///     )
+    ///     .unwrap();
+    /// let output = String::from_utf8(output).unwrap();
+    ///
+    /// assert!(output.contains("flush set ip filter blocked_v4\n"));
+    /// assert!(output.contains("\t\t\t192.0.2.0/24,\n"));
+    /// assert!(output.contains("\t\tip saddr @blocked_v4 drop\n"));
+    /// ```
+    pub fn export_nftables_ruleset(
+        &self,
+        mut writer: impl Write,
+        codes: &[&str],
+        family: NftablesFamily,
+        table: &str,
+        set_name: &str,
+        policy: NftablesPolicy,
+    ) -> io::Result<()> {
+        let family_name = family.as_str();
+        let chain = format!("{set_name}_input");
+        let mut sets = Vec::with_capacity(2);
+        if family.has_ipv4() {
+            sets.push((format!("{set_name}_v4"), "ipv4_addr", "ip"));
+        }
+        if family.has_ipv6() {
+            sets.push((format!("{set_name}_v6"), "ipv6_addr", "ip6"));
+        }
+
+        // `flush` fails on sets and chains that do not exist, so declare them first
+        writeln!(writer, "table {family_name} {table} {{")?;
+        for (name, kind, _) in &sets {
+            writeln!(writer, "\tset {name} {{ type {kind}; flags interval; }}")?;
+        }
+        writeln!(writer, "\tchain {chain} {{ {NFTABLES_CHAIN_HOOK} }}")?;
+        writeln!(writer, "}}")?;
+        for (name, _, _) in &sets {
+            writeln!(writer, "flush set {family_name} {table} {name}")?;
+        }
+        writeln!(writer, "flush chain {family_name} {table} {chain}")?;
+        writeln!(writer)?;
+
+        writeln!(writer, "table {family_name} {table} {{")?;
+        self.write_nftables_sets(&mut writer, codes, family, set_name)?;
+
+        writeln!(writer, "\tchain {chain} {{")?;
+        writeln!(writer, "\t\t{NFTABLES_CHAIN_HOOK}")?;
+        if policy == NftablesPolicy::Allow {
+            writeln!(writer, "\t\tct state established,related accept")?;
+            writeln!(writer, "\t\tiif \"lo\" accept")?;
+            // IPv6 stops working without neighbor discovery, which uses link-local addresses
+            if family.has_ipv6() {
+                writeln!(
+                    writer,
+                    "\t\ticmpv6 type {{ nd-router-advert, nd-neighbor-solicit, nd-neighbor-advert }} accept"
+                )?;
+            }
+        }
+        for (name, _, protocol) in &sets {
+            match policy {
+                NftablesPolicy::Block => writeln!(writer, "\t\t{protocol} saddr @{name} drop")?,
+                NftablesPolicy::Allow => writeln!(writer, "\t\t{protocol} saddr != @{name} drop")?,
+            }
+        }
+        writeln!(writer, "\t}}")?;
+        writeln!(writer, "}}")?;
+
+        writer.flush()
+    }
+
+    /// Write the sets of [`Self::export_nftables_set`], without the table around them.
+    fn write_nftables_sets(
+        &self,
+        writer: &mut impl Write,
+        codes: &[&str],
+        family: NftablesFamily,
+        set_name: &str,
+    ) -> io::Result<()> {
+        fn write_set<A: Address>(
+            writer: &mut impl Write,
+            name: &str,
+            kind: &str,
+            ranges: &[AddrRange<A>],
+        ) -> io::Result<()> {
+            writeln!(writer, "\tset {name} {{")?;
+            writeln!(writer, "\t\ttype {kind}")?;
+            writeln!(writer, "\t\tflags interval")?;
+
+            // `nft` rejects empty element lists
+            if !ranges.is_empty() {
+                writeln!(writer, "\t\telements = {{")?;
+                for (start, end) in ranges {
+                    for cidr in range_to_cidrs(*start, *end) {
+                        writeln!(writer, "\t\t\t{cidr},")?;
+                    }
+                }
+                writeln!(writer, "\t\t}}")?;
+            }
+
+            writeln!(writer, "\t}}")
+        }
+
+        let (ipv4_ranges, ipv6_ranges) = self.ranges_for_countries(codes);
+
+        if family.has_ipv4() {
+            let name = format!("{set_name}_v4");
+            write_set(writer, &name, "ipv4_addr", &ipv4_ranges)?;
+        }
+
+        if family.has_ipv6() {
+            let name = format!("{set_name}_v6");
+            write_set(writer, &name, "ipv6_addr", &ipv6_ranges)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write an `ipset restore` script that creates (or empties) and fills `hash:net` sets with
+    /// every range associated with any of the given country codes.
+    ///
+    /// The sets are named `<set_name>_v4` and `<set_name>_v6`.
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// use std::net::Ipv4Addr;
+    /// use ip_geo::{country_list::get_countries, GeoDatabase, IpAddrEntry, IpAddrMap};
+    ///
+    /// let belgium = get_countries().get("BE").unwrap().clone();
+    /// let mut ipv4_map = IpAddrMap::new();
+    /// ipv4_map.insert(
+    ///     IpAddrEntry::new(Ipv4Addr::new(192, 0, 2, 0), Ipv4Addr::new(192, 0, 2, 255), belgium)
+    ///         .unwrap(),
+    /// );
+    /// let database = GeoDatabase::new(ipv4_map, IpAddrMap::new());
+    ///
+    /// let mut output = Vec::new();
+    /// database.export_ipset(&mut output, &["BE"], "blocked").unwrap();
+    /// let output = String::from_utf8(output).unwrap();
+    ///
+    /// assert!(output.starts_with("create blocked_v4 hash:net family inet"));
+    /// assert!(output.contains("add blocked_v4 192.0.2.0/24\n"));
+    /// ```
+    pub fn export_ipset(
+        &self,
+        mut writer: impl Write,
+        codes: &[&str],
+        set_name: &str,
+    ) -> io::Result<()> {
+        fn write_set<A: Address>(
+            writer: &mut impl Write,
+            name: &str,
+            family: &str,
+            ranges: &[AddrRange<A>],
+        ) -> io::Result<()> {
+            // A single country can have more CIDR blocks than the default limit of 65536
+            writeln!(
+                writer,
+                "create {name} hash:net family {family} maxelem 1048576 -exist"
+            )?;
+            writeln!(writer, "flush {name}")?;
+
+            for (start, end) in ranges {
+                for cidr in range_to_cidrs(*start, *end) {
+                    writeln!(writer, "add {name} {cidr}")?;
+                }
+            }
+
+            Ok(())
+        }
+
+        let (ipv4_ranges, ipv6_ranges) = self.ranges_for_countries(codes);
+
+        write_set(&mut writer, &format!("{set_name}_v4"), "inet", &ipv4_ranges)?;
+        write_set(
+            &mut writer,
+            &format!("{set_name}_v6"),
+            "inet6",
+            &ipv6_ranges,
+        )?;
+
+        writer.flush()
+    }
+
+    /// Write every range in the database in the format of Tor's `geoip` and `geoip6` files, with
+    /// lines in the format of `<start>,<end>,<country_code>`.
+    ///
+    /// IPv4 ranges are written to `ipv4_writer` with addresses as integers, and IPv6 ranges are
+    /// written to `ipv6_writer` with addresses as text.
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// use std::net::{Ipv4Addr, Ipv6Addr};
+    /// use ip_geo::{country_list::get_countries, GeoDatabase, IpAddrEntry, IpAddrMap};
+    ///
+    /// let countries = get_countries();
+    /// let mut ipv4_map = IpAddrMap::new();
+    /// ipv4_map.insert(
+    ///     IpAddrEntry::new(
+    ///         Ipv4Addr::new(1, 1, 1, 1),
+    ///         Ipv4Addr::new(3, 3, 3, 3),
+    ///         countries.get("BE").unwrap().clone(),
+    ///     )
+    ///     .unwrap(),
+    /// );
+    /// let mut ipv6_map = IpAddrMap::new();
+    /// ipv6_map.insert(
+    ///     IpAddrEntry::new(
+    ///         "1::".parse::<Ipv6Addr>().unwrap(),
+    ///         "3::".parse::<Ipv6Addr>().unwrap(),
+    ///         countries.get("CA").unwrap().clone(),
+    ///     )
+    ///     .unwrap(),
+    /// );
+    /// let database = GeoDatabase::new(ipv4_map, ipv6_map);
+    ///
+    /// let (mut ipv4, mut ipv6) = (Vec::new(), Vec::new());
+    /// database.export_tor(&mut ipv4, &mut ipv6).unwrap();
+    ///
+    /// assert_eq!(String::from_utf8(ipv4).unwrap(), "16843009,50529027,BE\n");
+    /// assert_eq!(String::from_utf8(ipv6).unwrap(), "1::,3::,CA\n");
+    /// ```
+    pub fn export_tor(&self, ipv4_writer: impl Write, ipv6_writer: impl Write) -> io::Result<()> {
+        write_ranges(ipv4_writer, self.ipv4(), u32::from)?;
+        write_ranges(ipv6_writer, self.ipv6(), |address| address)
+    }
+
+    /// Write every range in the database as CSV, with lines in the format of
+    /// `<start>,<end>,<country_code>` and addresses as text.
+    ///
+    /// IPv4 ranges are written to `ipv4_writer` and IPv6 ranges are written to `ipv6_writer`.
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// use std::net::Ipv4Addr;
+    /// use ip_geo::{country_list::get_countries, GeoDatabase, IpAddrEntry, IpAddrMap};
+    ///
+    /// let belgium = get_countries().get("BE").unwrap().clone();
+    /// let mut ipv4_map = IpAddrMap::new();
+    /// ipv4_map.insert(
+    ///     IpAddrEntry::new(Ipv4Addr::new(1, 1, 1, 1), Ipv4Addr::new(3, 3, 3, 3), belgium).unwrap(),
+    /// );
+    /// let database = GeoDatabase::new(ipv4_map, IpAddrMap::new());
+    ///
+    /// let (mut ipv4, mut ipv6) = (Vec::new(), Vec::new());
+    /// database.export_csv(&mut ipv4, &mut ipv6).unwrap();
+    ///
+    /// assert_eq!(String::from_utf8(ipv4).unwrap(), "1.1.1.1,3.3.3.3,BE\n");
+    /// assert!(ipv6.is_empty());
+    /// ```
+    pub fn export_csv(&self, ipv4_writer: impl Write, ipv6_writer: impl Write) -> io::Result<()> {
+        write_ranges(ipv4_writer, self.ipv4(), |address| address)?;
+        write_ranges(ipv6_writer, self.ipv6(), |address| address)
+    }
+}
+
+/// Write every range in `map` to `writer` as `<start>,<end>,<country_code>`, with addresses
+/// formatted by `format`.
+fn write_ranges<A: Ord + Copy, D: Display>(
+    mut writer: impl Write,
+    map: &IpAddrMap<A, Country>,
+    format: impl Fn(A) -> D,
+) -> io::Result<()> {
+    for entry in map.iter() {
+        writeln!(
+            writer,
+            "{},{},{}",
+            format(*entry.start()),
+            format(*entry.end()),
+            entry.value().code
+        )?;
+    }
+
+    writer.flush()
+}
@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Encoding coordinates as [geohashes](https://en.wikipedia.org/wiki/Geohash), as used by Grafana
+//! Geomap panels.
+
+use alloc::string::String;
+
+use crate::country_list::Country;
+
+/// The alphabet of geohashes, which omits "a", "i", "l", and "o".
+const BASE32: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// Encode a longitude and latitude as a geohash of `precision` characters.
+///
+/// Each character narrows the area down by a factor of 32; five characters is roughly 5 km.
+///
+/// Example usage:
+///
+/// ```rust
+/// use ip_geo::geohash::encode;
+///
+/// assert_eq!(encode(-5.6, 42.6, 5), "ezs42");
+/// assert_eq!(encode(4.668055555, 50.641111111, 3), "u15");
+/// ```
+pub fn encode(longitude: f64, latitude: f64, precision: usize) -> String {
+    let mut longitude_range = (-180.0, 180.0);
+    let mut latitude_range = (-90.0, 90.0);
+
+    let mut hash = String::with_capacity(precision);
+    let mut bits = 0;
+
+    // Bits alternate between longitude and latitude, starting with longitude
+    for bit in 0..precision * 5 {
+        let (range, value) = match bit % 2 {
+            0 => (&mut longitude_range, longitude),
+            _ => (&mut latitude_range, latitude),
+        };
+
+        let middle = (range.0 + range.1) / 2.0;
+        bits <<= 1;
+
+        if value >= middle {
+            bits |= 1;
+            range.0 = middle;
+        } else {
+            range.1 = middle;
+        }
+
+        if bit % 5 == 4 {
+            hash.push(BASE32[bits] as char);
+            bits = 0;
+        }
+    }
+
+    hash
+}
+
+impl Country {
+    /// Return the geohash of the center of the country, with `precision` characters.
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// use ip_geo::country_list::get_countries;
+    ///
+    /// let countries = get_countries();
+    ///
+    /// assert_eq!(countries.get("BE").unwrap().geohash(3), "u15");
+    /// ```
+    pub fn geohash(&self, precision: usize) -> String {
+        let (longitude, latitude) = self.coordinates;
+
+        encode(longitude, latitude, precision)
+    }
+}
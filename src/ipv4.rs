@@ -16,15 +16,21 @@
 // You should have received a copy of the GNU Affero General Public License along with ip_geo. If
 // not, see <https://www.gnu.org/licenses/>.
 
+use crate::IpAddrEntry;
+#[cfg(feature = "std")]
 use crate::{
-    country_list::{get_countries, Country},
-    IpAddrEntry, IpAddrMap,
+    country_list::Country,
+    parse::{self, ParseMode, ParseReport},
+    Error, IpAddrMap,
 };
+use core::net::Ipv4Addr;
+#[cfg(feature = "std")]
 use serde::{
     de::{Unexpected, Visitor},
     Deserialize, Deserializer,
 };
-use std::{fs, net::Ipv4Addr, path::Path, str::FromStr};
+#[cfg(feature = "std")]
+use std::{fs, io::Read, path::Path, str::FromStr};
 
 /// Stores a range of IPv4 addresses and a value.
 ///
@@ -91,62 +97,132 @@ pub type Ipv4AddrEntry<T> = IpAddrEntry<Ipv4Addr, T>;
 /// assert_eq!(ipv4_map.get_from_index_as_ref(0).unwrap().value().code, value_a);
 /// assert_eq!(ipv4_map.get_from_index_as_ref(1).unwrap().value().code, value_b);
 /// ```
+#[cfg(feature = "std")]
 pub fn parse_ipv4_file(
     path: Box<Path>,
     len: usize,
     comment: Option<char>,
 ) -> IpAddrMap<Ipv4Addr, Country> {
-    #[derive(Deserialize, Debug)]
-    struct Schema {
-        #[serde(deserialize_with = "deserialize_ipv4")]
-        start: Ipv4Addr,
+    let (map, report) = parse_ipv4_file_with_mode(path.clone(), len, comment, ParseMode::Lenient)
+        .unwrap_or_else(|error| {
+            panic!(
+                "Could not open IPv4 database at {}: {error}",
+                path.to_string_lossy()
+            )
+        });
+
+    for malformed in report.malformed {
+        eprintln!("Skipped malformed IPv4 database entry on {malformed}");
+    }
 
-        #[serde(deserialize_with = "deserialize_ipv4")]
-        end: Ipv4Addr,
+    map
+}
 
-        country_code: Box<str>,
-    }
+/// For given IPv4 database file of a given length, parse it into an `IpAddrMap` holding IPv4
+/// addresses, handling malformed lines according to `mode`.
+///
+/// Addresses can be written as integers (as in Tor's databases) or as text.
+///
+/// In `ParseMode::Strict`, the first malformed line is returned as `Error::MalformedLine`. In
+/// `ParseMode::Lenient`, malformed lines are skipped and returned in a `ParseReport` alongside the
+/// map.
+///
+/// Example usage:
+///
+/// ```rust
+/// use std::{io::Write, net::Ipv4Addr};
+/// use ip_geo::{ipv4::parse_ipv4_file_with_mode, parse::ParseMode, Error};
+///
+/// let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+/// write!(
+///     temp_file,
+///     "{},{},BE\nnot an address,{},CA\n{},{},XX\n",
+///     u32::from(Ipv4Addr::new(1, 1, 1, 1)),
+///     u32::from(Ipv4Addr::new(3, 3, 3, 3)),
+///     u32::from(Ipv4Addr::new(6, 6, 6, 6)),
+///     u32::from(Ipv4Addr::new(7, 7, 7, 7)),
+///     u32::from(Ipv4Addr::new(8, 8, 8, 8)),
+/// )
+/// .unwrap();
+///
+/// let path = || temp_file.path().into();
+///
+/// let (map, report) = parse_ipv4_file_with_mode(path(), 3, None, ParseMode::Lenient).unwrap();
+/// assert_eq!(map.len(), 1);
+/// assert_eq!(report.malformed.len(), 2);
+/// assert_eq!(report.malformed[0].line, 2);
+/// assert_eq!(report.malformed[1].line, 3);
+///
+/// let error = parse_ipv4_file_with_mode(path(), 3, None, ParseMode::Strict).unwrap_err();
+/// assert!(matches!(error, Error::MalformedLine(malformed) if malformed.line == 2));
+/// ```
+#[cfg(feature = "std")]
+pub fn parse_ipv4_file_with_mode(
+    path: Box<Path>,
+    len: usize,
+    comment: Option<char>,
+    mode: ParseMode,
+) -> Result<(IpAddrMap<Ipv4Addr, Country>, ParseReport), Error> {
+    let file = fs::File::open(&path)?;
 
-    let file = fs::File::open(&path)
-        .unwrap_or_else(|_| panic!("Could not open IPv4 database at {}", path.to_string_lossy()));
-    let mut reader = csv::ReaderBuilder::new()
-        .has_headers(false)
-        .comment(comment.map(|c| c as u8))
-        .from_reader(file);
-
-    let mut map = IpAddrMap::new_with_capacity(len);
-    let countries = get_countries();
-
-    for entry in reader.deserialize() {
-        let data: Schema = entry.unwrap();
-
-        let code = data.country_code.as_ref();
-
-        // Ensure that it is a recognized country
-        match countries.get(code).cloned() {
-            Some(country) => {
-                // Only add ranges with associated countries
-                if country.code != "??".into() {
-                    map.insert(Ipv4AddrEntry::new(data.start, data.end, country).unwrap());
-                }
-            }
-            None => eprintln!("Unrecognized country or region '{}'!", data.country_code),
-        }
-    }
+    parse_ipv4_with_mode(file, len, comment, mode)
+}
 
-    map.cleanup();
+/// Like [`parse_ipv4_file_with_mode`], but parse an IPv4 database read from `reader` (ex. stdin)
+/// instead of a file.
+///
+/// Example usage:
+///
+/// ```rust
+/// use std::net::Ipv4Addr;
+/// use ip_geo::{ipv4::parse_ipv4_with_mode, parse::ParseMode};
+///
+/// let database = "16843009,50529027,BE\n# A comment\n4.4.4.4,not an address,CA\n";
+///
+/// let (mut map, report) =
+///     parse_ipv4_with_mode(database.as_bytes(), 1, Some('#'), ParseMode::Lenient).unwrap();
+/// assert_eq!(map.search(Ipv4Addr::new(2, 2, 2, 2)).unwrap().code.as_ref(), "BE");
+/// assert_eq!(report.malformed.len(), 1);
+/// ```
+#[cfg(feature = "std")]
+pub fn parse_ipv4_with_mode(
+    reader: impl Read,
+    len: usize,
+    comment: Option<char>,
+    mode: ParseMode,
+) -> Result<(IpAddrMap<Ipv4Addr, Country>, ParseReport), Error> {
+    parse::parse_database::<Ipv4Addr, Schema>(reader, len, comment, mode)
+}
 
-    map
+/// A single row of an IPv4 database.
+#[cfg(feature = "std")]
+#[derive(Deserialize, Debug)]
+struct Schema {
+    #[serde(deserialize_with = "deserialize_ipv4")]
+    start: Ipv4Addr,
+
+    #[serde(deserialize_with = "deserialize_ipv4")]
+    end: Ipv4Addr,
+
+    country_code: Box<str>,
+}
+
+#[cfg(feature = "std")]
+impl From<Schema> for (Ipv4Addr, Ipv4Addr, Box<str>) {
+    fn from(value: Schema) -> Self {
+        (value.start, value.end, value.country_code)
+    }
 }
 
-/// Serde deserializer to convert a `u32` into an `Ipv4Addr`.
+/// Serde deserializer to convert a `u32` (or an address written as text) into an `Ipv4Addr`.
+#[cfg(feature = "std")]
 fn deserialize_ipv4<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Ipv4Addr, D::Error> {
     pub struct Ipv4Deserializer;
 
     impl<'de> Visitor<'de> for Ipv4Deserializer {
         type Value = Ipv4Addr;
 
-        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
             write!(f, "an IPv4 address")
         }
 
@@ -161,9 +237,13 @@ fn deserialize_ipv4<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Ipv4Ad
         where
             E: serde::de::Error,
         {
-            Ipv4Addr::from_str(v).map_err(|_| E::invalid_value(Unexpected::Str(v), &self))
+            // Tor writes addresses as integers, but other databases write them as text
+            u32::from_str(v)
+                .map(Ipv4Addr::from_bits)
+                .or_else(|_| Ipv4Addr::from_str(v))
+                .map_err(|_| E::invalid_value(Unexpected::Str(v), &self))
         }
     }
 
-    deserializer.deserialize_u32(Ipv4Deserializer)
+    deserializer.deserialize_str(Ipv4Deserializer)
 }
@@ -16,12 +16,40 @@
 // You should have received a copy of the GNU Affero General Public License along with ip_geo. If
 // not, see <https://www.gnu.org/licenses/>.
 
-use std::{cmp::Ordering, ops::RangeInclusive};
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::{cmp::Ordering, ops::RangeInclusive};
+
+#[cfg(feature = "std")]
+pub mod asn;
+pub mod cidr;
+pub mod continent;
 pub mod country;
 pub mod country_list;
+pub mod country_names;
+pub mod database;
+pub mod diff;
+#[cfg(feature = "std")]
+pub mod export;
+pub mod geohash;
 pub mod ipv4;
 pub mod ipv6;
+#[cfg(feature = "std")]
+pub mod manifest;
+#[cfg(feature = "std")]
+pub mod mmdb;
+pub mod parse;
+pub mod privacy;
+#[cfg(feature = "std")]
+pub mod snapshot;
+pub mod tor;
+#[cfg(feature = "update")]
+pub mod update;
+
+pub use database::GeoDatabase;
 
 /// Stores a searchable list of `IpAddrEntries`.
 ///
@@ -65,7 +93,7 @@ impl<A: Ord + Copy, T: PartialEq> IpAddrMap<A, T> {
     /// Create a new, unsized instance of `Self`.
     pub const fn new() -> Self {
         Self {
-            inner: vec![],
+            inner: Vec::new(),
             dirty: false,
         }
     }
@@ -112,6 +140,30 @@ impl<A: Ord + Copy, T: PartialEq> IpAddrMap<A, T> {
         Ok(self.inner[index].value())
     }
 
+    /// Binary search for the index of the entry containing a given IP address, counting the number
+    /// of entries compared along the way: `(result, steps)`.
+    ///
+    /// Like `slice::binary_search_by()`, returns `Err` with the index where an entry for the
+    /// address would be inserted if none contains it. Requires that the map be clean.
+    pub(crate) fn search_index_counted(&self, address: A) -> (Result<usize, usize>, u32) {
+        let mut steps = 0;
+        let mut low = 0;
+        let mut high = self.inner.len();
+
+        while low < high {
+            let middle = low + (high - low) / 2;
+            steps += 1;
+
+            match self.inner[middle].partial_cmp(&address).unwrap() {
+                Ordering::Less => low = middle + 1,
+                Ordering::Greater => high = middle,
+                Ordering::Equal => return (Ok(middle), steps),
+            }
+        }
+
+        (Err(low), steps)
+    }
+
     /// If necessary, prepare internal `Vec` for searching by performing a dedup, sort, and shrink.
     ///
     /// This is called by `Self::search()`, it should not be necessary to perform manually unless
@@ -133,6 +185,17 @@ impl<A: Ord + Copy, T: PartialEq> IpAddrMap<A, T> {
         self.inner.get(index).ok_or(Error::NoValueFound)
     }
 
+    /// Return an iterator over the stored entries, in sorted order if the map is clean.
+    pub fn iter(&self) -> core::slice::Iter<'_, IpAddrEntry<A, T>> {
+        self.inner.iter()
+    }
+
+    /// Return an iterator over mutable references to the stored values, ex. to relabel them
+    /// without parsing the database again. The ranges stay the same, so the map stays sorted.
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.inner.iter_mut().map(IpAddrEntry::value_mut)
+    }
+
     /// Return the length of the internal `Vec`.
     pub fn len(&self) -> usize {
         self.inner.len()
@@ -153,7 +216,7 @@ impl<A: Ord + Copy, T: PartialEq> Default for IpAddrMap<A, T> {
 impl<A: Ord + Copy, T: PartialEq> IntoIterator for IpAddrMap<A, T> {
     type Item = IpAddrEntry<A, T>;
 
-    type IntoIter = std::vec::IntoIter<Self::Item>;
+    type IntoIter = alloc::vec::IntoIter<Self::Item>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.inner.into_iter()
@@ -250,7 +313,7 @@ impl<A: Ord + Copy, T> PartialEq<A> for IpAddrEntry<A, T> {
 }
 
 impl<A: Ord + Copy, T> PartialOrd<A> for IpAddrEntry<A, T> {
-    fn partial_cmp(&self, other: &A) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &A) -> Option<Ordering> {
         match other {
             v if v > &self.end => Some(Ordering::Less),
             v if v < &self.start => Some(Ordering::Greater),
@@ -275,4 +338,65 @@ pub enum Error {
     /// The error returned when attemping to construct an invalid range.
     #[error("tried to construct invalid range")]
     EmptyRangeError,
+
+    /// The error returned when a block of addresses in CIDR notation cannot be parsed.
+    #[error("invalid CIDR block '{0}'")]
+    InvalidCidr(alloc::boxed::Box<str>),
+
+    /// The error returned when a database could not be read.
+    #[cfg(feature = "std")]
+    #[error("failed to read database")]
+    Io(#[from] std::io::Error),
+
+    /// The error returned when parsing a database in `ParseMode::Strict` encounters a malformed
+    /// line.
+    #[error("malformed database entry on {0}")]
+    MalformedLine(parse::MalformedLine),
+
+    /// The error returned when a database manifest cannot be parsed.
+    #[cfg(feature = "std")]
+    #[error("invalid manifest: {0}")]
+    InvalidManifest(alloc::boxed::Box<str>),
+
+    /// The error returned when a database does not match its manifest.
+    #[cfg(feature = "std")]
+    #[error("database does not match manifest: expected {field} {expected}, found {actual}")]
+    ManifestMismatch {
+        field: &'static str,
+        expected: alloc::boxed::Box<str>,
+        actual: alloc::boxed::Box<str>,
+    },
+
+    /// The error returned when a snapshot cannot be read.
+    #[cfg(feature = "std")]
+    #[error("invalid snapshot: {0}")]
+    InvalidSnapshot(alloc::boxed::Box<str>),
+
+    /// The error returned when a MaxMind DB cannot be read.
+    #[cfg(feature = "std")]
+    #[error("invalid MaxMind DB: {0}")]
+    InvalidMmdb(alloc::boxed::Box<str>),
+
+    /// The error returned when a request to download a database fails.
+    #[cfg(feature = "update")]
+    #[error("failed to download database")]
+    Http(#[from] alloc::boxed::Box<ureq::Error>),
+
+    /// The error returned when a downloaded database does not match its published checksum.
+    #[cfg(feature = "update")]
+    #[error("checksum mismatch: expected {expected}, received {actual}")]
+    ChecksumMismatch {
+        expected: alloc::boxed::Box<str>,
+        actual: alloc::boxed::Box<str>,
+    },
+
+    /// The error returned when a published checksum cannot be parsed.
+    #[cfg(feature = "update")]
+    #[error("invalid checksum '{0}'")]
+    InvalidChecksum(alloc::boxed::Box<str>),
+
+    /// The error returned when a downloaded archive does not contain the expected database.
+    #[cfg(feature = "update")]
+    #[error("archive does not contain '{0}'")]
+    MissingDatabase(alloc::boxed::Box<str>),
 }
@@ -0,0 +1,198 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Sidecar manifests for checking the integrity of a database file before using it.
+//!
+//! A manifest is stored next to its database as `<database>.manifest`, holding one `key: value`
+//! pair per line:
+//!
+//! ```text
+//! sha256: 9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08
+//! entries: 198214
+//! source: tor 0.4.8.12
+//! built: 1722893539
+//! ```
+
+use std::{
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::Error;
+
+/// The expected contents of a database file.
+///
+/// Example usage:
+///
+/// ```rust
+/// use std::io::Write;
+/// use ip_geo::manifest::Manifest;
+///
+/// let mut database = tempfile::NamedTempFile::new().unwrap();
+/// writeln!(database, "# A comment\n16843009,16843263,AU\n16843264,16843519,CN").unwrap();
+///
+/// let manifest = Manifest::generate(database.path(), Some('#'), "example").unwrap();
+/// assert_eq!(manifest.entries, 2);
+///
+/// manifest.write(database.path()).unwrap();
+/// assert_eq!(Manifest::verify(database.path(), Some('#')).unwrap(), manifest);
+///
+/// writeln!(database, "16843520,16843775,AU").unwrap();
+/// assert!(Manifest::verify(database.path(), Some('#')).is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Manifest {
+    /// The SHA-256 checksum of the database file, as lowercase hexadecimal.
+    pub sha256: Box<str>,
+    /// The number of entries (non-empty, non-comment lines) in the database file.
+    pub entries: usize,
+    /// Where the database came from.
+    ///
+    /// Ex. "tor 0.4.8.12".
+    pub source: Box<str>,
+    /// When the manifest was generated, in seconds since the Unix epoch.
+    pub built: u64,
+}
+
+impl Manifest {
+    /// Generate a manifest describing the database file at `database_path`.
+    pub fn generate(
+        database_path: &Path,
+        comment: Option<char>,
+        source: impl AsRef<str>,
+    ) -> Result<Self, Error> {
+        let contents = fs::read(database_path)?;
+        let built = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+
+        Ok(Self {
+            sha256: sha256_hex(&contents),
+            entries: count_entries(&contents, comment),
+            source: source.as_ref().lines().next().unwrap_or_default().into(),
+            built,
+        })
+    }
+
+    /// Return the path of the manifest for the database file at `database_path`.
+    pub fn path_for(database_path: &Path) -> PathBuf {
+        let mut path = database_path.as_os_str().to_owned();
+        path.push(".manifest");
+
+        path.into()
+    }
+
+    /// Read the manifest for the database file at `database_path`.
+    pub fn read(database_path: &Path) -> Result<Self, Error> {
+        let contents = fs::read_to_string(Self::path_for(database_path))?;
+
+        let mut sha256 = None;
+        let mut entries = None;
+        let mut source = None;
+        let mut built = None;
+
+        for line in contents.lines().filter(|l| !l.trim().is_empty()) {
+            let invalid = || Error::InvalidManifest(line.into());
+            let (key, value) = line.split_once(':').ok_or_else(invalid)?;
+            let value = value.trim();
+
+            match key.trim() {
+                "sha256" => sha256 = Some(value.to_ascii_lowercase().into()),
+                "entries" => entries = Some(value.parse().map_err(|_| invalid())?),
+                "source" => source = Some(value.into()),
+                "built" => built = Some(value.parse().map_err(|_| invalid())?),
+                _ => return Err(invalid()),
+            }
+        }
+
+        let missing = |key: &str| Error::InvalidManifest(format!("missing '{key}'").into());
+
+        Ok(Self {
+            sha256: sha256.ok_or_else(|| missing("sha256"))?,
+            entries: entries.ok_or_else(|| missing("entries"))?,
+            source: source.ok_or_else(|| missing("source"))?,
+            built: built.ok_or_else(|| missing("built"))?,
+        })
+    }
+
+    /// Write the manifest for the database file at `database_path`.
+    pub fn write(&self, database_path: &Path) -> Result<(), Error> {
+        let contents = format!(
+            "sha256: {}\nentries: {}\nsource: {}\nbuilt: {}\n",
+            self.sha256, self.entries, self.source, self.built
+        );
+
+        fs::write(Self::path_for(database_path), contents)?;
+
+        Ok(())
+    }
+
+    /// Check the database file at `database_path` against its manifest, returning the manifest if
+    /// they match.
+    pub fn verify(database_path: &Path, comment: Option<char>) -> Result<Self, Error> {
+        let manifest = Self::read(database_path)?;
+        let contents = fs::read(database_path)?;
+
+        let sha256 = sha256_hex(&contents);
+        if sha256 != manifest.sha256 {
+            return Err(Error::ManifestMismatch {
+                field: "sha256",
+                expected: manifest.sha256,
+                actual: sha256,
+            });
+        }
+
+        let entries = count_entries(&contents, comment);
+        if entries != manifest.entries {
+            return Err(Error::ManifestMismatch {
+                field: "entries",
+                expected: manifest.entries.to_string().into(),
+                actual: entries.to_string().into(),
+            });
+        }
+
+        Ok(manifest)
+    }
+}
+
+/// Count the non-empty lines of a database that are not comments.
+fn count_entries(contents: &[u8], comment: Option<char>) -> usize {
+    let mut buffer = [0; 4];
+    let comment = comment.map(|c| c.encode_utf8(&mut buffer).as_bytes());
+
+    contents
+        .split(|&b| b == b'\n')
+        .map(<[u8]>::trim_ascii)
+        .filter(|line| !line.is_empty())
+        .filter(|line| !matches!(comment, Some(c) if line.starts_with(c)))
+        .count()
+}
+
+/// Hash `bytes` with SHA-256 and format it as lowercase hexadecimal.
+pub(crate) fn sha256_hex(bytes: &[u8]) -> Box<str> {
+    Sha256::digest(bytes)
+        .iter()
+        .fold(String::with_capacity(64), |mut output, byte| {
+            write!(output, "{byte:02x}").expect("string concatenation");
+            output
+        })
+        .into_boxed_str()
+}
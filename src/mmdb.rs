@@ -0,0 +1,468 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Reading [MaxMind DB](https://maxmind.github.io/MaxMind-DB/) files, such as the GeoLite2
+//! Country databases downloaded by `update::MaxMindUpdater`.
+//!
+//! Only the country of each network is read: its `country.iso_code`, or its
+//! `registered_country.iso_code` for networks without a physical location (ex. anycast networks).
+//!
+//! A MaxMind DB is laid out as:
+//!
+//! 1. A binary search tree, where each node holds a left (`0` bit) and right (`1` bit) record.
+//!    A record is either another node, the data of every address below it, or empty.
+//! 2. 16 zero bytes.
+//! 3. The data section, holding the values that records point to.
+//! 4. The bytes `\xAB\xCD\xEFMaxMind.com`, followed by the metadata as a map.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Read,
+    net::{Ipv4Addr, Ipv6Addr},
+    sync::Arc,
+};
+
+use crate::{
+    country::UNKNOWN_COUNTRY_CODE,
+    country_list::{get_countries, Country},
+    snapshot::DatabaseMetadata,
+    Error, GeoDatabase, IpAddrEntry, IpAddrMap,
+};
+
+/// The bytes that the metadata section begins with.
+const METADATA_MARKER: &[u8] = b"\xAB\xCD\xEFMaxMind.com";
+
+/// The number of zero bytes between the search tree and the data section.
+const DATA_SECTION_SEPARATOR: usize = 16;
+
+/// How deeply maps, arrays, and pointers may be nested before the data is treated as malformed.
+const MAX_DEPTH: u8 = 32;
+
+impl GeoDatabase {
+    /// Read a MaxMind DB file, such as `GeoLite2-Country.mmdb`.
+    ///
+    /// Networks whose country is not in `country_list::get_countries()` (ex. Kosovo, `XK`) are
+    /// left out, like ranges without a country. The metadata's `source` is the database's type
+    /// (ex. "GeoLite2-Country"), and `generated` is when MaxMind built it.
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// # // Builds a database with 0.0.0.0/1 in Belgium, 128.0.0.0/1 in France, and 8000::/1 in
+    /// # // Canada, where IPv4 addresses are stored under ::/96.
+    /// # fn string(value: &str) -> Vec<u8> {
+    /// #     [&[0x40 | value.len() as u8], value.as_bytes()].concat()
+    /// # }
+    /// # fn country(code: &str) -> Vec<u8> {
+    /// #     [vec![0xE1], string("country"), vec![0xE1], string("iso_code"), string(code)].concat()
+    /// # }
+    /// # let record = |value: u32| value.to_be_bytes()[1..].to_vec();
+    /// # let data = [country("BE"), country("FR"), country("CA")];
+    /// # let node_count = 97;
+    /// # let leaf = |index: usize| record(node_count + 16 + 22 * index as u32);
+    /// # let mut mmdb = Vec::new();
+    /// # for node in 0..96 {
+    /// #     mmdb.extend(record(node + 1));
+    /// #     mmdb.extend(if node == 0 { leaf(2) } else { record(node_count) });
+    /// # }
+    /// # mmdb.extend([leaf(0), leaf(1)].concat());
+    /// # mmdb.extend([0; 16]);
+    /// # mmdb.extend(data.concat());
+    /// # mmdb.extend(b"\xAB\xCD\xEFMaxMind.com\xE3");
+    /// # mmdb.extend([string("node_count"), vec![0xC1, node_count as u8]].concat());
+    /// # mmdb.extend([string("record_size"), vec![0xA1, 24]].concat());
+    /// # mmdb.extend([string("ip_version"), vec![0xA1, 6]].concat());
+    /// use ip_geo::GeoDatabase;
+    ///
+    /// let database = GeoDatabase::read_mmdb(mmdb.as_slice()).unwrap();
+    ///
+    /// assert_eq!(database.lookup("1.1.1.1".parse().unwrap()).unwrap().code, "BE".into());
+    /// assert_eq!(database.lookup("200.0.0.1".parse().unwrap()).unwrap().code, "FR".into());
+    /// assert_eq!(database.lookup("8000::1".parse().unwrap()).unwrap().code, "CA".into());
+    /// // IPv4 addresses are only found in the IPv4 map
+    /// assert!(database.lookup("::1.1.1.1".parse().unwrap()).is_err());
+    /// ```
+    pub fn read_mmdb(mut reader: impl Read) -> Result<Self, Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        let mmdb = Mmdb::new(&bytes)?;
+        let (ipv4, ipv6) = mmdb.countries()?;
+
+        let metadata = DatabaseMetadata {
+            generated: mmdb.build_epoch,
+            ..DatabaseMetadata::new(mmdb.database_type)
+        };
+
+        Ok(Self::new(ipv4, ipv6).with_metadata(metadata))
+    }
+}
+
+/// Create an `Error::InvalidMmdb`.
+fn invalid(reason: impl Into<Box<str>>) -> Error {
+    Error::InvalidMmdb(reason.into())
+}
+
+/// The IPv4 and IPv6 maps read from a MaxMind DB.
+type Maps = (IpAddrMap<Ipv4Addr, Country>, IpAddrMap<Ipv6Addr, Country>);
+
+/// A parsed MaxMind DB, borrowing its sections from the file's bytes.
+struct Mmdb<'a> {
+    tree: &'a [u8],
+    data: Decoder<'a>,
+    node_count: usize,
+    record_size: u16,
+    ip_version: u16,
+    database_type: &'a str,
+    build_epoch: u64,
+}
+
+impl<'a> Mmdb<'a> {
+    /// Split a MaxMind DB into its sections and read its metadata.
+    fn new(bytes: &'a [u8]) -> Result<Self, Error> {
+        let marker = bytes
+            .windows(METADATA_MARKER.len())
+            .rposition(|window| window == METADATA_MARKER)
+            .ok_or_else(|| invalid("no metadata section"))?;
+
+        let metadata = Decoder {
+            bytes: &bytes[marker + METADATA_MARKER.len()..],
+        };
+        let Value::Map(metadata) = metadata.decode(0, 0)?.0 else {
+            return Err(invalid("metadata is not a map"));
+        };
+
+        let uint = |key: &str| match metadata.get(key) {
+            Some(Value::Uint(value)) => Ok(Some(*value)),
+            Some(_) => Err(invalid(format!("metadata {key} is not an integer"))),
+            None => Ok(None),
+        };
+        let required = |key: &str| uint(key)?.ok_or_else(|| invalid(format!("no metadata {key}")));
+
+        let node_count = usize::try_from(required("node_count")?)
+            .map_err(|_| invalid("node_count is too large"))?;
+        let record_size = match required("record_size")? {
+            size @ (24 | 28 | 32) => size as u16,
+            size => return Err(invalid(format!("unsupported record_size {size}"))),
+        };
+        let ip_version = match required("ip_version")? {
+            version @ (4 | 6) => version as u16,
+            version => return Err(invalid(format!("unsupported ip_version {version}"))),
+        };
+        let build_epoch = uint("build_epoch")?.map_or(0, |epoch| epoch as u64);
+        let database_type = match metadata.get("database_type") {
+            Some(Value::String(name)) => name,
+            _ => "MaxMind DB",
+        };
+
+        let tree_size = node_count
+            .checked_mul(usize::from(record_size) / 4)
+            .filter(|size| size + DATA_SECTION_SEPARATOR <= marker)
+            .ok_or_else(|| invalid("search tree is larger than the file"))?;
+
+        Ok(Self {
+            tree: &bytes[..tree_size],
+            data: Decoder {
+                bytes: &bytes[tree_size + DATA_SECTION_SEPARATOR..marker],
+            },
+            node_count,
+            record_size,
+            ip_version,
+            database_type,
+            build_epoch,
+        })
+    }
+
+    /// Read the left or right record of a node.
+    fn record(&self, node: usize, right: bool) -> usize {
+        let node_size = usize::from(self.record_size) / 4;
+        let bytes = &self.tree[node * node_size..(node + 1) * node_size];
+        let uint = |bytes: &[u8]| bytes.iter().fold(0, |n, &b| (n << 8) | usize::from(b));
+
+        match (self.record_size, right) {
+            (24, false) => uint(&bytes[..3]),
+            (24, true) => uint(&bytes[3..]),
+            (28, false) => (usize::from(bytes[3] >> 4) << 24) | uint(&bytes[..3]),
+            (28, true) => (usize::from(bytes[3] & 0x0f) << 24) | uint(&bytes[4..]),
+            (_, false) => uint(&bytes[..4]),
+            (_, true) => uint(&bytes[4..]),
+        }
+    }
+
+    /// Read the countries of every network, as IPv4 and IPv6 maps.
+    ///
+    /// In IPv6 databases, IPv4 addresses are stored under `::/96`, and aliased from
+    /// `::ffff:0:0/96` and `2002::/16`. These are read into the IPv4 map only.
+    fn countries(&self) -> Result<Maps, Error> {
+        let mut countries = Countries {
+            data: &self.data,
+            countries: get_countries(),
+            cache: HashMap::new(),
+        };
+
+        let to_ipv4 = |bits: u128| Ipv4Addr::from_bits(bits as u32);
+
+        if self.ip_version == 4 {
+            let ipv4 = self.walk(0, 32, None, &mut countries, to_ipv4)?;
+            return Ok((ipv4, IpAddrMap::new()));
+        }
+
+        let mut ipv4_root = 0;
+        for _ in 0..96 {
+            if ipv4_root >= self.node_count {
+                break;
+            }
+            ipv4_root = self.record(ipv4_root, false);
+        }
+
+        let ipv4 = self.walk(ipv4_root, 32, None, &mut countries, to_ipv4)?;
+        let ipv6 = self.walk(0, 128, Some(ipv4_root), &mut countries, Ipv6Addr::from_bits)?;
+
+        Ok((ipv4, ipv6))
+    }
+
+    /// Read every network below `root` (a record) into a map, merging adjacent networks of the
+    /// same country, and skipping the node `skip` wherever it is found.
+    fn walk<A: Ord + Copy>(
+        &self,
+        root: usize,
+        bits: u32,
+        skip: Option<usize>,
+        countries: &mut Countries<'_>,
+        to_address: impl Fn(u128) -> A,
+    ) -> Result<IpAddrMap<A, Country>, Error> {
+        let mut map = IpAddrMap::new();
+        let mut last: Option<(u128, u128, Country)> = None;
+        let mut visited = 0;
+        let mut stack = vec![(root, 0_u128, 0_u32)];
+
+        while let Some((record, prefix, depth)) = stack.pop() {
+            if record < self.node_count {
+                if Some(record) == skip {
+                    continue;
+                }
+                visited += 1;
+                if depth == bits || visited > self.node_count {
+                    return Err(invalid("search tree contains a cycle"));
+                }
+
+                let right = prefix | (1_u128 << (bits - depth - 1));
+                // Pushed right first, so that networks are read in order
+                stack.push((self.record(record, true), right, depth + 1));
+                stack.push((self.record(record, false), prefix, depth + 1));
+                continue;
+            }
+
+            if record == self.node_count {
+                // An empty record, for networks without data
+                continue;
+            }
+
+            let Some(country) = countries.get(record - self.node_count)? else {
+                continue;
+            };
+            let end = prefix | (u128::MAX >> (128 - bits)).checked_shr(depth).unwrap_or(0);
+
+            match &mut last {
+                Some((_, last_end, last_country))
+                    if last_end.checked_add(1) == Some(prefix)
+                        && last_country.code == country.code =>
+                {
+                    *last_end = end;
+                }
+                _ => {
+                    if let Some((start, end, country)) = last.replace((prefix, end, country)) {
+                        map.insert(IpAddrEntry::new(
+                            to_address(start),
+                            to_address(end),
+                            country,
+                        )?);
+                    }
+                }
+            }
+        }
+
+        if let Some((start, end, country)) = last {
+            map.insert(IpAddrEntry::new(
+                to_address(start),
+                to_address(end),
+                country,
+            )?);
+        }
+        map.cleanup();
+
+        Ok(map)
+    }
+}
+
+/// Resolves data records to countries, caching them by their position in the data section.
+struct Countries<'a> {
+    data: &'a Decoder<'a>,
+    countries: BTreeMap<Arc<str>, Country>,
+    cache: HashMap<usize, Option<Country>>,
+}
+
+impl Countries<'_> {
+    /// Return the country of the data that a record points to, if it has a known one.
+    ///
+    /// `record` is the record's value minus the node count.
+    fn get(&mut self, record: usize) -> Result<Option<Country>, Error> {
+        if let Some(country) = self.cache.get(&record) {
+            return Ok(country.clone());
+        }
+
+        let offset = record
+            .checked_sub(DATA_SECTION_SEPARATOR)
+            .ok_or_else(|| invalid("record points into the data section separator"))?;
+        let Value::Map(data) = self.data.decode(offset, 0)?.0 else {
+            return Err(invalid("data record is not a map"));
+        };
+
+        let iso_code = |key: &str| match data.get(key) {
+            Some(Value::Map(country)) => match country.get("iso_code") {
+                Some(Value::String(code)) => Some(*code),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        let country = iso_code("country")
+            .or_else(|| iso_code("registered_country"))
+            .filter(|&code| code != UNKNOWN_COUNTRY_CODE)
+            .and_then(|code| self.countries.get(code).cloned());
+
+        self.cache.insert(record, country.clone());
+        Ok(country)
+    }
+}
+
+/// A value from a MaxMind DB, keeping only the types needed to find countries.
+enum Value<'a> {
+    String(&'a str),
+    Uint(u128),
+    Map(HashMap<&'a str, Value<'a>>),
+    Other,
+}
+
+/// Decodes values from the data (or metadata) section of a MaxMind DB.
+struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    /// Return `len` bytes starting at `offset`.
+    fn slice(&self, offset: usize, len: usize) -> Result<&'a [u8], Error> {
+        offset
+            .checked_add(len)
+            .and_then(|end| self.bytes.get(offset..end))
+            .ok_or_else(|| invalid("data runs past the end of its section"))
+    }
+
+    /// Read a big-endian unsigned integer of `len` bytes starting at `offset`.
+    fn uint(&self, offset: usize, len: usize) -> Result<usize, Error> {
+        Ok(self
+            .slice(offset, len)?
+            .iter()
+            .fold(0, |n, &b| (n << 8) | usize::from(b)))
+    }
+
+    /// Decode the value starting at `offset`, returning it and the offset just past it.
+    fn decode(&self, offset: usize, depth: u8) -> Result<(Value<'a>, usize), Error> {
+        if depth > MAX_DEPTH {
+            return Err(invalid("data is nested too deeply"));
+        }
+
+        let control = self.uint(offset, 1)? as u8;
+        let mut offset = offset + 1;
+
+        let kind = match control >> 5 {
+            1 => {
+                let high = usize::from(control & 0x07);
+                let (pointer, len) = match (control >> 3) & 0x03 {
+                    0 => ((high << 8) | self.uint(offset, 1)?, 1),
+                    1 => (((high << 16) | self.uint(offset, 2)?) + 2048, 2),
+                    2 => (((high << 24) | self.uint(offset, 3)?) + 526_336, 3),
+                    _ => (self.uint(offset, 4)?, 4),
+                };
+                let (value, _) = self.decode(pointer, depth + 1)?;
+                return Ok((value, offset + len));
+            }
+            0 => {
+                let kind = 7 + self.uint(offset, 1)?;
+                offset += 1;
+                kind
+            }
+            kind => usize::from(kind),
+        };
+
+        let (size, offset) = match usize::from(control & 0x1f) {
+            29 => (29 + self.uint(offset, 1)?, offset + 1),
+            30 => (285 + self.uint(offset, 2)?, offset + 2),
+            31 => (65_821 + self.uint(offset, 3)?, offset + 3),
+            size => (size, offset),
+        };
+
+        Ok(match kind {
+            // UTF-8 string
+            2 => {
+                let string = std::str::from_utf8(self.slice(offset, size)?)
+                    .map_err(|_| invalid("string is not UTF-8"))?;
+                (Value::String(string), offset + size)
+            }
+            // Unsigned 16, 32, 64, and 128 bit integers
+            5 | 6 | 9 | 10 => {
+                let bytes = self.slice(offset, size)?;
+                if bytes.len() > 16 {
+                    return Err(invalid("integer is too large"));
+                }
+                let uint = bytes.iter().fold(0, |n, &b| (n << 8) | u128::from(b));
+                (Value::Uint(uint), offset + size)
+            }
+            // Map
+            7 => {
+                let mut map = HashMap::with_capacity(size.min(64));
+                let mut offset = offset;
+                for _ in 0..size {
+                    let (key, next) = self.decode(offset, depth + 1)?;
+                    let Value::String(key) = key else {
+                        return Err(invalid("map key is not a string"));
+                    };
+                    let (value, next) = self.decode(next, depth + 1)?;
+                    map.insert(key, value);
+                    offset = next;
+                }
+                (Value::Map(map), offset)
+            }
+            // Array
+            11 => {
+                let mut offset = offset;
+                for _ in 0..size {
+                    offset = self.decode(offset, depth + 1)?.1;
+                }
+                (Value::Other, offset)
+            }
+            // Boolean, stored in its size
+            14 => (Value::Other, offset),
+            // Double, bytes, signed 32 bit integer, and float
+            3 | 4 | 8 | 15 => (
+                Value::Other,
+                self.slice(offset, size).map(|_| offset + size)?,
+            ),
+            kind => return Err(invalid(format!("unsupported data type {kind}"))),
+        })
+    }
+}
@@ -0,0 +1,175 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt::Display;
+
+#[cfg(feature = "std")]
+use crate::{
+    country::UNKNOWN_COUNTRY_CODE,
+    country_list::{get_countries, Country},
+    Error, IpAddrEntry, IpAddrMap,
+};
+#[cfg(feature = "std")]
+use alloc::{collections::BTreeMap, sync::Arc};
+#[cfg(feature = "std")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "std")]
+use std::io::Read;
+
+/// Controls how malformed lines are handled while parsing a database.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Abort parsing with `Error::MalformedLine` on the first malformed line.
+    Strict,
+    /// Skip malformed lines, recording them in a `ParseReport`.
+    #[default]
+    Lenient,
+}
+
+/// A line of a database that could not be parsed into an entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MalformedLine {
+    /// The line number of the malformed line, starting at 1.
+    pub line: u64,
+    /// Why the line could not be parsed.
+    pub reason: Box<str>,
+}
+
+impl MalformedLine {
+    /// Create a new `MalformedLine`.
+    pub fn new(line: u64, reason: impl Into<Box<str>>) -> Self {
+        Self {
+            line,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl Display for MalformedLine {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+/// The lines skipped while parsing a database in `ParseMode::Lenient`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParseReport {
+    /// Every malformed line, in the order they appeared in the database.
+    pub malformed: Vec<MalformedLine>,
+}
+
+impl ParseReport {
+    /// Returns true if no lines were skipped.
+    pub fn is_clean(&self) -> bool {
+        self.malformed.is_empty()
+    }
+}
+
+/// Parse a CSV database of `(start, end, country_code)` rows into a clean `IpAddrMap`.
+///
+/// `S` is the schema of a single row, which is responsible for parsing addresses.
+///
+/// Ranges associated with the unknown country (`??`) are left out of the map.
+#[cfg(feature = "std")]
+pub(crate) fn parse_database<A, S>(
+    reader: impl Read,
+    len: usize,
+    comment: Option<char>,
+    mode: ParseMode,
+) -> Result<(IpAddrMap<A, Country>, ParseReport), Error>
+where
+    A: Ord + Copy,
+    S: DeserializeOwned + Into<(A, A, Box<str>)>,
+{
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .comment(comment.map(|c| c as u8))
+        .from_reader(reader);
+
+    let mut map = IpAddrMap::new_with_capacity(len);
+    let mut report = ParseReport::default();
+    let countries = get_countries();
+
+    let mut record = csv::StringRecord::new();
+
+    loop {
+        let line = reader.position().line();
+
+        let malformed = match reader.read_record(&mut record) {
+            Ok(false) => break,
+            Ok(true) => match parse_record::<A, S>(&record, &countries) {
+                Ok(Some(entry)) => {
+                    map.insert(entry);
+                    None
+                }
+                Ok(None) => None,
+                Err(reason) => {
+                    let line = record.position().map_or(line, |p| p.line());
+                    Some(MalformedLine::new(line, reason))
+                }
+            },
+            Err(error) if error.is_io_error() => match error.into_kind() {
+                csv::ErrorKind::Io(error) => return Err(error.into()),
+                _ => unreachable!(),
+            },
+            Err(error) => Some(MalformedLine::new(line, error.to_string())),
+        };
+
+        match (malformed, mode) {
+            (None, _) => (),
+            (Some(malformed), ParseMode::Strict) => return Err(Error::MalformedLine(malformed)),
+            (Some(malformed), ParseMode::Lenient) => report.malformed.push(malformed),
+        }
+    }
+
+    map.cleanup();
+
+    Ok((map, report))
+}
+
+/// Parse a single row of a database, returning `Ok(None)` for ranges without an associated
+/// country.
+#[cfg(feature = "std")]
+fn parse_record<A, S>(
+    record: &csv::StringRecord,
+    countries: &BTreeMap<Arc<str>, Country>,
+) -> Result<Option<IpAddrEntry<A, Country>>, Box<str>>
+where
+    A: Ord + Copy,
+    S: DeserializeOwned + Into<(A, A, Box<str>)>,
+{
+    let (start, end, code) = record
+        .deserialize::<S>(None)
+        .map_err(|error| error.to_string())?
+        .into();
+
+    // Ensure that it is a recognized country
+    let country = countries
+        .get(code.as_ref())
+        .cloned()
+        .ok_or_else(|| format!("unrecognized country or region '{code}'"))?;
+
+    // Only add ranges with associated countries
+    if country.code.as_ref() == UNKNOWN_COUNTRY_CODE {
+        return Ok(None);
+    }
+
+    IpAddrEntry::new(start, end, country)
+        .map(Some)
+        .map_err(|error| error.to_string().into())
+}
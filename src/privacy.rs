@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Truncating IP addresses to a network prefix, so that full client addresses never need to be
+//! retained.
+
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::cidr::Address;
+
+/// The default prefix length that IPv4 addresses are truncated to.
+pub const DEFAULT_IPV4_PREFIX: u8 = 24;
+
+/// The default prefix length that IPv6 addresses are truncated to.
+pub const DEFAULT_IPV6_PREFIX: u8 = 48;
+
+/// Zero every bit of an address after the first `prefix_len` bits.
+///
+/// Prefix lengths longer than the address leave it unchanged.
+///
+/// Example usage:
+///
+/// ```rust
+/// use std::net::{Ipv4Addr, Ipv6Addr};
+/// use ip_geo::privacy::truncate;
+///
+/// assert_eq!(truncate(Ipv4Addr::new(192, 0, 2, 123), 24), Ipv4Addr::new(192, 0, 2, 0));
+/// assert_eq!(
+///     truncate("2001:db8:1234:5678::1".parse::<Ipv6Addr>().unwrap(), 48),
+///     "2001:db8:1234::".parse::<Ipv6Addr>().unwrap()
+/// );
+/// ```
+pub fn truncate<A: Address>(address: A, prefix_len: u8) -> A {
+    let host_bits = A::BITS.saturating_sub(prefix_len.into());
+    let mask = u128::MAX.checked_shl(host_bits).unwrap_or(0);
+
+    A::from_u128(address.to_u128() & mask)
+}
+
+/// The prefix lengths to truncate IPv4 and IPv6 addresses to.
+///
+/// Defaults to `/24` for IPv4 and `/48` for IPv6, the usual granularity of anonymized addresses.
+///
+/// Example usage:
+///
+/// ```rust
+/// use std::net::IpAddr;
+/// use ip_geo::privacy::Truncation;
+///
+/// let truncation = Truncation::default();
+/// let address: IpAddr = "198.51.100.42".parse().unwrap();
+///
+/// assert_eq!(truncation.apply(address), "198.51.100.0".parse::<IpAddr>().unwrap());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Truncation {
+    /// The number of leading bits of IPv4 addresses to keep.
+    pub ipv4_prefix: u8,
+    /// The number of leading bits of IPv6 addresses to keep.
+    pub ipv6_prefix: u8,
+}
+
+impl Truncation {
+    /// Create a new `Truncation` with given prefix lengths.
+    pub const fn new(ipv4_prefix: u8, ipv6_prefix: u8) -> Self {
+        Self {
+            ipv4_prefix,
+            ipv6_prefix,
+        }
+    }
+
+    /// Truncate an IPv4 or IPv6 address.
+    pub fn apply(&self, address: IpAddr) -> IpAddr {
+        match address {
+            IpAddr::V4(address) => self.apply_ipv4(address).into(),
+            IpAddr::V6(address) => self.apply_ipv6(address).into(),
+        }
+    }
+
+    /// Truncate an IPv4 address.
+    pub fn apply_ipv4(&self, address: Ipv4Addr) -> Ipv4Addr {
+        truncate(address, self.ipv4_prefix)
+    }
+
+    /// Truncate an IPv6 address.
+    pub fn apply_ipv6(&self, address: Ipv6Addr) -> Ipv6Addr {
+        truncate(address, self.ipv6_prefix)
+    }
+}
+
+impl Default for Truncation {
+    fn default() -> Self {
+        Self::new(DEFAULT_IPV4_PREFIX, DEFAULT_IPV6_PREFIX)
+    }
+}
@@ -0,0 +1,271 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! A compact binary format for storing a parsed `GeoDatabase`, with a metadata header describing
+//! where it came from.
+//!
+//! All integers are little-endian. A snapshot is laid out as:
+//!
+//! 1. The magic bytes `IPGEOSNP`.
+//! 2. The format version, as a `u16`.
+//! 3. The length of the metadata header in bytes, as a `u32`, followed by the header as UTF-8
+//!    `key: value` lines.
+//! 4. The number of IPv4 entries, as a `u32`, followed by each entry as a `u32` start, a `u32`
+//!    end, and a two byte country code.
+//! 5. The same for IPv6 entries, with `u128` starts and ends.
+
+use std::{
+    io::{self, Read, Write},
+    net::{Ipv4Addr, Ipv6Addr},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    cidr::Address,
+    country_list::{get_countries, Country},
+    Error, GeoDatabase, IpAddrEntry, IpAddrMap,
+};
+
+/// The bytes that every snapshot begins with.
+const MAGIC: &[u8; 8] = b"IPGEOSNP";
+
+/// The version of the snapshot format written by this version of `ip_geo`.
+pub const FORMAT_VERSION: u16 = 1;
+
+/// The name and version of this crate, recorded in snapshot metadata.
+pub const TOOL_VERSION: &str = concat!(env!("CARGO_PKG_NAME"), " ", env!("CARGO_PKG_VERSION"));
+
+/// Describes where a `GeoDatabase` came from, as stored in a snapshot's header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DatabaseMetadata {
+    /// The version of the snapshot format.
+    pub format_version: u16,
+    /// The file (or other source) that the database was parsed from.
+    ///
+    /// Ex. "/usr/share/tor/geoip".
+    pub source: Box<str>,
+    /// When the snapshot was generated, in seconds since the Unix epoch.
+    pub generated: u64,
+    /// The tool that generated the snapshot.
+    ///
+    /// Ex. "ip_geo 0.1.0".
+    pub tool_version: Box<str>,
+}
+
+impl DatabaseMetadata {
+    /// Create metadata for a snapshot of `source` generated now by this version of `ip_geo`.
+    pub fn new(source: impl AsRef<str>) -> Self {
+        let generated = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+
+        Self {
+            format_version: FORMAT_VERSION,
+            source: source.as_ref().lines().next().unwrap_or_default().into(),
+            generated,
+            tool_version: TOOL_VERSION.into(),
+        }
+    }
+
+    /// Format the metadata as the header of a snapshot.
+    fn to_header(&self) -> String {
+        format!(
+            "source: {}\ngenerated: {}\ntool_version: {}\n",
+            self.source, self.generated, self.tool_version
+        )
+    }
+
+    /// Parse the header of a snapshot of a given format version.
+    ///
+    /// Unknown keys are ignored, so that newer minor additions do not break older readers.
+    fn from_header(format_version: u16, header: &str) -> Result<Self, Error> {
+        let mut source = None;
+        let mut generated = None;
+        let mut tool_version = None;
+
+        for line in header.lines().filter(|l| !l.trim().is_empty()) {
+            let invalid = || Error::InvalidSnapshot(format!("invalid header line '{line}'").into());
+            let (key, value) = line.split_once(':').ok_or_else(invalid)?;
+            let value = value.trim();
+
+            match key.trim() {
+                "source" => source = Some(value.into()),
+                "generated" => generated = Some(value.parse().map_err(|_| invalid())?),
+                "tool_version" => tool_version = Some(value.into()),
+                _ => (),
+            }
+        }
+
+        let missing = |key: &str| Error::InvalidSnapshot(format!("missing '{key}'").into());
+
+        Ok(Self {
+            format_version,
+            source: source.ok_or_else(|| missing("source"))?,
+            generated: generated.ok_or_else(|| missing("generated"))?,
+            tool_version: tool_version.ok_or_else(|| missing("tool_version"))?,
+        })
+    }
+}
+
+impl GeoDatabase {
+    /// Write the database as a snapshot, with a header holding `metadata`.
+    ///
+    /// Example usage:
+    ///
+    /// ```rust
+    /// use std::net::Ipv4Addr;
+    /// use ip_geo::{
+    ///     country_list::get_countries, snapshot::DatabaseMetadata, GeoDatabase, IpAddrEntry,
+    ///     IpAddrMap,
+    /// };
+    ///
+    /// let belgium = get_countries().get("BE").unwrap().clone();
+    /// let mut ipv4_map = IpAddrMap::new();
+    /// ipv4_map.insert(
+    ///     IpAddrEntry::new(Ipv4Addr::new(1, 0, 0, 0), Ipv4Addr::new(1, 0, 0, 255), belgium).unwrap(),
+    /// );
+    /// let database = GeoDatabase::new(ipv4_map, IpAddrMap::new());
+    ///
+    /// let mut snapshot = Vec::new();
+    /// database.write_snapshot(&mut snapshot, &DatabaseMetadata::new("example")).unwrap();
+    ///
+    /// let database = GeoDatabase::read_snapshot(snapshot.as_slice()).unwrap();
+    ///
+    /// assert_eq!(database.metadata().unwrap().source.as_ref(), "example");
+    /// assert_eq!(database.lookup("1.0.0.1".parse().unwrap()).unwrap().code, "BE".into());
+    /// ```
+    pub fn write_snapshot(
+        &self,
+        mut writer: impl Write,
+        metadata: &DatabaseMetadata,
+    ) -> io::Result<()> {
+        let header = metadata.to_header();
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        writer.write_all(&length(header.len())?.to_le_bytes())?;
+        writer.write_all(header.as_bytes())?;
+
+        write_entries(&mut writer, self.ipv4(), |a| a.to_bits().to_le_bytes())?;
+        write_entries(&mut writer, self.ipv6(), |a| a.to_bits().to_le_bytes())?;
+
+        writer.flush()
+    }
+
+    /// Read a database from a snapshot written by `Self::write_snapshot()`.
+    ///
+    /// The snapshot's metadata is available from `Self::metadata()`.
+    pub fn read_snapshot(mut reader: impl Read) -> Result<Self, Error> {
+        let mut magic = [0; MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(Error::InvalidSnapshot("not an ip_geo snapshot".into()));
+        }
+
+        let format_version = u16::from_le_bytes(read_array(&mut reader)?);
+        if format_version != FORMAT_VERSION {
+            return Err(Error::InvalidSnapshot(
+                format!("unsupported format version {format_version}").into(),
+            ));
+        }
+
+        let header_len = u32::from_le_bytes(read_array(&mut reader)?);
+        let mut header = Vec::new();
+        (&mut reader)
+            .take(header_len.into())
+            .read_to_end(&mut header)?;
+        let header = String::from_utf8(header)
+            .map_err(|_| Error::InvalidSnapshot("header is not UTF-8".into()))?;
+        let metadata = DatabaseMetadata::from_header(format_version, &header)?;
+
+        let countries = get_countries();
+        let country = |code: [u8; 2]| {
+            let code = String::from_utf8_lossy(&code);
+            countries
+                .get(code.as_ref())
+                .cloned()
+                .ok_or_else(|| Error::InvalidSnapshot(format!("unknown country '{code}'").into()))
+        };
+
+        let ipv4 = read_entries(
+            &mut reader,
+            |bits| Ipv4Addr::from_bits(u32::from_le_bytes(bits)),
+            country,
+        )?;
+        let ipv6 = read_entries(
+            &mut reader,
+            |bits| Ipv6Addr::from_bits(u128::from_le_bytes(bits)),
+            country,
+        )?;
+
+        Ok(Self::new(ipv4, ipv6).with_metadata(metadata))
+    }
+}
+
+/// Convert a length to a `u32`, failing if it is too large to store in a snapshot.
+fn length(len: usize) -> io::Result<u32> {
+    u32::try_from(len).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too many entries"))
+}
+
+/// Write the number of entries in a map, then each entry.
+fn write_entries<A: Address, const N: usize>(
+    writer: &mut impl Write,
+    map: &IpAddrMap<A, Country>,
+    to_bytes: impl Fn(A) -> [u8; N],
+) -> io::Result<()> {
+    writer.write_all(&length(map.len())?.to_le_bytes())?;
+
+    for entry in map.iter() {
+        let code = entry.value().code.as_bytes();
+        let code = [code.first(), code.get(1)].map(|b| b.copied().unwrap_or(b'?'));
+
+        writer.write_all(&to_bytes(*entry.start()))?;
+        writer.write_all(&to_bytes(*entry.end()))?;
+        writer.write_all(&code)?;
+    }
+
+    Ok(())
+}
+
+/// Read the number of entries in a map, then each entry.
+fn read_entries<A: Address, const N: usize>(
+    reader: &mut impl Read,
+    from_bytes: impl Fn([u8; N]) -> A,
+    country: impl Fn([u8; 2]) -> Result<Country, Error>,
+) -> Result<IpAddrMap<A, Country>, Error> {
+    let len = u32::from_le_bytes(read_array(reader)?);
+    // Don't trust the length enough to allocate all of it up front
+    let mut map = IpAddrMap::new_with_capacity((len as usize).min(1 << 20));
+
+    for _ in 0..len {
+        let start = from_bytes(read_array(reader)?);
+        let end = from_bytes(read_array(reader)?);
+        let country = country(read_array(reader)?)?;
+
+        map.insert(IpAddrEntry::new(start, end, country)?);
+    }
+
+    Ok(map)
+}
+
+/// Read exactly `N` bytes.
+fn read_array<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut buffer = [0; N];
+    reader.read_exact(&mut buffer)?;
+
+    Ok(buffer)
+}
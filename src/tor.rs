@@ -0,0 +1,218 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Mapping relays from a [Tor network
+//! consensus](https://spec.torproject.org/dir-spec/consensus-formats.html) to countries.
+
+use alloc::{boxed::Box, collections::BTreeMap, sync::Arc, vec::Vec};
+use core::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use crate::GeoDatabase;
+
+pub use crate::country::UNKNOWN_COUNTRY_CODE;
+
+/// A relay listed in a Tor network consensus.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Relay {
+    /// The relay's nickname.
+    ///
+    /// Ex. "moria1".
+    pub nickname: Box<str>,
+    /// The IPv4 address of the relay's OR port.
+    pub ipv4: Ipv4Addr,
+    /// The IPv6 address of the relay's OR port, if it has one.
+    pub ipv6: Option<Ipv6Addr>,
+    /// The relay's consensus weight, from the `w Bandwidth=` line.
+    pub bandwidth: u64,
+    /// The relay's flags, from the `s` line.
+    ///
+    /// Ex. `["Fast", "Running", "Stable", "Valid"]`.
+    pub flags: Vec<Box<str>>,
+}
+
+impl Relay {
+    /// Returns true if the relay has a given flag.
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags.iter().any(|f| f.as_ref() == flag)
+    }
+}
+
+/// The number of relays and their total consensus weight in a country.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RelayStats {
+    /// The number of relays.
+    pub relays: u64,
+    /// The sum of the relays' consensus weights.
+    pub bandwidth: u64,
+}
+
+/// Parse the router status entries of a Tor network consensus into `Relay`s.
+///
+/// Lines that do not belong to a router status entry are ignored, as are entries with malformed
+/// `r` lines.
+///
+/// Example usage:
+///
+/// ```rust
+/// use std::net::Ipv4Addr;
+/// use ip_geo::tor::parse_consensus;
+///
+/// let consensus = "\
+/// network-status-version 3
+/// r moria1 lpXfw1/+uGEym58asExGOXAgzjE IpcJO3F1lA4TsXTUKmuZWyADqjs 2024-08-06 12:00:00 128.31.0.39 9101 9131
+/// a [2001:db8::1]:9101
+/// s Authority Fast Running Stable V2Dir Valid
+/// w Bandwidth=20
+/// r dannenberg CrBE/VgNUCXgOG7nHnVDWyLnOro Ccrp6u2fqgS0WWyq5SuvZ2ixa4s 2024-08-06 12:00:00 193.23.244.244 443 80
+/// w Bandwidth=30 Measured=31
+/// directory-footer
+/// ";
+///
+/// let relays = parse_consensus(consensus);
+///
+/// assert_eq!(relays.len(), 2);
+/// assert_eq!(relays[0].nickname.as_ref(), "moria1");
+/// assert_eq!(relays[0].ipv4, Ipv4Addr::new(128, 31, 0, 39));
+/// assert_eq!(relays[0].ipv6, Some("2001:db8::1".parse().unwrap()));
+/// assert!(relays[0].has_flag("Authority"));
+/// assert_eq!(relays[1].bandwidth, 30);
+/// ```
+pub fn parse_consensus(consensus: &str) -> Vec<Relay> {
+    let mut relays = Vec::new();
+    let mut current: Option<Relay> = None;
+
+    for line in consensus.lines() {
+        let mut words = line.split_ascii_whitespace();
+
+        match words.next() {
+            // Ex. "r moria1 <identity> <digest> 2024-08-06 12:00:00 128.31.0.39 9101 9131"
+            Some("r") => {
+                relays.extend(current.take());
+
+                let words: Vec<&str> = words.collect();
+                let (Some(nickname), Some(Ok(ipv4))) =
+                    (words.first(), words.get(5).map(|w| w.parse()))
+                else {
+                    continue;
+                };
+
+                current = Some(Relay {
+                    nickname: (*nickname).into(),
+                    ipv4,
+                    ipv6: None,
+                    bandwidth: 0,
+                    flags: Vec::new(),
+                });
+            }
+            // Ex. "a [2001:db8::1]:9101"
+            Some("a") => {
+                let Some(relay) = current.as_mut() else {
+                    continue;
+                };
+
+                if let Some(Ok(SocketAddr::V6(address))) = words.next().map(str::parse) {
+                    relay.ipv6.get_or_insert(*address.ip());
+                }
+            }
+            // Ex. "s Fast Running Stable Valid"
+            Some("s") => {
+                if let Some(relay) = current.as_mut() {
+                    relay.flags = words.map(Into::into).collect();
+                }
+            }
+            // Ex. "w Bandwidth=30 Measured=31"
+            Some("w") => {
+                let Some(relay) = current.as_mut() else {
+                    continue;
+                };
+
+                let bandwidth = words
+                    .filter_map(|w| w.strip_prefix("Bandwidth="))
+                    .find_map(|w| w.parse().ok());
+
+                if let Some(bandwidth) = bandwidth {
+                    relay.bandwidth = bandwidth;
+                }
+            }
+            // The end of the router status entries
+            Some("directory-footer") => relays.extend(current.take()),
+            _ => (),
+        }
+    }
+
+    relays.extend(current);
+
+    relays
+}
+
+/// Count the number of relays and their total consensus weight per country, keyed by country
+/// code.
+///
+/// Relays are located by their IPv4 address. Relays without an associated country are counted
+/// under `UNKNOWN_COUNTRY_CODE`.
+///
+/// Example usage:
+///
+/// ```rust
+/// use std::net::Ipv4Addr;
+/// use ip_geo::{
+///     country_list::get_countries,
+///     tor::{parse_consensus, relays_by_country},
+///     GeoDatabase, IpAddrEntry, IpAddrMap,
+/// };
+///
+/// let belgium = get_countries().get("BE").unwrap().clone();
+/// let mut ipv4_map = IpAddrMap::new();
+/// ipv4_map.insert(
+///     IpAddrEntry::new(Ipv4Addr::new(1, 0, 0, 0), Ipv4Addr::new(1, 255, 255, 255), belgium).unwrap(),
+/// );
+/// let database = GeoDatabase::new(ipv4_map, IpAddrMap::new());
+///
+/// let relays = parse_consensus("\
+/// r a AAAA BBBB 2024-08-06 12:00:00 1.1.1.1 9001 0
+/// w Bandwidth=10
+/// r b CCCC DDDD 2024-08-06 12:00:00 1.2.3.4 9001 0
+/// w Bandwidth=20
+/// r c EEEE FFFF 2024-08-06 12:00:00 9.9.9.9 9001 0
+/// w Bandwidth=40
+/// ");
+///
+/// let stats = relays_by_country(&relays, &database);
+///
+/// assert_eq!(stats.get("BE").unwrap().relays, 2);
+/// assert_eq!(stats.get("BE").unwrap().bandwidth, 30);
+/// assert_eq!(stats.get("??").unwrap().bandwidth, 40);
+/// ```
+pub fn relays_by_country(
+    relays: &[Relay],
+    database: &GeoDatabase,
+) -> BTreeMap<Arc<str>, RelayStats> {
+    let unknown: Arc<str> = UNKNOWN_COUNTRY_CODE.into();
+    let mut stats: BTreeMap<Arc<str>, RelayStats> = BTreeMap::new();
+
+    for relay in relays {
+        let code = database
+            .lookup_ipv4(relay.ipv4)
+            .map_or_else(|_| unknown.clone(), |country| country.code.clone());
+
+        let entry = stats.entry(code).or_default();
+        entry.relays += 1;
+        entry.bandwidth += relay.bandwidth;
+    }
+
+    stats
+}
@@ -0,0 +1,239 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright © 2024 RemasteredArch
+//
+// This file is part of ip_geo.
+//
+// ip_geo is free software: you can redistribute it and/or modify it under the terms of the GNU
+// Affero General Public License as published by the Free Software Foundation, either version 3 of
+// the License, or (at your option) any later version.
+//
+// ip_geo is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without
+// even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with ip_geo. If
+// not, see <https://www.gnu.org/licenses/>.
+
+//! Downloading MaxMind GeoLite2 databases, similar to
+//! [`geoipupdate`](https://github.com/maxmind/geoipupdate).
+
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use flate2::read::GzDecoder;
+
+use crate::{manifest::sha256_hex, Error};
+
+/// The default host to download databases from.
+pub const MAXMIND_BASE_URL: &str = "https://download.maxmind.com";
+
+/// The largest archive that will be downloaded, in bytes.
+const MAX_ARCHIVE_SIZE: u64 = 512 * 1024 * 1024;
+
+/// Downloads MaxMind databases into a directory, verifying their checksums and keeping old copies.
+///
+/// Each edition is stored as `<directory>/<edition_id>.mmdb`, alongside a
+/// `<edition_id>.mmdb.sha256` file holding the checksum of the archive it was extracted from. Old
+/// copies are rotated to `<edition_id>.mmdb.1`, `<edition_id>.mmdb.2`, and so on.
+///
+/// Example usage:
+///
+/// ```rust,no_run
+/// use ip_geo::update::{MaxMindUpdater, UpdateOutcome};
+///
+/// let updater = MaxMindUpdater::new("123456", "license_key", "/var/lib/ip_geo").keep(2);
+///
+/// match updater.update("GeoLite2-Country").unwrap() {
+///     UpdateOutcome::Updated { path, .. } => println!("Updated {}", path.display()),
+///     UpdateOutcome::UpToDate { path } => println!("{} is up to date", path.display()),
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct MaxMindUpdater {
+    account_id: Box<str>,
+    license_key: Box<str>,
+    directory: Box<Path>,
+    keep: usize,
+    base_url: Box<str>,
+}
+
+/// The result of successfully updating a database.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UpdateOutcome {
+    /// A new copy of the database was downloaded to `path`.
+    Updated {
+        path: PathBuf,
+        /// The SHA-256 checksum of the downloaded archive, as lowercase hexadecimal.
+        sha256: Box<str>,
+    },
+    /// The database at `path` already matches the latest available copy.
+    UpToDate { path: PathBuf },
+}
+
+impl MaxMindUpdater {
+    /// Create a new `MaxMindUpdater` that stores databases in `directory`.
+    ///
+    /// Keeps one old copy of each database by default.
+    pub fn new(
+        account_id: impl AsRef<str>,
+        license_key: impl AsRef<str>,
+        directory: impl AsRef<Path>,
+    ) -> Self {
+        Self {
+            account_id: account_id.as_ref().into(),
+            license_key: license_key.as_ref().into(),
+            directory: directory.as_ref().into(),
+            keep: 1,
+            base_url: MAXMIND_BASE_URL.into(),
+        }
+    }
+
+    /// Set the number of old copies of each database to keep.
+    pub fn keep(mut self, keep: usize) -> Self {
+        self.keep = keep;
+        self
+    }
+
+    /// Set the host to download databases from, instead of `MAXMIND_BASE_URL`.
+    pub fn base_url(mut self, base_url: impl AsRef<str>) -> Self {
+        self.base_url = base_url.as_ref().trim_end_matches('/').into();
+        self
+    }
+
+    /// Return the path that a given edition is stored at.
+    pub fn database_path(&self, edition_id: &str) -> PathBuf {
+        self.directory.join(format!("{edition_id}.mmdb"))
+    }
+
+    /// Download the latest copy of a database edition (ex. `GeoLite2-Country`), if it differs
+    /// from the local copy.
+    pub fn update(&self, edition_id: &str) -> Result<UpdateOutcome, Error> {
+        let path = self.database_path(edition_id);
+        let checksum_path = with_suffix(&path, ".sha256");
+
+        let expected = self.fetch_checksum(edition_id)?;
+
+        let current = fs::read_to_string(&checksum_path).ok();
+        if path.exists() && current.is_some_and(|c| c.trim() == expected.as_ref()) {
+            return Ok(UpdateOutcome::UpToDate { path });
+        }
+
+        let archive = self.fetch(edition_id, "tar.gz")?;
+        let actual = sha256_hex(&archive);
+        if actual != expected {
+            return Err(Error::ChecksumMismatch { expected, actual });
+        }
+
+        fs::create_dir_all(&self.directory)?;
+
+        // Extract into a temporary file first, so that a failure never leaves a partial database
+        let temporary_path = with_suffix(&path, ".tmp");
+        if let Err(error) = extract_database(&archive, edition_id, &temporary_path) {
+            let _ = fs::remove_file(&temporary_path);
+            return Err(error);
+        }
+
+        rotate(&path, self.keep)?;
+        fs::rename(&temporary_path, &path)?;
+        fs::write(&checksum_path, format!("{actual}\n"))?;
+
+        Ok(UpdateOutcome::Updated {
+            path,
+            sha256: actual,
+        })
+    }
+
+    /// Fetch the published SHA-256 checksum of the latest archive of an edition.
+    fn fetch_checksum(&self, edition_id: &str) -> Result<Box<str>, Error> {
+        let body = self.fetch(edition_id, "tar.gz.sha256")?;
+        let body = String::from_utf8_lossy(&body);
+
+        // Ex. "<checksum>  GeoLite2-Country_20240806.tar.gz"
+        match body.split_whitespace().next() {
+            Some(checksum) if checksum.len() == 64 => Ok(checksum.to_ascii_lowercase().into()),
+            _ => Err(Error::InvalidChecksum(body.trim().into())),
+        }
+    }
+
+    /// Download a file for an edition with a given suffix.
+    fn fetch(&self, edition_id: &str, suffix: &str) -> Result<Vec<u8>, Error> {
+        let url = format!(
+            "{}/geoip/databases/{edition_id}/download?suffix={suffix}",
+            self.base_url
+        );
+        let credentials = STANDARD.encode(format!("{}:{}", self.account_id, self.license_key));
+
+        let response = ureq::get(&url)
+            .set("Authorization", &format!("Basic {credentials}"))
+            .call()
+            .map_err(Box::new)?;
+
+        let mut body = Vec::new();
+        response
+            .into_reader()
+            .take(MAX_ARCHIVE_SIZE)
+            .read_to_end(&mut body)?;
+
+        Ok(body)
+    }
+}
+
+/// Extract the `.mmdb` file for an edition from a `.tar.gz` archive to `destination`.
+fn extract_database(archive: &[u8], edition_id: &str, destination: &Path) -> Result<(), Error> {
+    let file_name = format!("{edition_id}.mmdb");
+    let mut archive = tar::Archive::new(GzDecoder::new(archive));
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+
+        // Ex. "GeoLite2-Country_20240806/GeoLite2-Country.mmdb"
+        if entry.path()?.file_name() == Some(file_name.as_ref()) {
+            let mut file = fs::File::create(destination)?;
+            io::copy(&mut entry, &mut file)?;
+            file.sync_all()?;
+
+            return Ok(());
+        }
+    }
+
+    Err(Error::MissingDatabase(file_name.into()))
+}
+
+/// Shift old copies of `path` to make room for a new one, deleting any beyond `keep`.
+///
+/// `path` becomes `path.1`, `path.1` becomes `path.2`, and so on.
+fn rotate(path: &Path, keep: usize) -> io::Result<()> {
+    let numbered = |n: usize| with_suffix(path, &format!(".{n}"));
+
+    if keep == 0 {
+        return match fs::remove_file(path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+            _ => Ok(()),
+        };
+    }
+
+    for n in (1..keep).rev() {
+        if numbered(n).exists() {
+            fs::rename(numbered(n), numbered(n + 1))?;
+        }
+    }
+
+    if path.exists() {
+        fs::rename(path, numbered(1))?;
+    }
+
+    Ok(())
+}
+
+/// Append a suffix to a path, ex. `GeoLite2-Country.mmdb` -> `GeoLite2-Country.mmdb.sha256`.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+
+    path.into()
+}